@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Input is `<pattern>\n<path>`; without a newline the pattern is its own path.
+fuzz_target!(|input: &str| {
+    let (pattern, path) = input.split_once('\n').unwrap_or((input, input));
+    if let Err(detail) = cgrep::selftest::check_glob(pattern, path) {
+        panic!("{pattern:?} / {path:?}: {detail}");
+    }
+});
@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    if let Err(detail) = cgrep::selftest::check_query_phrase(input) {
+        panic!("{input:?}: {detail}");
+    }
+});
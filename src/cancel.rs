@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Cooperative cancellation for `search` and `symbols`.
+//!
+//! [`arm`] sets an optional deadline (`--timeout-ms`) and routes Ctrl-C to a
+//! flag instead of killing the process. Long loops poll [`should_stop`] and
+//! stop early, so the command still prints a complete (partial) result and
+//! then exits with [`TIMED_OUT_EXIT_CODE`] or [`INTERRUPTED_EXIT_CODE`]. A
+//! second Ctrl-C exits immediately.
+
+use once_cell::sync::OnceCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Exit status after an interrupted command (128 + SIGINT).
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Exit status after `--timeout-ms` cut work short, as with `timeout(1)`.
+pub const TIMED_OUT_EXIT_CODE: i32 = 124;
+
+/// Ends the stderr warning printed when work stopped early.
+pub const PARTIAL_RESULTS_NOTICE: &str = "results are partial";
+
+static DEADLINE: OnceCell<Instant> = OnceCell::new();
+static HANDLER: OnceCell<()> = OnceCell::new();
+static TIMED_OUT: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Start the deadline (when given) and take over Ctrl-C.
+pub fn arm(timeout: Option<Duration>) {
+    if let Some(timeout) = timeout {
+        let _ = DEADLINE.set(Instant::now() + timeout);
+    }
+    HANDLER.get_or_init(|| {
+        let _ = std::thread::Builder::new()
+            .name("cgrep-ctrl-c".to_string())
+            .spawn(watch_ctrl_c);
+    });
+}
+
+fn watch_ctrl_c() {
+    let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return;
+    };
+    runtime.block_on(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if INTERRUPTED.swap(true, Ordering::SeqCst) {
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
+        }
+    });
+}
+
+/// Whether work should stop now; records a passed deadline as a timeout.
+pub fn should_stop() -> bool {
+    if stopped() {
+        return true;
+    }
+    if DEADLINE
+        .get()
+        .is_some_and(|deadline| Instant::now() >= *deadline)
+    {
+        TIMED_OUT.store(true, Ordering::SeqCst);
+        return true;
+    }
+    false
+}
+
+/// Whether any check stopped work early; partial results must not be cached.
+pub fn stopped() -> bool {
+    timed_out() || interrupted()
+}
+
+/// The deadline passed while work was still running.
+pub fn timed_out() -> bool {
+    TIMED_OUT.load(Ordering::SeqCst)
+}
+
+/// Ctrl-C was pressed.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -2,7 +2,7 @@
 
 //! CLI argument parsing using clap
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use clap_complete::Shell;
 
 /// cgrep - Local semantic code search tool
@@ -20,7 +20,7 @@ use clap_complete::Shell;
     after_help = "Search quickstart:\n  cgrep s \"token refresh\" src/\n  cgrep search -r --include '**/*.rs' needle src/\n\nLiteral query tips:\n  cgrep search -- --literal\n  cgrep s read\n\nScenario presets:\n  cgrep s \"auth flow\" -P user\n  cgrep s \"auth flow\" -P ai -B tight --format json2 --compact"
 )]
 pub struct Cli {
-    /// Output format (text or json)
+    /// Output format (text, json, json2, github/gitlab CI annotations, sarif, or quickfix)
     #[arg(long, global = true)]
     pub format: Option<OutputFormat>,
 
@@ -28,6 +28,18 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub compact: bool,
 
+    /// Path separator style in output (native or posix)
+    #[arg(long = "path-style", global = true, value_enum)]
+    pub path_style: Option<CliPathStyle>,
+
+    /// Emit json2 payloads in this schema version (pins the shape across upgrades)
+    #[arg(long = "schema-version", global = true, value_name = "N")]
+    pub schema_version: Option<u32>,
+
+    /// Byte-identical JSON across runs and platforms: sorted keys, fixed float precision, zeroed timings
+    #[arg(long, global = true)]
+    pub stable: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -39,6 +51,46 @@ pub enum OutputFormat {
     Json,
     /// Structured JSON for AI agents (`meta` + `results`)
     Json2,
+    /// GitHub Actions workflow-command annotations (search and check)
+    Github,
+    /// GitLab Code Quality report JSON (search and check)
+    Gitlab,
+    /// SARIF 2.1.0 log (check only)
+    Sarif,
+    /// Vim/Emacs quickfix lines (`file:line:col: text`)
+    Quickfix,
+}
+
+/// Path separator style for displayed paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CliPathStyle {
+    /// Platform separator (`\` on Windows)
+    Native,
+    /// Always `/`
+    Posix,
+}
+
+/// What `search -C` shows around each match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextArg {
+    /// N raw lines before and after
+    Lines(usize),
+    /// Header lines of the enclosing function/class
+    SymbolHeader,
+}
+
+impl std::str::FromStr for ContextArg {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "symbol-header" {
+            return Ok(Self::SymbolHeader);
+        }
+        value
+            .parse()
+            .map(Self::Lines)
+            .map_err(|_| format!("expected a line count or `symbol-header`, got `{value}`"))
+    }
 }
 
 /// Search mode for queries
@@ -54,12 +106,83 @@ pub enum CliSearchMode {
 }
 
 /// Output budget preset for token-efficient responses
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CliBudgetPreset {
     Tight,
     Balanced,
     Full,
     Off,
+    /// A `[budgets.<name>]` table from config
+    Custom(String),
+}
+
+impl CliBudgetPreset {
+    /// Name as accepted by `--budget`.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Tight => "tight",
+            Self::Balanced => "balanced",
+            Self::Full => "full",
+            Self::Off => "off",
+            Self::Custom(name) => name,
+        }
+    }
+}
+
+impl std::str::FromStr for CliBudgetPreset {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        Ok(match value.to_ascii_lowercase().as_str() {
+            "tight" => Self::Tight,
+            "balanced" => Self::Balanced,
+            "full" => Self::Full,
+            "off" => Self::Off,
+            "" => return Err("budget preset name cannot be empty".to_string()),
+            _ => Self::Custom(value.to_string()),
+        })
+    }
+}
+
+/// Ordering of `symbols` results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum CliSymbolSort {
+    /// Best name match first: exact, then prefix, then substring
+    Score,
+    /// Alphabetically by symbol name
+    Name,
+    /// By file path, then line
+    #[default]
+    Path,
+    /// By symbol kind, then path
+    Kind,
+}
+
+/// Metric ranked by `metrics --by`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum CliMetric {
+    /// Branches, loops, cases, and boolean operators, plus one
+    #[default]
+    Complexity,
+    /// Lines spanned by the definition
+    Lines,
+    /// Parameters, without receivers
+    Params,
+    /// Deepest nesting of branches and loops
+    Nesting,
+}
+
+impl CliSymbolSort {
+    /// Name as accepted by `--sort`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Score => "score",
+            Self::Name => "name",
+            Self::Path => "path",
+            Self::Kind => "kind",
+        }
+    }
 }
 
 /// Usage lookup strategy for callers/references
@@ -107,8 +230,8 @@ pub enum AgentCommands {
         #[arg(short = 'M', long, value_enum)]
         mode: Option<CliSearchMode>,
 
-        /// Output budget preset (default: balanced)
-        #[arg(short = 'B', long, value_enum)]
+        /// Output budget preset: tight, balanced, full, off, or a `[budgets.<name>]` from config (default: balanced)
+        #[arg(short = 'B', long)]
         budget: Option<CliBudgetPreset>,
     },
 
@@ -146,8 +269,8 @@ pub enum AgentCommands {
         #[arg(short = 'M', long, value_enum)]
         mode: Option<CliSearchMode>,
 
-        /// Output budget preset for locate stage (default: balanced)
-        #[arg(short = 'B', long, value_enum)]
+        /// Output budget preset for locate stage: tight, balanced, full, off, or a `[budgets.<name>]` from config (default: balanced)
+        #[arg(short = 'B', long)]
         budget: Option<CliBudgetPreset>,
 
         /// Planning profile label (default: agent)
@@ -178,6 +301,30 @@ pub enum AgentCommands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum IndexCommands {
+    /// Save the current index under .cgrep/snapshots/<tag> (segments are hardlinked)
+    Snapshot {
+        /// Path containing the index (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Snapshot name (default: snapshot-<unix seconds>)
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// Replace the current index with a saved snapshot
+    Rollback {
+        /// Snapshot name given to `cgrep index snapshot --tag`
+        tag: String,
+
+        /// Path containing the index (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum DaemonCommands {
     /// Start background indexing daemon
@@ -283,187 +430,7 @@ pub enum Commands {
         visible_aliases = ["s", "find", "q"],
         after_help = "Examples:\n  cgrep s \"token refresh\" src/\n  cgrep search -r --no-ignore \"auth flow\" src/\n  cgrep search \"retry\" -p src/ -C 2"
     )]
-    Search {
-        /// Search query (natural language or keywords)
-        #[arg(required_unless_present = "help_advanced")]
-        query: Option<String>,
-
-        /// Optional path (grep-style positional form)
-        #[arg(value_name = "PATH")]
-        path_positional: Option<String>,
-
-        /// Path to search in (defaults to current directory)
-        #[arg(short, long, help_heading = "Core")]
-        path: Option<String>,
-
-        /// Search subdirectories recursively (grep -r, default)
-        #[arg(short = 'r', long, help_heading = "Scope")]
-        recursive: bool,
-
-        /// Search only the top-level directory in the scope
-        #[arg(long, conflicts_with = "recursive", help_heading = "Scope")]
-        no_recursive: bool,
-
-        /// Do not respect .gitignore/.ignore rules (forces scan mode)
-        #[arg(long, help_heading = "Scope")]
-        no_ignore: bool,
-
-        /// Maximum number of results
-        #[arg(
-            short = 'm',
-            long = "limit",
-            visible_alias = "max-results",
-            help_heading = "Core"
-        )]
-        limit: Option<usize>,
-
-        /// Show N lines before and after each match (like grep -C)
-        #[arg(short = 'C', long, help_heading = "Core")]
-        context: Option<usize>,
-
-        /// Filter by file type/language (e.g., rust, ts, python)
-        #[arg(short = 't', long = "type", help_heading = "Core")]
-        file_type: Option<String>,
-
-        /// Filter files matching glob pattern (e.g., "*.rs", "src/**/*.ts")
-        #[arg(short = 'g', long, visible_alias = "include", help_heading = "Core")]
-        glob: Option<String>,
-
-        /// Exclude files matching pattern
-        #[arg(
-            short = 'x',
-            long,
-            visible_alias = "exclude-dir",
-            help_heading = "Core"
-        )]
-        exclude: Option<String>,
-
-        /// Limit search to files changed since revision (default: HEAD)
-        #[arg(
-            short = 'u',
-            long,
-            num_args = 0..=1,
-            default_missing_value = "HEAD",
-            help_heading = "Core"
-        )]
-        changed: Option<String>,
-
-        /// Output budget preset (tight, balanced, full, off)
-        #[arg(short = 'B', long, value_enum, help_heading = "Core")]
-        budget: Option<CliBudgetPreset>,
-
-        /// Use a preset profile (human/user, agent/ai, fast/quick)
-        #[arg(short = 'P', long, help_heading = "Core")]
-        profile: Option<String>,
-
-        /// Suppress statistics output
-        #[arg(short = 'q', long, help_heading = "Core")]
-        quiet: bool,
-
-        /// Treat query as a regular expression (scan mode)
-        #[arg(long, help_heading = "Mode")]
-        regex: bool,
-
-        /// Grep compatibility flag (ignore case, default behavior)
-        #[arg(
-            short = 'i',
-            long = "ignore-case",
-            conflicts_with = "case_sensitive",
-            help_heading = "Mode"
-        )]
-        ignore_case: bool,
-
-        /// Case-sensitive search (scan mode)
-        #[arg(long, conflicts_with = "ignore_case", help_heading = "Mode")]
-        case_sensitive: bool,
-
-        /// Search mode: keyword, semantic, or hybrid (semantic/hybrid are experimental)
-        #[arg(short = 'M', long, value_enum, help_heading = "Mode")]
-        mode: Option<CliSearchMode>,
-
-        /// Emit deterministic score component breakdown for top matches
-        #[arg(long, help_heading = "Mode")]
-        explain: bool,
-
-        /// Deprecated: use `--mode keyword`
-        #[arg(
-            long,
-            hide = true,
-            conflicts_with = "semantic",
-            conflicts_with = "hybrid"
-        )]
-        keyword: bool,
-
-        /// Deprecated: use `--mode semantic`
-        #[arg(
-            long,
-            hide = true,
-            conflicts_with = "keyword",
-            conflicts_with = "hybrid"
-        )]
-        semantic: bool,
-
-        /// Deprecated: use `--mode hybrid`
-        #[arg(
-            long,
-            hide = true,
-            conflicts_with = "keyword",
-            conflicts_with = "semantic"
-        )]
-        hybrid: bool,
-
-        /// Print advanced options for search and exit
-        #[arg(long, help_heading = "Help")]
-        help_advanced: bool,
-
-        /// Context pack size for agent mode (merges overlapping context)
-        #[arg(long, hide = true)]
-        context_pack: Option<usize>,
-
-        /// Enable agent session caching
-        #[arg(long, hide = true)]
-        agent_cache: bool,
-
-        /// Cache TTL in milliseconds (default: 600000 = 10 minutes)
-        #[arg(long, hide = true)]
-        cache_ttl: Option<u64>,
-
-        /// Maximum characters per snippet in output
-        #[arg(long, hide = true)]
-        max_chars_per_snippet: Option<usize>,
-
-        /// Maximum total characters across returned results
-        #[arg(long, hide = true)]
-        max_total_chars: Option<usize>,
-
-        /// Maximum context characters per result (before+after)
-        #[arg(long, hide = true)]
-        max_context_chars: Option<usize>,
-
-        /// Remove duplicated context lines across results
-        #[arg(long, hide = true)]
-        dedupe_context: bool,
-
-        /// Use short path aliases (p1, p2, ...) in json2 output with lookup table in meta
-        #[arg(long, hide = true)]
-        path_alias: bool,
-
-        /// Suppress repeated boilerplate lines (imports/headers) in snippets and context
-        #[arg(long, hide = true)]
-        suppress_boilerplate: bool,
-
-        /// Enable fuzzy matching (allows 1-2 character differences)
-        #[arg(short = 'f', long, hide = true)]
-        fuzzy: bool,
-
-        /// Do not use the index; scan files directly
-        #[arg(long, hide = true)]
-        no_index: bool,
-
-        /// Internal flag for metadata when MCP bootstrapped an index before search
-        #[arg(long, hide = true)]
-        bootstrap_index: bool,
-    },
+    Search(Box<SearchArgs>),
 
     /// Read a file with smart full/outline output
     #[command(visible_aliases = ["rd", "cat", "view"])]
@@ -478,6 +445,10 @@ pub enum Commands {
         /// Force full content output (disable smart outline mode)
         #[arg(long)]
         full: bool,
+
+        /// Inline one symbol's body into the outline (name or `Outer.inner`)
+        #[arg(long, value_name = "SYMBOL", conflicts_with_all = ["section", "full"])]
+        expand: Option<String>,
     },
 
     /// Print a structural codebase map
@@ -492,6 +463,10 @@ pub enum Commands {
         depth: usize,
     },
 
+    /// ripgrep-compatible line search (`cgrep grep [OPTIONS] PATTERN [PATH]...`)
+    #[command(visible_aliases = ["rg"])]
+    Grep(Box<GrepArgs>),
+
     /// Agent-optimized workflow: locate/expand/install/uninstall
     #[command(visible_aliases = ["a"])]
     Agent {
@@ -506,12 +481,37 @@ pub enum Commands {
         command: DaemonCommands,
     },
 
+    /// Apply pending file changes to the index once, as the daemon would, and exit
+    Refresh {
+        /// Path to refresh (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
     /// Print index readiness and background build status
     #[command(visible_aliases = ["st"])]
     Status {
         /// Path to inspect (defaults to current directory)
         #[arg(short, long)]
         path: Option<String>,
+
+        /// Also report OS file-watch limits and how many watches are in use
+        #[arg(long)]
+        watchers: bool,
+    },
+
+    /// Summarize local search usage: queries per day, modes, latency, cache hits
+    Usage {
+        /// Number of days to include, ending today (UTC)
+        #[arg(long, default_value = "30")]
+        days: u64,
+    },
+
+    /// Diagnose index/checkout mismatches (worktrees, sparse checkouts)
+    Doctor {
+        /// Path to inspect (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
     },
 
     /// MCP server and host config integration
@@ -520,40 +520,16 @@ pub enum Commands {
         command: McpCommands,
     },
 
+    /// Neovim msgpack-rpc server on stdio (search/symbols/definition)
+    NvimRpc {
+        /// Lua module whose `_on_event` receives streamed results
+        #[arg(long, default_value = "cgrep")]
+        lua_module: String,
+    },
+
     /// Search for symbols (functions, classes, etc.)
     #[command(visible_aliases = ["sym", "sy"])]
-    Symbols {
-        /// Symbol name to search for
-        name: String,
-
-        /// Filter by symbol type (function, class, variable, etc.)
-        #[arg(short = 'T', long = "type")]
-        symbol_type: Option<String>,
-
-        /// Filter by language (typescript, python, rust, etc.)
-        #[arg(short, long)]
-        lang: Option<String>,
-
-        /// Filter by file type/language (e.g., rust, ts, python)
-        #[arg(short = 't', long = "file-type")]
-        file_type: Option<String>,
-
-        /// Filter files matching glob pattern (e.g., "*.rs", "src/**/*.ts")
-        #[arg(short = 'g', long, visible_alias = "include")]
-        glob: Option<String>,
-
-        /// Exclude files matching pattern
-        #[arg(short = 'x', long, visible_alias = "exclude-dir")]
-        exclude: Option<String>,
-
-        /// Limit symbol search to files changed since revision (default: HEAD)
-        #[arg(short = 'u', long, num_args = 0..=1, default_missing_value = "HEAD")]
-        changed: Option<String>,
-
-        /// Suppress statistics output
-        #[arg(short = 'q', long)]
-        quiet: bool,
-    },
+    Symbols(Box<SymbolsArgs>),
 
     /// Find symbol definition location
     #[command(visible_aliases = ["def", "d"])]
@@ -614,73 +590,255 @@ pub enum Commands {
         mode: UsageSearchMode,
     },
 
-    /// Find files that depend on a given file
+    /// List a symbol's references and check a new name for collisions before renaming
+    RenameCheck {
+        /// Current symbol name
+        old: String,
+
+        /// Proposed name
+        new: String,
+
+        /// Path to search in (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
+    /// Find files that depend on a given file, or list declared external dependencies
     #[command(visible_aliases = ["deps", "dep"])]
     Dependents {
         /// File path to find dependents for
+        #[arg(required_unless_present = "external")]
+        file: Option<String>,
+
+        /// Also follow dependents of dependents, reporting each chain and any cycles
+        #[arg(long)]
+        transitive: bool,
+
+        /// Import levels to follow with --transitive [default: 5]
+        #[arg(long, requires = "transitive")]
+        depth: Option<usize>,
+
+        /// List dependencies declared in Cargo.toml, package.json, and pyproject.toml,
+        /// with versions pinned by their lockfiles
+        #[arg(long, conflicts_with_all = ["file", "transitive"])]
+        external: bool,
+
+        /// Path to scan with --external (defaults to current directory)
+        #[arg(short, long, requires = "external")]
+        path: Option<String>,
+    },
+
+    /// List the files and external packages a file imports
+    Imports {
+        /// File path to list imports for
         file: String,
     },
 
-    /// Build or rebuild the search index
-    #[command(visible_aliases = ["ix", "i"])]
-    Index {
-        /// Path to index (defaults to current directory)
+    /// Inventory external packages imported across the codebase
+    Packages {
+        /// Only report this package, with every location that imports it
+        name: Option<String>,
+
+        /// Path to scan (defaults to current directory)
         #[arg(short, long)]
         path: Option<String>,
 
-        /// Force full reindex
+        /// Example locations listed per package
+        #[arg(long, default_value_t = 3)]
+        examples: usize,
+    },
+
+    /// List public API symbols per module, or diff them against a baseline report
+    Api {
+        /// Path to report on (defaults to current directory)
         #[arg(short, long)]
-        force: bool,
+        path: Option<String>,
+
+        /// Only report one language (rust, python, typescript, go, ...)
+        #[arg(short, long)]
+        lang: Option<String>,
+
+        /// Earlier `cgrep --format json api` report; fail when the API differs
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<String>,
+    },
+
+    /// List HTTP endpoints (axum, actix-web, Rocket, Flask, FastAPI, Express, Spring)
+    Routes {
+        /// Only routes serving this path (`/users/42` matches `/users/{id}`)
+        query: Option<String>,
+
+        /// Path to scan (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Only routes accepting this HTTP method (GET, POST, ...)
+        #[arg(short = 'X', long)]
+        method: Option<String>,
+    },
+
+    /// List environment variable reads with their locations and literal defaults
+    #[command(name = "envvars", visible_alias = "env")]
+    EnvVars {
+        /// Only variables whose name contains this text (case-insensitive)
+        name: Option<String>,
 
-        /// Embedding generation mode: auto, precompute, or off
-        #[arg(short = 'E', long, default_value = "off")]
-        embeddings: String,
+        /// Path to scan (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
+    /// List feature flag checks (LaunchDarkly, Cargo features, configured helpers) with usage sites
+    Flags {
+        /// Only this flag key
+        flag: Option<String>,
+
+        /// Path to scan (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
 
-        /// Force regeneration of all embeddings
-        #[arg(short = 'F', long)]
-        embeddings_force: bool,
+        /// Detector to run: a built-in (`launchdarkly`, `cargo-feature`), a
+        /// `[flags.patterns]` name, or a regex capturing the key (repeatable)
+        #[arg(long = "pattern", value_name = "REGEX|NAME")]
+        patterns: Vec<String>,
+    },
+
+    /// Locate risky constructs (unsafe code, eval, raw HTML, SQL concatenation) for security review
+    Audit {
+        /// Preset to run: rust, python, javascript, sql (repeatable; default all)
+        #[arg(long = "preset", value_name = "NAME")]
+        presets: Vec<String>,
+
+        /// Path to audit (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
 
-        /// Use a high-memory index writer (1GiB budget)
-        #[arg(short = 'H', long)]
-        high_memory: bool,
+    /// Compare symbols between two index snapshots (added, removed, changed)
+    #[command(name = "diff-index")]
+    DiffIndex {
+        /// Snapshot tags: `<from> [to]`; `to` defaults to the live index
+        #[arg(value_name = "SNAPSHOT", num_args = 0..=2)]
+        snapshots: Vec<String>,
 
-        /// Include files ignored by .gitignore/.ignore (opt-out of default ignore-respecting index)
+        /// Snapshot to compare against (the live index, or a single positional tag)
+        #[arg(long, value_name = "TAG")]
+        baseline: Option<String>,
+
+        /// Path containing the index (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Exit non-zero when symbols were removed
         #[arg(long)]
-        include_ignored: bool,
+        fail_on_removed: bool,
+    },
+
+    /// Rank functions by size and complexity metrics
+    Metrics {
+        /// Path to measure (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Filter by language (typescript, python, rust, etc.)
+        #[arg(short, long)]
+        lang: Option<String>,
 
-        /// Build index asynchronously in background and return immediately
+        /// Metric to rank by
+        #[arg(long, value_enum, default_value_t = CliMetric::Complexity)]
+        by: CliMetric,
+
+        /// Number of functions to list
+        #[arg(long, default_value_t = 50)]
+        top: usize,
+
+        /// Only functions meeting a metric condition (repeatable, e.g. "lines>100")
+        #[arg(long = "metric", value_name = "EXPR")]
+        metric_filters: Vec<String>,
+    },
+
+    /// Find a numeric value where it appears as a literal in code (not comments or names)
+    #[command(name = "const")]
+    Const {
+        /// Numeric value, in any literal spelling (86400, 0x15180, 1e3, -1)
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+
+        /// Path to search in (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Maximum number of results
+        #[arg(short = 'm', long = "limit")]
+        max_results: Option<usize>,
+    },
+
+    /// Find string literals by value (exact, normalized, or constant name)
+    #[command(visible_alias = "lit")]
+    Literals {
+        /// Literal value, e.g. a config key or error message
+        value: String,
+
+        /// Path to search in (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Also report literals that contain the value
         #[arg(long)]
-        background: bool,
+        contains: bool,
 
-        /// Internal: spawned background index worker
-        #[arg(long, hide = true)]
-        background_worker: bool,
+        /// Maximum number of results
+        #[arg(short = 'm', long = "limit")]
+        max_results: Option<usize>,
+    },
 
-        /// Reuse local compatible index artifacts: off, strict, or auto
-        #[arg(long = "reuse", default_value = "off")]
-        reuse: String,
+    /// Find the source line that printed a log message
+    #[command(name = "logsource", visible_alias = "logsrc")]
+    LogSource {
+        /// Log line as printed (timestamps, levels, and values are ignored)
+        log_line: String,
 
-        /// Disable manifest-based change detection and use legacy incremental behavior
-        #[arg(long = "no-manifest")]
-        no_manifest: bool,
+        /// Path to search in (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
 
-        /// Update only the manifest and diff summary without reindexing docs
-        #[arg(long = "manifest-only", conflicts_with = "no_manifest")]
-        manifest_only: bool,
+        /// Maximum number of candidates
+        #[arg(short = 'm', long = "limit", default_value = "10")]
+        max_results: usize,
+    },
 
-        /// Print deterministic added/modified/deleted diff after manifest scan
-        #[arg(long = "print-diff", conflicts_with = "no_manifest")]
-        print_diff: bool,
+    /// Annotate a diff with the symbols it touches, their definitions, and their callers
+    Review {
+        /// Revision range to review (`base..head`, `base...head`), or a
+        /// revision to compare with the working tree
+        #[arg(short = 'u', long, value_name = "RANGE", default_value = "HEAD")]
+        changed: String,
 
-        /// Include a path even if it is ignored by .gitignore/.ignore (repeatable)
-        #[arg(long = "include-path")]
-        include_paths: Vec<String>,
+        /// Limit the diff to a path (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
 
-        /// Paths/patterns to exclude (can be specified multiple times)
-        #[arg(long = "exclude", short = 'e')]
-        exclude_paths: Vec<String>,
+        /// Call sites listed per changed function
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        callers: usize,
     },
 
+    /// Resolve a stack trace (Python, Java, Node, Rust) to current files and lines
+    #[command(visible_alias = "tr")]
+    Trace {
+        /// Read the trace from a file instead of stdin (`-` for stdin)
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<String>,
+
+        /// Lines of context around each resolved frame
+        #[arg(short = 'C', long, default_value = "2")]
+        context: usize,
+    },
+
+    /// Build or rebuild the search index
+    #[command(visible_aliases = ["ix", "i"], args_conflicts_with_subcommands = true)]
+    Index(Box<IndexArgs>),
+
     /// Install cgrep for Claude Code
     #[command(name = "install-claude-code", hide = true)]
     InstallClaudeCode,
@@ -713,6 +871,89 @@ pub enum Commands {
     #[command(name = "uninstall-opencode", hide = true)]
     UninstallOpencode,
 
+    /// Check policy rules (literal/regex/AST patterns) from a YAML file
+    Check {
+        /// Rules file (YAML with a top-level `rules` list)
+        #[arg(short, long, value_name = "FILE")]
+        rules: String,
+
+        /// Path to check (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Only check files changed since revision (default: HEAD)
+        #[arg(short = 'u', long, num_args = 0..=1, default_missing_value = "HEAD")]
+        changed: Option<String>,
+
+        /// Only check files staged for commit (git index); implies --changed
+        #[arg(long)]
+        staged: bool,
+    },
+
+    /// Check imports against the allowed dependencies between layers
+    Boundaries {
+        /// Layer rules file (TOML with `[[layer]]` tables)
+        #[arg(short, long, value_name = "FILE", default_value = "boundaries.toml")]
+        rules: String,
+
+        /// Path to check (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+    },
+
+    /// Install a git pre-commit hook that runs `cgrep check` on staged files
+    #[command(name = "install-precommit")]
+    InstallPrecommit {
+        /// Rules file the hook passes to `cgrep check`
+        #[arg(short, long, value_name = "FILE", default_value = "rules.yaml")]
+        rules: String,
+
+        /// Repository path (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Add a local hook to .pre-commit-config.yaml instead of writing .git/hooks/pre-commit
+        #[arg(long = "pre-commit-framework")]
+        framework: bool,
+
+        /// Replace an existing pre-commit hook not written by cgrep
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Install git hooks that refresh the index after checkout, merge, and rewrite
+    #[command(name = "install-git-hooks")]
+    InstallGitHooks {
+        /// Repository path (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<String>,
+
+        /// Replace existing hooks not written by cgrep
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Print the JSON Schema for a command's json2 payload, or validate a payload
+    Schema {
+        /// Command name (search, status, doctor, agent-plan, agent-expand, read, map, check, trace); lists names when omitted
+        command: Option<String>,
+
+        /// Validate a payload file (`-` for stdin) against the schema instead of printing it
+        #[arg(long, value_name = "FILE", requires = "command")]
+        validate: Option<String>,
+    },
+
+    /// Check query escaping, section ranges, and glob matching on generated inputs
+    Selftest {
+        /// Generated inputs per check
+        #[arg(long, default_value = "1000")]
+        cases: usize,
+
+        /// Generator seed, to replay a failure (default: random)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
@@ -721,6 +962,571 @@ pub enum Commands {
     },
 }
 
+/// Arguments of [`Commands::Search`].
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+    /// Search query (natural language or keywords)
+    #[arg(required_unless_present = "help_advanced")]
+    pub query: Option<String>,
+
+    /// Optional path (grep-style positional form)
+    #[arg(value_name = "PATH")]
+    pub path_positional: Option<String>,
+
+    /// Path to search in (defaults to current directory)
+    #[arg(short, long, help_heading = "Core")]
+    pub path: Option<String>,
+
+    /// Search subdirectories recursively (grep -r, default)
+    #[arg(short = 'r', long, help_heading = "Scope")]
+    pub recursive: bool,
+
+    /// Search only the top-level directory in the scope
+    #[arg(long, conflicts_with = "recursive", help_heading = "Scope")]
+    pub no_recursive: bool,
+
+    /// Do not respect .gitignore/.ignore rules (forces scan mode)
+    #[arg(long, help_heading = "Scope")]
+    pub no_ignore: bool,
+
+    /// Search hidden files and directories (forces scan mode)
+    #[arg(long, help_heading = "Scope")]
+    pub hidden: bool,
+
+    /// Do not respect .gitignore, .git/info/exclude, or the global gitignore (forces scan mode)
+    #[arg(long, help_heading = "Scope")]
+    pub no_ignore_vcs: bool,
+
+    /// Do not respect the global gitignore (forces scan mode)
+    #[arg(long, help_heading = "Scope")]
+    pub no_ignore_global: bool,
+
+    /// Maximum number of results
+    #[arg(
+        short = 'm',
+        long = "limit",
+        visible_alias = "max-results",
+        help_heading = "Core"
+    )]
+    pub limit: Option<usize>,
+
+    /// Show N lines before and after each match (like grep -C), or
+    /// `symbol-header` for the enclosing function/class signature
+    #[arg(
+        short = 'C',
+        long,
+        value_name = "N|symbol-header",
+        help_heading = "Core"
+    )]
+    pub context: Option<ContextArg>,
+
+    /// Filter by file type/language (e.g., rust, ts, python)
+    #[arg(short = 't', long = "type", help_heading = "Core")]
+    pub file_type: Option<String>,
+
+    /// Filter files matching glob pattern (e.g., "*.rs", "src/**/*.ts")
+    #[arg(short = 'g', long, visible_alias = "include", help_heading = "Core")]
+    pub glob: Option<String>,
+
+    /// Exclude files matching pattern
+    #[arg(
+        short = 'x',
+        long,
+        visible_alias = "exclude-dir",
+        help_heading = "Core"
+    )]
+    pub exclude: Option<String>,
+
+    /// Limit search to files changed since revision (default: HEAD)
+    #[arg(
+        short = 'u',
+        long,
+        num_args = 0..=1,
+        default_missing_value = "HEAD",
+        help_heading = "Core"
+    )]
+    pub changed: Option<String>,
+
+    /// Limit search to files with uncommitted changes (staged, unstaged, or untracked)
+    #[arg(long, conflicts_with = "changed", help_heading = "Core")]
+    pub only_dirty: bool,
+
+    /// Only match lines added by a unified diff (`-` for stdin)
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["changed", "only_dirty"],
+        help_heading = "Scope"
+    )]
+    pub from_patch: Option<String>,
+
+    /// Limit search to files committed to since a date (YYYY-MM-DD) or age (30d, 2w)
+    #[arg(long, value_name = "DATE", help_heading = "Scope")]
+    pub modified_since: Option<String>,
+
+    /// Limit search to files with a commit author matching pattern (name or email)
+    #[arg(long, value_name = "PATTERN", help_heading = "Scope")]
+    pub author: Option<String>,
+
+    /// Output budget preset (tight, balanced, full, off, or a `[budgets.<name>]` from config)
+    #[arg(short = 'B', long, help_heading = "Core")]
+    pub budget: Option<CliBudgetPreset>,
+
+    /// Use a preset or config profile (human/user, agent/ai, fast/quick; default: $CGREP_PROFILE)
+    #[arg(short = 'P', long, help_heading = "Core")]
+    pub profile: Option<String>,
+
+    /// Suppress statistics output
+    #[arg(short = 'q', long, help_heading = "Core")]
+    pub quiet: bool,
+
+    /// Treat query as a regular expression (scan mode)
+    #[arg(long, help_heading = "Mode")]
+    pub regex: bool,
+
+    /// Grep compatibility flag (ignore case, default behavior)
+    #[arg(
+        short = 'i',
+        long = "ignore-case",
+        conflicts_with = "case_sensitive",
+        help_heading = "Mode"
+    )]
+    pub ignore_case: bool,
+
+    /// Case-sensitive search (scan mode)
+    #[arg(long, conflicts_with = "ignore_case", help_heading = "Mode")]
+    pub case_sensitive: bool,
+
+    /// Search mode: keyword, semantic, or hybrid (semantic/hybrid are experimental)
+    #[arg(short = 'M', long, value_enum, help_heading = "Mode")]
+    pub mode: Option<CliSearchMode>,
+
+    /// Emit deterministic score component breakdown for top matches
+    #[arg(long, help_heading = "Mode")]
+    pub explain: bool,
+
+    /// Do not boost the language named in the query (e.g. "python retry decorator")
+    #[arg(long, help_heading = "Mode")]
+    pub no_lang_route: bool,
+
+    /// Match only symbol doc comments (rustdoc, docstrings, JSDoc, Javadoc)
+    #[arg(long, help_heading = "Mode")]
+    pub search_docs: bool,
+
+    /// Cap results per directory and interleave across directories
+    #[arg(long, help_heading = "Mode")]
+    pub diversify: bool,
+
+    /// Group results into labeled clusters by embedding similarity
+    #[arg(long, help_heading = "Mode")]
+    pub cluster: bool,
+
+    /// One entry per file: match count, matched symbols, and line ranges
+    #[arg(long, help_heading = "Mode")]
+    pub summarize_files: bool,
+
+    /// Reindex first if the index is stale; fail if it cannot be refreshed
+    #[arg(long, help_heading = "Mode")]
+    pub require_fresh: bool,
+
+    /// Fail instead of falling back to scan mode when the index cannot answer
+    #[arg(long, help_heading = "Mode")]
+    pub no_fallback: bool,
+
+    /// Print how the query would be routed (index/scan, literal handling, filters, fetch sizes) without searching
+    #[arg(long, help_heading = "Mode")]
+    pub plan_only: bool,
+
+    /// Hash the top result files against the index and flag ones changed since indexing
+    #[arg(long, help_heading = "Mode")]
+    pub verify: bool,
+
+    /// Stop retrieval after this many milliseconds and print partial results
+    #[arg(long, value_name = "MS", help_heading = "Mode")]
+    pub timeout_ms: Option<u64>,
+
+    /// Deprecated: use `--mode keyword`
+    #[arg(
+        long,
+        hide = true,
+        conflicts_with = "semantic",
+        conflicts_with = "hybrid"
+    )]
+    pub keyword: bool,
+
+    /// Deprecated: use `--mode semantic`
+    #[arg(
+        long,
+        hide = true,
+        conflicts_with = "keyword",
+        conflicts_with = "hybrid"
+    )]
+    pub semantic: bool,
+
+    /// Deprecated: use `--mode hybrid`
+    #[arg(
+        long,
+        hide = true,
+        conflicts_with = "keyword",
+        conflicts_with = "semantic"
+    )]
+    pub hybrid: bool,
+
+    /// Print advanced options for search and exit
+    #[arg(long, help_heading = "Help")]
+    pub help_advanced: bool,
+
+    /// Context pack size for agent mode (merges overlapping context)
+    #[arg(long, hide = true)]
+    pub context_pack: Option<usize>,
+
+    /// Enable agent session caching
+    #[arg(long, hide = true)]
+    pub agent_cache: bool,
+
+    /// Cache TTL in milliseconds (default: 600000 = 10 minutes)
+    #[arg(long, hide = true)]
+    pub cache_ttl: Option<u64>,
+
+    /// Maximum characters per snippet in output
+    #[arg(long, hide = true)]
+    pub max_chars_per_snippet: Option<usize>,
+
+    /// Maximum total characters across returned results
+    #[arg(long, hide = true)]
+    pub max_total_chars: Option<usize>,
+
+    /// Maximum context characters per result (before+after)
+    #[arg(long, hide = true)]
+    pub max_context_chars: Option<usize>,
+
+    /// Remove duplicated context lines across results
+    #[arg(long, hide = true)]
+    pub dedupe_context: bool,
+
+    /// Use short path aliases (p1, p2, ...) in json2 output with lookup table in meta
+    #[arg(long, hide = true)]
+    pub path_alias: bool,
+
+    /// Suppress repeated boilerplate lines (imports/headers) in snippets and context
+    #[arg(long, hide = true)]
+    pub suppress_boilerplate: bool,
+
+    /// Enable fuzzy matching (allows 1-2 character differences)
+    #[arg(short = 'f', long, hide = true)]
+    pub fuzzy: bool,
+
+    /// Do not use the index; scan files directly
+    #[arg(long, hide = true)]
+    pub no_index: bool,
+
+    /// Internal flag for metadata when MCP bootstrapped an index before search
+    #[arg(long, hide = true)]
+    pub bootstrap_index: bool,
+}
+
+/// Arguments of [`Commands::Grep`].
+#[derive(Args, Debug)]
+pub struct GrepArgs {
+    /// Pattern to search for (repeatable); positionals are then all paths
+    #[arg(short = 'e', long = "regexp", value_name = "PATTERN")]
+    pub regexp: Vec<String>,
+
+    /// PATTERN followed by files or directories to search
+    #[arg(value_name = "PATTERN|PATH")]
+    pub args: Vec<String>,
+
+    /// Case insensitive search
+    #[arg(short = 'i', long)]
+    pub ignore_case: bool,
+
+    /// Case insensitive unless the pattern contains uppercase
+    #[arg(short = 'S', long)]
+    pub smart_case: bool,
+
+    /// Case sensitive search (overrides -i/-S)
+    #[arg(short = 's', long)]
+    pub case_sensitive: bool,
+
+    /// Only match whole words
+    #[arg(short = 'w', long)]
+    pub word_regexp: bool,
+
+    /// Treat patterns as literal strings
+    #[arg(short = 'F', long)]
+    pub fixed_strings: bool,
+
+    /// Include/exclude files by glob (prefix with `!` to exclude)
+    #[arg(short = 'g', long = "glob", value_name = "GLOB")]
+    pub glob: Vec<String>,
+
+    /// Only search files of TYPE (e.g. rust, py, ts)
+    #[arg(short = 't', long = "type", value_name = "TYPE")]
+    pub file_type: Vec<String>,
+
+    /// Skip files of TYPE
+    #[arg(short = 'T', long = "type-not", value_name = "TYPE")]
+    pub type_not: Vec<String>,
+
+    /// Lines of context after each match
+    #[arg(short = 'A', long, value_name = "NUM")]
+    pub after_context: Option<usize>,
+
+    /// Lines of context before each match
+    #[arg(short = 'B', long, value_name = "NUM")]
+    pub before_context: Option<usize>,
+
+    /// Lines of context before and after each match
+    #[arg(short = 'C', long, value_name = "NUM")]
+    pub context: Option<usize>,
+
+    /// Print only paths of files with matches
+    #[arg(short = 'l', long, conflicts_with = "count")]
+    pub files_with_matches: bool,
+
+    /// Print the number of matching lines per file
+    #[arg(short = 'c', long)]
+    pub count: bool,
+
+    /// Show line numbers (default when printing to a terminal)
+    #[arg(short = 'n', long)]
+    pub line_number: bool,
+
+    /// Hide line numbers
+    #[arg(short = 'N', long, conflicts_with = "line_number")]
+    pub no_line_number: bool,
+
+    /// Always print file paths
+    #[arg(short = 'H', long)]
+    pub with_filename: bool,
+
+    /// Never print file paths
+    #[arg(short = 'I', long, conflicts_with = "with_filename")]
+    pub no_filename: bool,
+
+    /// Show the column of the first match
+    #[arg(long)]
+    pub column: bool,
+
+    /// Print every match as `path:line:column:text`
+    #[arg(long)]
+    pub vimgrep: bool,
+
+    /// Stop after NUM matching lines per file
+    #[arg(short = 'm', long, value_name = "NUM")]
+    pub max_count: Option<usize>,
+
+    /// Print nothing; exit status reports whether anything matched
+    #[arg(short = 'q', long)]
+    pub quiet: bool,
+
+    /// Search hidden files and directories
+    #[arg(long)]
+    pub hidden: bool,
+
+    /// Do not respect .gitignore/.ignore files
+    #[arg(long)]
+    pub no_ignore: bool,
+
+    /// Do not respect .gitignore, .git/info/exclude, or the global gitignore
+    #[arg(long)]
+    pub no_ignore_vcs: bool,
+
+    /// Do not respect the global gitignore
+    #[arg(long)]
+    pub no_ignore_global: bool,
+}
+
+/// Arguments of [`Commands::Symbols`].
+#[derive(Args, Debug)]
+pub struct SymbolsArgs {
+    /// Symbol name to search for
+    #[arg(required_unless_present_any = ["names", "names_file", "returns", "params", "decorated_with", "metric_filters"])]
+    pub name: Option<String>,
+
+    /// Resolve several names in one pass (repeatable); output is keyed by name
+    #[arg(long = "name", value_name = "NAME")]
+    pub names: Vec<String>,
+
+    /// Read names to resolve from a file, one per line (`-` for stdin)
+    #[arg(long, value_name = "PATH")]
+    pub names_file: Option<String>,
+
+    /// Filter by symbol type (function, class, variable, etc.)
+    #[arg(short = 'T', long = "type")]
+    pub symbol_type: Option<String>,
+
+    /// Only functions whose return type matches (`_` is a wildcard, e.g. "Result<_>")
+    #[arg(long, value_name = "TYPE")]
+    pub returns: Option<String>,
+
+    /// Only functions with a parameter of this type (repeatable; `_` is a wildcard)
+    #[arg(long = "params", value_name = "TYPE")]
+    pub params: Vec<String>,
+
+    /// Only symbols with this decorator, attribute, or annotation (e.g. `pytest.fixture`, `tokio::main`, `Override`)
+    #[arg(long, value_name = "NAME")]
+    pub decorated_with: Option<String>,
+
+    /// Only functions meeting a metric condition (repeatable, e.g. "complexity>=10", "params>5")
+    #[arg(long = "metric", value_name = "EXPR")]
+    pub metric_filters: Vec<String>,
+
+    /// Filter by language (typescript, python, rust, etc.)
+    #[arg(short, long)]
+    pub lang: Option<String>,
+
+    /// Filter by file type/language (e.g., rust, ts, python)
+    #[arg(short = 't', long = "file-type")]
+    pub file_type: Option<String>,
+
+    /// Filter files matching glob pattern (e.g., "*.rs", "src/**/*.ts")
+    #[arg(short = 'g', long, visible_alias = "include")]
+    pub glob: Option<String>,
+
+    /// Exclude files matching pattern
+    #[arg(short = 'x', long, visible_alias = "exclude-dir")]
+    pub exclude: Option<String>,
+
+    /// Limit symbol search to files changed since revision (default: HEAD)
+    #[arg(short = 'u', long, num_args = 0..=1, default_missing_value = "HEAD")]
+    pub changed: Option<String>,
+
+    /// Limit symbol search to files with uncommitted changes (staged, unstaged, or untracked)
+    #[arg(long, conflicts_with = "changed")]
+    pub only_dirty: bool,
+
+    /// Result order (applied before --offset/--limit)
+    #[arg(long, value_enum, default_value_t = CliSymbolSort::Path)]
+    pub sort: CliSymbolSort,
+
+    /// Skip this many results, per name when batching
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub offset: usize,
+
+    /// Maximum number of results, per name when batching
+    #[arg(short = 'm', long = "limit", visible_alias = "max-results")]
+    pub limit: Option<usize>,
+
+    /// Stop after this many milliseconds and print the symbols found so far
+    #[arg(long, value_name = "MS")]
+    pub timeout_ms: Option<u64>,
+
+    /// Suppress statistics output
+    #[arg(short = 'q', long)]
+    pub quiet: bool,
+}
+
+/// Arguments of [`Commands::Index`].
+#[derive(Args, Debug)]
+pub struct IndexArgs {
+    #[command(subcommand)]
+    pub action: Option<IndexCommands>,
+
+    /// Path to index (defaults to current directory)
+    #[arg(short, long)]
+    pub path: Option<String>,
+
+    /// Force full reindex
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Embedding generation mode: auto, precompute, or off
+    #[arg(short = 'E', long, default_value = "off")]
+    pub embeddings: String,
+
+    /// Force regeneration of all embeddings
+    #[arg(short = 'F', long)]
+    pub embeddings_force: bool,
+
+    /// Continue an interrupted --embeddings-force rebuild from its last committed batch
+    #[arg(long = "resume")]
+    pub embeddings_resume: bool,
+
+    /// Symbols embedded and committed per checkpoint batch (default: 256)
+    #[arg(long = "embeddings-batch-size", value_name = "N")]
+    pub embeddings_batch_size: Option<usize>,
+
+    /// Use a high-memory index writer (1GiB budget)
+    #[arg(short = 'H', long)]
+    pub high_memory: bool,
+
+    /// Include files ignored by .gitignore/.ignore (opt-out of default ignore-respecting index)
+    #[arg(long)]
+    pub include_ignored: bool,
+
+    /// Index hidden files and directories
+    #[arg(long)]
+    pub hidden: bool,
+
+    /// Do not respect .gitignore, .git/info/exclude, or the global gitignore
+    #[arg(long)]
+    pub no_ignore_vcs: bool,
+
+    /// Do not respect the global gitignore (core.excludesFile)
+    #[arg(long)]
+    pub no_ignore_global: bool,
+
+    /// Build index asynchronously in background and return immediately
+    #[arg(long)]
+    pub background: bool,
+
+    /// Internal: spawned background index worker
+    #[arg(long, hide = true)]
+    pub background_worker: bool,
+
+    /// Reuse local compatible index artifacts: off, strict, or auto
+    #[arg(long = "reuse", default_value = "off")]
+    pub reuse: String,
+
+    /// Disable manifest-based change detection and use legacy incremental behavior
+    #[arg(long = "no-manifest")]
+    pub no_manifest: bool,
+
+    /// Update only the manifest and diff summary without reindexing docs
+    #[arg(long = "manifest-only", conflicts_with = "no_manifest")]
+    pub manifest_only: bool,
+
+    /// Print deterministic added/modified/deleted diff after manifest scan
+    #[arg(long = "print-diff", conflicts_with = "no_manifest")]
+    pub print_diff: bool,
+
+    /// Scan and diff only; report what would be indexed without writing anything
+    #[arg(
+        long = "dry-run",
+        conflicts_with_all = ["no_manifest", "manifest_only", "background"]
+    )]
+    pub dry_run: bool,
+
+    /// Include a path even if it is ignored by .gitignore/.ignore (repeatable)
+    #[arg(long = "include-path")]
+    pub include_paths: Vec<String>,
+
+    /// Paths/patterns to exclude (can be specified multiple times)
+    #[arg(long = "exclude", short = 'e')]
+    pub exclude_paths: Vec<String>,
+
+    /// Print the stored index scope (exclude/include/ignore settings) and exit
+    #[arg(long = "show-scope")]
+    pub show_scope: bool,
+
+    /// Ignore the stored scope and index with only the scope flags given now
+    #[arg(long = "reset-scope")]
+    pub reset_scope: bool,
+
+    /// Keep a separate index per git branch and swap it in on checkout (sticky)
+    #[arg(long = "branch-aware")]
+    pub branch_aware: bool,
+
+    /// Wait for another index run holding the lock to finish (default)
+    #[arg(long, conflicts_with = "no_wait")]
+    pub wait: bool,
+
+    /// Fail immediately if another index run holds the lock
+    #[arg(long = "no-wait")]
+    pub no_wait: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -745,16 +1551,17 @@ mod tests {
         .expect("parse search alias");
 
         match cli.command {
-            Commands::Search {
-                query,
-                mode,
-                budget,
-                profile,
-                exclude,
-                changed,
-                explain,
-                ..
-            } => {
+            Commands::Search(search) => {
+                let SearchArgs {
+                    query,
+                    mode,
+                    budget,
+                    profile,
+                    exclude,
+                    changed,
+                    explain,
+                    ..
+                } = *search;
                 assert_eq!(query.as_deref(), Some("auth flow"));
                 assert_eq!(mode, Some(CliSearchMode::Keyword));
                 assert_eq!(budget, Some(CliBudgetPreset::Tight));
@@ -773,11 +1580,12 @@ mod tests {
             .expect("parse search with positional path");
 
         match cli.command {
-            Commands::Search {
-                query,
-                path_positional,
-                ..
-            } => {
+            Commands::Search(search) => {
+                let SearchArgs {
+                    query,
+                    path_positional,
+                    ..
+                } = *search;
                 assert_eq!(query.as_deref(), Some("auth flow"));
                 assert_eq!(path_positional.as_deref(), Some("src"));
             }
@@ -791,13 +1599,14 @@ mod tests {
             .expect("parse search scope flags");
 
         match cli.command {
-            Commands::Search {
-                query,
-                path_positional,
-                recursive,
-                no_ignore,
-                ..
-            } => {
+            Commands::Search(search) => {
+                let SearchArgs {
+                    query,
+                    path_positional,
+                    recursive,
+                    no_ignore,
+                    ..
+                } = *search;
                 assert_eq!(query.as_deref(), Some("needle"));
                 assert_eq!(path_positional.as_deref(), Some("src"));
                 assert!(recursive);
@@ -813,7 +1622,8 @@ mod tests {
             .expect("parse search explain flag");
 
         match cli.command {
-            Commands::Search { explain, .. } => {
+            Commands::Search(search) => {
+                let SearchArgs { explain, .. } = *search;
                 assert!(explain);
             }
             other => panic!("expected search command, got {other:?}"),
@@ -893,12 +1703,13 @@ mod tests {
             .expect("parse index manifest flags");
 
         match cli.command {
-            Commands::Index {
-                manifest_only,
-                print_diff,
-                no_manifest,
-                ..
-            } => {
+            Commands::Index(index) => {
+                let IndexArgs {
+                    manifest_only,
+                    print_diff,
+                    no_manifest,
+                    ..
+                } = *index;
                 assert!(manifest_only);
                 assert!(print_diff);
                 assert!(!no_manifest);
@@ -913,12 +1724,13 @@ mod tests {
             .expect("parse background index");
 
         match cli.command {
-            Commands::Index {
-                background,
-                background_worker,
-                reuse,
-                ..
-            } => {
+            Commands::Index(index) => {
+                let IndexArgs {
+                    background,
+                    background_worker,
+                    reuse,
+                    ..
+                } = *index;
                 assert!(background);
                 assert!(!background_worker);
                 assert_eq!(reuse, "off");
@@ -933,24 +1745,80 @@ mod tests {
             Cli::try_parse_from(["cgrep", "index", "--reuse", "auto"]).expect("parse reuse mode");
 
         match cli.command {
-            Commands::Index { reuse, .. } => {
+            Commands::Index(index) => {
+                let IndexArgs { reuse, .. } = *index;
                 assert_eq!(reuse, "auto");
             }
             other => panic!("expected index command, got {other:?}"),
         }
     }
 
+    #[test]
+    fn index_wait_flags_conflict() {
+        let cli = Cli::try_parse_from(["cgrep", "index", "--no-wait"]).expect("parse no-wait");
+        match cli.command {
+            Commands::Index(index) => {
+                let IndexArgs { no_wait, wait, .. } = *index;
+                assert!(no_wait);
+                assert!(!wait);
+            }
+            other => panic!("expected index command, got {other:?}"),
+        }
+        assert!(Cli::try_parse_from(["cgrep", "index", "--wait", "--no-wait"]).is_err());
+    }
+
     #[test]
     fn status_alias_parses() {
         let cli = Cli::try_parse_from(["cgrep", "st", "-p", "src"]).expect("parse status alias");
         match cli.command {
-            Commands::Status { path } => {
+            Commands::Status { path, watchers } => {
                 assert_eq!(path.as_deref(), Some("src"));
+                assert!(!watchers);
             }
             other => panic!("expected status command, got {other:?}"),
         }
     }
 
+    #[test]
+    fn refresh_parses_path() {
+        let cli = Cli::try_parse_from(["cgrep", "refresh", "-p", "repo"]).expect("parse refresh");
+        match cli.command {
+            Commands::Refresh { path } => assert_eq!(path.as_deref(), Some("repo")),
+            other => panic!("expected refresh command, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn doctor_parses_path() {
+        let cli = Cli::try_parse_from(["cgrep", "doctor", "-p", "repo"]).expect("parse doctor");
+        match cli.command {
+            Commands::Doctor { path } => assert_eq!(path.as_deref(), Some("repo")),
+            other => panic!("expected doctor command, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn schema_validate_requires_command() {
+        assert!(Cli::try_parse_from(["cgrep", "schema", "--validate", "-"]).is_err());
+        let cli = Cli::try_parse_from(["cgrep", "schema", "search", "--validate", "out.json"])
+            .expect("parse schema");
+        match cli.command {
+            Commands::Schema { command, validate } => {
+                assert_eq!(command.as_deref(), Some("search"));
+                assert_eq!(validate.as_deref(), Some("out.json"));
+            }
+            other => panic!("expected schema command, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn schema_version_is_global() {
+        let cli = Cli::try_parse_from(["cgrep", "status", "--schema-version", "1"])
+            .expect("parse schema version");
+        assert_eq!(cli.schema_version, Some(1));
+        assert!(Cli::try_parse_from(["cgrep", "--schema-version", "x", "status"]).is_err());
+    }
+
     #[test]
     fn watch_command_is_not_available() {
         assert!(Cli::try_parse_from(["cgrep", "watch"]).is_err());
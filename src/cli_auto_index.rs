@@ -7,15 +7,24 @@ use std::process::{Command, Stdio};
 use std::time::SystemTime;
 
 use crate::indexer;
+use cgrep::config::{AutoIndexConfig, Config};
 
+/// Default for `[auto_index] debounce_ms` on the CLI path.
 const CLI_AUTO_INDEX_CHECK_COOLDOWN_MS: u64 = 2_000;
+/// Entries visited per allowed file before the size estimate gives up, so a
+/// home directory full of non-source files still stops early.
+const SIZE_ESTIMATE_ENTRIES_PER_FILE: usize = 4;
+
+/// Lower bound on what bootstrapping an index over a scope would read; the
+/// scan stops as soon as a limit is crossed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepoSizeEstimate {
+    pub files: usize,
+    pub bytes: u64,
+}
 
 pub fn maybe_prepare_cli_auto_index(path: Option<&str>) {
-    if std::env::var("CGREP_DISABLE_CLI_AUTO_INDEX")
-        .ok()
-        .as_deref()
-        .is_some_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
-    {
+    if cli_auto_index_disabled() {
         return;
     }
 
@@ -27,15 +36,31 @@ pub fn maybe_prepare_cli_auto_index(path: Option<&str>) {
         .as_ref()
         .map(|root| root.root.clone())
         .unwrap_or_else(|| search_scope.clone());
+    let config = Config::load_for_dir(&index_scope);
+    let policy = config.auto_index();
+    if !policy.enabled() {
+        return;
+    }
 
-    if existing_index_root.is_some() && cli_auto_index_check_is_fresh(&index_scope) {
+    let cooldown_ms = policy
+        .debounce_ms
+        .unwrap_or(CLI_AUTO_INDEX_CHECK_COOLDOWN_MS);
+    // A branch-aware index built for another branch is swapped before the cooldown applies.
+    let branch_switched =
+        existing_index_root.is_some() && indexer::branches::checkout_changed(&index_scope);
+    if existing_index_root.is_some()
+        && !branch_switched
+        && cli_auto_index_check_is_fresh(&index_scope, cooldown_ms)
+    {
         return;
     }
 
-    let should_index = if existing_index_root.is_some() {
+    let should_index = if branch_switched {
+        true
+    } else if existing_index_root.is_some() {
         cli_scope_has_indexable_changes_since(&search_scope, &index_scope).unwrap_or(true)
     } else {
-        true
+        oversized_scope(&index_scope, policy).is_none()
     };
     if existing_index_root.is_some() {
         let _ = touch_cli_auto_index_check(&index_scope);
@@ -44,11 +69,56 @@ pub fn maybe_prepare_cli_auto_index(path: Option<&str>) {
         return;
     }
 
-    if run_cli_index_for_scope(&index_scope).is_ok() {
+    if run_cli_index_for_scope(&index_scope, policy.embeddings_mode()).is_ok() {
         let _ = touch_cli_auto_index_check(&index_scope);
     }
 }
 
+/// Rebuild a stale index for `search --require-fresh`.
+pub fn reindex_stale_index(index_root: &Path) -> Result<()> {
+    let config = Config::load_for_dir(index_root);
+    if cli_auto_index_disabled() || !config.auto_index().enabled() {
+        anyhow::bail!("auto-indexing is disabled ([auto_index] enabled = false)");
+    }
+    run_cli_index_for_scope(index_root, config.auto_index().embeddings_mode())?;
+    let _ = touch_cli_auto_index_check(index_root);
+    Ok(())
+}
+
+/// The size estimate for `scope` when it is over the `[auto_index]`
+/// bootstrap limits (`max_repo_files`, `max_repo_size_mb`), `None` otherwise.
+pub fn oversized_scope(scope: &Path, policy: &AutoIndexConfig) -> Option<RepoSizeEstimate> {
+    let max_files = policy.max_repo_files();
+    let max_bytes = policy.max_repo_size_bytes().unwrap_or(u64::MAX);
+    let max_entries = max_files
+        .saturating_add(1)
+        .saturating_mul(SIZE_ESTIMATE_ENTRIES_PER_FILE);
+    let mut estimate = RepoSizeEstimate { files: 0, bytes: 0 };
+    for (visited, entry) in scope_walker(scope).flatten().enumerate() {
+        if visited >= max_entries {
+            return Some(estimate);
+        }
+        let path = entry.path();
+        if !path.is_file() || !should_track_cli_auto_index_path(scope, path) {
+            continue;
+        }
+        estimate.files += 1;
+        estimate.bytes += entry.metadata().map_or(0, |metadata| metadata.len());
+        if estimate.files > max_files || estimate.bytes > max_bytes {
+            return Some(estimate);
+        }
+    }
+    None
+}
+
+/// `CGREP_DISABLE_CLI_AUTO_INDEX=1` still overrides `[auto_index] enabled`.
+fn cli_auto_index_disabled() -> bool {
+    std::env::var("CGREP_DISABLE_CLI_AUTO_INDEX")
+        .ok()
+        .as_deref()
+        .is_some_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+}
+
 pub fn background_index_active_for_scope(path: Option<&str>) -> bool {
     let Ok(scope) = resolve_cli_scope(path) else {
         return false;
@@ -108,24 +178,7 @@ fn cli_scope_has_indexable_changes_since(search_scope: &Path, index_scope: &Path
         .and_then(|metadata| metadata.modified())
         .unwrap_or(SystemTime::UNIX_EPOCH);
 
-    let mut builder = WalkBuilder::new(search_scope);
-    builder
-        .hidden(false)
-        .ignore(true)
-        .git_ignore(true)
-        .git_exclude(true)
-        .git_global(true);
-    let walker = builder
-        .filter_entry(|entry| {
-            entry
-                .file_name()
-                .to_str()
-                .map(|name| !matches!(name, ".cgrep" | ".git" | ".hg" | ".svn"))
-                .unwrap_or(true)
-        })
-        .build();
-
-    for entry in walker {
+    for entry in scope_walker(search_scope) {
         let Ok(entry) = entry else { continue };
         let path = entry.path();
         if !path.is_file() || !should_track_cli_auto_index_path(search_scope, path) {
@@ -147,6 +200,25 @@ fn cli_scope_has_indexable_changes_since(search_scope: &Path, index_scope: &Path
     Ok(false)
 }
 
+fn scope_walker(scope: &Path) -> ignore::Walk {
+    let mut builder = WalkBuilder::new(scope);
+    builder
+        .hidden(false)
+        .ignore(true)
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(true);
+    builder
+        .filter_entry(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .map(|name| !matches!(name, ".cgrep" | ".git" | ".hg" | ".svn"))
+                .unwrap_or(true)
+        })
+        .build()
+}
+
 fn should_track_cli_auto_index_path(scope_root: &Path, path: &Path) -> bool {
     let relative = path.strip_prefix(scope_root).unwrap_or(path);
     if relative.as_os_str().is_empty() {
@@ -179,11 +251,17 @@ fn should_track_cli_auto_index_path(scope_root: &Path, path: &Path) -> bool {
     indexer::scanner::is_indexable_extension(ext)
 }
 
-fn run_cli_index_for_scope(scope: &Path) -> Result<()> {
+fn run_cli_index_for_scope(scope: &Path, embeddings: &str) -> Result<()> {
     let exe = std::env::current_exe()?;
     let scope_arg = scope.display().to_string();
     let status = Command::new(exe)
-        .args(["index", "-p", scope_arg.as_str(), "--embeddings", "off"])
+        .args([
+            "index",
+            "-p",
+            scope_arg.as_str(),
+            "--embeddings",
+            embeddings,
+        ])
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -195,7 +273,7 @@ fn run_cli_index_for_scope(scope: &Path) -> Result<()> {
     }
 }
 
-fn cli_auto_index_check_is_fresh(index_scope: &Path) -> bool {
+fn cli_auto_index_check_is_fresh(index_scope: &Path, cooldown_ms: u64) -> bool {
     let stamp_path = cli_auto_index_stamp_path(index_scope);
     let Ok(metadata) = std::fs::metadata(stamp_path) else {
         return false;
@@ -206,7 +284,7 @@ fn cli_auto_index_check_is_fresh(index_scope: &Path) -> bool {
     let Ok(elapsed) = modified.elapsed() else {
         return false;
     };
-    elapsed.as_millis() < u128::from(CLI_AUTO_INDEX_CHECK_COOLDOWN_MS)
+    elapsed.as_millis() < u128::from(cooldown_ms)
 }
 
 fn touch_cli_auto_index_check(index_scope: &Path) -> Result<()> {
@@ -274,4 +352,26 @@ mod tests {
             dir.canonicalize().expect("dir canonical")
         );
     }
+
+    #[test]
+    fn oversized_scope_stops_at_the_first_limit_crossed() {
+        let tmp = tempdir().expect("tempdir");
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            std::fs::write(tmp.path().join(name), "fn f() {}\n").expect("write");
+        }
+        std::fs::write(tmp.path().join("notes.bin"), "x").expect("write");
+
+        let policy =
+            |toml_src: &str| -> AutoIndexConfig { toml::from_str(toml_src).expect("parse policy") };
+        assert_eq!(oversized_scope(tmp.path(), &policy("")), None);
+
+        let estimate =
+            oversized_scope(tmp.path(), &policy("max_repo_files = 1")).expect("over file limit");
+        assert_eq!(estimate.files, 2);
+
+        assert_eq!(
+            oversized_scope(tmp.path(), &policy("max_repo_size_mb = 1")),
+            None
+        );
+    }
 }
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Profile defaults for every command.
+//!
+//! The active profile comes from `search -P/--profile` or `CGREP_PROFILE`.
+//! Its `format` and `compact` apply to every command, and its `args` table
+//! adds default flags per command (`search = ["--budget", "tight"]`). A
+//! default flag is dropped when the user passed it, or a flag it conflicts
+//! with, so the command line always wins.
+
+use clap::parser::ValueSource;
+use clap::{Arg, ArgMatches, Command, CommandFactory, FromArgMatches, Parser};
+use std::ffi::OsString;
+
+use crate::cli::Cli;
+use cgrep::config::{is_builtin_profile, Config, ProfileConfig};
+
+/// Resolve a requested profile name, warning when it names no profile.
+pub fn resolve(config: &Config, requested: &str) -> String {
+    let resolved = config.resolve_profile_name(requested);
+    if resolved == requested && !config.has_profile(requested) && !is_builtin_profile(requested) {
+        eprintln!(
+            "Warning: unknown profile `{}`. Built-ins: human (aliases: user, developer, dev), agent (aliases: ai, ai-agent, coding-agent, coding_agent), fast (alias: quick).",
+            requested
+        );
+    }
+    resolved
+}
+
+/// Parse the command line with the active profile's default flags added.
+pub fn parse(config: &Config) -> (Cli, Option<ProfileConfig>) {
+    let mut argv: Vec<OsString> = std::env::args_os().collect();
+    let command = Cli::command();
+    let Ok(matches) = command.clone().try_get_matches_from(&argv) else {
+        // Let clap report the error, help, or version.
+        return (Cli::parse_from(argv), None);
+    };
+    let Some((name, sub_matches)) = matches.subcommand() else {
+        return (from_matches(&matches), None);
+    };
+
+    let requested = if name == "search" {
+        sub_matches.get_one::<String>("profile").cloned()
+    } else {
+        None
+    }
+    .map(|name| name.trim().to_string())
+    .filter(|name| !name.is_empty())
+    .or_else(cgrep::config::env_profile);
+    let Some(requested) = requested else {
+        return (from_matches(&matches), None);
+    };
+    // `search` resolves (and warns about) its own profile.
+    let resolved = if name == "search" {
+        config.resolve_profile_name(&requested)
+    } else {
+        resolve(config, &requested)
+    };
+    let profile = config.profile(&resolved);
+
+    let defaults = profile
+        .args
+        .get(name)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let extra = match command.find_subcommand(name) {
+        Some(subcommand) => default_flags(subcommand, sub_matches, defaults),
+        None => Vec::new(),
+    };
+    if extra.is_empty() {
+        return (from_matches(&matches), Some(profile));
+    }
+    // Before `--`, so the defaults are not read as positional values.
+    let insert_at = argv
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(argv.len());
+    argv.splice(insert_at..insert_at, extra);
+    (Cli::parse_from(argv), Some(profile))
+}
+
+fn from_matches(matches: &ArgMatches) -> Cli {
+    Cli::from_arg_matches(matches).unwrap_or_else(|err| err.exit())
+}
+
+/// The `defaults` the user did not override, as command-line tokens.
+fn default_flags(command: &Command, matches: &ArgMatches, defaults: &[String]) -> Vec<OsString> {
+    let given =
+        |arg: &Arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine);
+    let explicit: Vec<&Arg> = command.get_arguments().filter(|arg| given(arg)).collect();
+
+    let mut extra = Vec::new();
+    for group in flag_groups(defaults) {
+        if let Some(arg) = find_arg(command, &group[0]) {
+            let overridden = given(arg)
+                || command.get_arg_conflicts_with(arg).into_iter().any(given)
+                || explicit.iter().any(|other| {
+                    command
+                        .get_arg_conflicts_with(other)
+                        .iter()
+                        .any(|conflict| conflict.get_id() == arg.get_id())
+                });
+            if overridden {
+                continue;
+            }
+        }
+        extra.extend(group.iter().map(OsString::from));
+    }
+    extra
+}
+
+/// Split default tokens into a flag followed by its values.
+fn flag_groups(tokens: &[String]) -> Vec<&[String]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for (idx, token) in tokens.iter().enumerate().skip(1) {
+        if token.starts_with('-') {
+            groups.push(&tokens[start..idx]);
+            start = idx;
+        }
+    }
+    if start < tokens.len() {
+        groups.push(&tokens[start..]);
+    }
+    groups
+}
+
+fn find_arg<'a>(command: &'a Command, flag: &str) -> Option<&'a Arg> {
+    if let Some(long) = flag.strip_prefix("--") {
+        let long = long.split_once('=').map_or(long, |(name, _)| name);
+        command.get_arguments().find(|arg| {
+            arg.get_long() == Some(long)
+                || arg
+                    .get_all_aliases()
+                    .is_some_and(|aliases| aliases.contains(&long))
+        })
+    } else {
+        let short = flag.strip_prefix('-')?.chars().next()?;
+        command
+            .get_arguments()
+            .find(|arg| arg.get_short() == Some(short))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults_for(argv: &[&str], defaults: &[&str]) -> Vec<String> {
+        let matches = Cli::command().get_matches_from(argv);
+        let (name, sub_matches) = matches.subcommand().expect("subcommand");
+        let command = Cli::command();
+        let subcommand = command.find_subcommand(name).expect("known subcommand");
+        let defaults: Vec<String> = defaults.iter().map(|token| token.to_string()).collect();
+        default_flags(subcommand, sub_matches, &defaults)
+            .into_iter()
+            .map(|token| token.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn explicit_and_conflicting_flags_drop_profile_defaults() {
+        let defaults = ["--budget", "tight", "--changed", "-m", "5"];
+        assert_eq!(
+            defaults_for(&["cgrep", "search", "needle"], &defaults),
+            defaults
+        );
+        assert_eq!(
+            defaults_for(&["cgrep", "search", "needle", "-B", "full"], &defaults),
+            ["--changed", "-m", "5"]
+        );
+        assert_eq!(
+            defaults_for(&["cgrep", "search", "needle", "--only-dirty"], &defaults),
+            ["--budget", "tight", "-m", "5"]
+        );
+        assert_eq!(
+            defaults_for(&["cgrep", "search", "needle", "--limit=9"], &defaults),
+            ["--budget", "tight", "--changed"]
+        );
+    }
+}
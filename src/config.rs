@@ -4,8 +4,8 @@
 //!
 //! Loads configuration from .cgreprc.toml in current directory or ~/.config/cgrep/config.toml
 
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 /// Output format for results (mirrored from cli for library use)
@@ -49,6 +49,67 @@ pub enum EmbeddingProviderType {
     Command,
 }
 
+/// Execution device for the builtin embedding provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingDevice {
+    /// Use an accelerator compiled into this build when one works, else CPU.
+    #[default]
+    Auto,
+    Cpu,
+    Cuda,
+    Coreml,
+    Directml,
+}
+
+impl EmbeddingDevice {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Cpu => "cpu",
+            Self::Cuda => "cuda",
+            Self::Coreml => "coreml",
+            Self::Directml => "directml",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "cpu" => Some(Self::Cpu),
+            "cuda" | "gpu" => Some(Self::Cuda),
+            "coreml" => Some(Self::Coreml),
+            "directml" | "dml" => Some(Self::Directml),
+            _ => None,
+        }
+    }
+}
+
+/// Where content-addressed embedding vectors are cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingCacheScope {
+    /// `.cgrep/embedding_cache.sqlite`, shared by every branch of this checkout.
+    #[default]
+    Repo,
+    /// A user-level cache shared by all repositories.
+    Global,
+    Off,
+}
+
+/// Handling of paths marked `linguist-vendored` / `linguist-generated` in `.gitattributes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinguistPolicy {
+    /// Rank them below all other results.
+    #[default]
+    Demote,
+    /// Drop them from results.
+    Exclude,
+    /// Ignore the attributes.
+    Off,
+}
+
 /// Search configuration
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(default)]
@@ -61,6 +122,20 @@ pub struct SearchConfig {
     pub weight_text: Option<f32>,
     /// Weight for vector/semantic scoring in hybrid mode (0.0-1.0)
     pub weight_vector: Option<f32>,
+    /// Query-time boost for matches in symbol doc comments (0.0-5.0)
+    pub docs_boost: Option<f32>,
+    /// Cap and interleave results per directory (`--diversify`)
+    pub diversify: Option<bool>,
+    /// Results kept per directory when diversifying (1-50)
+    pub max_results_per_dir: Option<usize>,
+    /// Sample the index manifest for staleness before each search
+    pub freshness_check: Option<bool>,
+    /// Hash result files against the index before returning them (`--verify`)
+    pub verify: Option<bool>,
+    /// Result files hashed per verified search (1-1000)
+    pub verify_sample: Option<usize>,
+    /// Handling of linguist-vendored / linguist-generated paths
+    pub linguist: Option<LinguistPolicy>,
 }
 
 impl SearchConfig {
@@ -83,6 +158,45 @@ impl SearchConfig {
     pub fn weight_vector(&self) -> f32 {
         self.weight_vector.unwrap_or(0.3)
     }
+
+    /// Get doc comment field boost (defaults to 1.5)
+    pub fn docs_boost(&self) -> f32 {
+        clamp_weight(self.docs_boost, 1.5, 0.0, 5.0)
+    }
+
+    /// Whether results are diversified across directories (defaults to false)
+    pub fn diversify(&self) -> bool {
+        self.diversify.unwrap_or(false)
+    }
+
+    /// Get per-directory result cap for diversification (defaults to 3)
+    pub fn max_results_per_dir(&self) -> usize {
+        self.max_results_per_dir
+            .filter(|value| (1..=50).contains(value))
+            .unwrap_or(3)
+    }
+
+    /// Whether searches check index freshness (defaults to false)
+    pub fn freshness_check(&self) -> bool {
+        self.freshness_check.unwrap_or(false)
+    }
+
+    /// Whether searches verify result files (defaults to false)
+    pub fn verify(&self) -> bool {
+        self.verify.unwrap_or(false)
+    }
+
+    /// Get the number of result files hashed by `--verify` (defaults to 20)
+    pub fn verify_sample(&self) -> usize {
+        self.verify_sample
+            .filter(|value| (1..=1000).contains(value))
+            .unwrap_or(20)
+    }
+
+    /// Get the linguist attribute policy (defaults to Demote)
+    pub fn linguist(&self) -> LinguistPolicy {
+        self.linguist.unwrap_or_default()
+    }
 }
 
 /// Keyword ranking configuration (non-embedding signals).
@@ -103,6 +217,10 @@ pub struct RankingConfig {
     pub kind_weight: Option<f32>,
     /// Penalty weight for weak identifier matches.
     pub weak_signal_penalty: Option<f32>,
+    /// Git commit frequency (churn) boost weight.
+    pub churn_weight: Option<f32>,
+    /// Recently modified file (mtime) boost weight.
+    pub recency_weight: Option<f32>,
     /// Number of top results with score explanation.
     pub explain_top_k: Option<usize>,
 }
@@ -136,6 +254,14 @@ impl RankingConfig {
         clamp_weight(self.weak_signal_penalty, 1.0, 0.0, 3.0)
     }
 
+    pub fn churn_weight(&self) -> f32 {
+        clamp_weight(self.churn_weight, 1.0, 0.0, 3.0)
+    }
+
+    pub fn recency_weight(&self) -> f32 {
+        clamp_weight(self.recency_weight, 1.0, 0.0, 3.0)
+    }
+
     pub fn explain_top_k(&self) -> usize {
         self.explain_top_k
             .filter(|value| (1..=50).contains(value))
@@ -163,6 +289,12 @@ pub struct EmbeddingConfig {
     pub batch_size: Option<usize>,
     /// Maximum characters per text passed to provider (builtin fastembed)
     pub max_chars: Option<usize>,
+    /// Execution device for the builtin provider (auto, cpu, cuda, coreml, directml)
+    pub device: Option<EmbeddingDevice>,
+    /// Symbols embedded per committed checkpoint batch during indexing
+    pub commit_batch_size: Option<usize>,
+    /// Content-hash embedding cache location (repo, global, off)
+    pub content_cache: Option<EmbeddingCacheScope>,
     /// Model identifier for the embedding provider (used by command provider)
     pub model: Option<String>,
     /// Command to execute for command provider
@@ -206,6 +338,21 @@ impl EmbeddingConfig {
         self.max_chars
     }
 
+    /// Get execution device (defaults to Auto)
+    pub fn device(&self) -> EmbeddingDevice {
+        self.device.unwrap_or_default()
+    }
+
+    /// Get checkpoint batch size (defaults to 256 symbols)
+    pub fn commit_batch_size(&self) -> usize {
+        self.commit_batch_size.unwrap_or(256).max(1)
+    }
+
+    /// Get content cache scope (defaults to Repo)
+    pub fn content_cache(&self) -> EmbeddingCacheScope {
+        self.content_cache.unwrap_or_default()
+    }
+
     /// Get model identifier (defaults to "local-model-id")
     pub fn model(&self) -> &str {
         self.model.as_deref().unwrap_or("local-model-id")
@@ -269,6 +416,10 @@ pub struct IndexConfig {
     pub max_file_size: Option<u64>,
     /// Whether index build should respect .gitignore/.ignore rules
     pub respect_git_ignore: Option<bool>,
+    /// Archive content indexing (`[index.archives]`)
+    pub archives: ArchiveConfig,
+    /// Reduced indexing for oversized files (`[[index.size_policies]]`)
+    pub size_policies: Vec<SizePolicyConfig>,
 }
 
 impl IndexConfig {
@@ -288,6 +439,202 @@ impl IndexConfig {
     }
 }
 
+/// Archive content indexing configuration (opt-in)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    /// Index text files inside archives (default: false)
+    pub enabled: Option<bool>,
+    /// Archive file suffixes to open (default: zip, jar, whl, tar.gz, tgz)
+    pub extensions: Option<Vec<String>>,
+    /// How many levels of nested archives to open (default: 1)
+    pub max_depth: Option<usize>,
+    /// Largest archive entry to index, in bytes (default: `index.max_file_size`)
+    pub max_entry_bytes: Option<u64>,
+}
+
+impl ArchiveConfig {
+    /// Whether archive indexing is enabled (default: false)
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+
+    /// Archive suffixes, lowercased and without a leading dot
+    pub fn extensions(&self) -> Vec<String> {
+        match &self.extensions {
+            Some(extensions) => extensions
+                .iter()
+                .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            None => ["zip", "jar", "whl", "tar.gz", "tgz"]
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+        }
+    }
+
+    /// Nesting depth (default: 1, i.e. archives inside archives are not opened)
+    pub fn max_depth(&self) -> usize {
+        self.max_depth.unwrap_or(1).max(1)
+    }
+
+    /// Per-entry size cap, falling back to the index-wide file size limit
+    pub fn max_entry_bytes(&self, max_file_size: u64) -> u64 {
+        self.max_entry_bytes.unwrap_or(max_file_size)
+    }
+}
+
+/// How much of a file matched by a size policy is indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizePolicyMode {
+    /// Symbol names and kinds only, without file contents or previews
+    #[default]
+    Symbols,
+    /// The path only
+    Path,
+}
+
+/// One `[[index.size_policies]]` entry
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SizePolicyConfig {
+    /// Glob matched against the repo-relative path (e.g. `dist/**`)
+    pub path: Option<String>,
+    /// File extensions, with or without a leading dot (e.g. `lock`)
+    pub extensions: Vec<String>,
+    /// Apply only to files larger than this many bytes (default: 0, every match)
+    pub max_bytes: Option<u64>,
+    /// What to keep of a matched file (default: symbols)
+    pub mode: SizePolicyMode,
+}
+
+/// Watch/daemon configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    /// Commands run after successful incremental reindexes
+    pub hooks: WatchHooksConfig,
+}
+
+/// Commands the watcher runs after a successful reindex
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WatchHooksConfig {
+    /// Shell commands, run in order from the watched root
+    pub commands: Vec<String>,
+    /// Minimum seconds between hook runs; reindexes in between are batched
+    pub debounce_secs: Option<u64>,
+    /// Seconds before a hook command is killed
+    pub timeout_secs: Option<u64>,
+}
+
+impl WatchHooksConfig {
+    /// Get debounce in seconds (defaults to 30)
+    pub fn debounce_secs(&self) -> u64 {
+        self.debounce_secs.unwrap_or(30)
+    }
+
+    /// Get timeout in seconds (defaults to 300)
+    pub fn timeout_secs(&self) -> u64 {
+        self.timeout_secs.unwrap_or(300).max(1)
+    }
+}
+
+/// Feature flag checks for `cgrep flags`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FlagsConfig {
+    /// Named regexes for repo-specific flag helpers; the `flag` group (or
+    /// the first group) captures the flag key
+    pub patterns: BTreeMap<String, String>,
+}
+
+/// Local usage stats for `cgrep usage`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UsageConfig {
+    /// Append one record per search to the local usage file
+    pub enabled: Option<bool>,
+}
+
+impl UsageConfig {
+    /// Get enabled (defaults to true)
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+}
+
+/// Automatic indexing before CLI commands and MCP tool calls
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AutoIndexConfig {
+    /// Create or refresh the index automatically
+    pub enabled: Option<bool>,
+    /// Refuse to bootstrap an index when indexable files exceed this many MiB
+    pub max_repo_size_mb: Option<u64>,
+    /// Refuse to bootstrap an index over more than this many indexable files
+    pub max_repo_files: Option<usize>,
+    /// Generate embeddings while auto-indexing
+    pub embeddings: Option<bool>,
+    /// Minimum milliseconds between change checks for one index
+    pub debounce_ms: Option<u64>,
+    /// Build or warm the index when an MCP session starts
+    pub mcp_warmup: Option<bool>,
+}
+
+impl AutoIndexConfig {
+    /// Get enabled (defaults to true)
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    /// Get mcp_warmup (defaults to false)
+    pub fn mcp_warmup(&self) -> bool {
+        self.mcp_warmup.unwrap_or(false)
+    }
+
+    /// Get the size limit in bytes (defaults to unlimited)
+    pub fn max_repo_size_bytes(&self) -> Option<u64> {
+        self.max_repo_size_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+
+    /// Get the file count limit (defaults to 200,000)
+    pub fn max_repo_files(&self) -> usize {
+        self.max_repo_files.unwrap_or(200_000)
+    }
+
+    /// `cgrep index --embeddings` value for auto-indexing (defaults to `off`)
+    pub fn embeddings_mode(&self) -> &'static str {
+        if self.embeddings.unwrap_or(false) {
+            "auto"
+        } else {
+            "off"
+        }
+    }
+}
+
+/// MCP server scope defaults
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct McpConfig {
+    /// Default scope for tool calls without `cwd`
+    pub default_root: Option<String>,
+    /// Default scope per client, keyed by the `clientInfo.name` sent at `initialize`
+    pub client_roots: HashMap<String, String>,
+    /// Run calls without `cwd` in the server's working directory when no root is known
+    pub cwd_fallback: Option<bool>,
+}
+
+impl McpConfig {
+    /// Get cwd_fallback (defaults to true)
+    pub fn cwd_fallback(&self) -> bool {
+        self.cwd_fallback.unwrap_or(true)
+    }
+}
+
 /// Cache configuration
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(default)]
@@ -310,6 +657,51 @@ impl CacheConfig {
     }
 }
 
+/// Telemetry (OpenTelemetry metrics export) configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// Whether metrics and spans are recorded and exported (requires the `otel` feature)
+    pub enabled: Option<bool>,
+    /// OTLP/HTTP collector endpoint (`/v1/metrics` and `/v1/traces` are appended)
+    pub endpoint: Option<String>,
+    /// `service.name` resource attribute
+    pub service_name: Option<String>,
+    /// Extra HTTP headers sent with each export (e.g. auth tokens)
+    pub headers: HashMap<String, String>,
+    /// Export interval for long-running processes (MCP server) in milliseconds
+    pub export_interval_ms: Option<u64>,
+    /// Export request timeout in milliseconds
+    pub timeout_ms: Option<u64>,
+}
+
+impl TelemetryConfig {
+    /// Get enabled (defaults to false)
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+
+    /// Get endpoint (defaults to "http://localhost:4318")
+    pub fn endpoint(&self) -> &str {
+        self.endpoint.as_deref().unwrap_or("http://localhost:4318")
+    }
+
+    /// Get service name (defaults to "cgrep")
+    pub fn service_name(&self) -> &str {
+        self.service_name.as_deref().unwrap_or("cgrep")
+    }
+
+    /// Get export interval (defaults to 10000 = 10 seconds, min 1000)
+    pub fn export_interval_ms(&self) -> u64 {
+        self.export_interval_ms.unwrap_or(10_000).max(1_000)
+    }
+
+    /// Get export timeout (defaults to 2000)
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms.unwrap_or(2_000)
+    }
+}
+
 /// Profile configuration for different usage modes
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(default)]
@@ -326,6 +718,21 @@ pub struct ProfileConfig {
     pub mode: Option<SearchMode>,
     /// Whether to use agent caching (for agent profile)
     pub agent_cache: Option<bool>,
+    /// Compact JSON output for every command (same as `--compact`)
+    pub compact: Option<bool>,
+    /// Default flags per command, e.g. `search = ["--budget", "tight"]`
+    pub args: HashMap<String, Vec<String>>,
+}
+
+/// Environment variable that selects a profile for every command.
+pub const PROFILE_ENV: &str = "CGREP_PROFILE";
+
+/// Profile named by `CGREP_PROFILE`, if set and non-empty.
+pub fn env_profile() -> Option<String> {
+    std::env::var(PROFILE_ENV)
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
 }
 
 const PROFILE_HUMAN_ALIASES: &[&str] = &["human", "user", "developer", "dev"];
@@ -364,6 +771,8 @@ impl ProfileConfig {
             max_results: Some(20),
             mode: Some(SearchMode::Keyword),
             agent_cache: None,
+            compact: None,
+            args: HashMap::new(),
         }
     }
 
@@ -376,6 +785,8 @@ impl ProfileConfig {
             max_results: Some(50),
             mode: Some(SearchMode::Hybrid),
             agent_cache: Some(true),
+            compact: None,
+            args: HashMap::new(),
         }
     }
 
@@ -388,6 +799,8 @@ impl ProfileConfig {
             max_results: Some(10),
             mode: Some(SearchMode::Keyword),
             agent_cache: None,
+            compact: None,
+            args: HashMap::new(),
         }
     }
 
@@ -422,6 +835,27 @@ impl ProfileConfig {
     }
 }
 
+/// Output budget preset from a `[budgets.<name>]` table.
+///
+/// A table named after a built-in preset (`tight`, `balanced`, `full`)
+/// overrides only the fields it sets; any other name starts from no caps.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// Total payload character cap
+    pub max_total_chars: Option<usize>,
+    /// Per-snippet character cap
+    pub max_chars_per_snippet: Option<usize>,
+    /// Per-result context character cap
+    pub max_context_chars: Option<usize>,
+    /// Remove duplicate context lines
+    pub dedupe_context: Option<bool>,
+    /// Use p1/p2 path aliases in json2
+    pub path_alias: Option<bool>,
+    /// Suppress repeated import/header lines
+    pub suppress_boilerplate: Option<bool>,
+}
+
 /// Configuration loaded from .cgreprc.toml or ~/.config/cgrep/config.toml
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
@@ -453,9 +887,37 @@ pub struct Config {
     #[serde(default)]
     pub ranking: RankingConfig,
 
+    /// Telemetry configuration
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+
+    /// Local usage stats configuration
+    #[serde(default)]
+    pub usage: UsageConfig,
+
+    /// Watch/daemon configuration
+    #[serde(default)]
+    pub watch: WatchConfig,
+
+    /// Auto-index policy for CLI and MCP
+    #[serde(default)]
+    pub auto_index: AutoIndexConfig,
+
+    /// Default scope for MCP tool calls
+    #[serde(default)]
+    pub mcp: McpConfig,
+
+    /// Feature flag helpers for `cgrep flags`
+    #[serde(default)]
+    pub flags: FlagsConfig,
+
     /// Named profiles (e.g., "human", "agent", "fast")
     #[serde(default, rename = "profile")]
     pub profiles: HashMap<String, ProfileConfig>,
+
+    /// Named output budget presets for `--budget`
+    #[serde(default)]
+    pub budgets: HashMap<String, BudgetConfig>,
 }
 
 impl Config {
@@ -541,6 +1003,11 @@ impl Config {
         trimmed.to_string()
     }
 
+    /// Budget preset table for a `--budget` name.
+    pub fn budget(&self, name: &str) -> Option<&BudgetConfig> {
+        self.budgets.get(name)
+    }
+
     /// Return true if a custom profile exists with an exact key match.
     pub fn has_profile(&self, name: &str) -> bool {
         self.profiles.contains_key(name)
@@ -590,6 +1057,26 @@ impl Config {
         &self.ranking
     }
 
+    /// Get the telemetry configuration
+    pub fn telemetry(&self) -> &TelemetryConfig {
+        &self.telemetry
+    }
+
+    /// Get the local usage stats configuration
+    pub fn usage(&self) -> &UsageConfig {
+        &self.usage
+    }
+
+    /// Get the auto-index configuration
+    pub fn auto_index(&self) -> &AutoIndexConfig {
+        &self.auto_index
+    }
+
+    /// Get the MCP configuration
+    pub fn mcp(&self) -> &McpConfig {
+        &self.mcp
+    }
+
     /// Check if embeddings should be enabled based on configuration and environment
     pub fn embeddings_enabled(&self) -> bool {
         match self.embeddings.enabled() {
@@ -614,6 +1101,8 @@ mod tests {
         assert_eq!(cfg.ranking().changed_weight(), 1.0);
         assert_eq!(cfg.ranking().kind_weight(), 1.0);
         assert_eq!(cfg.ranking().weak_signal_penalty(), 1.0);
+        assert_eq!(cfg.ranking().churn_weight(), 1.0);
+        assert_eq!(cfg.ranking().recency_weight(), 1.0);
         assert_eq!(cfg.ranking().explain_top_k(), 5);
     }
 
@@ -629,6 +1118,8 @@ language_weight = nan
 changed_weight = inf
 kind_weight = 2.5
 weak_signal_penalty = -0.5
+churn_weight = 7.0
+recency_weight = -1.0
 explain_top_k = 0
 "#,
         )
@@ -641,9 +1132,23 @@ explain_top_k = 0
         assert_eq!(cfg.ranking().changed_weight(), 1.0);
         assert_eq!(cfg.ranking().kind_weight(), 2.5);
         assert_eq!(cfg.ranking().weak_signal_penalty(), 0.0);
+        assert_eq!(cfg.ranking().churn_weight(), 3.0);
+        assert_eq!(cfg.ranking().recency_weight(), 0.0);
         assert_eq!(cfg.ranking().explain_top_k(), 5);
     }
 
+    #[test]
+    fn embedding_device_defaults_to_auto() {
+        let cfg = Config::default();
+        assert_eq!(cfg.embeddings.device(), EmbeddingDevice::Auto);
+
+        let cfg: Config =
+            toml::from_str("[embeddings]\ndevice = \"coreml\"\n").expect("parse config");
+        assert_eq!(cfg.embeddings.device(), EmbeddingDevice::Coreml);
+        assert_eq!(EmbeddingDevice::parse(" GPU "), Some(EmbeddingDevice::Cuda));
+        assert_eq!(EmbeddingDevice::parse("tpu"), None);
+    }
+
     #[test]
     fn profile_aliases_resolve_to_builtins() {
         let cfg = Config::default();
@@ -671,4 +1176,66 @@ max_results = 7
         assert_eq!(cfg.profile("user").format(), ConfigOutputFormat::Json);
         assert_eq!(cfg.profile("user").max_results(), 7);
     }
+
+    #[test]
+    fn auto_index_config_defaults_and_overrides() {
+        let cfg = Config::default();
+        assert!(cfg.auto_index().enabled());
+        assert_eq!(cfg.auto_index().max_repo_size_bytes(), None);
+        assert_eq!(cfg.auto_index().max_repo_files(), 200_000);
+        assert_eq!(cfg.auto_index().embeddings_mode(), "off");
+        assert_eq!(cfg.auto_index().debounce_ms, None);
+        assert!(!cfg.auto_index().mcp_warmup());
+
+        let cfg: Config = toml::from_str(
+            r#"
+[auto_index]
+enabled = false
+max_repo_size_mb = 2
+max_repo_files = 50
+embeddings = true
+debounce_ms = 5000
+mcp_warmup = true
+"#,
+        )
+        .expect("parse auto_index config");
+        assert!(!cfg.auto_index().enabled());
+        assert_eq!(
+            cfg.auto_index().max_repo_size_bytes(),
+            Some(2 * 1024 * 1024)
+        );
+        assert_eq!(cfg.auto_index().max_repo_files(), 50);
+        assert_eq!(cfg.auto_index().embeddings_mode(), "auto");
+        assert_eq!(cfg.auto_index().debounce_ms, Some(5000));
+        assert!(cfg.auto_index().mcp_warmup());
+    }
+
+    #[test]
+    fn telemetry_config_defaults_to_disabled() {
+        let cfg = Config::default();
+        assert!(!cfg.telemetry().enabled());
+        assert_eq!(cfg.telemetry().endpoint(), "http://localhost:4318");
+        assert_eq!(cfg.telemetry().service_name(), "cgrep");
+
+        let cfg: Config = toml::from_str(
+            r#"
+[telemetry]
+enabled = true
+endpoint = "https://otel.example.com"
+export_interval_ms = 10
+headers = { authorization = "Bearer x" }
+"#,
+        )
+        .expect("parse telemetry config");
+        assert!(cfg.telemetry().enabled());
+        assert_eq!(cfg.telemetry().endpoint(), "https://otel.example.com");
+        assert_eq!(cfg.telemetry().export_interval_ms(), 1_000);
+        assert_eq!(
+            cfg.telemetry()
+                .headers
+                .get("authorization")
+                .map(String::as_str),
+            Some("Bearer x")
+        );
+    }
 }
@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Embedding vectors keyed by `(model, content_hash)`.
+//!
+//! Symbol embeddings in [`EmbeddingStorage`](super::EmbeddingStorage) are tied
+//! to a path and symbol id, so moving or re-checking-out identical code would
+//! embed it again. This cache holds vectors by content alone; it lives next to
+//! the repository index (`repo`) or in the user cache directory shared by all
+//! repositories (`global`).
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::EmbeddingCacheScope;
+
+/// Overrides the `global` cache database path.
+pub const CONTENT_CACHE_ENV: &str = "CGREP_EMBEDDING_CACHE";
+
+const REPO_CACHE_FILE: &str = "embedding_cache.sqlite";
+const GLOBAL_CACHE_FILE: &str = "content-cache.sqlite";
+
+/// SQLite cache of embedding vectors keyed by model and content hash.
+pub struct ContentEmbeddingCache {
+    conn: Connection,
+}
+
+impl ContentEmbeddingCache {
+    /// Opens or creates a cache database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open database: {}", path.display()))?;
+        // Several repositories may write the global cache at once.
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS content_embeddings (
+                model TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (model, content_hash)
+            );
+            "#,
+        )
+        .context("Failed to initialize embedding cache schema")?;
+        Ok(Self { conn })
+    }
+
+    /// Opens the cache for `scope`, or `None` when caching is off.
+    pub fn open_for_scope(repo_root: &Path, scope: EmbeddingCacheScope) -> Result<Option<Self>> {
+        let path = match scope {
+            EmbeddingCacheScope::Off => return Ok(None),
+            EmbeddingCacheScope::Repo => repo_root.join(".cgrep").join(REPO_CACHE_FILE),
+            EmbeddingCacheScope::Global => match global_cache_path() {
+                Some(path) => path,
+                None => return Ok(None),
+            },
+        };
+        Self::open(path).map(Some)
+    }
+
+    /// Cached vectors for `hashes`, keyed by content hash.
+    pub fn get_many(&self, model: &str, hashes: &[&str]) -> Result<HashMap<String, Vec<f32>>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT embedding FROM content_embeddings WHERE model = ?1 AND content_hash = ?2",
+        )?;
+        let mut out = HashMap::new();
+        for hash in hashes {
+            if out.contains_key(*hash) {
+                continue;
+            }
+            let blob: Option<Vec<u8>> = stmt
+                .query_row(params![model, hash], |row| row.get(0))
+                .optional()?;
+            if let Some(blob) = blob {
+                out.insert(hash.to_string(), blob_to_embedding(&blob));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Stores vectors for content hashes in one transaction.
+    pub fn put_many(&mut self, model: &str, entries: &[(&str, &[f32])]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let created_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or_default();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                r#"
+                INSERT OR REPLACE INTO content_embeddings (model, content_hash, embedding, created_at)
+                VALUES (?1, ?2, ?3, ?4)
+                "#,
+            )?;
+            for (hash, embedding) in entries {
+                stmt.execute(params![
+                    model,
+                    hash,
+                    embedding_to_blob(embedding),
+                    created_at
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Number of cached vectors across all models.
+    pub fn len(&self) -> Result<u64> {
+        let count: i64 =
+            self.conn
+                .query_row("SELECT COUNT(*) FROM content_embeddings", [], |row| {
+                    row.get(0)
+                })?;
+        Ok(count as u64)
+    }
+
+    /// Whether the cache holds no vectors.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+}
+
+/// Location of the cache shared by all repositories.
+pub fn global_cache_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(CONTENT_CACHE_ENV) {
+        if !path.trim().is_empty() {
+            return Some(PathBuf::from(path));
+        }
+    }
+
+    #[cfg(windows)]
+    let base = dirs::data_local_dir();
+    #[cfg(not(windows))]
+    let base = dirs::cache_dir();
+    base.map(|base| {
+        base.join("cgrep")
+            .join("embeddings")
+            .join(GLOBAL_CACHE_FILE)
+    })
+}
+
+fn embedding_to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn blob_to_embedding(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn vectors_are_keyed_by_model_and_content_hash() {
+        let dir = tempdir().unwrap();
+        let mut cache = ContentEmbeddingCache::open(dir.path().join("cache.sqlite")).unwrap();
+        assert!(cache.is_empty().unwrap());
+
+        cache
+            .put_many("model-a", &[("h1", &[1.0, 2.0]), ("h2", &[3.0, 4.0])])
+            .unwrap();
+        let hits = cache.get_many("model-a", &["h1", "h3", "h1"]).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits["h1"], vec![1.0, 2.0]);
+        assert!(cache.get_many("model-b", &["h1"]).unwrap().is_empty());
+        assert_eq!(cache.len().unwrap(), 2);
+    }
+}
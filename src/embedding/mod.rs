@@ -6,14 +6,16 @@
 //! enabling semantic similarity search on top of the BM25 text search.
 
 pub mod chunker;
+pub mod content_cache;
 pub mod provider;
 pub mod storage;
 
 pub use chunker::{ChunkConfig, EmbeddingChunker, TextChunk};
+pub use content_cache::ContentEmbeddingCache;
 pub use provider::{
     CommandProvider, DummyProvider, EmbeddingProvider, EmbeddingProviderConfig, FastEmbedder,
 };
 pub use storage::{
-    EmbeddingStorage, SimilarityResult, SymbolEmbedding, SymbolEmbeddingInput,
+    EmbeddingStorage, SimilarityResult, SymbolEmbedding, SymbolEmbeddingInput, CHUNK_SYMBOL_KIND,
     DEFAULT_EMBEDDING_DIM,
 };
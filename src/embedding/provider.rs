@@ -2,17 +2,21 @@
 
 //! Embedding provider interface and implementations.
 //!
-//! This module provides a fastembed-based provider optimized for CPU throughput.
+//! This module provides a fastembed-based provider optimized for CPU throughput,
+//! with optional CUDA/CoreML/DirectML execution providers when cgrep is built
+//! with the matching cargo feature.
 
 use anyhow::{bail, Context, Result};
 #[cfg(not(all(target_os = "macos", target_arch = "x86_64")))]
-use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+use fastembed::{EmbeddingModel, ExecutionProviderDispatch, InitOptions, TextEmbedding};
 use serde_json::Value;
 use std::borrow::Cow;
 use std::env;
 use std::io::Write;
 use std::process::{Command, Stdio};
 
+use crate::config::EmbeddingDevice;
+
 const DEFAULT_FASTEMBED_MODEL: &str = "minilm";
 const DEFAULT_FASTEMBED_BATCH_SIZE: usize = 4;
 const MAX_FASTEMBED_BATCH_SIZE: usize = 1024;
@@ -41,6 +45,7 @@ pub struct EmbeddingProviderConfig {
     pub batch_size: usize,
     pub max_chars: usize,
     pub normalize: bool,
+    pub device: EmbeddingDevice,
 }
 
 impl EmbeddingProviderConfig {
@@ -66,20 +71,31 @@ impl EmbeddingProviderConfig {
         .map(normalize_max_chars)?;
 
         let normalize = parse_bool_env("FASTEMBED_NORMALIZE", true)?;
+        let device = parse_device_env(EmbeddingDevice::Cpu)?;
 
         Ok(Self {
             model,
             batch_size,
             max_chars,
             normalize,
+            device,
         })
     }
 
+    /// Use `device` unless `FASTEMBED_DEVICE` overrides it.
+    pub fn with_device(mut self, device: EmbeddingDevice) -> Self {
+        if env::var_os("FASTEMBED_DEVICE").is_none() {
+            self.device = device;
+        }
+        self
+    }
+
     pub fn has_env_overrides() -> bool {
         env::var_os("FASTEMBED_MODEL").is_some()
             || env::var_os("FASTEMBED_BATCH_SIZE").is_some()
             || env::var_os("FASTEMBED_MAX_CHARS").is_some()
             || env::var_os("FASTEMBED_NORMALIZE").is_some()
+            || env::var_os("FASTEMBED_DEVICE").is_some()
     }
 }
 
@@ -90,6 +106,7 @@ impl Default for EmbeddingProviderConfig {
             batch_size: DEFAULT_FASTEMBED_BATCH_SIZE,
             max_chars: DEFAULT_FASTEMBED_MAX_CHARS,
             normalize: true,
+            device: EmbeddingDevice::Cpu,
         }
     }
 }
@@ -131,9 +148,25 @@ impl FastEmbedder {
     pub fn new(config: EmbeddingProviderConfig) -> Result<Self> {
         let model = config.model.clone();
         let model_id = model.to_string();
-        let init = InitOptions::new(model);
-        let embedder =
-            TextEmbedding::try_new(init).context("Failed to initialize fastembed model")?;
+        let providers = execution_providers(config.device);
+        let embedder = if providers.is_empty() {
+            TextEmbedding::try_new(InitOptions::new(model))
+                .context("Failed to initialize fastembed model")?
+        } else {
+            let init = InitOptions::new(model.clone()).with_execution_providers(providers);
+            match TextEmbedding::try_new(init) {
+                Ok(embedder) => embedder,
+                Err(err) => {
+                    eprintln!(
+                        "Warning: {} embedding backend unavailable ({}); falling back to CPU",
+                        config.device.as_str(),
+                        err
+                    );
+                    TextEmbedding::try_new(InitOptions::new(model))
+                        .context("Failed to initialize fastembed model")?
+                }
+            }
+        };
 
         Ok(Self {
             embedder,
@@ -177,6 +210,46 @@ impl EmbeddingProvider for FastEmbedder {
     }
 }
 
+/// Execution providers to try for `device`; empty means plain CPU.
+///
+/// `auto` registers every accelerator compiled into this build and lets ONNX
+/// Runtime skip the ones that fail. An explicit device fails loudly so the
+/// caller can warn before retrying on CPU.
+#[cfg(not(all(target_os = "macos", target_arch = "x86_64")))]
+fn execution_providers(device: EmbeddingDevice) -> Vec<ExecutionProviderDispatch> {
+    let providers: Vec<ExecutionProviderDispatch> = match device {
+        EmbeddingDevice::Cpu => Vec::new(),
+        EmbeddingDevice::Auto => vec![
+            #[cfg(feature = "cuda")]
+            ort::ep::CUDA::default().build(),
+            #[cfg(feature = "coreml")]
+            ort::ep::CoreML::default().build(),
+            #[cfg(feature = "directml")]
+            ort::ep::DirectML::default().build(),
+        ],
+        EmbeddingDevice::Cuda => vec![
+            #[cfg(feature = "cuda")]
+            ort::ep::CUDA::default().build().error_on_failure(),
+        ],
+        EmbeddingDevice::Coreml => vec![
+            #[cfg(feature = "coreml")]
+            ort::ep::CoreML::default().build().error_on_failure(),
+        ],
+        EmbeddingDevice::Directml => vec![
+            #[cfg(feature = "directml")]
+            ort::ep::DirectML::default().build().error_on_failure(),
+        ],
+    };
+    if providers.is_empty() && !matches!(device, EmbeddingDevice::Cpu | EmbeddingDevice::Auto) {
+        eprintln!(
+            "Warning: this cgrep build has no {} support (rebuild with `--features {}`); using CPU",
+            device.as_str(),
+            device.as_str()
+        );
+    }
+    providers
+}
+
 /// FastEmbed provider stub for macOS x86_64 (fastembed backend unavailable).
 #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
 pub struct FastEmbedder;
@@ -422,6 +495,20 @@ fn parse_usize_env(name: &str, default: usize) -> Result<usize> {
     }
 }
 
+fn parse_device_env(default: EmbeddingDevice) -> Result<EmbeddingDevice> {
+    match env::var("FASTEMBED_DEVICE") {
+        Ok(raw) if raw.trim().is_empty() => Ok(default),
+        Ok(raw) => EmbeddingDevice::parse(&raw).with_context(|| {
+            format!(
+                "Invalid FASTEMBED_DEVICE value: {} (expected auto, cpu, cuda, coreml, directml)",
+                raw.trim()
+            )
+        }),
+        Err(env::VarError::NotPresent) => Ok(default),
+        Err(err) => Err(err).context("Failed to read FASTEMBED_DEVICE"),
+    }
+}
+
 fn parse_bool_env(name: &str, default: bool) -> Result<bool> {
     match env::var(name) {
         Ok(raw) => {
@@ -14,6 +14,9 @@ use std::path::{Path, PathBuf};
 /// Default embedding dimension for sentence-transformers/all-MiniLM-L6-v2.
 pub const DEFAULT_EMBEDDING_DIM: usize = 384;
 
+/// `symbol_kind` of line-window chunks embedded for files without symbols.
+pub const CHUNK_SYMBOL_KIND: &str = "chunk";
+
 /// Represents a symbol embedding with its metadata.
 #[derive(Debug, Clone)]
 pub struct SymbolEmbedding {
@@ -210,6 +213,26 @@ impl EmbeddingStorage {
         Ok(self.get_meta("unit")?.as_deref() == Some("symbol"))
     }
 
+    /// Runs `f` inside one transaction, so every file update it makes is
+    /// committed together (or not at all if `f` fails).
+    pub fn with_batch<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.conn
+            .execute_batch("BEGIN")
+            .context("Failed to begin embedding batch")?;
+        match f(self) {
+            Ok(value) => {
+                self.conn
+                    .execute_batch("COMMIT")
+                    .context("Failed to commit embedding batch")?;
+                Ok(value)
+            }
+            Err(err) => {
+                let _ = self.conn.execute_batch("ROLLBACK");
+                Err(err)
+            }
+        }
+    }
+
     /// Replaces all embeddings for a file in a single transaction.
     ///
     /// This deletes any existing symbols for the file and then inserts the new symbols.
@@ -220,7 +243,8 @@ impl EmbeddingStorage {
         last_modified: i64,
         symbols: &[SymbolEmbeddingInput<'_>],
     ) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        // A savepoint nests inside `with_batch` and acts as a transaction otherwise.
+        let tx = self.conn.savepoint()?;
 
         tx.execute(
             "DELETE FROM symbol_embeddings WHERE path = ?1",
@@ -287,7 +311,7 @@ impl EmbeddingStorage {
         symbol_ids: &[String],
         symbols: &[SymbolEmbeddingInput<'_>],
     ) -> Result<()> {
-        let tx = self.conn.transaction()?;
+        let tx = self.conn.savepoint()?;
 
         if symbol_ids.is_empty() {
             tx.execute(
@@ -697,6 +721,13 @@ impl EmbeddingStorage {
         Ok(())
     }
 
+    /// Removes a metadata key.
+    pub fn delete_meta(&self, key: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM meta WHERE key = ?1", params![key])?;
+        Ok(())
+    }
+
     /// Sets metadata value only if key doesn't exist.
     #[allow(dead_code)]
     fn set_meta_if_absent(&self, key: &str, value: &str) -> Result<()> {
@@ -952,4 +983,26 @@ mod tests {
         let remaining = storage.get_symbols_for_path("a.rs").unwrap();
         assert!(remaining.is_empty());
     }
+
+    #[test]
+    fn test_batch_commits_together_or_rolls_back() {
+        let dir = tempdir().unwrap();
+        let mut storage = EmbeddingStorage::open(dir.path().join("test.sqlite")).unwrap();
+
+        storage
+            .with_batch(|storage| {
+                storage.replace_file_symbols("a.rs", "ha", 1000, &[])?;
+                storage.sync_file_symbols("b.rs", "hb", 1000, &["b1".to_string()], &[])
+            })
+            .unwrap();
+        assert!(!storage.file_needs_update("a.rs", "ha").unwrap());
+        assert!(!storage.file_needs_update("b.rs", "hb").unwrap());
+
+        let failed: Result<()> = storage.with_batch(|storage| {
+            storage.replace_file_symbols("c.rs", "hc", 1000, &[])?;
+            anyhow::bail!("provider failed")
+        });
+        assert!(failed.is_err());
+        assert!(storage.file_needs_update("c.rs", "hc").unwrap());
+    }
 }
@@ -0,0 +1,308 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Text decoding for files that are not UTF-8.
+//!
+//! Valid UTF-8 is used as-is. Otherwise a byte-order mark decides, and
+//! without one chardetng guesses the legacy encoding (Shift_JIS, EUC-JP,
+//! EUC-KR, GBK, windows-1252, ...) the way browsers do for unlabeled pages;
+//! encoding_rs then decodes with it. Files with NUL bytes and no UTF-16 BOM
+//! are binary.
+//!
+//! Decoded text is normalized the way editors count lines: a leading BOM is
+//! dropped and CRLF or lone CR line breaks become LF, so line and column
+//! numbers agree between scan mode, the index, and the file as opened in an
+//! editor. The original convention is kept alongside the text.
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::Path;
+
+/// Line break convention of a file before normalization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+    Cr,
+    /// More than one kind of line break.
+    Mixed,
+}
+
+impl LineEnding {
+    pub fn is_lf(&self) -> bool {
+        *self == LineEnding::Lf
+    }
+}
+
+/// Text decoded from file bytes, with LF line breaks and no BOM.
+#[derive(Debug)]
+pub struct Decoded<'a> {
+    pub text: Cow<'a, str>,
+    /// WHATWG label of the source encoding, `None` for UTF-8.
+    pub encoding: Option<&'static str>,
+    pub line_ending: LineEnding,
+    /// Whether the bytes started with a byte-order mark.
+    pub bom: bool,
+}
+
+impl<'a> Decoded<'a> {
+    fn new(text: Cow<'a, str>, encoding: &'static Encoding, bom: bool) -> Self {
+        let (text, line_ending) = normalize_line_endings(text);
+        Self {
+            text,
+            encoding: (encoding != UTF_8).then(|| encoding.name()),
+            line_ending,
+            bom,
+        }
+    }
+
+    pub fn into_owned(self) -> Decoded<'static> {
+        Decoded {
+            text: Cow::Owned(self.text.into_owned()),
+            encoding: self.encoding,
+            line_ending: self.line_ending,
+            bom: self.bom,
+        }
+    }
+}
+
+/// Decode `bytes` as text, detecting the encoding. `None` for binary content.
+pub fn decode_text(bytes: &[u8]) -> Option<Decoded<'_>> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let body = &bytes[bom_len..];
+        let text = if encoding == UTF_8 {
+            Cow::Borrowed(std::str::from_utf8(body).ok()?)
+        } else {
+            encoding.decode_without_bom_handling(body).0
+        };
+        return Some(Decoded::new(text, encoding, true));
+    }
+    if bytes.contains(&0) {
+        return None;
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Some(Decoded::new(Cow::Borrowed(text), UTF_8, false));
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, false);
+    Some(decode_as(bytes, encoding))
+}
+
+/// Decode `bytes` with a previously detected encoding label, falling back
+/// to detection when the label is unknown. `None` for binary content.
+pub fn decode_with_label<'a>(bytes: &'a [u8], label: Option<&str>) -> Option<Decoded<'a>> {
+    match label.and_then(|label| Encoding::for_label(label.as_bytes())) {
+        Some(encoding) if encoding != UTF_8 => Some(decode_as(bytes, encoding)),
+        _ => decode_text(bytes),
+    }
+}
+
+/// Read and decode a file. `Ok(None)` for binary content.
+pub fn read_text_file(path: &Path) -> std::io::Result<Option<Decoded<'static>>> {
+    let bytes = std::fs::read(path)?;
+    Ok(decode_text(&bytes).map(Decoded::into_owned))
+}
+
+/// On-disk byte offset where each line of the normalized text starts, for
+/// UTF-8 `bytes` (`bom` as reported by [`decode_text`]).
+///
+/// Lines break at LF, CRLF, and lone CR, as in [`Decoded::text`]; a trailing
+/// break yields a final offset at the end of the file.
+pub fn line_starts(bytes: &[u8], bom: bool) -> Vec<usize> {
+    let mut starts = vec![if bom { 3 } else { 0 }];
+    let mut idx = starts[0];
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\r' if bytes.get(idx + 1) == Some(&b'\n') => idx += 1,
+            b'\r' | b'\n' => {}
+            _ => {
+                idx += 1;
+                continue;
+            }
+        }
+        idx += 1;
+        starts.push(idx);
+    }
+    starts
+}
+
+/// Maps byte offsets in a UTF-8 file's normalized text ([`Decoded::text`])
+/// to offsets into the file on disk.
+#[derive(Debug, Clone)]
+pub struct DiskOffsets {
+    /// Where each line starts in the normalized text.
+    text_starts: Vec<usize>,
+    /// Where the same lines start on disk.
+    disk_starts: Vec<usize>,
+}
+
+impl DiskOffsets {
+    /// `None` for binary content and for files that are not UTF-8, whose
+    /// decoded offsets do not address the file's bytes.
+    pub fn new(bytes: &[u8]) -> Option<Self> {
+        let decoded = decode_text(bytes)?;
+        if decoded.encoding.is_some() {
+            return None;
+        }
+        let text_starts = std::iter::once(0)
+            .chain(decoded.text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Some(Self {
+            text_starts,
+            disk_starts: line_starts(bytes, decoded.bom),
+        })
+    }
+
+    /// On-disk offset of byte `offset` of the normalized text.
+    pub fn map(&self, offset: usize) -> Option<usize> {
+        let line = self
+            .text_starts
+            .partition_point(|start| *start <= offset)
+            .checked_sub(1)?;
+        Some(self.disk_starts.get(line)? + offset - self.text_starts[line])
+    }
+}
+
+fn decode_as<'a>(bytes: &'a [u8], encoding: &'static Encoding) -> Decoded<'a> {
+    let bom = Encoding::for_bom(bytes).is_some();
+    let (text, _) = encoding.decode_with_bom_removal(bytes);
+    Decoded::new(text, encoding, bom)
+}
+
+/// Rewrite CRLF and lone CR as LF, reporting which breaks were present.
+fn normalize_line_endings(text: Cow<'_, str>) -> (Cow<'_, str>, LineEnding) {
+    if !text.contains('\r') {
+        return (text, LineEnding::Lf);
+    }
+    let bytes = text.as_bytes();
+    let (mut lf, mut crlf, mut cr) = (false, false, false);
+    for (idx, &byte) in bytes.iter().enumerate() {
+        match byte {
+            b'\r' if bytes.get(idx + 1) == Some(&b'\n') => crlf = true,
+            b'\r' => cr = true,
+            b'\n' if idx == 0 || bytes[idx - 1] != b'\r' => lf = true,
+            _ => {}
+        }
+    }
+    let line_ending = match (lf, crlf, cr) {
+        (false, true, false) => LineEnding::Crlf,
+        (false, false, true) => LineEnding::Cr,
+        _ => LineEnding::Mixed,
+    };
+    let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
+    (Cow::Owned(normalized), line_ending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{BIG5, EUC_JP, EUC_KR, GBK, SHIFT_JIS, WINDOWS_1251, WINDOWS_1252};
+
+    fn detect(text: &str, encoding: &'static Encoding) -> Option<&'static str> {
+        let (bytes, _, had_errors) = encoding.encode(text);
+        assert!(!had_errors);
+        let decoded = decode_text(&bytes).expect("text");
+        assert_eq!(decoded.text, text);
+        decoded.encoding
+    }
+
+    #[test]
+    fn legacy_encodings_are_detected_and_transcoded() {
+        assert_eq!(detect("fn main() {}\n", UTF_8), None);
+        assert_eq!(
+            detect("// 設定ファイルを読み込む\nfn load() {}\n", SHIFT_JIS),
+            Some("Shift_JIS")
+        );
+        assert_eq!(
+            detect("// 設定ファイルを読み込む\nfn load() {}\n", EUC_JP),
+            Some("EUC-JP")
+        );
+        assert_eq!(
+            detect("// 读取配置文件并返回结果\nfn load() {}\n", GBK),
+            Some("GBK")
+        );
+        assert_eq!(
+            detect("// 설정 파일을 읽습니다\nfn load() {}\n", EUC_KR),
+            Some("EUC-KR")
+        );
+        assert_eq!(
+            detect("# café naïve façade\nx = 1\n", WINDOWS_1252),
+            Some("windows-1252")
+        );
+        assert_eq!(
+            detect("// 讀取設定檔並傳回結果\nfn load() {}\n", BIG5),
+            Some("Big5")
+        );
+        assert_eq!(
+            detect("// Прочитать файл настроек\nfn load() {}\n", WINDOWS_1251),
+            Some("windows-1251")
+        );
+    }
+
+    #[test]
+    fn utf16_needs_a_bom_and_nul_bytes_mean_binary() {
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend("hi\n".encode_utf16().flat_map(u16::to_le_bytes));
+        let decoded = decode_text(&utf16).expect("text");
+        assert_eq!(decoded.text, "hi\n");
+        assert_eq!(decoded.encoding, Some("UTF-16LE"));
+
+        assert!(decode_text(b"ELF\0\x01\x02").is_none());
+    }
+
+    #[test]
+    fn bom_and_line_breaks_are_normalized_like_editors_count_lines() {
+        let decoded = decode_text(b"\xEF\xBB\xBFfn a() {}\r\nfn b() {}\r\n").expect("text");
+        assert_eq!(decoded.text, "fn a() {}\nfn b() {}\n");
+        assert_eq!(decoded.encoding, None);
+        assert_eq!(decoded.line_ending, LineEnding::Crlf);
+        assert!(decoded.bom);
+
+        let decoded = decode_text(b"a\rb\rc").expect("text");
+        assert_eq!(decoded.text.lines().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(decoded.line_ending, LineEnding::Cr);
+
+        let decoded = decode_text(b"a\r\nb\nc\r").expect("text");
+        assert_eq!(decoded.text, "a\nb\nc\n");
+        assert_eq!(decoded.line_ending, LineEnding::Mixed);
+
+        let decoded = decode_text(b"a\nb\n").expect("text");
+        assert!(matches!(decoded.text, Cow::Borrowed(_)));
+        assert_eq!(decoded.line_ending, LineEnding::Lf);
+        assert!(!decoded.bom);
+    }
+
+    #[test]
+    fn line_starts_point_into_the_original_bytes() {
+        let bytes = b"\xEF\xBB\xBFab\r\ncd\ref\ng";
+        let decoded = decode_text(bytes).expect("text");
+        let starts = line_starts(bytes, decoded.bom);
+        assert_eq!(starts, [3, 7, 10, 13]);
+        for (line, start) in decoded.text.lines().zip(&starts) {
+            assert_eq!(&bytes[*start..*start + line.len()], line.as_bytes());
+        }
+        assert_eq!(line_starts(b"a\n", false), [0, 2]);
+    }
+
+    #[test]
+    fn disk_offsets_skip_boms_and_crlf_breaks() {
+        let bytes = b"\xEF\xBB\xBFfn a() {}\r\nfn b() {}\r\n";
+        let decoded = decode_text(bytes).expect("text");
+        let offsets = DiskOffsets::new(bytes).expect("utf-8");
+        let start = decoded.text.find("fn b").expect("fn b");
+        let end = start + "fn b() {}".len();
+        let (start, end) = (
+            offsets.map(start).expect("start"),
+            offsets.map(end).expect("end"),
+        );
+        assert_eq!(&bytes[start..end], b"fn b() {}");
+
+        let (latin1, _, _) = WINDOWS_1252.encode("caf\u{e9} = 1\n");
+        assert!(DiskOffsets::new(&latin1).is_none());
+    }
+}
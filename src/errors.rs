@@ -71,6 +71,79 @@ impl fmt::Display for UnsupportedLanguageError {
 
 impl std::error::Error for UnsupportedLanguageError {}
 
+/// Error indicating another process holds the index write lock
+#[derive(Debug)]
+pub struct IndexLockedError {
+    pub lock_path: String,
+    pub pid: Option<u32>,
+    pub command: Option<String>,
+    pub acquired_at_ms: Option<u64>,
+}
+
+impl fmt::Display for IndexLockedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let holder = match (self.pid, self.command.as_deref()) {
+            (Some(pid), Some(command)) => format!("pid {} (cgrep {})", pid, command),
+            (Some(pid), None) => format!("pid {}", pid),
+            _ => "another process".to_string(),
+        };
+        write!(
+            f,
+            "Index is locked by {}\n\
+             Lock file: {}{}\n\n\
+             Suggestions:\n\
+             - Wait for the other run to finish: cgrep index --wait\n\
+             - Check background/daemon activity: cgrep status\n\
+             - If no cgrep process is running, delete the lock file",
+            holder,
+            self.lock_path,
+            self.acquired_at_ms
+                .map(|ms| format!("\nAcquired at (unix ms): {}", ms))
+                .unwrap_or_default()
+        )
+    }
+}
+
+impl std::error::Error for IndexLockedError {}
+
+/// Error indicating the index was written by a cgrep newer than this binary
+#[derive(Debug)]
+pub struct IndexVersionError {
+    pub index_path: String,
+    /// Refused operation: "read" or "update".
+    pub operation: &'static str,
+    pub written_by: String,
+    /// Index format the index was written in.
+    pub format: u32,
+    /// Oldest format a binary must support for `operation`.
+    pub required: u32,
+    /// Newest format this binary supports.
+    pub supported: u32,
+}
+
+impl fmt::Display for IndexVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Index at '{}' was written by cgrep {} (index format v{})\n\
+             To {} it, cgrep must support index format v{} or newer; this cgrep {} supports v{}\n\n\
+             Suggestions:\n\
+             - Upgrade cgrep to {} or newer\n\
+             - Or rebuild the index with this version: cgrep index --force",
+            self.index_path,
+            self.written_by,
+            self.format,
+            self.operation,
+            self.required,
+            env!("CARGO_PKG_VERSION"),
+            self.supported,
+            self.written_by
+        )
+    }
+}
+
+impl std::error::Error for IndexVersionError {}
+
 /// Helper functions for creating helpful error messages
 pub mod suggestions {
     /// Get a formatted list of supported languages
@@ -16,19 +16,7 @@ pub struct CompiledGlob {
 impl CompiledGlob {
     /// Compile a glob pattern to a regex
     pub fn new(pattern: &str) -> Option<Self> {
-        // Handle glob patterns properly:
-        // - `**` matches zero or more path segments
-        // - `*` matches any characters except `/`
-        let regex_pattern = pattern
-            .replace(".", "\\.")
-            .replace("**/", "{{DOUBLESTARSLASH}}") // Match zero or more dirs
-            .replace("/**", "{{SLASHDOUBLESTAR}}") // Match zero or more suffix
-            .replace("**", ".*") // Standalone ** matches anything
-            .replace("*", "[^/]*")
-            .replace("{{DOUBLESTARSLASH}}", "(.*/)?")
-            .replace("{{SLASHDOUBLESTAR}}", "(/.*)?");
-
-        Regex::new(&format!("(?i){}", regex_pattern))
+        Regex::new(&format!("(?i){}", glob_to_regex(pattern)))
             .ok()
             .map(|regex| Self { regex })
     }
@@ -39,6 +27,38 @@ impl CompiledGlob {
     }
 }
 
+/// Translate a glob into an (unanchored) regex:
+/// - `**/` matches zero or more leading directories, `/**` any suffix
+/// - a standalone `**` matches anything
+/// - `*` matches any characters except `/`, `?` one such character
+/// - everything else is literal
+fn glob_to_regex(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut out = String::with_capacity(pattern.len() * 2);
+    let mut idx = 0;
+    while idx < chars.len() {
+        let rest = &chars[idx..];
+        if rest.starts_with(&['*', '*', '/']) {
+            out.push_str("(.*/)?");
+            idx += 3;
+        } else if rest.starts_with(&['/', '*', '*']) && rest.get(3) != Some(&'/') {
+            out.push_str("(/.*)?");
+            idx += 3;
+        } else if rest.starts_with(&['*', '*']) {
+            out.push_str(".*");
+            idx += 2;
+        } else {
+            match rest[0] {
+                '*' => out.push_str("[^/]*"),
+                '?' => out.push_str("[^/]"),
+                ch => out.push_str(&regex::escape(ch.encode_utf8(&mut [0; 4]))),
+            }
+            idx += 1;
+        }
+    }
+    out
+}
+
 /// Check if file matches the given type filter
 pub fn matches_file_type(path: &str, file_type: Option<&str>) -> bool {
     let Some(filter) = file_type else { return true };
@@ -71,6 +91,54 @@ pub fn matches_file_type(path: &str, file_type: Option<&str>) -> bool {
     }
 }
 
+/// Language names recognized in queries, mapped to `matches_file_type` filters.
+///
+/// "go" and "c" are left out because they are common English words.
+const QUERY_LANGUAGE_WORDS: &[(&str, &str)] = &[
+    ("rust", "rust"),
+    ("python", "python"),
+    ("typescript", "typescript"),
+    ("javascript", "javascript"),
+    ("golang", "go"),
+    ("java", "java"),
+    ("kotlin", "kotlin"),
+    ("ruby", "ruby"),
+    ("php", "php"),
+    ("swift", "swift"),
+    ("scala", "scala"),
+    ("lua", "lua"),
+    ("bash", "shell"),
+    ("c++", "cpp"),
+    ("cpp", "cpp"),
+];
+
+/// Infer a language filter from a natural-language query such as
+/// "python retry decorator".
+///
+/// Returns `None` for single-word queries and when several languages are named.
+pub fn language_from_query(query: &str) -> Option<&'static str> {
+    let words: Vec<String> = query
+        .split(|c: char| !(c.is_alphanumeric() || c == '+' || c == '_'))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < 2 {
+        return None;
+    }
+
+    let mut found: Option<&'static str> = None;
+    for word in &words {
+        let Some((_, language)) = QUERY_LANGUAGE_WORDS.iter().find(|(name, _)| name == word) else {
+            continue;
+        };
+        match found {
+            Some(existing) if existing != *language => return None,
+            _ => found = Some(language),
+        }
+    }
+    found
+}
+
 /// Check if file matches glob pattern using precompiled regex
 pub fn matches_glob_compiled(path: &str, glob: Option<&CompiledGlob>) -> bool {
     match glob {
@@ -85,19 +153,7 @@ pub fn matches_glob(path: &str, glob_pattern: Option<&str>) -> bool {
     let Some(pattern) = glob_pattern else {
         return true;
     };
-
-    let regex_pattern = pattern
-        .replace(".", "\\.")
-        .replace("**/", "{{DOUBLESTARSLASH}}")
-        .replace("/**", "{{SLASHDOUBLESTAR}}")
-        .replace("**", ".*")
-        .replace("*", "[^/]*")
-        .replace("{{DOUBLESTARSLASH}}", "(.*/)?")
-        .replace("{{SLASHDOUBLESTAR}}", "(/.*)?");
-
-    Regex::new(&format!("(?i){}", regex_pattern))
-        .map(|re| re.is_match(path))
-        .unwrap_or(false)
+    CompiledGlob::new(pattern).is_some_and(|glob| glob.is_match(path))
 }
 
 /// Check if file should be excluded using precompiled glob
@@ -126,6 +182,20 @@ mod tests {
         assert!(matches_file_type("src/main.rs", None));
     }
 
+    #[test]
+    fn test_language_from_query() {
+        assert_eq!(
+            language_from_query("python retry decorator"),
+            Some("python")
+        );
+        assert_eq!(language_from_query("Retry logic in Rust"), Some("rust"));
+        assert_eq!(language_from_query("c++ move semantics"), Some("cpp"));
+        assert_eq!(language_from_query("python"), None);
+        assert_eq!(language_from_query("port python helper to rust"), None);
+        assert_eq!(language_from_query("go to definition"), None);
+        assert_eq!(language_from_query("javascript_bundle size"), None);
+    }
+
     #[test]
     fn test_compiled_glob() {
         let glob = CompiledGlob::new("src/**/*.rs").unwrap();
@@ -134,6 +204,17 @@ mod tests {
         assert!(!glob.is_match("tests/main.rs"));
     }
 
+    #[test]
+    fn test_glob_punctuation_is_literal() {
+        let glob = CompiledGlob::new("src/(legacy)/file?.rs").unwrap();
+        assert!(glob.is_match("src/(legacy)/file1.rs"));
+        assert!(!glob.is_match("src/legacy/file1.rs"));
+        assert!(!glob.is_match("src/(legacy)/fil.rs"));
+        assert!(matches_glob("c++/a+b.h", Some("c++/**")));
+        assert!(matches_glob("vendor/[x]/lib.rs", Some("vendor/[x]/*.rs")));
+        assert!(!matches_glob("vendor/x/lib.rs", Some("vendor/[x]/*.rs")));
+    }
+
     #[test]
     fn test_should_exclude() {
         assert!(should_exclude("target/debug/main", Some("target/**")));
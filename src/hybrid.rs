@@ -366,6 +366,49 @@ impl HybridSearcher {
     }
 }
 
+/// Collapse results whose line ranges overlap within the same file.
+///
+/// Nested symbols (a method and its class) and neighbouring chunks often match
+/// together. Input must be sorted best-first; the best result of each
+/// overlapping group is kept and its range widened to cover the others.
+pub fn merge_overlapping_results(results: Vec<HybridResult>) -> Vec<HybridResult> {
+    let mut merged: Vec<HybridResult> = Vec::with_capacity(results.len());
+    let mut kept_by_path: HashMap<String, Vec<usize>> = HashMap::new();
+
+    for result in results {
+        let Some((start, end)) = result_range(&result) else {
+            merged.push(result);
+            continue;
+        };
+        let kept = kept_by_path.entry(result.path.clone()).or_default();
+        let overlapping = kept.iter().copied().find(|&idx| {
+            result_range(&merged[idx])
+                .is_some_and(|(kept_start, kept_end)| start <= kept_end && kept_start <= end)
+        });
+        match overlapping {
+            Some(idx) => {
+                let best = &mut merged[idx];
+                let (kept_start, kept_end) = result_range(best).unwrap_or((start, end));
+                best.chunk_start = Some(kept_start.min(start));
+                best.chunk_end = Some(kept_end.max(end));
+            }
+            None => {
+                kept.push(merged.len());
+                merged.push(result);
+            }
+        }
+    }
+
+    merged
+}
+
+fn result_range(result: &HybridResult) -> Option<(u32, u32)> {
+    let line = result.line.map(|l| l as u32);
+    let start = result.chunk_start.or(line)?;
+    let end = result.chunk_end.or(line).unwrap_or(start);
+    Some((start, end.max(start)))
+}
+
 /// Context pack for AI agent consumption
 #[derive(Debug, Clone, Serialize)]
 pub struct ContextPack {
@@ -486,6 +529,45 @@ mod tests {
     use crate::embedding::SymbolEmbeddingInput;
     use tempfile::tempdir;
 
+    fn ranged(path: &str, score: f32, start: u32, end: u32) -> HybridResult {
+        HybridResult {
+            path: path.to_string(),
+            score,
+            text_score: 0.0,
+            vector_score: score,
+            text_norm: 0.0,
+            vector_norm: score,
+            snippet: String::new(),
+            line: Some(start as usize),
+            chunk_start: Some(start),
+            chunk_end: Some(end),
+            result_id: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_overlapping_results_keeps_best_per_range() {
+        let merged = merge_overlapping_results(vec![
+            ranged("a.rs", 0.9, 10, 20),
+            ranged("b.rs", 0.8, 10, 20),
+            ranged("a.rs", 0.7, 1, 40),
+            ranged("a.rs", 0.6, 50, 60),
+            ranged("a.rs", 0.5, 45, 55),
+        ]);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].score, 0.9);
+        assert_eq!(
+            (merged[0].chunk_start, merged[0].chunk_end),
+            (Some(1), Some(40))
+        );
+        assert_eq!(merged[0].line, Some(10));
+        assert_eq!(merged[1].path, "b.rs");
+        assert_eq!(
+            (merged[2].chunk_start, merged[2].chunk_end),
+            (Some(45), Some(60))
+        );
+    }
+
     #[test]
     fn test_search_mode_parsing() {
         assert_eq!(
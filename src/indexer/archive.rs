@@ -0,0 +1,451 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Opt-in indexing of text files inside archives (`[index.archives]`).
+//!
+//! Entries are addressed by virtual paths: the archive path, `!/`, then the
+//! entry path, repeated for nested archives (`libs/a.zip!/b.jar!/x/Y.java`).
+//! Index documents for entries keep the archive itself in `path_exact`, so
+//! re-indexing or deleting the archive replaces all of its entries at once.
+//!
+//! Compiled `.class` entries are indexed with the matching `.java` source from
+//! a sibling `<name>-sources.jar` when one exists; that sources jar is then not
+//! indexed on its own.
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use crate::indexer::scanner::{detect_language, is_indexable_extension};
+use cgrep::config::Config;
+use cgrep::encoding;
+
+/// Separator between an archive path and the entry path inside it.
+pub(crate) const ENTRY_SEPARATOR: &str = "!/";
+/// Upper bound on indexed entries per top-level archive.
+const MAX_ENTRIES_PER_ARCHIVE: usize = 20_000;
+/// Nested archives are buffered in memory; larger ones are skipped.
+const MAX_NESTED_ARCHIVE_BYTES: u64 = 64 * 1024 * 1024;
+const SOURCES_JAR_SUFFIX: &str = "-sources.jar";
+
+/// Archive settings persisted with the index options.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub(crate) struct ArchiveOptions {
+    pub enabled: bool,
+    pub extensions: Vec<String>,
+    pub max_depth: usize,
+    pub max_entry_bytes: u64,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        let config = Config::default();
+        Self::from_config(&config)
+    }
+}
+
+impl ArchiveOptions {
+    pub(crate) fn from_config(config: &Config) -> Self {
+        let index = config.index();
+        Self {
+            enabled: index.archives.enabled(),
+            extensions: index.archives.extensions(),
+            max_depth: index.archives.max_depth(),
+            max_entry_bytes: index.archives.max_entry_bytes(index.max_file_size()),
+        }
+    }
+
+    /// Whether `path` is an archive that should be opened.
+    pub(crate) fn matches(&self, path: &Path) -> bool {
+        self.enabled && self.kind_of(path).is_some()
+    }
+
+    /// Suffixes the scanner should list in addition to source files.
+    pub(crate) fn scan_extensions(&self) -> Vec<String> {
+        if self.enabled {
+            self.extensions.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn kind_of(&self, path: &Path) -> Option<ArchiveKind> {
+        let name = path.file_name()?.to_str()?;
+        archive_kind(name, &self.extensions)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// True when `name` ends with one of `extensions` (e.g. `tar.gz`).
+pub(crate) fn has_archive_suffix(name: &str, extensions: &[String]) -> bool {
+    archive_kind(name, extensions).is_some()
+}
+
+fn archive_kind(name: &str, extensions: &[String]) -> Option<ArchiveKind> {
+    let lower = name.to_ascii_lowercase();
+    let suffix = extensions.iter().find(|ext| {
+        lower.len() > ext.len() + 1
+            && lower.ends_with(ext.as_str())
+            && lower.as_bytes()[lower.len() - ext.len() - 1] == b'.'
+    })?;
+    Some(if suffix.ends_with("tar.gz") || suffix == "tgz" {
+        ArchiveKind::TarGz
+    } else if suffix == "tar" {
+        ArchiveKind::Tar
+    } else {
+        // jar, whl, war, nupkg, ... are all zip containers.
+        ArchiveKind::Zip
+    })
+}
+
+/// A text file found inside an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ArchiveEntry {
+    /// Path inside the archive, including `!/` hops for nested archives.
+    pub path: String,
+    pub text: String,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ArchiveContents {
+    pub hash: String,
+    pub entries: Vec<ArchiveEntry>,
+}
+
+/// Virtual path of an entry inside the archive at `archive_path`.
+pub(crate) fn virtual_path(archive_path: &str, entry_path: &str) -> String {
+    format!("{archive_path}{ENTRY_SEPARATOR}{entry_path}")
+}
+
+/// File on disk behind an indexed path: the outermost archive for an entry's
+/// virtual path, else the path itself.
+pub(crate) fn containing_file(path: &Path) -> PathBuf {
+    let text = path.to_string_lossy();
+    match text.split_once(ENTRY_SEPARATOR) {
+        Some((archive_path, _)) => PathBuf::from(archive_path),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Read the text entries of an archive (see module docs).
+pub(crate) fn read_archive(path: &Path, options: &ArchiveOptions) -> Result<ArchiveContents> {
+    let kind = options
+        .kind_of(path)
+        .with_context(|| format!("not an archive: {}", path.display()))?;
+    let bytes = std::fs::read(path)?;
+    let hash = blake3::hash(&bytes).to_hex().to_string();
+
+    if binary_jar_for_sources(path).is_some_and(|jar| jar.is_file()) {
+        return Ok(ArchiveContents {
+            hash,
+            entries: Vec::new(),
+        });
+    }
+
+    let sources = sources_jar_for(path)
+        .filter(|jar| jar.is_file())
+        .and_then(|jar| std::fs::read(jar).ok())
+        .map(|bytes| read_java_sources(&bytes, options.max_entry_bytes))
+        .unwrap_or_default();
+
+    let mut entries = Vec::new();
+    let walk = ArchiveWalk {
+        options,
+        sources: &sources,
+    };
+    walk.collect(kind, &bytes, "", 1, &mut entries)?;
+    Ok(ArchiveContents { hash, entries })
+}
+
+struct ArchiveWalk<'a> {
+    options: &'a ArchiveOptions,
+    /// `.java` sources keyed by path, used for top-level `.class` entries.
+    sources: &'a HashMap<String, String>,
+}
+
+impl ArchiveWalk<'_> {
+    fn collect(
+        &self,
+        kind: ArchiveKind,
+        bytes: &[u8],
+        prefix: &str,
+        depth: usize,
+        out: &mut Vec<ArchiveEntry>,
+    ) -> Result<()> {
+        for_each_entry(kind, bytes, |name, size, reader| {
+            if out.len() >= MAX_ENTRIES_PER_ARCHIVE {
+                return Ok(false);
+            }
+            let entry_path = format!("{prefix}{name}");
+
+            if let Some(nested) = archive_kind(name, &self.options.extensions) {
+                if depth < self.options.max_depth && size <= MAX_NESTED_ARCHIVE_BYTES {
+                    let data = read_limited(reader, size)?;
+                    let nested_prefix = format!("{entry_path}{ENTRY_SEPARATOR}");
+                    // A corrupt nested archive should not fail the outer one.
+                    let _ = self.collect(nested, &data, &nested_prefix, depth + 1, out);
+                }
+                return Ok(true);
+            }
+
+            let ext = extension_of(name);
+            if ext == "class" {
+                // Inner classes (`Y$Inner.class`) share the outer class's source.
+                if prefix.is_empty() && !name.contains('$') {
+                    if let Some(source) = self.sources.get(&class_source_path(name)) {
+                        out.push(ArchiveEntry {
+                            path: entry_path,
+                            text: source.clone(),
+                            language: Some("java".to_string()),
+                        });
+                    }
+                }
+                return Ok(true);
+            }
+
+            if !is_indexable_extension(ext) || size > self.options.max_entry_bytes {
+                return Ok(true);
+            }
+            if let Some(text) = decode_text(read_limited(reader, size)?) {
+                out.push(ArchiveEntry {
+                    path: entry_path,
+                    text,
+                    language: detect_language(ext),
+                });
+            }
+            Ok(true)
+        })
+    }
+}
+
+/// Visit regular-file entries; the callback returns `false` to stop early.
+fn for_each_entry<F>(kind: ArchiveKind, bytes: &[u8], mut visit: F) -> Result<()>
+where
+    F: FnMut(&str, u64, &mut dyn Read) -> Result<bool>,
+{
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+            for idx in 0..archive.len() {
+                let mut file = archive.by_index(idx)?;
+                if !file.is_file() {
+                    continue;
+                }
+                let name = normalize_entry_name(file.name());
+                let size = file.size();
+                if !visit(&name, size, &mut file)? {
+                    break;
+                }
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let reader: Box<dyn Read + '_> = if kind == ArchiveKind::TarGz {
+                Box::new(GzDecoder::new(bytes))
+            } else {
+                Box::new(bytes)
+            };
+            let mut archive = tar::Archive::new(reader);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = normalize_entry_name(&entry.path()?.to_string_lossy());
+                let size = entry.size();
+                if !visit(&name, size, &mut entry)? {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_java_sources(bytes: &[u8], max_entry_bytes: u64) -> HashMap<String, String> {
+    let mut sources = HashMap::new();
+    let _ = for_each_entry(ArchiveKind::Zip, bytes, |name, size, reader| {
+        if extension_of(name) == "java" && size <= max_entry_bytes {
+            if let Some(text) = decode_text(read_limited(reader, size)?) {
+                sources.insert(name.to_string(), text);
+            }
+        }
+        Ok(true)
+    });
+    sources
+}
+
+/// `com/x/Y.class` -> `com/x/Y.java`.
+fn class_source_path(class_path: &str) -> String {
+    let stem = class_path.strip_suffix(".class").unwrap_or(class_path);
+    format!("{stem}.java")
+}
+
+/// `foo.jar` -> `foo-sources.jar` (same directory).
+fn sources_jar_for(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    if name.ends_with(SOURCES_JAR_SUFFIX) {
+        return None;
+    }
+    let stem = name.strip_suffix(".jar")?;
+    Some(path.with_file_name(format!("{stem}{SOURCES_JAR_SUFFIX}")))
+}
+
+/// `foo-sources.jar` -> `foo.jar` (same directory).
+fn binary_jar_for_sources(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let stem = name.strip_suffix(SOURCES_JAR_SUFFIX)?;
+    Some(path.with_file_name(format!("{stem}.jar")))
+}
+
+fn normalize_entry_name(name: &str) -> String {
+    name.replace('\\', "/")
+        .trim_start_matches("./")
+        .trim_start_matches('/')
+        .to_string()
+}
+
+fn extension_of(name: &str) -> &str {
+    let file = name.rsplit('/').next().unwrap_or(name);
+    file.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("")
+}
+
+fn read_limited(reader: &mut dyn Read, size: u64) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(size.min(MAX_NESTED_ARCHIVE_BYTES) as usize);
+    reader
+        .take(size.min(MAX_NESTED_ARCHIVE_BYTES))
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn decode_text(bytes: Vec<u8>) -> Option<String> {
+    encoding::decode_text(&bytes).map(|decoded| decoded.text.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let file = std::fs::File::create(path).expect("create zip");
+        let mut zip = zip::ZipWriter::new(file);
+        for (name, data) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .expect("start entry");
+            zip.write_all(data).expect("write entry");
+        }
+        zip.finish().expect("finish zip");
+    }
+
+    fn enabled() -> ArchiveOptions {
+        ArchiveOptions {
+            enabled: true,
+            ..ArchiveOptions::default()
+        }
+    }
+
+    #[test]
+    fn archive_suffixes_match_case_insensitively() {
+        let options = enabled();
+        assert!(options.matches(Path::new("libs/foo.JAR")));
+        assert!(options.matches(Path::new("dist/pkg-1.0.tar.gz")));
+        assert!(options.matches(Path::new("dist/pkg-1.0-py3-none-any.whl")));
+        assert!(!options.matches(Path::new("src/zip.rs")));
+        assert!(!options.matches(Path::new("jar")));
+        assert!(!ArchiveOptions::default().matches(Path::new("libs/foo.jar")));
+    }
+
+    #[test]
+    fn class_entries_use_sibling_sources_jar() {
+        let dir = TempDir::new().expect("tempdir");
+        let jar = dir.path().join("foo.jar");
+        write_zip(
+            &jar,
+            &[
+                ("com/x/Y.class", b"\xCA\xFE\xBA\xBE\0\0"),
+                ("com/x/Y$Inner.class", b"\xCA\xFE\xBA\xBE\0\0"),
+                ("META-INF/notes.txt", b"archive_note_marker\n"),
+            ],
+        );
+        write_zip(
+            &dir.path().join("foo-sources.jar"),
+            &[("com/x/Y.java", b"class Y { void sourceMarker() {} }\n")],
+        );
+
+        let contents = read_archive(&jar, &enabled()).expect("read jar");
+        let paths: Vec<&str> = contents.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["com/x/Y.class", "META-INF/notes.txt"]);
+        assert_eq!(contents.entries[0].language.as_deref(), Some("java"));
+        assert!(contents.entries[0].text.contains("sourceMarker"));
+
+        let sources = read_archive(&dir.path().join("foo-sources.jar"), &enabled())
+            .expect("read sources jar");
+        assert!(sources.entries.is_empty());
+    }
+
+    #[test]
+    fn nested_archives_respect_max_depth() {
+        let dir = TempDir::new().expect("tempdir");
+        let inner = dir.path().join("inner.jar");
+        write_zip(&inner, &[("a/B.java", b"class B {}\n")]);
+        let inner_bytes = std::fs::read(&inner).expect("read inner");
+        let outer = dir.path().join("bundle.zip");
+        write_zip(
+            &outer,
+            &[
+                ("lib/inner.jar", &inner_bytes),
+                ("README.md", b"# bundle\n"),
+            ],
+        );
+
+        let shallow = read_archive(&outer, &enabled()).expect("read outer");
+        let paths: Vec<&str> = shallow.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["README.md"]);
+
+        let deep = ArchiveOptions {
+            max_depth: 2,
+            ..enabled()
+        };
+        let nested = read_archive(&outer, &deep).expect("read outer");
+        let paths: Vec<&str> = nested.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["lib/inner.jar!/a/B.java", "README.md"]);
+    }
+
+    #[test]
+    fn tar_gz_entries_are_read() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("pkg.tar.gz");
+        let file = std::fs::File::create(&path).expect("create tar.gz");
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        let data = b"def packaged_marker():\n    pass\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "./pkg/mod.py", &data[..])
+            .expect("append entry");
+        builder
+            .into_inner()
+            .expect("finish tar")
+            .finish()
+            .expect("finish gzip");
+
+        let contents = read_archive(&path, &enabled()).expect("read tar.gz");
+        assert_eq!(contents.entries.len(), 1);
+        assert_eq!(contents.entries[0].path, "pkg/mod.py");
+        assert_eq!(contents.entries[0].language.as_deref(), Some("python"));
+    }
+}
@@ -0,0 +1,321 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Branch-aware index overlays (`cgrep index --branch-aware`).
+//!
+//! The live index under `.cgrep` belongs to the branch recorded in
+//! `.cgrep/branch.json`. When an index run finds another branch checked out,
+//! the live index is parked under `.cgrep/branches/<branch>/` and the new
+//! branch starts from, in order: its own parked index, the base snapshot
+//! taken on the default branch at its merge-base, or a copy of the index
+//! just parked. The manifest-based incremental build then reindexes only
+//! the files that differ, which is the branch's delta.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::indexer::index::index_artifact_names;
+use crate::indexer::manifest;
+use crate::indexer::reuse::{copy_dir_recursive, copy_file};
+use cgrep::utils::INDEX_DIR;
+
+const STATE_FILE_NAME: &str = "branch.json";
+const BRANCHES_DIR_NAME: &str = "branches";
+const BASE_PREFIX: &str = "base-";
+/// Base snapshots kept; older ones are removed when a new one is taken.
+const MAX_BASE_SNAPSHOTS: usize = 2;
+
+/// The checkout an index was built for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Checkout {
+    /// Branch name, or `HEAD@<commit>` when detached.
+    pub branch: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    /// Merge-base of `head` with the default branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_base: Option<String>,
+}
+
+impl Checkout {
+    /// On the default branch itself (or a commit on it).
+    fn is_base(&self) -> bool {
+        self.head.is_some() && self.head == self.merge_base
+    }
+}
+
+fn state_path(root: &Path) -> PathBuf {
+    root.join(INDEX_DIR).join(STATE_FILE_NAME)
+}
+
+fn branches_dir(root: &Path) -> PathBuf {
+    root.join(INDEX_DIR).join(BRANCHES_DIR_NAME)
+}
+
+/// Whether a previous `--branch-aware` run opted this index in.
+pub(crate) fn enabled(root: &Path) -> bool {
+    state_path(root).is_file()
+}
+
+fn recorded_checkout(root: &Path) -> Option<Checkout> {
+    let raw = fs::read_to_string(state_path(root)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// True when the index is branch-aware and was built for another branch.
+pub(crate) fn checkout_changed(root: &Path) -> bool {
+    let Some(recorded) = recorded_checkout(root) else {
+        return false;
+    };
+    current_checkout(root).is_some_and(|current| current.branch != recorded.branch)
+}
+
+/// The checked-out branch of the repository containing `root`.
+pub(crate) fn current_checkout(root: &Path) -> Option<Checkout> {
+    let head = git_output(root, &["rev-parse", "--verify", "--quiet", "HEAD"]);
+    let branch = match git_output(root, &["symbolic-ref", "--quiet", "--short", "HEAD"]) {
+        Some(branch) => branch,
+        None => format!("HEAD@{}", &head.as_deref()?[..12]),
+    };
+    let merge_base = head.as_ref().and_then(|_| {
+        let default = default_branch(root)?;
+        git_output(root, &["merge-base", "HEAD", &default])
+    });
+    Some(Checkout {
+        branch,
+        head,
+        merge_base,
+    })
+}
+
+/// `origin/HEAD`'s target, else a local `main` or `master`.
+fn default_branch(root: &Path) -> Option<String> {
+    if let Some(remote) = git_output(
+        root,
+        &[
+            "symbolic-ref",
+            "--quiet",
+            "--short",
+            "refs/remotes/origin/HEAD",
+        ],
+    ) {
+        return Some(remote);
+    }
+    ["main", "master"].into_iter().find_map(|name| {
+        git_output(
+            root,
+            &[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("refs/heads/{name}"),
+            ],
+        )
+        .map(|_| name.to_string())
+    })
+}
+
+fn git_output(root: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Directory name for a branch: readable, with a hash so that names
+/// differing only in punctuation do not collide.
+fn branch_dir_name(branch: &str) -> String {
+    let slug: String = branch
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-') {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let hash = blake3::hash(branch.as_bytes()).to_hex();
+    format!("{}-{}", slug.trim_matches('-'), &hash[..8])
+}
+
+fn base_dir_name(commit: &str) -> String {
+    format!("{BASE_PREFIX}{}", &commit[..commit.len().min(12)])
+}
+
+/// Move the index artifacts from `from` into `to` (created if missing).
+fn move_index(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for name in index_artifact_names(from)? {
+        fs::rename(from.join(&name), to.join(&name))
+            .with_context(|| format!("Failed to move index entry {name}"))?;
+    }
+    Ok(())
+}
+
+fn copy_index(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for name in index_artifact_names(from)? {
+        let source = from.join(&name);
+        if source.is_dir() {
+            copy_dir_recursive(&source, &to.join(&name))?;
+        } else {
+            copy_file(&source, &to.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Swap in the index for the checked-out branch before an index run.
+/// Returns a note describing the switch, or `None` when the live index
+/// already belongs to this branch.
+pub(crate) fn prepare(root: &Path) -> Result<Option<String>> {
+    let Some(current) = current_checkout(root) else {
+        return Ok(None);
+    };
+    let Some(recorded) = recorded_checkout(root) else {
+        return Ok(None);
+    };
+    if recorded.branch == current.branch {
+        return Ok(None);
+    }
+
+    let live = root.join(INDEX_DIR);
+    let branches = branches_dir(root);
+    let parked = branches.join(branch_dir_name(&recorded.branch));
+    if parked.exists() {
+        fs::remove_dir_all(&parked)
+            .with_context(|| format!("Failed to clear {}", parked.display()))?;
+    }
+    move_index(&live, &parked)?;
+    manifest::atomic_write_bytes(
+        &parked.join(STATE_FILE_NAME),
+        &serde_json::to_vec_pretty(&recorded)?,
+    )?;
+
+    let own = branches.join(branch_dir_name(&current.branch));
+    let base = current
+        .merge_base
+        .as_deref()
+        .map(|commit| branches.join(base_dir_name(commit)))
+        .filter(|dir| dir.join("meta.json").is_file());
+    let note = if own.join("meta.json").is_file() {
+        move_index(&own, &live)?;
+        fs::remove_dir_all(&own).ok();
+        format!("restored the index parked for {}", current.branch)
+    } else if let Some(base) = base {
+        copy_index(&base, &live)?;
+        format!(
+            "started {} from the base index at {}",
+            current.branch,
+            current.merge_base.as_deref().map_or("", |c| &c[..12])
+        )
+    } else {
+        copy_index(&parked, &live)?;
+        format!(
+            "started {} from the index of {}",
+            current.branch, recorded.branch
+        )
+    };
+    Ok(Some(note))
+}
+
+/// Record the checkout the live index now reflects, and refresh the base
+/// snapshot when it is on the default branch.
+pub(crate) fn record(root: &Path) -> Result<()> {
+    let Some(current) = current_checkout(root) else {
+        return Ok(());
+    };
+    manifest::atomic_write_bytes(&state_path(root), &serde_json::to_vec_pretty(&current)?)?;
+    let Some(head) = current.head.as_deref().filter(|_| current.is_base()) else {
+        return Ok(());
+    };
+
+    let branches = branches_dir(root);
+    let base = branches.join(base_dir_name(head));
+    if !base.join("meta.json").is_file() {
+        let staging = branches.join(format!(".tmp-{}", std::process::id()));
+        fs::remove_dir_all(&staging).ok();
+        copy_index(&root.join(INDEX_DIR), &staging)?;
+        fs::remove_dir_all(&base).ok();
+        fs::rename(&staging, &base)
+            .with_context(|| format!("Failed to store base index {}", base.display()))?;
+    }
+    prune_base_snapshots(&branches, &base)
+}
+
+/// Keep the newest base snapshots, always including `keep`.
+fn prune_base_snapshots(branches: &Path, keep: &Path) -> Result<()> {
+    let mut bases: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(branches)?
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(BASE_PREFIX))
+        .filter(|entry| entry.path() != keep)
+        .map(|entry| {
+            let modified = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(std::time::UNIX_EPOCH);
+            (modified, entry.path())
+        })
+        .collect();
+    bases.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, stale) in bases.into_iter().skip(MAX_BASE_SNAPSHOTS - 1) {
+        fs::remove_dir_all(&stale).ok();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branch_dirs_are_safe_and_distinct() {
+        let feature = branch_dir_name("feature/login");
+        assert!(feature.starts_with("feature-login-"));
+        assert!(!feature.contains('/'));
+        assert_ne!(feature, branch_dir_name("feature-login"));
+        assert_eq!(base_dir_name("0123456789abcdef0123"), "base-0123456789ab");
+    }
+
+    #[test]
+    fn only_index_artifacts_are_moved() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let from = dir.path().join("from");
+        fs::create_dir_all(from.join("manifest")).expect("mkdir");
+        for name in [
+            "meta.json",
+            "metadata.json",
+            "0123456789abcdef0123456789abcdef.idx",
+            "status.json",
+            "index.lock",
+        ] {
+            fs::write(from.join(name), name).expect("write");
+        }
+        let to = dir.path().join("to");
+        move_index(&from, &to).expect("move");
+        let mut moved = index_artifact_names(&to).expect("entries");
+        moved.sort();
+        assert_eq!(
+            moved,
+            [
+                "0123456789abcdef0123456789abcdef.idx",
+                "manifest",
+                "meta.json",
+                "metadata.json"
+            ]
+        );
+        assert!(from.join("status.json").is_file());
+        assert!(from.join("index.lock").is_file());
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Per-file commit frequency ("churn") for ranking.
+//!
+//! Indexing records how many commits touched each file within
+//! [`CHURN_WINDOW_DAYS`] in the index metadata, counted during the same
+//! history walk that records [`super::git_history::FileHistory`]. Keyword ranking turns the
+//! counts into a small boost so files under active development rank above
+//! dormant or vendored code with similar text scores.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// History window counted by indexing.
+pub(crate) const CHURN_WINDOW_DAYS: u32 = 365;
+
+/// Churn scaled to `0.0..=1.0` by log commit count relative to the busiest
+/// file, keyed by path relative to `scope` (a subdirectory of the index
+/// root, given relative to it). Files outside `scope` are dropped.
+pub(crate) fn normalized_churn(
+    counts: &HashMap<String, u32>,
+    scope: &Path,
+) -> HashMap<String, f32> {
+    let Some(max) = counts.values().copied().max().filter(|max| *max > 0) else {
+        return HashMap::new();
+    };
+    let denominator = (1.0 + max as f32).ln();
+    counts
+        .iter()
+        .filter_map(|(path, count)| {
+            let rel = Path::new(path).strip_prefix(scope).ok()?;
+            let key = rel.to_string_lossy().replace('\\', "/");
+            Some((key, (1.0 + *count as f32).ln() / denominator))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn churn_is_log_scaled_and_rekeyed_to_scope() {
+        let counts: HashMap<String, u32> = [
+            ("src/hot.rs".to_string(), 15),
+            ("src/warm.rs".to_string(), 3),
+            ("vendor/lib.rs".to_string(), 1),
+        ]
+        .into_iter()
+        .collect();
+
+        let all = normalized_churn(&counts, Path::new(""));
+        assert_eq!(all["src/hot.rs"], 1.0);
+        assert!((all["src/warm.rs"] - 0.5).abs() < 1e-6);
+        assert!(all["vendor/lib.rs"] < all["src/warm.rs"]);
+
+        let scoped = normalized_churn(&counts, Path::new("src"));
+        assert_eq!(scoped.len(), 2);
+        assert_eq!(scoped["hot.rs"], 1.0);
+    }
+}
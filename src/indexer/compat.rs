@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Index compatibility between cgrep versions sharing one checkout.
+//!
+//! Every build stamps `metadata.json` with the index format it wrote and the
+//! oldest format a binary must support to read or to update that index. A
+//! binary older than `min_writer` refuses to update the index (an explicit
+//! `--force` rebuild still replaces it), and one older than `min_reader`
+//! refuses to search it. Newer binaries read older indexes as they are and
+//! only migrate them when asked to write. Indexes without a stamp predate the
+//! check and are treated as readable and writable by everyone.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::indexer::index::index_compat;
+use crate::indexer::migrate::INDEX_SCHEMA_VERSION;
+use cgrep::errors::IndexVersionError;
+use cgrep::utils::INDEX_DIR;
+
+/// Oldest format whose binaries can search an index this build writes.
+/// Formats 2 and 3 only added fields that older readers ignore.
+const MIN_READER_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct IndexCompat {
+    /// Index format (schema version) of the index.
+    pub format: u32,
+    pub min_reader: u32,
+    pub min_writer: u32,
+    /// cgrep version that wrote the index.
+    pub written_by: String,
+}
+
+impl IndexCompat {
+    /// Stamp for an index written by this binary.
+    pub(crate) fn current() -> Self {
+        Self {
+            format: INDEX_SCHEMA_VERSION,
+            min_reader: MIN_READER_FORMAT,
+            min_writer: INDEX_SCHEMA_VERSION,
+            written_by: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Refuse to update an index written for newer binaries.
+///
+/// With `force` the index is about to be replaced, so only a warning is
+/// printed.
+pub(crate) fn check_writable(root: &Path, force: bool) -> Result<()> {
+    let Some(error) = version_error(root, "update", |compat| compat.min_writer) else {
+        return Ok(());
+    };
+    if force {
+        eprintln!(
+            "Warning: replacing an index written by cgrep {} (index format v{})",
+            error.written_by, error.format
+        );
+        return Ok(());
+    }
+    Err(error.into())
+}
+
+/// Refuse to search an index this binary cannot read.
+pub(crate) fn check_readable(root: &Path) -> Result<()> {
+    match version_error(root, "read", |compat| compat.min_reader) {
+        Some(error) => Err(error.into()),
+        None => Ok(()),
+    }
+}
+
+/// Whether this binary may search the index under `root`.
+pub(crate) fn is_readable(root: &Path) -> bool {
+    version_error(root, "read", |compat| compat.min_reader).is_none()
+}
+
+fn version_error(
+    root: &Path,
+    operation: &'static str,
+    required: impl Fn(&IndexCompat) -> u32,
+) -> Option<IndexVersionError> {
+    let compat = index_compat(root)?;
+    let required = required(&compat);
+    (required > INDEX_SCHEMA_VERSION).then(|| IndexVersionError {
+        index_path: root.join(INDEX_DIR).display().to_string(),
+        operation,
+        written_by: compat.written_by,
+        format: compat.format,
+        required,
+        supported: INDEX_SCHEMA_VERSION,
+    })
+}
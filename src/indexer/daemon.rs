@@ -84,6 +84,11 @@ fn terminate_process(_pid: u32) -> bool {
     false
 }
 
+/// Whether a watch daemon is keeping the index under `root` fresh.
+pub fn is_running(root: &Path) -> bool {
+    matches!(read_pid(&pid_file(root)), Ok(Some(pid)) if process_alive(pid))
+}
+
 pub fn start(
     path: Option<&str>,
     debounce: u64,
@@ -0,0 +1,380 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep doctor`: checks for index/checkout mismatches.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::indexer::index;
+use crate::indexer::migrate;
+use crate::indexer::sparse::SparseExclusions;
+use crate::indexer::watch_limits::{self, WatchCapacity};
+use cgrep::output::{print_json, print_json2};
+use cgrep::utils::{find_checkout_root, find_index_root, is_linked_worktree_root, INDEX_DIR};
+
+/// Number of example paths included in a finding message.
+const SAMPLE_PATHS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Severity {
+    Info,
+    Warn,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Finding {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DoctorResult {
+    root: String,
+    index_root: Option<String>,
+    checkout_root: Option<String>,
+    linked_worktree: bool,
+    sparse_checkout: bool,
+    findings: Vec<Finding>,
+}
+
+#[derive(Debug, Serialize)]
+struct DoctorJson2Meta {
+    schema_version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct DoctorJson2Payload {
+    meta: DoctorJson2Meta,
+    result: DoctorResult,
+}
+
+fn resolve_root(path: Option<&str>) -> Result<PathBuf> {
+    let root = path
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .ok_or_else(|| anyhow::anyhow!("Cannot determine current directory"))?;
+    Ok(root.canonicalize().unwrap_or(root))
+}
+
+/// Nearest index above `start`, ignoring worktree boundaries.
+fn nearest_index_ignoring_worktrees(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(INDEX_DIR).join("meta.json").is_file())
+        .map(Path::to_path_buf)
+}
+
+fn sample(paths: &[String]) -> String {
+    let mut sorted: Vec<&str> = paths.iter().map(String::as_str).collect();
+    sorted.sort_unstable();
+    let mut out = sorted[..sorted.len().min(SAMPLE_PATHS)].join(", ");
+    if paths.len() > SAMPLE_PATHS {
+        out.push_str(&format!(", … (+{})", paths.len() - SAMPLE_PATHS));
+    }
+    out
+}
+
+fn diagnose(root: &Path) -> DoctorResult {
+    let mut findings = Vec::new();
+    let checkout_root = find_checkout_root(root);
+    let linked_worktree = checkout_root
+        .as_deref()
+        .map(is_linked_worktree_root)
+        .unwrap_or(false);
+    let index_root = find_index_root(root).map(|found| found.root);
+
+    if let (Some(checkout), Some(index_root)) = (checkout_root.as_deref(), index_root.as_deref()) {
+        if !index_root.starts_with(checkout) {
+            findings.push(Finding {
+                severity: Severity::Info,
+                code: "index_outside_checkout",
+                message: format!(
+                    "Index at {} covers the enclosing directory, not just checkout {}",
+                    index_root.display(),
+                    checkout.display()
+                ),
+                suggestion: None,
+            });
+        }
+    }
+
+    if index_root.is_none() {
+        let foreign = if linked_worktree {
+            nearest_index_ignoring_worktrees(root)
+        } else {
+            None
+        };
+        match (foreign, checkout_root.as_deref()) {
+            (Some(foreign), Some(worktree)) => findings.push(Finding {
+                severity: Severity::Warn,
+                code: "worktree_index_mismatch",
+                message: format!(
+                    "Linked worktree {} has no index of its own; the index at {} belongs to another checkout and is not used",
+                    worktree.display(),
+                    foreign.display()
+                ),
+                suggestion: Some(format!("cgrep index -p {}", worktree.display())),
+            }),
+            _ => findings.push(Finding {
+                severity: Severity::Warn,
+                code: "index_missing",
+                message: format!("No index found for {}", root.display()),
+                suggestion: Some("cgrep index".to_string()),
+            }),
+        }
+    }
+
+    let sparse = checkout_root
+        .as_deref()
+        .and_then(SparseExclusions::load_for_checkout);
+    if let Some(sparse) = sparse.as_ref() {
+        findings.push(Finding {
+            severity: Severity::Info,
+            code: "sparse_checkout",
+            message: format!(
+                "Sparse checkout excludes {} tracked path(s); indexing and --changed skip them",
+                sparse.len()
+            ),
+            suggestion: None,
+        });
+        if let Some(index_root) = index_root.as_deref() {
+            let stale: Vec<String> = index::indexed_file_paths(index_root)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|path| sparse.contains_path(Path::new(path)))
+                .collect();
+            if !stale.is_empty() {
+                findings.push(Finding {
+                    severity: Severity::Warn,
+                    code: "sparse_excluded_indexed",
+                    message: format!(
+                        "Index still contains {} file(s) outside the sparse checkout: {}",
+                        stale.len(),
+                        sample(&stale)
+                    ),
+                    suggestion: Some(format!("cgrep index -p {}", index_root.display())),
+                });
+            }
+        }
+    }
+
+    if let Some(stamp) = index_root.as_deref().and_then(index::index_compat) {
+        let supported = migrate::INDEX_SCHEMA_VERSION;
+        if stamp.min_writer > supported {
+            let access = if stamp.min_reader > supported {
+                "it cannot be searched or updated"
+            } else {
+                "searches read it as is, but updates are refused"
+            };
+            findings.push(Finding {
+                severity: Severity::Warn,
+                code: "index_version_newer",
+                message: format!(
+                    "Index was written by cgrep {} (index format v{}); this cgrep supports v{}, so {}",
+                    stamp.written_by, stamp.format, supported, access
+                ),
+                suggestion: Some(format!("upgrade cgrep to {} or newer", stamp.written_by)),
+            });
+        }
+    }
+
+    if let Some(schema) = index_root.as_deref().and_then(migrate::schema_status) {
+        match schema.version {
+            None => findings.push(Finding {
+                severity: Severity::Warn,
+                code: "index_schema_rebuild",
+                message: format!(
+                    "Index predates schema migrations; the next update rebuilds it for v{}",
+                    schema.current
+                ),
+                suggestion: Some("cgrep index --force".to_string()),
+            }),
+            Some(version) if !schema.pending.is_empty() => findings.push(Finding {
+                severity: Severity::Info,
+                code: "index_schema_migration",
+                message: format!(
+                    "Index schema v{} is older than v{}; the next update migrates it in place: {}",
+                    version,
+                    schema.current,
+                    schema.pending.join("; ")
+                ),
+                suggestion: Some("cgrep refresh".to_string()),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    DoctorResult {
+        root: root.display().to_string(),
+        index_root: index_root.map(|p| p.display().to_string()),
+        checkout_root: checkout_root.map(|p| p.display().to_string()),
+        linked_worktree,
+        sparse_checkout: sparse.is_some(),
+        findings,
+    }
+}
+
+/// Findings about file watching for `root`: a watcher polling because the
+/// OS watch limit was reached, or a limit close to running out.
+fn watch_findings(root: &Path, capacity: Option<&WatchCapacity>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let index_root = find_index_root(root).map_or_else(|| root.to_path_buf(), |found| found.root);
+    if let Some(polling) = watch_limits::active_fallback(&index_root) {
+        findings.push(Finding {
+            severity: Severity::Warn,
+            code: "watch_polling",
+            message: format!(
+                "Watcher for {} (pid {}) polls every {}s because the OS watch limit was reached: {}",
+                index_root.display(),
+                polling.pid,
+                polling.poll_interval_ms / 1000,
+                polling.reason
+            ),
+            suggestion: Some(watch_limits::RAISE_LIMIT_HINT.to_string()),
+        });
+    }
+    if let Some(capacity) = capacity.filter(|capacity| capacity.nearly_exhausted()) {
+        findings.push(Finding {
+            severity: Severity::Warn,
+            code: "watch_limit_nearly_exhausted",
+            message: format!(
+                "{} of {} inotify watches are in use; new watchers will fall back to polling",
+                capacity.watches_in_use, capacity.max_user_watches
+            ),
+            suggestion: Some(watch_limits::RAISE_LIMIT_HINT.to_string()),
+        });
+    }
+    findings
+}
+
+pub fn run(path: Option<&str>, format: OutputFormat, compact: bool) -> Result<()> {
+    let root = resolve_root(path)?;
+    let mut result = diagnose(&root);
+    result
+        .findings
+        .extend(watch_findings(&root, watch_limits::capacity().as_ref()));
+
+    match format {
+        OutputFormat::Text
+        | OutputFormat::Github
+        | OutputFormat::Gitlab
+        | OutputFormat::Sarif
+        | OutputFormat::Quickfix => {
+            println!("Root: {}", result.root);
+            println!(
+                "Index root: {}",
+                result.index_root.as_deref().unwrap_or("(none)")
+            );
+            if let Some(checkout) = result.checkout_root.as_deref() {
+                let mut kind = Vec::new();
+                if result.linked_worktree {
+                    kind.push("linked worktree");
+                }
+                if result.sparse_checkout {
+                    kind.push("sparse");
+                }
+                if kind.is_empty() {
+                    println!("Git checkout: {}", checkout);
+                } else {
+                    println!("Git checkout: {} ({})", checkout, kind.join(", "));
+                }
+            }
+            if result.findings.is_empty() {
+                println!("{} No issues found", "✓".green());
+            }
+            for finding in &result.findings {
+                let label = match finding.severity {
+                    Severity::Info => "info".cyan().to_string(),
+                    Severity::Warn => "warn".yellow().to_string(),
+                };
+                println!("[{}] {}: {}", label, finding.code, finding.message);
+                if let Some(suggestion) = finding.suggestion.as_deref() {
+                    println!("       fix: {}", suggestion);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&result, compact)?;
+        }
+        OutputFormat::Json2 => {
+            let payload = DoctorJson2Payload {
+                meta: DoctorJson2Meta {
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
+                },
+                result,
+            };
+            print_json2("doctor", &payload, compact)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn missing_index_is_reported() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let root = dir.path().canonicalize().expect("canonical");
+        let result = diagnose(&root);
+        assert!(result.index_root.is_none());
+        assert_eq!(result.findings.len(), 1);
+        assert_eq!(result.findings[0].code, "index_missing");
+    }
+
+    #[test]
+    fn worktree_without_index_reports_mismatch() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let root = dir.path().canonicalize().expect("canonical");
+        fs::create_dir_all(root.join(INDEX_DIR)).expect("mkdir index");
+        fs::write(root.join(INDEX_DIR).join("meta.json"), "{}").expect("write meta");
+        fs::create_dir_all(root.join(".git/worktrees/wt")).expect("mkdir gitdir");
+        let worktree = root.join("wt");
+        fs::create_dir_all(&worktree).expect("mkdir worktree");
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", root.join(".git/worktrees/wt").display()),
+        )
+        .expect("write .git file");
+
+        let result = diagnose(&worktree);
+        assert!(result.linked_worktree);
+        assert!(result.index_root.is_none());
+        assert_eq!(
+            result
+                .findings
+                .iter()
+                .map(|finding| finding.code)
+                .collect::<Vec<_>>(),
+            vec!["worktree_index_mismatch"]
+        );
+    }
+
+    #[test]
+    fn nearly_exhausted_watch_limit_is_reported() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let root = dir.path().canonicalize().expect("canonical");
+        let capacity = |watches_in_use| WatchCapacity {
+            max_user_watches: 8192,
+            max_user_instances: Some(128),
+            watches_in_use,
+            instances_in_use: 3,
+        };
+        assert!(watch_findings(&root, Some(&capacity(100))).is_empty());
+        assert!(watch_findings(&root, None).is_empty());
+        let findings = watch_findings(&root, Some(&capacity(8000)));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, "watch_limit_nearly_exhausted");
+        assert!(findings[0].message.contains("8000 of 8192"));
+    }
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Cheap check that the index still matches the working tree.
+//!
+//! A full manifest diff hashes every suspect file, which is too slow to run
+//! before each query. Instead a fixed-size, evenly spaced sample of manifest
+//! entries is stat'ed for size and mtime changes, and the directories holding
+//! indexed files are checked for modification after the manifest was
+//! written, which catches added and removed files.
+//!
+//! `search --verify` goes further for the files it is about to return: it
+//! hashes them and compares against the manifest, so content edited without
+//! an mtime change is caught too.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::indexer::manifest::{self, MANIFEST_V1_FILE_REL};
+
+/// Manifest entries stat'ed per check.
+pub(crate) const FRESHNESS_SAMPLE_FILES: usize = 512;
+/// Directories stat'ed per check.
+pub(crate) const FRESHNESS_SAMPLE_DIRS: usize = 256;
+/// Changed paths kept for the warning message.
+const MAX_REPORTED_CHANGES: usize = 5;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Freshness {
+    pub stale: bool,
+    /// Sampled files or directories that changed since indexing, root-relative.
+    pub changed: Vec<String>,
+}
+
+/// Sample the manifest under `root` against the file system.
+///
+/// `None` when the index has no manifest to compare against.
+pub(crate) fn check_freshness(root: &Path) -> Option<Freshness> {
+    let indexed_at = std::fs::metadata(root.join(MANIFEST_V1_FILE_REL))
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    let manifest = manifest::load_manifest(root)?;
+
+    let mut changed = Vec::new();
+    for entry in evenly_spaced(&manifest.entries, FRESHNESS_SAMPLE_FILES) {
+        let current = std::fs::metadata(root.join(&entry.path))
+            .ok()
+            .filter(|metadata| metadata.is_file());
+        let same = current.is_some_and(|metadata| {
+            metadata.len() == entry.size && manifest::file_mtime_nanos(&metadata) == entry.mtime
+        });
+        if !same {
+            changed.push(entry.path.clone());
+        }
+    }
+
+    let dirs: Vec<&str> = manifest
+        .entries
+        .iter()
+        .map(|entry| entry.path.rsplit_once('/').map_or("", |(dir, _)| dir))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    for dir in evenly_spaced(&dirs, FRESHNESS_SAMPLE_DIRS) {
+        let modified = std::fs::metadata(root.join(dir)).and_then(|metadata| metadata.modified());
+        if modified.map_or(true, |time| modified_after(time, indexed_at)) {
+            changed.push(if dir.is_empty() { "." } else { dir }.to_string());
+        }
+    }
+
+    let stale = !changed.is_empty();
+    changed.truncate(MAX_REPORTED_CHANGES);
+    Some(Freshness { stale, changed })
+}
+
+/// Hash `files` and return those that no longer match the manifest under
+/// `root`, including deleted ones. Files the manifest does not list are
+/// skipped.
+///
+/// `None` when the index has no manifest to compare against.
+pub(crate) fn changed_files(root: &Path, files: &[PathBuf]) -> Option<Vec<PathBuf>> {
+    let manifest = manifest::load_manifest(root)?;
+    let hashes: HashMap<&str, &str> = manifest
+        .entries
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry.hash.as_str()))
+        .collect();
+    let changed = files
+        .iter()
+        .filter(|path| {
+            let Some(expected) = manifest::relative_path(root, path)
+                .and_then(|rel| hashes.get(rel.as_str()).copied())
+            else {
+                return false;
+            };
+            manifest::hash_file_streaming(path).map_or(true, |hash| hash != expected)
+        })
+        .cloned()
+        .collect();
+    Some(changed)
+}
+
+fn modified_after(time: SystemTime, reference: SystemTime) -> bool {
+    time.duration_since(reference)
+        .is_ok_and(|elapsed| !elapsed.is_zero())
+}
+
+/// At most `limit` items spread evenly across `items`, starting with the first.
+fn evenly_spaced<T>(items: &[T], limit: usize) -> impl Iterator<Item = &T> {
+    let step = items.len().div_ceil(limit.max(1)).max(1);
+    items.iter().step_by(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::manifest::{compute_manifest_diff, write_manifest};
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+        std::fs::write(path, content).expect("write");
+    }
+
+    fn snapshot(root: &Path) {
+        let files = vec![root.join("src/a.rs"), root.join("src/b.rs")];
+        let diff = compute_manifest_diff(root, &files, None).expect("diff");
+        write_manifest(root, &diff.next).expect("write manifest");
+    }
+
+    #[test]
+    fn edits_and_new_files_mark_the_index_stale() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path();
+        assert_eq!(check_freshness(root), None);
+
+        write(root, "src/a.rs", "fn a() {}\n");
+        write(root, "src/b.rs", "fn b() {}\n");
+        snapshot(root);
+        assert_eq!(check_freshness(root).map(|f| f.stale), Some(false));
+
+        write(root, "src/a.rs", "fn a() { changed(); }\n");
+        let freshness = check_freshness(root).expect("manifest");
+        assert!(freshness.stale);
+        assert_eq!(freshness.changed, ["src/a.rs"]);
+
+        snapshot(root);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        write(root, "src/c.rs", "fn c() {}\n");
+        let freshness = check_freshness(root).expect("manifest");
+        assert_eq!(freshness.changed, ["src"]);
+    }
+
+    #[test]
+    fn hashing_catches_edits_that_keep_size_and_mtime() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path();
+        write(root, "src/a.rs", "fn a() {}\n");
+        write(root, "src/b.rs", "fn b() {}\n");
+        let files = vec![root.join("src/a.rs"), root.join("src/b.rs")];
+        assert_eq!(changed_files(root, &files), None);
+        snapshot(root);
+
+        let a = root.join("src/a.rs");
+        let mtime = std::fs::metadata(&a)
+            .and_then(|m| m.modified())
+            .expect("mtime");
+        write(root, "src/a.rs", "fn x() {}\n");
+        std::fs::File::options()
+            .write(true)
+            .open(&a)
+            .and_then(|file| file.set_modified(mtime))
+            .expect("restore mtime");
+        assert_eq!(check_freshness(root).map(|f| f.stale), Some(false));
+        assert_eq!(changed_files(root, &files), Some(vec![a]));
+
+        std::fs::remove_file(root.join("src/b.rs")).expect("remove");
+        let unindexed = root.join("src/c.rs");
+        assert_eq!(
+            changed_files(root, &[files[1].clone(), unindexed]),
+            Some(vec![files[1].clone()])
+        );
+    }
+
+    #[test]
+    fn sampling_is_evenly_spaced() {
+        let items: Vec<usize> = (0..10).collect();
+        let sampled: Vec<usize> = evenly_spaced(&items, 4).copied().collect();
+        assert_eq!(sampled, [0, 3, 6, 9]);
+        assert_eq!(evenly_spaced(&items, 50).count(), 10);
+    }
+}
@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Per-file git history for `search --modified-since` and `--author`.
+//!
+//! Indexing records, for each file, the time of the last commit touching it
+//! and everyone who committed to it, so the filters read the index metadata
+//! instead of walking history on every search. Without a recorded table
+//! (scan mode, or an index built before it existed) search asks git directly.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::indexer::churn::CHURN_WINDOW_DAYS;
+
+/// Commit metadata for one file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FileHistory {
+    /// Unix seconds of the newest commit touching the file.
+    pub last_commit: i64,
+    /// `Name <email>` of every commit author, sorted and deduplicated.
+    pub authors: Vec<String>,
+}
+
+/// Everything one `git log` walk records, keyed by root-relative,
+/// `/`-separated file path.
+#[derive(Debug, Default)]
+pub(crate) struct RepoHistory {
+    /// Last commit time and authors per file.
+    pub files: HashMap<String, FileHistory>,
+    /// Commits per file over [`CHURN_WINDOW_DAYS`], for ranking.
+    pub commit_counts: HashMap<String, u32>,
+}
+
+/// Walk the history of `root` once.
+///
+/// Empty when `root` is not inside a git work tree or git is unavailable.
+pub(crate) fn repo_history(root: &Path) -> RepoHistory {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args([
+            "log",
+            "--no-renames",
+            "--relative",
+            "--name-only",
+            "--format=%x00%ct%x09%an <%ae>",
+        ])
+        .output();
+    let Ok(output) = output else {
+        return RepoHistory::default();
+    };
+    if !output.status.success() {
+        return RepoHistory::default();
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64);
+    let churn_since = now - i64::from(CHURN_WINDOW_DAYS) * 86_400;
+    parse_log(&String::from_utf8_lossy(&output.stdout), churn_since)
+}
+
+fn parse_log(log: &str, churn_since: i64) -> RepoHistory {
+    let mut history = RepoHistory::default();
+    let mut commit: Option<(i64, &str)> = None;
+    for line in log.lines() {
+        if let Some(header) = line.strip_prefix('\0') {
+            commit = header
+                .split_once('\t')
+                .and_then(|(time, author)| Some((time.parse().ok()?, author)));
+            continue;
+        }
+        let path = line.trim();
+        let Some((time, author)) = commit.filter(|_| !path.is_empty()) else {
+            continue;
+        };
+        if time >= churn_since {
+            *history.commit_counts.entry(path.to_string()).or_insert(0) += 1;
+        }
+        let file = history.files.entry(path.to_string()).or_default();
+        // `git log` lists newest first, but merges can reorder timestamps.
+        file.last_commit = file.last_commit.max(time);
+        if let Err(idx) = file
+            .authors
+            .binary_search_by(|known| known.as_str().cmp(author))
+        {
+            file.authors.insert(idx, author.to_string());
+        }
+    }
+    history
+}
+
+/// `--modified-since` and `--author` as given on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HistoryFilter {
+    since: Option<i64>,
+    author: Option<String>,
+    label: String,
+}
+
+impl HistoryFilter {
+    /// `None` when neither filter is set.
+    pub(crate) fn new(modified_since: Option<&str>, author: Option<&str>) -> Result<Option<Self>> {
+        let since = modified_since.map(parse_since).transpose()?;
+        let author = author
+            .map(str::trim)
+            .filter(|author| !author.is_empty())
+            .map(str::to_lowercase);
+        let mut label = Vec::new();
+        if let Some(raw) = modified_since {
+            label.push(format!("since:{}", raw.trim()));
+        }
+        if let Some(author) = &author {
+            label.push(format!("author:{author}"));
+        }
+        if label.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            since,
+            author,
+            label: label.join(","),
+        }))
+    }
+
+    /// Short description for `changed_rev` and cache keys.
+    pub(crate) fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Whether a file with `history` passes both filters. Author patterns
+    /// match case-insensitively anywhere in `Name <email>`.
+    pub(crate) fn matches(&self, history: &FileHistory) -> bool {
+        if self.since.is_some_and(|since| history.last_commit < since) {
+            return false;
+        }
+        match &self.author {
+            Some(pattern) => history
+                .authors
+                .iter()
+                .any(|author| author.to_lowercase().contains(pattern)),
+            None => true,
+        }
+    }
+}
+
+/// Unix seconds for `YYYY-MM-DD` (UTC midnight) or a relative `<N>d` / `<N>w`.
+fn parse_since(raw: &str) -> Result<i64> {
+    let raw = raw.trim();
+    let relative_days = raw
+        .strip_suffix('d')
+        .and_then(|days| days.parse::<i64>().ok())
+        .or_else(|| {
+            raw.strip_suffix('w')
+                .and_then(|weeks| weeks.parse::<i64>().ok())
+                .map(|weeks| weeks * 7)
+        });
+    if let Some(days) = relative_days {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        return Ok(now - days * 86_400);
+    }
+    let parts: Vec<&str> = raw.split('-').collect();
+    if let [year, month, day] = parts.as_slice() {
+        if let (Ok(year), Ok(month), Ok(day)) = (
+            year.parse::<i64>(),
+            month.parse::<u32>(),
+            day.parse::<u32>(),
+        ) {
+            if (1..=12).contains(&month) && (1..=31).contains(&day) {
+                return Ok(days_from_civil(year, month, day) * 86_400);
+            }
+        }
+    }
+    bail!("Invalid --modified-since `{raw}`: use YYYY-MM-DD or a relative age such as 30d or 2w")
+}
+
+/// Days since the Unix epoch for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_output_is_folded_per_file() {
+        let log = "\u{0}200\tAda <ada@example.com>\n\nsrc/lib.rs\nsrc/main.rs\n\
+\u{0}100\tGrace <grace@example.com>\n\nsrc/lib.rs\n\
+\u{0}50\tAda <ada@example.com>\n\nsrc/lib.rs\n";
+        let history = parse_log(log, 100);
+        assert_eq!(
+            history.files["src/lib.rs"],
+            FileHistory {
+                last_commit: 200,
+                authors: vec![
+                    "Ada <ada@example.com>".to_string(),
+                    "Grace <grace@example.com>".to_string()
+                ],
+            }
+        );
+        assert_eq!(history.files["src/main.rs"].authors.len(), 1);
+        assert_eq!(history.commit_counts["src/lib.rs"], 2);
+        assert_eq!(history.commit_counts["src/main.rs"], 1);
+    }
+
+    #[test]
+    fn filters_check_date_and_author() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2024, 1, 1), 19_723);
+        assert!(parse_since("2024-13-01").is_err());
+        assert!(HistoryFilter::new(None, Some(" "))
+            .expect("filter")
+            .is_none());
+
+        let filter = HistoryFilter::new(Some("2024-01-01"), Some("GRACE"))
+            .expect("filter")
+            .expect("set");
+        assert_eq!(filter.label(), "since:2024-01-01,author:grace");
+        let recent = FileHistory {
+            last_commit: 19_723 * 86_400,
+            authors: vec!["Grace <grace@example.com>".to_string()],
+        };
+        assert!(filter.matches(&recent));
+        assert!(!filter.matches(&FileHistory {
+            last_commit: 19_722 * 86_400,
+            ..recent.clone()
+        }));
+        assert!(!filter.matches(&FileHistory {
+            authors: vec!["Ada <ada@example.com>".to_string()],
+            ..recent
+        }));
+    }
+}
@@ -16,25 +16,42 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
 use std::time::SystemTime;
+use std::time::{Duration, Instant};
 use tantivy::{
     schema::{Field, Schema, Term, STORED, STRING, TEXT},
     Index, IndexWriter, TantivyDocument,
 };
 
+use crate::indexer::archive::{self, ArchiveOptions};
+use crate::indexer::branches;
+use crate::indexer::compat::{self, IndexCompat};
+use crate::indexer::git_history::{self, FileHistory};
+use crate::indexer::literals;
+use crate::indexer::lock::{self, LockWait};
 use crate::indexer::manifest::{self, ManifestDiffSummary};
+use crate::indexer::migrate::{self, AppliedMigration};
+use crate::indexer::regions;
 use crate::indexer::reuse::{self, ReuseDecision, ReuseMode, ReuseProfile};
-use crate::indexer::scanner::{detect_language, FileScanner};
+use crate::indexer::scanner::{detect_language, FileScanner, IgnoreRules};
+use crate::indexer::size_policy::{SizePolicies, SizePolicy};
+use crate::indexer::sparse;
 use crate::indexer::status::{self, BuildStatus};
+use crate::parser::config_keys;
+use crate::parser::metrics::SymbolMetrics;
 use crate::parser::symbols::{Symbol, SymbolExtractor, SymbolKind};
-use cgrep::config::{Config, EmbeddingProviderType};
+use cgrep::config::{Config, EmbeddingProviderType, SizePolicyMode};
 use cgrep::embedding::{
-    CommandProvider, DummyProvider, EmbeddingProvider, EmbeddingProviderConfig, EmbeddingStorage,
-    FastEmbedder, SymbolEmbeddingInput, DEFAULT_EMBEDDING_DIM,
+    ChunkConfig, CommandProvider, ContentEmbeddingCache, DummyProvider, EmbeddingChunker,
+    EmbeddingProvider, EmbeddingProviderConfig, EmbeddingStorage, FastEmbedder,
+    SymbolEmbeddingInput, CHUNK_SYMBOL_KIND, DEFAULT_EMBEDDING_DIM,
 };
+use cgrep::encoding::{self, Decoded, LineEnding};
+use cgrep::paths;
 use cgrep::utils::INDEX_DIR;
 const METADATA_FILE: &str = ".cgrep/metadata.json";
+/// Staging directory (under `.cgrep`) used by `--force` rebuilds.
+pub(crate) const STAGING_DIR_NAME: &str = "staging";
 pub(crate) const DEFAULT_WRITER_BUDGET_BYTES: usize = 50_000_000;
 const HIGH_MEMORY_WRITER_BUDGET_BYTES: usize = 1024 * 1024 * 1024;
 
@@ -44,7 +61,10 @@ pub(crate) struct StoredIndexOptions {
     pub exclude_paths: Vec<String>,
     pub include_paths: Vec<String>,
     pub respect_git_ignore: bool,
+    pub ignore_rules: IgnoreRules,
     pub high_memory: bool,
+    pub archives: ArchiveOptions,
+    pub size_policies: Vec<SizePolicy>,
 }
 
 impl Default for StoredIndexOptions {
@@ -53,7 +73,10 @@ impl Default for StoredIndexOptions {
             exclude_paths: Vec::new(),
             include_paths: Vec::new(),
             respect_git_ignore: true,
+            ignore_rules: IgnoreRules::default(),
             high_memory: false,
+            archives: ArchiveOptions::default(),
+            size_policies: Vec::new(),
         }
     }
 }
@@ -159,6 +182,69 @@ struct EmbeddingIndexStats {
     files_skipped_up_to_date: usize,
     files_deleted: usize,
     symbols_embedded: usize,
+    /// Symbols whose vectors came from the content-hash cache.
+    symbols_cached: usize,
+    elapsed: Duration,
+}
+
+impl EmbeddingIndexStats {
+    fn symbols_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.symbols_embedded as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Embedding run knobs from `cgrep index`.
+#[derive(Debug, Clone, Copy, Default)]
+struct EmbeddingRunOptions {
+    force: bool,
+    /// Continue an interrupted `--embeddings-force` rebuild instead of restarting it.
+    resume: bool,
+    /// Symbols per committed batch (overrides `[embeddings] commit_batch_size`).
+    batch_size: Option<usize>,
+}
+
+/// Meta key present while an `--embeddings-force` rebuild is unfinished.
+const EMBEDDING_CHECKPOINT_META: &str = "rebuild_checkpoint";
+
+/// Interval between throughput lines during long embedding runs.
+const EMBEDDING_PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Periodic throughput reporting for embedding runs.
+struct EmbeddingProgress {
+    started: Instant,
+    last_report: Instant,
+    files_total: usize,
+}
+
+impl EmbeddingProgress {
+    fn new(files_total: usize) -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            last_report: now,
+            files_total,
+        }
+    }
+
+    fn maybe_report(&mut self, stats: &mut EmbeddingIndexStats) {
+        stats.elapsed = self.started.elapsed();
+        if self.last_report.elapsed() < EMBEDDING_PROGRESS_INTERVAL {
+            return;
+        }
+        self.last_report = Instant::now();
+        println!(
+            "Embeddings: {}/{} files, {} symbols embedded ({:.1} symbols/s)",
+            stats.files_embedded + stats.files_skipped_up_to_date,
+            self.files_total,
+            stats.symbols_embedded,
+            stats.symbols_per_sec()
+        );
+    }
 }
 
 fn create_embedding_provider(
@@ -183,6 +269,9 @@ fn create_embedding_provider(
         || config.embeddings.provider.is_some()
         || config.embeddings.batch_size.is_some()
         || config.embeddings.max_chars.is_some()
+        || config.embeddings.device.is_some()
+        || config.embeddings.commit_batch_size.is_some()
+        || config.embeddings.content_cache.is_some()
         || config.embeddings.model.is_some()
         || config.embeddings.command.is_some()
         || config.embeddings.chunk_lines.is_some()
@@ -201,6 +290,7 @@ fn create_embedding_provider(
             config.embeddings.batch_size(),
             config.embeddings.max_chars(),
         )
+        .map(|provider_config| provider_config.with_device(config.embeddings.device()))
         .and_then(FastEmbedder::new)
         .map(|provider| Box::new(provider) as Box<dyn EmbeddingProvider>),
         EmbeddingProviderType::Dummy => Ok(Box::new(DummyProvider::new(DEFAULT_EMBEDDING_DIM))),
@@ -228,29 +318,105 @@ fn create_embedding_provider(
     }
 }
 
-fn read_utf8_text_bytes(bytes: &[u8]) -> Result<Option<String>> {
-    if bytes.contains(&0) {
-        return Ok(None);
-    }
-    let text = match std::str::from_utf8(bytes) {
-        Ok(text) => text,
-        Err(_) => return Ok(None),
-    };
-    Ok(Some(text.to_string()))
+fn read_text_bytes(bytes: &[u8]) -> Option<String> {
+    encoding::decode_text(bytes).map(|decoded| decoded.text.into_owned())
 }
 
-fn read_utf8_text(path: &Path) -> Result<Option<String>> {
+fn read_text(path: &Path) -> Result<Option<String>> {
     let file = std::fs::File::open(path)?;
     if let Ok(mmap) = unsafe { Mmap::map(&file) } {
-        return read_utf8_text_bytes(&mmap);
+        return Ok(read_text_bytes(&mmap));
     }
 
     let bytes = std::fs::read(path)?;
-    read_utf8_text_bytes(&bytes)
+    Ok(read_text_bytes(&bytes))
+}
+
+/// Content-hash cache opened for one embedding run.
+struct EmbeddingCacheHandle {
+    cache: ContentEmbeddingCache,
+    /// `provider/model`, so vectors from different models never mix.
+    model_key: String,
+    /// Forced rebuilds refresh the cache instead of reading it.
+    read: bool,
+    dimension: Option<usize>,
+}
+
+/// Embed `texts`, reusing cached vectors for known content hashes.
+fn embed_with_cache(
+    provider: &mut dyn EmbeddingProvider,
+    cache: Option<&mut EmbeddingCacheHandle>,
+    texts: &[String],
+    hashes: &[&str],
+    stats: &mut EmbeddingIndexStats,
+) -> Result<Vec<Vec<f32>>> {
+    let Some(cache) = cache else {
+        return embed_in_chunks(provider, texts);
+    };
+
+    let mut hits = if cache.read {
+        cache.cache.get_many(&cache.model_key, hashes)?
+    } else {
+        HashMap::new()
+    };
+    if let Some(dimension) = cache.dimension {
+        hits.retain(|_, vector| vector.len() == dimension);
+    }
+
+    let misses: Vec<usize> = (0..texts.len())
+        .filter(|&i| !hits.contains_key(hashes[i]))
+        .collect();
+    let miss_texts: Vec<String> = misses.iter().map(|&i| texts[i].clone()).collect();
+    let miss_vectors = embed_in_chunks(provider, &miss_texts)?;
+    if let Some(first) = miss_vectors.first() {
+        cache.dimension = Some(first.len());
+    }
+
+    let new_entries: Vec<(&str, &[f32])> = misses
+        .iter()
+        .zip(miss_vectors.iter())
+        .map(|(&i, vector)| (hashes[i], vector.as_slice()))
+        .collect();
+    cache.cache.put_many(&cache.model_key, &new_entries)?;
+
+    stats.symbols_cached += texts.len() - misses.len();
+    let mut miss_vectors = miss_vectors.into_iter();
+    let mut vectors = Vec::with_capacity(texts.len());
+    for hash in hashes {
+        match hits.get(*hash) {
+            Some(vector) => vectors.push(vector.clone()),
+            None => vectors.push(miss_vectors.next().unwrap_or_default()),
+        }
+    }
+    Ok(vectors)
+}
+
+/// Embed `texts` in provider-sized chunks.
+fn embed_in_chunks(
+    provider: &mut dyn EmbeddingProvider,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    let embed_batch_size = provider.batch_size().max(1);
+    let mut vectors: Vec<Vec<f32>> = Vec::with_capacity(texts.len());
+
+    for chunk in texts.chunks(embed_batch_size) {
+        let chunk_vectors = provider.embed_texts(chunk)?;
+        if chunk_vectors.len() != chunk.len() {
+            anyhow::bail!(
+                "Embedding provider returned {} vectors for {} inputs",
+                chunk_vectors.len(),
+                chunk.len()
+            );
+        }
+        vectors.extend(chunk_vectors);
+    }
+    Ok(vectors)
 }
 
+/// Embed the pending batch and commit all of its files in one transaction.
 fn flush_embedding_batch(
     provider: &mut dyn EmbeddingProvider,
+    cache: Option<&mut EmbeddingCacheHandle>,
     batch_texts: &mut Vec<String>,
     batch_entries: &mut Vec<EmbeddingBatchEntry>,
     storage: &mut EmbeddingStorage,
@@ -260,53 +426,54 @@ fn flush_embedding_batch(
         return Ok(());
     }
 
-    let vectors = provider.embed_texts(batch_texts)?;
-    if vectors.len() != batch_texts.len() {
-        anyhow::bail!(
-            "Embedding provider returned {} vectors for {} inputs",
-            vectors.len(),
-            batch_texts.len()
-        );
-    }
+    let hashes: Vec<&str> = batch_entries
+        .iter()
+        .flat_map(|entry| entry.symbols.iter().map(|meta| meta.content_hash.as_str()))
+        .collect();
+    let vectors = embed_with_cache(provider, cache, batch_texts, &hashes, stats)?;
 
-    if let Some(first) = vectors.first() {
-        let dimension = first.len();
-        if dimension > 0 {
-            let _ = storage.set_meta("dimension", &dimension.to_string());
+    storage.with_batch(|storage| {
+        if let Some(first) = vectors.first() {
+            let dimension = first.len();
+            if dimension > 0 {
+                storage.set_meta("dimension", &dimension.to_string())?;
+            }
         }
-    }
 
-    for entry in batch_entries.iter() {
-        let end = entry.start_idx + entry.count;
-        let slice = &vectors[entry.start_idx..end];
-
-        let mut inputs: Vec<SymbolEmbeddingInput<'_>> = Vec::with_capacity(entry.count);
-        for (i, embedding) in slice.iter().enumerate() {
-            let meta = &entry.symbols[i];
-            inputs.push(SymbolEmbeddingInput {
-                symbol_id: meta.symbol_id.as_str(),
-                lang: meta.lang.as_str(),
-                symbol_kind: meta.kind.as_str(),
-                symbol_name: meta.name.as_str(),
-                start_line: meta.start_line,
-                end_line: meta.end_line,
-                content_hash: meta.content_hash.as_str(),
-                embedding: embedding.as_slice(),
-            });
-        }
+        for entry in batch_entries.iter() {
+            let end = entry.start_idx + entry.count;
+            let slice = &vectors[entry.start_idx..end];
+
+            let mut inputs: Vec<SymbolEmbeddingInput<'_>> = Vec::with_capacity(entry.count);
+            for (i, embedding) in slice.iter().enumerate() {
+                let meta = &entry.symbols[i];
+                inputs.push(SymbolEmbeddingInput {
+                    symbol_id: meta.symbol_id.as_str(),
+                    lang: meta.lang.as_str(),
+                    symbol_kind: meta.kind.as_str(),
+                    symbol_name: meta.name.as_str(),
+                    start_line: meta.start_line,
+                    end_line: meta.end_line,
+                    content_hash: meta.content_hash.as_str(),
+                    embedding: embedding.as_slice(),
+                });
+            }
 
-        storage.sync_file_symbols(
-            &entry.path,
-            &entry.file_hash,
-            entry.last_modified,
-            &entry.symbol_ids,
-            &inputs,
-        )?;
+            storage.sync_file_symbols(
+                &entry.path,
+                &entry.file_hash,
+                entry.last_modified,
+                &entry.symbol_ids,
+                &inputs,
+            )?;
+        }
+        Ok(())
+    })?;
 
+    for entry in batch_entries.iter() {
         stats.files_embedded += 1;
         stats.symbols_embedded += entry.count;
     }
-
     batch_texts.clear();
     batch_entries.clear();
     Ok(())
@@ -314,24 +481,17 @@ fn flush_embedding_batch(
 
 fn embed_large_file_symbols(
     provider: &mut dyn EmbeddingProvider,
+    cache: Option<&mut EmbeddingCacheHandle>,
     file: LargeEmbeddingFile<'_>,
     storage: &mut EmbeddingStorage,
     stats: &mut EmbeddingIndexStats,
 ) -> Result<()> {
-    let embed_batch_size = provider.batch_size().max(1);
-    let mut vectors: Vec<Vec<f32>> = Vec::with_capacity(file.texts.len());
-
-    for chunk in file.texts.chunks(embed_batch_size) {
-        let chunk_vectors = provider.embed_texts(chunk)?;
-        if chunk_vectors.len() != chunk.len() {
-            anyhow::bail!(
-                "Embedding provider returned {} vectors for {} inputs",
-                chunk_vectors.len(),
-                chunk.len()
-            );
-        }
-        vectors.extend(chunk_vectors);
-    }
+    let hashes: Vec<&str> = file
+        .symbols
+        .iter()
+        .map(|meta| meta.content_hash.as_str())
+        .collect();
+    let vectors = embed_with_cache(provider, cache, file.texts, &hashes, stats)?;
 
     if vectors.len() != file.symbols.len() {
         anyhow::bail!(
@@ -377,7 +537,7 @@ fn embed_large_file_symbols(
 fn index_embeddings(
     root: &Path,
     mode: EmbeddingsMode,
-    embeddings_force: bool,
+    run_options: EmbeddingRunOptions,
     config: &Config,
     index_metadata: &IndexMetadata,
 ) -> Result<EmbeddingIndexStats> {
@@ -388,8 +548,26 @@ fn index_embeddings(
     let mut stats = EmbeddingIndexStats::default();
     let mut storage = EmbeddingStorage::open_default(root)?;
 
+    // Files committed by an interrupted rebuild already carry current hashes,
+    // so resuming is an incremental pass over the rest.
+    let resuming = run_options.resume
+        && storage.is_symbol_unit()?
+        && storage.get_meta(EMBEDDING_CHECKPOINT_META)?.is_some();
+    let embeddings_force = run_options.force && !resuming;
+    if resuming {
+        println!(
+            "Resuming interrupted embedding rebuild ({} files already committed)",
+            storage.list_paths()?.len()
+        );
+    }
+
     if embeddings_force {
         storage.reset_schema()?;
+        let started_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        storage.set_meta(EMBEDDING_CHECKPOINT_META, &started_at.to_string())?;
     } else if !storage.is_symbol_unit()? {
         let message = "Embeddings DB schema mismatch (expected symbol-level). Run `cgrep index --embeddings-force` to rebuild embeddings.";
         return match mode {
@@ -413,9 +591,37 @@ fn index_embeddings(
     let _ = storage.set_meta("provider", provider_label);
     // Best-effort: record model early (dimension becomes known after first embed call).
     let _ = storage.set_meta("model", provider.model_id());
-    let batch_size = provider.batch_size().max(1);
+    let mut cache =
+        match ContentEmbeddingCache::open_for_scope(root, config.embeddings.content_cache()) {
+            Ok(cache) => cache.map(|cache| EmbeddingCacheHandle {
+                cache,
+                model_key: format!("{}/{}", provider_label, provider.model_id()),
+                read: !embeddings_force,
+                dimension: storage
+                    .get_meta("dimension")
+                    .ok()
+                    .flatten()
+                    .and_then(|raw| raw.parse().ok()),
+            }),
+            Err(err) => {
+                eprintln!("Warning: embedding cache unavailable: {}", err);
+                None
+            }
+        };
+    let batch_size = run_options
+        .batch_size
+        .unwrap_or_else(|| config.embeddings.commit_batch_size())
+        .max(provider.batch_size())
+        .max(1);
     let max_file_bytes = config.embeddings.max_file_bytes();
     let extractor = SymbolExtractor::new();
+    let chunker = EmbeddingChunker::new(
+        ChunkConfig::new(
+            config.embeddings.chunk_lines(),
+            config.embeddings.chunk_overlap(),
+        )
+        .unwrap_or_default(),
+    );
     let preview_lines = config.embeddings.symbol_preview_lines();
     let symbol_max_chars = config.embeddings.symbol_max_chars();
     let max_symbols_per_file = config.embeddings.max_symbols_per_file();
@@ -439,9 +645,11 @@ fn index_embeddings(
 
         let mut batch_texts: Vec<String> = Vec::new();
         let mut batch_entries: Vec<EmbeddingBatchEntry> = Vec::new();
+        let mut progress = EmbeddingProgress::new(index_metadata.files.len());
 
         for (path, meta) in index_metadata.files.iter() {
             stats.files_total += 1;
+            progress.maybe_report(&mut stats);
 
             // If the file is binary, ensure any old embeddings are removed.
             if meta.is_binary || meta.hash.is_empty() {
@@ -455,10 +663,10 @@ fn index_embeddings(
             }
 
             let file_path = Path::new(path);
-            let text = match read_utf8_text(file_path) {
+            let text = match read_text(file_path) {
                 Ok(Some(text)) => text,
                 Ok(None) => {
-                    // Binary/non-UTF8: ensure we don't keep stale embeddings.
+                    // Binary: ensure we don't keep stale embeddings.
                     let _ = storage.delete_file_symbols(path)?;
                     continue;
                 }
@@ -484,17 +692,43 @@ fn index_embeddings(
                 .and_then(detect_language)
                 .unwrap_or_default();
 
-            if lang_str.is_empty() {
-                storage.replace_file_symbols(path, &file_hash, last_modified, &[])?;
-                stats.files_embedded += 1;
-                continue;
-            }
-
-            let symbols = extractor.extract(&text, &lang_str).unwrap_or_default();
+            let symbols = if lang_str.is_empty() {
+                Vec::new()
+            } else {
+                extractor.extract(&text, &lang_str).unwrap_or_default()
+            };
 
-            let symbols = filter_symbols(symbols, allowed_kinds.as_ref(), max_symbols_per_file);
+            // Files without extracted symbols (docs, configs, unsupported
+            // languages) are embedded as line windows instead.
+            let units: Vec<EmbeddingUnit> = if symbols.is_empty() {
+                chunker
+                    .chunk_text(&text)
+                    .into_iter()
+                    .take(max_symbols_per_file)
+                    .map(|chunk| EmbeddingUnit::from_chunk(path, chunk, symbol_max_chars))
+                    .collect()
+            } else {
+                filter_symbols(symbols, allowed_kinds.as_ref(), max_symbols_per_file)
+                    .into_iter()
+                    .map(|symbol| {
+                        EmbeddingUnit::from_symbol(
+                            path,
+                            &lang_str,
+                            &text,
+                            &symbol,
+                            preview_lines,
+                            symbol_max_chars,
+                        )
+                    })
+                    .collect()
+            };
+            let unit_lang = if lang_str.is_empty() {
+                "text"
+            } else {
+                lang_str.as_str()
+            };
 
-            if symbols.is_empty() {
+            if units.is_empty() {
                 storage.replace_file_symbols(path, &file_hash, last_modified, &[])?;
                 stats.files_embedded += 1;
                 continue;
@@ -509,35 +743,31 @@ fn index_embeddings(
             let mut symbol_meta: Vec<SymbolEmbeddingMeta> = Vec::new();
             let mut symbol_ids: Vec<String> = Vec::new();
 
-            for symbol in symbols {
-                let symbol_id = symbol_id_for(path, &lang_str, &symbol);
-                let start_line = (symbol.line.min(u32::MAX as usize)) as u32;
-                let end_line = (symbol.end_line.min(u32::MAX as usize)) as u32;
-                let content = build_symbol_content(&text, &symbol, preview_lines, symbol_max_chars);
-                if content.is_empty() {
+            for unit in units {
+                if unit.content.is_empty() {
                     continue;
                 }
 
-                let content_hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+                let content_hash = blake3::hash(unit.content.as_bytes()).to_hex().to_string();
                 let unchanged = existing_hashes
-                    .get(&symbol_id)
+                    .get(&unit.symbol_id)
                     .map(|hash| hash == &content_hash)
                     .unwrap_or(false);
 
-                symbol_ids.push(symbol_id.clone());
+                symbol_ids.push(unit.symbol_id.clone());
 
                 if unchanged {
                     continue;
                 }
 
-                texts.push(content);
+                texts.push(unit.content);
                 symbol_meta.push(SymbolEmbeddingMeta {
-                    symbol_id,
-                    lang: lang_str.to_string(),
-                    kind: symbol.kind.to_string(),
-                    name: symbol.name.clone(),
-                    start_line,
-                    end_line,
+                    symbol_id: unit.symbol_id,
+                    lang: unit_lang.to_string(),
+                    kind: unit.kind,
+                    name: unit.name,
+                    start_line: unit.start_line,
+                    end_line: unit.end_line,
                     content_hash,
                 });
             }
@@ -557,6 +787,7 @@ fn index_embeddings(
             if texts.len() > batch_size {
                 flush_embedding_batch(
                     provider.as_mut(),
+                    cache.as_mut(),
                     &mut batch_texts,
                     &mut batch_entries,
                     &mut storage,
@@ -564,6 +795,7 @@ fn index_embeddings(
                 )?;
                 embed_large_file_symbols(
                     provider.as_mut(),
+                    cache.as_mut(),
                     LargeEmbeddingFile {
                         path,
                         file_hash: &file_hash,
@@ -582,6 +814,7 @@ fn index_embeddings(
             if !batch_texts.is_empty() && batch_texts.len() + texts.len() > batch_size {
                 flush_embedding_batch(
                     provider.as_mut(),
+                    cache.as_mut(),
                     &mut batch_texts,
                     &mut batch_entries,
                     &mut storage,
@@ -605,11 +838,14 @@ fn index_embeddings(
 
         flush_embedding_batch(
             provider.as_mut(),
+            cache.as_mut(),
             &mut batch_texts,
             &mut batch_entries,
             &mut storage,
             &mut stats,
         )?;
+        stats.elapsed = progress.started.elapsed();
+        storage.delete_meta(EMBEDDING_CHECKPOINT_META)?;
 
         Ok(())
     })();
@@ -634,6 +870,71 @@ struct IndexMetadata {
     index_options: Option<StoredIndexOptions>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     manifest_diff: Option<ManifestDiffSummary>,
+    /// Scope flags from the last `cgrep index` run that set them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<IndexScope>,
+    /// Commits per root-relative file path over the churn window.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    churn: HashMap<String, u32>,
+    /// Last commit time and authors per root-relative file path.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    git_history: HashMap<String, FileHistory>,
+    /// Schema migrations applied since the index was last built from scratch.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    migrations: Vec<AppliedMigration>,
+    /// Index format and the oldest formats that may read or update it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    compat: Option<IndexCompat>,
+}
+
+/// The compatibility stamp alone, checked before every read and write.
+#[derive(Debug, Default, Deserialize)]
+struct CompatMetadata {
+    #[serde(default)]
+    compat: Option<IndexCompat>,
+}
+
+/// The churn table alone, so ranking does not parse every file entry.
+#[derive(Debug, Default, Deserialize)]
+struct ChurnMetadata {
+    #[serde(default)]
+    churn: HashMap<String, u32>,
+}
+
+/// The git history table alone, for `--modified-since` and `--author`.
+#[derive(Debug, Default, Deserialize)]
+struct GitHistoryMetadata {
+    #[serde(default)]
+    git_history: HashMap<String, FileHistory>,
+}
+
+/// Scoping flags given to `cgrep index` (CLI only; config excludes are not included).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct IndexScope {
+    pub exclude_paths: Vec<String>,
+    pub include_paths: Vec<String>,
+    pub include_ignored: bool,
+    #[serde(skip_serializing_if = "IgnoreRules::is_default")]
+    pub ignore_rules: IgnoreRules,
+}
+
+impl IndexScope {
+    fn from_options(options: &RunOptions) -> Self {
+        Self {
+            exclude_paths: options.excludes.clone(),
+            include_paths: options.include_paths.clone(),
+            include_ignored: options.include_ignored,
+            ignore_rules: options.ignore_rules,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.exclude_paths.is_empty()
+            && self.include_paths.is_empty()
+            && !self.include_ignored
+            && self.ignore_rules.is_default()
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -644,6 +945,32 @@ struct FileMetadata {
     hash: String,
     symbols: String,
     is_binary: bool,
+    #[serde(flatten)]
+    format: TextFormat,
+}
+
+/// How a text file is stored on disk; indexed text is UTF-8 with LF breaks
+/// and no BOM.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct TextFormat {
+    /// Source encoding when transcoded to UTF-8.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<String>,
+    #[serde(skip_serializing_if = "LineEnding::is_lf")]
+    line_ending: LineEnding,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    bom: bool,
+}
+
+impl TextFormat {
+    fn of(decoded: &Decoded<'_>) -> Self {
+        Self {
+            encoding: decoded.encoding.map(str::to_string),
+            line_ending: decoded.line_ending,
+            bom: decoded.bom,
+        }
+    }
 }
 
 impl FileMetadata {
@@ -686,6 +1013,56 @@ fn load_index_metadata(root: &Path) -> Option<IndexMetadata> {
     serde_json::from_str(&content).ok()
 }
 
+/// Absolute paths recorded in the index metadata, if any.
+pub(crate) fn indexed_file_paths(root: &Path) -> Option<Vec<String>> {
+    load_index_metadata(root).map(|metadata| metadata.files.into_keys().collect())
+}
+
+/// Source encoding the index recorded for `path`, if it was transcoded and
+/// `bytes` are still what was indexed.
+pub(crate) fn indexed_encoding(path: &Path, bytes: &[u8]) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let index_root = cgrep::utils::find_index_root(path.parent()?)?;
+    let mut metadata = load_index_metadata(&index_root.root)?;
+    let meta = metadata.files.remove(&paths::encode_path(&path))?;
+    (meta.hash == blake3::hash(bytes).to_hex().as_str())
+        .then_some(meta.format.encoding)
+        .flatten()
+}
+
+/// Compatibility stamp of the index under `root`, if it has one.
+pub(crate) fn index_compat(root: &Path) -> Option<IndexCompat> {
+    std::fs::read_to_string(root.join(METADATA_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<CompatMetadata>(&content).ok())
+        .and_then(|metadata| metadata.compat)
+}
+
+/// Schema migrations recorded since the last full build.
+pub(crate) fn applied_migrations(root: &Path) -> Vec<AppliedMigration> {
+    load_index_metadata(root)
+        .map(|metadata| metadata.migrations)
+        .unwrap_or_default()
+}
+
+/// Per-file commit counts recorded by the last index build.
+pub(crate) fn indexed_churn(root: &Path) -> HashMap<String, u32> {
+    std::fs::read_to_string(root.join(METADATA_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<ChurnMetadata>(&content).ok())
+        .map(|metadata| metadata.churn)
+        .unwrap_or_default()
+}
+
+/// Per-file git history recorded by the last index build.
+pub(crate) fn indexed_git_history(root: &Path) -> HashMap<String, FileHistory> {
+    std::fs::read_to_string(root.join(METADATA_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<GitHistoryMetadata>(&content).ok())
+        .map(|metadata| metadata.git_history)
+        .unwrap_or_default()
+}
+
 fn save_index_metadata(root: &Path, metadata: &IndexMetadata) -> Result<()> {
     let metadata_path = root.join(METADATA_FILE);
     let metadata_json = serde_json::to_string_pretty(metadata)?;
@@ -703,7 +1080,10 @@ pub(crate) fn resolve_index_options_for_watch(root: &Path, config: &Config) -> S
         exclude_paths: config.index().exclude_paths().to_vec(),
         include_paths: Vec::new(),
         respect_git_ignore: config.index().respect_git_ignore(),
+        ignore_rules: IgnoreRules::default(),
         high_memory: false,
+        archives: ArchiveOptions::from_config(config),
+        size_policies: SizePolicy::from_config(config),
     }
 }
 
@@ -716,15 +1096,16 @@ const DEFAULT_SYMBOL_PREVIEW_LINES: usize = 12;
 #[allow(dead_code)]
 const DEFAULT_SYMBOL_MAX_CHARS: usize = 1200;
 
-struct TextChunk {
-    start_line: u64,
-    content: String,
+pub(crate) struct TextChunk {
+    pub start_line: u64,
+    pub content: String,
 }
 
 enum ReadOutcome {
     Text {
         chunks: Vec<TextChunk>,
         hash: String,
+        format: TextFormat,
     },
     Binary {
         hash: Option<String>,
@@ -742,18 +1123,17 @@ fn read_text_chunks(path: &Path, max_doc_bytes: usize) -> Result<ReadOutcome> {
 }
 
 fn read_text_chunks_from_bytes(bytes: &[u8], max_doc_bytes: usize) -> Result<ReadOutcome> {
-    if bytes.contains(&0) {
+    let Some(decoded) = encoding::decode_text(bytes) else {
         return Ok(ReadOutcome::Binary { hash: None });
-    }
-
-    let text = match std::str::from_utf8(bytes) {
-        Ok(text) => text,
-        Err(_) => return Ok(ReadOutcome::Binary { hash: None }),
     };
 
     let hash = blake3::hash(bytes).to_hex().to_string();
-    let chunks = build_chunks(text, max_doc_bytes);
-    Ok(ReadOutcome::Text { chunks, hash })
+    let chunks = build_chunks(&decoded.text, max_doc_bytes);
+    Ok(ReadOutcome::Text {
+        chunks,
+        hash,
+        format: TextFormat::of(&decoded),
+    })
 }
 
 fn build_chunks(text: &str, max_doc_bytes: usize) -> Vec<TextChunk> {
@@ -816,14 +1196,26 @@ fn join_chunks(chunks: &[TextChunk]) -> String {
     text
 }
 
-fn extract_symbols_from_text(text: &str, lang: &str) -> Vec<Symbol> {
+pub(crate) fn extract_symbols_from_text(text: &str, lang: &str) -> Vec<Symbol> {
     let extractor = SymbolExtractor::new();
     extractor.extract(text, lang).unwrap_or_default()
 }
 
+/// Doc comments of the symbols declared inside `chunk`, one per paragraph.
+pub(crate) fn chunk_doc_comments(chunk: &TextChunk, symbols: &[Symbol]) -> String {
+    let first = chunk.start_line as usize;
+    let last = first + chunk.content.lines().count();
+    symbols
+        .iter()
+        .filter(|symbol| (first..last).contains(&symbol.line))
+        .filter_map(|symbol| symbol.doc.as_deref())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 fn extract_symbol_names(symbols: &[Symbol]) -> String {
     let mut seen = HashSet::new();
-    for symbol in symbols {
+    for symbol in symbols.iter().filter(|symbol| !symbol.is_anonymous()) {
         seen.insert(symbol.name.clone());
     }
     seen.into_iter().collect::<Vec<_>>().join(" ")
@@ -833,10 +1225,10 @@ fn symbol_priority(kind: &SymbolKind) -> u8 {
     match kind {
         SymbolKind::Function | SymbolKind::Method => 0,
         SymbolKind::Class | SymbolKind::Struct | SymbolKind::Trait | SymbolKind::Interface => 1,
-        SymbolKind::Enum | SymbolKind::Module | SymbolKind::Type => 2,
-        SymbolKind::Property | SymbolKind::Constant => 3,
+        SymbolKind::Enum | SymbolKind::Module | SymbolKind::Type | SymbolKind::Macro => 2,
+        SymbolKind::Property | SymbolKind::Constant | SymbolKind::Key => 3,
         SymbolKind::Variable => 4,
-        SymbolKind::Unknown => 5,
+        SymbolKind::Closure | SymbolKind::Unknown => 5,
     }
 }
 
@@ -866,13 +1258,64 @@ fn filter_symbols(
                 .then_with(|| a.0.cmp(&b.0))
         });
         filtered.truncate(max_symbols);
-        filtered.sort_by(|a, b| a.0.cmp(&b.0));
+        filtered.sort_by_key(|a| a.0);
     }
 
     filtered.into_iter().map(|(_, symbol, _)| symbol).collect()
 }
 
-fn symbol_id_for(path: &str, lang: &str, symbol: &Symbol) -> String {
+/// One embedded span of a file: an extracted symbol or a line-window chunk.
+struct EmbeddingUnit {
+    symbol_id: String,
+    kind: String,
+    name: String,
+    start_line: u32,
+    end_line: u32,
+    content: String,
+}
+
+impl EmbeddingUnit {
+    fn from_symbol(
+        path: &str,
+        lang: &str,
+        source: &str,
+        symbol: &Symbol,
+        preview_lines: usize,
+        max_chars: usize,
+    ) -> Self {
+        Self {
+            symbol_id: symbol_id_for(path, lang, symbol),
+            kind: symbol.kind.to_string(),
+            name: symbol.name.clone(),
+            start_line: (symbol.line.min(u32::MAX as usize)) as u32,
+            end_line: (symbol.end_line.min(u32::MAX as usize)) as u32,
+            content: build_symbol_content(source, symbol, preview_lines, max_chars),
+        }
+    }
+
+    /// Chunks are named after their first non-blank line, which doubles as the
+    /// semantic-search snippet.
+    fn from_chunk(path: &str, chunk: cgrep::embedding::TextChunk, max_chars: usize) -> Self {
+        let name = chunk
+            .text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(|line| truncate_to_chars(line, 120))
+            .unwrap_or_default();
+        let input = format!("{}:chunk:{}:{}", path, chunk.start_line, chunk.end_line);
+        Self {
+            symbol_id: blake3::hash(input.as_bytes()).to_hex().to_string(),
+            kind: CHUNK_SYMBOL_KIND.to_string(),
+            name,
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            content: truncate_to_chars(&chunk.text, max_chars),
+        }
+    }
+}
+
+pub(crate) fn symbol_id_for(path: &str, lang: &str, symbol: &Symbol) -> String {
     let range = if let (Some(start), Some(end)) = (symbol.byte_start, symbol.byte_end) {
         format!("{}:{}", start, end)
     } else {
@@ -886,6 +1329,17 @@ fn symbol_id_for(path: &str, lang: &str, symbol: &Symbol) -> String {
     hash.to_hex().to_string()
 }
 
+/// Store the metrics of a function-like symbol on its document.
+pub(crate) fn add_symbol_metrics(
+    fields: &IndexFields,
+    doc: &mut TantivyDocument,
+    metrics: &SymbolMetrics,
+) {
+    doc.add_u64(fields.symbol_params, metrics.params as u64);
+    doc.add_u64(fields.symbol_nesting, metrics.nesting as u64);
+    doc.add_u64(fields.symbol_complexity, metrics.complexity as u64);
+}
+
 fn build_symbol_preview(source: &str, symbol: &Symbol, preview_lines: usize) -> String {
     let lines: Vec<&str> = source.lines().collect();
     if lines.is_empty() {
@@ -952,6 +1406,23 @@ fn should_skip_without_read(
     None
 }
 
+/// Metadata for an archive whose bytes still hash as indexed, refreshed
+/// with its current `mtime` and `size`, so it need not be expanded again.
+fn unchanged_archive(
+    path: &Path,
+    existing_meta: Option<&FileMetadata>,
+    mtime: u64,
+    size: u64,
+) -> Option<FileMetadata> {
+    let meta = existing_meta.filter(|meta| !meta.hash.is_empty())?;
+    let hash = manifest::hash_file_streaming(path).ok()?;
+    (hash == meta.hash).then(|| FileMetadata {
+        mtime,
+        size,
+        ..meta.clone()
+    })
+}
+
 fn path_matches_exclude_patterns(path: &Path, patterns: &[String]) -> bool {
     if patterns.is_empty() {
         return false;
@@ -979,11 +1450,107 @@ impl Default for ManifestBuildOptions {
     }
 }
 
+/// Remove everything under `.cgrep` except the index lock held by this run
+/// and the file stale-lock takeovers lock.
+fn clear_index_dir_except_lock(index_path: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(index_path)? {
+        let entry = entry?;
+        if entry.file_name() == lock::LOCK_FILE_NAME
+            || entry.file_name() == lock::TAKEOVER_FILE_NAME
+        {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Tantivy segment files are named `<32 hex uuid>.<ext>` (deletes: `<uuid>.<opstamp>.del`).
+pub(crate) fn is_segment_file_name(name: &str) -> bool {
+    let Some((stem, ext)) = name.split_once('.') else {
+        return false;
+    };
+    !ext.is_empty() && stem.len() == 32 && stem.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Non-segment entries under `.cgrep` that belong to one built index.
+const INDEX_ARTIFACT_NAMES: &[&str] = &[
+    "meta.json",
+    ".managed.json",
+    "metadata.json",
+    "manifest",
+    "embeddings.sqlite",
+];
+
+/// Names of the index's own entries (segments, Tantivy and cgrep metadata,
+/// manifest, embeddings) directly under `dir`; runtime state is left out.
+pub(crate) fn index_artifact_names(dir: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(names);
+    };
+    for entry in entries {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if INDEX_ARTIFACT_NAMES.contains(&name.as_str()) || is_segment_file_name(&name) {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+/// Promote a fully committed staging index into `index_path`.
+///
+/// New segment files are moved in first; renaming `meta.json` is the commit
+/// point, so a crash before it leaves the previous index untouched and a
+/// crash after it leaves the new one. Old segments are removed last.
+pub(crate) fn swap_staged_index(index_path: &Path, staging_path: &Path) -> Result<()> {
+    let mut staged_segments: HashSet<String> = HashSet::new();
+    for entry in std::fs::read_dir(staging_path)? {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if !is_segment_file_name(&name) {
+            continue;
+        }
+        std::fs::rename(entry.path(), index_path.join(&name))
+            .with_context(|| format!("Failed to move staged segment {}", name))?;
+        staged_segments.insert(name);
+    }
+
+    std::fs::rename(staging_path.join("meta.json"), index_path.join("meta.json"))
+        .context("Failed to swap staged index meta.json")?;
+    let managed = staging_path.join(".managed.json");
+    if managed.exists() {
+        std::fs::rename(&managed, index_path.join(".managed.json"))
+            .context("Failed to swap staged index .managed.json")?;
+    }
+
+    for entry in std::fs::read_dir(index_path)? {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if is_segment_file_name(&name) && !staged_segments.contains(&name) {
+            // Best effort: readers of the previous index may still hold files open.
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+    let _ = std::fs::remove_dir_all(staging_path);
+    Ok(())
+}
+
 fn to_absolute_path(root: &Path, rel: &str) -> PathBuf {
-    if Path::new(rel).is_absolute() {
-        PathBuf::from(rel)
+    let path = paths::decode_path(rel);
+    if path.is_absolute() {
+        path
     } else {
-        root.join(rel)
+        root.join(path)
     }
 }
 
@@ -1015,6 +1582,44 @@ fn manifest_from_metadata(
     manifest::Manifest { entries }
 }
 
+/// Result of `cgrep index --dry-run`.
+#[derive(Debug, Clone)]
+pub struct DryRunEstimate {
+    pub summary: ManifestDiffSummary,
+    /// Whether the run would rebuild everything (forced or no index yet).
+    pub full_rebuild: bool,
+    pub files_to_index: usize,
+    pub bytes_to_read: u64,
+}
+
+fn print_dry_run(estimate: &DryRunEstimate, print_diff: bool) {
+    let summary = &estimate.summary;
+    if print_diff {
+        print_manifest_diff(summary);
+    } else {
+        println!(
+            "Manifest diff: {} added, {} modified, {} deleted ({} unchanged, {} scanned)",
+            summary.added.len(),
+            summary.modified.len(),
+            summary.deleted.len(),
+            summary.unchanged,
+            summary.scanned
+        );
+    }
+    println!(
+        "Estimated work: {} files to index ({:.1} MiB to read), {} to remove{}",
+        estimate.files_to_index,
+        estimate.bytes_to_read as f64 / (1024.0 * 1024.0),
+        summary.deleted.len(),
+        if estimate.full_rebuild {
+            " [full rebuild]"
+        } else {
+            ""
+        }
+    );
+    println!("{} Dry run: nothing was written", "✓".green());
+}
+
 fn print_manifest_diff(summary: &ManifestDiffSummary) {
     println!(
         "Manifest diff: {} added, {} modified, {} deleted ({} unchanged, {} scanned, {} suspects, {} hashed)",
@@ -1059,6 +1664,12 @@ pub struct IndexFields {
     pub symbol_end_line: Field,
     #[allow(dead_code)]
     pub line_number: Field,
+    /// Doc comments of the symbols a document covers.
+    pub docs: Field,
+    /// Metrics of function-like symbols; line counts come from the line range.
+    pub symbol_params: Field,
+    pub symbol_nesting: Field,
+    pub symbol_complexity: Field,
 }
 
 /// Build search index
@@ -1069,11 +1680,15 @@ pub struct IndexBuilder {
     exclude_patterns: Vec<String>,
     include_paths: Vec<String>,
     respect_git_ignore: bool,
+    ignore_rules: IgnoreRules,
     high_memory: bool,
+    archives: ArchiveOptions,
+    size_policies: SizePolicies,
     symbol_preview_lines: usize,
     symbol_max_chars: usize,
     max_symbols_per_file: usize,
     allowed_symbol_kinds: Option<HashSet<String>>,
+    scope: Option<IndexScope>,
 }
 
 impl IndexBuilder {
@@ -1082,7 +1697,10 @@ impl IndexBuilder {
             exclude_paths: self.exclude_patterns.clone(),
             include_paths: self.include_paths.clone(),
             respect_git_ignore: self.respect_git_ignore,
+            ignore_rules: self.ignore_rules,
             high_memory: self.high_memory,
+            archives: self.archives.clone(),
+            size_policies: self.size_policies.policies().to_vec(),
         }
     }
 
@@ -1111,7 +1729,10 @@ impl IndexBuilder {
             exclude_paths,
             include_paths,
             respect_git_ignore,
+            ignore_rules,
             high_memory,
+            archives,
+            size_policies,
         } = index_options;
         let SymbolIndexOptions {
             symbol_preview_lines,
@@ -1121,42 +1742,326 @@ impl IndexBuilder {
         } = symbol_options;
         let mut schema_builder = Schema::builder();
 
-        let path = schema_builder.add_text_field("path", TEXT | STORED);
-        let path_exact = schema_builder.add_text_field("path_exact", STRING | STORED);
-        let content = schema_builder.add_text_field("content", TEXT | STORED);
-        let language = schema_builder.add_text_field("language", TEXT | STORED);
-        let symbols = schema_builder.add_text_field("symbols", TEXT | STORED);
-        let doc_type = schema_builder.add_text_field("doc_type", STRING | STORED);
-        let symbol_id = schema_builder.add_text_field("symbol_id", STRING | STORED);
-        let symbol_end_line = schema_builder.add_u64_field("symbol_end_line", STORED);
-        let line_number =
-            schema_builder.add_u64_field("line_number", tantivy::schema::INDEXED | STORED);
+        let path = schema_builder.add_text_field("path", TEXT | STORED);
+        let path_exact = schema_builder.add_text_field("path_exact", STRING | STORED);
+        let content = schema_builder.add_text_field("content", TEXT | STORED);
+        let language = schema_builder.add_text_field("language", TEXT | STORED);
+        let symbols = schema_builder.add_text_field("symbols", TEXT | STORED);
+        let doc_type = schema_builder.add_text_field("doc_type", STRING | STORED);
+        let symbol_id = schema_builder.add_text_field("symbol_id", STRING | STORED);
+        let symbol_end_line = schema_builder.add_u64_field("symbol_end_line", STORED);
+        let line_number =
+            schema_builder.add_u64_field("line_number", tantivy::schema::INDEXED | STORED);
+        let docs = schema_builder.add_text_field("docs", TEXT | STORED);
+        let symbol_params = schema_builder.add_u64_field("symbol_params", STORED);
+        let symbol_nesting = schema_builder.add_u64_field("symbol_nesting", STORED);
+        let symbol_complexity = schema_builder.add_u64_field("symbol_complexity", STORED);
+
+        let schema = schema_builder.build();
+        let fields = IndexFields {
+            path,
+            path_exact,
+            content,
+            language,
+            symbols,
+            doc_type,
+            symbol_id,
+            symbol_end_line,
+            line_number,
+            docs,
+            symbol_params,
+            symbol_nesting,
+            symbol_complexity,
+        };
+
+        Ok(Self {
+            root: root.as_ref().to_path_buf(),
+            schema,
+            fields,
+            exclude_patterns: exclude_paths,
+            include_paths,
+            respect_git_ignore,
+            ignore_rules,
+            high_memory,
+            archives,
+            size_policies: SizePolicies::new(size_policies),
+            symbol_preview_lines,
+            symbol_max_chars,
+            max_symbols_per_file,
+            allowed_symbol_kinds,
+            scope: None,
+        })
+    }
+
+    /// Record the CLI scope in metadata.json (otherwise the stored scope is kept).
+    pub(crate) fn with_scope(mut self, scope: IndexScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Whether an existing index lacks a field this build writes (e.g. one
+    /// created before `docs` existed); such an index is rebuilt, not updated.
+    fn index_schema_outdated(&self, index_path: &Path) -> bool {
+        if !index_path.join("meta.json").exists() {
+            return false;
+        }
+        let Ok(index) = Index::open_in_dir(index_path) else {
+            return false;
+        };
+        let existing = index.schema();
+        self.schema
+            .fields()
+            .any(|(_, entry)| existing.get_field(entry.name()).is_err())
+    }
+
+    /// Migrate an outdated index to this build's schema in place.
+    ///
+    /// `false` when the index has to be rebuilt instead: it predates every
+    /// migration, or the migration failed.
+    fn upgrade_index_schema(&self, index_path: &Path, writer_budget_bytes: usize) -> bool {
+        if !self.index_schema_outdated(index_path) {
+            return true;
+        }
+        match migrate::upgrade(index_path, &self.schema, &self.fields, writer_budget_bytes) {
+            Ok(Some(applied)) => {
+                for step in &applied {
+                    eprintln!(
+                        "Migrated index schema v{} -> v{}: {}",
+                        step.from, step.to, step.description
+                    );
+                }
+                if let Some(mut metadata) = load_index_metadata(&self.root) {
+                    metadata.migrations.extend(applied);
+                    metadata.compat = Some(IndexCompat::current());
+                    if let Err(err) = save_index_metadata(&self.root, &metadata) {
+                        eprintln!("Warning: failed to record index migration: {}", err);
+                    }
+                }
+                true
+            }
+            Ok(None) => {
+                eprintln!("Index schema changed since the last build; rebuilding the index.");
+                false
+            }
+            Err(err) => {
+                eprintln!(
+                    "Warning: index schema migration failed ({}); rebuilding the index.",
+                    err
+                );
+                false
+            }
+        }
+    }
+
+    fn persisted_scope(&self) -> Option<IndexScope> {
+        self.scope
+            .clone()
+            .or_else(|| load_index_metadata(&self.root).and_then(|metadata| metadata.scope))
+            .filter(|scope| !scope.is_empty())
+    }
+
+    /// File, symbol, and embedded-region documents for one text source, plus
+    /// its symbol names.
+    ///
+    /// `doc_path` is the path shown in results; `exact_path` is the on-disk
+    /// file the documents are keyed and deleted by. They differ only for
+    /// archive entries.
+    ///
+    /// Under a size policy the contents are replaced by a single file
+    /// document without content, and symbol documents (if kept) carry only
+    /// their name and kind.
+    fn text_documents(
+        &self,
+        doc_path: &str,
+        exact_path: &str,
+        chunks: &[TextChunk],
+        lang: &str,
+        size_policy: Option<SizePolicyMode>,
+    ) -> (Vec<TantivyDocument>, String) {
+        let fields = &self.fields;
+        let full_text = join_chunks(chunks);
+        let symbol_list = if !lang.is_empty() && size_policy != Some(SizePolicyMode::Path) {
+            extract_symbols_from_text(&full_text, lang)
+        } else {
+            Vec::new()
+        };
+        let symbols = extract_symbol_names(&symbol_list);
+        let chunk_docs: Vec<String> = match size_policy {
+            Some(_) => Vec::new(),
+            None => chunks
+                .iter()
+                .map(|chunk| chunk_doc_comments(chunk, &symbol_list))
+                .collect(),
+        };
+        // Config keys are only reachable through their symbol documents so
+        // the file-level symbol field (and its ranking stats) stays code-only.
+        let file_symbols = if config_keys::is_config_language(lang) {
+            ""
+        } else {
+            symbols.as_str()
+        };
+        let symbol_docs = filter_symbols(
+            symbol_list,
+            self.allowed_symbol_kinds.as_ref(),
+            self.max_symbols_per_file,
+        );
+
+        let mut docs: Vec<TantivyDocument> = Vec::with_capacity(chunks.len() + symbol_docs.len());
+        if size_policy.is_some() {
+            let mut doc = TantivyDocument::default();
+            doc.add_text(fields.path, doc_path);
+            doc.add_text(fields.path_exact, exact_path);
+            doc.add_text(fields.content, "");
+            doc.add_text(fields.language, lang);
+            doc.add_text(fields.symbols, file_symbols);
+            doc.add_text(fields.doc_type, "file");
+            doc.add_u64(fields.line_number, 1);
+            docs.push(doc);
+        }
+        for (chunk, chunk_docs) in chunks.iter().zip(&chunk_docs) {
+            let mut doc = TantivyDocument::default();
+            doc.add_text(fields.path, doc_path);
+            doc.add_text(fields.path_exact, exact_path);
+            doc.add_text(fields.content, &chunk.content);
+            doc.add_text(fields.language, lang);
+            doc.add_text(fields.symbols, file_symbols);
+            doc.add_text(fields.doc_type, "file");
+            doc.add_u64(fields.line_number, chunk.start_line);
+            if !chunk_docs.is_empty() {
+                doc.add_text(fields.docs, chunk_docs);
+            }
+            docs.push(doc);
+        }
+
+        for symbol in &symbol_docs {
+            let symbol_id = symbol_id_for(doc_path, lang, symbol);
+            let content = match size_policy {
+                Some(_) => truncate_to_chars(
+                    &format!("{} {}", symbol.name, symbol.kind),
+                    self.symbol_max_chars,
+                ),
+                None => build_symbol_content(
+                    &full_text,
+                    symbol,
+                    self.symbol_preview_lines,
+                    self.symbol_max_chars,
+                ),
+            };
+            if content.is_empty() {
+                continue;
+            }
+
+            let mut doc = TantivyDocument::default();
+            doc.add_text(fields.path, doc_path);
+            doc.add_text(fields.path_exact, exact_path);
+            doc.add_text(fields.content, &content);
+            doc.add_text(fields.language, lang);
+            doc.add_text(fields.symbols, &symbol.name);
+            doc.add_text(fields.doc_type, "symbol");
+            doc.add_text(fields.symbol_id, &symbol_id);
+            doc.add_u64(fields.line_number, symbol.line as u64);
+            doc.add_u64(fields.symbol_end_line, symbol.end_line as u64);
+            if let Some(symbol_doc) = symbol.doc.as_ref().filter(|_| size_policy.is_none()) {
+                doc.add_text(fields.docs, symbol_doc);
+            }
+            if let Some(metrics) = &symbol.metrics {
+                add_symbol_metrics(fields, &mut doc, metrics);
+            }
+            docs.push(doc);
+        }
+
+        if size_policy.is_some() {
+            return (docs, symbols);
+        }
+
+        for region in regions::extract_regions(&full_text, lang) {
+            let mut doc = TantivyDocument::default();
+            doc.add_text(fields.path, doc_path);
+            doc.add_text(fields.path_exact, exact_path);
+            doc.add_text(fields.content, &region.content);
+            doc.add_text(fields.language, region.language);
+            doc.add_text(fields.doc_type, "region");
+            doc.add_u64(fields.line_number, region.start_line);
+            docs.push(doc);
+        }
+
+        for literal in literals::extract_literals(&full_text, lang) {
+            let mut doc = TantivyDocument::default();
+            doc.add_text(fields.path, doc_path);
+            doc.add_text(fields.path_exact, exact_path);
+            doc.add_text(fields.content, &literal.value);
+            doc.add_text(fields.language, lang);
+            doc.add_text(
+                fields.symbols,
+                literal.constant.as_deref().unwrap_or_default(),
+            );
+            doc.add_text(fields.doc_type, "literal");
+            doc.add_u64(fields.line_number, literal.line);
+            docs.push(doc);
+        }
+
+        (docs, symbols)
+    }
+
+    /// Documents for every text entry of an archive, keyed by the archive path.
+    ///
+    /// Returns the documents, the archive hash, and the entries' symbol names.
+    fn archive_documents(
+        &self,
+        path: &Path,
+        path_str: &str,
+    ) -> Result<(Vec<TantivyDocument>, String, String)> {
+        let contents = archive::read_archive(path, &self.archives)?;
+        let mut docs = Vec::new();
+        let mut symbols: Vec<String> = Vec::new();
+        for entry in &contents.entries {
+            let chunks = build_chunks(&entry.text, MAX_DOC_BYTES);
+            let virtual_path = archive::virtual_path(path_str, &entry.path);
+            let lang = entry.language.as_deref().unwrap_or_default();
+            let (entry_docs, entry_symbols) =
+                self.text_documents(&virtual_path, path_str, &chunks, lang, None);
+            docs.extend(entry_docs);
+            if !entry_symbols.is_empty() {
+                symbols.push(entry_symbols);
+            }
+        }
+        Ok((docs, contents.hash, symbols.join(" ")))
+    }
+
+    /// Scan and diff against the stored manifest without writing anything.
+    pub fn dry_run(&self, force: bool) -> Result<DryRunEstimate> {
+        let files = FileScanner::with_excludes(&self.root, self.exclude_patterns.clone())
+            .with_includes(self.include_paths.clone())
+            .with_gitignore(self.respect_git_ignore)
+            .with_ignore_rules(self.ignore_rules)
+            .with_archives(self.archives.scan_extensions())
+            .list_files()?;
+        let files = sparse::drop_sparse_excluded(&self.root, files);
+        let old_manifest = manifest::load_manifest(&self.root);
+        let diff = manifest::compute_manifest_diff(&self.root, &files, old_manifest.as_ref())?;
 
-        let schema = schema_builder.build();
-        let fields = IndexFields {
-            path,
-            path_exact,
-            content,
-            language,
-            symbols,
-            doc_type,
-            symbol_id,
-            symbol_end_line,
-            line_number,
-        };
+        let full_rebuild = force || !self.root.join(INDEX_DIR).join("meta.json").exists();
+        let pending: HashSet<&str> = diff
+            .summary
+            .added
+            .iter()
+            .chain(diff.summary.modified.iter())
+            .map(String::as_str)
+            .collect();
+        let (files_to_index, bytes_to_read) = diff
+            .next
+            .entries
+            .iter()
+            .filter(|entry| full_rebuild || pending.contains(entry.path.as_str()))
+            .fold((0usize, 0u64), |(count, bytes), entry| {
+                (count + 1, bytes + entry.size)
+            });
 
-        Ok(Self {
-            root: root.as_ref().to_path_buf(),
-            schema,
-            fields,
-            exclude_patterns: exclude_paths,
-            include_paths,
-            respect_git_ignore,
-            high_memory,
-            symbol_preview_lines,
-            symbol_max_chars,
-            max_symbols_per_file,
-            allowed_symbol_kinds,
+        Ok(DryRunEstimate {
+            summary: diff.summary,
+            full_rebuild,
+            files_to_index,
+            bytes_to_read,
         })
     }
 
@@ -1201,6 +2106,9 @@ impl IndexBuilder {
 
         let index_path = self.root.join(INDEX_DIR);
         let metadata_path = self.root.join(METADATA_FILE);
+        let persisted_scope = self.persisted_scope();
+        compat::check_writable(&self.root, force)?;
+        let force = force || !self.upgrade_index_schema(&index_path, writer_budget_bytes);
 
         // Load existing metadata if not forcing rebuild
         let old_metadata = if !force && metadata_path.exists() {
@@ -1212,12 +2120,13 @@ impl IndexBuilder {
 
         let scanner = FileScanner::with_excludes(&self.root, self.exclude_patterns.clone())
             .with_includes(self.include_paths.clone())
-            .with_gitignore(self.respect_git_ignore);
-        let files = scanner.list_files()?;
-        let current_paths: HashSet<String> = files
-            .iter()
-            .map(|path| path.to_string_lossy().to_string())
-            .collect();
+            .with_gitignore(self.respect_git_ignore)
+            .with_ignore_rules(self.ignore_rules)
+            .with_archives(self.archives.scan_extensions());
+        // Stale copies of sparse-excluded files are treated as deleted.
+        let files = sparse::drop_sparse_excluded(&self.root, scanner.list_files()?);
+        let current_paths: HashSet<String> =
+            files.iter().map(|path| paths::encode_path(path)).collect();
         let total_files = files.len();
 
         let mut manifest_diff = ManifestDiffSummary {
@@ -1254,6 +2163,7 @@ impl IndexBuilder {
                     let mut metadata = old_metadata;
                     metadata.index_options = Some(self.stored_index_options());
                     metadata.manifest_diff = Some(manifest_diff);
+                    metadata.scope = persisted_scope;
                     save_index_metadata(&self.root, &metadata)?;
                     println!(
                         "{} Manifest updated ({} scanned, {} unchanged, {} added, {} modified, {} deleted)",
@@ -1320,6 +2230,7 @@ impl IndexBuilder {
             },
         }
 
+        let history = git_history::repo_history(&self.root);
         let mut new_metadata = IndexMetadata {
             files: if use_manifest && !force {
                 old_metadata.files.clone()
@@ -1328,6 +2239,15 @@ impl IndexBuilder {
             },
             index_options: Some(self.stored_index_options()),
             manifest_diff: None,
+            scope: persisted_scope,
+            churn: history.commit_counts,
+            git_history: history.files,
+            migrations: if force {
+                Vec::new()
+            } else {
+                old_metadata.migrations.clone()
+            },
+            compat: Some(IndexCompat::current()),
         };
         let mut indexed_count = 0usize;
         let mut skipped_count = if use_manifest && !force {
@@ -1351,9 +2271,26 @@ impl IndexBuilder {
 
         // Check if valid index exists (has meta.json from tantivy)
         let index_meta_exists = index_path.join("meta.json").exists();
+        let staging_path = index_path.join(STAGING_DIR_NAME);
+        // Leftovers from an interrupted rebuild are never valid.
+        if staging_path.exists() {
+            std::fs::remove_dir_all(&staging_path).with_context(|| {
+                format!(
+                    "Failed to remove stale staging index at {}",
+                    staging_path.display()
+                )
+            })?;
+        }
+        // Force rebuilds write into a staging directory so the live index stays
+        // queryable (and intact after a crash) until the final swap.
+        let use_staging = force && index_meta_exists;
 
         // Open existing index or create new one
-        let index = if index_meta_exists && !force {
+        let index = if use_staging {
+            std::fs::create_dir_all(&staging_path)?;
+            Index::create_in_dir(&staging_path, self.schema.clone())
+                .context("Failed to create staging index")?
+        } else if index_meta_exists && !force {
             let index = Index::open_in_dir(&index_path).context("Failed to open existing index")?;
             let schema = index.schema();
             if schema.get_field("path_exact").is_err()
@@ -1368,10 +2305,7 @@ impl IndexBuilder {
             }
             index
         } else {
-            if index_path.exists() {
-                std::fs::remove_dir_all(&index_path)?;
-            }
-            std::fs::create_dir_all(&index_path)?;
+            clear_index_dir_except_lock(&index_path)?;
             Index::create_in_dir(&index_path, self.schema.clone())
                 .context("Failed to create index")?
         };
@@ -1381,20 +2315,12 @@ impl IndexBuilder {
             .context("Failed to create index writer")?;
 
         let (tx, rx) = mpsc::sync_channel::<ProcessedFile>(64);
-        let path_field = self.fields.path;
         let path_exact_field = self.fields.path_exact;
-        let content_field = self.fields.content;
-        let language_field = self.fields.language;
-        let symbols_field = self.fields.symbols;
-        let doc_type_field = self.fields.doc_type;
-        let symbol_id_field = self.fields.symbol_id;
-        let symbol_end_line_field = self.fields.symbol_end_line;
-        let line_number_field = self.fields.line_number;
 
         if !deleted_paths.is_empty() {
             for raw in &deleted_paths {
                 let abs = to_absolute_path(&self.root, raw);
-                let abs_str = abs.to_string_lossy().to_string();
+                let abs_str = paths::encode_path(&abs);
                 writer.delete_term(Term::from_field_text(path_exact_field, &abs_str));
                 new_metadata.files.remove(&abs_str);
             }
@@ -1420,7 +2346,7 @@ impl IndexBuilder {
                 files_to_process
                     .par_iter()
                     .for_each_with(tx_producer, |tx, path| {
-                        let path_str = path.to_string_lossy().to_string();
+                        let path_str = paths::encode_path(path);
                         pb_producer.set_message(path_str.clone());
 
                         let metadata = match std::fs::metadata(path) {
@@ -1457,6 +2383,55 @@ impl IndexBuilder {
                             return;
                         }
 
+                        if self.archives.matches(path) {
+                            let unchanged = (!force)
+                                .then(|| {
+                                    unchanged_archive(path, existing_meta.as_ref(), mtime, size)
+                                })
+                                .flatten();
+                            if let Some(meta) = unchanged {
+                                let _ = tx.send(ProcessedFile::Skipped {
+                                    path: path_str,
+                                    meta,
+                                    delete_docs: false,
+                                });
+                                pb_producer.inc(1);
+                                return;
+                            }
+                            let processed = match self.archive_documents(path, &path_str) {
+                                Ok((docs, hash, symbols)) => {
+                                    let meta = FileMetadata {
+                                        mtime,
+                                        size,
+                                        hash,
+                                        symbols,
+                                        is_binary: false,
+                                        format: TextFormat::default(),
+                                    };
+                                    if docs.is_empty() {
+                                        ProcessedFile::Skipped {
+                                            path: path_str,
+                                            meta,
+                                            delete_docs: true,
+                                        }
+                                    } else {
+                                        ProcessedFile::Indexed {
+                                            path: path_str,
+                                            meta,
+                                            docs,
+                                        }
+                                    }
+                                }
+                                Err(_) => ProcessedFile::ReadError {
+                                    path: path_str,
+                                    fallback: existing_meta,
+                                },
+                            };
+                            let _ = tx.send(processed);
+                            pb_producer.inc(1);
+                            return;
+                        }
+
                         let outcome = match read_text_chunks(path, MAX_DOC_BYTES) {
                             Ok(outcome) => outcome,
                             Err(_) => {
@@ -1469,8 +2444,12 @@ impl IndexBuilder {
                             }
                         };
 
-                        let (chunks, hash) = match outcome {
-                            ReadOutcome::Text { chunks, hash } => (chunks, hash),
+                        let (chunks, hash, format) = match outcome {
+                            ReadOutcome::Text {
+                                chunks,
+                                hash,
+                                format,
+                            } => (chunks, hash, format),
                             ReadOutcome::Binary { hash } => {
                                 let meta = FileMetadata {
                                     mtime,
@@ -1478,6 +2457,7 @@ impl IndexBuilder {
                                     hash: hash.unwrap_or_default(),
                                     symbols: String::new(),
                                     is_binary: true,
+                                    format: TextFormat::default(),
                                 };
                                 let _ = tx.send(ProcessedFile::Skipped {
                                     path: path_str,
@@ -1510,29 +2490,23 @@ impl IndexBuilder {
                             .and_then(detect_language)
                             .unwrap_or_default();
 
-                        let full_text = join_chunks(&chunks);
-                        let symbol_list = if !lang_str.is_empty() {
-                            extract_symbols_from_text(&full_text, &lang_str)
-                        } else {
-                            Vec::new()
-                        };
-                        let symbols = if !lang_str.is_empty() {
-                            extract_symbol_names(&symbol_list)
-                        } else {
-                            String::new()
-                        };
-                        let symbol_docs = filter_symbols(
-                            symbol_list.clone(),
-                            self.allowed_symbol_kinds.as_ref(),
-                            self.max_symbols_per_file,
+                        let relative = paths::relative_display(path, &self.root)
+                            .unwrap_or_else(|| path_str.clone());
+                        let size_policy = self.size_policies.mode_for(&relative, size);
+                        let (docs, symbols) = self.text_documents(
+                            &path_str,
+                            &path_str,
+                            &chunks,
+                            &lang_str,
+                            size_policy,
                         );
-
                         let meta = FileMetadata {
                             mtime,
                             size,
                             hash,
-                            symbols: symbols.clone(),
+                            symbols,
                             is_binary: false,
+                            format,
                         };
 
                         if chunks.is_empty() {
@@ -1545,45 +2519,6 @@ impl IndexBuilder {
                             return;
                         }
 
-                        let mut docs: Vec<TantivyDocument> =
-                            Vec::with_capacity(chunks.len() + symbol_docs.len());
-                        for chunk in &chunks {
-                            let mut doc = TantivyDocument::default();
-                            doc.add_text(path_field, &path_str);
-                            doc.add_text(path_exact_field, &path_str);
-                            doc.add_text(content_field, &chunk.content);
-                            doc.add_text(language_field, &lang_str);
-                            doc.add_text(symbols_field, &symbols);
-                            doc.add_text(doc_type_field, "file");
-                            doc.add_u64(line_number_field, chunk.start_line);
-                            docs.push(doc);
-                        }
-
-                        for symbol in &symbol_docs {
-                            let symbol_id = symbol_id_for(&path_str, &lang_str, symbol);
-                            let content = build_symbol_content(
-                                &full_text,
-                                symbol,
-                                self.symbol_preview_lines,
-                                self.symbol_max_chars,
-                            );
-                            if content.is_empty() {
-                                continue;
-                            }
-
-                            let mut doc = TantivyDocument::default();
-                            doc.add_text(path_field, &path_str);
-                            doc.add_text(path_exact_field, &path_str);
-                            doc.add_text(content_field, &content);
-                            doc.add_text(language_field, &lang_str);
-                            doc.add_text(symbols_field, &symbol.name);
-                            doc.add_text(doc_type_field, "symbol");
-                            doc.add_text(symbol_id_field, &symbol_id);
-                            doc.add_u64(line_number_field, symbol.line as u64);
-                            doc.add_u64(symbol_end_line_field, symbol.end_line as u64);
-                            docs.push(doc);
-                        }
-
                         let _ = tx.send(ProcessedFile::Indexed {
                             path: path_str,
                             meta,
@@ -1638,6 +2573,13 @@ impl IndexBuilder {
         }
 
         writer.commit()?;
+        if use_staging {
+            writer
+                .wait_merging_threads()
+                .context("Failed to finalize staging index")?;
+            drop(index);
+            swap_staged_index(&index_path, &staging_path)?;
+        }
 
         if use_manifest && !manifest_precomputed {
             manifest_diff.added = files
@@ -1717,11 +2659,19 @@ impl IndexBuilder {
         if !index_path.join("meta.json").exists() || !metadata_path.exists() {
             return self.build_with_io_threads(false, writer_budget_bytes, None);
         }
+        compat::check_writable(&self.root, false)?;
+        if !self.upgrade_index_schema(&index_path, writer_budget_bytes) {
+            return self.build_with_io_threads(true, writer_budget_bytes, None);
+        }
 
         let content = std::fs::read_to_string(&metadata_path).unwrap_or_default();
         let old_metadata: IndexMetadata = serde_json::from_str(&content).unwrap_or_default();
         let mut new_metadata = old_metadata;
         new_metadata.index_options = Some(self.stored_index_options());
+        new_metadata.compat = Some(IndexCompat::current());
+        if let Some(scope) = self.scope.clone() {
+            new_metadata.scope = Some(scope).filter(|scope| !scope.is_empty());
+        }
         let old_manifest = manifest::load_manifest(&self.root);
 
         let index = Index::open_in_dir(&index_path).context("Failed to open existing index")?;
@@ -1741,15 +2691,7 @@ impl IndexBuilder {
             .writer(writer_budget_bytes)
             .context("Failed to create index writer")?;
 
-        let path_field = self.fields.path;
         let path_exact_field = self.fields.path_exact;
-        let content_field = self.fields.content;
-        let language_field = self.fields.language;
-        let symbols_field = self.fields.symbols;
-        let doc_type_field = self.fields.doc_type;
-        let symbol_id_field = self.fields.symbol_id;
-        let symbol_end_line_field = self.fields.symbol_end_line;
-        let line_number_field = self.fields.line_number;
 
         let mut seen_paths: HashSet<String> = HashSet::new();
         let mut indexed_count = 0usize;
@@ -1763,7 +2705,7 @@ impl IndexBuilder {
             } else {
                 self.root.join(raw_path)
             };
-            let path_str = path.to_string_lossy().to_string();
+            let path_str = paths::encode_path(&path);
             if !seen_paths.insert(path_str.clone()) {
                 continue;
             }
@@ -1788,6 +2730,7 @@ impl IndexBuilder {
                 continue;
             }
 
+            let is_archive = self.archives.matches(&path);
             let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
                 writer.delete_term(Term::from_field_text(path_exact_field, &path_str));
                 if new_metadata.files.remove(&path_str).is_some() {
@@ -1795,7 +2738,7 @@ impl IndexBuilder {
                 }
                 continue;
             };
-            if !crate::indexer::scanner::is_indexable_extension(ext) {
+            if !is_archive && !crate::indexer::scanner::is_indexable_extension(ext) {
                 writer.delete_term(Term::from_field_text(path_exact_field, &path_str));
                 if new_metadata.files.remove(&path_str).is_some() {
                     deleted_count += 1;
@@ -1828,6 +2771,42 @@ impl IndexBuilder {
                 continue;
             }
 
+            if is_archive {
+                if let Some(meta) = unchanged_archive(&path, existing_meta.as_ref(), mtime, size) {
+                    skipped_count += 1;
+                    new_metadata.files.insert(path_str, meta);
+                    continue;
+                }
+                let (docs, hash, symbols) = match self.archive_documents(&path, &path_str) {
+                    Ok(result) => result,
+                    Err(_) => {
+                        error_count += 1;
+                        eprintln!("Warning: failed to read {}", path_str);
+                        if let Some(meta) = existing_meta {
+                            new_metadata.files.insert(path_str, meta);
+                        }
+                        continue;
+                    }
+                };
+                writer.delete_term(Term::from_field_text(path_exact_field, &path_str));
+                for doc in docs {
+                    writer.add_document(doc)?;
+                }
+                indexed_count += 1;
+                new_metadata.files.insert(
+                    path_str,
+                    FileMetadata {
+                        mtime,
+                        size,
+                        hash,
+                        symbols,
+                        is_binary: false,
+                        format: TextFormat::default(),
+                    },
+                );
+                continue;
+            }
+
             let outcome = match read_text_chunks(&path, MAX_DOC_BYTES) {
                 Ok(outcome) => outcome,
                 Err(_) => {
@@ -1840,8 +2819,12 @@ impl IndexBuilder {
                 }
             };
 
-            let (chunks, hash) = match outcome {
-                ReadOutcome::Text { chunks, hash } => (chunks, hash),
+            let (chunks, hash, format) = match outcome {
+                ReadOutcome::Text {
+                    chunks,
+                    hash,
+                    format,
+                } => (chunks, hash, format),
                 ReadOutcome::Binary { hash } => {
                     writer.delete_term(Term::from_field_text(path_exact_field, &path_str));
                     skipped_count += 1;
@@ -1853,6 +2836,7 @@ impl IndexBuilder {
                             hash: hash.unwrap_or_default(),
                             symbols: String::new(),
                             is_binary: true,
+                            format: TextFormat::default(),
                         },
                     );
                     continue;
@@ -1871,29 +2855,18 @@ impl IndexBuilder {
             }
 
             let lang_str = detect_language(ext).unwrap_or_default();
-            let full_text = join_chunks(&chunks);
-            let symbol_list = if !lang_str.is_empty() {
-                extract_symbols_from_text(&full_text, &lang_str)
-            } else {
-                Vec::new()
-            };
-            let symbols = if !lang_str.is_empty() {
-                extract_symbol_names(&symbol_list)
-            } else {
-                String::new()
-            };
-            let symbol_docs = filter_symbols(
-                symbol_list.clone(),
-                self.allowed_symbol_kinds.as_ref(),
-                self.max_symbols_per_file,
-            );
-
+            let relative =
+                paths::relative_display(&path, &self.root).unwrap_or_else(|| path_str.clone());
+            let size_policy = self.size_policies.mode_for(&relative, size);
+            let (docs, symbols) =
+                self.text_documents(&path_str, &path_str, &chunks, &lang_str, size_policy);
             let meta = FileMetadata {
                 mtime,
                 size,
                 hash,
-                symbols: symbols.clone(),
+                symbols,
                 is_binary: false,
+                format,
             };
 
             writer.delete_term(Term::from_field_text(path_exact_field, &path_str));
@@ -1904,40 +2877,7 @@ impl IndexBuilder {
                 continue;
             }
 
-            for chunk in &chunks {
-                let mut doc = TantivyDocument::default();
-                doc.add_text(path_field, &path_str);
-                doc.add_text(path_exact_field, &path_str);
-                doc.add_text(content_field, &chunk.content);
-                doc.add_text(language_field, &lang_str);
-                doc.add_text(symbols_field, &symbols);
-                doc.add_text(doc_type_field, "file");
-                doc.add_u64(line_number_field, chunk.start_line);
-                writer.add_document(doc)?;
-            }
-
-            for symbol in &symbol_docs {
-                let symbol_id = symbol_id_for(&path_str, &lang_str, symbol);
-                let symbol_content = build_symbol_content(
-                    &full_text,
-                    symbol,
-                    self.symbol_preview_lines,
-                    self.symbol_max_chars,
-                );
-                if symbol_content.is_empty() {
-                    continue;
-                }
-
-                let mut doc = TantivyDocument::default();
-                doc.add_text(path_field, &path_str);
-                doc.add_text(path_exact_field, &path_str);
-                doc.add_text(content_field, &symbol_content);
-                doc.add_text(language_field, &lang_str);
-                doc.add_text(symbols_field, &symbol.name);
-                doc.add_text(doc_type_field, "symbol");
-                doc.add_text(symbol_id_field, &symbol_id);
-                doc.add_u64(line_number_field, symbol.line as u64);
-                doc.add_u64(symbol_end_line_field, symbol.end_line as u64);
+            for doc in docs {
                 writer.add_document(doc)?;
             }
 
@@ -1984,6 +2924,7 @@ pub struct RunOptions {
     pub include_paths: Vec<String>,
     pub high_memory: bool,
     pub include_ignored: bool,
+    pub ignore_rules: IgnoreRules,
     pub background: bool,
     pub background_worker: bool,
     pub reuse_mode: String,
@@ -1992,6 +2933,13 @@ pub struct RunOptions {
     pub print_diff: bool,
     pub embeddings_mode: String,
     pub embeddings_force: bool,
+    pub embeddings_resume: bool,
+    pub embeddings_batch_size: Option<usize>,
+    pub lock_wait: LockWait,
+    pub dry_run: bool,
+    pub show_scope: bool,
+    pub reset_scope: bool,
+    pub branch_aware: bool,
 }
 
 fn resolve_root(path: Option<&str>) -> Result<PathBuf> {
@@ -2002,24 +2950,82 @@ fn resolve_root(path: Option<&str>) -> Result<PathBuf> {
     Ok(root)
 }
 
+/// Scope for this run: explicit flags win, otherwise the stored scope is reused.
+fn resolve_scope(root: &Path, options: &RunOptions) -> (IndexScope, bool) {
+    let requested = IndexScope::from_options(options);
+    if !requested.is_empty() || options.reset_scope {
+        return (requested, false);
+    }
+    match load_index_metadata(root).and_then(|metadata| metadata.scope) {
+        Some(stored) if !stored.is_empty() => (stored, true),
+        _ => (requested, false),
+    }
+}
+
 fn resolve_effective_options(
     root: &Path,
     options: &RunOptions,
+    scope: &IndexScope,
 ) -> (Config, StoredIndexOptions, SymbolIndexOptions) {
     let config = Config::load_for_dir(root);
-    let mut all_excludes = options.excludes.clone();
+    let mut all_excludes = scope.exclude_paths.clone();
     all_excludes.extend(config.index().exclude_paths().iter().cloned());
-    let respect_git_ignore = config.index().respect_git_ignore() && !options.include_ignored;
+    let respect_git_ignore = config.index().respect_git_ignore() && !scope.include_ignored;
     let index_options = StoredIndexOptions {
         exclude_paths: all_excludes,
-        include_paths: options.include_paths.clone(),
+        include_paths: scope.include_paths.clone(),
         respect_git_ignore,
+        ignore_rules: scope.ignore_rules,
         high_memory: options.high_memory,
+        archives: ArchiveOptions::from_config(&config),
+        size_policies: SizePolicy::from_config(&config),
     };
     let symbol_options = SymbolIndexOptions::from_config(&config);
     (config, index_options, symbol_options)
 }
 
+fn print_scope(root: &Path) {
+    let config = Config::load_for_dir(root);
+    let metadata = load_index_metadata(root);
+    let scope = metadata
+        .as_ref()
+        .and_then(|metadata| metadata.scope.clone())
+        .unwrap_or_default();
+    let list = |items: &[String]| {
+        if items.is_empty() {
+            "(none)".to_string()
+        } else {
+            items.join(", ")
+        }
+    };
+
+    println!("Index scope for {}", root.display());
+    if metadata.is_none() {
+        println!("  (no index metadata yet; defaults apply)");
+    }
+    println!("  exclude (stored): {}", list(&scope.exclude_paths));
+    println!(
+        "  exclude (config): {}",
+        list(config.index().exclude_paths())
+    );
+    println!("  include paths:    {}", list(&scope.include_paths));
+    let respect_ignore = config.index().respect_git_ignore() && !scope.include_ignored;
+    println!("  respect ignore:   {}", respect_ignore);
+    let rules = scope.ignore_rules;
+    println!("  hidden files:     {}", rules.hidden);
+    println!(
+        "  vcs ignore:       {}",
+        respect_ignore && !rules.no_ignore_vcs
+    );
+    println!(
+        "  global gitignore: {}",
+        respect_ignore && !rules.no_ignore_vcs && !rules.no_ignore_global
+    );
+    if !scope.is_empty() {
+        println!("Reused by incremental runs; pass new scope flags or --reset-scope to change it.");
+    }
+}
+
 fn reuse_profile_hash(
     index_options: &StoredIndexOptions,
     symbol_options: &SymbolIndexOptions,
@@ -2030,7 +3036,11 @@ fn reuse_profile_hash(
         exclude_paths: &'a [String],
         include_paths: &'a [String],
         respect_git_ignore: bool,
+        #[serde(skip_serializing_if = "IgnoreRules::is_default")]
+        ignore_rules: IgnoreRules,
         high_memory: bool,
+        #[serde(skip_serializing_if = "<[SizePolicy]>::is_empty")]
+        size_policies: &'a [SizePolicy],
         symbol_preview_lines: usize,
         symbol_max_chars: usize,
         max_symbols_per_file: usize,
@@ -2050,7 +3060,9 @@ fn reuse_profile_hash(
         exclude_paths: &index_options.exclude_paths,
         include_paths: &index_options.include_paths,
         respect_git_ignore: index_options.respect_git_ignore,
+        ignore_rules: index_options.ignore_rules,
         high_memory: index_options.high_memory,
+        size_policies: &index_options.size_policies,
         symbol_preview_lines: symbol_options.symbol_preview_lines,
         symbol_max_chars: symbol_options.symbol_max_chars,
         max_symbols_per_file: symbol_options.max_symbols_per_file,
@@ -2078,6 +3090,15 @@ fn build_background_worker_args(root: &Path, options: &RunOptions) -> Vec<String
     if options.include_ignored {
         args.push("--include-ignored".to_string());
     }
+    if options.ignore_rules.hidden {
+        args.push("--hidden".to_string());
+    }
+    if options.ignore_rules.no_ignore_vcs {
+        args.push("--no-ignore-vcs".to_string());
+    }
+    if options.ignore_rules.no_ignore_global {
+        args.push("--no-ignore-global".to_string());
+    }
     if !options.use_manifest {
         args.push("--no-manifest".to_string());
     }
@@ -2090,11 +3111,27 @@ fn build_background_worker_args(root: &Path, options: &RunOptions) -> Vec<String
     if options.embeddings_force {
         args.push("--embeddings-force".to_string());
     }
+    if options.reset_scope {
+        args.push("--reset-scope".to_string());
+    }
+    if options.branch_aware {
+        args.push("--branch-aware".to_string());
+    }
+    if options.lock_wait == LockWait::NoWait {
+        args.push("--no-wait".to_string());
+    }
     args.push("--reuse".to_string());
     args.push(options.reuse_mode.clone());
 
     args.push("--embeddings".to_string());
     args.push(options.embeddings_mode.clone());
+    if options.embeddings_resume {
+        args.push("--resume".to_string());
+    }
+    if let Some(batch_size) = options.embeddings_batch_size {
+        args.push("--embeddings-batch-size".to_string());
+        args.push(batch_size.to_string());
+    }
 
     for include in &options.include_paths {
         args.push("--include-path".to_string());
@@ -2137,6 +3174,7 @@ fn run_foreground(
     config: &Config,
     index_options: &StoredIndexOptions,
     symbol_options: &SymbolIndexOptions,
+    scope: &IndexScope,
     mut background_status: Option<&mut BuildStatus>,
 ) -> Result<()> {
     let force = options.force;
@@ -2149,12 +3187,21 @@ fn run_foreground(
         status::mark_build_phase(root, status_state, "indexing", 0, 0, "indexing files")?;
     }
 
-    let builder = IndexBuilder::with_options(root, index_options.clone(), symbol_options.clone())?;
+    let builder = IndexBuilder::with_options(root, index_options.clone(), symbol_options.clone())?
+        .with_scope(scope.clone());
     if index_options.high_memory {
         eprintln!("Using high-memory indexing: writer budget = 1GiB");
     }
     let writer_budget_bytes = index_options.writer_budget_bytes();
-    let count = builder.build_with_io_threads_and_manifest(
+    // Without an index, even an incremental run builds everything.
+    let build_kind = if force || !status::full_index_ready(root) {
+        "full"
+    } else {
+        "incremental"
+    };
+    let build_started = std::time::Instant::now();
+    let span = cgrep::telemetry::Span::start("cgrep.index.build");
+    let built = builder.build_with_io_threads_and_manifest(
         force,
         writer_budget_bytes,
         None,
@@ -2163,7 +3210,26 @@ fn run_foreground(
             manifest_only,
             print_diff,
         },
-    )?;
+    );
+    span.end(vec![("kind", build_kind.to_string())], built.is_ok());
+    let count = built?;
+    cgrep::telemetry::record_duration(
+        "cgrep.index.build.duration",
+        build_started.elapsed(),
+        vec![("kind", build_kind.to_string())],
+    );
+    if !manifest_only {
+        status::record_last_build(
+            root,
+            background_status.as_deref_mut(),
+            status::LastBuild {
+                kind: build_kind.to_string(),
+                finished_at: status::now_unix_ms(),
+                elapsed_ms: build_started.elapsed().as_millis() as u64,
+                files_updated: count,
+            },
+        )?;
+    }
 
     if !manifest_only {
         println!("Index complete: {} files", count);
@@ -2211,13 +3277,20 @@ fn run_foreground(
         let index_metadata: IndexMetadata =
             serde_json::from_str(&content).context("Failed to parse index metadata")?;
 
-        let stats = index_embeddings(root, mode, embeddings_force, config, &index_metadata)?;
+        let run_options = EmbeddingRunOptions {
+            force: embeddings_force,
+            resume: options.embeddings_resume,
+            batch_size: options.embeddings_batch_size,
+        };
+        let stats = index_embeddings(root, mode, run_options, config, &index_metadata)?;
         if stats.files_embedded > 0 || stats.files_skipped_up_to_date > 0 || stats.files_deleted > 0
         {
             println!(
-                "Embeddings: {} files embedded ({} symbols), {} up-to-date, {} removed",
+                "Embeddings: {} files embedded ({} symbols, {} from cache, {:.1} symbols/s), {} up-to-date, {} removed",
                 stats.files_embedded,
                 stats.symbols_embedded,
+                stats.symbols_cached,
+                stats.symbols_per_sec(),
                 stats.files_skipped_up_to_date,
                 stats.files_deleted
             );
@@ -2231,6 +3304,11 @@ pub fn run(path: Option<&str>, options: RunOptions) -> Result<()> {
     let root = resolve_root(path)?;
     let reuse_mode = ReuseMode::parse(&options.reuse_mode)?;
 
+    if options.show_scope {
+        print_scope(&root);
+        return Ok(());
+    }
+
     if options.background && !options.background_worker {
         let existing = status::read_status_with_recovery(&root)?;
         if matches!(
@@ -2266,13 +3344,31 @@ pub fn run(path: Option<&str>, options: RunOptions) -> Result<()> {
         return Ok(());
     }
 
-    let (config, index_options, symbol_options) = resolve_effective_options(&root, &options);
+    let (scope, scope_reused) = resolve_scope(&root, &options);
+    if options.dry_run {
+        let (_config, index_options, symbol_options) =
+            resolve_effective_options(&root, &options, &scope);
+        let builder = IndexBuilder::with_options(&root, index_options, symbol_options)?;
+        let estimate = builder.dry_run(options.force)?;
+        print_dry_run(&estimate, options.print_diff);
+        return Ok(());
+    }
+
+    let _lock = lock::acquire(&root, "index", options.lock_wait)?;
+    if scope_reused {
+        eprintln!(
+            "Reusing stored index scope (see `cgrep index --show-scope`; --reset-scope to clear)"
+        );
+    }
+    let (config, index_options, symbol_options) =
+        resolve_effective_options(&root, &options, &scope);
     let profile_hash = reuse_profile_hash(&index_options, &symbol_options, options.use_manifest);
     let reuse_profile = ReuseProfile {
         profile_hash: profile_hash.clone(),
         excludes: index_options.exclude_paths.clone(),
         includes: index_options.include_paths.clone(),
         respect_git_ignore: index_options.respect_git_ignore,
+        ignore_rules: index_options.ignore_rules,
     };
 
     let mut background_state = if options.background_worker {
@@ -2287,8 +3383,20 @@ pub fn run(path: Option<&str>, options: RunOptions) -> Result<()> {
         None
     };
 
+    let branch_aware = options.branch_aware || branches::enabled(&root);
+    let branch_switch = if branch_aware {
+        branches::prepare(&root)?
+    } else {
+        None
+    };
+    if let Some(note) = branch_switch.as_deref() {
+        eprintln!("Branch-aware index: {note}");
+    }
+
     let mut reuse_decision = if options.force {
         ReuseDecision::miss(reuse_mode, None, "force_rebuild_requested")
+    } else if branch_switch.is_some() {
+        ReuseDecision::miss(reuse_mode, None, "branch_overlay_restored")
     } else if options.manifest_only {
         ReuseDecision::miss(reuse_mode, None, "manifest_only")
     } else {
@@ -2333,10 +3441,16 @@ pub fn run(path: Option<&str>, options: RunOptions) -> Result<()> {
         &config,
         &index_options,
         &symbol_options,
+        &scope,
         background_state.as_mut(),
     );
 
     if result.is_ok() {
+        if branch_aware && !options.manifest_only {
+            if let Err(err) = branches::record(&root) {
+                eprintln!("Warning: failed to record branch index state: {err}");
+            }
+        }
         if reuse_decision.active {
             reuse_decision.active = false;
         }
@@ -2416,6 +3530,120 @@ mod tests {
         searcher.search(&query, &Count).expect("count")
     }
 
+    #[test]
+    fn identical_symbol_content_reuses_cached_embeddings() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path();
+        std::fs::write(root.join("one.rs"), "fn alpha() {}\nfn beta() {}\n").expect("write one");
+        IndexBuilder::new(root)
+            .expect("builder")
+            .build(false, DEFAULT_WRITER_BUDGET_BYTES)
+            .expect("build");
+        let config: Config =
+            toml::from_str("[embeddings]\nprovider = \"dummy\"\n").expect("config");
+        let options = EmbeddingRunOptions::default();
+
+        let stats = index_embeddings(
+            root,
+            EmbeddingsMode::Precompute,
+            options,
+            &config,
+            &load_metadata(root),
+        )
+        .expect("first run");
+        assert_eq!(stats.symbols_embedded, 2);
+        assert_eq!(stats.symbols_cached, 0);
+        assert!(root.join(".cgrep/embedding_cache.sqlite").is_file());
+
+        // A copy of the same code, as after a branch switch or file move.
+        std::fs::copy(root.join("one.rs"), root.join("copy.rs")).expect("copy");
+        IndexBuilder::new(root)
+            .expect("builder")
+            .build(false, DEFAULT_WRITER_BUDGET_BYTES)
+            .expect("rebuild");
+        let stats = index_embeddings(
+            root,
+            EmbeddingsMode::Precompute,
+            options,
+            &config,
+            &load_metadata(root),
+        )
+        .expect("second run");
+        assert_eq!(stats.symbols_embedded, 2);
+        assert_eq!(stats.symbols_cached, 2);
+
+        let off: Config =
+            toml::from_str("[embeddings]\nprovider = \"dummy\"\ncontent_cache = \"off\"\n")
+                .expect("config");
+        let forced = EmbeddingRunOptions {
+            force: true,
+            ..options
+        };
+        let stats = index_embeddings(
+            root,
+            EmbeddingsMode::Precompute,
+            forced,
+            &off,
+            &load_metadata(root),
+        )
+        .expect("uncached run");
+        assert_eq!(stats.symbols_embedded, 4);
+        assert_eq!(stats.symbols_cached, 0);
+    }
+
+    #[test]
+    fn resume_continues_interrupted_embedding_rebuild() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path();
+        std::fs::write(root.join("one.rs"), "fn alpha() {}\nfn beta() {}\n").expect("write one");
+        std::fs::write(root.join("two.rs"), "fn gamma() {}\n").expect("write two");
+        IndexBuilder::new(root)
+            .expect("builder")
+            .build(false, DEFAULT_WRITER_BUDGET_BYTES)
+            .expect("build");
+        let metadata = load_metadata(root);
+        let config: Config =
+            toml::from_str("[embeddings]\nprovider = \"dummy\"\n").expect("config");
+        let force = EmbeddingRunOptions {
+            force: true,
+            batch_size: Some(1),
+            ..EmbeddingRunOptions::default()
+        };
+
+        let stats = index_embeddings(root, EmbeddingsMode::Precompute, force, &config, &metadata)
+            .expect("rebuild");
+        assert_eq!(stats.files_embedded, 2);
+        assert_eq!(stats.symbols_embedded, 3);
+
+        // Simulate a rebuild interrupted after its first committed batch.
+        let storage = EmbeddingStorage::open_default(root).expect("storage");
+        let two = metadata
+            .files
+            .keys()
+            .find(|path| path.ends_with("two.rs"))
+            .expect("two.rs metadata");
+        storage.delete_file_symbols(two).expect("delete");
+        storage
+            .set_meta(EMBEDDING_CHECKPOINT_META, "0")
+            .expect("checkpoint");
+        drop(storage);
+
+        let resume = EmbeddingRunOptions {
+            resume: true,
+            ..force
+        };
+        let stats = index_embeddings(root, EmbeddingsMode::Precompute, resume, &config, &metadata)
+            .expect("resume");
+        assert_eq!(stats.files_embedded, 1);
+        assert_eq!(stats.files_skipped_up_to_date, 1);
+        let storage = EmbeddingStorage::open_default(root).expect("storage");
+        assert_eq!(storage.count_symbols().expect("count"), 3);
+        assert!(storage
+            .get_meta(EMBEDDING_CHECKPOINT_META)
+            .expect("meta")
+            .is_none());
+    }
+
     #[test]
     fn incremental_index_skips_unchanged_files() {
         let dir = TempDir::new().expect("tempdir");
@@ -2444,7 +3672,17 @@ mod tests {
         std::fs::write(root.join(".venv/lib/site.py"), "print('ok')").expect("write venv file");
         std::fs::write(root.join("main.rs"), "fn main() {}").expect("write main");
 
-        let builder = IndexBuilder::new(root).expect("builder");
+        // `.venv` is hidden, so include hidden paths to exercise the ignore file alone.
+        let index_options = StoredIndexOptions {
+            ignore_rules: IgnoreRules {
+                hidden: true,
+                ..IgnoreRules::default()
+            },
+            ..StoredIndexOptions::default()
+        };
+        let builder =
+            IndexBuilder::with_options(root, index_options, SymbolIndexOptions::default())
+                .expect("builder");
         let indexed = builder
             .build(false, DEFAULT_WRITER_BUDGET_BYTES)
             .expect("build");
@@ -2472,6 +3710,29 @@ mod tests {
         assert_eq!(second, 0);
     }
 
+    #[test]
+    fn archives_with_unchanged_bytes_are_not_expanded() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("vendor.jar");
+        std::fs::write(&path, b"PK\x03\x04 not expanded").expect("write archive");
+        let meta = FileMetadata {
+            mtime: 1,
+            size: 1,
+            hash: manifest::hash_file_streaming(&path).expect("hash"),
+            symbols: "Vendor".to_string(),
+            is_binary: false,
+            format: TextFormat::default(),
+        };
+
+        let refreshed = unchanged_archive(&path, Some(&meta), 2, 21).expect("unchanged");
+        assert_eq!((refreshed.mtime, refreshed.size), (2, 21));
+        assert_eq!(refreshed.symbols, "Vendor");
+
+        std::fs::write(&path, b"PK\x03\x04 changed").expect("rewrite archive");
+        assert!(unchanged_archive(&path, Some(&meta), 3, 17).is_none());
+        assert!(unchanged_archive(&path, None, 3, 17).is_none());
+    }
+
     #[test]
     fn content_change_reindexes() {
         let dir = TempDir::new().expect("tempdir");
@@ -2548,6 +3809,145 @@ mod tests {
         assert_eq!(forced, 2);
     }
 
+    #[test]
+    fn force_rebuild_swaps_staged_index_and_drops_old_segments() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path();
+        let one = root.join("one.rs");
+        std::fs::write(&one, "fn one() {}\n").expect("write one");
+
+        let builder = IndexBuilder::new(root).expect("builder");
+        builder
+            .build(false, DEFAULT_WRITER_BUDGET_BYTES)
+            .expect("first build");
+        let segment_files = |root: &Path| -> HashSet<String> {
+            std::fs::read_dir(root.join(INDEX_DIR))
+                .expect("read index dir")
+                .filter_map(|entry| entry.ok()?.file_name().to_str().map(str::to_string))
+                .filter(|name| is_segment_file_name(name))
+                .collect()
+        };
+        let before = segment_files(root);
+        assert!(!before.is_empty());
+
+        // Simulate a crashed rebuild; the next force run must discard it.
+        let staging = root.join(INDEX_DIR).join(STAGING_DIR_NAME);
+        std::fs::create_dir_all(&staging).expect("create stale staging");
+        std::fs::write(staging.join("meta.json"), "garbage").expect("write stale meta");
+
+        let forced = builder
+            .build(true, DEFAULT_WRITER_BUDGET_BYTES)
+            .expect("force build");
+        assert_eq!(forced, 1);
+        assert!(!staging.exists());
+        let after = segment_files(root);
+        assert!(!after.is_empty());
+        assert!(before.is_disjoint(&after));
+        assert_eq!(count_docs_for_path(root, &one), 1);
+    }
+
+    #[test]
+    fn index_without_docs_field_is_migrated_in_place() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path();
+        let one = root.join("one.rs");
+        std::fs::write(&one, "/// Adds one.\nfn one() {}\n").expect("write one");
+
+        let mut old_schema = Schema::builder();
+        for name in ["path", "content", "language", "symbols"] {
+            old_schema.add_text_field(name, TEXT | STORED);
+        }
+        for name in ["path_exact", "doc_type", "symbol_id"] {
+            old_schema.add_text_field(name, STRING | STORED);
+        }
+        old_schema.add_u64_field("symbol_end_line", STORED);
+        old_schema.add_u64_field("line_number", tantivy::schema::INDEXED | STORED);
+        let old_schema = old_schema.build();
+        let index_path = root.join(INDEX_DIR);
+        std::fs::create_dir_all(&index_path).expect("create index dir");
+        let old_index = Index::create_in_dir(&index_path, old_schema.clone()).expect("old index");
+        let field = |name: &str| old_schema.get_field(name).expect("field");
+        let mut writer: IndexWriter = old_index.writer(15_000_000).expect("writer");
+        let mut doc = TantivyDocument::default();
+        doc.add_text(field("path"), one.to_string_lossy());
+        doc.add_text(field("path_exact"), one.to_string_lossy());
+        doc.add_text(field("content"), "/// Adds one.\nfn one() {}\n");
+        doc.add_text(field("language"), "rust");
+        doc.add_text(field("doc_type"), "file");
+        doc.add_u64(field("line_number"), 1);
+        writer.add_document(doc).expect("add");
+        let path = one.to_string_lossy();
+        let symbol = extract_symbols_from_text("/// Adds one.\nfn one() {}\n", "rust")
+            .into_iter()
+            .find(|symbol| symbol.name == "one")
+            .expect("symbol");
+        let mut doc = TantivyDocument::default();
+        doc.add_text(field("path"), &path);
+        doc.add_text(field("path_exact"), &path);
+        doc.add_text(field("content"), "fn one() {}");
+        doc.add_text(field("language"), "rust");
+        doc.add_text(field("doc_type"), "symbol");
+        doc.add_text(field("symbol_id"), symbol_id_for(&path, "rust", &symbol));
+        writer.add_document(doc).expect("add");
+        writer.commit().expect("commit");
+        drop(writer);
+        drop(old_index);
+
+        let builder = IndexBuilder::new(root).expect("builder");
+        assert!(builder.index_schema_outdated(&index_path));
+        assert!(builder.upgrade_index_schema(&index_path, DEFAULT_WRITER_BUDGET_BYTES));
+        assert!(!builder.index_schema_outdated(&index_path));
+
+        let index = Index::open_in_dir(&index_path).expect("open");
+        let docs_field = index.schema().get_field("docs").expect("docs field");
+        let searcher = index.reader().expect("reader").searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(docs_field, "adds"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count).expect("search"), 2);
+        assert_eq!(count_docs_for_path(root, &one), 1);
+
+        builder
+            .build(false, DEFAULT_WRITER_BUDGET_BYTES)
+            .expect("update");
+        assert_eq!(count_docs_for_path(root, &one), 1);
+    }
+
+    #[test]
+    fn index_without_symbol_fields_is_rebuilt_on_next_build() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path();
+        let one = root.join("one.rs");
+        std::fs::write(&one, "fn one() {}\n").expect("write one");
+
+        let mut old_schema = Schema::builder();
+        old_schema.add_text_field("path", TEXT | STORED);
+        old_schema.add_text_field("content", TEXT | STORED);
+        let index_path = root.join(INDEX_DIR);
+        std::fs::create_dir_all(&index_path).expect("create index dir");
+        Index::create_in_dir(&index_path, old_schema.build()).expect("old index");
+
+        let builder = IndexBuilder::new(root).expect("builder");
+        assert!(!builder.upgrade_index_schema(&index_path, DEFAULT_WRITER_BUDGET_BYTES));
+        builder
+            .build(false, DEFAULT_WRITER_BUDGET_BYTES)
+            .expect("rebuild");
+        assert!(!builder.index_schema_outdated(&index_path));
+        assert_eq!(count_docs_for_path(root, &one), 1);
+    }
+
+    #[test]
+    fn segment_file_names_are_recognized() {
+        assert!(is_segment_file_name("0123456789abcdef0123456789abcdef.idx"));
+        assert!(is_segment_file_name(
+            "0123456789abcdef0123456789abcdef.12.del"
+        ));
+        assert!(!is_segment_file_name("meta.json"));
+        assert!(!is_segment_file_name(".managed.json"));
+        assert!(!is_segment_file_name("0123456789abcdef0123456789abcdef"));
+    }
+
     #[test]
     fn rename_replaces_stale_path_docs() {
         let dir = TempDir::new().expect("tempdir");
@@ -2725,21 +4125,22 @@ mod tests {
     }
 
     #[test]
-    fn non_utf8_text_is_skipped_as_binary() {
+    fn non_utf8_text_is_transcoded_and_records_encoding() {
         let dir = TempDir::new().expect("tempdir");
         let root = dir.path();
-        std::fs::write(root.join("bad.rs"), vec![0xFF, 0xFE, 0xFD]).expect("write bad");
+        std::fs::write(root.join("latin1.rs"), b"// caf\xE9\nfn f() {}\n").expect("write latin1");
 
         let builder = IndexBuilder::new(root).expect("builder");
         let count = builder
             .build(false, DEFAULT_WRITER_BUDGET_BYTES)
             .expect("build");
-        assert_eq!(count, 0);
+        assert_eq!(count, 1);
 
         let metadata = load_metadata(root);
-        let bad_key = root.join("bad.rs").to_string_lossy().to_string();
-        let meta = metadata.files.get(&bad_key).expect("meta");
-        assert!(meta.is_binary);
+        let key = root.join("latin1.rs").to_string_lossy().to_string();
+        let meta = metadata.files.get(&key).expect("meta");
+        assert!(!meta.is_binary);
+        assert_eq!(meta.format.encoding.as_deref(), Some("windows-1252"));
     }
 
     #[test]
@@ -2789,6 +4190,10 @@ mod tests {
             byte_start: Some(100),
             byte_end: Some(200),
             scope: None,
+            doc: None,
+            signature: None,
+            decorators: Vec::new(),
+            metrics: None,
         };
 
         let id1 = symbol_id_for("src/lib.rs", "rust", &symbol);
@@ -2838,7 +4243,10 @@ mod tests {
                 exclude_paths: vec!["target/".to_string()],
                 include_paths: vec![".venv".to_string()],
                 respect_git_ignore: true,
+                ignore_rules: IgnoreRules::default(),
                 high_memory: true,
+                archives: ArchiveOptions::default(),
+                size_policies: Vec::new(),
             },
             SymbolIndexOptions::default(),
         )
@@ -2871,12 +4279,23 @@ mod tests {
             exclude_paths: vec!["from-metadata/".to_string()],
             include_paths: vec![".venv".to_string()],
             respect_git_ignore: false,
+            ignore_rules: IgnoreRules {
+                hidden: true,
+                ..IgnoreRules::default()
+            },
             high_memory: true,
+            archives: ArchiveOptions::default(),
+            size_policies: Vec::new(),
         };
         let metadata = IndexMetadata {
             files: HashMap::new(),
             index_options: Some(stored.clone()),
             manifest_diff: None,
+            scope: None,
+            churn: HashMap::new(),
+            git_history: HashMap::new(),
+            migrations: Vec::new(),
+            compat: None,
         };
         std::fs::write(
             &metadata_path,
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! String literals and named constants as an index facet.
+//!
+//! Every single-line string literal in a source file is indexed as a
+//! `literal` document holding the literal's value, with the constant name in
+//! the symbols field when the literal initializes a named constant
+//! (`const X = "..."`, `static final String X = "..."`, `UPPER_CASE = "..."`,
+//! `#define X "..."`). `cgrep literals` queries these documents to find config
+//! keys, error messages, and magic strings by value.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::indexer::regions::string_literal_spans;
+
+/// Longest literal value kept in the index.
+pub(crate) const MAX_LITERAL_CHARS: usize = 200;
+/// Cap on literal documents per file, for generated tables and fixtures.
+pub(crate) const MAX_LITERALS_PER_FILE: usize = 2000;
+
+static ASSIGNMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^.*?\b(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*(?::[^=]*)?:?=\s*$")
+        .expect("valid assignment pattern")
+});
+static DEFINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*#\s*define\s+(?P<name>\w+)\s+$").expect("valid define pattern"));
+static CONST_KEYWORD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:const|static|final|readonly|constexpr)\b").expect("valid keyword pattern")
+});
+
+/// One string literal occurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SourceLiteral {
+    /// 1-based line of the opening quote.
+    pub line: u64,
+    /// 1-based column (in characters) of the opening quote.
+    pub column: u64,
+    pub value: String,
+    /// Name of the constant this literal initializes, if any.
+    pub constant: Option<String>,
+}
+
+/// Extract single-line string literals from a file written in `lang`.
+pub(crate) fn extract_literals(text: &str, lang: &str) -> Vec<SourceLiteral> {
+    let mut literals = Vec::new();
+    let mut line = 1u64;
+    let mut line_start = 0usize;
+    let mut scanned = 0usize;
+    for span in string_literal_spans(text, lang) {
+        for (offset, _) in text[scanned..span.quote_start].match_indices('\n') {
+            line += 1;
+            line_start = scanned + offset + 1;
+        }
+        scanned = span.quote_start;
+
+        let value = &text[span.content_start..span.content_end];
+        if value.contains('\n')
+            || value.chars().count() > MAX_LITERAL_CHARS
+            || !value.chars().any(char::is_alphanumeric)
+        {
+            continue;
+        }
+        let before = &text[line_start..span.quote_start];
+        literals.push(SourceLiteral {
+            line,
+            column: before.chars().count() as u64 + 1,
+            value: value.to_string(),
+            constant: constant_name(before),
+        });
+        if literals.len() >= MAX_LITERALS_PER_FILE {
+            break;
+        }
+    }
+    literals
+}
+
+/// Constant name when the text before a literal declares one.
+pub(crate) fn constant_name(before: &str) -> Option<String> {
+    if let Some(caps) = DEFINE.captures(before) {
+        return Some(caps["name"].to_string());
+    }
+    let caps = ASSIGNMENT.captures(before)?;
+    let name = &caps["name"];
+    let upper_snake = name.chars().any(|c| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    (upper_snake || CONST_KEYWORD.is_match(before)).then(|| name.to_string())
+}
+
+/// Case- and separator-insensitive form: `Content-Type`, `content_type`,
+/// `CONTENT_TYPE`, and `contentType` all normalize to `content_type`.
+pub(crate) fn normalize_literal(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut prev: Option<char> = None;
+    for c in value.trim().chars() {
+        if c.is_alphanumeric() {
+            let camel_boundary = c.is_uppercase() && prev.is_some_and(char::is_lowercase);
+            if camel_boundary && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else if !out.is_empty() && !out.ends_with('_') {
+            out.push('_');
+        }
+        prev = Some(c);
+    }
+    while out.ends_with('_') {
+        out.pop();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literals_carry_position_and_constant_names() {
+        let source = "// \"commented out\"\nconst CONTENT_TYPE: &str = \"content-type\";\nfn f() {\n    headers.get(\"Content-Type\");\n    let x = \"\";\n}\n";
+        let literals = extract_literals(source, "rust");
+        assert_eq!(
+            literals,
+            vec![
+                SourceLiteral {
+                    line: 2,
+                    column: 28,
+                    value: "content-type".to_string(),
+                    constant: Some("CONTENT_TYPE".to_string()),
+                },
+                SourceLiteral {
+                    line: 4,
+                    column: 17,
+                    value: "Content-Type".to_string(),
+                    constant: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn constant_declarations_across_languages() {
+        assert_eq!(constant_name("#define HEADER "), Some("HEADER".into()));
+        assert_eq!(
+            constant_name("    public static final String KEY = "),
+            Some("KEY".into())
+        );
+        assert_eq!(
+            constant_name("const contentType = "),
+            Some("contentType".into())
+        );
+        assert_eq!(constant_name("TIMEOUT_KEY = "), Some("TIMEOUT_KEY".into()));
+        assert_eq!(constant_name("let header = "), None);
+        assert_eq!(constant_name("call("), None);
+    }
+
+    #[test]
+    fn normalization_ignores_case_and_separators() {
+        for value in [
+            "Content-Type",
+            "content_type",
+            "CONTENT_TYPE",
+            "contentType",
+            " content type ",
+        ] {
+            assert_eq!(normalize_literal(value), "content_type", "{value}");
+        }
+    }
+}
@@ -0,0 +1,341 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Cross-process index write lock (`.cgrep/index.lock`).
+//!
+//! The lock file is created with `create_new`, so only one writer can hold it.
+//! Holders record their PID and process start time; a lock whose holder is no
+//! longer running (or whose PID was reused by another process) is treated as
+//! stale and taken over. Takeovers are serialized by an OS lock on
+//! `.cgrep/index.lock.takeover` and replace the stale file with a `rename`, so
+//! the lock path never goes missing and two waiters cannot both win.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::indexer::status;
+use cgrep::errors::IndexLockedError;
+use cgrep::utils::INDEX_DIR;
+
+pub(crate) const LOCK_FILE_NAME: &str = "index.lock";
+/// Held (as an OS file lock) while replacing a stale lock; never deleted.
+pub(crate) const TAKEOVER_FILE_NAME: &str = "index.lock.takeover";
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Unparsable lock files younger than this may still be mid-write.
+const PARTIAL_LOCK_GRACE: Duration = Duration::from_secs(5);
+
+/// Behavior when another process holds the lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockWait {
+    /// Block until the lock is released.
+    Wait,
+    /// Fail immediately with [`IndexLockedError`].
+    NoWait,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LockInfo {
+    pub pid: u32,
+    /// Process start time as reported by the OS (Linux: clock ticks since boot).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_start: Option<u64>,
+    pub acquired_at_ms: u64,
+    pub command: String,
+}
+
+/// Held index lock; released on drop.
+#[derive(Debug)]
+pub struct IndexLock {
+    path: PathBuf,
+    info: LockInfo,
+}
+
+impl Drop for IndexLock {
+    fn drop(&mut self) {
+        // Only remove the file if it still belongs to us.
+        if read_lock_info(&self.path).as_ref() == Some(&self.info) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+pub fn lock_file_path(root: &Path) -> PathBuf {
+    root.join(INDEX_DIR).join(LOCK_FILE_NAME)
+}
+
+/// Acquire the index lock for `root`.
+pub fn acquire(root: &Path, command: &str, wait: LockWait) -> Result<IndexLock> {
+    let path = lock_file_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let pid = std::process::id();
+    let info = LockInfo {
+        pid,
+        process_start: process_start_time(pid),
+        acquired_at_ms: status::now_unix_ms(),
+        command: command.to_string(),
+    };
+
+    let mut announced_wait = false;
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                let bytes = serde_json::to_vec(&info)?;
+                file.write_all(&bytes)
+                    .and_then(|_| file.sync_all())
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                return Ok(IndexLock { path, info });
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to create {}", path.display()))
+            }
+        }
+
+        let Ok(raw) = fs::read(&path) else {
+            // Released between the create and the read.
+            continue;
+        };
+        let holder: Option<LockInfo> = serde_json::from_slice(&raw).ok();
+        if lock_is_stale(&path, holder.as_ref()) {
+            eprintln!(
+                "Removing stale index lock {}{}",
+                path.display(),
+                holder
+                    .as_ref()
+                    .map(|h| format!(" (pid={} no longer running)", h.pid))
+                    .unwrap_or_default()
+            );
+            if take_over_stale(&path, &raw, &info)
+                .with_context(|| format!("failed to replace stale {}", path.display()))?
+            {
+                return Ok(IndexLock { path, info });
+            }
+            continue;
+        }
+
+        if wait == LockWait::NoWait {
+            return Err(locked_error(&path, holder.as_ref()).into());
+        }
+        if !announced_wait {
+            eprintln!(
+                "Waiting for index lock held by {} ...",
+                holder
+                    .as_ref()
+                    .map(|h| format!("pid {} ({})", h.pid, h.command))
+                    .unwrap_or_else(|| "another process".to_string())
+            );
+            announced_wait = true;
+        }
+        thread::sleep(LOCK_POLL_INTERVAL);
+    }
+}
+
+/// Replace the lock file with one held by `info`, provided it still holds
+/// exactly the `stale` bytes it was judged by. Returns whether `info` now
+/// holds the lock.
+fn take_over_stale(path: &Path, stale: &[u8], info: &LockInfo) -> std::io::Result<bool> {
+    static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let guard = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path.with_file_name(TAKEOVER_FILE_NAME))?;
+    // Released when `guard` closes, or by the OS if this process dies.
+    guard.lock()?;
+
+    // Another waiter may have taken over, or the holder released, since the
+    // lock was judged stale.
+    match fs::read(path) {
+        Ok(current) if current == stale => {}
+        Ok(_) => return Ok(false),
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    }
+    let temp = path.with_file_name(format!(
+        "{LOCK_FILE_NAME}.{}.{}.tmp",
+        info.pid,
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp)
+        .and_then(|mut file| {
+            file.write_all(&serde_json::to_vec(info)?)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(err);
+    }
+    Ok(read_lock_info(path).as_ref() == Some(info))
+}
+
+fn locked_error(path: &Path, holder: Option<&LockInfo>) -> IndexLockedError {
+    IndexLockedError {
+        lock_path: path.display().to_string(),
+        pid: holder.map(|h| h.pid),
+        command: holder.map(|h| h.command.clone()),
+        acquired_at_ms: holder.map(|h| h.acquired_at_ms),
+    }
+}
+
+pub fn read_lock_info(path: &Path) -> Option<LockInfo> {
+    let raw = fs::read(path).ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+fn lock_is_stale(path: &Path, holder: Option<&LockInfo>) -> bool {
+    match holder {
+        Some(info) => !holder_alive(info),
+        None => fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .map(|age| age >= PARTIAL_LOCK_GRACE)
+            .unwrap_or(false),
+    }
+}
+
+#[cfg(unix)]
+fn holder_alive(info: &LockInfo) -> bool {
+    if !status::process_alive(info.pid) {
+        return false;
+    }
+    // A live PID with a different start time means the PID was reused.
+    match (info.process_start, process_start_time(info.pid)) {
+        (Some(recorded), Some(current)) => recorded == current,
+        _ => true,
+    }
+}
+
+#[cfg(not(unix))]
+fn holder_alive(_info: &LockInfo) -> bool {
+    // No portable liveness probe; never break another holder's lock.
+    true
+}
+
+#[cfg(target_os = "linux")]
+fn process_start_time(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // Field 22 (starttime); the comm field may contain spaces, so skip past ')'.
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    after_comm.split_whitespace().nth(19)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_start_time(_pid: u32) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn second_acquire_without_wait_reports_holder() {
+        let dir = TempDir::new().expect("tempdir");
+        let held = acquire(dir.path(), "index", LockWait::NoWait).expect("first acquire");
+
+        let err = acquire(dir.path(), "index", LockWait::NoWait).expect_err("lock is held");
+        let locked = err
+            .downcast_ref::<IndexLockedError>()
+            .expect("structured lock error");
+        assert_eq!(locked.pid, Some(std::process::id()));
+        assert_eq!(locked.command.as_deref(), Some("index"));
+
+        drop(held);
+        assert!(!lock_file_path(dir.path()).exists());
+        acquire(dir.path(), "index", LockWait::NoWait).expect("reacquire after release");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lock_from_dead_process_is_taken_over() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = lock_file_path(dir.path());
+        fs::create_dir_all(path.parent().expect("parent")).expect("create .cgrep");
+        let stale = LockInfo {
+            pid: u32::MAX - 1,
+            process_start: None,
+            acquired_at_ms: 1,
+            command: "index".to_string(),
+        };
+        fs::write(&path, serde_json::to_vec(&stale).expect("encode")).expect("write lock");
+
+        let lock = acquire(dir.path(), "index", LockWait::NoWait).expect("take over stale lock");
+        assert_eq!(
+            read_lock_info(&path).map(|info| info.pid),
+            Some(std::process::id())
+        );
+        drop(lock);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn concurrent_stale_takeovers_leave_one_holder() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = lock_file_path(dir.path());
+        fs::create_dir_all(path.parent().expect("parent")).expect("create .cgrep");
+        let stale = LockInfo {
+            pid: u32::MAX - 1,
+            process_start: None,
+            acquired_at_ms: 1,
+            command: "index".to_string(),
+        };
+        fs::write(&path, serde_json::to_vec(&stale).expect("encode")).expect("write lock");
+
+        let waiters = 8;
+        let barrier = std::sync::Barrier::new(waiters);
+        let results: Vec<Result<IndexLock>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..waiters)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        acquire(dir.path(), "index", LockWait::NoWait)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("waiter"))
+                .collect()
+        });
+
+        let held: Vec<&IndexLock> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+        assert_eq!(held.len(), 1, "exactly one waiter takes over");
+        assert_eq!(read_lock_info(&path).as_ref(), Some(&held[0].info));
+        for err in results.iter().filter_map(|r| r.as_ref().err()) {
+            assert!(err.downcast_ref::<IndexLockedError>().is_some(), "{err:#}");
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reused_pid_with_different_start_time_is_stale() {
+        let pid = std::process::id();
+        let current = process_start_time(pid).expect("start time");
+        let reused = LockInfo {
+            pid,
+            process_start: Some(current + 1),
+            acquired_at_ms: 1,
+            command: "index".to_string(),
+        };
+        assert!(!holder_alive(&reused));
+        let same = LockInfo {
+            process_start: Some(current),
+            ..reused
+        };
+        assert!(holder_alive(&same));
+    }
+}
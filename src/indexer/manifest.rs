@@ -11,6 +11,7 @@ use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::indexer::scanner::detect_language;
+use cgrep::paths;
 
 pub(crate) const MANIFEST_VERSION: &str = "1";
 pub(crate) const MANIFEST_DIR_REL: &str = ".cgrep/manifest";
@@ -299,7 +300,10 @@ pub(crate) fn apply_manifest_delta(
 
 pub(crate) fn relative_path(root: &Path, abs: &Path) -> Option<String> {
     let rel = abs.strip_prefix(root).ok()?;
-    let path = rel.to_string_lossy().replace('\\', "/");
+    let mut path = paths::encode_path(rel);
+    if cfg!(windows) {
+        path = path.replace('\\', "/");
+    }
     if path.is_empty() {
         None
     } else {
@@ -307,7 +311,7 @@ pub(crate) fn relative_path(root: &Path, abs: &Path) -> Option<String> {
     }
 }
 
-fn file_mtime_nanos(metadata: &std::fs::Metadata) -> u64 {
+pub(crate) fn file_mtime_nanos(metadata: &std::fs::Metadata) -> u64 {
     metadata
         .modified()
         .ok()
@@ -316,7 +320,7 @@ fn file_mtime_nanos(metadata: &std::fs::Metadata) -> u64 {
         .unwrap_or(0)
 }
 
-fn hash_file_streaming(path: &Path) -> Result<String> {
+pub(crate) fn hash_file_streaming(path: &Path) -> Result<String> {
     let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
     let mut reader = BufReader::new(file);
     let mut hasher = blake3::Hasher::new();
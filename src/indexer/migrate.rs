@@ -0,0 +1,396 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! In-place upgrades for indexes written by older cgrep versions.
+//!
+//! Each schema change that adds fields bumps [`INDEX_SCHEMA_VERSION`] and
+//! registers a [`Migration`] that derives the new fields from what the old
+//! index already stores. An upgrade copies every live document into a staging
+//! index with the current schema, lets each pending step fill in its fields
+//! one source file at a time, and swaps the result in like a forced rebuild.
+//! Indexes older than the first migratable version are rebuilt instead.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tantivy::schema::{Schema, Value};
+use tantivy::{DocAddress, Index, IndexWriter, TantivyDocument};
+
+use crate::indexer::index::{
+    add_symbol_metrics, applied_migrations, chunk_doc_comments, extract_symbols_from_text,
+    swap_staged_index, symbol_id_for, IndexFields, TextChunk, STAGING_DIR_NAME,
+};
+use crate::parser::symbols::Symbol;
+use cgrep::utils::INDEX_DIR;
+
+/// Schema version written by this build.
+pub(crate) const INDEX_SCHEMA_VERSION: u32 = 3;
+
+/// Fields of the oldest index layout that can still be migrated (version 1).
+const BASE_FIELDS: &[&str] = &[
+    "path",
+    "path_exact",
+    "content",
+    "language",
+    "symbols",
+    "doc_type",
+    "symbol_id",
+    "symbol_end_line",
+    "line_number",
+];
+
+/// Fills in the fields a migration adds, for all documents of one source path.
+type MigrateStep = fn(&IndexFields, &mut [TantivyDocument]);
+
+pub(crate) struct Migration {
+    /// Version the step upgrades to; it applies to indexes below it.
+    pub to: u32,
+    /// Fields that first appear in version `to`.
+    added_fields: &'static [&'static str],
+    pub description: &'static str,
+    apply: MigrateStep,
+}
+
+/// Registered steps, ordered by target version.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 2,
+        added_fields: &["docs"],
+        description: "derive doc comments (`docs`) from stored content",
+        apply: derive_docs,
+    },
+    Migration {
+        to: 3,
+        added_fields: &["symbol_params", "symbol_nesting", "symbol_complexity"],
+        description: "derive symbol metrics from stored content",
+        apply: derive_metrics,
+    },
+];
+
+/// A migration applied to the index, recorded in `metadata.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct AppliedMigration {
+    pub from: u32,
+    pub to: u32,
+    pub description: String,
+}
+
+/// Schema version and migrations of the index under a root, for `status`
+/// and `doctor`.
+#[derive(Debug, Serialize)]
+pub(crate) struct SchemaStatus {
+    /// `None` when the index predates every migration and must be rebuilt.
+    pub version: Option<u32>,
+    pub current: u32,
+    /// Steps the next index update applies.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending: Vec<String>,
+    /// Steps applied since the index was last built from scratch.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub migrations: Vec<AppliedMigration>,
+}
+
+/// Schema state of the index under `root`; `None` without an index.
+pub(crate) fn schema_status(root: &Path) -> Option<SchemaStatus> {
+    let index_path = root.join(INDEX_DIR);
+    if !index_path.join("meta.json").is_file() {
+        return None;
+    }
+    let index = Index::open_in_dir(&index_path).ok()?;
+    let version = detect_version(&index.schema());
+    let pending = version
+        .map(pending)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|step| step.description.to_string())
+        .collect();
+    Some(SchemaStatus {
+        version,
+        current: INDEX_SCHEMA_VERSION,
+        pending,
+        migrations: applied_migrations(root),
+    })
+}
+
+/// Schema version of an index with `schema`, or `None` when it predates every
+/// migration and can only be rebuilt.
+pub(crate) fn detect_version(schema: &Schema) -> Option<u32> {
+    let has = |name: &&str| schema.get_field(name).is_ok();
+    if !BASE_FIELDS.iter().all(has) {
+        return None;
+    }
+    let mut version = 1;
+    for migration in MIGRATIONS {
+        if !migration.added_fields.iter().all(has) {
+            break;
+        }
+        version = migration.to;
+    }
+    Some(version)
+}
+
+/// Steps that take an index at `version` to [`INDEX_SCHEMA_VERSION`].
+pub(crate) fn pending(version: u32) -> Vec<&'static Migration> {
+    MIGRATIONS
+        .iter()
+        .filter(|migration| migration.to > version)
+        .collect()
+}
+
+/// Upgrade the index at `index_path` to `schema` in place.
+///
+/// Returns the applied steps, or `None` when the index is too old to migrate.
+pub(crate) fn upgrade(
+    index_path: &Path,
+    schema: &Schema,
+    fields: &IndexFields,
+    writer_budget_bytes: usize,
+) -> Result<Option<Vec<AppliedMigration>>> {
+    let old = Index::open_in_dir(index_path).context("Failed to open existing index")?;
+    let old_schema = old.schema();
+    let Some(version) = detect_version(&old_schema) else {
+        return Ok(None);
+    };
+    let steps = pending(version);
+    if steps.is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+
+    let reader = old.reader().context("Failed to open index reader")?;
+    let searcher = reader.searcher();
+    let old_path = old_schema.get_field("path")?;
+    // Addresses only, so memory stays proportional to the document count;
+    // documents are loaded again one path at a time below.
+    let mut by_path: BTreeMap<String, Vec<DocAddress>> = BTreeMap::new();
+    for (segment_ord, segment) in searcher.segment_readers().iter().enumerate() {
+        for doc_id in segment.doc_ids_alive() {
+            let address = DocAddress::new(segment_ord as u32, doc_id);
+            let doc: TantivyDocument = searcher.doc(address)?;
+            let path = doc
+                .get_first(old_path)
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+                .to_string();
+            by_path.entry(path).or_default().push(address);
+        }
+    }
+
+    let staging_path = index_path.join(STAGING_DIR_NAME);
+    if staging_path.exists() {
+        std::fs::remove_dir_all(&staging_path)?;
+    }
+    std::fs::create_dir_all(&staging_path)?;
+    let staged = Index::create_in_dir(&staging_path, schema.clone())
+        .context("Failed to create staging index")?;
+    let mut writer: IndexWriter = staged
+        .writer(writer_budget_bytes)
+        .context("Failed to create index writer")?;
+    for addresses in by_path.values() {
+        let mut docs = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let doc: TantivyDocument = searcher.doc(*address)?;
+            docs.push(convert_document(&doc, &old_schema, schema));
+        }
+        for step in &steps {
+            (step.apply)(fields, &mut docs);
+        }
+        for doc in docs {
+            writer.add_document(doc)?;
+        }
+    }
+    writer.commit()?;
+    writer
+        .wait_merging_threads()
+        .context("Failed to finalize staging index")?;
+    drop(staged);
+    drop(searcher);
+    drop(reader);
+    drop(old);
+    swap_staged_index(index_path, &staging_path)?;
+
+    let mut from = version;
+    Ok(Some(
+        steps
+            .into_iter()
+            .map(|step| {
+                let applied = AppliedMigration {
+                    from,
+                    to: step.to,
+                    description: step.description.to_string(),
+                };
+                from = step.to;
+                applied
+            })
+            .collect(),
+    ))
+}
+
+/// Copy stored values of `doc` into a document for `schema`, matching fields
+/// by name.
+fn convert_document(
+    doc: &TantivyDocument,
+    old_schema: &Schema,
+    schema: &Schema,
+) -> TantivyDocument {
+    let mut converted = TantivyDocument::default();
+    for field_value in doc.field_values() {
+        let name = old_schema.get_field_name(field_value.field());
+        if let Ok(field) = schema.get_field(name) {
+            converted.add_field_value(field, field_value.value().clone());
+        }
+    }
+    converted
+}
+
+fn text(doc: &TantivyDocument, field: tantivy::schema::Field) -> &str {
+    doc.get_first(field)
+        .and_then(|value| value.as_str())
+        .unwrap_or_default()
+}
+
+/// Source of one file reassembled from its stored chunks.
+struct StoredFile {
+    /// Index of each chunk's document, with the chunk, in line order.
+    chunks: Vec<(usize, TextChunk)>,
+    doc_path: String,
+    lang: String,
+    /// Symbols re-extracted exactly as indexing computes them.
+    symbols: Vec<Symbol>,
+}
+
+/// `None` when the documents hold no file chunks or no language.
+fn stored_file(fields: &IndexFields, docs: &[TantivyDocument]) -> Option<StoredFile> {
+    let mut chunks: Vec<(usize, TextChunk)> = docs
+        .iter()
+        .enumerate()
+        .filter(|(_, doc)| text(doc, fields.doc_type) == "file")
+        .map(|(idx, doc)| {
+            let start_line = doc
+                .get_first(fields.line_number)
+                .and_then(|value| value.as_u64())
+                .unwrap_or(1);
+            let content = text(doc, fields.content).to_string();
+            (
+                idx,
+                TextChunk {
+                    start_line,
+                    content,
+                },
+            )
+        })
+        .collect();
+    let &(first, _) = chunks.first()?;
+    let lang = text(&docs[first], fields.language).to_string();
+    if lang.is_empty() {
+        return None;
+    }
+    let doc_path = text(&docs[first], fields.path).to_string();
+    chunks.sort_by_key(|(_, chunk)| chunk.start_line);
+    let full_text: String = chunks
+        .iter()
+        .map(|(_, chunk)| chunk.content.as_str())
+        .collect();
+    let symbols = extract_symbols_from_text(&full_text, &lang);
+    Some(StoredFile {
+        chunks,
+        doc_path,
+        lang,
+        symbols,
+    })
+}
+
+/// Version 2: doc comments for file chunks and symbols, re-extracted from the
+/// stored file chunks exactly as indexing computes them.
+fn derive_docs(fields: &IndexFields, docs: &mut [TantivyDocument]) {
+    let Some(file) = stored_file(fields, docs) else {
+        return;
+    };
+    for (idx, chunk) in &file.chunks {
+        let comments = chunk_doc_comments(chunk, &file.symbols);
+        if !comments.is_empty() {
+            docs[*idx].add_text(fields.docs, comments);
+        }
+    }
+    let symbol_docs: BTreeMap<String, &str> = file
+        .symbols
+        .iter()
+        .filter_map(|symbol| {
+            let doc = symbol.doc.as_deref()?;
+            Some((symbol_id_for(&file.doc_path, &file.lang, symbol), doc))
+        })
+        .collect();
+    for doc in docs.iter_mut() {
+        if text(doc, fields.doc_type) != "symbol" {
+            continue;
+        }
+        if let Some(comment) = symbol_docs.get(text(doc, fields.symbol_id)) {
+            doc.add_text(fields.docs, comment);
+        }
+    }
+}
+
+/// Version 3: metrics of function-like symbols, measured on the reassembled
+/// source.
+fn derive_metrics(fields: &IndexFields, docs: &mut [TantivyDocument]) {
+    let Some(file) = stored_file(fields, docs) else {
+        return;
+    };
+    let metrics: BTreeMap<String, _> = file
+        .symbols
+        .iter()
+        .filter_map(|symbol| {
+            let metrics = symbol.metrics?;
+            Some((symbol_id_for(&file.doc_path, &file.lang, symbol), metrics))
+        })
+        .collect();
+    for doc in docs.iter_mut() {
+        if text(doc, fields.doc_type) != "symbol" {
+            continue;
+        }
+        if let Some(metrics) = metrics.get(text(doc, fields.symbol_id)) {
+            add_symbol_metrics(fields, doc, metrics);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::schema::{STORED, STRING, TEXT};
+
+    fn base_schema() -> tantivy::schema::SchemaBuilder {
+        let mut builder = Schema::builder();
+        for name in ["path", "content", "language", "symbols"] {
+            builder.add_text_field(name, TEXT | STORED);
+        }
+        for name in ["path_exact", "doc_type", "symbol_id"] {
+            builder.add_text_field(name, STRING | STORED);
+        }
+        builder.add_u64_field("symbol_end_line", STORED);
+        builder.add_u64_field("line_number", tantivy::schema::INDEXED | STORED);
+        builder
+    }
+
+    #[test]
+    fn versions_are_detected_from_schema_fields() {
+        assert_eq!(detect_version(&base_schema().build()), Some(1));
+        let mut v2 = base_schema();
+        v2.add_text_field("docs", TEXT | STORED);
+        assert_eq!(detect_version(&v2.build()), Some(2));
+        let mut current = base_schema();
+        current.add_text_field("docs", TEXT | STORED);
+        for name in ["symbol_params", "symbol_nesting", "symbol_complexity"] {
+            current.add_u64_field(name, STORED);
+        }
+        assert_eq!(detect_version(&current.build()), Some(INDEX_SCHEMA_VERSION));
+
+        let mut pre_symbols = Schema::builder();
+        pre_symbols.add_text_field("path", TEXT | STORED);
+        pre_symbols.add_text_field("content", TEXT | STORED);
+        assert_eq!(detect_version(&pre_symbols.build()), None);
+
+        assert_eq!(pending(1).len(), 2);
+        assert_eq!(pending(2).len(), 1);
+        assert!(pending(INDEX_SCHEMA_VERSION).is_empty());
+    }
+}
@@ -2,12 +2,28 @@
 
 //! Indexer module - handles file scanning, indexing, and watching
 
+pub mod archive;
+pub mod branches;
+pub mod churn;
+pub mod compat;
 pub mod daemon;
+pub mod doctor;
+pub mod freshness;
+pub mod git_history;
 pub mod index;
+pub mod literals;
+pub mod lock;
 pub mod manifest;
+pub mod migrate;
+pub mod regions;
 pub mod reuse;
 pub mod scanner;
+pub mod size_policy;
+pub mod snapshot;
+pub mod sparse;
 pub mod status;
 pub mod watch;
+pub mod watch_hooks;
+pub mod watch_limits;
 
 pub use index::IndexBuilder;
@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Embedded-language regions inside host source files.
+//!
+//! String literals that hold another language (SQL in Python strings, GraphQL
+//! in `gql` template literals, HTML in `html` literals or component templates)
+//! are indexed as extra `region` documents tagged with the embedded language,
+//! so `--type sql` finds inline statements. Regions are excluded from searches
+//! that do not ask for an embedded language, since the host file's own
+//! documents already cover the text.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::parser::sfc::{self, SfcBlockKind};
+
+/// Shortest literal considered for classification.
+const MIN_REGION_CHARS: usize = 12;
+
+static SQL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?is)^\s*(select\b.+\bfrom\b|insert\s+into\b|update\s+\w.*\bset\b|delete\s+from\b|create\s+(or\s+replace\s+)?(table|index|view|unique\s+index)\b|alter\s+table\b|drop\s+(table|index|view)\b|with\s+\w+\s+as\s*\()",
+    )
+    .expect("valid SQL pattern")
+});
+
+static GRAPHQL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)^\s*(query|mutation|subscription|fragment)\b[^{]*\{")
+        .expect("valid GraphQL pattern")
+});
+
+static HTML_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)^\s*<([a-zA-Z][\w-]*)[^>]*>").expect("valid HTML pattern"));
+
+/// A span of embedded-language text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EmbeddedRegion {
+    pub language: &'static str,
+    /// 1-based line in the host file where the region starts.
+    pub start_line: u64,
+    pub content: String,
+}
+
+/// Whether a `--type` filter value names an embedded language.
+pub(crate) fn is_embedded_type(filter: &str) -> bool {
+    embedded_language_for_type(filter).is_some()
+}
+
+/// Whether a region tagged `language` satisfies a `--type` filter.
+pub(crate) fn region_matches_type(language: &str, filter: &str) -> bool {
+    embedded_language_for_type(filter) == Some(language)
+}
+
+fn embedded_language_for_type(filter: &str) -> Option<&'static str> {
+    match filter.to_ascii_lowercase().as_str() {
+        "sql" => Some("sql"),
+        "graphql" | "gql" => Some("graphql"),
+        "html" | "htm" => Some("html"),
+        _ => None,
+    }
+}
+
+/// Extract embedded regions from a host file written in `host_lang`.
+pub(crate) fn extract_regions(text: &str, host_lang: &str) -> Vec<EmbeddedRegion> {
+    let mut regions = Vec::new();
+    match host_lang {
+        "python" => collect_literals(text, 0, LexRules::PYTHON, &mut regions),
+        "javascript" | "typescript" => {
+            collect_literals(text, 0, LexRules::JAVASCRIPT, &mut regions)
+        }
+        "vue" | "svelte" => {
+            for block in sfc::split_blocks(text, host_lang) {
+                let offset = block.content_start;
+                match block.kind {
+                    SfcBlockKind::Script => {
+                        collect_literals(text, offset, LexRules::JAVASCRIPT, &mut regions)
+                    }
+                    SfcBlockKind::Template if block.lang.is_none() => {
+                        let content = block.content(text);
+                        if !content.trim().is_empty() {
+                            regions.push(EmbeddedRegion {
+                                language: "html",
+                                start_line: block.start_line as u64,
+                                content: content.to_string(),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        // YAML quoting is too loose for the C-like lexer.
+        "" | "yaml" => {}
+        _ => collect_literals(text, 0, LexRules::C_LIKE, &mut regions),
+    }
+    regions
+}
+
+/// Byte offsets of one string literal in its host file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LiteralSpan {
+    /// Offset of the opening quote.
+    pub quote_start: usize,
+    pub content_start: usize,
+    pub content_end: usize,
+}
+
+/// String literals in a host file written in `host_lang`, skipping comments.
+pub(crate) fn string_literal_spans(text: &str, host_lang: &str) -> Vec<LiteralSpan> {
+    let mut spans = Vec::new();
+    let mut push = |span: LiteralSpan, _tag: &str| spans.push(span);
+    match host_lang {
+        "python" => lex_literals(text, 0, LexRules::PYTHON, &mut push),
+        "javascript" | "typescript" => lex_literals(text, 0, LexRules::JAVASCRIPT, &mut push),
+        "vue" | "svelte" => {
+            for block in sfc::split_blocks(text, host_lang) {
+                if block.kind == SfcBlockKind::Script {
+                    lex_literals(text, block.content_start, LexRules::JAVASCRIPT, &mut push);
+                }
+            }
+        }
+        "" | "yaml" | "json" | "toml" => {}
+        _ => lex_literals(text, 0, LexRules::C_LIKE, &mut push),
+    }
+    spans
+}
+
+/// Comment and string syntax for the host language.
+#[derive(Clone, Copy)]
+struct LexRules {
+    hash_comments: bool,
+    slash_comments: bool,
+    triple_quotes: bool,
+    single_quote_strings: bool,
+    template_literals: bool,
+}
+
+impl LexRules {
+    const PYTHON: Self = Self {
+        hash_comments: true,
+        slash_comments: false,
+        triple_quotes: true,
+        single_quote_strings: true,
+        template_literals: false,
+    };
+    const JAVASCRIPT: Self = Self {
+        hash_comments: false,
+        slash_comments: true,
+        triple_quotes: false,
+        single_quote_strings: true,
+        template_literals: true,
+    };
+    /// Go/Java/Rust/C-family: `"..."` strings, plus Go raw `` `...` `` strings.
+    const C_LIKE: Self = Self {
+        hash_comments: false,
+        slash_comments: true,
+        triple_quotes: false,
+        single_quote_strings: false,
+        template_literals: true,
+    };
+}
+
+/// Lex string literals in `text[start..]` and keep the ones that classify.
+fn collect_literals(text: &str, start: usize, rules: LexRules, out: &mut Vec<EmbeddedRegion>) {
+    lex_literals(text, start, rules, &mut |span, tag| {
+        let content = &text[span.content_start..span.content_end];
+        if let Some(language) = classify(tag, content) {
+            out.push(EmbeddedRegion {
+                language,
+                start_line: line_of(text, span.content_start),
+                content: content.to_string(),
+            });
+        }
+    });
+}
+
+/// Call `visit` with each string literal in `text[start..]` and the tag
+/// before it (template literals only).
+fn lex_literals(
+    text: &str,
+    start: usize,
+    rules: LexRules,
+    visit: &mut impl FnMut(LiteralSpan, &str),
+) {
+    let bytes = text.as_bytes();
+    let mut idx = start;
+    while idx < bytes.len() {
+        let byte = bytes[idx];
+        if rules.hash_comments && byte == b'#' {
+            idx = skip_line(bytes, idx);
+            continue;
+        }
+        if rules.slash_comments && byte == b'/' && bytes.get(idx + 1) == Some(&b'/') {
+            idx = skip_line(bytes, idx);
+            continue;
+        }
+        if rules.slash_comments && byte == b'/' && bytes.get(idx + 1) == Some(&b'*') {
+            idx = find_from(bytes, idx + 2, b"*/").map_or(bytes.len(), |end| end + 2);
+            continue;
+        }
+        // A `</script>` ends a component script block.
+        if rules.template_literals && bytes[idx..].starts_with(b"</script") {
+            return;
+        }
+
+        let quote = match byte {
+            b'"' => Some(b'"'),
+            b'\'' if rules.single_quote_strings => Some(b'\''),
+            b'`' if rules.template_literals => Some(b'`'),
+            _ => None,
+        };
+        let Some(quote) = quote else {
+            idx += 1;
+            continue;
+        };
+
+        let triple = rules.triple_quotes
+            && bytes.get(idx + 1) == Some(&quote)
+            && bytes.get(idx + 2) == Some(&quote);
+        let (content_start, content_end, next) = if triple {
+            let delimiter = [quote; 3];
+            let content_start = idx + 3;
+            match find_from(bytes, content_start, &delimiter) {
+                Some(end) => (content_start, end, end + 3),
+                None => return,
+            }
+        } else {
+            let content_start = idx + 1;
+            let multiline = quote == b'`';
+            match find_closing_quote(bytes, content_start, quote, multiline) {
+                Some(end) => (content_start, end, end + 1),
+                None => {
+                    idx = content_start;
+                    continue;
+                }
+            }
+        };
+
+        let tag = if quote == b'`' {
+            literal_tag(&text[start..idx])
+        } else {
+            ""
+        };
+        visit(
+            LiteralSpan {
+                quote_start: idx,
+                content_start,
+                content_end,
+            },
+            tag,
+        );
+        idx = next;
+    }
+}
+
+fn classify(tag: &str, content: &str) -> Option<&'static str> {
+    match tag {
+        "gql" | "graphql" => return Some("graphql"),
+        "html" => return Some("html"),
+        "sql" => return Some("sql"),
+        _ => {}
+    }
+    if content.trim().len() < MIN_REGION_CHARS {
+        return None;
+    }
+    if SQL_RE.is_match(content) {
+        Some("sql")
+    } else if GRAPHQL_RE.is_match(content) {
+        Some("graphql")
+    } else if HTML_RE.is_match(content) && content.contains("</") {
+        Some("html")
+    } else {
+        None
+    }
+}
+
+/// Identifier immediately before a template literal (`gql`, `sql.unsafe` -> `sql`).
+fn literal_tag(before: &str) -> &str {
+    let trimmed = before.trim_end();
+    let start = trimmed
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+        .map_or(0, |pos| pos + 1);
+    let ident = &trimmed[start..];
+    ident.split('.').next().unwrap_or("")
+}
+
+fn find_closing_quote(bytes: &[u8], from: usize, quote: u8, multiline: bool) -> Option<usize> {
+    let mut idx = from;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\\' => idx += 2,
+            b'\n' if !multiline => return None,
+            byte if byte == quote => return Some(idx),
+            _ => idx += 1,
+        }
+    }
+    None
+}
+
+fn find_from(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| from + pos)
+}
+
+fn skip_line(bytes: &[u8], from: usize) -> usize {
+    bytes[from..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |pos| from + pos + 1)
+}
+
+fn line_of(text: &str, offset: usize) -> u64 {
+    text[..offset].matches('\n').count() as u64 + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sql_in_python_strings_is_extracted() {
+        let source = r#"# don't treat this comment as a string
+def load(db):
+    rows = db.execute("""
+        SELECT id, name
+        FROM users
+        WHERE active = 1
+    """)
+    label = "select a color"
+    db.execute('DELETE FROM sessions WHERE expired = 1')
+"#;
+        let regions = extract_regions(source, "python");
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].language, "sql");
+        assert_eq!(regions[0].start_line, 3);
+        assert!(regions[0].content.contains("FROM users"));
+        assert_eq!(regions[1].start_line, 9);
+    }
+
+    #[test]
+    fn tagged_template_literals_use_their_tag() {
+        let source = "const Q = gql`\n  query Viewer { viewer { id } }\n`;\nconst view = html`<p>${name}</p>`;\nconst s = `hello ${name}`;\n";
+        let regions = extract_regions(source, "typescript");
+        let languages: Vec<&str> = regions.iter().map(|r| r.language).collect();
+        assert_eq!(languages, vec!["graphql", "html"]);
+        assert_eq!(regions[0].start_line, 1);
+        assert_eq!(regions[1].start_line, 4);
+    }
+
+    #[test]
+    fn component_templates_are_html_regions() {
+        let source = "<template>\n  <div class=\"card\">{{ title }}</div>\n</template>\n<script>\nconst q = `SELECT * FROM cards`;\n</script>\n";
+        let regions = extract_regions(source, "vue");
+        let languages: Vec<&str> = regions.iter().map(|r| r.language).collect();
+        assert_eq!(languages, vec!["html", "sql"]);
+        assert_eq!(regions[1].start_line, 5);
+    }
+
+    #[test]
+    fn embedded_type_filters_are_recognized() {
+        assert!(is_embedded_type("SQL"));
+        assert!(is_embedded_type("gql"));
+        assert!(!is_embedded_type("python"));
+        assert!(region_matches_type("graphql", "gql"));
+        assert!(!region_matches_type("sql", "html"));
+    }
+}
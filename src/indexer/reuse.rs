@@ -12,8 +12,11 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::indexer::index::STAGING_DIR_NAME;
+use crate::indexer::lock::{LOCK_FILE_NAME, TAKEOVER_FILE_NAME};
 use crate::indexer::manifest;
-use crate::indexer::scanner::FileScanner;
+use crate::indexer::scanner::{FileScanner, IgnoreRules};
+use crate::indexer::snapshot::SNAPSHOTS_DIR_NAME;
 use cgrep::utils::INDEX_DIR;
 
 pub(crate) const REUSE_STATE_FILE_NAME: &str = "reuse-state.json";
@@ -165,6 +168,7 @@ pub struct ReuseProfile {
     pub excludes: Vec<String>,
     pub includes: Vec<String>,
     pub respect_git_ignore: bool,
+    pub ignore_rules: IgnoreRules,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -416,7 +420,8 @@ fn fingerprint_from_workspace(
 ) -> Result<SimilarityFingerprint> {
     let scanner = FileScanner::with_excludes(root, profile.excludes.clone())
         .with_includes(profile.includes.clone())
-        .with_gitignore(profile.respect_git_ignore);
+        .with_gitignore(profile.respect_git_ignore)
+        .with_ignore_rules(profile.ignore_rules);
     let files = scanner.list_files()?;
 
     let mut rel_abs_pairs: Vec<(String, PathBuf)> = files
@@ -594,7 +599,7 @@ fn similarity_score(snapshot: &SimilarityFingerprint, current: &SimilarityFinger
     hash_component + path_component - file_delta
 }
 
-fn copy_file(src: &Path, dst: &Path) -> Result<()> {
+pub(crate) fn copy_file(src: &Path, dst: &Path) -> Result<()> {
     if let Some(parent) = dst.parent() {
         fs::create_dir_all(parent)?;
     }
@@ -603,7 +608,7 @@ fn copy_file(src: &Path, dst: &Path) -> Result<()> {
     Ok(())
 }
 
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+pub(crate) fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     if !src.exists() {
         return Ok(());
     }
@@ -634,6 +639,11 @@ fn clear_local_index_artifacts(root: &Path) -> Result<()> {
         "watch.pid",
         "watch.log",
         REUSE_STATE_FILE_NAME,
+        LOCK_FILE_NAME,
+        TAKEOVER_FILE_NAME,
+        "branch.json",
+        "branches",
+        SNAPSHOTS_DIR_NAME,
     ];
     for entry in fs::read_dir(&state_dir)? {
         let entry = entry?;
@@ -873,11 +883,17 @@ fn copy_local_artifacts_to_snapshot(root: &Path, snapshot_stage: &Path) -> Resul
         if matches!(
             name,
             "manifest"
+                | STAGING_DIR_NAME
+                | LOCK_FILE_NAME
+                | TAKEOVER_FILE_NAME
                 | "status.json"
                 | "index-background.log"
                 | "watch.pid"
                 | "watch.log"
                 | REUSE_STATE_FILE_NAME
+                | "branch.json"
+                | "branches"
+                | SNAPSHOTS_DIR_NAME
         ) {
             continue;
         }
@@ -4,13 +4,18 @@
 
 use anyhow::Result;
 use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 
+use crate::indexer::archive::has_archive_suffix;
+use cgrep::{cancel, encoding};
+
 const INDEXABLE_EXTENSIONS: &[&str] = &[
     "rs", "ts", "tsx", "js", "jsx", "py", "go", "java", "c", "cpp", "cc", "h", "hpp", "cs", "rb",
-    "php", "swift", "kt", "kts", "scala", "lua", "md", "txt", "json", "yaml", "toml",
+    "php", "swift", "kt", "kts", "scala", "lua", "vue", "svelte", "md", "txt", "json", "yaml",
+    "yml", "toml",
 ];
 
 /// Scanned file with content
@@ -21,13 +26,48 @@ pub struct ScannedFile {
     pub language: Option<String>,
 }
 
+/// Hidden-file and ignore-source switches, with ripgrep's meaning
+/// (`--hidden`, `--no-ignore-vcs`, `--no-ignore-global`).
+///
+/// `.ignore` files are governed separately by [`FileScanner::with_gitignore`]
+/// (`--no-ignore` turns every source off).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IgnoreRules {
+    /// Walk hidden files and directories (skipped by default).
+    pub hidden: bool,
+    /// Skip `.gitignore`, `.git/info/exclude`, and the global gitignore.
+    pub no_ignore_vcs: bool,
+    /// Skip only the global gitignore (`core.excludesFile`).
+    pub no_ignore_global: bool,
+}
+
+impl IgnoreRules {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Short stable tag for cache keys.
+    pub fn cache_tag(&self) -> String {
+        format!(
+            "h{}v{}g{}",
+            usize::from(self.hidden),
+            usize::from(self.no_ignore_vcs),
+            usize::from(self.no_ignore_global)
+        )
+    }
+}
+
 /// File scanner that respects ignore files and custom excludes
 pub struct FileScanner {
     root: PathBuf,
     exclude_patterns: Vec<String>,
     include_paths: Vec<String>,
     respect_git_ignore: bool,
+    ignore_rules: IgnoreRules,
     recursive: bool,
+    archive_extensions: Vec<String>,
+    cancellable: bool,
 }
 
 impl FileScanner {
@@ -37,7 +77,10 @@ impl FileScanner {
             exclude_patterns: Vec::new(),
             include_paths: Vec::new(),
             respect_git_ignore: true,
+            ignore_rules: IgnoreRules::default(),
             recursive: true,
+            archive_extensions: Vec::new(),
+            cancellable: false,
         }
     }
 
@@ -54,31 +97,51 @@ impl FileScanner {
         self
     }
 
+    /// Hidden-file and per-source ignore switches.
+    pub fn with_ignore_rules(mut self, rules: IgnoreRules) -> Self {
+        self.ignore_rules = rules;
+        self
+    }
+
     /// Explicit paths to include even when ignore files would normally skip them.
     pub fn with_includes(mut self, includes: Vec<String>) -> Self {
         self.include_paths = includes;
         self
     }
 
+    /// Also list archives with these suffixes (e.g. `jar`, `tar.gz`).
+    pub fn with_archives(mut self, extensions: Vec<String>) -> Self {
+        self.archive_extensions = extensions;
+        self
+    }
+
     /// Enable or disable recursive traversal
     pub fn with_recursive(mut self, enabled: bool) -> Self {
         self.recursive = enabled;
         self
     }
 
+    /// Stop walking once `cgrep::cancel` reports a timeout or Ctrl-C. Only for
+    /// searches; an index built from a partial walk would drop files.
+    pub fn with_cancellation(mut self, enabled: bool) -> Self {
+        self.cancellable = enabled;
+        self
+    }
+
     fn make_builder(&self) -> WalkBuilder {
         let mut builder = WalkBuilder::new(&self.root);
-        builder.hidden(false);
+        builder.hidden(!self.ignore_rules.hidden);
         if !self.recursive {
             builder.max_depth(Some(1));
         }
 
         if self.respect_git_ignore {
+            let vcs = !self.ignore_rules.no_ignore_vcs;
             builder
                 .ignore(true)
-                .git_ignore(true)
-                .git_exclude(true)
-                .git_global(true);
+                .git_ignore(vcs)
+                .git_exclude(vcs)
+                .git_global(vcs && !self.ignore_rules.no_ignore_global);
         } else {
             builder
                 .ignore(false)
@@ -197,11 +260,15 @@ impl FileScanner {
             .build_parallel();
 
         let exclude_patterns = self.exclude_patterns.clone();
+        let cancellable = self.cancellable;
         walker.run(|| {
             let tx = tx.clone();
             let exclude_patterns = exclude_patterns.clone();
 
             Box::new(move |entry| {
+                if cancellable && cancel::should_stop() {
+                    return ignore::WalkState::Quit;
+                }
                 if let Ok(entry) = entry {
                     let path = entry.path();
 
@@ -212,7 +279,8 @@ impl FileScanner {
                     if path.is_file() {
                         if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
                             if is_indexable_extension(ext) {
-                                if let Ok(content) = std::fs::read_to_string(path) {
+                                if let Ok(Some(decoded)) = encoding::read_text_file(path) {
+                                    let content = decoded.text.into_owned();
                                     let language = detect_language(ext);
                                     let _ = tx.send(ScannedFile {
                                         path: path.to_path_buf(),
@@ -234,7 +302,8 @@ impl FileScanner {
         if !explicit_files.is_empty() {
             for path in explicit_files {
                 if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                    if let Ok(content) = std::fs::read_to_string(&path) {
+                    if let Ok(Some(decoded)) = encoding::read_text_file(&path) {
+                        let content = decoded.text.into_owned();
                         let language = detect_language(ext);
                         files.push(ScannedFile {
                             path,
@@ -267,9 +336,11 @@ impl FileScanner {
             .build_parallel();
 
         let exclude_patterns = self.exclude_patterns.clone();
+        let archive_extensions = self.archive_extensions.clone();
         walker.run(|| {
             let tx = tx.clone();
             let exclude_patterns = exclude_patterns.clone();
+            let archive_extensions = archive_extensions.clone();
 
             Box::new(move |entry| {
                 if let Ok(entry) = entry {
@@ -281,7 +352,12 @@ impl FileScanner {
 
                     if path.is_file() {
                         if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                            if is_indexable_extension(ext) {
+                            let is_archive = || {
+                                entry.file_name().to_str().is_some_and(|name| {
+                                    has_archive_suffix(name, &archive_extensions)
+                                })
+                            };
+                            if is_indexable_extension(ext) || is_archive() {
                                 let _ = tx.send(path.to_path_buf());
                             }
                         }
@@ -324,19 +400,27 @@ pub fn detect_language(ext: &str) -> Option<String> {
         "kt" | "kts" => Some("kotlin".into()),
         "scala" => Some("scala".into()),
         "lua" => Some("lua".into()),
+        // Single-file components; symbols come from their script blocks.
+        "vue" => Some("vue".into()),
+        "svelte" => Some("svelte".into()),
+        // Config files; their keys are indexed as dotted-path symbols.
+        "json" => Some("json".into()),
+        "yaml" | "yml" => Some("yaml".into()),
+        "toml" => Some("toml".into()),
         _ => None,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{detect_language, is_indexable_extension};
+    use super::{detect_language, is_indexable_extension, FileScanner, IgnoreRules};
+    use tempfile::TempDir;
 
     #[test]
     fn detectable_code_extensions_are_indexable() {
         for ext in [
             "rs", "ts", "tsx", "js", "jsx", "py", "go", "java", "c", "h", "cpp", "cc", "hpp", "cs",
-            "rb", "php", "swift", "kt", "kts", "scala", "lua",
+            "rb", "php", "swift", "kt", "kts", "scala", "lua", "vue", "svelte",
         ] {
             assert!(is_indexable_extension(ext), "{ext} should be indexable");
         }
@@ -349,5 +433,48 @@ mod tests {
         assert_eq!(detect_language("kts").as_deref(), Some("kotlin"));
         assert!(is_indexable_extension("CC"));
         assert!(is_indexable_extension("KTS"));
+        assert_eq!(detect_language("yml").as_deref(), Some("yaml"));
+    }
+
+    #[test]
+    fn ignore_rules_follow_ripgrep_semantics() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".git")).expect("git dir");
+        std::fs::create_dir_all(root.join(".hidden")).expect("hidden dir");
+        std::fs::write(root.join(".gitignore"), "gen.rs\n").expect("gitignore");
+        std::fs::write(root.join(".ignore"), "skip.rs\n").expect("ignore");
+        for rel in ["a.rs", "gen.rs", "skip.rs", ".hidden/h.rs"] {
+            std::fs::write(root.join(rel), "fn f() {}\n").expect("write");
+        }
+
+        let list = |rules: IgnoreRules| {
+            let mut files: Vec<String> = FileScanner::new(root)
+                .with_ignore_rules(rules)
+                .list_files()
+                .expect("list")
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(root)
+                        .expect("relative")
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect();
+            files.sort();
+            files
+        };
+
+        assert_eq!(list(IgnoreRules::default()), ["a.rs"]);
+        let hidden = IgnoreRules {
+            hidden: true,
+            ..IgnoreRules::default()
+        };
+        assert_eq!(list(hidden), [".hidden/h.rs", "a.rs"]);
+        let no_vcs = IgnoreRules {
+            no_ignore_vcs: true,
+            ..IgnoreRules::default()
+        };
+        assert_eq!(list(no_vcs), ["a.rs", "gen.rs"]);
     }
 }
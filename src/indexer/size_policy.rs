@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Reduced indexing for oversized files (`[[index.size_policies]]`).
+//!
+//! Lockfiles, generated bundles, and data dumps with a text extension can
+//! dominate index time and crowd search results. A file matched by a policy
+//! stays discoverable by path, but its contents are left out: `symbols` keeps
+//! symbol headers (name and kind), `path` keeps nothing but the path.
+//!
+//! Policies are checked in order and the first match wins.
+
+use serde::{Deserialize, Serialize};
+
+use cgrep::config::{Config, SizePolicyMode};
+use cgrep::filters::CompiledGlob;
+
+/// One policy as persisted with the index options.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub(crate) struct SizePolicy {
+    pub path: Option<String>,
+    /// Lowercased, without a leading dot.
+    pub extensions: Vec<String>,
+    pub max_bytes: u64,
+    pub mode: SizePolicyMode,
+}
+
+impl SizePolicy {
+    pub(crate) fn from_config(config: &Config) -> Vec<Self> {
+        config
+            .index()
+            .size_policies
+            .iter()
+            .map(|policy| Self {
+                path: policy
+                    .path
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .map(str::to_string),
+                extensions: policy
+                    .extensions
+                    .iter()
+                    .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+                    .filter(|ext| !ext.is_empty())
+                    .collect(),
+                max_bytes: policy.max_bytes.unwrap_or(0),
+                mode: policy.mode,
+            })
+            .collect()
+    }
+
+    fn has_extension(&self, relative_path: &str) -> bool {
+        let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        let lower = name.to_ascii_lowercase();
+        self.extensions.iter().any(|ext| {
+            lower.len() > ext.len() + 1
+                && lower.ends_with(ext.as_str())
+                && lower.as_bytes()[lower.len() - ext.len() - 1] == b'.'
+        })
+    }
+}
+
+/// Policies with their path globs compiled.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SizePolicies {
+    policies: Vec<SizePolicy>,
+    globs: Vec<Option<CompiledGlob>>,
+}
+
+impl SizePolicies {
+    pub(crate) fn new(policies: Vec<SizePolicy>) -> Self {
+        let globs = policies
+            .iter()
+            .map(|policy| policy.path.as_deref().and_then(CompiledGlob::new))
+            .collect();
+        Self { policies, globs }
+    }
+
+    pub(crate) fn policies(&self) -> &[SizePolicy] {
+        &self.policies
+    }
+
+    /// Mode of the first policy matching a file at `relative_path` (with `/`
+    /// separators) that is `size` bytes long.
+    pub(crate) fn mode_for(&self, relative_path: &str, size: u64) -> Option<SizePolicyMode> {
+        self.policies
+            .iter()
+            .zip(&self.globs)
+            .find(|(policy, glob)| {
+                size > policy.max_bytes
+                    && match (&policy.path, glob) {
+                        (None, _) => true,
+                        (Some(_), Some(glob)) => glob.is_match(relative_path),
+                        (Some(_), None) => false,
+                    }
+                    && (policy.extensions.is_empty() || policy.has_extension(relative_path))
+            })
+            .map(|(policy, _)| policy.mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(path: Option<&str>, extensions: &[&str], max_bytes: u64) -> SizePolicy {
+        SizePolicy {
+            path: path.map(str::to_string),
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+            max_bytes,
+            mode: SizePolicyMode::Path,
+        }
+    }
+
+    #[test]
+    fn first_matching_policy_wins() {
+        let policies = SizePolicies::new(vec![
+            policy(None, &["lock"], 0),
+            SizePolicy {
+                mode: SizePolicyMode::Symbols,
+                ..policy(Some("dist/**"), &[], 1000)
+            },
+            policy(None, &["min.js"], 0),
+        ]);
+        assert_eq!(
+            policies.mode_for("Cargo.lock", 10),
+            Some(SizePolicyMode::Path)
+        );
+        assert_eq!(
+            policies.mode_for("web/dist/app.js", 5000),
+            Some(SizePolicyMode::Symbols)
+        );
+        assert_eq!(policies.mode_for("web/dist/app.js", 500), None);
+        assert_eq!(
+            policies.mode_for("web/dist/app.min.js", 500),
+            Some(SizePolicyMode::Path)
+        );
+        assert_eq!(policies.mode_for("src/block.rs", 1 << 20), None);
+        assert_eq!(policies.mode_for("empty.lock", 0), None);
+    }
+}
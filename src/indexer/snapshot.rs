@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Tagged index snapshots (`cgrep index snapshot` / `cgrep index rollback`).
+//!
+//! A snapshot is a copy of the index artifacts under
+//! `.cgrep/snapshots/<tag>/`. Tantivy segment files are never modified after
+//! they are written, so they are hardlinked and a snapshot costs little more
+//! than its metadata; files that change in place (Tantivy and cgrep
+//! metadata, the manifest, the embeddings database) are copied. Rollback
+//! stages a copy the same way, swaps it in with `meta.json` as the commit
+//! point, and leaves the snapshot in place.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::indexer::branches;
+use crate::indexer::index::{index_artifact_names, is_segment_file_name, swap_staged_index};
+use crate::indexer::lock::{self, LockWait};
+use crate::indexer::manifest;
+use crate::indexer::reuse::{copy_dir_recursive, copy_file};
+use crate::indexer::status::now_unix_ms;
+use cgrep::output::print_json;
+use cgrep::utils::INDEX_DIR;
+
+pub(crate) const SNAPSHOTS_DIR_NAME: &str = "snapshots";
+const SNAPSHOT_INFO_FILE_NAME: &str = "snapshot.json";
+
+/// Stored as `snapshot.json` next to the captured artifacts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotInfo {
+    tag: String,
+    /// Unix milliseconds.
+    created_at: u64,
+    /// Commit checked out when the snapshot was taken.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    head: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SnapshotReport {
+    action: &'static str,
+    root: String,
+    tag: String,
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    head: Option<String>,
+    /// Index entries captured or restored.
+    entries: usize,
+    /// Segment files shared by hardlink instead of copied.
+    hardlinked: usize,
+}
+
+pub(crate) fn resolve_root(path: Option<&str>) -> Result<PathBuf> {
+    let root = path
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .ok_or_else(|| anyhow::anyhow!("Cannot determine current directory"))?;
+    Ok(root.canonicalize().unwrap_or(root))
+}
+
+fn snapshots_dir(root: &Path) -> PathBuf {
+    root.join(INDEX_DIR).join(SNAPSHOTS_DIR_NAME)
+}
+
+/// Tags become directory names: ASCII letters, digits, `.`, `_`, `-`.
+fn validate_tag(tag: &str) -> Result<()> {
+    let valid = !tag.is_empty()
+        && !tag.starts_with('.')
+        && tag
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-'));
+    if !valid {
+        bail!("Invalid snapshot tag `{tag}`: use letters, digits, `.`, `_`, or `-`, not starting with `.`");
+    }
+    Ok(())
+}
+
+/// Tags of the stored snapshots, sorted.
+fn snapshot_tags(root: &Path) -> Vec<String> {
+    let mut tags: Vec<String> = fs::read_dir(snapshots_dir(root))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join(SNAPSHOT_INFO_FILE_NAME).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    tags.sort();
+    tags
+}
+
+/// Directory and metadata of the stored snapshot `tag`.
+fn load_snapshot(root: &Path, tag: &str) -> Result<(PathBuf, SnapshotInfo)> {
+    validate_tag(tag)?;
+    let source = snapshots_dir(root).join(tag);
+    let info_raw = match fs::read_to_string(source.join(SNAPSHOT_INFO_FILE_NAME)) {
+        Ok(raw) => raw,
+        Err(_) => {
+            let tags = snapshot_tags(root);
+            if tags.is_empty() {
+                bail!(
+                    "Snapshot `{tag}` not found; no snapshots exist under {}",
+                    root.display()
+                );
+            }
+            bail!("Snapshot `{tag}` not found; available: {}", tags.join(", "));
+        }
+    };
+    let info: SnapshotInfo = serde_json::from_str(&info_raw)
+        .with_context(|| format!("Invalid snapshot metadata for `{tag}`"))?;
+    if !source.join("meta.json").is_file() {
+        bail!("Snapshot `{tag}` is missing its index files");
+    }
+    Ok((source, info))
+}
+
+/// Index directory of the stored snapshot `tag`, for reading it in place.
+pub(crate) fn snapshot_index_dir(root: &Path, tag: &str) -> Result<PathBuf> {
+    load_snapshot(root, tag.trim()).map(|(source, _)| source)
+}
+
+/// Hardlink `src` to `dst`, copying when the filesystem refuses.
+fn link_or_copy(src: &Path, dst: &Path) -> Result<bool> {
+    if fs::hard_link(src, dst).is_ok() {
+        return Ok(true);
+    }
+    copy_file(src, dst)?;
+    Ok(false)
+}
+
+/// Capture the index artifacts of `from` into `to`. Returns the number of
+/// entries and how many of them were hardlinked.
+fn capture(from: &Path, to: &Path) -> Result<(usize, usize)> {
+    fs::create_dir_all(to)?;
+    let names = index_artifact_names(from)?;
+    let mut hardlinked = 0;
+    for name in &names {
+        let source = from.join(name);
+        let target = to.join(name);
+        if source.is_dir() {
+            copy_dir_recursive(&source, &target)?;
+        } else if is_segment_file_name(name) {
+            hardlinked += usize::from(link_or_copy(&source, &target)?);
+        } else {
+            copy_file(&source, &target)?;
+        }
+    }
+    Ok((names.len(), hardlinked))
+}
+
+/// `cgrep index snapshot [--tag <name>]`
+pub fn create(
+    path: Option<&str>,
+    tag: Option<&str>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let root = resolve_root(path)?;
+    let live = root.join(INDEX_DIR);
+    if !live.join("meta.json").is_file() {
+        bail!(
+            "No index found at {}. Run `cgrep index` first.",
+            root.display()
+        );
+    }
+    let created_at = now_unix_ms();
+    let tag = match tag {
+        Some(tag) => tag.trim().to_string(),
+        None => format!("snapshot-{}", created_at / 1000),
+    };
+    validate_tag(&tag)?;
+
+    let _lock = lock::acquire(&root, "index snapshot", LockWait::Wait)?;
+    let snapshots = snapshots_dir(&root);
+    let target = snapshots.join(&tag);
+    if target.exists() {
+        bail!(
+            "Snapshot `{tag}` already exists; pick another --tag or remove {}",
+            target.display()
+        );
+    }
+    let staging = snapshots.join(format!(".tmp-{}", std::process::id()));
+    fs::remove_dir_all(&staging).ok();
+    let (entries, hardlinked) = capture(&live, &staging)?;
+    let info = SnapshotInfo {
+        tag: tag.clone(),
+        created_at,
+        head: branches::current_checkout(&root).and_then(|checkout| checkout.head),
+    };
+    manifest::atomic_write_bytes(
+        &staging.join(SNAPSHOT_INFO_FILE_NAME),
+        &serde_json::to_vec_pretty(&info)?,
+    )?;
+    fs::rename(&staging, &target)
+        .with_context(|| format!("Failed to store snapshot {}", target.display()))?;
+
+    report(
+        SnapshotReport {
+            action: "snapshot",
+            root: root.display().to_string(),
+            tag,
+            path: target.display().to_string(),
+            head: info.head,
+            entries,
+            hardlinked,
+        },
+        format,
+        compact,
+    )
+}
+
+/// `cgrep index rollback <tag>`
+pub fn rollback(path: Option<&str>, tag: &str, format: OutputFormat, compact: bool) -> Result<()> {
+    let root = resolve_root(path)?;
+    let tag = tag.trim();
+    let (source, info) = load_snapshot(&root, tag)?;
+
+    let _lock = lock::acquire(&root, "index rollback", LockWait::Wait)?;
+    let live = root.join(INDEX_DIR);
+    let staging = snapshots_dir(&root).join(format!(".rollback-{}", std::process::id()));
+    fs::remove_dir_all(&staging).ok();
+    let restored = capture(&source, &staging).and_then(|counts| {
+        restore_from_staging(&live, &staging)?;
+        Ok(counts)
+    });
+    fs::remove_dir_all(&staging).ok();
+    let (entries, hardlinked) = restored?;
+
+    report(
+        SnapshotReport {
+            action: "rollback",
+            root: root.display().to_string(),
+            tag: tag.to_string(),
+            path: source.display().to_string(),
+            head: info.head,
+            entries,
+            hardlinked,
+        },
+        format,
+        compact,
+    )
+}
+
+/// Whether `name` belongs to the Tantivy index proper, which
+/// [`swap_staged_index`] replaces as a unit.
+fn is_tantivy_entry(name: &str) -> bool {
+    name == "meta.json" || name == ".managed.json" || is_segment_file_name(name)
+}
+
+/// Replace the live index under `live` with the one captured in `staging`.
+///
+/// cgrep's own artifacts (metadata, manifest, embeddings) are replaced first,
+/// each by a rename. Until the Tantivy swap renames `meta.json`, readers keep
+/// the previous index, and a failure leaves it in place with restored
+/// metadata that at worst makes the next refresh reindex more files than
+/// needed. Artifacts the snapshot lacks are removed once it is committed.
+fn restore_from_staging(live: &Path, staging: &Path) -> Result<()> {
+    let staged = index_artifact_names(staging)?;
+    for name in staged.iter().filter(|name| !is_tantivy_entry(name)) {
+        let target = live.join(name);
+        if target.is_dir() {
+            // Directories cannot be renamed over; park the live one first.
+            let parked = staging.join(format!(".replaced-{name}"));
+            fs::rename(&target, &parked)
+                .with_context(|| format!("Failed to replace index entry {name}"))?;
+        }
+        fs::rename(staging.join(name), &target)
+            .with_context(|| format!("Failed to restore index entry {name}"))?;
+    }
+
+    swap_staged_index(live, staging)?;
+
+    for name in index_artifact_names(live)? {
+        if is_tantivy_entry(&name) || staged.contains(&name) {
+            continue;
+        }
+        let path = live.join(&name);
+        let _ = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+    }
+    Ok(())
+}
+
+fn report(report: SnapshotReport, format: OutputFormat, compact: bool) -> Result<()> {
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&report, compact),
+        _ => {
+            let verb = if report.action == "rollback" {
+                "Restored index from snapshot"
+            } else {
+                "Saved index snapshot"
+            };
+            println!("{verb} `{}` ({})", report.tag, report.path);
+            println!(
+                "Entries: {} ({} segment files hardlinked)",
+                report.entries, report.hardlinked
+            );
+            if let Some(head) = report.head.as_deref() {
+                println!("Commit: {head}");
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_must_be_plain_names() {
+        for tag in ["eval-2024.06", "before_rebuild", "v1"] {
+            assert!(validate_tag(tag).is_ok(), "{tag}");
+        }
+        for tag in ["", ".tmp-1", "../escape", "a/b", "has space"] {
+            assert!(validate_tag(tag).is_err(), "{tag}");
+        }
+    }
+
+    #[test]
+    fn capture_links_segments_and_copies_metadata() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let from = dir.path().join("from");
+        fs::create_dir_all(from.join("manifest")).expect("mkdir");
+        fs::write(from.join("manifest").join("v1.json"), "{}").expect("write");
+        let segment = "0123456789abcdef0123456789abcdef.idx";
+        for name in [segment, "meta.json", "embeddings.sqlite", "status.json"] {
+            fs::write(from.join(name), name).expect("write");
+        }
+        let to = dir.path().join("to");
+        let (entries, hardlinked) = capture(&from, &to).expect("capture");
+        assert_eq!((entries, hardlinked), (4, 1));
+        assert!(to.join("manifest").join("v1.json").is_file());
+        assert!(!to.join("status.json").exists());
+
+        // Copied files are independent of the live index; segments are shared.
+        fs::write(from.join("embeddings.sqlite"), "changed").expect("write");
+        assert_eq!(
+            fs::read_to_string(to.join("embeddings.sqlite")).expect("read"),
+            "embeddings.sqlite"
+        );
+        assert_eq!(fs::read_to_string(to.join(segment)).expect("read"), segment);
+    }
+
+    #[test]
+    fn restore_swaps_in_the_staged_index_and_drops_extra_artifacts() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let live = dir.path().join("live");
+        let staging = dir.path().join("staging");
+        let old_segment = "00000000000000000000000000000000.idx";
+        let new_segment = "11111111111111111111111111111111.idx";
+        fs::create_dir_all(live.join("manifest")).expect("mkdir");
+        fs::create_dir_all(staging.join("manifest")).expect("mkdir");
+        for name in [
+            old_segment,
+            "meta.json",
+            "metadata.json",
+            "embeddings.sqlite",
+        ] {
+            fs::write(live.join(name), "live").expect("write");
+        }
+        fs::write(live.join("manifest").join("v1.json"), "live").expect("write");
+        fs::write(live.join("status.json"), "runtime").expect("write");
+        for name in [new_segment, "meta.json", "metadata.json"] {
+            fs::write(staging.join(name), "snapshot").expect("write");
+        }
+        fs::write(staging.join("manifest").join("v1.json"), "snapshot").expect("write");
+
+        restore_from_staging(&live, &staging).expect("restore");
+        let read = |rel: &str| fs::read_to_string(live.join(rel)).expect("read");
+        assert_eq!(read("meta.json"), "snapshot");
+        assert_eq!(read("metadata.json"), "snapshot");
+        assert_eq!(read("manifest/v1.json"), "snapshot");
+        assert_eq!(read(new_segment), "snapshot");
+        assert!(!live.join(old_segment).exists());
+        assert!(!live.join("embeddings.sqlite").exists());
+        assert_eq!(read("status.json"), "runtime");
+        assert!(!staging.exists());
+    }
+}
@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Sparse-checkout awareness.
+//!
+//! Paths outside the sparse cone carry git's skip-worktree bit. An index built
+//! before the cone was narrowed still references them, and with
+//! `sparse.expectFilesOutsideOfPatterns` copies may even remain on disk; either
+//! way they should not be indexed or reported as changed.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use cgrep::utils::{find_checkout_root, resolve_git_dir};
+
+/// Skip-worktree paths of the checkout enclosing a directory.
+#[derive(Debug, Clone)]
+pub(crate) struct SparseExclusions {
+    checkout_root: PathBuf,
+    /// Checkout-relative paths with `/` separators.
+    paths: HashSet<String>,
+}
+
+impl SparseExclusions {
+    /// Load exclusions for the checkout containing `start`.
+    ///
+    /// Returns `None` when `start` is not in a git checkout, sparse checkout
+    /// was never configured, or no path is currently excluded.
+    pub(crate) fn load(start: &Path) -> Option<Self> {
+        let checkout_root = find_checkout_root(start)?;
+        Self::load_for_checkout(&checkout_root)
+    }
+
+    pub(crate) fn load_for_checkout(checkout_root: &Path) -> Option<Self> {
+        // Cheap probe first: `git ls-files -t` walks the whole index.
+        let git_dir = resolve_git_dir(checkout_root)?;
+        if !git_dir.join("info").join("sparse-checkout").is_file() {
+            return None;
+        }
+
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(checkout_root)
+            .args(["ls-files", "-t", "-z"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let paths = parse_skip_worktree_entries(&output.stdout);
+        if paths.is_empty() {
+            return None;
+        }
+        Some(Self {
+            checkout_root: checkout_root.to_path_buf(),
+            paths,
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Whether a checkout-relative path is outside the sparse cone.
+    pub(crate) fn contains_rel(&self, rel_path: &str) -> bool {
+        self.paths.contains(rel_path)
+    }
+
+    /// Whether an absolute path is outside the sparse cone.
+    pub(crate) fn contains_path(&self, path: &Path) -> bool {
+        path.strip_prefix(&self.checkout_root)
+            .ok()
+            .map(|rel| self.contains_rel(&rel.to_string_lossy().replace('\\', "/")))
+            .unwrap_or(false)
+    }
+}
+
+/// Drop sparse-excluded files from a scanned file list.
+pub(crate) fn drop_sparse_excluded(root: &Path, files: Vec<PathBuf>) -> Vec<PathBuf> {
+    match SparseExclusions::load(root) {
+        Some(sparse) => files
+            .into_iter()
+            .filter(|path| !sparse.contains_path(path))
+            .collect(),
+        None => files,
+    }
+}
+
+/// Parse `git ls-files -t -z` output, keeping skip-worktree (`S`) entries.
+fn parse_skip_worktree_entries(stdout: &[u8]) -> HashSet<String> {
+    String::from_utf8_lossy(stdout)
+        .split('\0')
+        .filter_map(|entry| entry.strip_prefix("S "))
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .status()
+            .expect("run git");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn parses_only_skip_worktree_entries() {
+        let parsed = parse_skip_worktree_entries(b"H src/lib.rs\0S docs/guide.md\0S a b.rs\0");
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed.contains("docs/guide.md"));
+        assert!(parsed.contains("a b.rs"));
+    }
+
+    #[test]
+    fn sparse_checkout_excludes_paths_outside_cone() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path().canonicalize().expect("canonical");
+        git(&root, &["init", "-q"]);
+        git(&root, &["config", "user.email", "test@example.com"]);
+        git(&root, &["config", "user.name", "test"]);
+        fs::create_dir_all(root.join("src")).expect("mkdir src");
+        fs::create_dir_all(root.join("docs")).expect("mkdir docs");
+        fs::write(root.join("src/lib.rs"), "pub fn kept() {}\n").expect("write lib");
+        fs::write(root.join("docs/guide.md"), "guide\n").expect("write guide");
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-q", "-m", "initial"]);
+
+        assert!(SparseExclusions::load(&root).is_none());
+
+        git(&root, &["sparse-checkout", "set", "src"]);
+        let sparse = SparseExclusions::load(&root.join("src")).expect("sparse exclusions");
+        assert!(sparse.contains_rel("docs/guide.md"));
+        assert!(!sparse.contains_rel("src/lib.rs"));
+        assert!(sparse.contains_path(&root.join("docs/guide.md")));
+    }
+}
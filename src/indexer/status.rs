@@ -11,9 +11,13 @@ use std::process::{Command, Stdio};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::cli::OutputFormat;
+use crate::indexer::index::indexed_file_paths;
 use crate::indexer::manifest;
+use crate::indexer::migrate;
 use crate::indexer::reuse;
-use cgrep::output::print_json;
+use crate::indexer::watch_limits::{self, WatchCapacity, WatchFallback};
+use cgrep::output::{print_json, print_json2};
+use cgrep::utils::INDEX_DIR;
 
 const STATUS_FILE_NAME: &str = "status.json";
 const BACKGROUND_LOG_FILE_NAME: &str = "index-background.log";
@@ -38,6 +42,18 @@ pub struct BuildStatus {
     pub progress: BuildProgress,
     pub pid: Option<u32>,
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_build: Option<LastBuild>,
+}
+
+/// Timing of the most recent completed index build.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LastBuild {
+    /// `full` or `incremental`.
+    pub kind: String,
+    pub finished_at: u64,
+    pub elapsed_ms: u64,
+    pub files_updated: usize,
 }
 
 impl BuildStatus {
@@ -53,6 +69,7 @@ impl BuildStatus {
             progress: BuildProgress::default(),
             pid: None,
             message: String::new(),
+            last_build: None,
         }
     }
 }
@@ -66,6 +83,25 @@ struct DaemonStatus {
     log_file: String,
 }
 
+/// File watching resources: OS limits (with `--watchers`) and a polling
+/// fallback in effect.
+#[derive(Debug, Serialize)]
+struct WatchResources {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    capacity: Option<WatchCapacity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    polling: Option<WatchFallback>,
+}
+
+/// Size of the index on disk.
+#[derive(Debug, Serialize)]
+struct IndexCounts {
+    /// Source files recorded in the index metadata.
+    files: usize,
+    /// Tantivy documents (file chunks and symbols).
+    documents: u64,
+}
+
 #[derive(Debug, Serialize)]
 struct StatusResult {
     root: String,
@@ -79,7 +115,15 @@ struct StatusResult {
     message: String,
     daemon: DaemonStatus,
     #[serde(skip_serializing_if = "Option::is_none")]
+    watch: Option<WatchResources>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<IndexCounts>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_build: Option<LastBuild>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     reuse: Option<reuse::ReuseRuntimeState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_schema: Option<migrate::SchemaStatus>,
 }
 
 #[derive(Debug, Serialize)]
@@ -141,7 +185,7 @@ pub fn save_build_status(root: &Path, status: &BuildStatus) -> Result<()> {
 }
 
 #[cfg(unix)]
-fn process_alive(pid: u32) -> bool {
+pub(crate) fn process_alive(pid: u32) -> bool {
     Command::new("kill")
         .args(["-0", &pid.to_string()])
         .stdout(Stdio::null())
@@ -152,7 +196,7 @@ fn process_alive(pid: u32) -> bool {
 }
 
 #[cfg(not(unix))]
-fn process_alive(_pid: u32) -> bool {
+pub(crate) fn process_alive(_pid: u32) -> bool {
     false
 }
 
@@ -223,10 +267,31 @@ pub fn mark_build_start(
     };
     status.pid = pid;
     status.message = message.into();
+    status.last_build = load_build_status(root).and_then(|previous| previous.last_build);
     save_build_status(root, &status)?;
     Ok(status)
 }
 
+/// Record a finished build in `status` (a background build in progress) or
+/// in the saved status.
+pub fn record_last_build(
+    root: &Path,
+    status: Option<&mut BuildStatus>,
+    last_build: LastBuild,
+) -> Result<()> {
+    let mut saved;
+    let status = match status {
+        Some(status) => status,
+        None => {
+            saved = read_status_with_recovery(root)?;
+            &mut saved
+        }
+    };
+    status.full_ready = full_index_ready(root);
+    status.last_build = Some(last_build);
+    save_build_status(root, status)
+}
+
 pub fn mark_build_phase(
     root: &Path,
     status: &mut BuildStatus,
@@ -296,6 +361,25 @@ fn daemon_status(root: &Path) -> DaemonStatus {
     }
 }
 
+fn index_counts(root: &Path) -> Option<IndexCounts> {
+    let files = indexed_file_paths(root)?.len();
+    let index = tantivy::Index::open_in_dir(root.join(INDEX_DIR)).ok()?;
+    let documents = index.reader().ok()?.searcher().num_docs();
+    Some(IndexCounts { files, documents })
+}
+
+/// Watch usage is live and changes between runs, so it is only read on
+/// request; a polling fallback is always reported.
+fn watch_resources(root: &Path, watchers: bool) -> Option<WatchResources> {
+    let capacity = if watchers {
+        watch_limits::capacity()
+    } else {
+        None
+    };
+    let polling = watch_limits::active_fallback(root);
+    (capacity.is_some() || polling.is_some()).then_some(WatchResources { capacity, polling })
+}
+
 fn resolve_root(path: Option<&str>) -> Result<PathBuf> {
     let root = path
         .map(PathBuf::from)
@@ -304,7 +388,7 @@ fn resolve_root(path: Option<&str>) -> Result<PathBuf> {
     Ok(root.canonicalize().unwrap_or(root))
 }
 
-pub fn run(path: Option<&str>, format: OutputFormat, compact: bool) -> Result<()> {
+pub fn run(path: Option<&str>, watchers: bool, format: OutputFormat, compact: bool) -> Result<()> {
     let root = resolve_root(path)?;
     let status = read_status_with_recovery(&root)?;
     let daemon = daemon_status(&root);
@@ -320,11 +404,19 @@ pub fn run(path: Option<&str>, format: OutputFormat, compact: bool) -> Result<()
         pid: status.pid,
         message: status.message.clone(),
         daemon,
+        watch: watch_resources(&root, watchers),
+        index: index_counts(&root),
+        last_build: status.last_build.clone(),
         reuse: reuse_state,
+        index_schema: migrate::schema_status(&root),
     };
 
     match format {
-        OutputFormat::Text => {
+        OutputFormat::Text
+        | OutputFormat::Github
+        | OutputFormat::Gitlab
+        | OutputFormat::Sarif
+        | OutputFormat::Quickfix => {
             println!("Index root: {}", result.root);
             println!(
                 "Basic readiness: {}",
@@ -350,6 +442,18 @@ pub fn run(path: Option<&str>, format: OutputFormat, compact: bool) -> Result<()
             if let Some(pid) = result.pid {
                 println!("Background pid: {}", pid);
             }
+            if let Some(index) = result.index.as_ref() {
+                println!(
+                    "Indexed: {} files, {} documents",
+                    index.files, index.documents
+                );
+            }
+            if let Some(last) = result.last_build.as_ref() {
+                println!(
+                    "Last build: {} in {}ms ({} files updated)",
+                    last.kind, last.elapsed_ms, last.files_updated
+                );
+            }
             if !result.message.is_empty() {
                 println!("Message: {}", result.message);
             }
@@ -367,6 +471,32 @@ pub fn run(path: Option<&str>, format: OutputFormat, compact: bool) -> Result<()
                 detail.push_str(&format!(", active={}", reuse.active));
                 println!("Reuse: {}", detail);
             }
+            if let Some(schema) = result.index_schema.as_ref() {
+                let version = schema
+                    .version
+                    .map_or_else(|| "unknown".to_string(), |v| format!("v{v}"));
+                if schema.version.is_none() {
+                    println!(
+                        "Index schema: {} (rebuild needed for v{})",
+                        version, schema.current
+                    );
+                } else if !schema.pending.is_empty() {
+                    println!(
+                        "Index schema: {} (next update migrates to v{}: {})",
+                        version,
+                        schema.current,
+                        schema.pending.join("; ")
+                    );
+                } else {
+                    println!("Index schema: {}", version);
+                }
+                for step in &schema.migrations {
+                    println!(
+                        "  migrated v{} -> v{}: {}",
+                        step.from, step.to, step.description
+                    );
+                }
+            }
             if result.daemon.running {
                 println!(
                     "Watch daemon: running (pid={})",
@@ -382,6 +512,33 @@ pub fn run(path: Option<&str>, format: OutputFormat, compact: bool) -> Result<()
             }
             println!("Watch pid file: {}", result.daemon.pid_file);
             println!("Watch log file: {}", result.daemon.log_file);
+            let watch = result.watch.as_ref();
+            if let Some(capacity) = watch.and_then(|watch| watch.capacity.as_ref()) {
+                let mut line = format!(
+                    "Watch capacity: {}/{} inotify watches in use",
+                    capacity.watches_in_use, capacity.max_user_watches
+                );
+                if let Some(max_instances) = capacity.max_user_instances {
+                    line.push_str(&format!(
+                        " ({}/{} instances)",
+                        capacity.instances_in_use, max_instances
+                    ));
+                }
+                if capacity.nearly_exhausted() {
+                    println!("{} {}", line, "(nearly exhausted)".yellow());
+                } else {
+                    println!("{}", line);
+                }
+            }
+            if let Some(polling) = watch.and_then(|watch| watch.polling.as_ref()) {
+                println!(
+                    "{} polling every {}s (pid={}): {}",
+                    "Watch mode:".yellow(),
+                    polling.poll_interval_ms / 1000,
+                    polling.pid,
+                    polling.reason
+                );
+            }
         }
         OutputFormat::Json => {
             print_json(&result, compact)?;
@@ -389,11 +546,11 @@ pub fn run(path: Option<&str>, format: OutputFormat, compact: bool) -> Result<()
         OutputFormat::Json2 => {
             let payload = StatusJson2Payload {
                 meta: StatusJson2Meta {
-                    schema_version: "1",
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
                 },
                 result,
             };
-            print_json(&payload, compact)?;
+            print_json2("status", &payload, compact)?;
         }
     }
 
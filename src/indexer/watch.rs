@@ -4,9 +4,7 @@
 
 use anyhow::Result;
 use colored::Colorize;
-use notify::{
-    Config as NotifyConfig, Event, RecommendedWatcher, RecursiveMode, Watcher as NotifyWatcher,
-};
+use notify::{Config as NotifyConfig, Event};
 use serde_json::Value;
 use std::collections::HashSet;
 use std::path::{Component, Path, PathBuf};
@@ -14,9 +12,12 @@ use std::sync::mpsc::{channel, RecvTimeoutError};
 use std::time::{Duration, Instant};
 
 use crate::indexer::index::SymbolIndexOptions;
+use crate::indexer::lock::{self, LockWait};
 use crate::indexer::scanner::is_indexable_extension;
+use crate::indexer::watch_hooks::WatchHooks;
+use crate::indexer::watch_limits;
 use crate::indexer::IndexBuilder;
-use cgrep::config::Config;
+use cgrep::config::{Config, WatchHooksConfig};
 
 /// Default debounce interval in seconds
 const DEFAULT_DEBOUNCE_SECS: u64 = 15;
@@ -59,6 +60,7 @@ pub struct Watcher {
     max_batch_delay: Duration,
     adaptive: bool,
     bulk_refresh_threshold: usize,
+    hooks: WatchHooksConfig,
 }
 
 impl Watcher {
@@ -84,9 +86,16 @@ impl Watcher {
             max_batch_delay: Duration::from_secs(max_batch_delay_secs.max(1)),
             adaptive,
             bulk_refresh_threshold: recommended_bulk_refresh_threshold(&root),
+            hooks: WatchHooksConfig::default(),
         }
     }
 
+    /// Run `[watch.hooks]` commands after successful reindexes
+    pub fn with_hooks(mut self, hooks: WatchHooksConfig) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
     /// Start watching for file changes with debouncing
     pub fn watch(&self) -> Result<()> {
         let (tx, rx) = channel();
@@ -94,8 +103,23 @@ impl Watcher {
         let config = NotifyConfig::default()
             .with_poll_interval(Duration::from_secs(WATCH_POLL_INTERVAL_SECS));
 
-        let mut watcher = RecommendedWatcher::new(tx, config)?;
-        watcher.watch(&self.root, RecursiveMode::Recursive)?;
+        let watch = watch_limits::watch_recursive(
+            &self.root,
+            tx,
+            config,
+            Duration::from_secs(WATCH_POLL_INTERVAL_SECS),
+        )?;
+        match watch.fallback.as_deref() {
+            Some(reason) => eprintln!(
+                "Warning: OS file watch limit reached; polling every {}s instead: {}. Raise it with: {}",
+                WATCH_POLL_INTERVAL_SECS,
+                reason,
+                watch_limits::RAISE_LIMIT_HINT
+            ),
+            None => {
+                let _ = watch_limits::clear_fallback(&self.root);
+            }
+        }
 
         println!(
             "{} Watching {} for changes...",
@@ -116,6 +140,14 @@ impl Watcher {
             "  Bulk refresh threshold: {} changed paths",
             self.bulk_refresh_threshold
         );
+        let mut hooks = WatchHooks::new(&self.root, &self.hooks);
+        if !hooks.is_empty() {
+            println!(
+                "  Hooks: {} command(s), debounce {}s",
+                self.hooks.commands.len(),
+                self.hooks.debounce_secs()
+            );
+        }
         println!("Press Ctrl+C to stop\n");
 
         // Track pending changes and last reindex time
@@ -146,6 +178,10 @@ impl Watcher {
                     last_reindex_duration,
                 )
             };
+            // Wake up in time for debounced hooks.
+            let timeout = hooks.due_in().map_or(timeout, |due| {
+                timeout.min(due.max(Duration::from_millis(100)))
+            });
 
             match rx.recv_timeout(timeout) {
                 Ok(Ok(event)) => {
@@ -190,6 +226,12 @@ impl Watcher {
                 }
                 Ok(Err(e)) => {
                     eprintln!("{} Watch error: {}", "✗".red(), e);
+                    if watch_limits::is_limit_error(&e) {
+                        eprintln!(
+                            "Warning: new directories are not watched until the limit is raised: {}",
+                            watch_limits::RAISE_LIMIT_HINT
+                        );
+                    }
                 }
                 Err(RecvTimeoutError::Timeout) => {
                     // Check if we should flush pending changes
@@ -265,18 +307,24 @@ impl Watcher {
                     last_event_time = None;
 
                     let start = Instant::now();
-                    let reindex_result = if bulk_refresh_pending {
-                        // For large churn bursts, use default thread selection to
-                        // shorten recovery time after branch-scale updates.
-                        self.builder
-                            .build_with_io_threads(false, self.writer_budget_bytes, None)
-                    } else {
-                        self.builder.update_paths_with_io_threads(
-                            &changed_paths,
-                            self.writer_budget_bytes,
-                            Some(WATCH_IO_THREADS),
-                        )
-                    };
+                    let reindex_result = lock::acquire(&self.root, "daemon", LockWait::Wait)
+                        .and_then(|_lock| {
+                            if bulk_refresh_pending {
+                                // For large churn bursts, use default thread selection to
+                                // shorten recovery time after branch-scale updates.
+                                self.builder.build_with_io_threads(
+                                    false,
+                                    self.writer_budget_bytes,
+                                    None,
+                                )
+                            } else {
+                                self.builder.update_paths_with_io_threads(
+                                    &changed_paths,
+                                    self.writer_budget_bytes,
+                                    Some(WATCH_IO_THREADS),
+                                )
+                            }
+                        });
                     if let Err(e) = reindex_result {
                         eprintln!("{} Reindex failed: {}", "✗".red(), e);
                     } else {
@@ -296,12 +344,15 @@ impl Watcher {
                             );
                         }
                         last_reindex_duration = Some(elapsed);
+                        hooks.record_reindex(&changed_paths);
                     }
                     bulk_refresh_pending = false;
 
                     last_reindex_time = Some(Instant::now());
                 }
             }
+
+            hooks.run_if_due();
         }
 
         Ok(())
@@ -433,6 +484,65 @@ fn should_track_path(root: &Path, path: &Path, exclude_patterns: &[String]) -> b
     is_indexable_extension(ext)
 }
 
+/// Root, config, and builder shared by the watcher and `cgrep refresh`.
+struct WatchTarget {
+    root: PathBuf,
+    config: Config,
+    builder: IndexBuilder,
+    excludes: Vec<String>,
+    writer_budget_bytes: usize,
+}
+
+impl WatchTarget {
+    fn resolve(path: Option<&str>) -> Result<Self> {
+        let root = path
+            .map(PathBuf::from)
+            .or_else(|| std::env::current_dir().ok())
+            .ok_or_else(|| anyhow::anyhow!("Cannot determine current directory"))?;
+        let root = root.canonicalize().unwrap_or(root);
+
+        let config = Config::load_for_dir(&root);
+        let index_options = crate::indexer::index::resolve_index_options_for_watch(&root, &config);
+        let excludes = index_options.exclude_paths.clone();
+        let symbol_options = SymbolIndexOptions::from_config(&config);
+        let builder = IndexBuilder::with_options(&root, index_options.clone(), symbol_options)?;
+        let writer_budget_bytes = index_options.writer_budget_bytes();
+        if index_options.high_memory {
+            eprintln!("Using high-memory indexing in watch mode: writer budget = 1GiB");
+        }
+        Ok(Self {
+            root,
+            config,
+            builder,
+            excludes,
+            writer_budget_bytes,
+        })
+    }
+
+    /// Manifest-diff incremental build under the daemon lock.
+    fn refresh(&self) -> Result<usize> {
+        let _lock = lock::acquire(&self.root, "daemon", LockWait::Wait)?;
+        self.builder
+            .build_with_io_threads(false, self.writer_budget_bytes, Some(WATCH_IO_THREADS))
+    }
+}
+
+/// Run one incremental refresh, exactly as the watcher does on startup, and exit.
+///
+/// Meant for git hooks and CI steps that want an up-to-date index without a
+/// long-running watcher.
+pub fn refresh(path: Option<&str>) -> Result<()> {
+    let target = WatchTarget::resolve(path)?;
+    let start = Instant::now();
+    target.refresh()?;
+    println!(
+        "{} Refresh complete in {:.1}s",
+        "✓".green(),
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
 /// Run the watch command
 pub fn run(
     path: Option<&str>,
@@ -441,25 +551,18 @@ pub fn run(
     max_batch_delay_secs: Option<u64>,
     adaptive: bool,
 ) -> Result<()> {
-    let root = path
-        .map(PathBuf::from)
-        .or_else(|| std::env::current_dir().ok())
-        .ok_or_else(|| anyhow::anyhow!("Cannot determine current directory"))?;
-    let root = root.canonicalize().unwrap_or(root);
-
-    let config = Config::load_for_dir(&root);
-    let index_options = crate::indexer::index::resolve_index_options_for_watch(&root, &config);
-    let excludes = index_options.exclude_paths.clone();
-    let symbol_options = SymbolIndexOptions::from_config(&config);
-    let builder = IndexBuilder::with_options(&root, index_options.clone(), symbol_options)?;
-    let writer_budget_bytes = index_options.writer_budget_bytes();
-    if index_options.high_memory {
-        eprintln!("Using high-memory indexing in watch mode: writer budget = 1GiB");
-    }
+    let target = WatchTarget::resolve(path)?;
 
     // Build initial index
-    builder.build_with_io_threads(false, writer_budget_bytes, Some(WATCH_IO_THREADS))?;
+    target.refresh()?;
 
+    let WatchTarget {
+        root,
+        config,
+        builder,
+        excludes,
+        writer_budget_bytes,
+    } = target;
     let watcher = Watcher::with_options(
         &root,
         builder,
@@ -469,7 +572,8 @@ pub fn run(
         min_interval_secs.unwrap_or(MIN_REINDEX_INTERVAL_SECS),
         max_batch_delay_secs.unwrap_or(DEFAULT_MAX_BATCH_DELAY_SECS),
         adaptive,
-    );
+    )
+    .with_hooks(config.watch.hooks.clone());
     watcher.watch()
 }
 
@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! User-configured commands run by the watcher after successful reindexes.
+//!
+//! Configured under `[watch.hooks]`. Reindexes that land within the debounce
+//! window are batched into one run. A batch runs on its own thread so file
+//! events keep flowing while hooks work; its outcomes are reported when the
+//! watch loop next polls. Each hook gets its own process group, and a timeout
+//! kills the whole group. Hook output is discarded unless a command fails, in
+//! which case its status and stderr tail go to stderr (the daemon redirects
+//! stderr to `.cgrep/watch.log`).
+
+use colored::Colorize;
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use cgrep::config::WatchHooksConfig;
+
+/// Trailing stderr lines kept in the log for a failed hook.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// How often a running hook is polled for exit.
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often the watch loop checks on a running batch.
+const BATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a failed hook's stderr is awaited once the hook is gone; a
+/// process that inherited the pipe may hold it open.
+const STDERR_GRACE: Duration = Duration::from_secs(1);
+
+/// Pending hook state between reindexes.
+pub struct WatchHooks {
+    root: PathBuf,
+    commands: Vec<String>,
+    debounce: Duration,
+    timeout: Duration,
+    /// Changed paths since the last run; `None` once a bulk refresh joined the batch.
+    changed: Option<BTreeSet<String>>,
+    pending: bool,
+    last_run: Option<Instant>,
+    /// Outcomes of the batch in flight, one per command.
+    running: Option<Receiver<HookOutcome>>,
+}
+
+struct HookOutcome {
+    command: String,
+    elapsed: Duration,
+    result: Result<(), String>,
+}
+
+/// What a hook runs with.
+#[derive(Clone)]
+struct HookRun {
+    root: PathBuf,
+    timeout: Duration,
+    changed_files: String,
+    changed_count: String,
+}
+
+impl WatchHooks {
+    pub fn new(root: &Path, config: &WatchHooksConfig) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            commands: config
+                .commands
+                .iter()
+                .map(|command| command.trim().to_string())
+                .filter(|command| !command.is_empty())
+                .collect(),
+            debounce: Duration::from_secs(config.debounce_secs()),
+            timeout: Duration::from_secs(config.timeout_secs()),
+            changed: Some(BTreeSet::new()),
+            pending: false,
+            last_run: None,
+            running: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Record a successful reindex; an empty `paths` means a bulk refresh.
+    pub fn record_reindex(&mut self, paths: &[PathBuf]) {
+        if self.is_empty() {
+            return;
+        }
+        self.pending = true;
+        if paths.is_empty() {
+            self.changed = None;
+        } else if let Some(changed) = self.changed.as_mut() {
+            for path in paths {
+                let rel = path.strip_prefix(&self.root).unwrap_or(path);
+                changed.insert(rel.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+
+    /// Time until [`Self::run_if_due`] has work: pending hooks may start, or
+    /// a running batch should be checked on.
+    pub fn due_in(&self) -> Option<Duration> {
+        if self.running.is_some() {
+            return Some(BATCH_POLL_INTERVAL);
+        }
+        if !self.pending {
+            return None;
+        }
+        Some(
+            self.last_run
+                .map(|last| self.debounce.saturating_sub(last.elapsed()))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Report finished hooks, then start pending ones once the debounce
+    /// window has passed and no batch is running. Never waits for a hook.
+    pub fn run_if_due(&mut self) {
+        self.report_finished();
+        if self.running.is_some() || self.due_in() != Some(Duration::ZERO) {
+            return;
+        }
+        let changed = self.changed.replace(BTreeSet::new());
+        self.pending = false;
+        self.last_run = Some(Instant::now());
+
+        let run = HookRun {
+            root: self.root.clone(),
+            timeout: self.timeout,
+            changed_files: changed
+                .as_ref()
+                .map(|paths| paths.iter().cloned().collect::<Vec<_>>().join("\n"))
+                .unwrap_or_default(),
+            changed_count: changed
+                .as_ref()
+                .map(|paths| paths.len().to_string())
+                .unwrap_or_else(|| "bulk".to_string()),
+        };
+        let commands = self.commands.clone();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for command in commands {
+                let start = Instant::now();
+                let result = run.run_one(&command);
+                let outcome = HookOutcome {
+                    command,
+                    elapsed: start.elapsed(),
+                    result,
+                };
+                if tx.send(outcome).is_err() {
+                    break;
+                }
+            }
+        });
+        self.running = Some(rx);
+    }
+
+    /// Print outcomes of the running batch; clears it once all are in.
+    fn report_finished(&mut self) {
+        let Some(rx) = self.running.as_ref() else {
+            return;
+        };
+        loop {
+            match rx.try_recv() {
+                Ok(outcome) => outcome.report(),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        self.running = None;
+    }
+
+    /// Block until the running batch is done, reporting its outcomes.
+    #[cfg(test)]
+    fn wait(&mut self) {
+        if let Some(rx) = self.running.take() {
+            rx.iter().for_each(HookOutcome::report);
+        }
+    }
+}
+
+impl HookOutcome {
+    fn report(self) {
+        match self.result {
+            Ok(()) => println!(
+                "{} Hook `{}` finished in {:.1}s",
+                "✓".green(),
+                self.command,
+                self.elapsed.as_secs_f64()
+            ),
+            Err(failure) => eprintln!("{} Hook `{}` failed: {}", "✗".red(), self.command, failure),
+        }
+    }
+}
+
+impl HookRun {
+    fn run_one(&self, command: &str) -> Result<(), String> {
+        let mut child = shell_command(command)
+            .current_dir(&self.root)
+            .env("CGREP_ROOT", &self.root)
+            .env("CGREP_CHANGED_FILES", &self.changed_files)
+            .env("CGREP_CHANGED_COUNT", &self.changed_count)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("could not start: {e}"))?;
+
+        // Drain stderr on a thread so a chatty hook cannot block on a full pipe.
+        let mut stderr = child.stderr.take();
+        let (stderr_tx, stderr_rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buf = String::new();
+            if let Some(stderr) = stderr.as_mut() {
+                let _ = stderr.read_to_string(&mut buf);
+            }
+            let _ = stderr_tx.send(buf);
+        });
+
+        let start = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) if start.elapsed() >= self.timeout => {
+                    kill_group(&mut child);
+                    let _ = child.wait();
+                    break None;
+                }
+                Ok(None) => std::thread::sleep(HOOK_POLL_INTERVAL),
+                Err(e) => {
+                    kill_group(&mut child);
+                    return Err(format!("wait failed: {e}"));
+                }
+            }
+        };
+
+        let reason = match status {
+            Some(status) if status.success() => return Ok(()),
+            Some(status) => format!("exited with {status}"),
+            None => format!("timed out after {}s", self.timeout.as_secs()),
+        };
+        let stderr = stderr_rx.recv_timeout(STDERR_GRACE).unwrap_or_default();
+        let lines: Vec<&str> = stderr.lines().collect();
+        let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n");
+        if tail.trim().is_empty() {
+            Err(reason)
+        } else {
+            Err(format!("{reason}\n{tail}"))
+        }
+    }
+}
+
+/// Kill a hook and everything it started.
+#[cfg(unix)]
+fn kill_group(child: &mut Child) {
+    // The hook leads its own process group (see `shell_command`).
+    let killed = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !killed {
+        let _ = child.kill();
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command).process_group(0);
+    cmd
+}
+
+#[cfg(not(unix))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hooks(root: &Path, commands: &[&str], debounce_secs: u64) -> WatchHooks {
+        let config = WatchHooksConfig {
+            commands: commands.iter().map(|c| c.to_string()).collect(),
+            debounce_secs: Some(debounce_secs),
+            timeout_secs: Some(5),
+        };
+        WatchHooks::new(root, &config)
+    }
+
+    fn run(root: &Path, timeout: Duration, changed_files: &str) -> HookRun {
+        HookRun {
+            root: root.to_path_buf(),
+            timeout,
+            changed_files: changed_files.to_string(),
+            changed_count: "1".to_string(),
+        }
+    }
+
+    #[test]
+    fn reindexes_within_debounce_are_batched() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let mut hooks = hooks(dir.path(), &["true"], 60);
+        assert_eq!(hooks.due_in(), None);
+
+        hooks.record_reindex(&[dir.path().join("a.rs")]);
+        assert_eq!(hooks.due_in(), Some(Duration::ZERO));
+        hooks.run_if_due();
+        assert_eq!(hooks.due_in(), Some(BATCH_POLL_INTERVAL), "batch running");
+        hooks.wait();
+        assert_eq!(hooks.due_in(), None);
+
+        hooks.record_reindex(&[dir.path().join("b.rs")]);
+        let wait = hooks.due_in().expect("pending");
+        assert!(wait > Duration::from_secs(50));
+        hooks.run_if_due();
+        assert!(hooks.running.is_none(), "still inside debounce window");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn running_hooks_do_not_block_the_loop() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let mut hooks = hooks(dir.path(), &["sleep 2"], 0);
+        hooks.record_reindex(&[dir.path().join("a.rs")]);
+        let start = Instant::now();
+        hooks.run_if_due();
+        hooks.record_reindex(&[dir.path().join("b.rs")]);
+        hooks.run_if_due();
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(hooks.pending, "next batch waits for the running one");
+        hooks.wait();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hooks_receive_changed_files_and_report_failures() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let hook = run(dir.path(), Duration::from_secs(5), "src/a.rs");
+        assert!(hook
+            .run_one("test \"$CGREP_CHANGED_FILES\" = \"src/a.rs\"")
+            .is_ok());
+        let err = hook.run_one("echo boom >&2; exit 3").expect_err("failure");
+        assert!(err.contains("exit status: 3"), "{err}");
+        assert!(err.ends_with("boom"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeouts_kill_the_whole_process_group() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let hook = run(dir.path(), Duration::from_secs(1), "");
+        // The grandchild inherits stderr and would hold the pipe open.
+        let start = Instant::now();
+        let err = hook
+            .run_one("sleep 30 & echo $! > grandchild.pid; wait")
+            .expect_err("timeout");
+        assert!(err.starts_with("timed out after 1s"), "{err}");
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        let pid: u32 = std::fs::read_to_string(dir.path().join("grandchild.pid"))
+            .expect("pid file")
+            .trim()
+            .parse()
+            .expect("pid");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while crate::indexer::status::process_alive(pid) && Instant::now() < deadline {
+            std::thread::sleep(HOOK_POLL_INTERVAL);
+        }
+        assert!(!crate::indexer::status::process_alive(pid));
+    }
+}
@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! OS file-watch capacity, and polling when it runs out.
+//!
+//! A recursive watch on Linux takes one inotify watch per directory out of a
+//! per-user budget (`fs.inotify.max_user_watches`). Once that is spent,
+//! notify fails with `MaxFilesWatch` (ENOSPC) and a partial watch would miss
+//! changes without saying so. [`watch_recursive`] drops the native watcher in
+//! that case and polls instead; the fallback is recorded under `.cgrep/` for
+//! as long as the process runs, so `cgrep status` and `cgrep doctor` can
+//! report it next to the current watch usage.
+
+use anyhow::Result;
+use notify::{
+    Config as NotifyConfig, ErrorKind, EventHandler, PollWatcher, RecommendedWatcher,
+    RecursiveMode, Watcher as NotifyWatcher,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::indexer::manifest;
+use crate::indexer::status::{now_unix_ms, process_alive};
+use cgrep::utils::INDEX_DIR;
+
+const FALLBACK_FILE_NAME: &str = "watch-fallback.json";
+
+/// Share of `max_user_watches` in use at which status and doctor warn.
+const NEARLY_EXHAUSTED_RATIO: f64 = 0.9;
+
+/// Suggested fix for an exhausted inotify budget.
+pub(crate) const RAISE_LIMIT_HINT: &str =
+    "sudo sysctl fs.inotify.max_user_watches=524288 (persist it in /etc/sysctl.d/)";
+
+/// A watcher that fell back to polling, as recorded for a scope.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct WatchFallback {
+    /// Process that polls the scope.
+    pub pid: u32,
+    pub reason: String,
+    pub poll_interval_ms: u64,
+    pub since: u64,
+}
+
+/// inotify limits and the usage of this user's processes.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct WatchCapacity {
+    pub max_user_watches: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_user_instances: Option<u64>,
+    /// Watches held by the processes that could be inspected.
+    pub watches_in_use: u64,
+    pub instances_in_use: u64,
+}
+
+impl WatchCapacity {
+    pub(crate) fn nearly_exhausted(&self) -> bool {
+        self.max_user_watches > 0
+            && self.watches_in_use as f64 >= self.max_user_watches as f64 * NEARLY_EXHAUSTED_RATIO
+    }
+}
+
+/// A running recursive watch.
+pub(crate) struct WatchHandle {
+    // Dropping the watcher stops watching.
+    _watcher: Box<dyn NotifyWatcher + Send>,
+    /// Why the watch polls instead of using OS events.
+    pub fallback: Option<String>,
+}
+
+/// Watch `root` recursively with OS events, or by polling every
+/// `poll_interval` when the OS watch limit is reached.
+pub(crate) fn watch_recursive<F>(
+    root: &Path,
+    handler: F,
+    config: NotifyConfig,
+    poll_interval: Duration,
+) -> notify::Result<WatchHandle>
+where
+    F: EventHandler + Clone,
+{
+    let native = RecommendedWatcher::new(handler.clone(), config).and_then(|mut watcher| {
+        watcher.watch(root, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    });
+    let err = match native {
+        Ok(watcher) => {
+            return Ok(WatchHandle {
+                _watcher: Box::new(watcher),
+                fallback: None,
+            })
+        }
+        Err(err) if is_limit_error(&err) => err,
+        Err(err) => return Err(err),
+    };
+
+    let reason = limit_reason(&err);
+    let mut watcher = PollWatcher::new(handler, config.with_poll_interval(poll_interval))?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+    record_fallback(
+        root,
+        &WatchFallback {
+            pid: std::process::id(),
+            reason: reason.clone(),
+            poll_interval_ms: poll_interval.as_millis() as u64,
+            since: now_unix_ms(),
+        },
+    );
+    Ok(WatchHandle {
+        _watcher: Box::new(watcher),
+        fallback: Some(reason),
+    })
+}
+
+/// Whether `err` means the OS ran out of watches or watcher instances.
+pub(crate) fn is_limit_error(err: &notify::Error) -> bool {
+    match &err.kind {
+        ErrorKind::MaxFilesWatch => true,
+        ErrorKind::Io(io) => {
+            matches!(io.kind(), std::io::ErrorKind::StorageFull)
+                || (cfg!(target_os = "linux") && io.raw_os_error() == Some(24))
+        }
+        _ => false,
+    }
+}
+
+fn limit_reason(err: &notify::Error) -> String {
+    match capacity() {
+        Some(capacity) => format!(
+            "{err} (fs.inotify.max_user_watches={}, {} in use)",
+            capacity.max_user_watches, capacity.watches_in_use
+        ),
+        None => err.to_string(),
+    }
+}
+
+fn fallback_path(root: &Path) -> PathBuf {
+    root.join(INDEX_DIR).join(FALLBACK_FILE_NAME)
+}
+
+/// Best effort: a scope without `.cgrep/` has nothing to report it in.
+fn record_fallback(root: &Path, fallback: &WatchFallback) {
+    if !root.join(INDEX_DIR).is_dir() {
+        return;
+    }
+    if let Ok(content) = serde_json::to_string_pretty(fallback) {
+        let _ = manifest::atomic_write_bytes(&fallback_path(root), content.as_bytes());
+    }
+}
+
+/// The polling fallback recorded for `root`, if its process still runs.
+pub(crate) fn active_fallback(root: &Path) -> Option<WatchFallback> {
+    let content = fs::read_to_string(fallback_path(root)).ok()?;
+    let fallback: WatchFallback = serde_json::from_str(&content).ok()?;
+    process_alive(fallback.pid).then_some(fallback)
+}
+
+/// Forget a fallback recorded for `root`, e.g. once a native watch works.
+pub(crate) fn clear_fallback(root: &Path) -> Result<()> {
+    match fs::remove_file(fallback_path(root)) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+/// inotify limits and usage, on Linux.
+#[cfg(target_os = "linux")]
+pub(crate) fn capacity() -> Option<WatchCapacity> {
+    let read_limit = |name: &str| -> Option<u64> {
+        fs::read_to_string(Path::new("/proc/sys/fs/inotify").join(name))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let max_user_watches = read_limit("max_user_watches")?;
+    let (watches_in_use, instances_in_use) = inotify_usage();
+    Some(WatchCapacity {
+        max_user_watches,
+        max_user_instances: read_limit("max_user_instances"),
+        watches_in_use,
+        instances_in_use,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn capacity() -> Option<WatchCapacity> {
+    None
+}
+
+/// inotify watches and instances held by this user's processes, counted
+/// from `/proc/<pid>/fdinfo`. Processes that cannot be read are skipped.
+#[cfg(target_os = "linux")]
+fn inotify_usage() -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(uid) = fs::metadata("/proc/self").map(|meta| meta.uid()) else {
+        return (0, 0);
+    };
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return (0, 0);
+    };
+    let (mut watches, mut instances) = (0, 0);
+    for process in processes.filter_map(|entry| entry.ok()) {
+        let proc_dir = process.path();
+        let is_pid = process
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.bytes().all(|byte| byte.is_ascii_digit()));
+        if !is_pid || fs::metadata(&proc_dir).map(|meta| meta.uid()).ok() != Some(uid) {
+            continue;
+        }
+        let Ok(fds) = fs::read_dir(proc_dir.join("fd")) else {
+            continue;
+        };
+        for fd in fds.filter_map(|entry| entry.ok()) {
+            let is_inotify = fs::read_link(fd.path())
+                .is_ok_and(|target| target.as_os_str() == "anon_inode:inotify");
+            if !is_inotify {
+                continue;
+            }
+            instances += 1;
+            if let Ok(info) = fs::read_to_string(proc_dir.join("fdinfo").join(fd.file_name())) {
+                watches += info
+                    .lines()
+                    .filter(|line| line.starts_with("inotify wd:"))
+                    .count() as u64;
+            }
+        }
+    }
+    (watches, instances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_errors_are_recognized() {
+        assert!(is_limit_error(&notify::Error::new(
+            ErrorKind::MaxFilesWatch
+        )));
+        assert!(is_limit_error(&notify::Error::io(std::io::Error::from(
+            std::io::ErrorKind::StorageFull
+        ))));
+        assert!(!is_limit_error(&notify::Error::path_not_found()));
+    }
+
+    #[test]
+    fn fallbacks_are_reported_while_their_process_runs() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let root = dir.path();
+        fs::create_dir_all(root.join(INDEX_DIR)).expect("mkdir .cgrep");
+        let mut fallback = WatchFallback {
+            pid: std::process::id(),
+            reason: "OS file watch limit reached.".to_string(),
+            poll_interval_ms: 15_000,
+            since: 1,
+        };
+        record_fallback(root, &fallback);
+        assert_eq!(active_fallback(root), Some(fallback.clone()));
+
+        #[cfg(unix)]
+        {
+            let mut exited = std::process::Command::new("true").spawn().expect("spawn");
+            exited.wait().expect("wait");
+            fallback.pid = exited.id();
+            record_fallback(root, &fallback);
+            assert_eq!(active_fallback(root), None);
+        }
+
+        clear_fallback(root).expect("clear");
+        assert!(!fallback_path(root).exists());
+        clear_fallback(root).expect("clear twice");
+    }
+}
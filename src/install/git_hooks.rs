@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Git hooks that keep the index warm without a daemon
+//!
+//! Writes `post-checkout`, `post-merge`, and `post-rewrite` hooks that start
+//! `cgrep refresh` in the background, so branch switches, pulls, and rebases
+//! are indexed without delaying git.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use super::{git_output, make_executable, write_file_if_changed};
+
+/// Marker line identifying hooks written by this command.
+const HOOK_MARKER: &str = "# Installed by `cgrep install-git-hooks`";
+const HOOK_NAMES: [&str; 3] = ["post-checkout", "post-merge", "post-rewrite"];
+const REFRESH_COMMAND: &str = "cgrep refresh";
+
+fn hook_script() -> String {
+    format!("#!/bin/sh\n{HOOK_MARKER}\n({REFRESH_COMMAND} >/dev/null 2>&1 &)\n")
+}
+
+pub fn install(path: Option<&str>, force: bool) -> Result<()> {
+    let dir = match path {
+        Some(p) => PathBuf::from(p),
+        None => std::env::current_dir().context("Failed to get current directory")?,
+    };
+    let repo_root = PathBuf::from(git_output(
+        &dir,
+        &["rev-parse", "--show-toplevel"],
+        "install-git-hooks",
+    )?);
+    let hooks_dir = repo_root.join(git_output(
+        &repo_root,
+        &["rev-parse", "--git-path", "hooks"],
+        "install-git-hooks",
+    )?);
+
+    let hook_paths: Vec<PathBuf> = HOOK_NAMES.iter().map(|name| hooks_dir.join(name)).collect();
+    if !force {
+        let foreign: Vec<String> = hook_paths
+            .iter()
+            .filter(|hook_path| {
+                hook_path.exists()
+                    && !fs::read_to_string(hook_path)
+                        .unwrap_or_default()
+                        .contains(HOOK_MARKER)
+            })
+            .map(|hook_path| hook_path.display().to_string())
+            .collect();
+        if !foreign.is_empty() {
+            bail!(
+                "{} already exist and were not written by cgrep (use --force to replace them)",
+                foreign.join(", ")
+            );
+        }
+    }
+
+    let script = hook_script();
+    for (name, hook_path) in HOOK_NAMES.iter().zip(&hook_paths) {
+        let changed = write_file_if_changed(hook_path, &script)
+            .with_context(|| format!("Failed to write {name} hook"))?;
+        make_executable(hook_path)?;
+        if changed {
+            println!("✓ Installed {name} hook at {}", hook_path.display());
+        } else {
+            println!("{name} hook already up to date");
+        }
+    }
+    println!("  Runs: {REFRESH_COMMAND} (in the background)");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hook_script_backgrounds_refresh() {
+        let script = hook_script();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains(HOOK_MARKER));
+        assert!(script.contains("(cgrep refresh >/dev/null 2>&1 &)"));
+    }
+}
@@ -9,11 +9,14 @@ pub mod codex;
 pub mod content;
 pub mod copilot;
 pub mod cursor;
+pub mod git_hooks;
 pub mod opencode;
+pub mod precommit;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use std::fs;
 use std::path::Path;
+use std::process::Command;
 
 /// Helper to write a file only if the content differs from existing
 pub fn write_file_if_changed(path: &Path, content: &str) -> Result<bool> {
@@ -58,6 +61,36 @@ pub fn append_if_not_present(path: &Path, content: &str) -> Result<bool> {
     Ok(true)
 }
 
+/// Run git in `dir` and return trimmed stdout; `command` names the cgrep
+/// subcommand in the error when `dir` is not a repository.
+fn git_output(dir: &Path, args: &[&str], command: &str) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{command} requires a git repository: {}", stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+        .with_context(|| format!("Failed to make {} executable", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
 /// Get the user's home directory
 pub fn home_dir() -> Result<std::path::PathBuf> {
     dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))
@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Pre-commit integration for `cgrep check`
+//!
+//! Writes a git `pre-commit` hook (or a `repo: local` entry in the pre-commit
+//! framework's `.pre-commit-config.yaml`) that runs policy rules against staged
+//! files.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{git_output, make_executable, write_file_if_changed};
+
+/// Marker line identifying hooks written by this command.
+const HOOK_MARKER: &str = "# Installed by `cgrep install-precommit`";
+const FRAMEWORK_HOOK_ID: &str = "cgrep-check";
+const FRAMEWORK_CONFIG: &str = ".pre-commit-config.yaml";
+
+fn check_command(rules: &str) -> String {
+    let quoted = format!("'{}'", rules.replace('\'', "'\\''"));
+    format!("cgrep check --rules {quoted} --changed --staged")
+}
+
+fn hook_script(rules: &str) -> String {
+    format!("#!/bin/sh\n{HOOK_MARKER}\nexec {}\n", check_command(rules))
+}
+
+/// A `repos:` item running the check as a local hook, indented by `indent`.
+fn framework_entry(rules: &str, indent: &str) -> String {
+    [
+        "- repo: local".to_string(),
+        "  hooks:".to_string(),
+        format!("    - id: {FRAMEWORK_HOOK_ID}"),
+        "      name: cgrep policy check".to_string(),
+        format!("      entry: {}", check_command(rules)),
+        "      language: system".to_string(),
+        "      pass_filenames: false".to_string(),
+        "      stages: [pre-commit]".to_string(),
+    ]
+    .iter()
+    .map(|line| format!("{indent}{line}\n"))
+    .collect()
+}
+
+/// `config` with the local hook added to the end of its `repos:` list, or
+/// `None` when `repos:` is written inline and cannot be extended safely.
+fn add_framework_entry(config: &str, rules: &str) -> Option<String> {
+    let lines: Vec<&str> = config.lines().collect();
+    let Some(repos) = lines
+        .iter()
+        .position(|line| line.trim_end().starts_with("repos:"))
+    else {
+        let mut updated = config.to_string();
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str("repos:\n");
+        updated.push_str(&framework_entry(rules, "  "));
+        return Some(updated);
+    };
+    if !lines[repos].trim_end()["repos:".len()..].trim().is_empty() {
+        return None;
+    }
+    // The list ends at the next top-level key.
+    let end = lines[repos + 1..]
+        .iter()
+        .position(|line| !line.is_empty() && !line.starts_with([' ', '\t', '-', '#']))
+        .map_or(lines.len(), |offset| repos + 1 + offset);
+    let indent: String = lines[repos + 1..end]
+        .iter()
+        .find(|line| line.trim_start().starts_with('-'))
+        .map(|line| line[..line.len() - line.trim_start().len()].to_string())
+        .unwrap_or_else(|| "  ".to_string());
+    // Keep trailing blank lines and comments after the new item.
+    let insert_at = (repos + 1..end)
+        .rev()
+        .find(|&i| {
+            let line = lines[i].trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map_or(repos + 1, |i| i + 1);
+
+    let mut updated = String::new();
+    for line in &lines[..insert_at] {
+        updated.push_str(line);
+        updated.push('\n');
+    }
+    updated.push_str(&framework_entry(rules, &indent));
+    for line in &lines[insert_at..] {
+        updated.push_str(line);
+        updated.push('\n');
+    }
+    Some(updated)
+}
+
+pub fn install(rules: &str, path: Option<&str>, framework: bool, force: bool) -> Result<()> {
+    let dir = match path {
+        Some(p) => PathBuf::from(p),
+        None => std::env::current_dir().context("Failed to get current directory")?,
+    };
+    let repo_root = PathBuf::from(git_output(
+        &dir,
+        &["rev-parse", "--show-toplevel"],
+        "install-precommit",
+    )?);
+    if !repo_root.join(rules).is_file() {
+        eprintln!(
+            "Warning: rules file {} not found; create it before committing",
+            repo_root.join(rules).display()
+        );
+    }
+
+    if framework {
+        return install_framework_entry(&repo_root, rules);
+    }
+
+    let hooks_dir = git_output(
+        &repo_root,
+        &["rev-parse", "--git-path", "hooks"],
+        "install-precommit",
+    )?;
+    let hook_path = repo_root.join(hooks_dir).join("pre-commit");
+    if hook_path.exists() && !force {
+        let existing = fs::read_to_string(&hook_path).unwrap_or_default();
+        if !existing.contains(HOOK_MARKER) {
+            bail!(
+                "{} already exists and was not written by cgrep (use --force to replace it)",
+                hook_path.display()
+            );
+        }
+    }
+
+    let changed = write_file_if_changed(&hook_path, &hook_script(rules))
+        .context("Failed to write pre-commit hook")?;
+    make_executable(&hook_path)?;
+    if changed {
+        println!("✓ Installed pre-commit hook at {}", hook_path.display());
+    } else {
+        println!("Pre-commit hook already up to date");
+    }
+    println!("  Runs: {}", check_command(rules));
+    println!("  Tip: with `cgrep daemon start` running, checks use the warm index to skip staged files that cannot match a rule");
+    Ok(())
+}
+
+fn install_framework_entry(repo_root: &Path, rules: &str) -> Result<()> {
+    let config_file = repo_root.join(FRAMEWORK_CONFIG);
+    let existing = fs::read_to_string(&config_file).unwrap_or_default();
+    if existing.contains(&format!("id: {FRAMEWORK_HOOK_ID}")) {
+        println!(
+            "{} already defines {FRAMEWORK_HOOK_ID}",
+            config_file.display()
+        );
+        return Ok(());
+    }
+    let Some(updated) = add_framework_entry(&existing, rules) else {
+        bail!(
+            "{} lists `repos:` inline; add this entry to it by hand:\n{}",
+            config_file.display(),
+            framework_entry(rules, "  ")
+        );
+    };
+    write_file_if_changed(&config_file, &updated)
+        .with_context(|| format!("Failed to update {FRAMEWORK_CONFIG}"))?;
+    println!(
+        "✓ Added {FRAMEWORK_HOOK_ID} local hook to {}",
+        config_file.display()
+    );
+    println!("  Run `pre-commit install` if the framework's git hook is not installed yet");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hook_command_quotes_rules_path() {
+        assert_eq!(
+            check_command("policy/it's.yaml"),
+            "cgrep check --rules 'policy/it'\\''s.yaml' --changed --staged"
+        );
+        assert!(hook_script("rules.yaml").contains(HOOK_MARKER));
+    }
+
+    #[test]
+    fn framework_entry_joins_the_existing_repos_list() {
+        let config = "repos:\n-   repo: https://github.com/pre-commit/pre-commit-hooks\n    rev: v4.6.0\n    hooks:\n    -   id: trailing-whitespace\n\n# trailing comment\ndefault_stages: [pre-commit]\n";
+        let updated = add_framework_entry(config, "rules.yaml").expect("entry added");
+        assert!(updated.contains(
+            "    -   id: trailing-whitespace\n- repo: local\n  hooks:\n    - id: cgrep-check\n"
+        ));
+        assert!(updated.ends_with("\n# trailing comment\ndefault_stages: [pre-commit]\n"));
+
+        let created = add_framework_entry("", "rules.yaml").expect("config created");
+        assert!(created.starts_with("repos:\n  - repo: local\n"));
+        assert_eq!(add_framework_entry("repos: []\n", "rules.yaml"), None);
+    }
+}
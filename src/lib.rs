@@ -5,10 +5,18 @@
 //! Shared modules for the cgrep CLI tool.
 
 pub mod cache;
+pub mod cancel;
 pub mod config;
 pub mod embedding;
+pub mod encoding;
 pub mod errors;
 pub mod filters;
 pub mod hybrid;
 pub mod output;
+pub mod paths;
+pub mod schema;
+pub mod selftest;
+pub mod syntax;
+pub mod telemetry;
+pub mod usage;
 pub mod utils;
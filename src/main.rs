@@ -5,19 +5,29 @@
 //! A high-performance, AST-aware search tool combining tree-sitter
 //! for code structure analysis and tantivy for BM25 text ranking.
 
+// The MCP tool schemas are large `json!` literals.
+#![recursion_limit = "256"]
+
 mod cli;
 mod cli_auto_index;
+mod cli_profile;
 mod indexer;
 mod install;
 mod mcp;
+mod nvim_rpc;
 mod parser;
 mod query;
 
 use anyhow::Result;
-use clap::{CommandFactory, Parser};
+use clap::CommandFactory;
 use clap_complete::generate;
-use cli::{AgentProvider, Cli, CliBudgetPreset, Commands, DaemonCommands, McpCommands};
+use cli::{
+    AgentProvider, Cli, CliBudgetPreset, Commands, ContextArg, DaemonCommands, IndexCommands,
+    McpCommands,
+};
 use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
 use tracing_subscriber::EnvFilter;
 
 fn config_output_to_cli(format: cgrep::config::ConfigOutputFormat) -> cli::OutputFormat {
@@ -54,9 +64,47 @@ struct BudgetDefaults {
     suppress_boilerplate: bool,
 }
 
-fn budget_defaults(preset: Option<CliBudgetPreset>) -> BudgetDefaults {
+/// Caps for a budget preset, with `[budgets.<name>]` from config applied.
+fn budget_defaults(
+    preset: Option<&CliBudgetPreset>,
+    config: &cgrep::config::Config,
+) -> Result<BudgetDefaults> {
+    let Some(preset) = preset else {
+        return Ok(BudgetDefaults::default());
+    };
+    let custom = config.budget(preset.name());
+    if let (CliBudgetPreset::Custom(name), None) = (preset, custom) {
+        let mut names: Vec<&str> = config.budgets.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        anyhow::bail!(
+            "Unknown budget preset `{}`. Built-ins: tight, balanced, full, off; configured: {}",
+            name,
+            if names.is_empty() {
+                "none (add a [budgets.<name>] table)".to_string()
+            } else {
+                names.join(", ")
+            }
+        );
+    }
+    let base = builtin_budget_defaults(preset);
+    Ok(match custom {
+        Some(custom) => BudgetDefaults {
+            max_chars_per_snippet: custom.max_chars_per_snippet.or(base.max_chars_per_snippet),
+            max_total_chars: custom.max_total_chars.or(base.max_total_chars),
+            max_context_chars: custom.max_context_chars.or(base.max_context_chars),
+            dedupe_context: custom.dedupe_context.unwrap_or(base.dedupe_context),
+            path_alias: custom.path_alias.unwrap_or(base.path_alias),
+            suppress_boilerplate: custom
+                .suppress_boilerplate
+                .unwrap_or(base.suppress_boilerplate),
+        },
+        None => base,
+    })
+}
+
+fn builtin_budget_defaults(preset: &CliBudgetPreset) -> BudgetDefaults {
     match preset {
-        Some(CliBudgetPreset::Tight) => BudgetDefaults {
+        CliBudgetPreset::Tight => BudgetDefaults {
             max_chars_per_snippet: Some(120),
             max_total_chars: Some(2_400),
             max_context_chars: Some(320),
@@ -64,7 +112,7 @@ fn budget_defaults(preset: Option<CliBudgetPreset>) -> BudgetDefaults {
             path_alias: true,
             suppress_boilerplate: true,
         },
-        Some(CliBudgetPreset::Balanced) => BudgetDefaults {
+        CliBudgetPreset::Balanced => BudgetDefaults {
             max_chars_per_snippet: Some(220),
             max_total_chars: Some(6_000),
             max_context_chars: Some(1_200),
@@ -72,7 +120,7 @@ fn budget_defaults(preset: Option<CliBudgetPreset>) -> BudgetDefaults {
             path_alias: true,
             suppress_boilerplate: true,
         },
-        Some(CliBudgetPreset::Full) => BudgetDefaults {
+        CliBudgetPreset::Full => BudgetDefaults {
             max_chars_per_snippet: Some(500),
             max_total_chars: Some(15_000),
             max_context_chars: Some(4_000),
@@ -80,7 +128,7 @@ fn budget_defaults(preset: Option<CliBudgetPreset>) -> BudgetDefaults {
             path_alias: false,
             suppress_boilerplate: true,
         },
-        Some(CliBudgetPreset::Off) | None => BudgetDefaults::default(),
+        CliBudgetPreset::Off | CliBudgetPreset::Custom(_) => BudgetDefaults::default(),
     }
 }
 
@@ -125,7 +173,9 @@ fn uninstall_for_provider(provider: AgentProvider) -> Result<()> {
     }
 }
 
-fn main() -> Result<()> {
+/// Returns the exit status rather than calling `process::exit`, so the
+/// telemetry guard still flushes on non-zero exits.
+fn main() -> Result<ExitCode> {
     // Initialize tracing with CGREP_LOG env var (e.g., CGREP_LOG=debug cgrep search "query")
     tracing_subscriber::fmt()
         .with_env_filter(
@@ -133,58 +183,103 @@ fn main() -> Result<()> {
         )
         .init();
 
-    let cli = Cli::parse();
     let global_config = cgrep::config::Config::load();
+    let (cli, active_profile) = cli_profile::parse(&global_config);
+    let _telemetry = cgrep::telemetry::init(global_config.telemetry());
+    cgrep::usage::init(global_config.usage());
     let default_format = global_config
         .output_format()
         .map(config_output_to_cli)
         .unwrap_or(cli::OutputFormat::Text);
     let cli_format = cli.format;
-    let compact = cli.compact;
-    let global_format = cli_format.unwrap_or(default_format);
+    let compact = cli.compact
+        || active_profile
+            .as_ref()
+            .and_then(|profile| profile.compact)
+            .unwrap_or(false);
+    let global_format = cli_format
+        .or_else(|| {
+            active_profile
+                .as_ref()
+                .and_then(|profile| profile.format)
+                .map(config_output_to_cli)
+        })
+        .unwrap_or(default_format);
+    cgrep::output::set_stable(cli.stable);
+    // Stable output uses `/` on every platform unless a style is given.
+    let path_style = cli
+        .path_style
+        .or(cli.stable.then_some(cli::CliPathStyle::Posix));
+    if let Some(style) = path_style {
+        cgrep::paths::set_path_style(match style {
+            cli::CliPathStyle::Native => cgrep::paths::PathStyle::Native,
+            cli::CliPathStyle::Posix => cgrep::paths::PathStyle::Posix,
+        });
+    }
+    if let Some(version) = cli.schema_version {
+        cgrep::schema::set_requested_version(version).map_err(anyhow::Error::msg)?;
+    }
 
     match cli.command {
-        Commands::Search {
-            query,
-            path_positional,
-            path,
-            recursive: _,
-            no_recursive,
-            no_ignore,
-            limit,
-            context,
-            file_type,
-            glob,
-            exclude,
-            changed,
-            budget,
-            profile,
-            quiet,
-            regex,
-            ignore_case: _,
-            case_sensitive,
-            mode,
-            keyword,
-            semantic,
-            hybrid,
-            explain,
-            help_advanced,
-            context_pack,
-            agent_cache,
-            cache_ttl,
-            max_chars_per_snippet,
-            max_total_chars,
-            max_context_chars,
-            dedupe_context,
-            path_alias,
-            suppress_boilerplate,
-            fuzzy,
-            no_index,
-            bootstrap_index,
-        } => {
+        Commands::Search(search) => {
+            let cli::SearchArgs {
+                query,
+                path_positional,
+                path,
+                recursive: _,
+                no_recursive,
+                no_ignore,
+                hidden,
+                no_ignore_vcs,
+                no_ignore_global,
+                limit,
+                context,
+                file_type,
+                glob,
+                exclude,
+                changed,
+                only_dirty,
+                from_patch,
+                modified_since,
+                author,
+                budget,
+                profile,
+                quiet,
+                regex,
+                ignore_case: _,
+                case_sensitive,
+                mode,
+                keyword,
+                semantic,
+                hybrid,
+                explain,
+                no_lang_route,
+                search_docs,
+                diversify,
+                cluster,
+                summarize_files,
+                require_fresh,
+                no_fallback,
+                plan_only,
+                verify,
+                timeout_ms,
+                help_advanced,
+                context_pack,
+                agent_cache,
+                cache_ttl,
+                max_chars_per_snippet,
+                max_total_chars,
+                max_context_chars,
+                dedupe_context,
+                path_alias,
+                suppress_boilerplate,
+                fuzzy,
+                no_index,
+                bootstrap_index,
+            } = *search;
             if help_advanced {
                 print_search_advanced_help();
-                return Ok(());
+                return Ok(ExitCode::SUCCESS);
             }
 
             let query = query.ok_or_else(|| {
@@ -198,20 +293,12 @@ fn main() -> Result<()> {
             let requested_profile = profile
                 .as_deref()
                 .map(str::trim)
-                .filter(|name| !name.is_empty());
-            let resolved_profile_name =
-                requested_profile.map(|name| config.resolve_profile_name(name));
-            if let Some(requested) = requested_profile {
-                let unknown_profile = resolved_profile_name.as_deref() == Some(requested)
-                    && !config.has_profile(requested)
-                    && !cgrep::config::is_builtin_profile(requested);
-                if unknown_profile {
-                    eprintln!(
-                        "Warning: unknown profile `{}`. Built-ins: human (aliases: user, developer, dev), agent (aliases: ai, ai-agent, coding-agent, coding_agent), fast (alias: quick).",
-                        requested
-                    );
-                }
-            }
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .or_else(cgrep::config::env_profile);
+            let resolved_profile_name = requested_profile
+                .as_deref()
+                .map(|name| cli_profile::resolve(&config, name));
             let profile_config = resolved_profile_name
                 .as_deref()
                 .map(|name| config.profile(name));
@@ -221,7 +308,7 @@ fn main() -> Result<()> {
             } else {
                 None
             });
-            let budget_defaults = budget_defaults(budget_preset);
+            let budget_defaults = budget_defaults(budget_preset.as_ref(), &config)?;
 
             let effective_format = cli_format
                 .or_else(|| {
@@ -238,14 +325,20 @@ fn main() -> Result<()> {
                 .or(config.max_results)
                 .unwrap_or(20);
             let effective_context = context
-                .or_else(|| profile_config.as_ref().and_then(|p| p.context))
-                .unwrap_or(0);
+                .or_else(|| {
+                    profile_config
+                        .as_ref()
+                        .and_then(|p| p.context)
+                        .map(ContextArg::Lines)
+                })
+                .unwrap_or(ContextArg::Lines(0));
             let effective_context_pack = context_pack.or_else(|| {
                 profile_config
                     .as_ref()
                     .and_then(|p| p.context_pack.or(p.context))
             });
-            let effective_agent_cache = agent_cache
+            // Cached results would report `cache_hit`, which differs between runs.
+            let effective_agent_cache = !cli.stable && agent_cache
                 || profile_config
                     .as_ref()
                     .and_then(|p| p.agent_cache)
@@ -297,10 +390,21 @@ fn main() -> Result<()> {
             let background_active =
                 cli_auto_index::background_index_active_for_scope(effective_path);
             let effective_no_index = no_index || background_active;
-            if !effective_no_index && !regex && !no_ignore {
+            let ignore_rules = indexer::scanner::IgnoreRules {
+                hidden,
+                no_ignore_vcs,
+                no_ignore_global,
+            };
+            if !effective_no_index
+                && !regex
+                && !no_ignore
+                && !plan_only
+                && ignore_rules.is_default()
+            {
                 cli_auto_index::maybe_prepare_cli_auto_index(effective_path);
             }
 
+            cgrep::cancel::arm(timeout_ms.map(Duration::from_millis));
             query::search::run(
                 &query,
                 effective_path,
@@ -310,6 +414,10 @@ fn main() -> Result<()> {
                 glob.as_deref(),
                 exclude.as_deref(),
                 changed.as_deref(),
+                only_dirty,
+                from_patch.as_deref(),
+                modified_since.as_deref(),
+                author.as_deref(),
                 quiet,
                 fuzzy,
                 effective_no_index,
@@ -317,6 +425,7 @@ fn main() -> Result<()> {
                 case_sensitive,
                 effective_recursive,
                 no_ignore,
+                ignore_rules,
                 effective_format,
                 compact,
                 effective_mode,
@@ -333,18 +442,111 @@ fn main() -> Result<()> {
                 explicit_mode,
                 bootstrap_index,
                 explain,
+                !no_lang_route,
+                search_docs,
+                diversify,
+                cluster,
+                summarize_files,
+                require_fresh,
+                no_fallback,
+                plan_only,
+                verify,
             )?;
         }
         Commands::Read {
             path,
             section,
             full,
+            expand,
         } => {
-            query::read::run(&path, section.as_deref(), full, global_format, compact)?;
+            query::read::run(
+                &path,
+                section.as_deref(),
+                full,
+                expand.as_deref(),
+                global_format,
+                compact,
+            )?;
         }
         Commands::Map { path, depth } => {
             query::map::run(path.as_deref(), depth, global_format, compact)?;
         }
+        Commands::Grep(grep) => {
+            let cli::GrepArgs {
+                regexp,
+                args,
+                ignore_case,
+                smart_case,
+                case_sensitive,
+                word_regexp,
+                fixed_strings,
+                glob,
+                file_type,
+                type_not,
+                after_context,
+                before_context,
+                context,
+                files_with_matches,
+                count,
+                line_number,
+                no_line_number,
+                with_filename,
+                no_filename,
+                column,
+                vimgrep,
+                max_count,
+                quiet,
+                hidden,
+                no_ignore,
+                no_ignore_vcs,
+                no_ignore_global,
+            } = *grep;
+            // ripgrep exit status: 0 on match, 1 on no match, 2 on error.
+            let result =
+                query::grep::split_positionals(regexp, args).and_then(|(patterns, paths)| {
+                    query::grep::run(&query::grep::GrepOptions {
+                        patterns,
+                        paths,
+                        ignore_case: ignore_case && !case_sensitive,
+                        smart_case: smart_case && !case_sensitive,
+                        word_regexp,
+                        fixed_strings,
+                        globs: glob,
+                        types: file_type,
+                        types_not: type_not,
+                        after_context: after_context.or(context).unwrap_or(0),
+                        before_context: before_context.or(context).unwrap_or(0),
+                        files_with_matches,
+                        count,
+                        line_number: match (line_number, no_line_number) {
+                            (true, _) => Some(true),
+                            (_, true) => Some(false),
+                            _ => None,
+                        },
+                        with_filename: match (with_filename, no_filename) {
+                            (true, _) => Some(true),
+                            (_, true) => Some(false),
+                            _ => None,
+                        },
+                        column,
+                        vimgrep,
+                        max_count,
+                        quiet,
+                        hidden,
+                        no_ignore,
+                        no_ignore_vcs,
+                        no_ignore_global,
+                    })
+                });
+            match result {
+                Ok(true) => {}
+                Ok(false) => return Ok(ExitCode::from(1)),
+                Err(err) => {
+                    eprintln!("cgrep: {err:#}");
+                    return Ok(ExitCode::from(2));
+                }
+            }
+        }
         Commands::Agent { command } => match command {
             cli::AgentCommands::Locate {
                 query,
@@ -359,7 +561,8 @@ fn main() -> Result<()> {
                     .map(cgrep::config::Config::load_for_dir)
                     .unwrap_or_else(cgrep::config::Config::load);
                 let effective_limit = limit.or(config.max_results).unwrap_or(20);
-                let defaults = budget_defaults(Some(budget.unwrap_or(CliBudgetPreset::Balanced)));
+                let defaults =
+                    budget_defaults(Some(&budget.unwrap_or(CliBudgetPreset::Balanced)), &config)?;
                 let effective_mode = mode
                     .map(cli_search_mode_to_hybrid)
                     .or(Some(cgrep::hybrid::SearchMode::Keyword));
@@ -369,11 +572,15 @@ fn main() -> Result<()> {
                     &query,
                     path.as_deref(),
                     effective_limit,
-                    0,
+                    ContextArg::Lines(0),
                     None,
                     None,
                     None,
                     changed.as_deref(),
+                    false,
+                    None,
+                    None,
+                    None,
                     true,
                     false,
                     false,
@@ -381,6 +588,7 @@ fn main() -> Result<()> {
                     false,
                     true,
                     false,
+                    indexer::scanner::IgnoreRules::default(),
                     cli::OutputFormat::Json2,
                     compact,
                     effective_mode,
@@ -397,6 +605,15 @@ fn main() -> Result<()> {
                     true,
                     false,
                     false,
+                    true,
+                    false,
+                    false,
+                    false,
+                    false,
+                    false,
+                    false,
+                    false,
+                    false,
                 )?;
             }
             cli::AgentCommands::Expand { ids, path, context } => {
@@ -470,8 +687,20 @@ fn main() -> Result<()> {
                 )?;
             }
         },
-        Commands::Status { path } => {
-            indexer::status::run(path.as_deref(), global_format, compact)?;
+        Commands::Refresh { path } => {
+            indexer::watch::refresh(path.as_deref())?;
+        }
+        Commands::Status { path, watchers } => {
+            indexer::status::run(path.as_deref(), watchers, global_format, compact)?;
+        }
+        Commands::Usage { days } => {
+            query::usage::run(days, global_format, compact)?;
+        }
+        Commands::Doctor { path } => {
+            indexer::doctor::run(path.as_deref(), global_format, compact)?;
+        }
+        Commands::NvimRpc { lua_module } => {
+            nvim_rpc::run(&lua_module)?;
         }
         Commands::Mcp { command } => match command {
             McpCommands::Serve => {
@@ -484,25 +713,52 @@ fn main() -> Result<()> {
                 mcp::install::uninstall(host)?;
             }
         },
-        Commands::Symbols {
-            name,
-            symbol_type,
-            lang,
-            file_type,
-            glob,
-            exclude,
-            changed,
-            quiet,
-        } => {
+        Commands::Symbols(symbols) => {
+            let cli::SymbolsArgs {
+                name,
+                names,
+                names_file,
+                symbol_type,
+                returns,
+                params,
+                decorated_with,
+                metric_filters,
+                lang,
+                file_type,
+                glob,
+                exclude,
+                changed,
+                only_dirty,
+                sort,
+                offset,
+                limit,
+                timeout_ms,
+                quiet,
+            } = *symbols;
             cli_auto_index::maybe_prepare_cli_auto_index(None);
+            cgrep::cancel::arm(timeout_ms.map(Duration::from_millis));
+            let batch = !names.is_empty() || names_file.is_some();
+            let mut all_names: Vec<String> = name.into_iter().chain(names).collect();
+            if let Some(path) = names_file.as_deref() {
+                all_names.extend(query::symbols::read_names_file(path)?);
+            }
             query::symbols::run(
-                &name,
+                &all_names,
+                batch,
                 symbol_type.as_deref(),
+                returns.as_deref(),
+                &params,
+                decorated_with.as_deref(),
+                &metric_filters,
                 lang.as_deref(),
                 file_type.as_deref(),
                 glob.as_deref(),
                 exclude.as_deref(),
                 changed.as_deref(),
+                only_dirty,
+                sort,
+                offset,
+                limit,
                 quiet,
                 global_format,
                 compact,
@@ -538,52 +794,255 @@ fn main() -> Result<()> {
                 compact,
             )?;
         }
-        Commands::Dependents { file } => {
-            let dependents_scope = Path::new(&file)
-                .parent()
-                .and_then(|parent| parent.to_str())
-                .filter(|parent| !parent.is_empty() && *parent != ".");
-            cli_auto_index::maybe_prepare_cli_auto_index(dependents_scope);
-            query::dependents::run(&file, global_format, compact)?;
+        Commands::RenameCheck { old, new, path } => {
+            cli_auto_index::maybe_prepare_cli_auto_index(path.as_deref());
+            query::rename_check::run(&old, &new, path.as_deref(), global_format, compact)?;
         }
-        Commands::Index {
+        Commands::Dependents {
+            file,
+            transitive,
+            depth,
+            external: _,
             path,
-            force,
-            embeddings,
-            embeddings_force,
-            high_memory,
-            include_ignored,
-            background,
-            background_worker,
-            reuse,
-            no_manifest,
-            manifest_only,
-            print_diff,
-            include_paths,
-            exclude_paths,
+        } => match file {
+            Some(file) => {
+                let dependents_scope = Path::new(&file)
+                    .parent()
+                    .and_then(|parent| parent.to_str())
+                    .filter(|parent| !parent.is_empty() && *parent != ".");
+                cli_auto_index::maybe_prepare_cli_auto_index(dependents_scope);
+                query::dependents::run(&file, transitive, depth, global_format, compact)?;
+            }
+            // `--external`, which clap makes the only way to omit the file.
+            None => query::deps::run(path.as_deref(), global_format, compact)?,
+        },
+        Commands::Imports { file } => {
+            query::imports::run(&file, global_format, compact)?;
+        }
+        Commands::Packages {
+            name,
+            path,
+            examples,
         } => {
-            indexer::index::run(
+            query::packages::run(
+                name.as_deref(),
                 path.as_deref(),
-                indexer::index::RunOptions {
-                    force,
-                    excludes: exclude_paths,
-                    include_paths,
-                    high_memory,
-                    include_ignored,
-                    background,
-                    background_worker,
-                    reuse_mode: reuse,
-                    use_manifest: !no_manifest,
-                    manifest_only,
-                    print_diff,
-                    embeddings_mode: embeddings,
-                    embeddings_force,
-                },
+                examples,
+                global_format,
+                compact,
+            )?;
+        }
+        Commands::Api {
+            path,
+            lang,
+            baseline,
+        } => {
+            query::api::run(
+                path.as_deref(),
+                lang.as_deref(),
+                baseline.as_deref(),
+                global_format,
+                compact,
             )?;
-            if !background {
-                cli_auto_index::touch_cli_auto_index_check_for_scope(path.as_deref());
-            }
         }
+        Commands::Routes {
+            query: route,
+            path,
+            method,
+        } => {
+            query::routes::run(
+                route.as_deref(),
+                path.as_deref(),
+                method.as_deref(),
+                global_format,
+                compact,
+            )?;
+        }
+        Commands::EnvVars { name, path } => {
+            query::envvars::run(name.as_deref(), path.as_deref(), global_format, compact)?;
+        }
+        Commands::Flags {
+            flag,
+            path,
+            patterns,
+        } => {
+            query::flags::run(
+                flag.as_deref(),
+                path.as_deref(),
+                &patterns,
+                global_format,
+                compact,
+            )?;
+        }
+        Commands::Audit { presets, path } => {
+            query::audit::run(&presets, path.as_deref(), global_format, compact)?;
+        }
+        Commands::DiffIndex {
+            snapshots,
+            baseline,
+            path,
+            fail_on_removed,
+        } => {
+            query::diff_index::run(
+                &snapshots,
+                baseline.as_deref(),
+                path.as_deref(),
+                fail_on_removed,
+                global_format,
+                compact,
+            )?;
+        }
+        Commands::Metrics {
+            path,
+            lang,
+            by,
+            top,
+            metric_filters,
+        } => {
+            cli_auto_index::maybe_prepare_cli_auto_index(path.as_deref());
+            query::metrics::run(
+                path.as_deref(),
+                lang.as_deref(),
+                by,
+                top,
+                &metric_filters,
+                global_format,
+                compact,
+            )?;
+        }
+        Commands::Const {
+            value,
+            path,
+            max_results,
+        } => {
+            query::constants::run(&value, path.as_deref(), max_results, global_format, compact)?;
+        }
+        Commands::Literals {
+            value,
+            path,
+            contains,
+            max_results,
+        } => {
+            cli_auto_index::maybe_prepare_cli_auto_index(path.as_deref());
+            query::literals::run(
+                &value,
+                path.as_deref(),
+                contains,
+                max_results,
+                global_format,
+                compact,
+            )?;
+        }
+        Commands::LogSource {
+            log_line,
+            path,
+            max_results,
+        } => {
+            cli_auto_index::maybe_prepare_cli_auto_index(path.as_deref());
+            query::logsource::run(
+                &log_line,
+                path.as_deref(),
+                max_results,
+                global_format,
+                compact,
+            )?;
+        }
+        Commands::Review {
+            changed,
+            path,
+            callers,
+        } => {
+            cli_auto_index::maybe_prepare_cli_auto_index(path.as_deref());
+            query::review::run(&changed, path.as_deref(), callers, global_format, compact)?;
+        }
+        Commands::Trace { file, context } => {
+            cli_auto_index::maybe_prepare_cli_auto_index(None);
+            query::trace::run(file.as_deref(), context, global_format, compact)?;
+        }
+        Commands::Index(index) => match *index {
+            cli::IndexArgs {
+                action: Some(action),
+                ..
+            } => match action {
+                IndexCommands::Snapshot { path, tag } => {
+                    indexer::snapshot::create(
+                        path.as_deref(),
+                        tag.as_deref(),
+                        global_format,
+                        compact,
+                    )?;
+                }
+                IndexCommands::Rollback { tag, path } => {
+                    indexer::snapshot::rollback(path.as_deref(), &tag, global_format, compact)?;
+                }
+            },
+            cli::IndexArgs {
+                action: None,
+                path,
+                force,
+                embeddings,
+                embeddings_force,
+                embeddings_resume,
+                embeddings_batch_size,
+                high_memory,
+                include_ignored,
+                hidden,
+                no_ignore_vcs,
+                no_ignore_global,
+                background,
+                background_worker,
+                reuse,
+                no_manifest,
+                manifest_only,
+                print_diff,
+                dry_run,
+                include_paths,
+                exclude_paths,
+                show_scope,
+                reset_scope,
+                branch_aware,
+                wait: _,
+                no_wait,
+            } => {
+                indexer::index::run(
+                    path.as_deref(),
+                    indexer::index::RunOptions {
+                        force,
+                        excludes: exclude_paths,
+                        include_paths,
+                        high_memory,
+                        include_ignored,
+                        ignore_rules: indexer::scanner::IgnoreRules {
+                            hidden,
+                            no_ignore_vcs,
+                            no_ignore_global,
+                        },
+                        background,
+                        background_worker,
+                        reuse_mode: reuse,
+                        use_manifest: !no_manifest,
+                        manifest_only,
+                        print_diff,
+                        embeddings_mode: embeddings,
+                        embeddings_force,
+                        embeddings_resume,
+                        embeddings_batch_size,
+                        lock_wait: if no_wait {
+                            indexer::lock::LockWait::NoWait
+                        } else {
+                            indexer::lock::LockWait::Wait
+                        },
+                        dry_run,
+                        show_scope,
+                        reset_scope,
+                        branch_aware,
+                    },
+                )?;
+                if !background && !dry_run && !show_scope {
+                    cli_auto_index::touch_cli_auto_index_check_for_scope(path.as_deref());
+                }
+            }
+        },
         // Legacy installation commands (deprecated)
         Commands::InstallClaudeCode => {
             eprintln!(
@@ -631,11 +1090,52 @@ fn main() -> Result<()> {
             );
             uninstall_for_provider(AgentProvider::Opencode)?;
         }
+        Commands::Check {
+            rules,
+            path,
+            changed,
+            staged,
+        } => {
+            query::check::run(
+                &rules,
+                path.as_deref(),
+                changed.as_deref(),
+                staged,
+                global_format,
+                compact,
+            )?;
+        }
+        Commands::Boundaries { rules, path } => {
+            query::boundaries::run(&rules, path.as_deref(), global_format, compact)?;
+        }
+        Commands::InstallPrecommit {
+            rules,
+            path,
+            framework,
+            force,
+        } => {
+            install::precommit::install(&rules, path.as_deref(), framework, force)?;
+        }
+        Commands::InstallGitHooks { path, force } => {
+            install::git_hooks::install(path.as_deref(), force)?;
+        }
+        Commands::Schema { command, validate } => {
+            query::schema::run(command.as_deref(), validate.as_deref())?;
+        }
+        Commands::Selftest { cases, seed } => {
+            query::selftest::run(cases, seed, global_format, compact)?;
+        }
         Commands::Completions { shell } => {
             let mut cmd = Cli::command();
             generate(shell, &mut cmd, "cgrep", &mut std::io::stdout());
         }
     }
 
-    Ok(())
+    if cgrep::cancel::interrupted() {
+        return Ok(ExitCode::from(cgrep::cancel::INTERRUPTED_EXIT_CODE as u8));
+    }
+    if cgrep::cancel::timed_out() {
+        return Ok(ExitCode::from(cgrep::cancel::TIMED_OUT_EXIT_CODE as u8));
+    }
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! MCP `logging`: structured `notifications/message` about work the server
+//! does around tool calls (auto-index bootstraps, refreshes, scan fallbacks).
+//!
+//! Nothing is sent until the client picks a minimum level with
+//! `logging/setLevel`, so hosts that never ask only see responses.
+
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// RFC 5424 severities, least severe first, as named by MCP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Level {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+const LEVELS: [(&str, Level); 8] = [
+    ("debug", Level::Debug),
+    ("info", Level::Info),
+    ("notice", Level::Notice),
+    ("warning", Level::Warning),
+    ("error", Level::Error),
+    ("critical", Level::Critical),
+    ("alert", Level::Alert),
+    ("emergency", Level::Emergency),
+];
+
+/// Minimum level as a `Level` discriminant; `OFF` until the client sets one.
+const OFF: u8 = u8::MAX;
+static MIN_LEVEL: AtomicU8 = AtomicU8::new(OFF);
+
+impl Level {
+    fn name(self) -> &'static str {
+        LEVELS[self as usize].0
+    }
+}
+
+/// Apply `logging/setLevel` params.
+pub(super) fn set_level(params: &Value) -> Result<(), String> {
+    let raw = params
+        .get("level")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "missing required parameter: level".to_string())?;
+    let (_, level) = LEVELS
+        .iter()
+        .find(|(name, _)| *name == raw)
+        .ok_or_else(|| {
+            format!(
+                "invalid log level `{raw}`. Use one of: {}",
+                LEVELS.map(|(name, _)| name).join(", ")
+            )
+        })?;
+    MIN_LEVEL.store(*level as u8, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Send `data` to the client when `level` meets the requested minimum.
+pub(super) fn log(level: Level, data: Value) {
+    let min = MIN_LEVEL.load(Ordering::SeqCst);
+    if min == OFF || (level as u8) < min {
+        return;
+    }
+    let message = json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/message",
+        "params": {
+            "level": level.name(),
+            "logger": "cgrep",
+            "data": data
+        }
+    });
+    // Responses hold the same lock for a whole line, so lines never interleave.
+    let mut out = io::stdout().lock();
+    let _ = serde_json::to_writer(&mut out, &message);
+    let _ = out.write_all(b"\n");
+    let _ = out.flush();
+}
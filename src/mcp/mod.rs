@@ -3,14 +3,19 @@
 //! MCP server support for cgrep (stdio JSON-RPC).
 
 pub mod install;
+mod logging;
+mod paging;
+mod result_cache;
+mod roots;
+mod session;
+mod warmup;
 
 use crate::indexer::scanner::is_indexable_extension;
-use notify::{
-    Config as NotifyConfig, Event, EventKind, RecommendedWatcher, RecursiveMode,
-    Watcher as NotifyWatcher,
-};
+use crate::indexer::watch_limits::{self, WatchHandle};
+use notify::{Config as NotifyConfig, Event, EventKind};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::io::{self, BufRead, Read, Write};
 use std::path::{Component, Path, PathBuf};
@@ -22,6 +27,10 @@ use std::time::{Duration, Instant};
 
 const PROTOCOL_VERSION: &str = "2024-11-05";
 const DEFAULT_MCP_TOOL_TIMEOUT_MS: u64 = 45_000;
+const DEFAULT_MCP_WORKERS: usize = 4;
+/// Upper bound on the share of the tool timeout kept for printing partial
+/// results after `--timeout-ms` fires.
+const PARTIAL_RESULT_RESERVE_MS: u64 = 2_000;
 const DEFAULT_MCP_TOOL_MAX_OUTPUT_BYTES: usize = 64 * 1024 * 1024;
 const DEFAULT_MCP_MAP_DEPTH: u64 = 2;
 const PIPE_DRAIN_GRACE_MS: u64 = 250;
@@ -30,11 +39,21 @@ const AUTO_INDEX_FAILURE_TTL_MS: u64 = 60_000;
 const AUTO_INDEX_REFRESH_DEBOUNCE_MS: u64 = 500;
 const AUTO_INDEX_REFRESH_FAILURE_TTL_MS: u64 = 60_000;
 const AUTO_INDEX_WATCH_POLL_INTERVAL_MS: u64 = 1_500;
+/// Polling interval once the OS watch limit is reached; polling walks the
+/// whole scope, so it runs far less often than the OS backend would report.
+const AUTO_INDEX_WATCH_FALLBACK_POLL_INTERVAL_MS: u64 = 15_000;
 const AUTO_INDEX_SCOPE_IDLE_TTL_MS: u64 = 15 * 60_000;
-static AUTO_INDEX_FAILURES: OnceLock<Mutex<HashMap<String, Instant>>> = OnceLock::new();
-static AUTO_INDEX_SCOPE_STATES: OnceLock<Mutex<HashMap<String, AutoIndexScopeState>>> =
+const DEFAULT_MCP_MAX_WATCHERS: usize = 16;
+/// Held while an auto-index bootstrap or refresh runs, so concurrent tool
+/// calls wait for one index build instead of racing their own.
+static AUTO_INDEX_PREPARE: Mutex<()> = Mutex::new(());
+static AUTO_INDEX_FAILURES: OnceLock<Mutex<HashMap<ScopeKey, Instant>>> = OnceLock::new();
+static AUTO_INDEX_SCOPE_STATES: OnceLock<Mutex<HashMap<ScopeKey, AutoIndexScopeState>>> =
     OnceLock::new();
 
+/// Auto-index state is kept per session and index scope.
+type ScopeKey = (session::Session, String);
+
 // Keep harness guidance close to the server so every MCP host gets the same behavior.
 const HARNESS_INSTRUCTIONS: &str = "\
 cgrep MCP harness (search/navigation only).\n\
@@ -47,6 +66,8 @@ Recommended workflow:\n\
 3) cgrep_agent_expand for exact windows on selected IDs\n\
 4) cgrep_search/cgrep_read only when locate/expand is insufficient\n\
 5) cgrep_definition/cgrep_references/cgrep_callers for symbol relationships\n\
+6) cgrep_status to check index readiness before choosing cgrep_index or scan mode\n\
+7) cgrep_set_root once when calls without `cwd` should run in another project\n\
 \n\
 Harness rules:\n\
 - Prefer structured tool calls with explicit arguments.\n\
@@ -60,42 +81,127 @@ Harness rules:\n\
   cgrep_definition(path/limit), cgrep_references(path/limit/changed/mode),\n\
   cgrep_index(exclude_paths/include_paths/include_ignored/high_memory).\n\
 - For edits, use your host's edit tool after locating exact targets with cgrep.\n\
+- Before renaming a symbol, run cgrep_rename_check and resolve any collisions it reports.\n\
+- When reviewing a change, run cgrep_review on its range to see the symbols each hunk touches and their callers.\n\
 \n\
 This server is read/search oriented; it does not mutate files.";
 
+/// Serve JSON-RPC over stdio.
+///
+/// `tools/call` requests run on a small worker pool so a slow search does not
+/// hold up `ping` or `tools/list`; their responses are written as they finish
+/// and matched by id. Every other method is answered inline, in order. On EOF
+/// the server waits for in-flight calls before returning. Responses to the
+/// server's own `roots/list` requests update the workspace roots. Each
+/// `initialize`, and EOF, releases the ending session's auto-index state.
 pub fn run() -> io::Result<()> {
     let stdin = io::stdin();
-    let stdout = io::stdout();
-    let mut stdout = stdout.lock();
-
-    for line in stdin.lock().lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
+    let stdout = Mutex::new(io::stdout());
+    let (tx, rx) = mpsc::channel::<JsonRpcRequest>();
+    let rx = Mutex::new(rx);
+
+    let served = thread::scope(|scope| {
+        for _ in 0..mcp_worker_count() {
+            scope.spawn(|| loop {
+                let next = rx
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .recv();
+                let Ok(req) = next else {
+                    break;
+                };
+                if write_response(&stdout, &handle_request(&req)).is_err() {
+                    break;
+                }
+            });
         }
 
-        let req = match serde_json::from_str::<JsonRpcRequest>(&line) {
-            Ok(req) => req,
-            Err(err) => {
-                write_error(&mut stdout, None, -32700, &format!("parse error: {}", err))?;
+        for line in stdin.lock().lines() {
+            let line = line?;
+            if line.trim().is_empty() {
                 continue;
             }
-        };
 
-        // JSON-RPC notifications have no id; no response needed.
-        if req.id.is_none() {
-            continue;
-        }
+            let req = match serde_json::from_str::<Value>(&line).and_then(|message| {
+                // Answers to the server's own requests carry no method.
+                if message.get("method").is_none()
+                    && message.get("id").is_some_and(roots::is_list_response)
+                {
+                    prewarm_roots(roots::update(message.get("result")));
+                    return Ok(None);
+                }
+                serde_json::from_value::<JsonRpcRequest>(message).map(Some)
+            }) {
+                Ok(Some(req)) => req,
+                Ok(None) => continue,
+                Err(err) => {
+                    write_error(&stdout, None, -32700, &format!("parse error: {}", err))?;
+                    continue;
+                }
+            };
+
+            // JSON-RPC notifications have no id; no response needed.
+            if req.id.is_none() {
+                if matches!(
+                    req.method.as_str(),
+                    "notifications/initialized" | "notifications/roots/list_changed"
+                ) {
+                    if let Some(request) = roots::list_request() {
+                        write_message(&stdout, &request)?;
+                    }
+                }
+                continue;
+            }
+            if req.method == "initialize" {
+                release_session_state(session::start_connection());
+                roots::note_initialize(&req.params);
+            }
 
-        let resp = handle_request(&req);
-        serde_json::to_writer(&mut stdout, &resp)?;
-        stdout.write_all(b"\n")?;
-        stdout.flush()?;
-    }
+            if req.method == "tools/call" {
+                if let Err(mpsc::SendError(req)) = tx.send(req) {
+                    write_response(&stdout, &handle_request(&req))?;
+                }
+            } else {
+                write_response(&stdout, &handle_request(&req))?;
+            }
+        }
+        drop(tx);
+        Ok(())
+    });
+    release_session_state(session::connection());
+    served
+}
 
+/// Write one response line; the lock keeps concurrent responses whole.
+fn write_response(stdout: &Mutex<io::Stdout>, resp: &JsonRpcResponse) -> io::Result<()> {
+    write_message(stdout, resp)?;
+    cgrep::telemetry::maybe_flush();
     Ok(())
 }
 
+fn write_message(stdout: &Mutex<io::Stdout>, message: &impl Serialize) -> io::Result<()> {
+    let out = stdout
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    // Log notifications take the stdout lock too; hold it for the whole line.
+    let mut out = out.lock();
+    serde_json::to_writer(&mut out, message)?;
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+/// Bootstrap or refresh the index of newly reported workspace roots in the
+/// background, so the first tool call in each finds it ready.
+fn prewarm_roots(new_roots: Vec<PathBuf>) {
+    for root in new_roots {
+        let session = session::current();
+        thread::spawn(move || {
+            let root = root.display().to_string();
+            session::with(session, || ensure_index_for_search(Some(&root), None))
+        });
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct JsonRpcRequest {
     #[serde(rename = "jsonrpc")]
@@ -124,22 +230,36 @@ struct JsonRpcError {
 
 fn handle_request(req: &JsonRpcRequest) -> JsonRpcResponse {
     match req.method.as_str() {
-        "initialize" => JsonRpcResponse {
-            jsonrpc: "2.0",
-            id: req.id.clone(),
-            result: Some(json!({
+        "initialize" => {
+            let mut result = json!({
                 "protocolVersion": PROTOCOL_VERSION,
                 "capabilities": {
-                    "tools": {}
+                    "tools": {},
+                    "logging": {}
                 },
                 "serverInfo": {
                     "name": "cgrep",
                     "version": env!("CARGO_PKG_VERSION")
                 },
                 "instructions": HARNESS_INSTRUCTIONS
-            })),
-            error: None,
-        },
+            });
+            let mut meta = serde_json::Map::new();
+            if let Some(root) = roots::default_root_meta() {
+                meta.insert("cgrep/root".to_string(), root);
+            }
+            if let Some(warmup) = warmup::start(roots::default_root()) {
+                meta.insert("cgrep/warmup".to_string(), warmup);
+            }
+            if !meta.is_empty() {
+                result["_meta"] = Value::Object(meta);
+            }
+            JsonRpcResponse {
+                jsonrpc: "2.0",
+                id: req.id.clone(),
+                result: Some(result),
+                error: None,
+            }
+        }
         "ping" => JsonRpcResponse {
             jsonrpc: "2.0",
             id: req.id.clone(),
@@ -155,6 +275,23 @@ fn handle_request(req: &JsonRpcRequest) -> JsonRpcResponse {
             error: None,
         },
         "tools/call" => handle_tool_call(req),
+        "logging/setLevel" => match logging::set_level(&req.params) {
+            Ok(()) => JsonRpcResponse {
+                jsonrpc: "2.0",
+                id: req.id.clone(),
+                result: Some(json!({})),
+                error: None,
+            },
+            Err(message) => JsonRpcResponse {
+                jsonrpc: "2.0",
+                id: req.id.clone(),
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32602,
+                    message,
+                }),
+            },
+        },
         _ => JsonRpcResponse {
             jsonrpc: "2.0",
             id: req.id.clone(),
@@ -175,19 +312,71 @@ fn handle_tool_call(req: &JsonRpcRequest) -> JsonRpcResponse {
         .unwrap_or_default();
     let args = params.get("arguments").unwrap_or(&Value::Null);
 
-    let result = dispatch_tool(tool_name, args);
+    let started = Instant::now();
+    let span = cgrep::telemetry::Span::start("cgrep.mcp.tool_call");
+    let result: Result<ToolOutput, String> = session::with_call(params, || {
+        let offset = paging::start_offset(tool_name, args)?;
+        let scoped = roots::scope_arguments(tool_name, args)?;
+        let output = cached_dispatch(tool_name, &scoped)?;
+        let (text, truncation) = paging::paginate(
+            tool_name,
+            args,
+            output.text,
+            offset,
+            mcp_tool_max_output_bytes(),
+        )?;
+        Ok(ToolOutput {
+            text,
+            truncation,
+            ..output
+        })
+    });
+    let outcome = match &result {
+        Ok(output) if output.partial => "partial",
+        Ok(_) => "ok",
+        Err(_) => "error",
+    };
+    let attributes = vec![
+        ("tool", tool_name.to_string()),
+        ("outcome", outcome.to_string()),
+    ];
+    cgrep::telemetry::add_counter("cgrep.mcp.tool_calls", 1, attributes.clone());
+    cgrep::telemetry::record_duration(
+        "cgrep.mcp.tool_call.duration",
+        started.elapsed(),
+        attributes.clone(),
+    );
+    span.end(attributes, result.is_ok());
     match result {
-        Ok(output) => JsonRpcResponse {
-            jsonrpc: "2.0",
-            id: req.id.clone(),
-            result: Some(json!({
-                "content": [{
+        Ok(output) => {
+            let mut content = vec![json!({
+                "type": "text",
+                "text": output.text
+            })];
+            if output.partial {
+                content.push(json!({
                     "type": "text",
-                    "text": output
-                }]
-            })),
-            error: None,
-        },
+                    "text": "Partial results: cgrep stopped at its time budget before finishing. Narrow scope (`path`, `glob`, or `changed`) or raise `timeout_ms` for complete results."
+                }));
+            }
+            if let Some(truncation) = &output.truncation {
+                content.push(json!({
+                    "type": "text",
+                    "text": format!(
+                        "Truncated: {} of {} remaining entries fit the output limit. Call {} again with the same arguments plus `continuation`: \"{}\" for the next chunk.",
+                        truncation.returned, truncation.remaining, tool_name, truncation.token
+                    )
+                }));
+            }
+            JsonRpcResponse {
+                jsonrpc: "2.0",
+                id: req.id.clone(),
+                result: Some(json!({
+                    "content": content
+                })),
+                error: None,
+            }
+        }
         Err(err) => JsonRpcResponse {
             jsonrpc: "2.0",
             id: req.id.clone(),
@@ -203,24 +392,115 @@ fn handle_tool_call(req: &JsonRpcRequest) -> JsonRpcResponse {
     }
 }
 
-fn dispatch_tool(tool: &str, args: &Value) -> Result<String, String> {
+/// Text returned by a tool call.
+struct ToolOutput {
+    text: String,
+    /// cgrep stopped at its deadline and `text` holds what it found so far.
+    partial: bool,
+    /// `text` holds the first chunk of output over the size limit.
+    truncation: Option<paging::Truncation>,
+}
+
+impl From<String> for ToolOutput {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            partial: false,
+            truncation: None,
+        }
+    }
+}
+
+/// [`dispatch_tool`] through the session result cache. Partial results,
+/// scan-mode results, and calls that built or refreshed the index on the way
+/// are not stored.
+fn cached_dispatch(tool: &str, args: &Value) -> Result<ToolOutput, String> {
+    if !result_cache::is_cacheable(tool)
+        || opt_bool(args, result_cache::NO_CACHE_ARG)
+        || opt_bool(args, "no_index")
+    {
+        return dispatch_tool(tool, args);
+    }
+    let key = result_cache_key(tool, args);
+    if let Some(text) = key.as_deref().and_then(result_cache::get) {
+        logging::log(
+            logging::Level::Debug,
+            json!({ "event": "result_cache_hit", "tool": tool }),
+        );
+        return Ok(ToolOutput::from(text));
+    }
+    let output = dispatch_tool(tool, args)?;
+    if let Some(key) =
+        key.filter(|_| !output.partial && !result_cache::is_scan_output(&output.text))
+    {
+        if result_cache_key(tool, args).as_ref() == Some(&key) {
+            result_cache::put(key, output.text.clone());
+        }
+    }
+    Ok(output)
+}
+
+/// Cache key for a call, or `None` without an index or while its scope has
+/// file changes the index has not picked up yet.
+fn result_cache_key(tool: &str, args: &Value) -> Option<String> {
+    let search_root = resolve_search_root(opt_cwd(args), None).ok()?;
+    let index_root = cgrep::utils::find_index_root(&search_root)?.root;
+    let pending_changes = scope_state_cache()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(&scope_key(&index_root))
+        .is_some_and(|state| !state.tracks_changes() || state.dirty.load(Ordering::Acquire));
+    if pending_changes {
+        return None;
+    }
+    result_cache::key(tool, args, &index_root)
+}
+
+thread_local! {
+    /// `profile` argument of the tool call running on this worker, passed to
+    /// child commands as `CGREP_PROFILE`.
+    static CALL_PROFILE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn dispatch_tool(tool: &str, args: &Value) -> Result<ToolOutput, String> {
+    let profile = opt_str(args, "profile")
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string);
+    CALL_PROFILE.with(|current| *current.borrow_mut() = profile);
+    let output = dispatch_tool_inner(tool, args);
+    CALL_PROFILE.with(|current| current.borrow_mut().take());
+    output
+}
+
+fn dispatch_tool_inner(tool: &str, args: &Value) -> Result<ToolOutput, String> {
     match tool {
         "cgrep_search" => tool_search(args),
-        "cgrep_agent_locate" => tool_agent_locate(args),
-        "cgrep_agent_expand" => tool_agent_expand(args),
-        "cgrep_read" => tool_read(args),
-        "cgrep_map" => tool_map(args),
         "cgrep_symbols" => tool_symbols(args),
-        "cgrep_definition" => tool_definition(args),
-        "cgrep_references" => tool_references(args),
-        "cgrep_callers" => tool_callers(args),
-        "cgrep_dependents" => tool_dependents(args),
-        "cgrep_index" => tool_index(args),
-        _ => Err(format!("unknown tool: {}", tool)),
+        _ => {
+            let output = match tool {
+                "cgrep_agent_locate" => tool_agent_locate(args),
+                "cgrep_agent_expand" => tool_agent_expand(args),
+                "cgrep_read" => tool_read(args),
+                "cgrep_map" => tool_map(args),
+                "cgrep_definition" => tool_definition(args),
+                "cgrep_references" => tool_references(args),
+                "cgrep_rename_check" => tool_rename_check(args),
+                "cgrep_review" => tool_review(args),
+                "cgrep_callers" => tool_callers(args),
+                "cgrep_dependents" => tool_dependents(args),
+                "cgrep_imports" => tool_imports(args),
+                "cgrep_index" => tool_index(args),
+                "cgrep_status" => tool_status(args),
+                "cgrep_set_root" => tool_set_root(args),
+                _ => Err(format!("unknown tool: {}", tool)),
+            };
+            output.map(ToolOutput::from)
+        }
     }
 }
 
-fn tool_search(args: &Value) -> Result<String, String> {
+fn tool_search(args: &Value) -> Result<ToolOutput, String> {
     let query = required_str(args, "query")?;
     let cwd = opt_cwd(args);
     let path = opt_str(args, "path");
@@ -249,6 +529,9 @@ fn tool_search(args: &Value) -> Result<String, String> {
     push_opt_flag_value(&mut cmd, "-p", path);
     push_opt_flag_value_u64(&mut cmd, "-m", opt_u64(args, "limit"));
     push_opt_flag_value_u64(&mut cmd, "-C", opt_u64(args, "context"));
+    if opt_str(args, "context") == Some("symbol-header") {
+        push_opt_flag_value(&mut cmd, "-C", Some("symbol-header"));
+    }
     push_opt_flag_value(&mut cmd, "-t", opt_str(args, "file_type"));
     push_opt_flag_value(&mut cmd, "--glob", opt_str(args, "glob"));
     push_opt_flag_value(&mut cmd, "--exclude", opt_str(args, "exclude"));
@@ -275,6 +558,13 @@ fn tool_search(args: &Value) -> Result<String, String> {
     push_opt_flag_value(&mut cmd, "-P", search_profile.as_deref());
     push_opt_flag_value(&mut cmd, "--mode", search_mode.as_deref());
     push_changed(&mut cmd, args.get("changed"));
+    push_bool_flag(&mut cmd, "--only-dirty", opt_bool(args, "only_dirty"));
+    push_opt_flag_value(
+        &mut cmd,
+        "--modified-since",
+        opt_str(args, "modified_since"),
+    );
+    push_opt_flag_value(&mut cmd, "--author", opt_str(args, "author"));
     push_bool_flag(
         &mut cmd,
         "--dedupe-context",
@@ -303,13 +593,36 @@ fn tool_search(args: &Value) -> Result<String, String> {
     );
     push_bool_flag(&mut cmd, "--no-recursive", opt_bool(args, "no_recursive"));
     push_bool_flag(&mut cmd, "--no-ignore", opt_bool(args, "no_ignore"));
+    push_bool_flag(&mut cmd, "--hidden", opt_bool(args, "hidden"));
+    push_bool_flag(&mut cmd, "--no-ignore-vcs", opt_bool(args, "no_ignore_vcs"));
+    push_bool_flag(
+        &mut cmd,
+        "--no-ignore-global",
+        opt_bool(args, "no_ignore_global"),
+    );
     push_bool_flag(&mut cmd, "--fuzzy", opt_bool(args, "fuzzy"));
+    push_bool_flag(&mut cmd, "--search-docs", opt_bool(args, "search_docs"));
+    push_bool_flag(&mut cmd, "--diversify", opt_bool(args, "diversify"));
+    push_bool_flag(&mut cmd, "--cluster", opt_bool(args, "cluster"));
+    push_bool_flag(
+        &mut cmd,
+        "--summarize-files",
+        opt_bool(args, "summarize_files"),
+    );
+    push_bool_flag(&mut cmd, "--require-fresh", opt_bool(args, "require_fresh"));
+    push_bool_flag(&mut cmd, "--no-fallback", opt_bool(args, "no_fallback"));
+    push_bool_flag(&mut cmd, "--verify", opt_bool(args, "verify"));
+    push_opt_flag_value_u64(
+        &mut cmd,
+        "--timeout-ms",
+        Some(partial_result_deadline_ms(opt_u64(args, "timeout_ms"))),
+    );
     push_bool_flag(&mut cmd, "-q", opt_bool(args, "quiet"));
     push_bool_flag(&mut cmd, "--bootstrap-index", bootstrap_index);
     cmd.push("--".to_string());
     cmd.push(query.to_string());
 
-    run_cgrep(&cmd, cwd)
+    run_cgrep_output(&cmd, cwd)
 }
 
 fn tool_agent_locate(args: &Value) -> Result<String, String> {
@@ -363,18 +676,19 @@ fn tool_read(args: &Value) -> Result<String, String> {
     let paths = read_paths(args)?;
     let section = resolve_read_section(args)?;
     let full = opt_bool(args, "full");
+    let expand = opt_str(args, "expand");
 
     for path in &paths {
         require_bounded_relative_scope("cgrep_read", cwd, Some(path.as_str()), false)?;
     }
 
     if paths.len() == 1 {
-        return run_read_for_path(paths[0].as_str(), section.as_deref(), full, cwd);
+        return run_read_for_path(paths[0].as_str(), section.as_deref(), full, expand, cwd);
     }
 
     let mut results: Vec<Value> = Vec::with_capacity(paths.len());
     for path in paths {
-        let output = run_read_for_path(path.as_str(), section.as_deref(), full, cwd)?;
+        let output = run_read_for_path(path.as_str(), section.as_deref(), full, expand, cwd)?;
         let parsed =
             serde_json::from_str::<Value>(&output).unwrap_or_else(|_| json!({ "raw": output }));
         results.push(json!({
@@ -399,6 +713,7 @@ fn run_read_for_path(
     path: &str,
     section: Option<&str>,
     full: bool,
+    expand: Option<&str>,
     cwd: Option<&str>,
 ) -> Result<String, String> {
     let mut cmd = vec![
@@ -410,6 +725,7 @@ fn run_read_for_path(
     ];
     push_opt_flag_value(&mut cmd, "--section", section);
     push_bool_flag(&mut cmd, "--full", full);
+    push_opt_flag_value(&mut cmd, "--expand", expand);
     run_cgrep(&cmd, cwd)
 }
 
@@ -428,8 +744,13 @@ fn tool_map(args: &Value) -> Result<String, String> {
     run_cgrep(&cmd, cwd)
 }
 
-fn tool_symbols(args: &Value) -> Result<String, String> {
-    let name = required_str(args, "name")?;
+fn tool_symbols(args: &Value) -> Result<ToolOutput, String> {
+    let names = opt_array_str(args, "names").filter(|names| !names.is_empty());
+    let decorated_with = opt_str(args, "decorated_with");
+    let name = match (&names, decorated_with) {
+        (None, None) => Some(required_str(args, "name")?),
+        _ => opt_str(args, "name"),
+    };
     let cwd = opt_cwd(args);
     require_bounded_relative_scope("cgrep_symbols", cwd, None, true)?;
     maybe_prepare_auto_index(args, cwd, None)?;
@@ -438,16 +759,34 @@ fn tool_symbols(args: &Value) -> Result<String, String> {
         "json".to_string(),
         "--compact".to_string(),
         "symbols".to_string(),
-        name.to_string(),
     ];
+    match names {
+        // Batched lookups return an object keyed by requested name.
+        Some(names) => {
+            for name in name.into_iter().chain(names) {
+                cmd.push(format!("--name={name}"));
+            }
+        }
+        None => cmd.extend(name.map(str::to_string)),
+    }
     push_opt_flag_value(&mut cmd, "-T", opt_str(args, "symbol_type"));
+    push_opt_flag_value(&mut cmd, "--decorated-with", decorated_with);
     push_opt_flag_value(&mut cmd, "--lang", opt_str(args, "lang"));
     push_opt_flag_value(&mut cmd, "--file-type", opt_str(args, "file_type"));
     push_opt_flag_value(&mut cmd, "--glob", opt_str(args, "glob"));
     push_opt_flag_value(&mut cmd, "--exclude", opt_str(args, "exclude"));
     push_changed(&mut cmd, args.get("changed"));
+    push_bool_flag(&mut cmd, "--only-dirty", opt_bool(args, "only_dirty"));
+    push_opt_flag_value(&mut cmd, "--sort", opt_str(args, "sort"));
+    push_opt_flag_value_u64(&mut cmd, "--offset", opt_u64(args, "offset"));
+    push_opt_flag_value_u64(&mut cmd, "--limit", opt_u64(args, "limit"));
+    push_opt_flag_value_u64(
+        &mut cmd,
+        "--timeout-ms",
+        Some(partial_result_deadline_ms(opt_u64(args, "timeout_ms"))),
+    );
     push_bool_flag(&mut cmd, "-q", opt_bool(args, "quiet"));
-    run_cgrep(&cmd, cwd)
+    run_cgrep_output(&cmd, cwd)
 }
 
 fn tool_definition(args: &Value) -> Result<String, String> {
@@ -488,6 +827,42 @@ fn tool_references(args: &Value) -> Result<String, String> {
     run_cgrep(&cmd, cwd)
 }
 
+fn tool_rename_check(args: &Value) -> Result<String, String> {
+    let old = required_str(args, "old")?;
+    let new = required_str(args, "new")?;
+    let cwd = opt_cwd(args);
+    let path = opt_str(args, "path");
+    require_bounded_relative_scope("cgrep_rename_check", cwd, path, true)?;
+    maybe_prepare_auto_index(args, cwd, path)?;
+    let mut cmd = vec![
+        "--format".to_string(),
+        "json".to_string(),
+        "--compact".to_string(),
+        "rename-check".to_string(),
+        old.to_string(),
+        new.to_string(),
+    ];
+    push_opt_flag_value(&mut cmd, "-p", path);
+    run_cgrep(&cmd, cwd)
+}
+
+fn tool_review(args: &Value) -> Result<String, String> {
+    let cwd = opt_cwd(args);
+    let path = opt_str(args, "path");
+    require_bounded_relative_scope("cgrep_review", cwd, path, true)?;
+    maybe_prepare_auto_index(args, cwd, path)?;
+    let mut cmd = vec![
+        "--format".to_string(),
+        "json2".to_string(),
+        "--compact".to_string(),
+        "review".to_string(),
+    ];
+    push_opt_flag_value(&mut cmd, "--changed", opt_str(args, "range"));
+    push_opt_flag_value(&mut cmd, "-p", path);
+    push_opt_flag_value_u64(&mut cmd, "--callers", opt_u64(args, "callers"));
+    run_cgrep(&cmd, cwd)
+}
+
 fn tool_callers(args: &Value) -> Result<String, String> {
     let function = required_str(args, "function")?;
     let cwd = opt_cwd(args);
@@ -513,13 +888,31 @@ fn tool_dependents(args: &Value) -> Result<String, String> {
         .and_then(|parent| parent.to_str())
         .filter(|parent| !parent.is_empty() && *parent != ".");
     maybe_prepare_auto_index(args, cwd, dependents_scope)?;
-    let cmd = vec![
+    let mut cmd = vec![
         "--format".to_string(),
         "json".to_string(),
         "--compact".to_string(),
         "dependents".to_string(),
         file.to_string(),
     ];
+    push_bool_flag(&mut cmd, "--transitive", opt_bool(args, "transitive"));
+    if opt_bool(args, "transitive") {
+        push_opt_flag_value_u64(&mut cmd, "--depth", opt_u64(args, "depth"));
+    }
+    run_cgrep(&cmd, cwd)
+}
+
+fn tool_imports(args: &Value) -> Result<String, String> {
+    let file = required_str(args, "file")?;
+    let cwd = opt_cwd(args);
+    require_bounded_relative_scope("cgrep_imports", cwd, Some(file), false)?;
+    let cmd = vec![
+        "--format".to_string(),
+        "json".to_string(),
+        "--compact".to_string(),
+        "imports".to_string(),
+        file.to_string(),
+    ];
     run_cgrep(&cmd, cwd)
 }
 
@@ -553,6 +946,85 @@ fn tool_index(args: &Value) -> Result<String, String> {
     run_cgrep(&cmd, cwd)
 }
 
+fn tool_set_root(args: &Value) -> Result<String, String> {
+    let (root, summary) = roots::set_root(args)?;
+    prewarm_roots(vec![root]);
+    Ok(summary)
+}
+
+/// Index health for the scope: `status` and `doctor` of its index root, the
+/// server's watcher state, and a suggested next step for the agent.
+fn tool_status(args: &Value) -> Result<String, String> {
+    let cwd = opt_cwd(args);
+    let path = opt_str(args, "path");
+    require_bounded_relative_scope("cgrep_status", cwd, path, true)?;
+    let search_root = resolve_search_root(cwd, path)?;
+    let root = cgrep::utils::find_index_root(&search_root)
+        .map(|found| found.root)
+        .unwrap_or(search_root);
+    let root_arg = root.display().to_string();
+    let report = |command: &[&str]| -> Result<Value, String> {
+        let cmd: Vec<String> = ["--format", "json", "--compact"]
+            .iter()
+            .chain(command)
+            .chain(&["-p", &root_arg])
+            .map(|arg| arg.to_string())
+            .collect();
+        let text = run_cgrep(&cmd, cwd)?;
+        serde_json::from_str(&text)
+            .map_err(|err| format!("invalid cgrep {} output: {err}", command[0]))
+    };
+    let status = report(&["status", "--watchers"])?;
+    let doctor = report(&["doctor"])?;
+
+    let building = matches!(
+        status["phase"].as_str(),
+        Some("starting" | "indexing" | "embedding")
+    );
+    let next_step = if building {
+        "wait_for_build"
+    } else if status["full_ready"] != true || status["index_schema"]["version"].is_null() {
+        "run_cgrep_index"
+    } else {
+        "ready"
+    };
+    Ok(json!({
+        "root": root_arg,
+        "next_step": next_step,
+        "status": status,
+        "doctor": doctor,
+        "auto_index": auto_index_scope_snapshot(&root),
+    })
+    .to_string())
+}
+
+/// The server's watcher state for an index scope; `null` until a tool call
+/// has auto-indexed or refreshed it.
+fn auto_index_scope_snapshot(index_scope: &Path) -> Value {
+    let cache = scope_state_cache()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    cache
+        .get(&scope_key(index_scope))
+        .map_or(Value::Null, |state| {
+            let polling = state
+                .watcher
+                .as_ref()
+                .and_then(|watch| watch.fallback.as_deref());
+            json!({
+                "watching": state.has_watcher,
+                "mode": match (state.has_watcher, polling) {
+                    (false, _) => "none",
+                    (true, None) => "native",
+                    (true, Some(_)) => "poll",
+                },
+                "fallback_reason": polling,
+                "limit_reached": state.limit_reached.load(Ordering::Acquire),
+                "dirty": state.dirty.load(Ordering::Acquire),
+            })
+        })
+}
+
 fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
     args.get(key)
         .and_then(Value::as_str)
@@ -621,23 +1093,7 @@ fn resolve_read_section(args: &Value) -> Result<Option<String>, String> {
     let section = opt_str(args, "section")
         .map(str::trim)
         .filter(|value| !value.is_empty());
-    Ok(section.map(normalize_section_range))
-}
-
-fn normalize_section_range(value: &str) -> String {
-    let trimmed = value.trim();
-    if let Some((start, end)) = trimmed.split_once(':') {
-        let start = start.trim();
-        let end = end.trim();
-        if !start.is_empty()
-            && !end.is_empty()
-            && start.chars().all(|c| c.is_ascii_digit())
-            && end.chars().all(|c| c.is_ascii_digit())
-        {
-            return format!("{start}-{end}");
-        }
-    }
-    trimmed.to_string()
+    Ok(section.map(cgrep::syntax::normalize_section_range))
 }
 
 fn opt_str<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
@@ -686,9 +1142,20 @@ fn require_bounded_relative_scope(
 
     let server_cwd =
         std::env::current_dir().map_err(|err| format!("failed to resolve server cwd: {err}"))?;
-    if server_cwd == Path::new("/") {
+    if cgrep::paths::is_filesystem_root(&server_cwd) {
         return Err(format!(
-            "{tool_name} requires `cwd` (or an absolute `path`) when server cwd is `/` to avoid scanning the system root"
+            "{tool_name} requires `cwd` (or an absolute `path`) when server cwd is a filesystem root ({}) to avoid scanning the whole drive; or call cgrep_set_root first",
+            server_cwd.display()
+        ));
+    }
+    let is_home = dirs::home_dir()
+        .and_then(|home| cgrep::paths::canonicalize(&home).ok())
+        .zip(cgrep::paths::canonicalize(&server_cwd).ok())
+        .is_some_and(|(home, cwd)| home == cwd);
+    if is_home {
+        return Err(format!(
+            "{tool_name} requires `cwd` (or an absolute `path`) when server cwd is the home directory ({}) to avoid scanning every project in it; or call cgrep_set_root first",
+            server_cwd.display()
         ));
     }
 
@@ -698,8 +1165,11 @@ fn require_bounded_relative_scope(
 struct AutoIndexScopeState {
     dirty: Arc<AtomicBool>,
     has_watcher: bool,
+    /// The OS watch limit was hit after the watch started, so some new
+    /// directories go unwatched.
+    limit_reached: Arc<AtomicBool>,
     // Hold watcher lifetime for this scope. Dropping it stops watching.
-    _watcher: Option<RecommendedWatcher>,
+    watcher: Option<WatchHandle>,
     last_seen_at: Instant,
     last_refresh_attempt_at: Option<Instant>,
     last_refresh_failure_at: Option<Instant>,
@@ -708,16 +1178,24 @@ struct AutoIndexScopeState {
 impl AutoIndexScopeState {
     fn new(scope: &Path) -> Self {
         let dirty = Arc::new(AtomicBool::new(true));
-        let watcher = create_scope_watcher(scope, Arc::clone(&dirty));
+        let limit_reached = Arc::new(AtomicBool::new(false));
+        let watcher = create_scope_watcher(scope, Arc::clone(&dirty), Arc::clone(&limit_reached));
         Self {
             dirty,
             has_watcher: watcher.is_some(),
-            _watcher: watcher,
+            limit_reached,
+            watcher,
             last_seen_at: Instant::now(),
             last_refresh_attempt_at: None,
             last_refresh_failure_at: None,
         }
     }
+
+    /// Whether the watcher sees every change, so a clean scope needs no
+    /// refresh.
+    fn tracks_changes(&self) -> bool {
+        self.has_watcher && !self.limit_reached.load(Ordering::Acquire)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -728,7 +1206,7 @@ enum BootstrapOutcome {
     FellBackToScan,
 }
 
-fn maybe_prepare_auto_index(
+pub(crate) fn maybe_prepare_auto_index(
     args: &Value,
     cwd: Option<&str>,
     path: Option<&str>,
@@ -744,36 +1222,115 @@ fn ensure_index_for_search(
     cwd: Option<&str>,
     path: Option<&str>,
 ) -> Result<BootstrapOutcome, String> {
+    let _prepare = AUTO_INDEX_PREPARE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
     let search_root = resolve_search_root(cwd, path)?;
     let existing_index_root = cgrep::utils::find_index_root(&search_root);
     let index_scope = existing_index_root
         .as_ref()
         .map(|root| root.root.clone())
         .unwrap_or_else(|| search_root.clone());
+    let config = cgrep::config::Config::load_for_dir(&index_scope);
+    let policy = config.auto_index();
     if existing_index_root.is_some() {
         clear_bootstrap_failure(&index_scope);
-        if maybe_refresh_existing_index(cwd, &index_scope)? {
+        if policy.enabled() && maybe_refresh_existing_index(cwd, &index_scope, policy)? {
             return Ok(BootstrapOutcome::Refreshed);
         }
         return Ok(BootstrapOutcome::AlreadyIndexed);
     }
-    if recently_failed_bootstrap(&index_scope) {
+    let scope = index_scope.display().to_string();
+    if !policy.enabled() || recently_failed_bootstrap(&index_scope) {
+        let reason = if policy.enabled() {
+            "a bootstrap failed recently"
+        } else {
+            "auto-indexing is disabled by config"
+        };
+        logging::log(
+            logging::Level::Notice,
+            json!({ "event": "scan_fallback", "scope": scope, "reason": reason }),
+        );
         return Ok(BootstrapOutcome::FellBackToScan);
     }
+    if let Some(estimate) = crate::cli_auto_index::oversized_scope(&index_scope, policy) {
+        logging::log(
+            logging::Level::Warning,
+            json!({
+                "event": "auto_index_refused",
+                "scope": scope,
+                "reason": "scope exceeds [auto_index] limits",
+                "files": estimate.files,
+            }),
+        );
+        return Err(oversized_scope_error(&index_scope, policy, estimate));
+    }
 
-    match run_index_for_scope(cwd, &index_scope) {
+    logging::log(
+        logging::Level::Info,
+        json!({ "event": "auto_index_bootstrap_started", "scope": scope }),
+    );
+    let started = Instant::now();
+    match run_index_for_scope(cwd, &index_scope, policy.embeddings_mode()) {
         Ok(_) => {
             clear_bootstrap_failure(&index_scope);
             mark_scope_indexed(&index_scope);
+            logging::log(
+                logging::Level::Info,
+                json!({
+                    "event": "auto_index_bootstrap",
+                    "scope": scope,
+                    "elapsed_ms": started.elapsed().as_millis() as u64,
+                }),
+            );
             Ok(BootstrapOutcome::Bootstrapped)
         }
-        Err(_) => {
+        Err(err) => {
             record_bootstrap_failure(&index_scope);
+            logging::log(
+                logging::Level::Warning,
+                json!({
+                    "event": "scan_fallback",
+                    "scope": scope,
+                    "reason": "auto-index bootstrap failed",
+                    "error": err,
+                    "elapsed_ms": started.elapsed().as_millis() as u64,
+                }),
+            );
             Ok(BootstrapOutcome::FellBackToScan)
         }
     }
 }
 
+/// Structured refusal for a bootstrap over an oversized scope, such as a
+/// home directory reached through a missing `cwd`.
+fn oversized_scope_error(
+    scope: &Path,
+    policy: &cgrep::config::AutoIndexConfig,
+    estimate: crate::cli_auto_index::RepoSizeEstimate,
+) -> String {
+    json!({
+        "error": "auto_index_scope_too_large",
+        "message": format!(
+            "refusing to auto-index {}: at least {} indexable files ({} bytes) found",
+            scope.display(),
+            estimate.files,
+            estimate.bytes
+        ),
+        "scope": scope.display().to_string(),
+        "estimate": {
+            "files": estimate.files,
+            "bytes": estimate.bytes,
+        },
+        "limits": {
+            "max_repo_files": policy.max_repo_files(),
+            "max_repo_size_mb": policy.max_repo_size_mb,
+        },
+        "hint": "pass `cwd` (or `path`) pointing at a project directory, raise `[auto_index] max_repo_files` / `max_repo_size_mb`, or set `auto_index: false` to search without an index",
+    })
+    .to_string()
+}
+
 fn resolve_search_root(cwd: Option<&str>, path: Option<&str>) -> Result<PathBuf, String> {
     let base = match cwd {
         Some(raw) => PathBuf::from(raw),
@@ -785,22 +1342,22 @@ fn resolve_search_root(cwd: Option<&str>, path: Option<&str>) -> Result<PathBuf,
     } else {
         base.join(requested)
     };
-    if let Ok(canonical) = absolute.canonicalize() {
+    if let Ok(canonical) = cgrep::paths::canonicalize(&absolute) {
         absolute = canonical;
     }
     Ok(absolute)
 }
 
-fn failure_cache() -> &'static Mutex<HashMap<String, Instant>> {
+fn failure_cache() -> &'static Mutex<HashMap<ScopeKey, Instant>> {
     AUTO_INDEX_FAILURES.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
-fn scope_state_cache() -> &'static Mutex<HashMap<String, AutoIndexScopeState>> {
+fn scope_state_cache() -> &'static Mutex<HashMap<ScopeKey, AutoIndexScopeState>> {
     AUTO_INDEX_SCOPE_STATES.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
 fn recently_failed_bootstrap(search_root: &Path) -> bool {
-    let key = search_root.display().to_string();
+    let key = scope_key(search_root);
     let ttl = Duration::from_millis(AUTO_INDEX_FAILURE_TTL_MS);
     let now = Instant::now();
     let mut cache = failure_cache()
@@ -811,7 +1368,7 @@ fn recently_failed_bootstrap(search_root: &Path) -> bool {
 }
 
 fn record_bootstrap_failure(search_root: &Path) {
-    let key = search_root.display().to_string();
+    let key = scope_key(search_root);
     let mut cache = failure_cache()
         .lock()
         .unwrap_or_else(|poisoned| poisoned.into_inner());
@@ -819,44 +1376,54 @@ fn record_bootstrap_failure(search_root: &Path) {
 }
 
 fn clear_bootstrap_failure(search_root: &Path) {
-    let key = search_root.display().to_string();
+    let key = scope_key(search_root);
     let mut cache = failure_cache()
         .lock()
         .unwrap_or_else(|poisoned| poisoned.into_inner());
     cache.remove(&key);
 }
 
-fn maybe_refresh_existing_index(cwd: Option<&str>, index_scope: &Path) -> Result<bool, String> {
-    if !should_attempt_index_refresh(index_scope) {
+fn maybe_refresh_existing_index(
+    cwd: Option<&str>,
+    index_scope: &Path,
+    policy: &cgrep::config::AutoIndexConfig,
+) -> Result<bool, String> {
+    let debounce_ms = policy.debounce_ms.unwrap_or(AUTO_INDEX_REFRESH_DEBOUNCE_MS);
+    if !should_attempt_index_refresh(index_scope, debounce_ms) {
         return Ok(false);
     }
-    match run_index_for_scope(cwd, index_scope) {
+    let started = Instant::now();
+    let result = run_index_for_scope(cwd, index_scope, policy.embeddings_mode());
+    let mut data = json!({
+        "event": "auto_index_refresh",
+        "scope": index_scope.display().to_string(),
+        "ok": result.is_ok(),
+        "elapsed_ms": started.elapsed().as_millis() as u64,
+    });
+    record_scope_refresh_result(index_scope, result.is_ok());
+    match result {
         Ok(_) => {
-            record_scope_refresh_result(index_scope, true);
+            logging::log(logging::Level::Info, data);
             Ok(true)
         }
-        Err(_) => {
-            record_scope_refresh_result(index_scope, false);
+        Err(err) => {
+            // Tool calls keep using the index as it was.
+            data["error"] = json!(err);
+            logging::log(logging::Level::Warning, data);
             Ok(false)
         }
     }
 }
 
-fn should_attempt_index_refresh(index_scope: &Path) -> bool {
+fn should_attempt_index_refresh(index_scope: &Path, debounce_ms: u64) -> bool {
     let now = Instant::now();
-    let key = index_scope.display().to_string();
-    let refresh_debounce = Duration::from_millis(AUTO_INDEX_REFRESH_DEBOUNCE_MS);
+    let refresh_debounce = Duration::from_millis(debounce_ms);
     let refresh_failure_ttl = Duration::from_millis(AUTO_INDEX_REFRESH_FAILURE_TTL_MS);
 
     let mut cache = scope_state_cache()
         .lock()
         .unwrap_or_else(|poisoned| poisoned.into_inner());
-    prune_idle_scope_states(&mut cache, now);
-
-    let state = cache
-        .entry(key)
-        .or_insert_with(|| AutoIndexScopeState::new(index_scope));
-    state.last_seen_at = now;
+    let state = touch_scope_state(&mut cache, index_scope, now);
 
     if state
         .last_refresh_attempt_at
@@ -871,7 +1438,7 @@ fn should_attempt_index_refresh(index_scope: &Path) -> bool {
         return false;
     }
 
-    let should_refresh = if state.has_watcher {
+    let should_refresh = if state.tracks_changes() {
         state.dirty.load(Ordering::Acquire)
     } else {
         // If watcher setup fails, keep correctness by refreshing opportunistically.
@@ -887,30 +1454,20 @@ fn should_attempt_index_refresh(index_scope: &Path) -> bool {
 
 fn mark_scope_indexed(index_scope: &Path) {
     let now = Instant::now();
-    let key = index_scope.display().to_string();
     let mut cache = scope_state_cache()
         .lock()
         .unwrap_or_else(|poisoned| poisoned.into_inner());
-    prune_idle_scope_states(&mut cache, now);
-    let state = cache
-        .entry(key)
-        .or_insert_with(|| AutoIndexScopeState::new(index_scope));
-    state.last_seen_at = now;
+    let state = touch_scope_state(&mut cache, index_scope, now);
     state.dirty.store(false, Ordering::Release);
     state.last_refresh_failure_at = None;
 }
 
 fn record_scope_refresh_result(index_scope: &Path, success: bool) {
     let now = Instant::now();
-    let key = index_scope.display().to_string();
     let mut cache = scope_state_cache()
         .lock()
         .unwrap_or_else(|poisoned| poisoned.into_inner());
-    prune_idle_scope_states(&mut cache, now);
-    let state = cache
-        .entry(key)
-        .or_insert_with(|| AutoIndexScopeState::new(index_scope));
-    state.last_seen_at = now;
+    let state = touch_scope_state(&mut cache, index_scope, now);
     if success {
         state.dirty.store(false, Ordering::Release);
         state.last_refresh_failure_at = None;
@@ -919,37 +1476,115 @@ fn record_scope_refresh_result(index_scope: &Path, success: bool) {
     }
 }
 
-fn prune_idle_scope_states(cache: &mut HashMap<String, AutoIndexScopeState>, now: Instant) {
+fn scope_key(index_scope: &Path) -> ScopeKey {
+    (session::current(), index_scope.display().to_string())
+}
+
+/// The current session's state for `index_scope`, created on first use.
+/// Idle states are dropped first, and creating one while `max_watchers`
+/// scopes are watched stops the least recently used watcher.
+fn touch_scope_state<'a>(
+    cache: &'a mut HashMap<ScopeKey, AutoIndexScopeState>,
+    index_scope: &Path,
+    now: Instant,
+) -> &'a mut AutoIndexScopeState {
+    prune_idle_scope_states(cache, now);
+    let key = scope_key(index_scope);
+    if !cache.contains_key(&key) {
+        evict_watchers_over(cache, mcp_max_watchers().saturating_sub(1));
+    }
+    let state = cache
+        .entry(key)
+        .or_insert_with(|| AutoIndexScopeState::new(index_scope));
+    state.last_seen_at = now;
+    state
+}
+
+fn prune_idle_scope_states(cache: &mut HashMap<ScopeKey, AutoIndexScopeState>, now: Instant) {
     let idle_ttl = Duration::from_millis(AUTO_INDEX_SCOPE_IDLE_TTL_MS);
     cache.retain(|_, state| now.duration_since(state.last_seen_at) <= idle_ttl);
 }
 
-fn create_scope_watcher(index_scope: &Path, dirty: Arc<AtomicBool>) -> Option<RecommendedWatcher> {
-    let watch_root = index_scope.to_path_buf();
-    let callback_root = watch_root.clone();
+/// Drop the least recently used watched scopes until at most `limit` remain
+/// watched. Their state goes with the watcher, so the next call there starts
+/// over with a refresh.
+fn evict_watchers_over(cache: &mut HashMap<ScopeKey, AutoIndexScopeState>, limit: usize) {
+    loop {
+        let watched = cache.values().filter(|state| state.has_watcher).count();
+        if watched <= limit {
+            return;
+        }
+        let Some(oldest) = cache
+            .iter()
+            .filter(|(_, state)| state.has_watcher)
+            .min_by_key(|(_, state)| state.last_seen_at)
+            .map(|(key, _)| key.clone())
+        else {
+            return;
+        };
+        cache.remove(&oldest);
+        logging::log(
+            logging::Level::Debug,
+            json!({ "event": "watcher_evicted", "scope": oldest.1, "limit": limit + 1 }),
+        );
+    }
+}
+
+/// Stop the watchers and forget the auto-index state of every session in
+/// an ended `connection`.
+fn release_session_state(connection: u64) {
+    scope_state_cache()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .retain(|(session, _), _| !session.in_connection(connection));
+    failure_cache()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .retain(|(session, _), _| !session.in_connection(connection));
+    roots::release(connection);
+}
+
+fn create_scope_watcher(
+    index_scope: &Path,
+    dirty: Arc<AtomicBool>,
+    limit_reached: Arc<AtomicBool>,
+) -> Option<WatchHandle> {
+    let callback_root = index_scope.to_path_buf();
     let callback_dirty = Arc::clone(&dirty);
     let config = NotifyConfig::default()
         .with_poll_interval(Duration::from_millis(AUTO_INDEX_WATCH_POLL_INTERVAL_MS));
-    let mut watcher = match RecommendedWatcher::new(
-        move |event: Result<Event, notify::Error>| {
-            if let Ok(event) = event {
-                if should_mark_scope_dirty(&callback_root, &event) {
-                    callback_dirty.store(true, Ordering::Release);
-                }
+    let handler = move |event: Result<Event, notify::Error>| match event {
+        Ok(event) => {
+            if should_mark_scope_dirty(&callback_root, &event) {
+                callback_dirty.store(true, Ordering::Release);
             }
-        },
-        config,
-    ) {
-        Ok(watcher) => watcher,
-        Err(_) => return None,
+        }
+        // Directories created past the limit go unwatched; refresh on every
+        // call from now on rather than miss their changes.
+        Err(err) if watch_limits::is_limit_error(&err) => {
+            limit_reached.store(true, Ordering::Release);
+        }
+        Err(_) => {}
     };
-    if watcher
-        .watch(&watch_root, RecursiveMode::Recursive)
-        .is_err()
-    {
-        return None;
+    let watch = watch_limits::watch_recursive(
+        index_scope,
+        handler,
+        config,
+        Duration::from_millis(AUTO_INDEX_WATCH_FALLBACK_POLL_INTERVAL_MS),
+    )
+    .ok()?;
+    if let Some(reason) = watch.fallback.as_deref() {
+        logging::log(
+            logging::Level::Warning,
+            json!({
+                "event": "watcher_polling",
+                "scope": index_scope.display().to_string(),
+                "reason": reason,
+                "hint": watch_limits::RAISE_LIMIT_HINT,
+            }),
+        );
     }
-    Some(watcher)
+    Some(watch)
 }
 
 fn should_mark_scope_dirty(scope_root: &Path, event: &Event) -> bool {
@@ -994,13 +1629,17 @@ fn should_track_auto_index_path(scope_root: &Path, path: &Path) -> bool {
     is_indexable_extension(ext)
 }
 
-fn run_index_for_scope(cwd: Option<&str>, scope: &Path) -> Result<String, String> {
+fn run_index_for_scope(
+    cwd: Option<&str>,
+    scope: &Path,
+    embeddings: &str,
+) -> Result<String, String> {
     let cmd = vec![
         "index".to_string(),
         "-p".to_string(),
         scope.display().to_string(),
         "--embeddings".to_string(),
-        "off".to_string(),
+        embeddings.to_string(),
     ];
     run_cgrep(&cmd, cwd)
 }
@@ -1079,7 +1718,31 @@ or pass profile `fast|agent|human` via `profile`.",
     Ok((mode, profile))
 }
 
-fn run_cgrep(args: &[String], cwd: Option<&str>) -> Result<String, String> {
+pub(crate) fn run_cgrep(args: &[String], cwd: Option<&str>) -> Result<String, String> {
+    run_cgrep_output(args, cwd).map(|output| output.text)
+}
+
+/// Like [`run_cgrep`], but with hit streaming on: each hit the child finds is
+/// sent to `hits` while it runs, and the rest of its stdout is returned.
+pub(crate) fn run_cgrep_streaming(
+    args: &[String],
+    cwd: Option<&str>,
+    hits: mpsc::Sender<Value>,
+) -> Result<String, String> {
+    run_cgrep_child(args, cwd, Some(hits)).map(|output| output.text)
+}
+
+/// Run cgrep as a child process; output is partial when the child exits with
+/// the status of a command that stopped early.
+fn run_cgrep_output(args: &[String], cwd: Option<&str>) -> Result<ToolOutput, String> {
+    run_cgrep_child(args, cwd, None)
+}
+
+fn run_cgrep_child(
+    args: &[String],
+    cwd: Option<&str>,
+    hits: Option<mpsc::Sender<Value>>,
+) -> Result<ToolOutput, String> {
     let exe =
         std::env::current_exe().map_err(|e| format!("failed to resolve executable: {}", e))?;
     let mut command = Command::new(exe);
@@ -1092,6 +1755,15 @@ fn run_cgrep(args: &[String], cwd: Option<&str>) -> Result<String, String> {
     if let Some(cwd) = cwd {
         command.current_dir(cwd);
     }
+    if let Some(profile) = CALL_PROFILE.with(|current| current.borrow().clone()) {
+        command.env(cgrep::config::PROFILE_ENV, profile);
+    }
+    if let Some(traceparent) = cgrep::telemetry::traceparent() {
+        command.env("TRACEPARENT", traceparent);
+    }
+    if hits.is_some() {
+        command.env(crate::query::hits::STREAM_ENV, "1");
+    }
     let mut child = command
         .spawn()
         .map_err(|e| format!("failed to execute cgrep: {}", e))?;
@@ -1103,8 +1775,12 @@ fn run_cgrep(args: &[String], cwd: Option<&str>) -> Result<String, String> {
         .stderr
         .take()
         .ok_or_else(|| "failed to capture cgrep stderr".to_string())?;
-    let max_output_bytes = mcp_tool_max_output_bytes();
-    let stdout_reader = spawn_pipe_reader(stdout, "stdout", max_output_bytes);
+    // Output over the tool limit is paged afterwards; this only bounds memory.
+    let max_output_bytes = mcp_tool_max_output_bytes().max(DEFAULT_MCP_TOOL_MAX_OUTPUT_BYTES);
+    let stdout_reader = match hits {
+        Some(hits) => spawn_hit_reader(stdout, hits, max_output_bytes),
+        None => spawn_pipe_reader(stdout, "stdout", max_output_bytes),
+    };
     let stderr_reader = spawn_pipe_reader(stderr, "stderr", max_output_bytes);
 
     let timeout = mcp_tool_timeout();
@@ -1177,8 +1853,16 @@ fn run_cgrep(args: &[String], cwd: Option<&str>) -> Result<String, String> {
     let stdout = String::from_utf8_lossy(&stdout_bytes).to_string();
     let stderr = String::from_utf8_lossy(&stderr_bytes).to_string();
 
-    if status.success() {
-        Ok(stdout.trim_end().to_string())
+    let partial = matches!(
+        status.code(),
+        Some(cgrep::cancel::TIMED_OUT_EXIT_CODE | cgrep::cancel::INTERRUPTED_EXIT_CODE)
+    );
+    if status.success() || partial {
+        Ok(ToolOutput {
+            text: stdout.trim_end().to_string(),
+            partial,
+            truncation: None,
+        })
     } else {
         let mut msg = String::new();
         if !stderr.trim().is_empty() {
@@ -1213,6 +1897,49 @@ where
     rx
 }
 
+/// Read a streaming child's stdout line by line, forwarding hit lines to
+/// `hits` as they arrive and keeping the rest as the command's output.
+fn spawn_hit_reader<R>(
+    pipe: R,
+    hits: mpsc::Sender<Value>,
+    max_output_bytes: usize,
+) -> mpsc::Receiver<Result<Vec<u8>, String>>
+where
+    R: Read + Send + 'static,
+{
+    let (tx, rx) = mpsc::sync_channel(1);
+    thread::spawn(move || {
+        let mut reader = io::BufReader::new(pipe);
+        let mut out = Vec::new();
+        let mut line = Vec::new();
+        let result = loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => break Ok(out),
+                Ok(_) => {}
+                Err(err) => break Err(format!("failed to read cgrep stdout: {err}")),
+            }
+            if line.starts_with(crate::query::hits::HIT_PREFIX.as_bytes()) {
+                if let Ok(Value::Object(mut hit)) = serde_json::from_slice(&line) {
+                    // A receiver that stopped listening only means nobody wants the hits.
+                    let _ = hits.send(hit.remove("hit").unwrap_or_default());
+                }
+                continue;
+            }
+            if out.len().saturating_add(line.len()) > max_output_bytes {
+                break Err(format!(
+                    "cgrep MCP tool call output exceeded {} bytes. Retry with narrower scope (`path`, `glob`, or `changed`).",
+                    max_output_bytes
+                ));
+            }
+            out.extend_from_slice(&line);
+        };
+        drop(hits);
+        let _ = tx.send(result);
+    });
+    rx
+}
+
 fn read_pipe_with_limit<R: Read>(
     pipe: &mut R,
     stream: &'static str,
@@ -1278,6 +2005,23 @@ fn output_drain_wait(timeout: Duration, started_at: Instant) -> Duration {
     remaining.max(Duration::from_millis(MIN_PIPE_DRAIN_WAIT_MS))
 }
 
+/// Concurrent `tools/call` workers (`CGREP_MCP_WORKERS`, at least one).
+fn mcp_worker_count() -> usize {
+    std::env::var("CGREP_MCP_WORKERS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MCP_WORKERS)
+        .max(1)
+}
+
+fn mcp_max_watchers() -> usize {
+    std::env::var("CGREP_MCP_MAX_WATCHERS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MCP_MAX_WATCHERS)
+        .max(1)
+}
+
 fn mcp_tool_timeout() -> Duration {
     let timeout_ms = std::env::var("CGREP_MCP_TOOL_TIMEOUT_MS")
         .ok()
@@ -1286,6 +2030,14 @@ fn mcp_tool_timeout() -> Duration {
     Duration::from_millis(timeout_ms)
 }
 
+/// `--timeout-ms` for tools that can stop early: the requested deadline,
+/// capped so the child prints what it has before the tool timeout kills it.
+fn partial_result_deadline_ms(requested: Option<u64>) -> u64 {
+    let budget = mcp_tool_timeout().as_millis() as u64;
+    let cap = budget - (budget / 4).min(PARTIAL_RESULT_RESERVE_MS);
+    requested.map_or(cap, |ms| ms.min(cap))
+}
+
 fn mcp_tool_max_output_bytes() -> usize {
     std::env::var("CGREP_MCP_TOOL_MAX_OUTPUT_BYTES")
         .ok()
@@ -1295,7 +2047,7 @@ fn mcp_tool_max_output_bytes() -> usize {
 }
 
 fn tool_definitions() -> Vec<Value> {
-    vec![
+    let mut tools = vec![
         json!({
             "name": "cgrep_search",
             "description": "Full-text code search with deterministic JSON2 output.",
@@ -1307,11 +2059,16 @@ fn tool_definitions() -> Vec<Value> {
                     "path": { "type": "string", "description": "Optional scope root for this search." },
                     "cwd": { "type": "string", "description": "Working directory used to resolve relative paths." },
                     "limit": { "type": "number" },
-                    "context": { "type": "number" },
+                    "context": {
+                        "oneOf": [
+                            { "type": "number" },
+                            { "type": "string", "enum": ["symbol-header"] }
+                        ]
+                    },
                     "file_type": { "type": "string" },
                     "glob": { "type": "string" },
                     "exclude": { "type": "string" },
-                    "budget": { "type": "string", "enum": ["tight", "balanced", "full", "off"] },
+                    "budget": { "type": "string", "description": "`tight|balanced|full|off`, or a `[budgets.<name>]` preset from config." },
                     "max_total_chars": { "type": "number" },
                     "max_chars_per_snippet": { "type": "number" },
                     "max_context_chars": { "type": "number" },
@@ -1320,6 +2077,9 @@ fn tool_definitions() -> Vec<Value> {
                     "suppress_boilerplate": { "type": "boolean" },
                     "auto_index": { "type": "boolean" },
                     "changed": { "oneOf": [{ "type": "boolean" }, { "type": "string" }] },
+                    "only_dirty": { "type": "boolean", "description": "Only files with uncommitted changes (staged, unstaged, or untracked)." },
+                    "modified_since": { "type": "string", "description": "Only files committed to since this date (`YYYY-MM-DD`) or age (`30d`, `2w`)." },
+                    "author": { "type": "string", "description": "Only files with a commit author whose name or email contains this text." },
                     "mode": { "type": "string", "description": "Search mode (`keyword|semantic|hybrid`). Legacy aliases `fast|quick|agent|ai|human|user` are treated as profiles." },
                     "profile": { "type": "string", "description": "Search profile (`fast|quick|agent|ai|human|user`)." },
                     "regex": { "type": "boolean" },
@@ -1327,8 +2087,19 @@ fn tool_definitions() -> Vec<Value> {
                     "no_index": { "type": "boolean" },
                     "no_recursive": { "type": "boolean" },
                     "no_ignore": { "type": "boolean" },
+                    "hidden": { "type": "boolean" },
+                    "no_ignore_vcs": { "type": "boolean" },
+                    "no_ignore_global": { "type": "boolean" },
                     "quiet": { "type": "boolean" },
-                    "fuzzy": { "type": "boolean" }
+                    "fuzzy": { "type": "boolean" },
+                    "search_docs": { "type": "boolean", "description": "Match only symbol doc comments (rustdoc, docstrings, JSDoc, Javadoc)." },
+                    "diversify": { "type": "boolean", "description": "Cap results per directory and interleave across directories." },
+                    "cluster": { "type": "boolean", "description": "Group results into labeled clusters with a representative snippet each." },
+                    "summarize_files": { "type": "boolean", "description": "Return one entry per file (match count, symbols, merged line ranges) instead of individual matches." },
+                    "require_fresh": { "type": "boolean", "description": "Reindex first if the index is stale; fail if it cannot be refreshed." },
+                    "no_fallback": { "type": "boolean", "description": "Fail instead of silently scanning when the index cannot answer; json2 `meta.fallback_reason` says why a search scanned." },
+                    "verify": { "type": "boolean", "description": "Hash the top result files against the index; results from files changed since indexing get `stale: true` and are listed in json2 `meta.stale_files`." },
+                    "timeout_ms": { "type": "number", "description": "Stop retrieval after this many milliseconds (default: just under the tool timeout); partial results set meta.timed_out." }
                 }
             }
         }),
@@ -1346,7 +2117,7 @@ fn tool_definitions() -> Vec<Value> {
                     "changed": { "oneOf": [{ "type": "boolean" }, { "type": "string" }] },
                     "limit": { "type": "number" },
                     "mode": { "type": "string", "enum": ["keyword", "semantic", "hybrid"] },
-                    "budget": { "type": "string", "enum": ["tight", "balanced", "full", "off"] }
+                    "budget": { "type": "string", "description": "`tight|balanced|full|off`, or a `[budgets.<name>]` preset from config." }
                 }
             }
         }),
@@ -1383,7 +2154,8 @@ fn tool_definitions() -> Vec<Value> {
                     "section": { "type": "string", "description": "Line range (`start-end`) or heading text. Numeric `start:end` is also accepted." },
                     "section_start": { "type": "number", "description": "Optional start line number for range reads (use with section_end)." },
                     "section_end": { "type": "number", "description": "Optional end line number for range reads (use with section_start)." },
-                    "full": { "type": "boolean" }
+                    "full": { "type": "boolean" },
+                    "expand": { "type": "string", "description": "Inline one symbol's body into the nested outline (name or `Outer.inner`)." }
                 }
             }
         }),
@@ -1404,9 +2176,15 @@ fn tool_definitions() -> Vec<Value> {
             "description": "Find symbols by name and optional filters.",
             "inputSchema": {
                 "type": "object",
-                "required": ["name"],
+                "anyOf": [
+                    { "required": ["name"] },
+                    { "required": ["names"] },
+                    { "required": ["decorated_with"] }
+                ],
                 "properties": {
                     "name": { "type": "string" },
+                    "names": { "type": "array", "items": { "type": "string" }, "description": "Optional batched names resolved in one pass; the result is an object keyed by requested name." },
+                    "decorated_with": { "type": "string", "description": "Only symbols with this decorator, attribute, or annotation (`pytest.fixture`, `tokio::main`, `Override`); matches the full name or its last path segments. Without `name`, lists every decorated symbol." },
                     "cwd": { "type": "string" },
                     "auto_index": { "type": "boolean" },
                     "symbol_type": { "type": "string" },
@@ -1415,6 +2193,11 @@ fn tool_definitions() -> Vec<Value> {
                     "glob": { "type": "string" },
                     "exclude": { "type": "string" },
                     "changed": { "oneOf": [{ "type": "boolean" }, { "type": "string" }] },
+                    "only_dirty": { "type": "boolean", "description": "Only files with uncommitted changes (staged, unstaged, or untracked)." },
+                    "sort": { "type": "string", "enum": ["score", "name", "path", "kind"], "description": "Result order before offset/limit (default: path)." },
+                    "offset": { "type": "number", "description": "Skip this many results (per name when batching)." },
+                    "limit": { "type": "number", "description": "Maximum results (per name when batching); page with offset += limit." },
+                    "timeout_ms": { "type": "number", "description": "Stop after this many milliseconds (default: just under the tool timeout) and return the symbols found so far." },
                     "quiet": { "type": "boolean" }
                 }
             }
@@ -1451,6 +2234,35 @@ fn tool_definitions() -> Vec<Value> {
                 }
             }
         }),
+        json!({
+            "name": "cgrep_rename_check",
+            "description": "Before renaming a symbol: list every reference to `old` with its scope, and flag where `new` already exists (collision, shadowing, or a definition elsewhere). `safe` is true when nothing collides or shadows.",
+            "inputSchema": {
+                "type": "object",
+                "required": ["old", "new"],
+                "properties": {
+                    "old": { "type": "string" },
+                    "new": { "type": "string" },
+                    "cwd": { "type": "string" },
+                    "path": { "type": "string" },
+                    "auto_index": { "type": "boolean" }
+                }
+            }
+        }),
+        json!({
+            "name": "cgrep_review",
+            "description": "Review a diff: for each hunk of `range` (`base..head`, or a revision compared with the working tree; default HEAD), list the symbols it touches with their definitions and top callers.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "range": { "type": "string" },
+                    "cwd": { "type": "string" },
+                    "path": { "type": "string" },
+                    "callers": { "type": "number" },
+                    "auto_index": { "type": "boolean" }
+                }
+            }
+        }),
         json!({
             "name": "cgrep_callers",
             "description": "Find call sites for a function.",
@@ -1467,17 +2279,53 @@ fn tool_definitions() -> Vec<Value> {
         }),
         json!({
             "name": "cgrep_dependents",
-            "description": "Find files depending on a target file.",
+            "description": "Find files depending on a target file. With `transitive`, follows dependents of dependents up to `depth` levels and reports each chain and any import cycles.",
             "inputSchema": {
                 "type": "object",
                 "required": ["file"],
                 "properties": {
                     "file": { "type": "string" },
+                    "transitive": { "type": "boolean" },
+                    "depth": { "type": "number" },
                     "cwd": { "type": "string" },
                     "auto_index": { "type": "boolean" }
                 }
             }
         }),
+        json!({
+            "name": "cgrep_imports",
+            "description": "List what a file imports: workspace files (internal) and packages (external).",
+            "inputSchema": {
+                "type": "object",
+                "required": ["file"],
+                "properties": {
+                    "file": { "type": "string" },
+                    "cwd": { "type": "string" }
+                }
+            }
+        }),
+        json!({
+            "name": "cgrep_status",
+            "description": "Index health: readiness, file and document counts, last build timing, watcher state, and a suggested next step (`ready`, `run_cgrep_index`, or `wait_for_build`). Without an index, search tools fall back to scanning.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "cwd": { "type": "string" }
+                }
+            }
+        }),
+        json!({
+            "name": "cgrep_set_root",
+            "description": "Set the session's default root: calls without `cwd` run there until it is set again. Must stay within the client's workspace roots when the client reports any. Returns the new and previous root.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Project directory, absolute or relative to the current default root." }
+                },
+                "required": ["path"]
+            }
+        }),
         json!({
             "name": "cgrep_index",
             "description": "Build or refresh the local cgrep index.",
@@ -1501,10 +2349,39 @@ fn tool_definitions() -> Vec<Value> {
                 }
             }
         }),
-    ]
+    ];
+    for tool in &mut tools {
+        let cacheable = tool["name"]
+            .as_str()
+            .is_some_and(result_cache::is_cacheable);
+        if let Some(properties) = tool.pointer_mut("/inputSchema/properties") {
+            if properties.get("profile").is_none() {
+                properties["profile"] = json!({
+                    "type": "string",
+                    "description": "Config profile whose defaults apply to this call (same as `CGREP_PROFILE`)."
+                });
+            }
+            properties[paging::CONTINUATION_ARG] = json!({
+                "type": "string",
+                "description": "Token from a truncated result; repeat the same arguments with it to get the next chunk."
+            });
+            if cacheable {
+                properties[result_cache::NO_CACHE_ARG] = json!({
+                    "type": "boolean",
+                    "description": "Run the call even if an identical one was answered moments ago."
+                });
+            }
+        }
+    }
+    tools
 }
 
-fn write_error(w: &mut impl Write, id: Option<Value>, code: i32, message: &str) -> io::Result<()> {
+fn write_error(
+    stdout: &Mutex<io::Stdout>,
+    id: Option<Value>,
+    code: i32,
+    message: &str,
+) -> io::Result<()> {
     let resp = JsonRpcResponse {
         jsonrpc: "2.0",
         id,
@@ -1514,7 +2391,5 @@ fn write_error(w: &mut impl Write, id: Option<Value>, code: i32, message: &str)
             message: message.to_string(),
         }),
     };
-    serde_json::to_writer(&mut *w, &resp)?;
-    w.write_all(b"\n")?;
-    w.flush()
+    write_response(stdout, &resp)
 }
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Paging for tool output over `CGREP_MCP_TOOL_MAX_OUTPUT_BYTES`.
+//!
+//! Oversized JSON output keeps the leading entries of its result list that
+//! fit and hands back a continuation token. Repeating the call with the same
+//! arguments plus `continuation` returns the next chunk: cgrep output is
+//! deterministic, so the server reruns the command and skips the entries
+//! already returned. The token is bound to the tool and its arguments.
+
+use serde_json::Value;
+
+/// Tool argument carrying a token from an earlier truncated call.
+pub(super) const CONTINUATION_ARG: &str = "continuation";
+
+/// Output cut short at a result boundary.
+pub(super) struct Truncation {
+    /// Pass as `continuation` to fetch the entries after this chunk.
+    pub token: String,
+    /// Entries in this chunk.
+    pub returned: usize,
+    /// Entries from this chunk to the end of the list.
+    pub remaining: usize,
+}
+
+/// Index of the first entry to return, from the call's `continuation`.
+pub(super) fn start_offset(tool: &str, args: &Value) -> Result<usize, String> {
+    let Some(token) = args.get(CONTINUATION_ARG).and_then(Value::as_str) else {
+        return Ok(0);
+    };
+    token
+        .split_once(':')
+        .filter(|(_, fingerprint)| *fingerprint == call_fingerprint(tool, args))
+        .and_then(|(offset, _)| offset.parse::<usize>().ok())
+        .ok_or_else(|| {
+            format!(
+                "{tool}: continuation token does not match these arguments. \
+Repeat the call that returned it with the same arguments, or drop `continuation`."
+            )
+        })
+}
+
+/// Skip `offset` entries of `text`'s result list, then keep as many as fit
+/// in `max_bytes`. Output that is not a JSON list (or an object holding one)
+/// is returned whole when it fits and refused otherwise.
+pub(super) fn paginate(
+    tool: &str,
+    args: &Value,
+    text: String,
+    offset: usize,
+    max_bytes: usize,
+) -> Result<(String, Option<Truncation>), String> {
+    if offset == 0 && text.len() <= max_bytes {
+        return Ok((text, None));
+    }
+    let too_large = || {
+        format!(
+            "cgrep MCP tool call output exceeded {max_bytes} bytes. Retry with narrower scope (`path`, `glob`, or `changed`)."
+        )
+    };
+    let Ok(mut payload) = serde_json::from_str::<Value>(&text) else {
+        return Err(too_large());
+    };
+    let Some(pointer) = result_list_pointer(&payload) else {
+        return Err(too_large());
+    };
+    let mut rest = match payload.pointer_mut(&pointer) {
+        Some(Value::Array(entries)) => std::mem::take(entries),
+        _ => Vec::new(),
+    };
+    rest.drain(..offset.min(rest.len()));
+    let remaining = rest.len();
+
+    // Bytes of the payload around an empty list, plus each entry and its comma.
+    let frame = serde_json::to_string(&payload).map_err(|err| err.to_string())?;
+    let mut used = frame.len();
+    let mut fits = 0;
+    for (idx, entry) in rest.iter().enumerate() {
+        let size = serde_json::to_string(entry).map_or(usize::MAX, |entry| entry.len());
+        let size = size.saturating_add(usize::from(idx > 0));
+        if used.saturating_add(size) > max_bytes {
+            break;
+        }
+        used += size;
+        fits += 1;
+    }
+    if fits == 0 && remaining > 0 {
+        return Err(too_large());
+    }
+
+    rest.truncate(fits);
+    if let Some(slot) = payload.pointer_mut(&pointer) {
+        *slot = Value::Array(rest);
+    }
+    let text = serde_json::to_string(&payload).map_err(|err| err.to_string())?;
+    let truncation = (fits < remaining).then(|| Truncation {
+        token: format!("{}:{}", offset + fits, call_fingerprint(tool, args)),
+        returned: fits,
+        remaining,
+    });
+    Ok((text, truncation))
+}
+
+/// JSON pointer to the list being paged: the payload itself, or its largest
+/// array field.
+fn result_list_pointer(payload: &Value) -> Option<String> {
+    match payload {
+        Value::Array(_) => Some(String::new()),
+        Value::Object(map) => map
+            .iter()
+            .filter(|(_, value)| value.is_array())
+            .max_by_key(|(_, value)| value.to_string().len())
+            .map(|(key, _)| format!("/{}", key.replace('~', "~0").replace('/', "~1"))),
+        _ => None,
+    }
+}
+
+/// Short digest of the tool and its arguments other than `continuation`.
+fn call_fingerprint(tool: &str, args: &Value) -> String {
+    let mut args = args.clone();
+    if let Value::Object(map) = &mut args {
+        map.remove(CONTINUATION_ARG);
+    }
+    let key = format!("{tool}\n{args}");
+    blake3::hash(key.as_bytes()).to_hex()[..16].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn oversized_lists_page_through_every_entry() {
+        let args = json!({ "path": "src" });
+        let text = json!({
+            "depth": 2,
+            "entries": (0..20).map(|idx| format!("entry-{idx:02}")).collect::<Vec<_>>()
+        })
+        .to_string();
+
+        let mut offset = 0;
+        let mut seen = Vec::new();
+        loop {
+            let (page, truncation) =
+                paginate("cgrep_map", &args, text.clone(), offset, 120).expect("page");
+            assert!(page.len() <= 120, "{page}");
+            let page: Value = serde_json::from_str(&page).expect("json");
+            assert_eq!(page["depth"], 2);
+            seen.extend(page["entries"].as_array().expect("entries").clone());
+            let Some(truncation) = truncation else {
+                break;
+            };
+            let next = json!({ "path": "src", "continuation": truncation.token });
+            offset = start_offset("cgrep_map", &next).expect("token");
+        }
+        assert_eq!(seen.len(), 20);
+        assert_eq!(seen[19], "entry-19");
+    }
+
+    #[test]
+    fn tokens_are_bound_to_the_original_arguments() {
+        let args = json!({ "path": "src" });
+        let text = json!((0..50).collect::<Vec<_>>()).to_string();
+        let (_, truncation) = paginate("cgrep_map", &args, text, 0, 40).expect("page");
+        let token = truncation.expect("truncated").token;
+
+        let other = json!({ "path": "tests", "continuation": token });
+        assert!(start_offset("cgrep_map", &other).is_err());
+        assert!(start_offset(
+            "cgrep_search",
+            &json!({ "path": "src", "continuation": token })
+        )
+        .is_err());
+        assert!(paginate("cgrep_read", &args, "x".repeat(50), 0, 40).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! In-memory cache of tool results for repeated calls within a session.
+//!
+//! Agents often retry a call verbatim. A result is reused while the tool,
+//! its arguments, and the index fingerprint all match and the entry is
+//! younger than `CGREP_MCP_CACHE_TTL_MS`. The server skips the cache for
+//! scopes with unindexed file changes and for calls passing `no_cache`.
+//! Output read from files rather than the index (`cgrep_read`, scan-mode
+//! searches) is never cached: the index fingerprint says nothing about it.
+
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use cgrep::utils::INDEX_DIR;
+
+use super::paging::CONTINUATION_ARG;
+
+/// Tool argument that bypasses the cache for one call.
+pub(super) const NO_CACHE_ARG: &str = "no_cache";
+
+const DEFAULT_TTL_MS: u64 = 30_000;
+const CAPACITY: usize = 64;
+
+/// Read-only tools whose output depends only on arguments and the index.
+const CACHEABLE_TOOLS: &[&str] = &[
+    "cgrep_search",
+    "cgrep_agent_locate",
+    "cgrep_agent_expand",
+    "cgrep_map",
+    "cgrep_symbols",
+    "cgrep_definition",
+    "cgrep_references",
+    "cgrep_callers",
+    "cgrep_dependents",
+];
+
+struct Entry {
+    key: String,
+    stored_at: Instant,
+    text: String,
+}
+
+/// Least recently used first.
+static ENTRIES: Mutex<VecDeque<Entry>> = Mutex::new(VecDeque::new());
+
+pub(super) fn is_cacheable(tool: &str) -> bool {
+    CACHEABLE_TOOLS.contains(&tool)
+}
+
+/// Whether a call's output came from scanning files, per its json2
+/// `meta.index_mode`.
+pub(super) fn is_scan_output(text: &str) -> bool {
+    serde_json::from_str::<Value>(text).is_ok_and(|payload| {
+        payload
+            .get("meta")
+            .and_then(|meta| meta.get("index_mode"))
+            .and_then(Value::as_str)
+            == Some("scan")
+    })
+}
+
+/// Cache key for `tool` with `args` against the index under `index_root`,
+/// or `None` when the index has no readable metadata.
+pub(super) fn key(tool: &str, args: &Value, index_root: &Path) -> Option<String> {
+    let mut args = args.clone();
+    if let Value::Object(map) = &mut args {
+        // Pages of one result share its entry.
+        map.remove(CONTINUATION_ARG);
+        map.remove(NO_CACHE_ARG);
+    }
+    let fingerprint = index_fingerprint(index_root)?;
+    let key = format!("{tool}\n{args}\n{}\n{fingerprint}", index_root.display());
+    Some(blake3::hash(key.as_bytes()).to_hex().to_string())
+}
+
+/// Index files change on every update, so their sizes and mtimes identify
+/// the index contents without reading them.
+fn index_fingerprint(index_root: &Path) -> Option<String> {
+    let index_dir = index_root.join(INDEX_DIR);
+    let mut parts = Vec::new();
+    for name in ["metadata.json", "meta.json"] {
+        let metadata = std::fs::metadata(index_dir.join(name)).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+        parts.push(format!("{}:{modified}", metadata.len()));
+    }
+    Some(parts.join(","))
+}
+
+fn ttl() -> Duration {
+    let ttl_ms = std::env::var("CGREP_MCP_CACHE_TTL_MS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_TTL_MS);
+    Duration::from_millis(ttl_ms)
+}
+
+/// Cached text for `key` if still fresh.
+pub(super) fn get(key: &str) -> Option<String> {
+    let ttl = ttl();
+    let mut entries = ENTRIES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    entries.retain(|entry| entry.stored_at.elapsed() < ttl);
+    let idx = entries.iter().position(|entry| entry.key == key)?;
+    let entry = entries.remove(idx)?;
+    let text = entry.text.clone();
+    entries.push_back(entry);
+    Some(text)
+}
+
+pub(super) fn put(key: String, text: String) {
+    if ttl().is_zero() {
+        return;
+    }
+    let mut entries = ENTRIES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    entries.retain(|entry| entry.key != key);
+    if entries.len() >= CAPACITY {
+        entries.pop_front();
+    }
+    entries.push_back(Entry {
+        key,
+        stored_at: Instant::now(),
+        text,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keys_ignore_paging_and_follow_the_index() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let root = dir.path();
+        assert_eq!(key("cgrep_search", &json!({}), root), None);
+
+        std::fs::create_dir_all(root.join(INDEX_DIR)).expect("mkdir");
+        std::fs::write(root.join(INDEX_DIR).join("meta.json"), "{}").expect("meta");
+        std::fs::write(root.join(INDEX_DIR).join("metadata.json"), "{}").expect("metadata");
+        let args = json!({ "query": "x" });
+        let first = key("cgrep_search", &args, root).expect("key");
+        let paged = json!({ "query": "x", "continuation": "3:abc", "no_cache": false });
+        assert_eq!(key("cgrep_search", &paged, root), Some(first.clone()));
+        assert_ne!(key("cgrep_map", &args, root), Some(first.clone()));
+
+        std::fs::write(root.join(INDEX_DIR).join("metadata.json"), "{\"files\":{}}")
+            .expect("metadata");
+        assert_ne!(key("cgrep_search", &args, root), Some(first));
+    }
+
+    #[test]
+    fn file_reads_and_scans_are_not_cached() {
+        assert!(!is_cacheable("cgrep_read"));
+        assert!(is_cacheable("cgrep_search"));
+        assert!(is_scan_output(
+            r#"{"meta":{"index_mode":"scan"},"results":[]}"#
+        ));
+        assert!(!is_scan_output(
+            r#"{"meta":{"index_mode":"index"},"results":[]}"#
+        ));
+        assert!(!is_scan_output("plain text"));
+    }
+
+    #[test]
+    fn entries_are_returned_until_evicted() {
+        put("cache-test-a".to_string(), "a".to_string());
+        assert_eq!(get("cache-test-a").as_deref(), Some("a"));
+        for idx in 0..CAPACITY {
+            put(format!("cache-test-fill-{idx}"), String::new());
+        }
+        assert_eq!(get("cache-test-a"), None);
+    }
+}
@@ -0,0 +1,491 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! MCP `roots`: workspace folders reported by the client, and the session's
+//! default root.
+//!
+//! When the client declares the capability at `initialize`, the server asks
+//! for `roots/list` once the session is initialized and again on
+//! `notifications/roots/list_changed`. Tool calls without `cwd` then run in a
+//! root instead of the server's own working directory, and `cwd` or absolute
+//! paths outside every root are refused.
+//!
+//! A default root takes precedence over the roots when picking `cwd`. It is
+//! set by `cgrep_set_root`, or at `initialize` from `_meta."cgrep/root"`,
+//! from `[mcp] client_roots` for the client's `clientInfo.name`, or from
+//! `[mcp] default_root`, in that order.
+//!
+//! Both are kept per [`Session`]. Tagged sessions share their connection's
+//! roots and its `initialize` default root, but `cgrep_set_root` only moves
+//! the default root of the session that calls it.
+
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use super::session::{self, Session};
+
+/// Prefix of ids for `roots/list` requests sent by the server.
+const REQUEST_ID_PREFIX: &str = "cgrep-roots-";
+/// Tool that sets the default root; it runs unscoped.
+const SET_ROOT_TOOL: &str = "cgrep_set_root";
+/// `initialize` `_meta` key naming the session's default root.
+const HANDSHAKE_KEY: &str = "cgrep/root";
+
+static SUPPORTED: AtomicBool = AtomicBool::new(false);
+static NEXT_REQUEST: AtomicU64 = AtomicU64::new(1);
+static SESSION_SCOPES: OnceLock<Mutex<HashMap<Session, SessionScope>>> = OnceLock::new();
+/// `[mcp] cwd_fallback`: calls may run in the server's working directory.
+static CWD_FALLBACK: AtomicBool = AtomicBool::new(true);
+
+/// Where the session's default root came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RootSource {
+    SetRoot,
+    Handshake,
+    ClientConfig,
+    Config,
+}
+
+impl RootSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            RootSource::SetRoot => SET_ROOT_TOOL,
+            RootSource::Handshake => "initialize",
+            RootSource::ClientConfig => "client_roots",
+            RootSource::Config => "default_root",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DefaultRoot {
+    path: PathBuf,
+    source: RootSource,
+}
+
+/// The roots and default root a session runs tool calls in.
+#[derive(Debug, Clone, Default)]
+struct SessionScope {
+    /// `None` until the client answers; an empty list imposes no bounds.
+    roots: Option<Vec<PathBuf>>,
+    default_root: Option<DefaultRoot>,
+}
+
+fn session_scopes() -> MutexGuard<'static, HashMap<Session, SessionScope>> {
+    SESSION_SCOPES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Scope of the current session, falling back to its connection's roots and
+/// to a default root the connection did not get from `cgrep_set_root`.
+fn current_scope() -> SessionScope {
+    let session = session::current();
+    let scopes = session_scopes();
+    let own = scopes.get(&session).cloned().unwrap_or_default();
+    let connection = scopes.get(&session.connection_session());
+    SessionScope {
+        roots: own
+            .roots
+            .or_else(|| connection.and_then(|scope| scope.roots.clone())),
+        default_root: own.default_root.or_else(|| {
+            connection
+                .and_then(|scope| scope.default_root.clone())
+                .filter(|root| root.source != RootSource::SetRoot)
+        }),
+    }
+}
+
+/// Forget the roots of every session of `connection`.
+pub(super) fn release(connection: u64) {
+    session_scopes().retain(|session, _| !session.in_connection(connection));
+}
+
+/// Record what the client's `initialize` params say about scope: whether it
+/// supports `roots`, and the default root from the handshake or `[mcp]`
+/// config. Candidates that are not directories are skipped.
+pub(super) fn note_initialize(params: &Value) {
+    let supported = params
+        .get("capabilities")
+        .and_then(|capabilities| capabilities.get("roots"))
+        .is_some_and(Value::is_object);
+    SUPPORTED.store(supported, Ordering::SeqCst);
+
+    let server_cwd = std::env::current_dir().ok();
+    let base = server_cwd.clone().unwrap_or_default();
+    let config = cgrep::config::Config::load_for_dir(&base);
+    let mcp = config.mcp();
+    CWD_FALLBACK.store(mcp.cwd_fallback(), Ordering::SeqCst);
+
+    let handshake = params
+        .get("_meta")
+        .and_then(|meta| meta.get(HANDSHAKE_KEY))
+        .and_then(Value::as_str);
+    let client_root = params
+        .get("clientInfo")
+        .and_then(|info| info.get("name"))
+        .and_then(Value::as_str)
+        .and_then(|name| mcp.client_roots.get(name));
+    let candidates = [
+        (handshake, RootSource::Handshake),
+        (client_root.map(String::as_str), RootSource::ClientConfig),
+        (mcp.default_root.as_deref(), RootSource::Config),
+    ];
+    let default_root = candidates.into_iter().find_map(|(raw, source)| {
+        let path = resolve_root(raw?, &base)?;
+        Some(DefaultRoot { path, source })
+    });
+    session_scopes()
+        .entry(session::current())
+        .or_default()
+        .default_root = default_root;
+}
+
+/// The session's default root and its source, for `initialize` `_meta`.
+pub(super) fn default_root_meta() -> Option<Value> {
+    current_scope().default_root.map(|root| {
+        json!({
+            "path": root.path.display().to_string(),
+            "source": root.source.as_str(),
+        })
+    })
+}
+
+/// The session's default root, if one is set.
+pub(super) fn default_root() -> Option<PathBuf> {
+    current_scope().default_root.map(|root| root.path)
+}
+
+/// `cgrep_set_root`: make `path` the default scope of calls without `cwd`.
+/// A relative path resolves against the current default root. Returns the
+/// new root and a JSON summary.
+pub(super) fn set_root(args: &Value) -> Result<(PathBuf, String), String> {
+    let raw = args
+        .get("path")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|raw| !raw.is_empty())
+        .ok_or_else(|| format!("{SET_ROOT_TOOL}: missing `path`"))?;
+    let scope = current_scope();
+    let previous = scope.default_root.map(|root| root.path);
+    let base = match &previous {
+        Some(root) => root.clone(),
+        None => std::env::current_dir()
+            .map_err(|err| format!("{SET_ROOT_TOOL}: failed to resolve server cwd: {err}"))?,
+    };
+    let path = resolve_root(raw, &base)
+        .ok_or_else(|| format!("{SET_ROOT_TOOL}: {raw} is not a directory"))?;
+    if let Some(roots) = scope.roots.as_deref().filter(|roots| !roots.is_empty()) {
+        if !within_roots(&path, roots) {
+            return Err(outside_roots_error(SET_ROOT_TOOL, raw, roots));
+        }
+    }
+    session_scopes()
+        .entry(session::current())
+        .or_default()
+        .default_root = Some(DefaultRoot {
+        path: path.clone(),
+        source: RootSource::SetRoot,
+    });
+    let summary = json!({
+        "root": path.display().to_string(),
+        "previous": previous.map(|root| root.display().to_string()),
+    });
+    Ok((path, summary.to_string()))
+}
+
+/// A `roots/list` request to send the client, if it supports roots.
+pub(super) fn list_request() -> Option<Value> {
+    if !SUPPORTED.load(Ordering::SeqCst) {
+        return None;
+    }
+    let seq = NEXT_REQUEST.fetch_add(1, Ordering::SeqCst);
+    Some(json!({
+        "jsonrpc": "2.0",
+        "id": format!("{REQUEST_ID_PREFIX}{seq}"),
+        "method": "roots/list"
+    }))
+}
+
+/// Whether `id` belongs to a `roots/list` request from [`list_request`].
+pub(super) fn is_list_response(id: &Value) -> bool {
+    id.as_str()
+        .is_some_and(|id| id.starts_with(REQUEST_ID_PREFIX))
+}
+
+/// Store the roots in a `roots/list` result and return those not seen
+/// before. An error result leaves the current roots unchanged.
+pub(super) fn update(result: Option<&Value>) -> Vec<PathBuf> {
+    let Some(entries) = result
+        .and_then(|result| result.get("roots"))
+        .and_then(Value::as_array)
+    else {
+        return Vec::new();
+    };
+    let roots: Vec<PathBuf> = entries
+        .iter()
+        .filter_map(|root| root.get("uri").and_then(Value::as_str))
+        .filter_map(path_from_file_uri)
+        .map(|path| cgrep::paths::canonicalize(&path).unwrap_or(path))
+        .collect();
+    let mut scopes = session_scopes();
+    let current = &mut scopes.entry(session::current()).or_default().roots;
+    let known = current.take().unwrap_or_default();
+    let added = roots
+        .iter()
+        .filter(|root| !known.contains(root))
+        .cloned()
+        .collect();
+    *current = Some(roots);
+    added
+}
+
+/// Tool arguments scoped for the session: `cwd` defaults to the default
+/// root, else to a client root, and `cwd` or absolute paths outside every
+/// root are refused. With `[mcp] cwd_fallback = false`, a call that would
+/// still run in the server's working directory is refused.
+pub(super) fn scope_arguments<'a>(tool: &str, args: &'a Value) -> Result<Cow<'a, Value>, String> {
+    if tool == SET_ROOT_TOOL {
+        return Ok(Cow::Borrowed(args));
+    }
+    let scope = current_scope();
+    let args = match scope.default_root.filter(|_| !has_cwd(args)) {
+        Some(root) => Cow::Owned(with_cwd(args, &root.path)),
+        None => Cow::Borrowed(args),
+    };
+    let args = match scope.roots.as_deref() {
+        Some(roots) if !roots.is_empty() => bound_to_roots(tool, args, roots)?,
+        _ => args,
+    };
+    if !has_cwd(&args) && !CWD_FALLBACK.load(Ordering::SeqCst) {
+        return Err(format!(
+            "{tool}: no `cwd` given and no workspace root is known ([mcp] cwd_fallback = false); pass `cwd` or call cgrep_set_root first"
+        ));
+    }
+    Ok(args)
+}
+
+fn bound_to_roots<'a>(
+    tool: &str,
+    args: Cow<'a, Value>,
+    roots: &[PathBuf],
+) -> Result<Cow<'a, Value>, String> {
+    let cwd = args
+        .get("cwd")
+        .and_then(Value::as_str)
+        .filter(|cwd| !cwd.trim().is_empty());
+    let mut checked: Vec<&str> = cwd.into_iter().collect();
+    checked.extend(args.get("path").and_then(Value::as_str));
+    if let Some(paths) = args.get("paths").and_then(Value::as_array) {
+        checked.extend(paths.iter().filter_map(Value::as_str));
+    }
+    for raw in checked {
+        let path = Path::new(raw);
+        if path.is_absolute() && !within_roots(path, roots) {
+            return Err(outside_roots_error(tool, raw, roots));
+        }
+    }
+    if cwd.is_some() {
+        return Ok(args);
+    }
+
+    // Prefer the root holding the server's own cwd, then the first one.
+    let server_cwd = std::env::current_dir()
+        .ok()
+        .map(|dir| cgrep::paths::canonicalize(&dir).unwrap_or(dir));
+    let root = server_cwd
+        .and_then(|dir| roots.iter().find(|root| dir.starts_with(root)))
+        .unwrap_or(&roots[0]);
+    Ok(Cow::Owned(with_cwd(&args, root)))
+}
+
+fn has_cwd(args: &Value) -> bool {
+    args.get("cwd")
+        .and_then(Value::as_str)
+        .is_some_and(|cwd| !cwd.trim().is_empty())
+}
+
+fn with_cwd(args: &Value, root: &Path) -> Value {
+    let mut scoped = match args {
+        Value::Object(map) => map.clone(),
+        _ => serde_json::Map::new(),
+    };
+    scoped.insert("cwd".to_string(), json!(root.display().to_string()));
+    Value::Object(scoped)
+}
+
+fn outside_roots_error(tool: &str, raw: &str, roots: &[PathBuf]) -> String {
+    format!(
+        "{tool}: {raw} is outside the client's workspace roots ({})",
+        roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Directory named by a path or `file://` URI; `~/` is the home directory
+/// and relative paths resolve against `base`.
+fn resolve_root(raw: &str, base: &Path) -> Option<PathBuf> {
+    let raw = raw.trim();
+    let path = if raw.starts_with("file://") {
+        path_from_file_uri(raw)?
+    } else if let Some(rest) = raw.strip_prefix("~/").or(raw.strip_prefix("~\\")) {
+        dirs::home_dir()?.join(rest)
+    } else if raw == "~" {
+        dirs::home_dir()?
+    } else if raw.is_empty() {
+        return None;
+    } else {
+        PathBuf::from(raw)
+    };
+    let path = if path.is_absolute() {
+        path
+    } else {
+        base.join(path)
+    };
+    let path = cgrep::paths::canonicalize(&path).ok()?;
+    path.is_dir().then_some(path)
+}
+
+fn within_roots(path: &Path, roots: &[PathBuf]) -> bool {
+    let path = cgrep::paths::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    roots.iter().any(|root| path.starts_with(root))
+}
+
+/// Local path of a `file://` URI, percent-decoded. Other schemes are ignored.
+fn path_from_file_uri(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Drop an authority such as `localhost`; the path starts at the next '/'.
+    let path = &rest[rest.find('/')?..];
+    let decoded = percent_decode(path)?;
+    // `file:///C:/src` names `C:/src` on Windows.
+    let bytes = decoded.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[2] == b':' && bytes[1].is_ascii_alphabetic() {
+        return Some(PathBuf::from(&decoded[1..]));
+    }
+    Some(PathBuf::from(decoded))
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' {
+            let hex = input.get(idx + 1..idx + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            idx += 3;
+        } else {
+            out.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_uris_are_decoded_to_paths() {
+        assert_eq!(
+            path_from_file_uri("file:///home/me/my%20project"),
+            Some(PathBuf::from("/home/me/my project"))
+        );
+        assert_eq!(
+            path_from_file_uri("file://localhost/srv/repo"),
+            Some(PathBuf::from("/srv/repo"))
+        );
+        assert_eq!(
+            path_from_file_uri("file:///C:/src/app"),
+            Some(PathBuf::from("C:/src/app"))
+        );
+        assert_eq!(path_from_file_uri("https://example.com/repo"), None);
+        assert_eq!(path_from_file_uri("file:///bad%zz"), None);
+    }
+
+    #[test]
+    fn arguments_default_to_a_root_and_stay_inside_roots() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let root = cgrep::paths::canonicalize(dir.path()).expect("canonical");
+        let inner = root.join("src");
+        std::fs::create_dir_all(&inner).expect("mkdir");
+        let roots = [root.clone()];
+
+        let args = json!({ "query": "x" });
+        let scoped = bound_to_roots("cgrep_search", Cow::Borrowed(&args), &roots).expect("scoped");
+        assert_eq!(scoped["cwd"], json!(root.display().to_string()));
+        assert_eq!(scoped["query"], "x");
+
+        let args = json!({ "query": "x", "cwd": inner.display().to_string() });
+        assert!(matches!(
+            bound_to_roots("cgrep_search", Cow::Borrowed(&args), &roots),
+            Ok(Cow::Borrowed(_))
+        ));
+
+        let outside = root.parent().expect("parent").display().to_string();
+        let args = json!({ "cwd": outside });
+        let err = bound_to_roots("cgrep_search", Cow::Borrowed(&args), &roots)
+            .expect_err("outside roots");
+        assert!(
+            err.contains("outside the client's workspace roots"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn default_roots_resolve_paths_uris_and_relative_dirs() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let base = cgrep::paths::canonicalize(dir.path()).expect("canonical");
+        let app = base.join("app");
+        std::fs::create_dir_all(&app).expect("mkdir");
+        std::fs::write(base.join("notes.txt"), "x").expect("write");
+
+        assert_eq!(resolve_root("app", &base), Some(app.clone()));
+        assert_eq!(
+            resolve_root(&app.display().to_string(), Path::new("/")),
+            Some(app.clone())
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            resolve_root(&format!("file://{}", app.display()), Path::new("/")),
+            Some(app.clone())
+        );
+        assert_eq!(resolve_root("notes.txt", &base), None);
+        assert_eq!(resolve_root("missing", &base), None);
+        assert_eq!(resolve_root("  ", &base), None);
+    }
+
+    #[test]
+    fn set_root_only_moves_the_calling_session() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let base = cgrep::paths::canonicalize(dir.path()).expect("canonical");
+        let (app, lib) = (base.join("app"), base.join("lib"));
+        std::fs::create_dir_all(&app).expect("mkdir app");
+        std::fs::create_dir_all(&lib).expect("mkdir lib");
+        let tagged = |tag: &str| json!({ "_meta": { "cgrep/session": tag } });
+        let in_session =
+            |tag: &str, work: &dyn Fn() -> Option<PathBuf>| session::with_call(&tagged(tag), work);
+
+        // An `initialize` default root is shared by the connection's sessions.
+        note_initialize(&json!({ "_meta": { HANDSHAKE_KEY: app.display().to_string() } }));
+        assert_eq!(in_session("roots-alice", &default_root), Some(app.clone()));
+
+        let moved = in_session("roots-alice", &|| {
+            set_root(&json!({ "path": lib.display().to_string() }))
+                .ok()
+                .map(|(root, _)| root)
+        });
+        assert_eq!(moved, Some(lib.clone()));
+        assert_eq!(in_session("roots-alice", &default_root), Some(lib));
+        assert_eq!(in_session("roots-bob", &default_root), Some(app.clone()));
+        assert_eq!(default_root(), Some(app));
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! MCP sessions: the unit that owns auto-index state.
+//!
+//! Each `initialize` starts a new connection session and ends the one before
+//! it; closing stdin ends the last. A host that multiplexes several clients
+//! over one server tags their tool calls with `params._meta."cgrep/session"`,
+//! and each tag becomes a session of its own within the connection. Bootstrap
+//! failures, refresh debounce, file watchers, and default roots are kept per
+//! session, so clients working in different repositories do not share them.
+
+use serde_json::Value;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// `_meta` key of a tool call naming the client session it belongs to.
+const SESSION_KEY: &str = "cgrep/session";
+
+static CONNECTION: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Session of the tool call or background task running on this thread.
+    static CURRENT: RefCell<Option<Session>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) struct Session {
+    connection: u64,
+    client: Option<String>,
+}
+
+impl Session {
+    /// Whether this session belongs to `connection`.
+    pub(super) fn in_connection(&self, connection: u64) -> bool {
+        self.connection == connection
+    }
+
+    /// The untagged session of this session's connection.
+    pub(super) fn connection_session(&self) -> Session {
+        Session {
+            connection: self.connection,
+            client: None,
+        }
+    }
+}
+
+/// Start a new connection session at `initialize`; returns the one it ends.
+pub(super) fn start_connection() -> u64 {
+    CONNECTION.fetch_add(1, Ordering::SeqCst)
+}
+
+/// The connection session started by the latest `initialize`.
+pub(super) fn connection() -> u64 {
+    CONNECTION.load(Ordering::SeqCst)
+}
+
+/// Session of the work running on this thread: the tagged call's, else the
+/// connection's.
+pub(super) fn current() -> Session {
+    CURRENT
+        .with(|current| current.borrow().clone())
+        .unwrap_or_else(|| Session {
+            connection: connection(),
+            client: None,
+        })
+}
+
+/// Run `work` as part of the session a `tools/call` request names.
+pub(super) fn with_call<T>(params: &Value, work: impl FnOnce() -> T) -> T {
+    let client = params
+        .get("_meta")
+        .and_then(|meta| meta.get(SESSION_KEY))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string);
+    with(
+        Session {
+            connection: connection(),
+            client,
+        },
+        work,
+    )
+}
+
+/// Run `work` as part of `session`, e.g. on a thread spawned for it.
+pub(super) fn with<T>(session: Session, work: impl FnOnce() -> T) -> T {
+    let _restore = Restore(CURRENT.with(|current| current.replace(Some(session))));
+    work()
+}
+
+/// Puts back the session a [`with`] replaced, also when its work panics.
+struct Restore(Option<Session>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tagged_calls_run_in_their_own_session() {
+        let untagged = with_call(&json!({ "name": "cgrep_search" }), current);
+        let alice = with_call(&json!({ "_meta": { "cgrep/session": "alice" } }), current);
+        let bob = with_call(&json!({ "_meta": { "cgrep/session": "bob" } }), current);
+        assert_eq!(untagged.client, None);
+        assert_eq!(alice.client.as_deref(), Some("alice"));
+        assert_ne!(alice, bob);
+        assert!(alice.in_connection(connection()));
+        // The tag does not outlive the call.
+        assert_eq!(current().client, None);
+        assert_eq!(alice.connection_session(), untagged);
+    }
+
+    #[test]
+    fn panicking_work_restores_the_previous_session() {
+        let tagged = json!({ "_meta": { "cgrep/session": "carol" } });
+        let unwound = std::panic::catch_unwind(|| with_call(&tagged, || panic!("tool failed")));
+        assert!(unwound.is_err());
+        assert_eq!(current().client, None);
+    }
+}
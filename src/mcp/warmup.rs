@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Index warmup when an MCP session starts (`[auto_index] mcp_warmup`).
+//!
+//! Tools run cgrep in child processes, so warming means having the index
+//! ready and its files in the OS page cache before the first call. A missing
+//! index is bootstrapped as a tool call would do it, a stale one refreshed,
+//! and the index files and embeddings database are then read once. All of it
+//! runs in the background; `initialize` only reports that it started.
+
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Instant;
+
+use cgrep::utils::INDEX_DIR;
+
+use super::logging::{self, Level};
+use super::session;
+use super::{ensure_index_for_search, BootstrapOutcome};
+
+/// Start warming the index for the session's default root, else the
+/// server's working directory, when the config asks for it. Returns the
+/// status reported under `_meta`.
+pub(super) fn start(default_root: Option<PathBuf>) -> Option<Value> {
+    let cwd = match default_root {
+        Some(root) => root,
+        None => std::env::current_dir().ok()?,
+    };
+    let cwd = cgrep::paths::canonicalize(&cwd).unwrap_or(cwd);
+    let existing = cgrep::utils::find_index_root(&cwd);
+    let scope = existing
+        .as_ref()
+        .map(|found| found.root.clone())
+        .unwrap_or_else(|| cwd.clone());
+    let config = cgrep::config::Config::load_for_dir(&scope);
+    if !config.auto_index().mcp_warmup() {
+        return None;
+    }
+    let scope_text = scope.display().to_string();
+    if cgrep::paths::is_filesystem_root(&scope) {
+        return Some(json!({
+            "state": "skipped",
+            "scope": scope_text,
+            "reason": "server cwd is a filesystem root; pass `cwd` on tool calls",
+        }));
+    }
+
+    let index = if existing.is_some() {
+        "present"
+    } else {
+        "missing"
+    };
+    let session = session::current();
+    thread::spawn(move || session::with(session, || warm(scope)));
+    Some(json!({
+        "state": "started",
+        "scope": scope_text,
+        "index": index,
+    }))
+}
+
+fn warm(scope: PathBuf) {
+    let started = Instant::now();
+    let scope_text = scope.display().to_string();
+    let outcome = match ensure_index_for_search(Some(&scope_text), None) {
+        Ok(BootstrapOutcome::AlreadyIndexed) => "already_indexed",
+        Ok(BootstrapOutcome::Refreshed) => "refreshed",
+        Ok(BootstrapOutcome::Bootstrapped) => "bootstrapped",
+        Ok(BootstrapOutcome::FellBackToScan) => "scan_fallback",
+        Err(err) => {
+            logging::log(
+                Level::Warning,
+                json!({ "event": "warmup", "scope": scope_text, "error": err }),
+            );
+            return;
+        }
+    };
+    let bytes = read_index_files(&scope.join(INDEX_DIR));
+    logging::log(
+        Level::Info,
+        json!({
+            "event": "warmup",
+            "scope": scope_text,
+            "outcome": outcome,
+            "bytes_read": bytes,
+            "elapsed_ms": started.elapsed().as_millis() as u64,
+        }),
+    );
+}
+
+/// Read every file directly under the index directory (segments, metadata,
+/// embeddings database) so later opens hit the page cache.
+fn read_index_files(index_dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(index_dir) else {
+        return 0;
+    };
+    let mut buf = vec![0u8; 1 << 16];
+    let mut total = 0;
+    for entry in entries.flatten() {
+        if !entry.file_type().is_ok_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let Ok(mut file) = fs::File::open(entry.path()) else {
+            continue;
+        };
+        while let Ok(read) = file.read(&mut buf) {
+            if read == 0 {
+                break;
+            }
+            total += read as u64;
+        }
+    }
+    total
+}
@@ -0,0 +1,420 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Neovim msgpack-rpc server for cgrep (stdio).
+//!
+//! `search`, `symbols`, and `definition` answer `vim.rpcrequest` with the
+//! whole result list. Sent as `vim.rpcnotify` with a `token`, the same
+//! methods run without blocking the editor and deliver their results in
+//! batches by calling `require(<lua module>)._on_event(event)` through
+//! `nvim_exec_lua`. Keyword `search` and `symbols` stream: the cgrep child
+//! reports each hit as it finds it (see [`crate::query::hits`]), and a batch
+//! goes out once it is full or the hits pause, so a picker fills in while
+//! the query runs. Other queries send their finished results in batches.
+//! `cancel` drops a superseded query before it runs or stops its batches.
+//! Queries run on a small worker pool against the on-disk index the watch
+//! daemon keeps warm, like the MCP server's tools; `ping` and `cancel` are
+//! answered inline.
+
+pub mod msgpack;
+
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io::{self, BufReader, Write};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const REQUEST: u64 = 0;
+const RESPONSE: u64 = 1;
+const NOTIFICATION: u64 = 2;
+const DEFAULT_BATCH_SIZE: usize = 50;
+/// A partial batch goes out after this long without a new hit.
+const BATCH_INTERVAL: Duration = Duration::from_millis(50);
+/// Queries running at once; later ones wait in order.
+const WORKERS: usize = 4;
+
+struct Server {
+    out: Mutex<io::Stdout>,
+    lua_module: String,
+    cancelled: Mutex<HashSet<String>>,
+}
+
+/// A query waiting for a worker: a request to answer, or a notification
+/// whose results go out as events.
+struct Job {
+    msgid: Option<Value>,
+    method: String,
+    args: Value,
+}
+
+/// Serve msgpack-rpc on stdin/stdout until the peer closes stdin.
+pub fn run(lua_module: &str) -> Result<()> {
+    if lua_module.is_empty()
+        || !lua_module
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-')
+    {
+        bail!("Invalid Lua module name: {lua_module}");
+    }
+    let server = Server {
+        out: Mutex::new(io::stdout()),
+        lua_module: lua_module.to_string(),
+        cancelled: Mutex::new(HashSet::new()),
+    };
+    let (tx, rx) = mpsc::channel::<Job>();
+    let rx = Mutex::new(rx);
+
+    // Leaving the scope lets in-flight and queued queries answer before
+    // exiting on stdin EOF.
+    thread::scope(|scope| {
+        for _ in 0..WORKERS {
+            scope.spawn(|| loop {
+                let next = rx
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .recv();
+                let Ok(job) = next else {
+                    break;
+                };
+                server.run_job(job);
+            });
+        }
+
+        let mut input = BufReader::new(io::stdin().lock());
+        while let Some(message) = msgpack::read(&mut input)? {
+            let Value::Array(parts) = message else {
+                continue;
+            };
+            let kind = parts.first().and_then(Value::as_u64);
+            // Requests are [0, msgid, method, params]; notifications [2, method, params].
+            let (msgid, method, params) = match kind {
+                Some(REQUEST) => (parts.get(1).cloned(), parts.get(2), parts.get(3)),
+                Some(NOTIFICATION) => (None, parts.get(1), parts.get(2)),
+                _ => continue,
+            };
+            let method = method
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let args = first_arg(params);
+            let job = Job {
+                msgid,
+                method,
+                args,
+            };
+            if matches!(job.method.as_str(), "ping" | "cancel") {
+                server.run_job(job);
+            } else if let Err(mpsc::SendError(job)) = tx.send(job) {
+                server.run_job(job);
+            }
+        }
+        drop(tx);
+        Ok(())
+    })
+}
+
+impl Server {
+    fn run_job(&self, job: Job) {
+        match job.msgid {
+            Some(msgid) => {
+                let result = self.call(&job.method, &job.args);
+                self.respond(msgid, result);
+            }
+            None => self.notify(&job.method, &job.args),
+        }
+    }
+
+    fn call(&self, method: &str, args: &Value) -> Result<Value, String> {
+        match method {
+            "ping" => Ok(json!("pong")),
+            "cancel" => {
+                self.cancel(args);
+                Ok(json!(true))
+            }
+            "search" | "symbols" | "definition" => {
+                let (results, meta) = query(method, args, None)?;
+                let mut payload = json!({ "total": results.len(), "results": results });
+                if let Some(meta) = meta {
+                    payload["meta"] = meta;
+                }
+                Ok(payload)
+            }
+            _ => Err(format!("unknown method: {method}")),
+        }
+    }
+
+    fn notify(&self, method: &str, args: &Value) {
+        if method == "cancel" {
+            self.cancel(args);
+            return;
+        }
+        let Some(token) = args.get("token").cloned() else {
+            return;
+        };
+        let key = token.to_string();
+        let batch_size = args
+            .get("batch_size")
+            .and_then(Value::as_u64)
+            .filter(|size| *size > 0)
+            .map_or(DEFAULT_BATCH_SIZE, |size| size as usize);
+
+        // Superseded while waiting for a worker.
+        if self.is_cancelled(&key) {
+            self.forget_cancelled(&key);
+            return;
+        }
+        let (hits, streamed) = mpsc::channel();
+        let outcome = thread::scope(|scope| {
+            let running = scope.spawn(|| query(method, args, Some(hits)));
+            let mut batch = Batch::new(&token, batch_size);
+            let mut open = true;
+            loop {
+                let hit = match streamed.recv_timeout(BATCH_INTERVAL) {
+                    Ok(hit) => Some(hit),
+                    Err(mpsc::RecvTimeoutError::Timeout) => None,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                };
+                // After a cancel, keep draining so the query can finish.
+                if !open {
+                    continue;
+                }
+                if self.is_cancelled(&key) {
+                    open = false;
+                    continue;
+                }
+                match hit {
+                    Some(hit) => {
+                        if batch.push(hit) {
+                            self.flush(&mut batch);
+                        }
+                    }
+                    None => self.flush(&mut batch),
+                }
+            }
+            let outcome = running
+                .join()
+                .unwrap_or_else(|_| Err("query panicked".to_string()));
+            (outcome, batch, open)
+        });
+        match outcome {
+            (Ok((results, _)), mut batch, true) => {
+                // Nothing streamed (e.g. semantic search or a cache hit): send
+                // the finished results instead.
+                if batch.sent == 0 && batch.pending.is_empty() {
+                    for hit in results {
+                        if self.is_cancelled(&key) {
+                            break;
+                        }
+                        if batch.push(hit) {
+                            self.flush(&mut batch);
+                        }
+                    }
+                }
+                if !self.is_cancelled(&key) {
+                    self.flush(&mut batch);
+                    self.emit(json!({ "token": token, "kind": "done", "total": batch.sent }));
+                }
+            }
+            (Ok(_), _, false) => {}
+            (Err(message), _, _) => {
+                self.emit(json!({ "token": token, "kind": "error", "message": message }));
+            }
+        }
+        self.forget_cancelled(&key);
+    }
+
+    /// Send the batch's pending hits as one `results` event.
+    fn flush(&self, batch: &mut Batch) {
+        if batch.pending.is_empty() {
+            return;
+        }
+        let items = std::mem::take(&mut batch.pending);
+        batch.sent += items.len();
+        self.emit(json!({ "token": batch.token, "kind": "results", "items": items }));
+    }
+
+    fn forget_cancelled(&self, key: &str) {
+        if let Ok(mut cancelled) = self.cancelled.lock() {
+            cancelled.remove(key);
+        }
+    }
+
+    fn cancel(&self, args: &Value) {
+        if let (Some(token), Ok(mut cancelled)) = (args.get("token"), self.cancelled.lock()) {
+            cancelled.insert(token.to_string());
+        }
+    }
+
+    fn is_cancelled(&self, key: &str) -> bool {
+        self.cancelled
+            .lock()
+            .map(|cancelled| cancelled.contains(key))
+            .unwrap_or(false)
+    }
+
+    fn respond(&self, msgid: Value, result: Result<Value, String>) {
+        let message = match result {
+            Ok(result) => json!([RESPONSE, msgid, null, result]),
+            Err(err) => json!([RESPONSE, msgid, err, null]),
+        };
+        self.send(&message);
+        cgrep::telemetry::maybe_flush();
+    }
+
+    fn emit(&self, event: Value) {
+        let code = format!("return require('{}')._on_event(...)", self.lua_module);
+        self.send(&json!([NOTIFICATION, "nvim_exec_lua", [code, [event]]]));
+    }
+
+    fn send(&self, message: &Value) {
+        let Ok(mut out) = self.out.lock() else {
+            return;
+        };
+        // The peer going away ends the session through stdin EOF; ignore write errors here.
+        let _ = msgpack::write(&mut *out, message).and_then(|()| out.flush());
+    }
+}
+
+/// Hits of a notification query waiting to go out, deduplicated by location.
+struct Batch {
+    token: Value,
+    size: usize,
+    pending: Vec<Value>,
+    seen: HashSet<(Value, Value)>,
+    sent: usize,
+}
+
+impl Batch {
+    fn new(token: &Value, size: usize) -> Self {
+        Self {
+            token: token.clone(),
+            size,
+            pending: Vec::new(),
+            seen: HashSet::new(),
+            sent: 0,
+        }
+    }
+
+    /// Queue a hit; returns true once a full batch is pending.
+    fn push(&mut self, hit: Value) -> bool {
+        let location = (hit["path"].clone(), hit["line"].clone());
+        if self.seen.insert(location) {
+            self.pending.push(hit);
+        }
+        self.pending.len() >= self.size
+    }
+}
+
+fn first_arg(params: Option<&Value>) -> Value {
+    match params {
+        Some(Value::Array(items)) => items.first().cloned().unwrap_or_else(|| json!({})),
+        Some(value @ Value::Object(_)) => value.clone(),
+        _ => json!({}),
+    }
+}
+
+/// Run `method` through the CLI and return its results plus json2 `meta` if any.
+/// With `hits`, each hit is also sent there as soon as the CLI finds it.
+fn query(
+    method: &str,
+    args: &Value,
+    hits: Option<mpsc::Sender<Value>>,
+) -> Result<(Vec<Value>, Option<Value>), String> {
+    let cwd = str_arg(args, "cwd");
+    let path = str_arg(args, "path");
+    crate::mcp::maybe_prepare_auto_index(args, cwd, path)?;
+
+    let mut cmd: Vec<String> = Vec::new();
+    match method {
+        "search" => {
+            let query = required_str(args, "query")?;
+            cmd.extend(["--format", "json2", "--compact", "search"].map(String::from));
+            push_value(&mut cmd, "-p", path);
+            push_value(&mut cmd, "-m", u64_arg(args, "limit").as_deref());
+            push_value(&mut cmd, "-C", u64_arg(args, "context").as_deref());
+            push_value(&mut cmd, "-t", str_arg(args, "file_type"));
+            push_value(&mut cmd, "--glob", str_arg(args, "glob"));
+            push_value(&mut cmd, "--exclude", str_arg(args, "exclude"));
+            push_value(&mut cmd, "--mode", str_arg(args, "mode"));
+            push_flag(&mut cmd, "--regex", bool_arg(args, "regex"));
+            push_flag(
+                &mut cmd,
+                "--case-sensitive",
+                bool_arg(args, "case_sensitive"),
+            );
+            cmd.push("--".to_string());
+            cmd.push(query.to_string());
+        }
+        "symbols" => {
+            let name = required_str(args, "name")?;
+            cmd.extend(["--format", "json", "--compact", "symbols"].map(String::from));
+            cmd.push(name.to_string());
+            push_value(&mut cmd, "-T", str_arg(args, "symbol_type"));
+            push_value(&mut cmd, "--lang", str_arg(args, "lang"));
+            push_value(&mut cmd, "--file-type", str_arg(args, "file_type"));
+            push_value(&mut cmd, "--glob", str_arg(args, "glob"));
+            push_flag(&mut cmd, "-q", true);
+        }
+        "definition" => {
+            let name = required_str(args, "name")?;
+            cmd.extend(["--format", "json", "--compact", "definition"].map(String::from));
+            cmd.push(name.to_string());
+            push_value(&mut cmd, "-p", path);
+            push_value(&mut cmd, "--limit", u64_arg(args, "limit").as_deref());
+        }
+        _ => return Err(format!("unknown method: {method}")),
+    }
+
+    let output = match hits {
+        Some(hits) => crate::mcp::run_cgrep_streaming(&cmd, cwd, hits)?,
+        None => crate::mcp::run_cgrep(&cmd, cwd)?,
+    };
+    if output.trim().is_empty() {
+        return Ok((Vec::new(), None));
+    }
+    let parsed: Value = serde_json::from_str(&output)
+        .map_err(|err| format!("failed to parse cgrep output: {err}"))?;
+    Ok(match parsed {
+        Value::Array(items) => (items, None),
+        Value::Object(mut payload) => {
+            let results = match payload.remove("results") {
+                Some(Value::Array(items)) => items,
+                _ => Vec::new(),
+            };
+            (results, payload.remove("meta"))
+        }
+        _ => (Vec::new(), None),
+    })
+}
+
+fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
+    str_arg(args, key).ok_or_else(|| format!("missing required argument: {key}"))
+}
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .filter(|value| !value.is_empty())
+}
+
+fn u64_arg(args: &Value, key: &str) -> Option<String> {
+    args.get(key).and_then(Value::as_u64).map(|v| v.to_string())
+}
+
+fn bool_arg(args: &Value, key: &str) -> bool {
+    args.get(key).and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn push_value(cmd: &mut Vec<String>, flag: &str, value: Option<&str>) {
+    if let Some(value) = value {
+        cmd.push(flag.to_string());
+        cmd.push(value.to_string());
+    }
+}
+
+fn push_flag(cmd: &mut Vec<String>, flag: &str, enabled: bool) {
+    if enabled {
+        cmd.push(flag.to_string());
+    }
+}
@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Minimal MessagePack codec over `serde_json::Value`.
+//!
+//! Covers what msgpack-rpc peers such as Neovim send: nil, booleans,
+//! integers, floats, str/bin, arrays, and maps. Binary strings decode as
+//! lossy UTF-8, map keys are stringified, and ext values decode as nil.
+
+use serde_json::{Map, Number, Value};
+use std::io::{self, Read, Write};
+
+/// Upper bound for preallocation from untrusted length prefixes.
+const MAX_PREALLOC: usize = 1024;
+
+/// Encode `value` as MessagePack.
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(&mut out, value);
+    out
+}
+
+/// Encode `value` and write it to `writer`.
+pub fn write<W: Write>(writer: &mut W, value: &Value) -> io::Result<()> {
+    writer.write_all(&encode(value))
+}
+
+/// Read one value, or `None` on a clean end of input.
+pub fn read<R: Read>(reader: &mut R) -> io::Result<Option<Value>> {
+    let mut marker = [0u8; 1];
+    match reader.read(&mut marker)? {
+        0 => Ok(None),
+        _ => read_with_marker(reader, marker[0]).map(Some),
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(false) => out.push(0xc2),
+        Value::Bool(true) => out.push(0xc3),
+        Value::Number(number) => write_number(out, number),
+        Value::String(text) => write_str(out, text),
+        Value::Array(items) => {
+            write_len(out, items.len(), 0x90, 0xdc, 0xdd);
+            for item in items {
+                write_value(out, item);
+            }
+        }
+        Value::Object(map) => {
+            write_len(out, map.len(), 0x80, 0xde, 0xdf);
+            for (key, item) in map {
+                write_str(out, key);
+                write_value(out, item);
+            }
+        }
+    }
+}
+
+fn write_number(out: &mut Vec<u8>, number: &Number) {
+    if let Some(value) = number.as_u64() {
+        match value {
+            0..=0x7f => out.push(value as u8),
+            0x80..=0xff => out.extend([0xcc, value as u8]),
+            0x100..=0xffff => {
+                out.push(0xcd);
+                out.extend((value as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                out.push(0xce);
+                out.extend((value as u32).to_be_bytes());
+            }
+            _ => {
+                out.push(0xcf);
+                out.extend(value.to_be_bytes());
+            }
+        }
+    } else if let Some(value) = number.as_i64() {
+        if value >= -32 {
+            out.push(value as i8 as u8);
+        } else {
+            out.push(0xd3);
+            out.extend(value.to_be_bytes());
+        }
+    } else {
+        out.push(0xcb);
+        out.extend(number.as_f64().unwrap_or_default().to_be_bytes());
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, text: &str) {
+    let len = text.len();
+    if len < 32 {
+        out.push(0xa0 | len as u8);
+    } else if len <= 0xff {
+        out.extend([0xd9, len as u8]);
+    } else if len <= 0xffff {
+        out.push(0xda);
+        out.extend((len as u16).to_be_bytes());
+    } else {
+        out.push(0xdb);
+        out.extend((len as u32).to_be_bytes());
+    }
+    out.extend(text.as_bytes());
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize, fix: u8, marker16: u8, marker32: u8) {
+    if len < 16 {
+        out.push(fix | len as u8);
+    } else if len <= 0xffff {
+        out.push(marker16);
+        out.extend((len as u16).to_be_bytes());
+    } else {
+        out.push(marker32);
+        out.extend((len as u32).to_be_bytes());
+    }
+}
+
+fn read_with_marker<R: Read>(reader: &mut R, marker: u8) -> io::Result<Value> {
+    let value = match marker {
+        0x00..=0x7f => Value::from(marker),
+        0x80..=0x8f => read_map(reader, (marker & 0x0f) as usize)?,
+        0x90..=0x9f => read_array(reader, (marker & 0x0f) as usize)?,
+        0xa0..=0xbf => read_str(reader, (marker & 0x1f) as usize)?,
+        0xc0 => Value::Null,
+        0xc2 => Value::Bool(false),
+        0xc3 => Value::Bool(true),
+        0xc4 => {
+            let len = read_uint(reader, 1)? as usize;
+            read_str(reader, len)?
+        }
+        0xc5 => {
+            let len = read_uint(reader, 2)? as usize;
+            read_str(reader, len)?
+        }
+        0xc6 => {
+            let len = read_uint(reader, 4)? as usize;
+            read_str(reader, len)?
+        }
+        0xc7..=0xc9 => {
+            let len = read_uint(reader, 1 << (marker - 0xc7))? as usize;
+            skip(reader, len + 1)?;
+            Value::Null
+        }
+        0xca => Value::from(f32::from_bits(read_uint(reader, 4)? as u32) as f64),
+        0xcb => Value::from(f64::from_bits(read_uint(reader, 8)?)),
+        0xcc..=0xcf => Value::from(read_uint(reader, 1 << (marker - 0xcc))?),
+        0xd0 => Value::from(read_uint(reader, 1)? as u8 as i8),
+        0xd1 => Value::from(read_uint(reader, 2)? as u16 as i16),
+        0xd2 => Value::from(read_uint(reader, 4)? as u32 as i32),
+        0xd3 => Value::from(read_uint(reader, 8)? as i64),
+        0xd4..=0xd8 => {
+            skip(reader, 1 + (1 << (marker - 0xd4)))?;
+            Value::Null
+        }
+        0xd9 => {
+            let len = read_uint(reader, 1)? as usize;
+            read_str(reader, len)?
+        }
+        0xda => {
+            let len = read_uint(reader, 2)? as usize;
+            read_str(reader, len)?
+        }
+        0xdb => {
+            let len = read_uint(reader, 4)? as usize;
+            read_str(reader, len)?
+        }
+        0xdc => {
+            let len = read_uint(reader, 2)? as usize;
+            read_array(reader, len)?
+        }
+        0xdd => {
+            let len = read_uint(reader, 4)? as usize;
+            read_array(reader, len)?
+        }
+        0xde => {
+            let len = read_uint(reader, 2)? as usize;
+            read_map(reader, len)?
+        }
+        0xdf => {
+            let len = read_uint(reader, 4)? as usize;
+            read_map(reader, len)?
+        }
+        0xe0..=0xff => Value::from(marker as i8),
+        0xc1 => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid msgpack marker 0xc1",
+            ))
+        }
+    };
+    Ok(value)
+}
+
+fn read_next<R: Read>(reader: &mut R) -> io::Result<Value> {
+    let mut marker = [0u8; 1];
+    reader.read_exact(&mut marker)?;
+    read_with_marker(reader, marker[0])
+}
+
+fn read_uint<R: Read>(reader: &mut R, width: usize) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf[8 - width..])?;
+    Ok(u64::from_be_bytes(buf))
+}
+
+fn read_str<R: Read>(reader: &mut R, len: usize) -> io::Result<Value> {
+    let mut bytes = Vec::with_capacity(len.min(MAX_PREALLOC));
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(Value::String(String::from_utf8(bytes).unwrap_or_else(
+        |err| String::from_utf8_lossy(err.as_bytes()).into_owned(),
+    )))
+}
+
+fn read_array<R: Read>(reader: &mut R, len: usize) -> io::Result<Value> {
+    let mut items = Vec::with_capacity(len.min(MAX_PREALLOC));
+    for _ in 0..len {
+        items.push(read_next(reader)?);
+    }
+    Ok(Value::Array(items))
+}
+
+fn read_map<R: Read>(reader: &mut R, len: usize) -> io::Result<Value> {
+    let mut map = Map::new();
+    for _ in 0..len {
+        let key = match read_next(reader)? {
+            Value::String(key) => key,
+            other => other.to_string(),
+        };
+        map.insert(key, read_next(reader)?);
+    }
+    Ok(Value::Object(map))
+}
+
+fn skip<R: Read>(reader: &mut R, len: usize) -> io::Result<()> {
+    let skipped = io::copy(&mut reader.take(len as u64), &mut io::sink())?;
+    if skipped as usize != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn values_round_trip() {
+        let value = json!([
+            0,
+            1,
+            "search",
+            [{
+                "query": "token refresh",
+                "limit": 300,
+                "offset": -5,
+                "big": 70000,
+                "neg": -40000,
+                "ratio": 0.5,
+                "regex": false,
+                "path": null,
+                "long": "x".repeat(40),
+                "items": (0..20).collect::<Vec<_>>(),
+            }]
+        ]);
+        let bytes = encode(&value);
+        let decoded = read(&mut bytes.as_slice()).unwrap().unwrap();
+        assert_eq!(decoded, value);
+        assert!(read(&mut &[][..]).unwrap().is_none());
+    }
+
+    #[test]
+    fn decodes_neovim_encodings() {
+        // fixarray [fixint 0, uint8 200, bin8 "ab", map {1: true}, ext8 skipped]
+        let bytes = [
+            0x95, 0x00, 0xcc, 0xc8, 0xc4, 0x02, b'a', b'b', 0x81, 0x01, 0xc3, 0xc7, 0x01, 0x00,
+            0xff,
+        ];
+        let decoded = read(&mut &bytes[..]).unwrap().unwrap();
+        assert_eq!(decoded, json!([0, 200, "ab", {"1": true}, null]));
+    }
+}
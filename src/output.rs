@@ -5,12 +5,82 @@
 //! Provides shared color functions respecting NO_COLOR environment variable.
 
 use colored::Colorize;
+use once_cell::sync::OnceCell;
 use serde::Serialize;
+use serde_json::Value;
 
 use anyhow::Result;
 
+/// Decimal places kept for floats in `--stable` output.
+const STABLE_FLOAT_DECIMALS: i32 = 6;
+
+/// Wall-clock measurements, zeroed in `--stable` output.
+const TIMING_KEYS: &[&str] = &["elapsed_ms"];
+
+static STABLE: OnceCell<bool> = OnceCell::new();
+
+/// Enable deterministic JSON output for this process (`--stable`).
+pub fn set_stable(stable: bool) {
+    let _ = STABLE.set(stable);
+}
+
+pub fn stable() -> bool {
+    STABLE.get().copied().unwrap_or(false)
+}
+
+/// `value` rounded to the precision `--stable` output uses, so that scores
+/// differing only in the last bits compare (and print) equal.
+pub fn stable_float(value: f64) -> f64 {
+    let scale = 10f64.powi(STABLE_FLOAT_DECIMALS);
+    let rounded = (value * scale).round() / scale;
+    // Avoid printing `-0.0`.
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
+    }
+}
+
+/// Normalize a JSON payload for `--stable`: object keys sorted, floats
+/// rounded to a fixed precision, and timings zeroed.
+pub fn stabilize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let value = if TIMING_KEYS.contains(&key.as_str()) && value.is_number() {
+                            Value::from(0)
+                        } else {
+                            stabilize(value)
+                        };
+                        (key, value)
+                    })
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(stabilize).collect()),
+        Value::Number(number) if number.is_f64() => number
+            .as_f64()
+            .and_then(|float| serde_json::Number::from_f64(stable_float(float)))
+            .map_or(Value::Number(number), Value::Number),
+        other => other,
+    }
+}
+
 /// Print JSON output, optionally compact (no pretty formatting).
 pub fn print_json<T: Serialize>(value: &T, compact: bool) -> Result<()> {
+    if stable() {
+        let value = stabilize(serde_json::to_value(value)?);
+        return print_json_value(&value, compact);
+    }
+    print_json_value(value, compact)
+}
+
+fn print_json_value<T: Serialize + ?Sized>(value: &T, compact: bool) -> Result<()> {
     if compact {
         println!("{}", serde_json::to_string(value)?);
     } else {
@@ -19,6 +89,183 @@ pub fn print_json<T: Serialize>(value: &T, compact: bool) -> Result<()> {
     Ok(())
 }
 
+/// Print a json2 payload for `command` in the schema version selected by
+/// `--schema-version`.
+pub fn print_json2<T: Serialize>(command: &str, value: &T, compact: bool) -> Result<()> {
+    let target = crate::schema::requested_version();
+    if target == crate::schema::CURRENT_SCHEMA_VERSION {
+        return print_json(value, compact);
+    }
+    // Round-trip through text so `f32` fields keep their short form.
+    let payload = serde_json::from_str(&serde_json::to_string(value)?)?;
+    let payload = crate::schema::downgrade(command, payload, target).map_err(anyhow::Error::msg)?;
+    print_json(&payload, compact)
+}
+
+/// Severity of a CI annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationLevel {
+    Notice,
+    Warning,
+    Error,
+}
+
+impl AnnotationLevel {
+    fn github_command(self) -> &'static str {
+        match self {
+            AnnotationLevel::Notice => "notice",
+            AnnotationLevel::Warning => "warning",
+            AnnotationLevel::Error => "error",
+        }
+    }
+
+    fn gitlab_severity(self) -> &'static str {
+        match self {
+            AnnotationLevel::Notice => "info",
+            AnnotationLevel::Warning => "minor",
+            AnnotationLevel::Error => "major",
+        }
+    }
+}
+
+/// A single finding rendered as a CI annotation.
+#[derive(Debug, Clone)]
+pub struct Annotation<'a> {
+    pub path: &'a str,
+    pub line: Option<usize>,
+    pub level: AnnotationLevel,
+    /// Annotation title; also the GitLab `check_name`.
+    pub title: &'a str,
+    pub message: &'a str,
+    /// Stable identifier, used as the GitLab fingerprint when present.
+    pub id: Option<&'a str>,
+}
+
+/// Print GitHub Actions workflow commands (`::warning ...`), one per annotation.
+pub fn print_github_annotations(annotations: &[Annotation<'_>]) {
+    for annotation in annotations {
+        println!("{}", github_annotation(annotation));
+    }
+}
+
+fn github_annotation(annotation: &Annotation<'_>) -> String {
+    let mut properties = format!("file={}", escape_github_property(annotation.path));
+    if let Some(line) = annotation.line {
+        properties.push_str(&format!(",line={line}"));
+    }
+    properties.push_str(&format!(
+        ",title={}",
+        escape_github_property(annotation.title)
+    ));
+    format!(
+        "::{} {}::{}",
+        annotation.level.github_command(),
+        properties,
+        escape_github_data(annotation.message)
+    )
+}
+
+fn escape_github_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_github_property(value: &str) -> String {
+    escape_github_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+#[derive(Serialize)]
+struct GitlabIssue<'a> {
+    description: &'a str,
+    check_name: &'a str,
+    fingerprint: String,
+    severity: &'static str,
+    location: GitlabLocation<'a>,
+}
+
+#[derive(Serialize)]
+struct GitlabLocation<'a> {
+    path: &'a str,
+    lines: GitlabLines,
+}
+
+#[derive(Serialize)]
+struct GitlabLines {
+    begin: usize,
+}
+
+/// Print a GitLab Code Quality report (a JSON array of issues).
+pub fn print_gitlab_report(annotations: &[Annotation<'_>], compact: bool) -> Result<()> {
+    let issues: Vec<GitlabIssue<'_>> = annotations
+        .iter()
+        .map(|annotation| {
+            let begin = annotation.line.unwrap_or(1);
+            let fingerprint = match annotation.id {
+                Some(id) => id.to_string(),
+                None => blake3::hash(
+                    format!(
+                        "{}:{}:{begin}:{}",
+                        annotation.title, annotation.path, annotation.message
+                    )
+                    .as_bytes(),
+                )
+                .to_hex()
+                .to_string(),
+            };
+            GitlabIssue {
+                description: annotation.message,
+                check_name: annotation.title,
+                fingerprint,
+                severity: annotation.level.gitlab_severity(),
+                location: GitlabLocation {
+                    path: annotation.path,
+                    lines: GitlabLines { begin },
+                },
+            }
+        })
+        .collect();
+    print_json(&issues, compact)
+}
+
+/// A location printed as one quickfix line.
+#[derive(Debug, Clone)]
+pub struct QuickfixEntry<'a> {
+    pub path: &'a str,
+    pub line: usize,
+    /// 1-based byte column (`1` when unknown).
+    pub column: usize,
+    /// Message; only its first non-blank line is printed.
+    pub text: &'a str,
+}
+
+/// Print `file:line:col: text` lines, which Vim's default `errorformat`
+/// (`:grep`, `:cgetexpr`) and Emacs compilation-mode parse without setup.
+pub fn print_quickfix(entries: &[QuickfixEntry<'_>]) {
+    for entry in entries {
+        println!("{}", quickfix_line(entry));
+    }
+}
+
+fn quickfix_line(entry: &QuickfixEntry<'_>) -> String {
+    let text = entry
+        .text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    format!(
+        "{}:{}:{}: {}",
+        entry.path,
+        entry.line.max(1),
+        entry.column.max(1),
+        text
+    )
+}
+
 /// Check if colors should be used (respects NO_COLOR env var)
 pub fn use_colors() -> bool {
     std::env::var("NO_COLOR").is_err()
@@ -77,3 +324,50 @@ pub fn colorize_name(text: &str, use_color: bool) -> String {
         text.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stabilize_sorts_keys_rounds_floats_and_zeroes_timings() {
+        let mut map = serde_json::Map::new();
+        map.insert("zeta".to_string(), Value::from(0.1f32));
+        map.insert("alpha".to_string(), Value::from(vec![Value::from(-1e-9)]));
+        map.insert("elapsed_ms".to_string(), Value::from(12.5));
+        map.insert("count".to_string(), Value::from(3));
+        let stable = stabilize(Value::Object(map));
+        assert_eq!(
+            serde_json::to_string(&stable).expect("json"),
+            r#"{"alpha":[0.0],"count":3,"elapsed_ms":0,"zeta":0.1}"#
+        );
+        assert_eq!(stable_float(0.123_456_749), 0.123_457);
+    }
+
+    #[test]
+    fn github_annotations_escape_properties_and_data() {
+        let annotation = Annotation {
+            path: "src/a,b.rs",
+            line: Some(7),
+            level: AnnotationLevel::Warning,
+            title: "cgrep: TODO",
+            message: "50% done\nTODO: x",
+            id: None,
+        };
+        assert_eq!(
+            github_annotation(&annotation),
+            "::warning file=src/a%2Cb.rs,line=7,title=cgrep%3A TODO::50%25 done%0ATODO: x"
+        );
+    }
+
+    #[test]
+    fn quickfix_lines_use_first_nonblank_message_line() {
+        let entry = QuickfixEntry {
+            path: "src/lib.rs",
+            line: 12,
+            column: 0,
+            text: "\n    fn parse() {\n        body\n",
+        };
+        assert_eq!(quickfix_line(&entry), "src/lib.rs:12:1: fn parse() {");
+    }
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Syntax tree helpers shared by the commands that read literals from the
+//! AST (`routes`, `envvars`, `flags`).
+
+use tree_sitter::Node;
+
+/// Whether a node is a string literal in any of the supported grammars.
+pub fn is_string(node: Node) -> bool {
+    matches!(
+        node.kind(),
+        "string"
+            | "string_literal"
+            | "raw_string_literal"
+            | "interpreted_string_literal"
+            | "template_string"
+    )
+}
+
+/// The string literal `node` is part of (`node` itself included), if any.
+pub fn enclosing_string(node: Node) -> Option<Node> {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if is_string(node) {
+            return Some(node);
+        }
+        current = node.parent();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::languages::LANGUAGES;
+
+    #[test]
+    fn string_literals_are_found_from_their_contents() {
+        let source =
+            "fn main() {\n    let key = \"beta\"; // \"gamma\"\n    let raw = r\"delta\";\n}\n";
+        let mut parser = LANGUAGES.parser("rust").expect("rust grammar");
+        let tree = parser.parse(source, None).expect("parse");
+        let at = |needle: &str| {
+            let start = source.find(needle).expect("needle");
+            tree.root_node()
+                .descendant_for_byte_range(start, start + needle.len())
+                .expect("node")
+        };
+
+        let beta = enclosing_string(at("beta")).expect("beta is a string");
+        assert_eq!(beta.kind(), "string_literal");
+        assert_eq!(&source[beta.byte_range()], "\"beta\"");
+        assert!(enclosing_string(at("delta")).is_some_and(is_string));
+        assert!(enclosing_string(at("gamma")).is_none());
+    }
+}
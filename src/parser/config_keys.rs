@@ -0,0 +1,491 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Keys of structured config files (JSON, YAML, TOML) as symbols.
+//!
+//! Every key, top-level or nested, becomes a `key` symbol named by its dotted
+//! path (`server.http.port`) and located where the key is spelled out. Items
+//! of sequences/arrays do not add a path segment, so `steps.name` covers the
+//! `name` of every step. These formats have no tree-sitter grammar here, so
+//! each one is scanned line by line (JSON byte by byte) with just enough
+//! syntax to tell keys from values.
+
+use crate::parser::symbols::{Symbol, SymbolKind};
+
+/// Languages whose symbols are config keys.
+pub fn is_config_language(language: &str) -> bool {
+    matches!(language, "json" | "yaml" | "toml")
+}
+
+/// Extract every key of a config file as a dotted-path `key` symbol.
+pub fn extract_keys(source: &str, language: &str) -> Vec<Symbol> {
+    let mut keys = match language {
+        "json" => json_keys(source),
+        "yaml" => yaml_keys(source),
+        "toml" => toml_keys(source),
+        _ => Vec::new(),
+    };
+    extend_over_children(&mut keys);
+    keys
+}
+
+fn join(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+fn key_symbol(
+    path: String,
+    parent: &str,
+    line: usize,
+    column: usize,
+    bytes: (usize, usize),
+) -> Symbol {
+    Symbol {
+        name: path,
+        kind: SymbolKind::Key,
+        line,
+        column,
+        end_line: line,
+        byte_start: Some(bytes.0),
+        byte_end: Some(bytes.1),
+        scope: (!parent.is_empty()).then(|| parent.to_string()),
+        doc: None,
+        signature: None,
+        decorators: Vec::new(),
+        metrics: None,
+    }
+}
+
+/// Stretch each key's `end_line` over the nested keys that follow it.
+fn extend_over_children(keys: &mut [Symbol]) {
+    for idx in (0..keys.len()).rev() {
+        let prefix = format!("{}.", keys[idx].name);
+        let last_child = keys[idx + 1..]
+            .iter()
+            .take_while(|key| key.name.starts_with(&prefix))
+            .map(|key| key.end_line)
+            .max();
+        if let Some(end_line) = last_child {
+            keys[idx].end_line = keys[idx].end_line.max(end_line);
+        }
+    }
+}
+
+struct JsonFrame {
+    path: String,
+    is_object: bool,
+    last_key: Option<String>,
+}
+
+/// JSON (and JSONC: `//` and `/* */` comments are skipped).
+fn json_keys(source: &str) -> Vec<Symbol> {
+    let bytes = source.as_bytes();
+    let mut keys = Vec::new();
+    let mut stack: Vec<JsonFrame> = Vec::new();
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\n' => {
+                line += 1;
+                line_start = idx + 1;
+                idx += 1;
+            }
+            b'/' if bytes.get(idx + 1) == Some(&b'/') => {
+                idx = bytes[idx..]
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(bytes.len(), |pos| idx + pos);
+            }
+            b'/' if bytes.get(idx + 1) == Some(&b'*') => {
+                let end = source[idx + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |pos| idx + 2 + pos + 2);
+                let skipped = &source[idx..end];
+                if let Some(last_newline) = skipped.rfind('\n') {
+                    line += skipped.matches('\n').count();
+                    line_start = idx + last_newline + 1;
+                }
+                idx = end;
+            }
+            open @ (b'{' | b'[') => {
+                let path = match stack.last() {
+                    Some(frame) if frame.is_object => {
+                        frame.last_key.clone().unwrap_or_else(|| frame.path.clone())
+                    }
+                    Some(frame) => frame.path.clone(),
+                    None => String::new(),
+                };
+                stack.push(JsonFrame {
+                    path,
+                    is_object: open == b'{',
+                    last_key: None,
+                });
+                idx += 1;
+            }
+            b'}' | b']' => {
+                stack.pop();
+                idx += 1;
+            }
+            b'"' => {
+                let start = idx;
+                let Some(end) = closing_json_quote(bytes, start + 1) else {
+                    break;
+                };
+                idx = end + 1;
+                let Some(frame) = stack.last_mut() else {
+                    continue;
+                };
+                let next = bytes[idx..]
+                    .iter()
+                    .position(|b| !b.is_ascii_whitespace())
+                    .map(|pos| bytes[idx + pos]);
+                if !frame.is_object || next != Some(b':') {
+                    continue;
+                }
+                let path = join(&frame.path, &source[start + 1..end]);
+                keys.push(key_symbol(
+                    path.clone(),
+                    &frame.path,
+                    line,
+                    start - line_start + 1,
+                    (start, end + 1),
+                ));
+                frame.last_key = Some(path);
+            }
+            _ => idx += 1,
+        }
+    }
+    keys
+}
+
+fn closing_json_quote(bytes: &[u8], from: usize) -> Option<usize> {
+    let mut idx = from;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\\' => idx += 2,
+            b'"' => return Some(idx),
+            b'\n' => return None,
+            _ => idx += 1,
+        }
+    }
+    None
+}
+
+/// Block-style YAML. Flow mappings (`{a: 1}`) are treated as values.
+fn yaml_keys(source: &str) -> Vec<Symbol> {
+    let mut keys = Vec::new();
+    // (indent, dotted path) of the enclosing keys.
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut block_scalar_indent: Option<usize> = None;
+    let mut line_offset = 0;
+
+    for (line_idx, raw) in source.split('\n').enumerate() {
+        let offset = line_offset;
+        line_offset += raw.len() + 1;
+        let text = raw.trim_end_matches('\r');
+        let content = text.trim_start();
+        let mut indent = text.len() - content.len();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        if let Some(scalar_indent) = block_scalar_indent {
+            if indent > scalar_indent {
+                continue;
+            }
+            block_scalar_indent = None;
+        }
+        if indent == 0 && is_yaml_document_marker(content) {
+            stack.clear();
+            continue;
+        }
+
+        // Keys inside `- ` sequence items sit at the item's content column.
+        let mut rest = content;
+        while let Some(item) = rest.strip_prefix("- ") {
+            let trimmed = item.trim_start();
+            indent += rest.len() - trimmed.len();
+            rest = trimmed;
+        }
+
+        let Some((key, key_len, value)) = parse_yaml_key(rest) else {
+            continue;
+        };
+        while stack.last().is_some_and(|(level, _)| *level >= indent) {
+            stack.pop();
+        }
+        let parent = stack.last().map(|(_, path)| path.as_str()).unwrap_or("");
+        let path = join(parent, &key);
+        let column = text.len() - rest.len();
+        keys.push(key_symbol(
+            path.clone(),
+            parent,
+            line_idx + 1,
+            column + 1,
+            (offset + column, offset + column + key_len),
+        ));
+
+        if value.trim_start().starts_with(['|', '>']) {
+            block_scalar_indent = Some(indent);
+        }
+        stack.push((indent, path));
+    }
+    keys
+}
+
+fn is_yaml_document_marker(content: &str) -> bool {
+    ["---", "..."].iter().any(|marker| {
+        content == *marker
+            || content
+                .strip_prefix(marker)
+                .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+    })
+}
+
+/// `key: value` -> (key, key token length, value).
+fn parse_yaml_key(text: &str) -> Option<(String, usize, &str)> {
+    let is_separator = |after: &str| after.is_empty() || after.starts_with(char::is_whitespace);
+
+    if let Some(quote) = text.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+        let close = text[1..].find(quote)? + 1;
+        let after = text[close + 1..].trim_start();
+        let value = after.strip_prefix(':').filter(|v| is_separator(v))?;
+        return Some((text[1..close].to_string(), close + 1, value));
+    }
+
+    if text.starts_with([
+        '{', '[', '&', '*', '!', '|', '>', '%', '@', '`', '?', '#', ':',
+    ]) {
+        return None;
+    }
+    let bytes = text.as_bytes();
+    for (idx, &byte) in bytes.iter().enumerate() {
+        if byte == b'#' && idx > 0 && bytes[idx - 1].is_ascii_whitespace() {
+            return None;
+        }
+        if byte == b':' && is_separator(&text[idx + 1..]) {
+            let key = text[..idx].trim_end();
+            return (!key.is_empty()).then(|| (key.to_string(), key.len(), &text[idx + 1..]));
+        }
+    }
+    None
+}
+
+/// TOML tables, arrays of tables, and (dotted) keys.
+fn toml_keys(source: &str) -> Vec<Symbol> {
+    let mut keys = Vec::new();
+    let mut table: Vec<String> = Vec::new();
+    let mut open_string: Option<&str> = None;
+    let mut bracket_depth = 0usize;
+    let mut line_offset = 0;
+
+    for (line_idx, raw) in source.split('\n').enumerate() {
+        let offset = line_offset;
+        line_offset += raw.len() + 1;
+        let text = raw.trim_end_matches('\r');
+
+        // Continuation lines of multi-line strings, arrays, and inline tables.
+        if let Some(delimiter) = open_string {
+            if text.contains(delimiter) {
+                open_string = None;
+            }
+            continue;
+        }
+        if bracket_depth > 0 {
+            bracket_depth = toml_bracket_depth(bracket_depth, text);
+            continue;
+        }
+
+        let content = text.trim_start();
+        let column = text.len() - content.len();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+
+        if content.starts_with('[') {
+            let (open, close) = if content.starts_with("[[") {
+                ("[[", "]]")
+            } else {
+                ("[", "]")
+            };
+            let Some(end) = content.find(close) else {
+                continue;
+            };
+            let segments = toml_key_segments(&content[open.len()..end]);
+            if segments.is_empty() {
+                continue;
+            }
+            let parent = segments[..segments.len() - 1].join(".");
+            keys.push(key_symbol(
+                segments.join("."),
+                &parent,
+                line_idx + 1,
+                column + 1,
+                (offset + column, offset + column + end + close.len()),
+            ));
+            table = segments;
+            continue;
+        }
+
+        let Some(eq) = find_unquoted_equals(content) else {
+            continue;
+        };
+        let segments = toml_key_segments(&content[..eq]);
+        if segments.is_empty() {
+            continue;
+        }
+        let mut full = table.clone();
+        full.extend(segments);
+        let parent = full[..full.len() - 1].join(".");
+        let key_len = content[..eq].trim_end().len();
+        keys.push(key_symbol(
+            full.join("."),
+            &parent,
+            line_idx + 1,
+            column + 1,
+            (offset + column, offset + column + key_len),
+        ));
+
+        let value = content[eq + 1..].trim_start();
+        for delimiter in ["\"\"\"", "'''"] {
+            if let Some(rest) = value.strip_prefix(delimiter) {
+                if !rest.contains(delimiter) {
+                    open_string = Some(delimiter);
+                }
+            }
+        }
+        if open_string.is_none() {
+            bracket_depth = toml_bracket_depth(0, value);
+        }
+    }
+    keys
+}
+
+/// Split a (possibly dotted, possibly quoted) TOML key into its segments.
+fn toml_key_segments(raw: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    for ch in raw.chars() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => current.push(ch),
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '.' => segments.push(std::mem::take(&mut current)),
+            None if ch.is_whitespace() => {}
+            None => current.push(ch),
+        }
+    }
+    segments.push(current);
+    if segments.iter().any(String::is_empty) {
+        return Vec::new();
+    }
+    segments
+}
+
+fn find_unquoted_equals(text: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (idx, ch) in text.char_indices() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' => return None,
+            None if ch == '=' => return Some(idx),
+            None => {}
+        }
+    }
+    None
+}
+
+/// Bracket depth after `text`, ignoring strings and comments.
+fn toml_bracket_depth(mut depth: usize, text: &str) -> usize {
+    let mut quote: Option<char> = None;
+    for ch in text.chars() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '#' => break,
+            None if ch == '[' || ch == '{' => depth += 1,
+            None if ch == ']' || ch == '}' => depth = depth.saturating_sub(1),
+            None => {}
+        }
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names_and_lines(keys: &[Symbol]) -> Vec<(&str, usize)> {
+        keys.iter()
+            .map(|key| (key.name.as_str(), key.line))
+            .collect()
+    }
+
+    #[test]
+    fn yaml_keys_use_dotted_paths() {
+        let source = "# service\nserver:\n  http:\n    port: 8080 # default\n    banner: |\n      key: not a key\n  name: \"api\"\nsteps:\n  - name: build\n    run: make\n---\nother: 1\n";
+        let keys = extract_keys(source, "yaml");
+        assert_eq!(
+            names_and_lines(&keys),
+            vec![
+                ("server", 2),
+                ("server.http", 3),
+                ("server.http.port", 4),
+                ("server.http.banner", 5),
+                ("server.name", 7),
+                ("steps", 8),
+                ("steps.name", 9),
+                ("steps.run", 10),
+                ("other", 12),
+            ]
+        );
+        assert_eq!(keys[2].column, 5);
+        assert_eq!(keys[2].scope.as_deref(), Some("server.http"));
+        assert_eq!(keys[0].end_line, 7);
+    }
+
+    #[test]
+    fn json_keys_follow_nesting() {
+        let source = "{\n  // comment\n  \"server\": {\n    \"http\": { \"port\": 8080 },\n    \"tags\": [\"a:b\", {\"id\": 1}]\n  }\n}\n";
+        let keys = extract_keys(source, "json");
+        assert_eq!(
+            names_and_lines(&keys),
+            vec![
+                ("server", 3),
+                ("server.http", 4),
+                ("server.http.port", 4),
+                ("server.tags", 5),
+                ("server.tags.id", 5),
+            ]
+        );
+        assert_eq!(keys[2].column, 15);
+    }
+
+    #[test]
+    fn toml_tables_and_dotted_keys() {
+        let source = "title = \"x\"\n\n[server.http]\nport = 8080\nlimits.max = [\n  1,\n  2,\n]\nnotes = \"\"\"\nfake = 1\n\"\"\"\n\n[[\"bin\"]]\nname = \"cli\"\n";
+        let keys = extract_keys(source, "toml");
+        assert_eq!(
+            names_and_lines(&keys),
+            vec![
+                ("title", 1),
+                ("server.http", 3),
+                ("server.http.port", 4),
+                ("server.http.limits.max", 5),
+                ("server.http.notes", 9),
+                ("bin", 13),
+                ("bin.name", 14),
+            ]
+        );
+        assert_eq!(keys[3].scope.as_deref(), Some("server.http.limits"));
+    }
+}
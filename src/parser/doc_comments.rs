@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Doc comments attached to symbols: rustdoc (`///`), Python docstrings,
+//! JSDoc/Javadoc/Doxygen blocks (`/** ... */`), and the `//` or `#` comment
+//! runs Go, C/C++, and Ruby use for documentation.
+
+use crate::parser::symbols::{Symbol, SymbolKind};
+
+/// Longest doc text kept per symbol.
+const MAX_DOC_CHARS: usize = 2000;
+/// Lines scanned for the end of a multi-line Python signature.
+const MAX_SIGNATURE_LINES: usize = 20;
+
+/// Fill `doc` for every symbol in `source`.
+pub fn attach_doc_comments(source: &str, language: &str, symbols: &mut [Symbol]) {
+    let lines: Vec<&str> = source.lines().collect();
+    for symbol in symbols {
+        if symbol.kind == SymbolKind::Key || symbol.is_anonymous() {
+            continue;
+        }
+        symbol.doc = doc_comment(&lines, language, symbol.line);
+    }
+}
+
+/// Doc comment for the symbol declared on 1-based `line`.
+pub fn doc_comment(lines: &[&str], language: &str, line: usize) -> Option<String> {
+    let idx = line.checked_sub(1).filter(|idx| *idx < lines.len())?;
+    let doc = if language == "python" {
+        python_docstring(lines, idx)
+    } else {
+        leading_comment(lines, language, idx)
+    }?;
+    let doc = doc.trim();
+    if doc.is_empty() {
+        return None;
+    }
+    Some(match doc.char_indices().nth(MAX_DOC_CHARS) {
+        Some((cut, _)) => doc[..cut].to_string(),
+        None => doc.to_string(),
+    })
+}
+
+/// Comment lines directly above the declaration, skipping attributes and
+/// annotations between the comment and the symbol.
+fn leading_comment(lines: &[&str], language: &str, idx: usize) -> Option<String> {
+    let line_prefix = line_doc_prefix(language);
+    let mut collected: Vec<String> = Vec::new();
+    let mut cursor = idx;
+    while cursor > 0 {
+        cursor -= 1;
+        let trimmed = lines[cursor].trim();
+        if collected.is_empty() && is_attribute_line(trimmed, language) {
+            continue;
+        }
+        if collected.is_empty() && trimmed.ends_with("*/") {
+            return block_comment(lines, language, cursor);
+        }
+        let Some(prefix) = line_prefix.filter(|prefix| is_line_doc(trimmed, prefix)) else {
+            break;
+        };
+        let text = &trimmed[prefix.len()..];
+        collected.push(
+            text.strip_prefix(' ')
+                .unwrap_or(text)
+                .trim_end()
+                .to_string(),
+        );
+    }
+    if collected.is_empty() {
+        return None;
+    }
+    collected.reverse();
+    Some(collected.join("\n"))
+}
+
+/// `/** ... */` (or any `/* ... */` in C/C++) ending on line `end`.
+fn block_comment(lines: &[&str], language: &str, end: usize) -> Option<String> {
+    let start = (0..=end).rev().find(|&idx| lines[idx].contains("/*"))?;
+    let opening = lines[start].trim_start();
+    let is_doc = opening.starts_with("/**") || matches!(language, "c" | "cpp");
+    if !is_doc || !opening.starts_with("/*") {
+        return None;
+    }
+
+    let mut text: Vec<String> = Vec::new();
+    for (offset, raw) in lines[start..=end].iter().enumerate() {
+        let mut line = raw.trim();
+        if offset == 0 {
+            line = line.trim_start_matches("/*").trim_start_matches('*');
+        }
+        if offset == end - start {
+            line = line.trim_end_matches("*/");
+        }
+        let line = line.trim_start().trim_start_matches('*');
+        text.push(
+            line.strip_prefix(' ')
+                .unwrap_or(line)
+                .trim_end()
+                .to_string(),
+        );
+    }
+    Some(text.join("\n"))
+}
+
+fn line_doc_prefix(language: &str) -> Option<&'static str> {
+    match language {
+        "rust" => Some("///"),
+        "go" | "c" | "cpp" => Some("//"),
+        "ruby" | "shell" | "bash" => Some("#"),
+        _ => None,
+    }
+}
+
+fn is_line_doc(line: &str, prefix: &str) -> bool {
+    // `////` is a plain rustdoc-excluded comment; `#!` is a shebang.
+    line.starts_with(prefix) && !line.starts_with("////") && !line.starts_with("#!")
+}
+
+fn is_attribute_line(line: &str, language: &str) -> bool {
+    match language {
+        "rust" => line.starts_with("#[") || line.starts_with("#!["),
+        "java" | "kotlin" | "typescript" | "javascript" | "tsx" => line.starts_with('@'),
+        "csharp" => line.starts_with('['),
+        _ => false,
+    }
+}
+
+/// The string literal opening a `def` or `class` body.
+fn python_docstring(lines: &[&str], idx: usize) -> Option<String> {
+    let header = (idx..lines.len()).find(|&i| !lines[i].trim_start().starts_with('@'))?;
+    let first = lines[header].trim_start();
+    if !(first.starts_with("def ")
+        || first.starts_with("async def ")
+        || first.starts_with("class "))
+    {
+        return None;
+    }
+    let header_end = (header..lines.len().min(header + MAX_SIGNATURE_LINES))
+        .find(|&i| code_part(lines[i]).trim_end().ends_with(':'))?;
+    let body = (header_end + 1..lines.len()).find(|&i| !lines[i].trim().is_empty())?;
+
+    let opening = lines[body]
+        .trim_start()
+        .trim_start_matches(['r', 'R', 'u', 'U', 'b', 'B']);
+    let quote = ["\"\"\"", "'''", "\"", "'"]
+        .into_iter()
+        .find(|quote| opening.starts_with(quote))?;
+    let rest = &opening[quote.len()..];
+    if let Some(close) = rest.find(quote) {
+        return Some(rest[..close].to_string());
+    }
+    if quote.len() == 1 {
+        return None;
+    }
+
+    let mut text = vec![rest.trim().to_string()];
+    for line in &lines[body + 1..] {
+        if let Some(close) = line.find(quote) {
+            text.push(line[..close].trim().to_string());
+            return Some(text.join("\n"));
+        }
+        text.push(line.trim().to_string());
+    }
+    None
+}
+
+/// Line text before a trailing `#` comment (ignoring `#` inside strings).
+fn code_part(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    for (idx, c) in line.char_indices() {
+        match (quote, c) {
+            (Some(open), _) if c == open => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..idx],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::symbols::SymbolExtractor;
+
+    fn docs(source: &str, language: &str) -> Vec<(String, Option<String>)> {
+        SymbolExtractor::new()
+            .extract(source, language)
+            .expect("parse")
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.doc))
+            .collect()
+    }
+
+    #[test]
+    fn rustdoc_skips_attributes_and_ignores_plain_comments() {
+        let source = "/// Parses the config.\n///\n/// Returns defaults on error.\n#[inline]\npub fn parse() {}\n\n// not a doc\nfn other() {}\n";
+        assert_eq!(
+            docs(source, "rust"),
+            vec![
+                (
+                    "parse".to_string(),
+                    Some("Parses the config.\n\nReturns defaults on error.".to_string())
+                ),
+                ("other".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_docstrings_follow_multiline_signatures() {
+        let source = "class Cache:\n    '''LRU cache.'''\n\n    def get(\n        self, key,\n    ):  # lookup\n        \"\"\"Return the cached value\n        or None.\n        \"\"\"\n        return None\n";
+        let found = docs(source, "python");
+        assert!(found.contains(&("Cache".to_string(), Some("LRU cache.".to_string()))));
+        assert!(found.contains(&(
+            "get".to_string(),
+            Some("Return the cached value\nor None.".to_string())
+        )));
+    }
+
+    #[test]
+    fn jsdoc_and_javadoc_blocks_are_cleaned() {
+        let js = "/**\n * Retry a request with backoff.\n * @param {number} n attempts\n */\nexport function retry(n) {}\n/* plain block */\nfunction other() {}\n";
+        assert_eq!(
+            docs(js, "javascript"),
+            vec![
+                (
+                    "retry".to_string(),
+                    Some("Retry a request with backoff.\n@param {number} n attempts".to_string())
+                ),
+                ("other".to_string(), None),
+            ]
+        );
+
+        let java =
+            "class A {\n    /** Opens the pool. */\n    @Override\n    public void open() {}\n}\n";
+        let found = docs(java, "java");
+        assert!(found.contains(&("open".to_string(), Some("Opens the pool.".to_string()))));
+    }
+}
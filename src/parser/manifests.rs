@@ -0,0 +1,635 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Dependency manifests and lockfiles as structured records.
+//!
+//! Manifests (`Cargo.toml`, `package.json`, `pyproject.toml`) declare
+//! dependencies with a version requirement; lockfiles (`Cargo.lock`,
+//! `package-lock.json`, `yarn.lock`, `poetry.lock`, `uv.lock`) pin the
+//! versions that were resolved. Declarations are located with the config key
+//! scanner so each one carries the line it is written on.
+
+use anyhow::Result;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use toml::{Table, Value as TomlValue};
+
+use crate::parser::config_keys;
+
+/// A file this module can read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestKind {
+    CargoToml,
+    CargoLock,
+    PackageJson,
+    PackageLock,
+    YarnLock,
+    Pyproject,
+    PoetryLock,
+    UvLock,
+}
+
+impl ManifestKind {
+    /// Kind of the file called `file_name`, if it is a manifest or lockfile.
+    pub fn of(file_name: &str) -> Option<Self> {
+        Some(match file_name {
+            "Cargo.toml" => Self::CargoToml,
+            "Cargo.lock" => Self::CargoLock,
+            "package.json" => Self::PackageJson,
+            "package-lock.json" | "npm-shrinkwrap.json" => Self::PackageLock,
+            "yarn.lock" => Self::YarnLock,
+            "pyproject.toml" => Self::Pyproject,
+            "poetry.lock" => Self::PoetryLock,
+            "uv.lock" => Self::UvLock,
+            _ => return None,
+        })
+    }
+
+    pub fn is_lockfile(self) -> bool {
+        !matches!(self, Self::CargoToml | Self::PackageJson | Self::Pyproject)
+    }
+
+    /// Package registry, named as in `cgrep packages`.
+    pub fn ecosystem(self) -> &'static str {
+        match self {
+            Self::CargoToml | Self::CargoLock => "crates.io",
+            Self::PackageJson | Self::PackageLock | Self::YarnLock => "npm",
+            Self::Pyproject | Self::PoetryLock | Self::UvLock => "pypi",
+        }
+    }
+}
+
+/// Where a declared dependency comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencySource {
+    Registry,
+    Git,
+    /// A local path or workspace package.
+    Path,
+    /// Cargo `workspace = true`: defined in `[workspace.dependencies]`.
+    Workspace,
+}
+
+impl DependencySource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Registry => "registry",
+            Self::Git => "git",
+            Self::Path => "path",
+            Self::Workspace => "workspace",
+        }
+    }
+}
+
+/// One dependency declared in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclaredDependency {
+    /// Package name (the real one, not a Cargo rename or npm alias).
+    pub name: String,
+    /// Version requirement as written (`^1.2`, `>=2.0`).
+    pub requirement: Option<String>,
+    /// `normal`, `dev`, `build`, `peer`, `optional`, `group`, or `workspace`
+    /// (a Cargo `[workspace.dependencies]` entry).
+    pub kind: &'static str,
+    /// Extra or dependency group the declaration belongs to.
+    pub group: Option<String>,
+    pub source: DependencySource,
+    pub optional: bool,
+    pub line: usize,
+}
+
+/// One package version pinned by a lockfile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+}
+
+/// Dependencies declared in a manifest, in file order.
+pub fn parse_manifest(kind: ManifestKind, content: &str) -> Result<Vec<DeclaredDependency>> {
+    let mut deps = match kind {
+        ManifestKind::CargoToml => cargo_manifest(content)?,
+        ManifestKind::PackageJson => package_json(content)?,
+        ManifestKind::Pyproject => pyproject(content)?,
+        _ => Vec::new(),
+    };
+    deps.sort_by_key(|dep| dep.line);
+    Ok(deps)
+}
+
+/// Packages pinned by a lockfile.
+pub fn parse_lockfile(kind: ManifestKind, content: &str) -> Result<Vec<LockedPackage>> {
+    match kind {
+        ManifestKind::CargoLock | ManifestKind::PoetryLock | ManifestKind::UvLock => {
+            toml_lockfile(content)
+        }
+        ManifestKind::PackageLock => package_lock(content),
+        ManifestKind::YarnLock => Ok(yarn_lock(content)),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Python package names compare case-insensitively, with `-`, `_`, and `.`
+/// interchangeable (PEP 503).
+pub fn normalize_python_name(name: &str) -> String {
+    name.to_ascii_lowercase().replace(['_', '.'], "-")
+}
+
+/// First line of every dotted key path in a TOML or JSON file, including
+/// the paths only spelled out as a prefix (`serde.workspace = true`).
+fn key_lines(content: &str, language: &str) -> HashMap<String, usize> {
+    let mut lines = HashMap::new();
+    for key in config_keys::extract_keys(content, language) {
+        for (idx, _) in key.name.match_indices('.') {
+            lines.entry(key.name[..idx].to_string()).or_insert(key.line);
+        }
+        lines.entry(key.name).or_insert(key.line);
+    }
+    lines
+}
+
+fn line_of(lines: &HashMap<String, usize>, path: &[&str]) -> usize {
+    lines.get(&path.join(".")).copied().unwrap_or(1)
+}
+
+/// Line of an array item spelled `"item"` or `'item'`, searching from the
+/// line of the array's key.
+fn item_line(content: &str, from: usize, item: &str) -> usize {
+    content
+        .lines()
+        .enumerate()
+        .skip(from.saturating_sub(1))
+        .find(|(_, line)| {
+            line.contains(&format!("\"{item}\"")) || line.contains(&format!("'{item}'"))
+        })
+        .map_or(from, |(idx, _)| idx + 1)
+}
+
+fn cargo_manifest(content: &str) -> Result<Vec<DeclaredDependency>> {
+    let manifest: Table = content.parse()?;
+    let lines = key_lines(content, "toml");
+    let mut deps = Vec::new();
+    let sections = [
+        ("dependencies", "normal"),
+        ("dev-dependencies", "dev"),
+        ("build-dependencies", "build"),
+    ];
+    for (section, kind) in sections {
+        cargo_section(&manifest, &[section], kind, &lines, &mut deps);
+    }
+    if let Some(targets) = manifest.get("target").and_then(TomlValue::as_table) {
+        for (cfg, target) in targets {
+            let Some(target) = target.as_table() else {
+                continue;
+            };
+            for (section, kind) in sections {
+                cargo_section(target, &["target", cfg, section], kind, &lines, &mut deps);
+            }
+        }
+    }
+    if let Some(workspace) = manifest.get("workspace").and_then(TomlValue::as_table) {
+        cargo_section(
+            workspace,
+            &["workspace", "dependencies"],
+            "workspace",
+            &lines,
+            &mut deps,
+        );
+    }
+    Ok(deps)
+}
+
+/// Entries of the table at the last segment of `path` inside `parent`.
+fn cargo_section(
+    parent: &Table,
+    path: &[&str],
+    kind: &'static str,
+    lines: &HashMap<String, usize>,
+    deps: &mut Vec<DeclaredDependency>,
+) {
+    let Some(section) = path
+        .last()
+        .and_then(|name| parent.get(*name))
+        .and_then(TomlValue::as_table)
+    else {
+        return;
+    };
+    for (key, spec) in section {
+        let mut key_path = path.to_vec();
+        key_path.push(key);
+        let line = line_of(lines, &key_path);
+        let mut dep = DeclaredDependency {
+            name: key.clone(),
+            requirement: None,
+            kind,
+            group: None,
+            source: DependencySource::Registry,
+            optional: false,
+            line,
+        };
+        match spec {
+            TomlValue::String(requirement) => dep.requirement = Some(requirement.clone()),
+            TomlValue::Table(spec) => {
+                let text = |field: &str| spec.get(field).and_then(TomlValue::as_str);
+                if let Some(package) = text("package") {
+                    dep.name = package.to_string();
+                }
+                dep.requirement = text("version").map(str::to_string);
+                dep.optional = spec.get("optional").and_then(TomlValue::as_bool) == Some(true);
+                dep.source = if spec.get("workspace").and_then(TomlValue::as_bool) == Some(true) {
+                    DependencySource::Workspace
+                } else if text("path").is_some() {
+                    DependencySource::Path
+                } else if text("git").is_some() {
+                    DependencySource::Git
+                } else {
+                    DependencySource::Registry
+                };
+            }
+            _ => continue,
+        }
+        deps.push(dep);
+    }
+}
+
+fn package_json(content: &str) -> Result<Vec<DeclaredDependency>> {
+    let manifest: JsonValue = serde_json::from_str(content)?;
+    let lines = key_lines(content, "json");
+    let mut deps = Vec::new();
+    for (section, kind) in [
+        ("dependencies", "normal"),
+        ("devDependencies", "dev"),
+        ("peerDependencies", "peer"),
+        ("optionalDependencies", "optional"),
+    ] {
+        let Some(entries) = manifest.get(section).and_then(JsonValue::as_object) else {
+            continue;
+        };
+        for (key, spec) in entries {
+            let Some(spec) = spec.as_str() else {
+                continue;
+            };
+            let (name, requirement) = match spec.strip_prefix("npm:") {
+                // `"alias": "npm:real-name@^1.0"`
+                Some(aliased) => match aliased.get(1..).and_then(|rest| rest.find('@')) {
+                    Some(at) => (&aliased[..at + 1], &aliased[at + 2..]),
+                    None => (aliased, ""),
+                },
+                None => (key.as_str(), spec),
+            };
+            deps.push(DeclaredDependency {
+                name: name.to_string(),
+                requirement: (!requirement.is_empty()).then(|| requirement.to_string()),
+                kind,
+                group: None,
+                source: npm_source(requirement),
+                optional: kind == "optional",
+                line: line_of(&lines, &[section, key]),
+            });
+        }
+    }
+    Ok(deps)
+}
+
+fn npm_source(spec: &str) -> DependencySource {
+    const LOCAL: [&str; 4] = ["workspace:", "file:", "link:", "portal:"];
+    const GIT: [&str; 5] = ["git+", "git:", "github:", "gitlab:", "bitbucket:"];
+    if LOCAL.iter().any(|prefix| spec.starts_with(prefix)) || spec.starts_with('.') {
+        DependencySource::Path
+    } else if GIT.iter().any(|prefix| spec.starts_with(prefix))
+        // `user/repo` is a GitHub shorthand.
+        || (spec.contains('/') && !spec.contains(':') && !spec.starts_with('@'))
+    {
+        DependencySource::Git
+    } else {
+        DependencySource::Registry
+    }
+}
+
+fn pyproject(content: &str) -> Result<Vec<DeclaredDependency>> {
+    let manifest: Table = content.parse()?;
+    let lines = key_lines(content, "toml");
+    let mut deps = Vec::new();
+    let table_at = |path: &[&str]| -> Option<&Table> {
+        let mut table = &manifest;
+        for segment in path {
+            table = table.get(*segment)?.as_table()?;
+        }
+        Some(table)
+    };
+
+    // PEP 621, PEP 735, and PEP 518 lists of PEP 508 requirements.
+    let mut requirement_list = |path: &[&str], kind: &'static str, group: Option<&str>| {
+        let (parent, key) = path.split_at(path.len() - 1);
+        let Some(items) = table_at(parent)
+            .and_then(|table| table.get(key[0]))
+            .and_then(TomlValue::as_array)
+        else {
+            return;
+        };
+        let from = line_of(&lines, path);
+        for item in items.iter().filter_map(TomlValue::as_str) {
+            let Some((name, requirement, source)) = parse_pep508(item) else {
+                continue;
+            };
+            deps.push(DeclaredDependency {
+                name,
+                requirement,
+                kind,
+                group: group.map(str::to_string),
+                source,
+                optional: kind == "optional",
+                line: item_line(content, from, item),
+            });
+        }
+    };
+    requirement_list(&["project", "dependencies"], "normal", None);
+    if let Some(extras) = table_at(&["project", "optional-dependencies"]) {
+        for extra in extras.keys() {
+            requirement_list(
+                &["project", "optional-dependencies", extra],
+                "optional",
+                Some(extra),
+            );
+        }
+    }
+    if let Some(groups) = table_at(&["dependency-groups"]) {
+        for group in groups.keys() {
+            requirement_list(&["dependency-groups", group], "group", Some(group));
+        }
+    }
+    requirement_list(&["build-system", "requires"], "build", None);
+
+    // Poetry tables of `name = "^1.0"` or `name = { version = ... }`.
+    let mut poetry_tables: Vec<(Vec<&str>, &'static str, Option<&str>)> = vec![
+        (vec!["tool", "poetry", "dependencies"], "normal", None),
+        (vec!["tool", "poetry", "dev-dependencies"], "dev", None),
+    ];
+    if let Some(groups) = table_at(&["tool", "poetry", "group"]) {
+        for group in groups.keys() {
+            poetry_tables.push((
+                vec!["tool", "poetry", "group", group, "dependencies"],
+                "group",
+                Some(group),
+            ));
+        }
+    }
+    for (path, kind, group) in poetry_tables {
+        let Some(table) = table_at(&path) else {
+            continue;
+        };
+        for (name, spec) in table {
+            if name == "python" {
+                continue;
+            }
+            let mut key_path = path.clone();
+            key_path.push(name);
+            let mut dep = DeclaredDependency {
+                name: name.clone(),
+                requirement: None,
+                kind,
+                group: group.map(str::to_string),
+                source: DependencySource::Registry,
+                optional: false,
+                line: line_of(&lines, &key_path),
+            };
+            match spec {
+                TomlValue::String(requirement) => dep.requirement = Some(requirement.clone()),
+                TomlValue::Table(spec) => {
+                    let text = |field: &str| spec.get(field).and_then(TomlValue::as_str);
+                    dep.requirement = text("version").map(str::to_string);
+                    dep.optional = spec.get("optional").and_then(TomlValue::as_bool) == Some(true);
+                    dep.source = if text("path").is_some() {
+                        DependencySource::Path
+                    } else if text("git").is_some() {
+                        DependencySource::Git
+                    } else {
+                        DependencySource::Registry
+                    };
+                }
+                _ => continue,
+            }
+            deps.push(dep);
+        }
+    }
+    Ok(deps)
+}
+
+/// Name, version specifier, and source of a PEP 508 requirement such as
+/// `requests[socks]>=2.0; python_version < "3.8"` or `pkg @ git+https://...`.
+fn parse_pep508(raw: &str) -> Option<(String, Option<String>, DependencySource)> {
+    let spec = raw.split(';').next()?.trim();
+    let name_len = spec
+        .find(|ch: char| !(ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.')))
+        .unwrap_or(spec.len());
+    if name_len == 0 {
+        return None;
+    }
+    let name = spec[..name_len].to_string();
+    let mut rest = spec[name_len..].trim_start();
+    if rest.starts_with('[') {
+        rest = rest
+            .find(']')
+            .map_or("", |end| rest[end + 1..].trim_start());
+    }
+    if let Some(url) = rest.strip_prefix('@') {
+        let url = url.trim();
+        let source = if url.starts_with("git+") {
+            DependencySource::Git
+        } else if url.starts_with("file:") {
+            DependencySource::Path
+        } else {
+            DependencySource::Registry
+        };
+        return Some((name, None, source));
+    }
+    let requirement = rest.trim_start_matches('(').trim_end_matches(')').trim();
+    Some((
+        name,
+        (!requirement.is_empty()).then(|| requirement.to_string()),
+        DependencySource::Registry,
+    ))
+}
+
+/// `[[package]]` tables with `name` and `version` (Cargo, Poetry, uv).
+fn toml_lockfile(content: &str) -> Result<Vec<LockedPackage>> {
+    let lockfile: Table = content.parse()?;
+    let packages = lockfile
+        .get("package")
+        .and_then(TomlValue::as_array)
+        .map(|packages| {
+            packages
+                .iter()
+                .filter_map(|package| {
+                    let name = package.get("name")?.as_str()?;
+                    let version = package.get("version")?.as_str()?;
+                    Some(LockedPackage {
+                        name: name.to_string(),
+                        version: version.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(packages)
+}
+
+fn package_lock(content: &str) -> Result<Vec<LockedPackage>> {
+    let lockfile: JsonValue = serde_json::from_str(content)?;
+    let version_of = |entry: &JsonValue| entry.get("version")?.as_str().map(str::to_string);
+    // lockfileVersion 2 and 3 key installed packages by their path.
+    if let Some(packages) = lockfile.get("packages").and_then(JsonValue::as_object) {
+        return Ok(packages
+            .iter()
+            .filter_map(|(path, entry)| {
+                let name = path.strip_prefix("node_modules/")?;
+                if name.contains("/node_modules/") {
+                    return None;
+                }
+                Some(LockedPackage {
+                    name: name.to_string(),
+                    version: version_of(entry)?,
+                })
+            })
+            .collect());
+    }
+    Ok(lockfile
+        .get("dependencies")
+        .and_then(JsonValue::as_object)
+        .map(|dependencies| {
+            dependencies
+                .iter()
+                .filter_map(|(name, entry)| {
+                    Some(LockedPackage {
+                        name: name.clone(),
+                        version: version_of(entry)?,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Yarn classic and Berry lockfiles: `"name@^1.0", name@^1.1:` headers
+/// followed by an indented `version` line.
+fn yarn_lock(content: &str) -> Vec<LockedPackage> {
+    let mut packages = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    for line in content.lines() {
+        if line.trim_start().starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            names = line
+                .trim_end_matches(':')
+                .split(", ")
+                .filter_map(|spec| {
+                    let spec = spec.trim().trim_matches('"');
+                    let at = spec.get(1..)?.find('@')? + 1;
+                    Some(spec[..at].to_string())
+                })
+                .collect();
+            names.dedup();
+            continue;
+        }
+        let Some(version) = line.trim().strip_prefix("version") else {
+            continue;
+        };
+        let version = version.trim_start_matches(':').trim().trim_matches('"');
+        for name in names.drain(..) {
+            packages.push(LockedPackage {
+                name,
+                version: version.to_string(),
+            });
+        }
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(deps: &[DeclaredDependency]) -> Vec<(&str, Option<&str>, &str, &str, usize)> {
+        deps.iter()
+            .map(|dep| {
+                (
+                    dep.name.as_str(),
+                    dep.requirement.as_deref(),
+                    dep.kind,
+                    dep.source.as_str(),
+                    dep.line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cargo_manifest_reads_every_dependency_table() {
+        let manifest = "[package]\nname = \"app\"\n\n[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\nlocal = { path = \"../local\" }\nanyhow.workspace = true\n\n[dev-dependencies.tempfile]\nversion = \"3\"\n\n[target.'cfg(unix)'.dependencies]\nlibc = \"0.2\"\n\n[workspace.dependencies]\nanyhow = \"1.0\"\n";
+        assert_eq!(
+            summary(&parse_manifest(ManifestKind::CargoToml, manifest).unwrap()),
+            [
+                ("serde", Some("1"), "normal", "registry", 5),
+                ("local", None, "normal", "path", 6),
+                ("anyhow", None, "normal", "workspace", 7),
+                ("tempfile", Some("3"), "dev", "registry", 9),
+                ("libc", Some("0.2"), "normal", "registry", 13),
+                ("anyhow", Some("1.0"), "workspace", "registry", 16),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_json_and_pyproject_declarations() {
+        let package = "{\n  \"dependencies\": {\n    \"react\": \"^18.2.0\",\n    \"shared\": \"workspace:*\",\n    \"old\": \"npm:left-pad@^1.3.0\"\n  },\n  \"devDependencies\": {\n    \"@types/node\": \"^20\"\n  }\n}\n";
+        assert_eq!(
+            summary(&parse_manifest(ManifestKind::PackageJson, package).unwrap()),
+            [
+                ("react", Some("^18.2.0"), "normal", "registry", 3),
+                ("shared", Some("workspace:*"), "normal", "path", 4),
+                ("left-pad", Some("^1.3.0"), "normal", "registry", 5),
+                ("@types/node", Some("^20"), "dev", "registry", 8),
+            ]
+        );
+
+        let pyproject = "[project]\nname = \"app\"\ndependencies = [\n  \"requests[socks]>=2.0; python_version < '3.12'\",\n  \"tool @ git+https://example.com/tool.git\",\n]\n\n[project.optional-dependencies]\ndocs = [\"sphinx\"]\n\n[tool.poetry.group.test.dependencies]\npytest = \"^8\"\n";
+        let deps = parse_manifest(ManifestKind::Pyproject, pyproject).unwrap();
+        assert_eq!(
+            summary(&deps),
+            [
+                ("requests", Some(">=2.0"), "normal", "registry", 4),
+                ("tool", None, "normal", "git", 5),
+                ("sphinx", None, "optional", "registry", 9),
+                ("pytest", Some("^8"), "group", "registry", 12),
+            ]
+        );
+        assert_eq!(deps[2].group.as_deref(), Some("docs"));
+    }
+
+    #[test]
+    fn lockfiles_pin_versions() {
+        let cargo = "version = 3\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.210\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n";
+        let npm = "{\"lockfileVersion\": 3, \"packages\": {\"\": {}, \"node_modules/react\": {\"version\": \"18.3.1\"}, \"node_modules/a/node_modules/b\": {\"version\": \"1.0.0\"}}}";
+        let yarn = "# yarn lockfile v1\n\n\"@babel/core@^7.0.0\", \"@babel/core@^7.1.0\":\n  version \"7.24.0\"\n  resolved \"https://registry.yarnpkg.com/x\"\n";
+        let pinned = |kind, content| -> Vec<(String, String)> {
+            parse_lockfile(kind, content)
+                .unwrap()
+                .into_iter()
+                .map(|package| (package.name, package.version))
+                .collect()
+        };
+        let pair = |name: &str, version: &str| (name.to_string(), version.to_string());
+        assert_eq!(
+            pinned(ManifestKind::CargoLock, cargo),
+            [pair("serde", "1.0.210")]
+        );
+        assert_eq!(
+            pinned(ManifestKind::PackageLock, npm),
+            [pair("react", "18.3.1")]
+        );
+        assert_eq!(
+            pinned(ManifestKind::YarnLock, yarn),
+            [pair("@babel/core", "7.24.0")]
+        );
+    }
+}
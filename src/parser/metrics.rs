@@ -0,0 +1,427 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Cheap size and complexity metrics for functions, methods, and closures,
+//! read from the syntax tree while symbols are extracted.
+//!
+//! - `lines`: lines spanned by the definition.
+//! - `params`: parameters, without receivers (from the signature).
+//! - `nesting`: deepest nesting of branches, loops, `match`/`switch`, and
+//!   `try` in the body; an `else if` stays at the depth of its `if`.
+//! - `complexity`: 1 plus one per branch, loop, case or match arm, `catch`,
+//!   conditional expression, and `&&`/`||` operand, in the spirit of
+//!   cyclomatic complexity.
+//!
+//! Nested functions, closures, and classes are measured on their own and do
+//! not count toward the enclosing symbol.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use crate::parser::signatures::Signature;
+
+/// Metrics of one function-like symbol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolMetrics {
+    pub lines: usize,
+    pub params: usize,
+    pub nesting: usize,
+    pub complexity: usize,
+}
+
+/// A metric that results can be filtered or ranked by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Complexity,
+    Lines,
+    Params,
+    Nesting,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 4] = [
+        Metric::Complexity,
+        Metric::Lines,
+        Metric::Params,
+        Metric::Nesting,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Complexity => "complexity",
+            Metric::Lines => "lines",
+            Metric::Params => "params",
+            Metric::Nesting => "nesting",
+        }
+    }
+
+    pub fn of(self, metrics: &SymbolMetrics) -> usize {
+        match self {
+            Metric::Complexity => metrics.complexity,
+            Metric::Lines => metrics.lines,
+            Metric::Params => metrics.params,
+            Metric::Nesting => metrics.nesting,
+        }
+    }
+}
+
+/// A `--metric` condition such as `complexity>=10` or `params>5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricFilter {
+    pub metric: Metric,
+    op: Comparison,
+    value: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+}
+
+impl MetricFilter {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.trim();
+        let Some(split) = raw.find(['>', '<', '=']) else {
+            bail!("Invalid metric filter `{raw}`: expected e.g. `complexity>=10`");
+        };
+        let (name, rest) = raw.split_at(split);
+        let name = name.trim();
+        let Some(metric) = Metric::ALL.into_iter().find(|metric| metric.name() == name) else {
+            bail!("Unknown metric `{name}` (available: complexity, lines, params, nesting)");
+        };
+        let (op, value) = if let Some(value) = rest.strip_prefix(">=") {
+            (Comparison::GreaterOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix("<=") {
+            (Comparison::LessOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix('>') {
+            (Comparison::Greater, value)
+        } else if let Some(value) = rest.strip_prefix('<') {
+            (Comparison::Less, value)
+        } else {
+            let value = rest.trim_start_matches('=');
+            (Comparison::Equal, value)
+        };
+        let Ok(value) = value.trim().parse() else {
+            bail!(
+                "Invalid metric filter `{raw}`: `{}` is not a count",
+                value.trim()
+            );
+        };
+        Ok(Self { metric, op, value })
+    }
+
+    pub fn matches(&self, metrics: &SymbolMetrics) -> bool {
+        let actual = self.metric.of(metrics);
+        match self.op {
+            Comparison::Greater => actual > self.value,
+            Comparison::GreaterOrEqual => actual >= self.value,
+            Comparison::Less => actual < self.value,
+            Comparison::LessOrEqual => actual <= self.value,
+            Comparison::Equal => actual == self.value,
+        }
+    }
+}
+
+impl std::fmt::Display for MetricFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self.op {
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "=",
+        };
+        write!(f, "{}{}{}", self.metric.name(), op, self.value)
+    }
+}
+
+/// Metrics of the function, method, or closure defined by `node`.
+pub fn measure(node: Node, source: &[u8], signature: Option<&Signature>) -> SymbolMetrics {
+    let mut metrics = SymbolMetrics {
+        lines: node.end_position().row - node.start_position().row + 1,
+        params: signature.map_or(0, |signature| signature.params.len()),
+        nesting: 0,
+        complexity: 1,
+    };
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk(child, source, 0, &mut metrics);
+    }
+    metrics
+}
+
+fn walk(node: Node, source: &[u8], depth: usize, metrics: &mut SymbolMetrics) {
+    // Keywords are anonymous nodes that share kinds like `if` and `for`.
+    if !node.is_named() {
+        return;
+    }
+    if is_nested_definition(node.kind()) {
+        return;
+    }
+    if is_decision(node, source) {
+        metrics.complexity += 1;
+    }
+    let depth = if nests(node) && !is_else_if(node) {
+        depth + 1
+    } else {
+        depth
+    };
+    metrics.nesting = metrics.nesting.max(depth);
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk(child, source, depth, metrics);
+    }
+}
+
+fn is_nested_definition(kind: &str) -> bool {
+    matches!(
+        kind,
+        "function_item"
+            | "closure_expression"
+            | "function_definition"
+            | "lambda"
+            | "function_declaration"
+            | "function_expression"
+            | "function"
+            | "generator_function"
+            | "arrow_function"
+            | "generator_function_declaration"
+            | "method_definition"
+            | "class_declaration"
+            | "class_definition"
+            | "method_declaration"
+            | "func_literal"
+            | "lambda_expression"
+            | "method"
+            | "singleton_method"
+            | "class"
+            | "impl_item"
+    )
+}
+
+fn is_branch(kind: &str) -> bool {
+    matches!(
+        kind,
+        "if_statement"
+            | "if_expression"
+            | "if_let_expression"
+            | "if"
+            | "unless"
+            | "if_modifier"
+            | "unless_modifier"
+            | "elif_clause"
+            | "elsif"
+    )
+}
+
+fn is_loop(kind: &str) -> bool {
+    matches!(
+        kind,
+        "for_statement"
+            | "for_in_statement"
+            | "for_expression"
+            | "for_range_loop"
+            | "enhanced_for_statement"
+            | "while_statement"
+            | "while_expression"
+            | "while_let_expression"
+            | "loop_expression"
+            | "do_statement"
+            | "while"
+            | "until"
+            | "for"
+            | "while_modifier"
+            | "until_modifier"
+    )
+}
+
+/// Nodes that add a decision path.
+fn is_decision(node: Node, source: &[u8]) -> bool {
+    let kind = node.kind();
+    if is_branch(kind) || is_loop(kind) {
+        return true;
+    }
+    match kind {
+        "match_arm" | "case_clause" | "switch_case" | "case_statement" | "switch_label"
+        | "expression_case" | "type_case" | "communication_case" | "when" => {
+            // `default:` and `_ =>` arms do not add a path.
+            let text = node.utf8_text(source).unwrap_or_default().trim_start();
+            !(text.starts_with("default") || text.starts_with("_ =>") || text.starts_with("_ if"))
+        }
+        "catch_clause"
+        | "except_clause"
+        | "rescue"
+        | "conditional_expression"
+        | "ternary_expression"
+        | "boolean_operator" => true,
+        "binary_expression" => node
+            .child_by_field_name("operator")
+            .and_then(|op| op.utf8_text(source).ok())
+            .is_some_and(|op| matches!(op, "&&" | "||" | "and" | "or")),
+        _ => false,
+    }
+}
+
+/// Nodes whose body is one level deeper.
+fn nests(node: Node) -> bool {
+    let kind = node.kind();
+    (is_branch(kind)
+        && !matches!(
+            kind,
+            "elif_clause" | "elsif" | "if_modifier" | "unless_modifier"
+        ))
+        || (is_loop(kind) && !matches!(kind, "while_modifier" | "until_modifier"))
+        || matches!(
+            kind,
+            "match_expression"
+                | "match_statement"
+                | "switch_statement"
+                | "switch_expression"
+                | "expression_switch_statement"
+                | "type_switch_statement"
+                | "select_statement"
+                | "case"
+                | "try_statement"
+                | "try_expression"
+                | "begin"
+        )
+}
+
+/// `if` that is the `else` branch of another `if`.
+fn is_else_if(node: Node) -> bool {
+    if !is_branch(node.kind()) {
+        return false;
+    }
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    parent.kind() == "else_clause"
+        || parent.kind() == "else"
+        || (is_branch(parent.kind()) && parent.child_by_field_name("alternative") == Some(node))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::symbols::SymbolExtractor;
+    use proptest::prelude::*;
+
+    fn metrics_of(source: &str, lang: &str, name: &str) -> SymbolMetrics {
+        SymbolExtractor::new()
+            .extract(source, lang)
+            .unwrap()
+            .into_iter()
+            .find(|symbol| symbol.name == name)
+            .and_then(|symbol| symbol.metrics)
+            .unwrap()
+    }
+
+    #[test]
+    fn branches_loops_and_operators_count_toward_complexity() {
+        let rust = "fn route(x: u8, y: bool) -> u8 {\n    if x > 1 && y {\n        for i in 0..x {\n            match i {\n                0 => return 0,\n                1 => return 1,\n                _ => {}\n            }\n        }\n    } else if y {\n        return 2;\n    }\n    let f = |v: u8| if v > 0 { v } else { 0 };\n    f(x)\n}\n";
+        assert_eq!(
+            metrics_of(rust, "rust", "route"),
+            SymbolMetrics {
+                lines: 15,
+                params: 2,
+                nesting: 3,
+                complexity: 7,
+            }
+        );
+
+        let python = "def handle(self, event):\n    try:\n        if event.kind == 'a' or event.retry:\n            pass\n        elif event.kind == 'b':\n            pass\n    except KeyError:\n        pass\n";
+        assert_eq!(
+            metrics_of(python, "python", "handle"),
+            SymbolMetrics {
+                lines: 8,
+                params: 1,
+                nesting: 2,
+                complexity: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn metric_filters_parse_comparisons() {
+        let metrics = SymbolMetrics {
+            lines: 40,
+            params: 3,
+            nesting: 2,
+            complexity: 12,
+        };
+        for (raw, expected) in [
+            ("complexity>=12", true),
+            ("complexity>12", false),
+            ("lines<50", true),
+            ("params=3", true),
+            ("nesting <= 1", false),
+        ] {
+            assert_eq!(
+                MetricFilter::parse(raw).unwrap().matches(&metrics),
+                expected,
+                "{raw}"
+            );
+        }
+        assert!(MetricFilter::parse("depth>2").is_err());
+        assert!(MetricFilter::parse("complexity").is_err());
+        assert!(MetricFilter::parse("lines>many").is_err());
+    }
+
+    /// Operators as written, with the one `Display` prints for each.
+    const OPERATORS: [(&str, &str); 6] = [
+        (">=", ">="),
+        ("<=", "<="),
+        (">", ">"),
+        ("<", "<"),
+        ("=", "="),
+        ("==", "="),
+    ];
+
+    fn padding() -> impl Strategy<Value = String> {
+        proptest::string::string_regex("[ \t]{0,2}").unwrap()
+    }
+
+    proptest! {
+        #[test]
+        fn metric_filters_never_panic(raw in "[a-z<>=! \t0-9.-]{0,16}|\\PC{0,16}") {
+            let _ = MetricFilter::parse(&raw);
+        }
+
+        #[test]
+        fn metric_filters_round_trip_and_compare(
+            metric in proptest::sample::select(Metric::ALL.to_vec()),
+            (written, printed) in proptest::sample::select(OPERATORS.to_vec()),
+            value in 0usize..1_000,
+            actual in 0usize..1_000,
+            pads in proptest::array::uniform4(padding()),
+        ) {
+            let raw = format!(
+                "{}{}{}{written}{}{value}{}",
+                pads[0], metric.name(), pads[1], pads[2], pads[3]
+            );
+            let filter = MetricFilter::parse(&raw).map_err(|err| TestCaseError::fail(err.to_string()))?;
+            let canonical = format!("{}{printed}{value}", metric.name());
+            prop_assert_eq!(filter.to_string(), canonical.clone());
+            prop_assert_eq!(MetricFilter::parse(&canonical).unwrap().to_string(), canonical);
+
+            let metrics = SymbolMetrics {
+                lines: actual,
+                params: actual,
+                nesting: actual,
+                complexity: actual,
+            };
+            let expected = match printed {
+                ">=" => actual >= value,
+                "<=" => actual <= value,
+                ">" => actual > value,
+                "<" => actual < value,
+                _ => actual == value,
+            };
+            prop_assert_eq!(filter.matches(&metrics), expected);
+        }
+    }
+}
@@ -2,5 +2,13 @@
 
 //! Parser module - AST parsing using tree-sitter
 
+pub mod ast;
+pub mod config_keys;
+pub mod doc_comments;
 pub mod languages;
+pub mod manifests;
+pub mod metrics;
+pub mod rust_macros;
+pub mod sfc;
+pub mod signatures;
 pub mod symbols;
@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Symbols that common Rust macros generate, found without expanding them.
+//!
+//! tree-sitter sees a macro body as a flat token tree, so anything a macro
+//! defines is invisible to the regular symbol walk. Three heuristics cover
+//! the usual cases:
+//!
+//! - `#[derive(...)]` adds the trait's method (`Clone` adds `clone`) to the
+//!   type, for the standard and serde traits that have one.
+//! - Item keywords followed by a plain identifier inside a `macro_rules!`
+//!   body or an item-level invocation (`fn helper`, `static ref CACHE`) are
+//!   read as the items the macro defines. Metavariables (`fn $name`) are not
+//!   resolved.
+//! - Item-level invocations (`lazy_static! { ... }` at module or impl level)
+//!   become `macro` symbols named `name!`, next to the `macro` symbols for
+//!   `macro_rules!` definitions.
+//!
+//! Attribute macros such as `#[tokio::main]` keep the item they annotate, so
+//! the regular walk already finds it.
+
+use tree_sitter::Node;
+
+use crate::parser::symbols::{Symbol, SymbolKind};
+
+/// Methods a derive adds, by trait name.
+const DERIVED_METHODS: &[(&str, &str)] = &[
+    ("Clone", "clone"),
+    ("Debug", "fmt"),
+    ("Default", "default"),
+    ("PartialEq", "eq"),
+    ("PartialOrd", "partial_cmp"),
+    ("Ord", "cmp"),
+    ("Hash", "hash"),
+    ("Serialize", "serialize"),
+    ("Deserialize", "deserialize"),
+];
+
+/// Symbols generated by derives and macros anywhere under `root`.
+pub fn expand(root: Node, source: &[u8]) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    visit(root, source, &mut symbols);
+    symbols
+}
+
+fn visit(node: Node, source: &[u8], symbols: &mut Vec<Symbol>) {
+    match node.kind() {
+        "struct_item" | "enum_item" | "union_item" => derived_methods(node, source, symbols),
+        "macro_definition" => defined_items(node, source, symbols),
+        "macro_invocation" if is_item_level(node) => {
+            if let Some(name) = node
+                .child_by_field_name("macro")
+                .and_then(|name| name.utf8_text(source).ok())
+            {
+                symbols.push(symbol(format!("{name}!"), SymbolKind::Macro, node, None));
+            }
+            defined_items(node, source, symbols);
+        }
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        visit(child, source, symbols);
+    }
+}
+
+/// Whether an invocation stands where an item could, so it may define items.
+fn is_item_level(node: Node) -> bool {
+    let mut parent = node.parent();
+    if parent.is_some_and(|p| p.kind() == "expression_statement") {
+        parent = parent.and_then(|p| p.parent());
+    }
+    parent.is_some_and(|p| matches!(p.kind(), "source_file" | "declaration_list"))
+}
+
+/// Trait methods from the `#[derive(...)]` attributes above a type.
+fn derived_methods(item: Node, source: &[u8], symbols: &mut Vec<Symbol>) {
+    let Some(type_name) = item
+        .child_by_field_name("name")
+        .and_then(|name| name.utf8_text(source).ok())
+    else {
+        return;
+    };
+    let mut sibling = item.prev_named_sibling();
+    while let Some(attribute) = sibling.filter(|node| {
+        matches!(
+            node.kind(),
+            "attribute_item" | "line_comment" | "block_comment"
+        )
+    }) {
+        sibling = attribute.prev_named_sibling();
+        let Some(derive) = attribute
+            .named_child(0)
+            .filter(|attr| attr.kind() == "attribute")
+            .filter(|attr| {
+                attr.named_child(0)
+                    .and_then(|path| path.utf8_text(source).ok())
+                    == Some("derive")
+            })
+            .and_then(|attr| attr.child_by_field_name("arguments"))
+        else {
+            continue;
+        };
+        for token in leaves(derive) {
+            let Ok(path) = token.utf8_text(source) else {
+                continue;
+            };
+            // The last identifier of `serde::Serialize` names the trait.
+            let next = token.next_sibling().map(|next| next.kind());
+            if token.kind() != "identifier" || next == Some("::") {
+                continue;
+            }
+            if let Some((_, method)) = DERIVED_METHODS.iter().find(|(name, _)| *name == path) {
+                symbols.push(symbol(
+                    method.to_string(),
+                    SymbolKind::Method,
+                    token,
+                    Some(type_name.to_string()),
+                ));
+            }
+        }
+    }
+}
+
+/// Items spelled out with a literal name inside a macro's token trees.
+fn defined_items(node: Node, source: &[u8], symbols: &mut Vec<Symbol>) {
+    let tokens = leaves(node);
+    for (idx, token) in tokens.iter().enumerate() {
+        let kind = match token.kind() {
+            "fn" => SymbolKind::Function,
+            "struct" | "union" => SymbolKind::Struct,
+            "enum" => SymbolKind::Enum,
+            "trait" => SymbolKind::Trait,
+            "type" => SymbolKind::Type,
+            "const" => SymbolKind::Constant,
+            "static" => SymbolKind::Variable,
+            "mod" => SymbolKind::Module,
+            _ => continue,
+        };
+        let mut rest = tokens[idx + 1..].iter().filter(|next| {
+            // `static ref` (lazy_static) and `static mut` precede the name.
+            !matches!(next.utf8_text(source), Ok("ref" | "mut"))
+        });
+        let Some(name) = rest.next().filter(|next| next.kind() == "identifier") else {
+            continue;
+        };
+        let Ok(text) = name.utf8_text(source) else {
+            continue;
+        };
+        symbols.push(symbol(text.to_string(), kind, *name, None));
+    }
+}
+
+/// Leaf tokens under `node`, in source order.
+fn leaves(node: Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    let mut stack = vec![node];
+    while let Some(current) = stack.pop() {
+        if current.child_count() == 0 {
+            out.push(current);
+            continue;
+        }
+        let mut cursor = current.walk();
+        let children: Vec<Node> = current.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    out
+}
+
+fn symbol(name: String, kind: SymbolKind, node: Node, scope: Option<String>) -> Symbol {
+    Symbol {
+        name,
+        kind,
+        line: node.start_position().row + 1,
+        column: node.start_position().column + 1,
+        end_line: node.end_position().row + 1,
+        byte_start: Some(node.start_byte()),
+        byte_end: Some(node.end_byte()),
+        scope,
+        doc: None,
+        signature: None,
+        decorators: Vec::new(),
+        metrics: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::symbols::SymbolExtractor;
+
+    fn names(symbols: &[Symbol], kind: SymbolKind) -> Vec<String> {
+        symbols
+            .iter()
+            .filter(|symbol| symbol.kind == kind)
+            .map(Symbol::qualified_name)
+            .collect()
+    }
+
+    #[test]
+    fn derives_and_macro_bodies_add_symbols() {
+        let source = r#"
+macro_rules! make_helper {
+    ($name:ident) => {
+        fn generated_helper() -> u32 { 1 }
+        fn $name() {}
+    };
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct Config {
+    port: u16,
+}
+
+lazy_static! {
+    static ref CACHE: u32 = 1;
+}
+
+fn main() {
+    make_helper!(port);
+}
+"#;
+        let symbols = SymbolExtractor::new().extract(source, "rust").unwrap();
+        assert_eq!(
+            names(&symbols, SymbolKind::Macro),
+            ["make_helper", "lazy_static!"]
+        );
+        assert_eq!(
+            names(&symbols, SymbolKind::Method),
+            ["Config::fmt", "Config::clone", "Config::serialize"]
+        );
+        let functions = names(&symbols, SymbolKind::Function);
+        assert!(functions.contains(&"generated_helper".to_string()));
+        assert!(!functions.iter().any(|name| name.contains('$')));
+        assert_eq!(names(&symbols, SymbolKind::Variable), ["CACHE"]);
+    }
+}
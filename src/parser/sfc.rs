@@ -0,0 +1,324 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Single-file component (Vue / Svelte) block splitting.
+//!
+//! `.vue` and `.svelte` files are split into top-level `<script>`, `<template>`
+//! and `<style>` blocks. Script blocks are parsed with the JavaScript or
+//! TypeScript grammar, and each block records where its content starts so
+//! block-relative positions can be mapped back to the file.
+
+/// Kind of top-level SFC block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SfcBlockKind {
+    Script,
+    Template,
+    Style,
+}
+
+/// A top-level block of a single-file component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SfcBlock {
+    pub kind: SfcBlockKind,
+    /// `lang` attribute, lowercased (e.g. `ts`, `scss`).
+    pub lang: Option<String>,
+    /// Byte range of the block content (between the opening and closing tags).
+    pub content_start: usize,
+    pub content_end: usize,
+    /// 1-based file line of `content_start`.
+    pub start_line: usize,
+    /// 0-based column of `content_start` on `start_line`.
+    pub start_column: usize,
+}
+
+impl SfcBlock {
+    pub fn content<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.content_start..self.content_end]
+    }
+
+    /// Grammar used for a script block.
+    pub fn script_language(&self) -> &'static str {
+        match self.lang.as_deref() {
+            Some("ts" | "tsx" | "typescript") => "typescript",
+            _ => "javascript",
+        }
+    }
+
+    /// Map a 1-based line within the block to the 1-based file line.
+    pub fn file_line(&self, block_line: usize) -> usize {
+        self.start_line + block_line.saturating_sub(1)
+    }
+
+    /// Map a 1-based column on `block_line` to the 1-based file column.
+    pub fn file_column(&self, block_line: usize, column: usize) -> usize {
+        if block_line <= 1 {
+            column + self.start_column
+        } else {
+            column
+        }
+    }
+}
+
+/// Languages whose files are split into SFC blocks.
+pub fn is_sfc_language(language: &str) -> bool {
+    matches!(language, "vue" | "svelte")
+}
+
+/// Split a component into its top-level blocks, in source order.
+///
+/// Svelte has no `<template>` wrapper, so for `svelte` the markup between
+/// script and style blocks is reported as template blocks.
+pub fn split_blocks(source: &str, language: &str) -> Vec<SfcBlock> {
+    let mut blocks = Vec::new();
+    let bytes = source.as_bytes();
+    let mut idx = 0;
+
+    while let Some(offset) = source[idx..].find('<') {
+        let tag_start = idx + offset;
+        if source[tag_start..].starts_with("<!--") {
+            idx = source[tag_start..]
+                .find("-->")
+                .map(|end| tag_start + end + 3)
+                .unwrap_or(bytes.len());
+            continue;
+        }
+
+        let Some((kind, name)) = block_tag_at(source, tag_start) else {
+            idx = tag_start + 1;
+            continue;
+        };
+        let Some(open_end) = find_tag_end(bytes, tag_start + 1 + name.len()) else {
+            break;
+        };
+        let attrs = &source[tag_start + 1 + name.len()..open_end];
+        let content_start = open_end + 1;
+        if attrs.trim_end().ends_with('/') {
+            idx = content_start;
+            continue;
+        }
+
+        let (content_end, next) = match kind {
+            SfcBlockKind::Template => find_matching_close(source, content_start, name),
+            _ => find_close(source, content_start, name),
+        };
+        let (start_line, start_column) = line_and_column(source, content_start);
+        blocks.push(SfcBlock {
+            kind,
+            lang: attribute_value(attrs, "lang").map(|lang| lang.to_ascii_lowercase()),
+            content_start,
+            content_end,
+            start_line,
+            start_column,
+        });
+        idx = next;
+    }
+
+    if language == "svelte" {
+        blocks = with_svelte_markup(source, blocks);
+    }
+    blocks
+}
+
+fn block_tag_at(source: &str, tag_start: usize) -> Option<(SfcBlockKind, &'static str)> {
+    let rest = &source.as_bytes()[tag_start + 1..];
+    [
+        (SfcBlockKind::Script, "script"),
+        (SfcBlockKind::Template, "template"),
+        (SfcBlockKind::Style, "style"),
+    ]
+    .into_iter()
+    .find(|(_, name)| {
+        rest.len() > name.len()
+            && rest[..name.len()].eq_ignore_ascii_case(name.as_bytes())
+            && matches!(rest[name.len()], b'>' | b'/' | b' ' | b'\t' | b'\r' | b'\n')
+    })
+}
+
+/// Index of the `>` closing an opening tag, skipping quoted attribute values.
+fn find_tag_end(bytes: &[u8], from: usize) -> Option<usize> {
+    let mut quote: Option<u8> = None;
+    for (offset, &byte) in bytes[from..].iter().enumerate() {
+        match quote {
+            Some(q) if byte == q => quote = None,
+            Some(_) => {}
+            None if byte == b'"' || byte == b'\'' => quote = Some(byte),
+            None if byte == b'>' => return Some(from + offset),
+            None => {}
+        }
+    }
+    None
+}
+
+/// First `</name>`; returns (content end, index after the closing tag).
+fn find_close(source: &str, from: usize, name: &str) -> (usize, usize) {
+    match find_ignore_case(source, from, &format!("</{name}")) {
+        Some(close) => (close, after_tag(source, close)),
+        None => (source.len(), source.len()),
+    }
+}
+
+/// Closing tag that balances nested `<name>` tags (for `<template>`).
+fn find_matching_close(source: &str, from: usize, name: &str) -> (usize, usize) {
+    let open = format!("<{name}");
+    let close = format!("</{name}");
+    let mut depth = 0usize;
+    let mut idx = from;
+    loop {
+        let next_open = find_ignore_case(source, idx, &open);
+        let Some(next_close) = find_ignore_case(source, idx, &close) else {
+            return (source.len(), source.len());
+        };
+        match next_open {
+            Some(open_at) if open_at < next_close => {
+                depth += 1;
+                idx = open_at + open.len();
+            }
+            _ if depth == 0 => return (next_close, after_tag(source, next_close)),
+            _ => {
+                depth -= 1;
+                idx = next_close + close.len();
+            }
+        }
+    }
+}
+
+fn after_tag(source: &str, tag_start: usize) -> usize {
+    source[tag_start..]
+        .find('>')
+        .map(|end| tag_start + end + 1)
+        .unwrap_or(source.len())
+}
+
+fn find_ignore_case(source: &str, from: usize, needle: &str) -> Option<usize> {
+    let haystack = &source.as_bytes()[from..];
+    let needle = needle.as_bytes();
+    haystack
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+        .map(|pos| from + pos)
+}
+
+fn attribute_value(attrs: &str, name: &str) -> Option<String> {
+    let lower = attrs.to_ascii_lowercase();
+    let mut search_from = 0;
+    while let Some(pos) = lower[search_from..].find(name) {
+        let start = search_from + pos;
+        search_from = start + name.len();
+        let preceded_ok = start == 0 || lower.as_bytes()[start - 1].is_ascii_whitespace();
+        let rest = lower[search_from..].trim_start();
+        if !preceded_ok || !rest.starts_with('=') {
+            continue;
+        }
+        let value_offset = attrs.len() - rest.len() + 1;
+        let value = attrs[value_offset..].trim_start();
+        let value = match value.chars().next() {
+            Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or(""),
+            _ => value
+                .split(|c: char| c.is_whitespace())
+                .next()
+                .unwrap_or(""),
+        };
+        return Some(value.to_string());
+    }
+    None
+}
+
+fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map(|nl| offset - nl - 1)
+        .unwrap_or(offset);
+    (line, column)
+}
+
+/// Add the markup between script/style blocks as template blocks.
+fn with_svelte_markup(source: &str, blocks: Vec<SfcBlock>) -> Vec<SfcBlock> {
+    let mut out = Vec::with_capacity(blocks.len() * 2 + 1);
+    let mut gap_start = 0;
+    let push_gap = |out: &mut Vec<SfcBlock>, start: usize, end: usize| {
+        if start < end && !source[start..end].trim().is_empty() {
+            let (start_line, start_column) = line_and_column(source, start);
+            out.push(SfcBlock {
+                kind: SfcBlockKind::Template,
+                lang: None,
+                content_start: start,
+                content_end: end,
+                start_line,
+                start_column,
+            });
+        }
+    };
+    for block in blocks {
+        let open_start = source[..block.content_start].rfind('<').unwrap_or(0);
+        push_gap(&mut out, gap_start, open_start);
+        gap_start = after_tag(source, block.content_end).min(source.len());
+        out.push(block);
+    }
+    push_gap(&mut out, gap_start, source.len());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VUE: &str = r#"<template>
+  <div><template v-if="ok">{{ msg }}</template></div>
+</template>
+
+<script setup lang="ts">
+import { ref } from 'vue'
+function greet(name: string) {
+  return `hi ${name}`
+}
+</script>
+
+<style scoped>
+.a { color: red; }
+</style>
+"#;
+
+    #[test]
+    fn vue_blocks_are_split_with_line_mapping() {
+        let blocks = split_blocks(VUE, "vue");
+        let kinds: Vec<SfcBlockKind> = blocks.iter().map(|b| b.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SfcBlockKind::Template,
+                SfcBlockKind::Script,
+                SfcBlockKind::Style
+            ]
+        );
+
+        let template = &blocks[0];
+        assert!(template.content(VUE).contains("v-if"));
+        assert!(template.content(VUE).trim_end().ends_with("</div>"));
+
+        let script = &blocks[1];
+        assert_eq!(script.lang.as_deref(), Some("ts"));
+        assert_eq!(script.script_language(), "typescript");
+        assert_eq!(script.start_line, 5);
+        // Line 3 of the block content is `function greet`.
+        assert_eq!(script.file_line(3), 7);
+    }
+
+    #[test]
+    fn svelte_markup_becomes_template_blocks() {
+        let source = "<script context=\"module\">export const prerender = true;</script>\n<h1>{title}</h1>\n<style>h1 { margin: 0; }</style>\n";
+        let blocks = split_blocks(source, "svelte");
+        let kinds: Vec<SfcBlockKind> = blocks.iter().map(|b| b.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SfcBlockKind::Script,
+                SfcBlockKind::Template,
+                SfcBlockKind::Style
+            ]
+        );
+        assert_eq!(blocks[0].script_language(), "javascript");
+        assert_eq!(blocks[0].start_column, 25);
+        assert_eq!(blocks[1].content(source).trim(), "<h1>{title}</h1>");
+    }
+}
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Function signatures read from the syntax tree: one type per parameter and
+//! the declared return type, for `cgrep symbols --params/--returns`.
+//!
+//! Parameters without a written type (Python, JavaScript, Ruby) are recorded
+//! as `_`. Receivers (`self`, `&mut self`, Python `self`/`cls`) are skipped.
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+/// Placeholder for a parameter without a type annotation.
+pub const UNTYPED: &str = "_";
+/// Declarator nesting searched for a C/C++ `function_declarator`.
+const MAX_DECLARATOR_DEPTH: usize = 8;
+
+/// Parameter and return types of a function or method.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature {
+    pub params: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<String>,
+}
+
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({})", self.params.join(", "))?;
+        if let Some(returns) = &self.returns {
+            write!(f, " -> {}", returns)?;
+        }
+        Ok(())
+    }
+}
+
+/// Signature of the function declared by `node`.
+pub fn signature(node: Node, source: &[u8], lang: &str) -> Option<Signature> {
+    let (params_node, returns) = match lang {
+        "c" | "cpp" => {
+            let declarator = if node.kind() == "function_declarator" {
+                node
+            } else {
+                function_declarator(node.child_by_field_name("declarator")?, 0)?
+            };
+            (
+                declarator.child_by_field_name("parameters")?,
+                c_like_return_type(declarator, source),
+            )
+        }
+        "go" => (
+            node.child_by_field_name("parameters")?,
+            node.child_by_field_name("result")
+                .and_then(|ty| type_text(ty, source)),
+        ),
+        "java" => (
+            node.child_by_field_name("parameters")?,
+            node.child_by_field_name("type")
+                .and_then(|ty| type_text(ty, source)),
+        ),
+        _ => (
+            node.child_by_field_name("parameters")?,
+            node.child_by_field_name("return_type")
+                .and_then(|ty| type_text(ty, source)),
+        ),
+    };
+
+    let mut params = Vec::new();
+    let mut cursor = params_node.walk();
+    for param in params_node.named_children(&mut cursor) {
+        params.extend(param_types(param, source, lang));
+    }
+    if matches!(lang, "c" | "cpp") && params == ["void"] {
+        params.clear();
+    }
+    Some(Signature { params, returns })
+}
+
+/// Types contributed by one parameter node; Go declares several names per type.
+fn param_types(param: Node, source: &[u8], lang: &str) -> Vec<String> {
+    let kind = param.kind();
+    if kind == "comment" || kind == "self_parameter" {
+        return Vec::new();
+    }
+    if lang == "python" && kind == "identifier" {
+        let name = param.utf8_text(source).unwrap_or_default();
+        if matches!(name, "self" | "cls") {
+            return Vec::new();
+        }
+    }
+
+    let ty = match lang {
+        "c" | "cpp" => {
+            qualified_type(param, source).map(|ty| with_declarator_modifiers(ty, param, source))
+        }
+        _ => param
+            .child_by_field_name("type")
+            .and_then(|ty| type_text(ty, source)),
+    }
+    .unwrap_or_else(|| UNTYPED.to_string());
+
+    if lang == "go" {
+        let mut cursor = param.walk();
+        let names = param.children_by_field_name("name", &mut cursor).count();
+        return vec![ty; names.max(1)];
+    }
+    vec![ty]
+}
+
+/// C/C++ spell pointers and references on the declarator: `const char *name`.
+fn with_declarator_modifiers(mut ty: String, param: Node, source: &[u8]) -> String {
+    let mut declarator = param.child_by_field_name("declarator");
+    while let Some(node) = declarator {
+        match node.kind() {
+            "pointer_declarator" | "abstract_pointer_declarator" => ty.push('*'),
+            "reference_declarator" | "abstract_reference_declarator" => {
+                let text = node.utf8_text(source).unwrap_or_default();
+                ty.push_str(if text.starts_with("&&") { "&&" } else { "&" });
+            }
+            "array_declarator" | "abstract_array_declarator" => ty.push_str("[]"),
+            _ => break,
+        }
+        declarator = node.child_by_field_name("declarator").or_else(|| {
+            node.named_child(0)
+                .filter(|child| child.kind() != "identifier")
+        });
+    }
+    ty
+}
+
+fn function_declarator(node: Node, depth: usize) -> Option<Node> {
+    if node.kind() == "function_declarator" {
+        return Some(node);
+    }
+    if depth >= MAX_DECLARATOR_DEPTH {
+        return None;
+    }
+    function_declarator(node.child_by_field_name("declarator")?, depth + 1)
+}
+
+/// Return type of a C/C++ function: the owning declaration's type plus the
+/// pointer and reference declarators wrapped around the function declarator.
+fn c_like_return_type(declarator: Node, source: &[u8]) -> Option<String> {
+    let mut suffix = String::new();
+    let mut owner = declarator.parent();
+    while let Some(node) = owner {
+        match node.kind() {
+            "pointer_declarator" => suffix.insert(0, '*'),
+            "reference_declarator" => suffix.insert(0, '&'),
+            "parenthesized_declarator" => {}
+            _ => break,
+        }
+        owner = node.parent();
+    }
+    let owner = owner.filter(|node| {
+        matches!(
+            node.kind(),
+            "function_definition" | "declaration" | "field_declaration"
+        )
+    })?;
+    Some(qualified_type(owner, source)? + &suffix)
+}
+
+/// C/C++ `type` field with its qualifiers: `const struct node`.
+fn qualified_type(node: Node, source: &[u8]) -> Option<String> {
+    let ty = node.child_by_field_name("type")?;
+    let mut parts = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if child.kind() == "type_qualifier" && child.start_byte() < ty.start_byte() {
+            parts.push(type_text(child, source)?);
+        }
+    }
+    parts.push(type_text(ty, source)?);
+    Some(parts.join(" "))
+}
+
+/// Type text with annotation punctuation dropped and whitespace collapsed.
+fn type_text(node: Node, source: &[u8]) -> Option<String> {
+    let text = node.utf8_text(source).ok()?;
+    let text = text.trim().trim_start_matches(':').trim_start_matches("->");
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!collapsed.is_empty()).then_some(collapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::symbols::SymbolExtractor;
+
+    fn signatures(source: &str, language: &str) -> Vec<(String, String)> {
+        SymbolExtractor::new()
+            .extract(source, language)
+            .expect("parse")
+            .into_iter()
+            .filter_map(|symbol| Some((symbol.name, symbol.signature?.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn typed_languages_record_params_and_returns() {
+        let rust = "impl Loader {\n    pub fn load(&self, path: &Path, retries: usize) -> Result<Config> { todo!() }\n}\nfn noop() {}\n";
+        assert_eq!(
+            signatures(rust, "rust"),
+            vec![
+                (
+                    "load".to_string(),
+                    "(&Path, usize) -> Result<Config>".to_string()
+                ),
+                ("noop".to_string(), "()".to_string()),
+            ]
+        );
+
+        let go =
+            "package m\nfunc Split(a, b string, n int) ([]string, error) { return nil, nil }\n";
+        assert_eq!(
+            signatures(go, "go"),
+            vec![(
+                "Split".to_string(),
+                "(string, string, int) -> ([]string, error)".to_string()
+            )]
+        );
+
+        let ts = "function parse(input: string, strict?: boolean): Promise<Ast> { return p; }\n";
+        assert_eq!(
+            signatures(ts, "typescript"),
+            vec![(
+                "parse".to_string(),
+                "(string, boolean) -> Promise<Ast>".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn untyped_params_and_receivers() {
+        let python =
+            "class A:\n    def get(self, key, default: int = 0) -> Optional[str]:\n        pass\n";
+        assert!(signatures(python, "python")
+            .contains(&("get".to_string(), "(_, int) -> Optional[str]".to_string())));
+
+        let c = "static const char *name_of(const struct node *n, int flags);\nint main(void) { return 0; }\n";
+        let found = signatures(c, "c");
+        assert!(found.contains(&(
+            "name_of".to_string(),
+            "(const struct node*, int) -> const char*".to_string()
+        )));
+        assert!(found.contains(&("main".to_string(), "() -> int".to_string())));
+    }
+}
@@ -7,7 +7,13 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use tree_sitter::{Node, Parser};
 
+use crate::parser::config_keys;
+use crate::parser::doc_comments;
 use crate::parser::languages::LANGUAGES;
+use crate::parser::metrics::{self, SymbolMetrics};
+use crate::parser::rust_macros;
+use crate::parser::sfc::{self, SfcBlockKind};
+use crate::parser::signatures::{self, Signature};
 
 /// Symbol kinds
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -24,6 +30,12 @@ pub enum SymbolKind {
     Trait,
     Method,
     Property,
+    /// Config file key, named by its dotted path.
+    Key,
+    /// Closure, lambda, or anonymous function, named `parent::<closure@L42>`.
+    Closure,
+    /// Macro definition, or an item-level invocation named `name!`.
+    Macro,
     Unknown,
 }
 
@@ -42,6 +54,9 @@ impl std::fmt::Display for SymbolKind {
             SymbolKind::Trait => write!(f, "trait"),
             SymbolKind::Method => write!(f, "method"),
             SymbolKind::Property => write!(f, "property"),
+            SymbolKind::Key => write!(f, "key"),
+            SymbolKind::Closure => write!(f, "closure"),
+            SymbolKind::Macro => write!(f, "macro"),
             SymbolKind::Unknown => write!(f, "unknown"),
         }
     }
@@ -57,7 +72,87 @@ pub struct Symbol {
     pub end_line: usize,
     pub byte_start: Option<usize>,
     pub byte_end: Option<usize>,
+    /// Qualified name of the enclosing symbol, for nested symbols.
     pub scope: Option<String>,
+    /// Doc comment attached to the symbol (rustdoc, docstring, JSDoc, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Parameter and return types, for functions and methods.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Signature>,
+    /// Decorators, attributes, or annotations on the definition, without
+    /// `@`/`#[...]` or arguments (`pytest.fixture`, `tokio::main`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decorators: Vec<String>,
+    /// Size and complexity, for functions, methods, and closures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<SymbolMetrics>,
+}
+
+impl Symbol {
+    /// Whether the name was synthesized for a closure or anonymous class.
+    pub fn is_anonymous(&self) -> bool {
+        self.local_name().starts_with('<') && self.name.ends_with('>')
+    }
+
+    /// Name without the parent prefix of an anonymous symbol
+    /// (`<closure@L42>` for `outer::<closure@L42>`).
+    pub fn local_name(&self) -> &str {
+        match self.name.rfind("::<") {
+            Some(idx) => &self.name[idx + 2..],
+            None => &self.name,
+        }
+    }
+
+    /// Whether a decorator is `name` or ends with it as a path segment, so
+    /// `fixture` matches `pytest.fixture`. A leading `@` or `#[...]` and
+    /// arguments in `name` are ignored.
+    pub fn has_decorator(&self, name: &str) -> bool {
+        let name = decorator_name(name);
+        !name.is_empty()
+            && self.decorators.iter().any(|decorator| {
+                decorator == name
+                    || decorator
+                        .strip_suffix(name)
+                        .is_some_and(|prefix| prefix.ends_with('.') || prefix.ends_with("::"))
+            })
+    }
+
+    /// Name prefixed with its enclosing symbols (`Outer::inner`).
+    ///
+    /// Anonymous names already carry their parent; config keys carry their
+    /// dotted path.
+    pub fn qualified_name(&self) -> String {
+        match self.scope.as_deref() {
+            Some(scope) if !self.is_anonymous() && self.kind != SymbolKind::Key => {
+                format!("{scope}::{}", self.name)
+            }
+            _ => self.name.clone(),
+        }
+    }
+}
+
+/// Innermost symbol whose body holds `line`, closures included.
+///
+/// One-line variables, constants, and properties do not form a scope.
+pub fn enclosing_symbol(symbols: &[Symbol], line: usize) -> Option<&Symbol> {
+    symbols
+        .iter()
+        .filter(|symbol| symbol.kind != SymbolKind::Key)
+        .filter(|symbol| {
+            symbol.line < symbol.end_line
+                || !matches!(
+                    symbol.kind,
+                    SymbolKind::Variable | SymbolKind::Constant | SymbolKind::Property
+                )
+        })
+        .filter(|symbol| symbol.line <= line && line <= symbol.end_line)
+        .min_by_key(|symbol| {
+            (
+                symbol.end_line - symbol.line,
+                std::cmp::Reverse(symbol.line),
+            )
+        })
 }
 
 /// Symbol extractor using tree-sitter node traversal
@@ -87,6 +182,13 @@ impl SymbolExtractor {
         language: &str,
         parser: &mut Parser,
     ) -> Result<Vec<Symbol>> {
+        if sfc::is_sfc_language(language) {
+            return self.extract_sfc(source, language, parser);
+        }
+        if config_keys::is_config_language(language) {
+            return Ok(config_keys::extract_keys(source, language));
+        }
+
         let lang = LANGUAGES
             .get(language)
             .ok_or_else(|| anyhow::anyhow!("Unsupported language: {}", language))?;
@@ -100,7 +202,13 @@ impl SymbolExtractor {
         let source_bytes = source.as_bytes();
         let mut symbols = Vec::new();
 
-        self.traverse_node(tree.root_node(), source_bytes, language, &mut symbols);
+        self.traverse_node(
+            tree.root_node(),
+            source_bytes,
+            language,
+            &mut Vec::new(),
+            &mut symbols,
+        );
 
         if matches!(language, "c" | "cpp") {
             let mut seen = HashSet::new();
@@ -115,7 +223,38 @@ impl SymbolExtractor {
         }
 
         dedupe_symbols_in_place(&mut symbols);
+        doc_comments::attach_doc_comments(source, language, &mut symbols);
+        if language == "rust" {
+            symbols.extend(rust_macros::expand(tree.root_node(), source_bytes));
+        }
+
+        Ok(symbols)
+    }
 
+    /// Extract symbols from the script blocks of a Vue/Svelte component,
+    /// with positions mapped back to the component file.
+    fn extract_sfc(
+        &self,
+        source: &str,
+        language: &str,
+        parser: &mut Parser,
+    ) -> Result<Vec<Symbol>> {
+        let mut symbols = Vec::new();
+        for block in sfc::split_blocks(source, language) {
+            if block.kind != SfcBlockKind::Script {
+                continue;
+            }
+            let block_symbols =
+                self.extract_with_parser(block.content(source), block.script_language(), parser)?;
+            for mut symbol in block_symbols {
+                symbol.column = block.file_column(symbol.line, symbol.column);
+                symbol.line = block.file_line(symbol.line);
+                symbol.end_line = block.file_line(symbol.end_line);
+                symbol.byte_start = symbol.byte_start.map(|b| b + block.content_start);
+                symbol.byte_end = symbol.byte_end.map(|b| b + block.content_start);
+                symbols.push(symbol);
+            }
+        }
         Ok(symbols)
     }
 
@@ -135,17 +274,35 @@ impl SymbolExtractor {
         self.extract_with_parser(source, language, parser)
     }
 
-    /// Traverse the AST and extract symbols
-    fn traverse_node(&self, node: Node, source: &[u8], lang: &str, symbols: &mut Vec<Symbol>) {
+    /// Traverse the AST and extract symbols, linking nested symbols to the
+    /// qualified names in `parents`.
+    fn traverse_node(
+        &self,
+        node: Node,
+        source: &[u8],
+        lang: &str,
+        parents: &mut Vec<String>,
+        symbols: &mut Vec<Symbol>,
+    ) {
         // Extract symbol based on node type and language
-        if let Some(symbol) = self.extract_symbol_from_node(node, source, lang) {
+        let parent = parents.last().map(String::as_str);
+        let symbol = self
+            .extract_symbol_from_node(node, source, lang)
+            .or_else(|| anonymous_symbol(node, source, lang, parent));
+        let pushed = symbol.is_some();
+        if let Some(mut symbol) = symbol {
+            symbol.scope = parent.map(str::to_string);
+            parents.push(symbol.qualified_name());
             symbols.push(symbol);
         }
 
         // Recursively traverse children
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
-            self.traverse_node(child, source, lang, symbols);
+            self.traverse_node(child, source, lang, parents, symbols);
+        }
+        if pushed {
+            parents.pop();
         }
     }
 
@@ -204,6 +361,23 @@ impl SymbolExtractor {
             return None;
         }
 
+        let signature = matches!(effective_kind, SymbolKind::Function | SymbolKind::Method)
+            .then(|| signatures::signature(node, source, lang))
+            .flatten();
+        let metrics = match effective_kind {
+            SymbolKind::Function | SymbolKind::Method => {
+                Some(metrics::measure(node, source, signature.as_ref()))
+            }
+            // `const handler = () => {}` is measured as the function it holds.
+            SymbolKind::Variable => node
+                .child_by_field_name("value")
+                .filter(|value| is_js_function_value(value.kind()))
+                .map(|value| {
+                    let signature = signatures::signature(value, source, lang);
+                    metrics::measure(value, source, signature.as_ref())
+                }),
+            _ => None,
+        };
         Some(Symbol {
             name,
             kind: effective_kind,
@@ -213,6 +387,10 @@ impl SymbolExtractor {
             byte_start: Some(node.start_byte()),
             byte_end: Some(node.end_byte()),
             scope: None,
+            doc: None,
+            signature,
+            decorators: decorators(node, source, lang),
+            metrics,
         })
     }
 
@@ -355,6 +533,10 @@ impl SymbolExtractor {
                     byte_start: None,
                     byte_end: None,
                     scope: None,
+                    doc: None,
+                    signature: None,
+                    decorators: Vec::new(),
+                    metrics: None,
                 });
                 break;
             }
@@ -382,6 +564,7 @@ impl SymbolExtractor {
             "const_item" => Some((SymbolKind::Constant, "name")),
             "static_item" => Some((SymbolKind::Variable, "name")),
             "mod_item" => Some((SymbolKind::Module, "name")),
+            "macro_definition" => Some((SymbolKind::Macro, "name")),
             _ => None,
         }
     }
@@ -475,6 +658,155 @@ fn canonicalize_c_like_function_name(raw: &str) -> String {
     token.to_string()
 }
 
+/// Decorators of a definition node: Python decorators, Java annotations,
+/// JS/TS decorators, and Rust outer attributes.
+fn decorators(node: Node, source: &[u8], lang: &str) -> Vec<String> {
+    let mut nodes: Vec<Node> = Vec::new();
+    match lang {
+        "python" => {
+            if let Some(parent) = node
+                .parent()
+                .filter(|parent| parent.kind() == "decorated_definition")
+            {
+                nodes.extend(
+                    parent
+                        .children(&mut parent.walk())
+                        .filter(|child| child.kind() == "decorator"),
+                );
+            }
+        }
+        "java" => {
+            for modifiers in node
+                .children(&mut node.walk())
+                .filter(|child| child.kind() == "modifiers")
+            {
+                nodes.extend(
+                    modifiers
+                        .children(&mut modifiers.walk())
+                        .filter(|child| matches!(child.kind(), "annotation" | "marker_annotation")),
+                );
+            }
+        }
+        "typescript" | "javascript" => {
+            // `@Component export class ...` puts the decorator on the export.
+            let export = node
+                .parent()
+                .filter(|parent| parent.kind() == "export_statement");
+            for owner in export.into_iter().chain([node]) {
+                nodes.extend(
+                    owner
+                        .children(&mut owner.walk())
+                        .filter(|child| child.kind() == "decorator"),
+                );
+            }
+        }
+        "rust" => {
+            let mut sibling = node.prev_named_sibling();
+            while let Some(attribute) = sibling.filter(|sibling| {
+                matches!(
+                    sibling.kind(),
+                    "attribute_item" | "line_comment" | "block_comment"
+                )
+            }) {
+                if attribute.kind() == "attribute_item" {
+                    nodes.push(attribute);
+                }
+                sibling = attribute.prev_named_sibling();
+            }
+            nodes.reverse();
+        }
+        _ => {}
+    }
+    nodes
+        .into_iter()
+        .filter_map(|node| node.utf8_text(source).ok())
+        .map(decorator_name)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `pytest.fixture` for `@pytest.fixture(scope="module")`, `derive` for
+/// `#[derive(Debug)]`.
+pub fn decorator_name(text: &str) -> &str {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("#[")
+        .map_or(text, |inner| inner.trim_end_matches(']'))
+        .trim_start_matches('@');
+    text.split(|ch: char| ch == '(' || ch == '=' || ch.is_whitespace())
+        .next()
+        .unwrap_or_default()
+}
+
+/// Closure, lambda, or anonymous class at `node`, named after its line and
+/// `parent`.
+fn anonymous_symbol(node: Node, source: &[u8], lang: &str, parent: Option<&str>) -> Option<Symbol> {
+    // Keyword tokens share kind names with these nodes (`function`, `lambda`).
+    if !node.is_named() {
+        return None;
+    }
+    let (kind, label) = match (lang, node.kind()) {
+        ("rust", "closure_expression") | ("go", "func_literal") => (SymbolKind::Closure, "closure"),
+        ("cpp", "lambda_expression")
+        | ("java", "lambda_expression")
+        | ("python", "lambda")
+        | ("ruby", "lambda") => (SymbolKind::Closure, "lambda"),
+        (
+            "typescript" | "javascript",
+            "arrow_function" | "function_expression" | "function" | "generator_function",
+        ) => (SymbolKind::Closure, "closure"),
+        ("typescript" | "javascript", "class") => (SymbolKind::Class, "class"),
+        ("java", "object_creation_expression")
+            if node
+                .children(&mut node.walk())
+                .any(|child| child.kind() == "class_body") =>
+        {
+            (SymbolKind::Class, "anonymous")
+        }
+        _ => return None,
+    };
+    // `const handler = () => {}` is already named by its variable.
+    if matches!(lang, "typescript" | "javascript")
+        && node
+            .parent()
+            .is_some_and(|p| p.kind() == "variable_declarator")
+    {
+        return None;
+    }
+    let metrics = (kind == SymbolKind::Closure).then(|| {
+        let signature = signatures::signature(node, source, lang);
+        metrics::measure(node, source, signature.as_ref())
+    });
+    let line = node.start_position().row + 1;
+    let local = format!("<{label}@L{line}>");
+    Some(Symbol {
+        name: match parent {
+            Some(parent) => format!("{parent}::{local}"),
+            None => local,
+        },
+        kind,
+        line,
+        column: node.start_position().column + 1,
+        end_line: node.end_position().row + 1,
+        byte_start: Some(node.start_byte()),
+        byte_end: Some(node.end_byte()),
+        scope: None,
+        doc: None,
+        signature: None,
+        decorators: Vec::new(),
+        metrics,
+    })
+}
+
+/// Function values that name their variable in JavaScript/TypeScript.
+fn is_js_function_value(kind: &str) -> bool {
+    matches!(
+        kind,
+        "arrow_function" | "function_expression" | "function" | "generator_function"
+    )
+}
+
 fn symbol_dedupe_key(symbol: &Symbol) -> String {
     format!(
         "{}:{}:{}:{}:{}",
@@ -736,6 +1068,102 @@ TensorIteratorConfig& TensorIteratorConfig::add_owned_output(const TensorBase& o
         );
     }
 
+    #[test]
+    fn test_vue_script_symbols_use_file_lines() {
+        let source = r#"<template>
+  <button @click="greet">Hi</button>
+</template>
+
+<script lang="ts">
+export function greet(name: string) {
+  return name;
+}
+</script>
+"#;
+        let extractor = SymbolExtractor::new();
+        let symbols = extractor.extract(source, "vue").unwrap();
+        let greet = symbols.iter().find(|s| s.name == "greet").expect("greet");
+        assert_eq!(greet.kind, SymbolKind::Function);
+        assert_eq!(greet.line, 6);
+        assert_eq!(greet.end_line, 8);
+        let start = greet.byte_start.unwrap();
+        assert!(source[start..].starts_with("function greet"));
+    }
+
+    #[test]
+    fn closures_and_anonymous_classes_are_named_after_their_parent() {
+        let extractor = SymbolExtractor::new();
+        let rust = "fn outer(items: &[i32]) -> usize {\n    items.iter().map(|x| {\n        x + 1\n    }).count()\n}\n";
+        let symbols = extractor.extract(rust, "rust").unwrap();
+        let closure = symbols
+            .iter()
+            .find(|s| s.kind == SymbolKind::Closure)
+            .expect("closure");
+        assert_eq!(closure.name, "outer::<closure@L2>");
+        assert_eq!(closure.scope.as_deref(), Some("outer"));
+        assert_eq!((closure.line, closure.end_line), (2, 4));
+        assert!(closure.is_anonymous());
+        assert_eq!(closure.local_name(), "<closure@L2>");
+        assert_eq!(
+            enclosing_symbol(&symbols, 3).map(Symbol::qualified_name),
+            Some("outer::<closure@L2>".to_string())
+        );
+
+        let java = "class C {\n    void start() {\n        Runnable r = new Runnable() {\n            public void run() { work(); }\n        };\n    }\n}\n";
+        let symbols = extractor.extract(java, "java").unwrap();
+        let names: Vec<String> = symbols.iter().map(Symbol::qualified_name).collect();
+        assert!(names.contains(&"C::start::<anonymous@L3>".to_string()));
+        assert!(names.contains(&"C::start::<anonymous@L3>::run".to_string()));
+
+        // A function bound to a variable keeps the variable's name.
+        let ts = "const handler = (x: number) => {\n  return list.map((y) => y + x);\n};\n";
+        let symbols = extractor.extract(ts, "typescript").unwrap();
+        let anonymous: Vec<&str> = symbols
+            .iter()
+            .filter(|s| s.is_anonymous())
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(anonymous, ["handler::<closure@L2>"]);
+    }
+
+    #[test]
+    fn decorators_attributes_and_annotations_are_recorded() {
+        let extractor = SymbolExtractor::new();
+        let decorators = |source: &str, lang: &str, name: &str| -> Vec<String> {
+            extractor
+                .extract(source, lang)
+                .unwrap()
+                .into_iter()
+                .find(|s| s.name == name)
+                .map(|s| s.decorators)
+                .unwrap_or_default()
+        };
+        let python = "@pytest.fixture(scope=\"module\")\n@other\ndef client():\n    pass\n";
+        assert_eq!(
+            decorators(python, "python", "client"),
+            ["pytest.fixture", "other"]
+        );
+        let rust = "/// Entry point.\n#[tokio::main]\n#[allow(dead_code)]\nasync fn main() {}\n";
+        assert_eq!(decorators(rust, "rust", "main"), ["tokio::main", "allow"]);
+        let java = "class A {\n    @Override\n    @Deprecated(since = \"2\")\n    public String toString() { return \"\"; }\n}\n";
+        assert_eq!(
+            decorators(java, "java", "toString"),
+            ["Override", "Deprecated"]
+        );
+        let ts = "@Component({ selector: 'x' })\nexport class Widget {}\n";
+        assert_eq!(decorators(ts, "typescript", "Widget"), ["Component"]);
+
+        let symbol = extractor
+            .extract(python, "python")
+            .unwrap()
+            .into_iter()
+            .find(|s| s.name == "client")
+            .unwrap();
+        assert!(symbol.has_decorator("pytest.fixture"));
+        assert!(symbol.has_decorator("@fixture"));
+        assert!(!symbol.has_decorator("test.fixture"));
+    }
+
     #[test]
     fn test_unsupported_language() {
         let extractor = SymbolExtractor::new();
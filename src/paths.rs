@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Platform-aware path normalization for comparison and display.
+//!
+//! On Windows, `canonicalize` returns verbatim paths (`\\?\C:\repo`,
+//! `\\?\UNC\server\share`) that neither compare equal to user-supplied paths
+//! nor read well in output. Helpers here strip those prefixes, detect
+//! filesystem roots (`/`, `C:\`, `\\server\share`), and render paths with the
+//! separator chosen by `--path-style`.
+//!
+//! Paths that are not valid UTF-8 (possible on Unix) are carried through the
+//! index and JSON output in an escaped form; see [`encode_path`]. Only that
+//! form is stored. The readable form JSON output adds next to it is derived
+//! when printing, by [`display_name`].
+
+use once_cell::sync::OnceCell;
+use std::path::{Path, PathBuf};
+
+/// Longest path Windows APIs accept without a verbatim prefix.
+const WINDOWS_MAX_PATH: usize = 260;
+
+/// Separator policy for displayed paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathStyle {
+    /// Platform separator (`\` on Windows, `/` elsewhere).
+    #[default]
+    Native,
+    /// Always `/`, e.g. for tools that parse output on any platform.
+    Posix,
+}
+
+static PATH_STYLE: OnceCell<PathStyle> = OnceCell::new();
+
+/// Select the display style for this process (`--path-style`).
+pub fn set_path_style(style: PathStyle) {
+    let _ = PATH_STYLE.set(style);
+}
+
+pub fn path_style() -> PathStyle {
+    PATH_STYLE.get().copied().unwrap_or_default()
+}
+
+/// Remove a Windows verbatim prefix, keeping the path otherwise unchanged.
+pub fn strip_verbatim(path: &Path) -> PathBuf {
+    match path.to_str().and_then(strip_verbatim_str) {
+        Some(stripped) => PathBuf::from(stripped),
+        None => path.to_path_buf(),
+    }
+}
+
+/// `std::fs::canonicalize` without Windows verbatim prefixes.
+///
+/// Paths at or beyond `MAX_PATH` keep the prefix, since it is what lets
+/// Windows open them.
+pub fn canonicalize(path: &Path) -> std::io::Result<PathBuf> {
+    let canonical = path.canonicalize()?;
+    let stripped = strip_verbatim(&canonical);
+    if stripped.as_os_str().len() < WINDOWS_MAX_PATH {
+        Ok(stripped)
+    } else {
+        Ok(canonical)
+    }
+}
+
+/// Lossless string form of a path, used for index keys and JSON output.
+///
+/// Valid UTF-8 paths are returned unchanged. Otherwise (Unix only) each byte
+/// that is not part of a valid UTF-8 sequence becomes `\xNN` and literal
+/// backslashes are doubled; [`decode_path`] reverses this. Elsewhere the
+/// result is the lossy form.
+pub fn encode_path(path: &Path) -> String {
+    if let Some(valid) = path.to_str() {
+        return valid.to_string();
+    }
+    encode_non_utf8(path)
+}
+
+#[cfg(unix)]
+fn encode_non_utf8(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    escape_bytes(path.as_os_str().as_bytes())
+}
+
+#[cfg(not(unix))]
+fn encode_non_utf8(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Inverse of [`encode_path`].
+///
+/// Strings naming an existing path, or whose unescaped bytes would be valid
+/// UTF-8 (which [`encode_path`] never escapes), are taken literally.
+pub fn decode_path(encoded: &str) -> PathBuf {
+    let literal = PathBuf::from(encoded);
+    if !encoded.contains("\\x") || literal.exists() {
+        return literal;
+    }
+    decode_escaped(encoded).unwrap_or(literal)
+}
+
+/// Readable form of an [`encode_path`] string that has escapes: the invalid
+/// bytes become U+FFFD, as file managers show them. `None` when `encoded` has
+/// none, since it then reads as it is.
+///
+/// The result is for people only; it cannot be decoded back to the path.
+pub fn display_name(encoded: &str) -> Option<String> {
+    if !encoded.contains("\\x") {
+        return None;
+    }
+    let bytes = unescape_bytes(encoded).filter(|bytes| std::str::from_utf8(bytes).is_err())?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(unix)]
+fn decode_escaped(encoded: &str) -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+    unescape_bytes(encoded)
+        .filter(|bytes| std::str::from_utf8(bytes).is_err())
+        .map(|bytes| PathBuf::from(OsString::from_vec(bytes)))
+}
+
+#[cfg(not(unix))]
+fn decode_escaped(_encoded: &str) -> Option<PathBuf> {
+    None
+}
+
+fn escape_bytes(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() + 8);
+    for chunk in bytes.utf8_chunks() {
+        for ch in chunk.valid().chars() {
+            if ch == '\\' {
+                out.push_str("\\\\");
+            } else {
+                out.push(ch);
+            }
+        }
+        for byte in chunk.invalid() {
+            out.push_str(&format!("\\x{byte:02X}"));
+        }
+    }
+    out
+}
+
+fn unescape_bytes(encoded: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len());
+    let bytes = encoded.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] != b'\\' {
+            out.push(bytes[idx]);
+            idx += 1;
+            continue;
+        }
+        match bytes.get(idx + 1) {
+            Some(b'\\') => {
+                out.push(b'\\');
+                idx += 2;
+            }
+            Some(b'x') => {
+                let hex = encoded.get(idx + 2..idx + 4)?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                idx += 4;
+            }
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+/// Whether `path` is a filesystem root: `/`, a drive root, or a UNC share root.
+pub fn is_filesystem_root(path: &Path) -> bool {
+    let stripped = strip_verbatim(path);
+    match stripped.to_str() {
+        Some(raw) => is_root_str(raw, cfg!(windows)),
+        None => stripped.parent().is_none() && stripped.has_root(),
+    }
+}
+
+/// Render a path for output using the selected [`PathStyle`].
+pub fn display(path: &Path) -> String {
+    render(
+        &encode_path(&strip_verbatim(path)),
+        path_style(),
+        cfg!(windows),
+    )
+}
+
+/// `full` relative to `base` (both normalized), rendered for output.
+pub fn relative_display(full: &Path, base: &Path) -> Option<String> {
+    let full = strip_verbatim(full);
+    let base = strip_verbatim(base);
+    full.strip_prefix(&base).ok().map(display)
+}
+
+fn strip_verbatim_str(raw: &str) -> Option<String> {
+    if let Some(rest) = raw.strip_prefix(r"\\?\UNC\") {
+        return Some(format!(r"\\{rest}"));
+    }
+    let rest = raw.strip_prefix(r"\\?\")?;
+    // Only drive paths have a non-verbatim spelling (`\\?\Volume{...}` does not).
+    let bytes = rest.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        Some(rest.to_string())
+    } else {
+        None
+    }
+}
+
+fn is_root_str(raw: &str, windows: bool) -> bool {
+    if raw == "/" {
+        return true;
+    }
+    if !windows {
+        return false;
+    }
+    let trimmed = raw.trim_end_matches(['\\', '/']);
+    let bytes = trimmed.as_bytes();
+    if bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        // `C:\` is a root; a bare `C:` is the drive's current directory.
+        return trimmed.len() < raw.len();
+    }
+    if trimmed.is_empty() {
+        return true;
+    }
+    // `\\server\share` has exactly two components after the leading `\\`.
+    trimmed
+        .strip_prefix(r"\\")
+        .or_else(|| trimmed.strip_prefix("//"))
+        .map(|unc| unc.split(['\\', '/']).filter(|c| !c.is_empty()).count() == 2)
+        .unwrap_or(false)
+}
+
+fn render(raw: &str, style: PathStyle, windows: bool) -> String {
+    // `\` is an ordinary file-name character on Unix, so only rewrite on Windows.
+    if windows && style == PathStyle::Posix {
+        raw.replace('\\', "/")
+    } else {
+        raw.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbatim_prefixes_are_stripped() {
+        assert_eq!(
+            strip_verbatim_str(r"\\?\C:\repo\src").as_deref(),
+            Some(r"C:\repo\src")
+        );
+        assert_eq!(
+            strip_verbatim_str(r"\\?\UNC\server\share\repo").as_deref(),
+            Some(r"\\server\share\repo")
+        );
+        assert_eq!(strip_verbatim_str(r"\\?\Volume{1234}\repo"), None);
+        assert_eq!(strip_verbatim_str("/home/user/repo"), None);
+    }
+
+    #[test]
+    fn filesystem_roots_are_detected_per_platform() {
+        assert!(is_root_str("/", false));
+        assert!(!is_root_str("/home", false));
+        assert!(!is_root_str(r"C:\", false));
+
+        assert!(is_root_str(r"C:\", true));
+        assert!(is_root_str("c:/", true));
+        assert!(!is_root_str("C:", true));
+        assert!(!is_root_str(r"C:\Users", true));
+        assert!(is_root_str(r"\", true));
+        assert!(is_root_str(r"\\server\share\", true));
+        assert!(!is_root_str(r"\\server\share\repo", true));
+    }
+
+    #[test]
+    fn posix_style_only_rewrites_windows_separators() {
+        assert_eq!(render(r"src\lib.rs", PathStyle::Posix, true), "src/lib.rs");
+        assert_eq!(
+            render(r"src\lib.rs", PathStyle::Native, true),
+            r"src\lib.rs"
+        );
+        assert_eq!(
+            render(r"odd\name.rs", PathStyle::Posix, false),
+            r"odd\name.rs"
+        );
+    }
+
+    #[test]
+    fn escaped_bytes_round_trip() {
+        let raw = b"dir\\caf\xE9/\xFFname.rs";
+        let escaped = escape_bytes(raw);
+        assert_eq!(escaped, "dir\\\\caf\\xE9/\\xFFname.rs");
+        assert_eq!(unescape_bytes(&escaped).as_deref(), Some(&raw[..]));
+        assert_eq!(unescape_bytes("bad\\q"), None);
+    }
+
+    #[test]
+    fn display_names_replace_escaped_bytes() {
+        assert_eq!(
+            display_name("dir\\\\caf\\xE9.rs").as_deref(),
+            Some("dir\\caf\u{FFFD}.rs")
+        );
+        assert_eq!(display_name("src/lib.rs"), None);
+        // Escapes that spell valid UTF-8 are never produced by `encode_path`.
+        assert_eq!(display_name("a\\x41.rs"), None);
+        assert_eq!(display_name(r"C:\xtools\main.rs"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_paths_encode_and_decode_losslessly() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(OsStr::from_bytes(b"caf\xE9.rs"));
+        std::fs::write(&path, "pub fn latin1() {}\n").unwrap();
+
+        let encoded = encode_path(&path);
+        assert!(encoded.ends_with("caf\\xE9.rs"));
+        assert_eq!(decode_path(&encoded), path);
+        assert_eq!(encode_path(Path::new("/plain/lib.rs")), "/plain/lib.rs");
+        assert_eq!(decode_path("/plain/lib.rs"), PathBuf::from("/plain/lib.rs"));
+    }
+
+    #[test]
+    fn relative_display_strips_base() {
+        assert_eq!(
+            relative_display(Path::new("/repo/src/lib.rs"), Path::new("/repo")).as_deref(),
+            Some("src/lib.rs")
+        );
+        assert_eq!(
+            relative_display(Path::new("/other/lib.rs"), Path::new("/repo")),
+            None
+        );
+    }
+}
@@ -13,7 +13,8 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::cli::{CliBudgetPreset, CliSearchMode};
 use crate::indexer::scanner::FileScanner;
-use cgrep::output::print_json;
+use cgrep::encoding;
+use cgrep::output::print_json2;
 
 const AGENT_HINT_CACHE_REL: &str = ".cgrep/cache/agent_expand_hints.json";
 const AGENT_HINT_CACHE_VERSION: u32 = 1;
@@ -248,10 +249,10 @@ pub fn run_plan(query: &str, options: &AgentPlanOptions) -> Result<()> {
     }
 
     let profile = normalize_profile_name(&options.profile);
-    let budget = budget_name(options.budget).to_string();
+    let budget = options.budget.name().to_string();
     let mut payload = AgentPlanPayload {
         meta: AgentPlanMeta {
-            schema_version: "1",
+            schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
             stage: "plan",
             query: query.to_string(),
             profile,
@@ -319,7 +320,7 @@ pub fn run_plan(query: &str, options: &AgentPlanOptions) -> Result<()> {
         locate_limit.to_string(),
     ];
     locate_args.push("--budget".to_string());
-    locate_args.push(budget_name(options.budget).to_string());
+    locate_args.push(options.budget.name().to_string());
     if let Some(path) = options.path.as_ref() {
         locate_args.push("--path".to_string());
         locate_args.push(path.clone());
@@ -489,7 +490,7 @@ pub fn run_plan(query: &str, options: &AgentPlanOptions) -> Result<()> {
     }
 
     payload.meta.truncated = enforce_plan_payload_budget(&mut payload, PLAN_PAYLOAD_CHAR_LIMIT);
-    print_json(&payload, options.compact)?;
+    print_json2("agent-plan", &payload, options.compact)?;
     Ok(())
 }
 
@@ -525,11 +526,11 @@ fn emit_plan_error(
 ) -> Result<()> {
     let payload = AgentPlanPayload {
         meta: AgentPlanMeta {
-            schema_version: "1",
+            schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
             stage: "plan",
             query: query.to_string(),
             profile: normalize_profile_name(&options.profile),
-            budget: budget_name(options.budget).to_string(),
+            budget: options.budget.name().to_string(),
             strategy: "broad-narrow-expand",
             max_steps: options.max_steps.unwrap_or(PLAN_DEFAULT_MAX_STEPS),
             max_candidates: options
@@ -543,7 +544,7 @@ fn emit_plan_error(
         diagnostics: Vec::new(),
         error: Some(error),
     };
-    print_json(&payload, options.compact)?;
+    print_json2("agent-plan", &payload, options.compact)?;
     Ok(())
 }
 
@@ -558,15 +559,6 @@ fn normalize_profile_name(raw: &str) -> String {
     }
 }
 
-fn budget_name(budget: CliBudgetPreset) -> &'static str {
-    match budget {
-        CliBudgetPreset::Tight => "tight",
-        CliBudgetPreset::Balanced => "balanced",
-        CliBudgetPreset::Full => "full",
-        CliBudgetPreset::Off => "off",
-    }
-}
-
 fn mode_name(mode: CliSearchMode) -> &'static str {
     match mode {
         CliSearchMode::Keyword => "keyword",
@@ -912,9 +904,9 @@ pub fn run_expand(ids: &[String], path: Option<&str>, context: usize, compact: b
                 break;
             }
 
-            let content = match fs::read_to_string(&file_path) {
-                Ok(content) => content,
-                Err(_) => continue,
+            let content = match encoding::read_text_file(&file_path) {
+                Ok(Some(decoded)) => decoded.text.into_owned(),
+                _ => continue,
             };
             let rel_path = file_path
                 .strip_prefix(&search_root)
@@ -959,7 +951,7 @@ pub fn run_expand(ids: &[String], path: Option<&str>, context: usize, compact: b
 
     let payload = AgentExpandPayload {
         meta: AgentExpandMeta {
-            schema_version: "1",
+            schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
             stage: "expand",
             requested_ids: wanted.len(),
             resolved_ids: results.len(),
@@ -970,7 +962,7 @@ pub fn run_expand(ids: &[String], path: Option<&str>, context: usize, compact: b
         },
         results,
     };
-    print_json(&payload, compact)?;
+    print_json2("agent-expand", &payload, compact)?;
 
     Ok(())
 }
@@ -1111,7 +1103,7 @@ fn resolve_from_hint(
     }
 
     if !line_cache.contains_key(&hint.path) {
-        let content = fs::read_to_string(&full_path).ok()?;
+        let content = encoding::read_text_file(&full_path).ok()??.text;
         let lines = content
             .lines()
             .map(|line| line.to_string())
@@ -0,0 +1,637 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep api`: report the public API surface of a codebase.
+//!
+//! A symbol is public when its language says so: `pub` in Rust, exported
+//! names in Go, `export` in JavaScript/TypeScript, `__all__` (or the lack of a
+//! leading underscore) in Python, `public` in Java, non-`static` top-level
+//! declarations and public class members in C/C++, and methods outside a
+//! `private` section in Ruby. Members count only when their container is
+//! public, and symbols inside function bodies never do.
+//!
+//! Items are listed by module (file) in a stable order. `--baseline` compares
+//! against an earlier `cgrep --format json api` report and fails when items
+//! were added, removed, or changed signature.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::FileScanner;
+use crate::parser::symbols::{Symbol, SymbolExtractor, SymbolKind};
+use cgrep::output::{
+    colorize_kind, colorize_name, colorize_path, print_json, print_quickfix, use_colors,
+    QuickfixEntry,
+};
+
+static PYTHON_ALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?ms)^__all__\s*(?::[^=]*)?=\s*[\[(](?P<names>.*?)[\])]").expect("valid __all__")
+});
+static QUOTED: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"["']([A-Za-z_][A-Za-z0-9_]*)["']"#).expect("valid quoted name"));
+static RUST_IMPL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*impl\b(?:<[^{]*?>)?\s+(?:[\w:<>, ]+?\s+for\s+)?(?:[\w]+::)*(?P<name>\w+)")
+        .expect("valid impl header")
+});
+static JS_EXPORT_CLAUSE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^\s*export\s*\{(?P<names>[^}]*)\}").expect("valid export"));
+static JS_EXPORT_DEFAULT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^\s*export\s+default\s+(?P<name>[A-Za-z_$][\w$]*)\s*;?\s*$")
+        .expect("valid export default")
+});
+
+/// One public item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ApiItem {
+    /// File declaring the item, relative to the search root.
+    module: String,
+    kind: String,
+    name: String,
+    /// Enclosing public type or module.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    container: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    #[serde(default)]
+    line: usize,
+}
+
+impl ApiItem {
+    /// Identity across revisions; the line and signature may change.
+    fn key(&self) -> (&str, Option<&str>, &str, &str) {
+        (
+            &self.module,
+            self.container.as_deref(),
+            &self.kind,
+            &self.name,
+        )
+    }
+
+    fn qualified_name(&self) -> String {
+        match &self.container {
+            Some(container) => format!("{}.{}", container, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} [{}] {}{}",
+            self.module,
+            self.kind,
+            self.qualified_name(),
+            self.signature.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChangedItem {
+    before: ApiItem,
+    after: ApiItem,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ApiDiff {
+    added: Vec<ApiItem>,
+    removed: Vec<ApiItem>,
+    changed: Vec<ChangedItem>,
+}
+
+impl ApiDiff {
+    fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+}
+
+/// Run the api command.
+pub fn run(
+    path: Option<&str>,
+    lang: Option<&str>,
+    baseline: Option<&str>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let search_root = match path {
+        Some(p) => PathBuf::from(p)
+            .canonicalize()
+            .with_context(|| format!("Path not found: {p}"))?,
+        None => std::env::current_dir()?.canonicalize()?,
+    };
+
+    let extractor = SymbolExtractor::new();
+    let mut items = Vec::new();
+    for file in FileScanner::new(&search_root).scan()? {
+        let Some(language) = file.language.as_deref() else {
+            continue;
+        };
+        if lang.is_some_and(|lang| lang != language) {
+            continue;
+        }
+        let Ok(symbols) = extractor.extract(&file.content, language) else {
+            continue;
+        };
+        let module = match file.path.strip_prefix(&search_root) {
+            Ok(rel) if !rel.as_os_str().is_empty() => rel.display().to_string(),
+            _ => file.path.display().to_string(),
+        };
+        items.extend(public_items(&module, &file.content, language, symbols));
+    }
+    items.sort_by(|a, b| {
+        a.module
+            .cmp(&b.module)
+            .then(a.line.cmp(&b.line))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let Some(baseline) = baseline else {
+        print_items(&items, format, compact)?;
+        return Ok(());
+    };
+    let content = std::fs::read_to_string(baseline)
+        .with_context(|| format!("Failed to read API baseline: {baseline}"))?;
+    let previous: Vec<ApiItem> = serde_json::from_str(&content).with_context(|| {
+        format!("API baseline is not a `cgrep --format json api` report: {baseline}")
+    })?;
+    let diff = diff_items(&previous, &items);
+    print_diff(&diff, format, compact)?;
+    if diff.len() > 0 {
+        bail!("{} public API change(s) against {}", diff.len(), baseline);
+    }
+    Ok(())
+}
+
+fn print_items(items: &[ApiItem], format: OutputFormat, compact: bool) -> Result<()> {
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&items, compact)?,
+        OutputFormat::Quickfix => {
+            let texts: Vec<String> = items
+                .iter()
+                .map(|item| format!("[{}] {}", item.kind, item.qualified_name()))
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = items
+                .iter()
+                .zip(&texts)
+                .map(|(item, text)| QuickfixEntry {
+                    path: &item.module,
+                    line: item.line,
+                    column: 1,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            let use_color = use_colors() && format == OutputFormat::Text;
+            if items.is_empty() {
+                println!("No public API found");
+                return Ok(());
+            }
+            let mut module: Option<&str> = None;
+            for item in items {
+                if module != Some(item.module.as_str()) {
+                    if module.is_some() {
+                        println!();
+                    }
+                    println!("{}", colorize_path(&item.module, use_color));
+                    module = Some(&item.module);
+                }
+                let kind = format!("[{}]", item.kind);
+                println!(
+                    "  {} {}{}",
+                    colorize_kind(&kind, use_color),
+                    colorize_name(&item.qualified_name(), use_color),
+                    item.signature.as_deref().unwrap_or_default()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_diff(diff: &ApiDiff, format: OutputFormat, compact: bool) -> Result<()> {
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(diff, compact)?,
+        OutputFormat::Quickfix => {
+            let mut texts = Vec::new();
+            for item in &diff.added {
+                texts.push((item, format!("added: {}", item.describe())));
+            }
+            for change in &diff.changed {
+                texts.push((
+                    &change.after,
+                    format!(
+                        "changed: {} (was {})",
+                        change.after.describe(),
+                        change.before.signature.as_deref().unwrap_or_default()
+                    ),
+                ));
+            }
+            let entries: Vec<QuickfixEntry<'_>> = texts
+                .iter()
+                .map(|(item, text)| QuickfixEntry {
+                    path: &item.module,
+                    line: item.line,
+                    column: 1,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            let use_color = use_colors() && format == OutputFormat::Text;
+            if diff.len() == 0 {
+                println!("No public API changes");
+                return Ok(());
+            }
+            for item in &diff.removed {
+                let line = format!("- {}", item.describe());
+                println!("{}", if use_color { line.red() } else { line.normal() });
+            }
+            for item in &diff.added {
+                let line = format!("+ {}", item.describe());
+                println!(
+                    "{}",
+                    if use_color {
+                        line.green()
+                    } else {
+                        line.normal()
+                    }
+                );
+            }
+            for change in &diff.changed {
+                let line = format!(
+                    "~ {} (was {})",
+                    change.after.describe(),
+                    change.before.signature.as_deref().unwrap_or_default()
+                );
+                println!(
+                    "{}",
+                    if use_color {
+                        line.yellow()
+                    } else {
+                        line.normal()
+                    }
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn diff_items(previous: &[ApiItem], current: &[ApiItem]) -> ApiDiff {
+    let before: BTreeMap<_, &ApiItem> = previous.iter().map(|item| (item.key(), item)).collect();
+    let after: BTreeMap<_, &ApiItem> = current.iter().map(|item| (item.key(), item)).collect();
+    let mut diff = ApiDiff::default();
+    for (key, item) in &after {
+        match before.get(key) {
+            None => diff.added.push((*item).clone()),
+            Some(old) if old.signature != item.signature => diff.changed.push(ChangedItem {
+                before: (*old).clone(),
+                after: (*item).clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, item) in &before {
+        if !after.contains_key(key) {
+            diff.removed.push((*item).clone());
+        }
+    }
+    diff
+}
+
+/// Public items among one file's symbols.
+fn public_items(
+    module: &str,
+    source: &str,
+    language: &str,
+    mut symbols: Vec<Symbol>,
+) -> Vec<ApiItem> {
+    symbols.retain(|symbol| symbol.kind != SymbolKind::Key && !symbol.is_anonymous());
+    symbols.sort_by_key(|symbol| {
+        (
+            symbol.line,
+            symbol.byte_start.is_none(),
+            symbol.byte_start,
+            usize::MAX - symbol.end_line,
+        )
+    });
+    let file = SourceFile::new(source, language);
+
+    // The C/C++ type fallback can report a declaration twice; keep the
+    // syntax-tree copy, which carries byte ranges.
+    let mut seen = HashSet::new();
+    symbols
+        .retain(|symbol| seen.insert((symbol.name.clone(), symbol.kind.to_string(), symbol.line)));
+
+    let mut public = vec![false; symbols.len()];
+    let mut items = Vec::new();
+    for idx in 0..symbols.len() {
+        let symbol = &symbols[idx];
+        let container = (0..idx)
+            .rev()
+            .find(|&outer| contains(&symbols[outer], symbol));
+        let visible = match container {
+            Some(outer) if is_callable(&symbols[outer].kind) => false,
+            Some(outer) if !public[outer] => false,
+            Some(outer) => file.member_is_public(&symbols[outer], symbol),
+            None => file.top_level_is_public(symbol),
+        };
+        public[idx] = visible;
+        if !visible {
+            continue;
+        }
+        items.push(ApiItem {
+            module: module.to_string(),
+            kind: symbol.kind.to_string(),
+            name: symbol.name.clone(),
+            container: match container {
+                Some(outer) => Some(symbols[outer].name.clone()),
+                None if language == "rust" => file.rust_impl_owner(symbol),
+                None => None,
+            },
+            signature: symbol.signature.as_ref().map(ToString::to_string),
+            line: symbol.line,
+        });
+    }
+    items
+}
+
+fn is_callable(kind: &SymbolKind) -> bool {
+    matches!(kind, SymbolKind::Function | SymbolKind::Method)
+}
+
+fn contains(outer: &Symbol, inner: &Symbol) -> bool {
+    match (
+        outer.byte_start,
+        outer.byte_end,
+        inner.byte_start,
+        inner.byte_end,
+    ) {
+        (Some(os), Some(oe), Some(is), Some(ie)) => os <= is && ie <= oe && (os, oe) != (is, ie),
+        _ => {
+            outer.line <= inner.line && inner.end_line <= outer.end_line && outer.line != inner.line
+        }
+    }
+}
+
+struct SourceFile<'a> {
+    source: &'a str,
+    lines: Vec<&'a str>,
+    language: &'a str,
+    /// Python `__all__` or JavaScript `export { ... }` names.
+    exported_names: Option<HashSet<String>>,
+}
+
+impl<'a> SourceFile<'a> {
+    fn new(source: &'a str, language: &'a str) -> Self {
+        let exported_names = match language {
+            "python" => PYTHON_ALL.captures(source).map(|caps| {
+                QUOTED
+                    .captures_iter(&caps["names"])
+                    .map(|name| name[1].to_string())
+                    .collect()
+            }),
+            "javascript" | "typescript" => {
+                let mut names: HashSet<String> = HashSet::new();
+                for caps in JS_EXPORT_CLAUSE.captures_iter(source) {
+                    for entry in caps["names"].split(',') {
+                        let local = entry.split_whitespace().next().unwrap_or_default();
+                        if !local.is_empty() && local != "type" {
+                            names.insert(local.to_string());
+                        }
+                    }
+                }
+                for caps in JS_EXPORT_DEFAULT.captures_iter(source) {
+                    names.insert(caps["name"].to_string());
+                }
+                Some(names)
+            }
+            _ => None,
+        };
+        Self {
+            source,
+            lines: source.lines().collect(),
+            language,
+            exported_names,
+        }
+    }
+
+    fn line(&self, line: usize) -> &str {
+        self.lines
+            .get(line.saturating_sub(1))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Declaration text before the symbol's name on its first line.
+    fn modifiers(&self, symbol: &Symbol) -> &str {
+        let line = self.line(symbol.line);
+        line.find(symbol.name.as_str())
+            .map_or(line, |end| &line[..end])
+    }
+
+    fn has_modifier(&self, symbol: &Symbol, modifier: &str) -> bool {
+        self.modifiers(symbol)
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|word| word == modifier)
+    }
+
+    fn declaration(&self, symbol: &Symbol) -> &str {
+        match (symbol.byte_start, symbol.byte_end) {
+            (Some(start), Some(end)) => self.source.get(start..end).unwrap_or_default(),
+            _ => self.line(symbol.line),
+        }
+    }
+
+    /// Type of the `impl` block a Rust method is declared in.
+    fn rust_impl_owner(&self, symbol: &Symbol) -> Option<String> {
+        let indent = |line: &str| line.len() - line.trim_start().len();
+        let own_indent = indent(self.line(symbol.line));
+        if own_indent == 0 {
+            return None;
+        }
+        (1..symbol.line)
+            .rev()
+            .map(|line| self.line(line))
+            .find(|line| {
+                !line.trim().is_empty()
+                    && indent(line) < own_indent
+                    && !line.trim_start().starts_with(['#', '/'])
+            })
+            .and_then(|line| RUST_IMPL.captures(line))
+            .map(|caps| caps["name"].to_string())
+    }
+
+    fn top_level_is_public(&self, symbol: &Symbol) -> bool {
+        match self.language {
+            "rust" => is_rust_pub(self.declaration(symbol)),
+            "go" => is_go_exported(&symbol.name),
+            "python" => match &self.exported_names {
+                Some(names) => names.contains(&symbol.name),
+                None => !symbol.name.starts_with('_'),
+            },
+            "javascript" | "typescript" => {
+                self.line(symbol.line).trim_start().starts_with("export ")
+                    || self
+                        .exported_names
+                        .as_ref()
+                        .is_some_and(|names| names.contains(&symbol.name))
+            }
+            "java" => self.has_modifier(symbol, "public"),
+            "c" | "cpp" => {
+                !self.has_modifier(symbol, "static") && self.c_like_is_definition(symbol)
+            }
+            "ruby" => !is_callable(&symbol.kind),
+            _ => false,
+        }
+    }
+
+    fn member_is_public(&self, container: &Symbol, member: &Symbol) -> bool {
+        match self.language {
+            // Trait items and enum variants take the container's visibility.
+            "rust" => container.kind == SymbolKind::Trait || is_rust_pub(self.declaration(member)),
+            "go" => is_go_exported(&member.name),
+            "python" => {
+                !member.name.starts_with('_')
+                    || (member.name.starts_with("__") && member.name.ends_with("__"))
+            }
+            "javascript" | "typescript" => {
+                !member.name.starts_with('#')
+                    && !self.has_modifier(member, "private")
+                    && !self.has_modifier(member, "protected")
+            }
+            "java" => {
+                container.kind == SymbolKind::Interface || self.has_modifier(member, "public")
+            }
+            "c" | "cpp" => {
+                if container.kind == SymbolKind::Module {
+                    return self.top_level_is_public(member);
+                }
+                let default_public = container.kind != SymbolKind::Class;
+                self.section_is_public(container, member, default_public, |line| {
+                    line.strip_suffix(':').map(str::trim_end)
+                }) && self.c_like_is_definition(member)
+            }
+            "ruby" => {
+                self.modifiers(member).contains("self.")
+                    || self.section_is_public(container, member, true, |line| {
+                        (!line.contains(' ')).then_some(line)
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the last access label between the container's first line and
+    /// the member (`public:`/`private:` in C++, bare `private` in Ruby) is
+    /// public.
+    fn section_is_public(
+        &self,
+        container: &Symbol,
+        member: &Symbol,
+        default_public: bool,
+        label: impl Fn(&str) -> Option<&str>,
+    ) -> bool {
+        let mut public = default_public;
+        for line in container.line + 1..member.line {
+            match label(self.line(line).trim()) {
+                Some("public") => public = true,
+                Some("private" | "protected") => public = false,
+                _ => {}
+            }
+        }
+        if self.language == "ruby" {
+            let modifiers = self.modifiers(member).trim();
+            if modifiers.starts_with("private") || modifiers.starts_with("protected") {
+                return false;
+            }
+        }
+        public
+    }
+
+    /// C/C++ types count only where they are defined, not where a
+    /// `struct name` merely appears in a declaration.
+    fn c_like_is_definition(&self, symbol: &Symbol) -> bool {
+        match symbol.kind {
+            SymbolKind::Struct | SymbolKind::Class | SymbolKind::Enum => {
+                self.declaration(symbol).contains('{')
+            }
+            _ => true,
+        }
+    }
+}
+
+fn is_rust_pub(declaration: &str) -> bool {
+    let declaration = declaration.trim_start();
+    declaration.starts_with("pub ") || declaration.starts_with("pub\n")
+}
+
+fn is_go_exported(name: &str) -> bool {
+    name.chars().next().is_some_and(char::is_uppercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api(source: &str, language: &str) -> Vec<String> {
+        let symbols = SymbolExtractor::new()
+            .extract(source, language)
+            .expect("parse");
+        public_items("m", source, language, symbols)
+            .iter()
+            .map(ApiItem::qualified_name)
+            .collect()
+    }
+
+    #[test]
+    fn visibility_rules_per_language() {
+        let rust = "pub struct Config;\nstruct Hidden;\npub(crate) fn internal() {}\nimpl Config {\n    pub fn new() -> Self { Config }\n}\npub fn load() { fn helper() {} }\nmod private { pub fn leaked() {} }\npub trait Store { fn get(&self) {} }\n";
+        assert_eq!(
+            api(rust, "rust"),
+            ["Config", "Config.new", "load", "Store", "Store.get"]
+        );
+
+        let python = "__all__ = [\"load\"]\n\ndef load():\n    pass\n\ndef dump():\n    pass\n";
+        assert_eq!(api(python, "python"), ["load"]);
+        let python = "class Cache:\n    def get(self):\n        pass\n    def _evict(self):\n        pass\n\ndef _helper():\n    pass\n";
+        assert_eq!(api(python, "python"), ["Cache", "Cache.get"]);
+
+        let ts = "export function parse(): void {}\nfunction local() {}\nconst shared = 1;\nexport { shared as value };\n";
+        assert_eq!(api(ts, "typescript"), ["parse", "shared"]);
+
+        let go = "package m\nfunc Open() {}\nfunc close() {}\ntype Client struct{}\n";
+        assert_eq!(api(go, "go"), ["Open", "Client"]);
+
+        let java =
+            "public class Pool {\n    public void open() {}\n    private void drain() {}\n}\n";
+        assert_eq!(api(java, "java"), ["Pool", "Pool.open"]);
+    }
+
+    #[test]
+    fn baseline_diff_reports_added_removed_and_changed() {
+        let item = |name: &str, signature: &str| ApiItem {
+            module: "lib.rs".to_string(),
+            kind: "function".to_string(),
+            name: name.to_string(),
+            container: None,
+            signature: Some(signature.to_string()),
+            line: 1,
+        };
+        let diff = diff_items(
+            &[item("load", "()"), item("dump", "()")],
+            &[item("load", "(&Path)"), item("open", "()")],
+        );
+        assert_eq!(diff.added, [item("open", "()")]);
+        assert_eq!(diff.removed, [item("dump", "()")]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].after.signature.as_deref(), Some("(&Path)"));
+    }
+}
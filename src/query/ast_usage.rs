@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-//! AST-based usage extraction helpers for callers/references.
+//! AST-based usage extraction helpers for callers/references/const.
 
 use std::collections::HashMap;
 
@@ -14,6 +14,15 @@ pub struct UsageMatch {
     pub column: usize,
 }
 
+/// A numeric literal in code, with a leading unary minus folded in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumericLiteral {
+    pub line: usize,
+    pub column: usize,
+    /// Literal as written, e.g. `86_400`, `0x1F`, `-1`, `2.5f`.
+    pub text: String,
+}
+
 pub struct AstUsageExtractor {
     parser_cache: HashMap<String, Parser>,
 }
@@ -93,6 +102,45 @@ impl AstUsageExtractor {
         Some(matches)
     }
 
+    /// Numeric literal nodes; comments, strings, and identifiers are never
+    /// literal nodes, so digits inside them are not reported.
+    pub fn numeric_literals(
+        &mut self,
+        source: &str,
+        language: &str,
+    ) -> Option<Vec<NumericLiteral>> {
+        let tree = self.parse(source, language)?;
+        let source_bytes = source.as_bytes();
+        let mut literals = Vec::new();
+
+        walk_tree(tree.root_node(), &mut |node| {
+            if node.kind() == "preproc_arg" {
+                literals.extend(preproc_number(node, source_bytes));
+                return;
+            }
+            if !is_numeric_literal(node.kind()) {
+                return;
+            }
+            let Ok(text) = node.utf8_text(source_bytes) else {
+                return;
+            };
+            let start = negation_parent(node).unwrap_or(node).start_position();
+            let sign = if start == node.start_position() {
+                ""
+            } else {
+                "-"
+            };
+            literals.push(NumericLiteral {
+                line: start.row + 1,
+                column: start.column + 1,
+                text: format!("{sign}{text}"),
+            });
+        });
+
+        literals.sort_by_key(|literal| (literal.line, literal.column));
+        Some(literals)
+    }
+
     fn parse(&mut self, source: &str, language: &str) -> Option<Tree> {
         let lang = LANGUAGES.get(language)?;
         use std::collections::hash_map::Entry;
@@ -133,6 +181,54 @@ fn is_identifier_like(kind: &str) -> bool {
     )
 }
 
+fn is_numeric_literal(kind: &str) -> bool {
+    matches!(
+        kind,
+        "integer_literal"
+            | "float_literal"
+            | "int_literal"
+            | "imaginary_literal"
+            | "number_literal"
+            | "number"
+            | "integer"
+            | "float"
+            | "decimal_integer_literal"
+            | "hex_integer_literal"
+            | "octal_integer_literal"
+            | "binary_integer_literal"
+            | "decimal_floating_point_literal"
+            | "hex_floating_point_literal"
+    )
+}
+
+/// The value of `#define NAME <number>`, which tree-sitter keeps as raw text.
+fn preproc_number(node: Node<'_>, source: &[u8]) -> Option<NumericLiteral> {
+    let text = node.utf8_text(source).ok()?;
+    let value = text.trim();
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let numeric = digits.starts_with(|c: char| c.is_ascii_digit())
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '\''));
+    if !numeric || text[..text.len() - text.trim_start().len()].contains('\n') {
+        return None;
+    }
+    let start = node.start_position();
+    Some(NumericLiteral {
+        line: start.row + 1,
+        column: start.column + text.len() - text.trim_start().len() + 1,
+        text: value.to_string(),
+    })
+}
+
+/// The `-<literal>` unary expression wrapping `node`, if any.
+fn negation_parent(node: Node<'_>) -> Option<Node<'_>> {
+    let parent = node.parent()?;
+    let operator = parent.child(0)?;
+    (parent.child_count() == 2 && operator.kind() == "-" && parent.child(1)? == node)
+        .then_some(parent)
+}
+
 fn is_call_like(kind: &str) -> bool {
     matches!(
         kind,
@@ -141,6 +237,7 @@ fn is_call_like(kind: &str) -> bool {
             | "function_call_expression"
             | "function_call"
             | "call"
+            | "macro_invocation"
     )
 }
 
@@ -0,0 +1,553 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep audit`: locate risky constructs for security review.
+//!
+//! Findings come from the syntax tree, so a construct named in a comment or
+//! a string is never reported. Rules are grouped in presets:
+//!
+//! - `rust`: `unsafe` blocks, functions, impls, and traits; `transmute`.
+//! - `python`: `eval`/`exec`, `pickle.load(s)`, `shell=True`.
+//! - `javascript` (also TypeScript/TSX): `eval`, `new Function`,
+//!   `dangerouslySetInnerHTML`, and `innerHTML`/`outerHTML` assignment.
+//! - `sql` (every language): a query string built from non-literal parts by
+//!   `+`, `%`, f-strings, template strings, `format!`, `.format()`,
+//!   `Sprintf`, or `String.format`.
+//!
+//! Findings are reported like `check` violations, so the annotation, SARIF,
+//! and quickfix formats work the same way. Any `error` finding fails the
+//! command.
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::FileScanner;
+use crate::parser::languages::LANGUAGES;
+use crate::query::check::{print_violations, RuleInfo, Severity, SeverityCounts, Violation};
+use cgrep::output::{print_json, print_json2};
+use cgrep::paths;
+
+/// Start of an SQL statement that reads or writes rows.
+static SQL_STATEMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?is)^\s*(?:select\b.*\bfrom\b|insert\s+into\b|update\s+\S+\s+set\b|delete\s+from\b)",
+    )
+    .expect("valid SQL statement regex")
+});
+
+/// A built-in audit rule.
+struct AuditRule {
+    id: &'static str,
+    preset: &'static str,
+    severity: Severity,
+    message: &'static str,
+}
+
+const PRESETS: &[&str] = &["rust", "python", "javascript", "sql"];
+
+const RULES: &[AuditRule] = &[
+    AuditRule {
+        id: "rust-unsafe",
+        preset: "rust",
+        severity: Severity::Warning,
+        message: "unsafe code: check the invariants it relies on",
+    },
+    AuditRule {
+        id: "rust-transmute",
+        preset: "rust",
+        severity: Severity::Warning,
+        message: "mem::transmute reinterprets bits without type checks",
+    },
+    AuditRule {
+        id: "python-eval",
+        preset: "python",
+        severity: Severity::Error,
+        message: "eval/exec runs arbitrary code",
+    },
+    AuditRule {
+        id: "python-pickle",
+        preset: "python",
+        severity: Severity::Warning,
+        message: "unpickling untrusted data runs arbitrary code",
+    },
+    AuditRule {
+        id: "python-shell-true",
+        preset: "python",
+        severity: Severity::Warning,
+        message: "shell=True passes the command through the shell",
+    },
+    AuditRule {
+        id: "js-eval",
+        preset: "javascript",
+        severity: Severity::Error,
+        message: "eval/new Function runs arbitrary code",
+    },
+    AuditRule {
+        id: "js-dangerously-set-inner-html",
+        preset: "javascript",
+        severity: Severity::Warning,
+        message: "dangerouslySetInnerHTML renders unescaped HTML",
+    },
+    AuditRule {
+        id: "js-inner-html",
+        preset: "javascript",
+        severity: Severity::Warning,
+        message: "assigning innerHTML/outerHTML renders unescaped HTML",
+    },
+    AuditRule {
+        id: "sql-string-concat",
+        preset: "sql",
+        severity: Severity::Error,
+        message: "SQL built from non-literal parts: use bound parameters",
+    },
+];
+
+fn rule(id: &str) -> &'static AuditRule {
+    RULES
+        .iter()
+        .find(|rule| rule.id == id)
+        .expect("known audit rule")
+}
+
+#[derive(Debug, Serialize)]
+struct AuditJson2Meta<'a> {
+    schema_version: &'static str,
+    command: &'static str,
+    presets: &'a [&'a str],
+    files_checked: usize,
+    counts: SeverityCounts,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditJson2Payload<'a> {
+    meta: AuditJson2Meta<'a>,
+    results: &'a [Violation],
+}
+
+/// Run the audit command.
+pub fn run(
+    presets: &[String],
+    path: Option<&str>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let cwd = paths::canonicalize(&std::env::current_dir()?)?;
+    let root = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => cwd.clone(),
+    };
+    let mut selected: Vec<&str> = Vec::new();
+    for preset in presets {
+        let Some(known) = PRESETS.iter().find(|known| **known == preset) else {
+            bail!(
+                "Unknown audit preset `{}` (available: {})",
+                preset,
+                PRESETS.join(", ")
+            );
+        };
+        if !selected.contains(known) {
+            selected.push(known);
+        }
+    }
+    if selected.is_empty() {
+        selected = PRESETS.to_vec();
+    }
+
+    let mut violations = Vec::new();
+    let mut files_checked = 0;
+    for file in FileScanner::new(&root).scan()? {
+        let Some(language) = file.language.as_deref() else {
+            continue;
+        };
+        let found = findings(&file.content, language, &selected);
+        files_checked += 1;
+        if found.is_empty() {
+            continue;
+        }
+        let display_path =
+            paths::relative_display(&file.path, &cwd).unwrap_or_else(|| paths::display(&file.path));
+        let lines: Vec<&str> = file.content.lines().collect();
+        for (rule, line, column) in found {
+            violations.push(Violation {
+                rule: rule.id.to_string(),
+                severity: rule.severity,
+                message: rule.message.to_string(),
+                path: display_path.clone(),
+                line,
+                column,
+                code: lines
+                    .get(line - 1)
+                    .map(|code| code.trim().to_string())
+                    .unwrap_or_default(),
+            });
+        }
+    }
+    violations.sort_by(|a, b| {
+        (&a.path, a.line, a.column, &a.rule).cmp(&(&b.path, b.line, b.column, &b.rule))
+    });
+    let counts = SeverityCounts::of(&violations);
+    let errors = counts.error;
+
+    match format {
+        OutputFormat::Json => print_json(&violations, compact)?,
+        OutputFormat::Json2 => {
+            let payload = AuditJson2Payload {
+                meta: AuditJson2Meta {
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
+                    command: "audit",
+                    presets: &selected,
+                    files_checked,
+                    counts,
+                },
+                results: &violations,
+            };
+            print_json2("audit", &payload, compact)?;
+        }
+        _ => {
+            let infos: Vec<RuleInfo<'_>> = RULES
+                .iter()
+                .filter(|rule| selected.contains(&rule.preset))
+                .map(|rule| RuleInfo {
+                    id: rule.id,
+                    message: rule.message,
+                    severity: rule.severity,
+                })
+                .collect();
+            print_violations(&violations, &infos, format, compact)?;
+        }
+    }
+
+    if errors > 0 {
+        bail!("{errors} audit finding(s) with severity error");
+    }
+    Ok(())
+}
+
+/// Rules matched in one file, with the 1-based line and column of each.
+fn findings(
+    content: &str,
+    language: &str,
+    presets: &[&str],
+) -> Vec<(&'static AuditRule, usize, usize)> {
+    let language_preset = match language {
+        "rust" => Some("rust"),
+        "python" => Some("python"),
+        "javascript" | "typescript" | "tsx" => Some("javascript"),
+        _ => None,
+    }
+    .filter(|preset| presets.contains(preset));
+    let sql = presets.contains(&"sql");
+    if language_preset.is_none() && !sql {
+        return Vec::new();
+    }
+    let Some(mut parser) = LANGUAGES.parser(language) else {
+        return Vec::new();
+    };
+    let Some(tree) = parser.parse(content, None) else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    let source = content.as_bytes();
+    while let Some(node) = stack.pop() {
+        let id = match language_preset {
+            Some("rust") => rust_rule(node, source),
+            Some("python") => python_rule(node, source),
+            Some("javascript") => js_rule(node, source),
+            _ => None,
+        }
+        .or_else(|| (sql && builds_sql(node, language, source)).then_some("sql-string-concat"));
+        if let Some(id) = id {
+            let position = node.start_position();
+            found.push((rule(id), position.row + 1, position.column + 1));
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    found
+}
+
+fn text<'s>(node: Node, source: &'s [u8]) -> &'s str {
+    node.utf8_text(source).unwrap_or_default()
+}
+
+/// Last segment of a callee: `transmute` for `std::mem::transmute`,
+/// `loads` for `pickle.loads`.
+fn callee_name<'s>(function: Node, source: &'s [u8]) -> &'s str {
+    text(function, source)
+        .rsplit(['.', ':'])
+        .next()
+        .unwrap_or_default()
+}
+
+fn has_child_kind(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|child| child.kind() == kind);
+    found
+}
+
+fn rust_rule(node: Node, source: &[u8]) -> Option<&'static str> {
+    match node.kind() {
+        "unsafe_block" => Some("rust-unsafe"),
+        "function_item" | "function_signature_item" => {
+            let mut cursor = node.walk();
+            let is_unsafe = node.children(&mut cursor).any(|child| {
+                child.kind() == "function_modifiers" && has_child_kind(child, "unsafe")
+            });
+            is_unsafe.then_some("rust-unsafe")
+        }
+        "impl_item" | "trait_item" => has_child_kind(node, "unsafe").then_some("rust-unsafe"),
+        "call_expression" => {
+            let function = node.child_by_field_name("function")?;
+            let name = callee_name(function, source);
+            let name = name.split("::<").next().unwrap_or(name);
+            (name == "transmute" || text(function, source).contains("transmute::<"))
+                .then_some("rust-transmute")
+        }
+        _ => None,
+    }
+}
+
+fn python_rule(node: Node, source: &[u8]) -> Option<&'static str> {
+    if node.kind() != "call" {
+        return None;
+    }
+    let function = node.child_by_field_name("function")?;
+    let callee = text(function, source);
+    if function.kind() == "identifier" && matches!(callee, "eval" | "exec") {
+        return Some("python-eval");
+    }
+    if matches!(
+        callee,
+        "pickle.load" | "pickle.loads" | "cPickle.load" | "cPickle.loads"
+    ) {
+        return Some("python-pickle");
+    }
+    let arguments = node.child_by_field_name("arguments")?;
+    let mut cursor = arguments.walk();
+    let shell = arguments.named_children(&mut cursor).any(|arg| {
+        arg.kind() == "keyword_argument"
+            && arg
+                .child_by_field_name("name")
+                .is_some_and(|name| text(name, source) == "shell")
+            && arg
+                .child_by_field_name("value")
+                .is_some_and(|value| value.kind() == "true")
+    });
+    shell.then_some("python-shell-true")
+}
+
+fn js_rule(node: Node, source: &[u8]) -> Option<&'static str> {
+    match node.kind() {
+        "call_expression" => {
+            let function = node.child_by_field_name("function")?;
+            (function.kind() == "identifier" && text(function, source) == "eval")
+                .then_some("js-eval")
+        }
+        "new_expression" => {
+            let constructor = node.child_by_field_name("constructor")?;
+            (text(constructor, source) == "Function").then_some("js-eval")
+        }
+        "jsx_attribute" => {
+            let name = node.named_child(0)?;
+            (text(name, source) == "dangerouslySetInnerHTML")
+                .then_some("js-dangerously-set-inner-html")
+        }
+        "assignment_expression" | "augmented_assignment_expression" => {
+            let left = node
+                .child_by_field_name("left")
+                .filter(|left| left.kind() == "member_expression")?;
+            let property = text(left.child_by_field_name("property")?, source);
+            matches!(property, "innerHTML" | "outerHTML").then_some("js-inner-html")
+        }
+        _ => None,
+    }
+}
+
+fn is_string(node: Node) -> bool {
+    matches!(
+        node.kind(),
+        "string"
+            | "string_literal"
+            | "raw_string_literal"
+            | "interpreted_string_literal"
+            | "template_string"
+    )
+}
+
+/// Text of a string literal without prefixes and quotes.
+fn string_text<'s>(node: Node, source: &'s [u8]) -> &'s str {
+    text(node, source)
+        .trim_start_matches(|ch: char| ch.is_ascii_alphabetic() || ch == '#' || ch == '@')
+        .trim_start_matches(['"', '\'', '`'])
+}
+
+fn is_sql(node: Node, source: &[u8]) -> bool {
+    is_string(node) && SQL_STATEMENT.is_match(string_text(node, source))
+}
+
+/// Whether a string literal interpolates values (f-strings, template
+/// strings).
+fn interpolates(node: Node) -> bool {
+    has_child_kind(node, "interpolation") || has_child_kind(node, "template_substitution")
+}
+
+/// Whether `node` builds an SQL statement from non-literal parts.
+fn builds_sql(node: Node, language: &str, source: &[u8]) -> bool {
+    match node.kind() {
+        // `"SELECT ... " + id`, `"... %s" % id`; reported once, at the
+        // outermost operator of the chain.
+        "binary_expression" | "binary_operator" => {
+            let operator = node
+                .child_by_field_name("operator")
+                .map(|op| text(op, source))
+                .unwrap_or_default();
+            if !(operator == "+" || (operator == "%" && language == "python")) {
+                return false;
+            }
+            let parent_continues = node.parent().is_some_and(|parent| {
+                parent.kind() == node.kind()
+                    && parent.child_by_field_name("left") == Some(node)
+                    && parent
+                        .child_by_field_name("operator")
+                        .is_some_and(|op| text(op, source) == operator)
+            });
+            if parent_continues {
+                return false;
+            }
+            let mut head = node;
+            let mut dynamic = false;
+            while matches!(head.kind(), "binary_expression" | "binary_operator") {
+                if let Some(right) = head.child_by_field_name("right") {
+                    dynamic |= !is_string(right);
+                }
+                let Some(left) = head.child_by_field_name("left") else {
+                    return false;
+                };
+                head = left;
+            }
+            dynamic && is_sql(head, source)
+        }
+        // f"SELECT ... {id}", `SELECT ... ${id}` (not tagged templates).
+        "string" | "template_string" => {
+            interpolates(node)
+                && is_sql(node, source)
+                && node
+                    .parent()
+                    .is_none_or(|parent| parent.kind() != "call_expression")
+        }
+        // `format!("SELECT ... {}", id)`
+        "macro_invocation" => {
+            let is_format = node
+                .child_by_field_name("macro")
+                .is_some_and(|name| text(name, source) == "format");
+            let mut cursor = node.walk();
+            let tree = node
+                .named_children(&mut cursor)
+                .find(|child| child.kind() == "token_tree");
+            is_format
+                && tree
+                    .and_then(|tree| tree.named_child(0))
+                    .is_some_and(|first| is_sql(first, source) && text(first, source).contains('{'))
+        }
+        // `"SELECT ...".format(id)`, `fmt.Sprintf("SELECT ... %s", id)`,
+        // `String.format("SELECT ... %s", id)`
+        "call" | "call_expression" | "method_invocation" => {
+            let (callee, first_arg) = match node.kind() {
+                "method_invocation" => (
+                    node.child_by_field_name("name")
+                        .map(|name| text(name, source))
+                        .unwrap_or_default(),
+                    node.child_by_field_name("arguments")
+                        .and_then(|args| args.named_child(0)),
+                ),
+                _ => {
+                    let Some(function) = node.child_by_field_name("function") else {
+                        return false;
+                    };
+                    if function.kind() == "attribute"
+                        && function
+                            .child_by_field_name("attribute")
+                            .is_some_and(|attr| text(attr, source) == "format")
+                    {
+                        return function
+                            .child_by_field_name("object")
+                            .is_some_and(|object| is_sql(object, source));
+                    }
+                    (
+                        callee_name(function, source),
+                        node.child_by_field_name("arguments")
+                            .and_then(|args| args.named_child(0)),
+                    )
+                }
+            };
+            matches!(callee, "Sprintf" | "format")
+                && first_arg
+                    .is_some_and(|first| is_sql(first, source) && text(first, source).contains('%'))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule_ids(content: &str, language: &str) -> Vec<(&'static str, usize)> {
+        findings(content, language, PRESETS)
+            .into_iter()
+            .map(|(rule, line, _)| (rule.id, line))
+            .collect()
+    }
+
+    #[test]
+    fn risky_constructs_are_found_outside_comments_and_strings() {
+        let rust = "// unsafe { ptr.read() }\nunsafe fn raw() {}\nfn f(p: *const u8) -> u8 {\n    let s = \"unsafe\";\n    unsafe { *p }\n}\nunsafe impl Send for X {}\nfn g(x: u32) -> f32 { unsafe { std::mem::transmute::<u32, f32>(x) } }\nfn q(id: &str) -> String { format!(\"SELECT * FROM users WHERE id = {}\", id) }\n";
+        assert_eq!(
+            rule_ids(rust, "rust"),
+            [
+                ("rust-unsafe", 2),
+                ("rust-unsafe", 5),
+                ("rust-unsafe", 7),
+                ("rust-unsafe", 8),
+                ("rust-transmute", 8),
+                ("sql-string-concat", 9),
+            ]
+        );
+
+        let python = "# eval(x)\neval(expr)\ndata = pickle.loads(blob)\nsubprocess.run(cmd, shell=True)\ncur.execute(\"SELECT * FROM t WHERE id = \" + user_id)\ncur.execute(f\"DELETE FROM t WHERE id = {user_id}\")\ncur.execute(\"SELECT * FROM t WHERE id = %s\", (user_id,))\ncur.execute(\"UPDATE t SET a = '%s'\" % a)\n";
+        assert_eq!(
+            rule_ids(python, "python"),
+            [
+                ("python-eval", 2),
+                ("python-pickle", 3),
+                ("python-shell-true", 4),
+                ("sql-string-concat", 5),
+                ("sql-string-concat", 6),
+                ("sql-string-concat", 8),
+            ]
+        );
+
+        let tsx = "const a = <div dangerouslySetInnerHTML={{ __html: html }} />;\nel.innerHTML = html;\nconst f = new Function('return 1');\nconst q = `SELECT * FROM users WHERE name = '${name}'`;\nconst safe = sql`SELECT * FROM users WHERE name = ${name}`;\nconst p = 'SELECT * FROM t WHERE a = ' + a + ' AND b = ' + b;\n";
+        assert_eq!(
+            rule_ids(tsx, "tsx"),
+            [
+                ("js-dangerously-set-inner-html", 1),
+                ("js-inner-html", 2),
+                ("js-eval", 3),
+                ("sql-string-concat", 4),
+                ("sql-string-concat", 6),
+            ]
+        );
+
+        let go = "package db\nfunc q(id string) string {\n\treturn fmt.Sprintf(\"SELECT * FROM t WHERE id = %s\", id)\n}\n";
+        assert_eq!(rule_ids(go, "go"), [("sql-string-concat", 3)]);
+    }
+}
@@ -0,0 +1,347 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep boundaries`: check the import graph against allowed dependencies
+//! between layers declared in a TOML file.
+//!
+//! Each `[[layer]]` names the directories (or globs) it owns and the layers
+//! it may import from. A file belongs to the first layer that claims it.
+//! Imports are resolved the same way as `cgrep imports`; an import of a file
+//! in another layer that is not in `allow` is a violation. Imports within a
+//! layer, of unclaimed files, and of external packages are not checked.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::FileScanner;
+use crate::query::check::Severity;
+use crate::query::imports::{resolve_imports, Target};
+use cgrep::filters::CompiledGlob;
+use cgrep::output::{
+    print_github_annotations, print_gitlab_report, print_json, print_quickfix, Annotation,
+    QuickfixEntry,
+};
+use cgrep::paths;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoundaryFile {
+    #[serde(rename = "layer")]
+    layers: Vec<LayerSpec>,
+}
+
+/// A layer as written in the rules file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LayerSpec {
+    name: String,
+    /// Directories (`src/domain`) or globs (`src/**/models/*.rs`)
+    paths: Vec<String>,
+    /// Layers this one may import from
+    #[serde(default)]
+    allow: Vec<String>,
+    /// Severity of this layer's violations [default: error]
+    severity: Option<Severity>,
+    message: Option<String>,
+}
+
+enum PathMatcher {
+    Dir(String),
+    Glob(CompiledGlob),
+}
+
+struct Layer {
+    name: String,
+    paths: Vec<PathMatcher>,
+    allow: HashSet<String>,
+    severity: Severity,
+    message: Option<String>,
+}
+
+impl Layer {
+    fn contains(&self, rel_path: &str) -> bool {
+        self.paths.iter().any(|matcher| match matcher {
+            PathMatcher::Dir(dir) => rel_path
+                .strip_prefix(dir.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+            PathMatcher::Glob(glob) => glob.is_match(rel_path),
+        })
+    }
+}
+
+fn parse_layers(raw: &str) -> Result<Vec<Layer>> {
+    let file: BoundaryFile = toml::from_str(raw)?;
+    let names: HashSet<&str> = file
+        .layers
+        .iter()
+        .map(|layer| layer.name.as_str())
+        .collect();
+    if names.len() != file.layers.len() {
+        bail!("Layer names must be unique");
+    }
+    let mut layers = Vec::with_capacity(file.layers.len());
+    for spec in &file.layers {
+        if spec.paths.is_empty() {
+            bail!("Layer `{}` must list at least one path", spec.name);
+        }
+        if let Some(unknown) = spec
+            .allow
+            .iter()
+            .find(|name| !names.contains(name.as_str()))
+        {
+            bail!("Layer `{}` allows unknown layer `{unknown}`", spec.name);
+        }
+        let paths = spec
+            .paths
+            .iter()
+            .map(|path| {
+                if path.contains(['*', '?']) {
+                    CompiledGlob::new(path)
+                        .map(PathMatcher::Glob)
+                        .with_context(|| {
+                            format!("Layer `{}` has an invalid glob `{path}`", spec.name)
+                        })
+                } else {
+                    let dir = path.trim_start_matches("./").trim_end_matches('/');
+                    Ok(PathMatcher::Dir(dir.to_string()))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        layers.push(Layer {
+            name: spec.name.clone(),
+            paths,
+            allow: spec.allow.iter().cloned().collect(),
+            severity: spec.severity.unwrap_or(Severity::Error),
+            message: spec.message.clone(),
+        });
+    }
+    Ok(layers)
+}
+
+fn load_layers(path: &Path) -> Result<Vec<Layer>> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read boundaries file {}", path.display()))?;
+    parse_layers(&raw).with_context(|| format!("Invalid boundaries file {}", path.display()))
+}
+
+fn layer_of<'a>(layers: &'a [Layer], rel_path: &str) -> Option<&'a Layer> {
+    layers.iter().find(|layer| layer.contains(rel_path))
+}
+
+/// An import that crosses a boundary its layer does not allow.
+#[derive(Debug, Serialize)]
+struct BoundaryViolation {
+    layer: String,
+    imported_layer: String,
+    severity: Severity,
+    message: String,
+    path: String,
+    line: usize,
+    /// The import as written
+    import: String,
+    /// The file it resolves to
+    imported_path: String,
+    code: String,
+}
+
+/// Run the boundaries command
+pub fn run(
+    rules_path: &str,
+    path: Option<&str>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let cwd = paths::canonicalize(&std::env::current_dir()?)?;
+    let root = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => cwd.clone(),
+    };
+    let layers = load_layers(Path::new(rules_path))?;
+
+    let mut files = FileScanner::new(&root).list_files()?;
+    files.sort();
+    let mut files_checked = 0;
+    let mut violations: Vec<BoundaryViolation> = Vec::new();
+    for file in files {
+        let rel_path = paths::relative_display(&file, &root)
+            .unwrap_or_default()
+            .replace('\\', "/");
+        let Some(layer) = layer_of(&layers, &rel_path) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let Some(imports) = resolve_imports(&root, &file, &content) else {
+            continue;
+        };
+        files_checked += 1;
+        let display_path =
+            paths::relative_display(&file, &cwd).unwrap_or_else(|| paths::display(&file));
+        for (import, target) in imports {
+            let Target::Internal(imported_path) = target else {
+                continue;
+            };
+            let Some(imported) = layer_of(&layers, &imported_path) else {
+                continue;
+            };
+            if imported.name == layer.name || layer.allow.contains(&imported.name) {
+                continue;
+            }
+            violations.push(BoundaryViolation {
+                layer: layer.name.clone(),
+                imported_layer: imported.name.clone(),
+                severity: layer.severity,
+                message: layer.message.clone().unwrap_or_else(|| {
+                    format!("`{}` may not import from `{}`", layer.name, imported.name)
+                }),
+                path: display_path.clone(),
+                line: import.line,
+                import: import.specifier,
+                imported_path,
+                code: import.text,
+            });
+        }
+    }
+    let errors = violations
+        .iter()
+        .filter(|violation| violation.severity == Severity::Error)
+        .count();
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&violations, compact)?,
+        OutputFormat::Github | OutputFormat::Gitlab => {
+            let titles: Vec<String> = violations
+                .iter()
+                .map(|violation| format!("{} -> {}", violation.layer, violation.imported_layer))
+                .collect();
+            let annotations: Vec<Annotation<'_>> = violations
+                .iter()
+                .zip(&titles)
+                .map(|(violation, title)| Annotation {
+                    path: &violation.path,
+                    line: Some(violation.line),
+                    level: violation.severity.annotation_level(),
+                    title,
+                    message: &violation.message,
+                    id: None,
+                })
+                .collect();
+            if format == OutputFormat::Github {
+                print_github_annotations(&annotations);
+            } else {
+                print_gitlab_report(&annotations, compact)?;
+            }
+        }
+        OutputFormat::Quickfix => {
+            let texts: Vec<String> = violations
+                .iter()
+                .map(|violation| {
+                    format!(
+                        "{}: {} ({})",
+                        violation.severity.as_str(),
+                        violation.message,
+                        violation.imported_path
+                    )
+                })
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = violations
+                .iter()
+                .zip(&texts)
+                .map(|(violation, text)| QuickfixEntry {
+                    path: &violation.path,
+                    line: violation.line,
+                    column: 1,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Sarif => {
+            print_text(&violations, layers.len(), files_checked)
+        }
+    }
+
+    if errors > 0 {
+        bail!("{errors} boundary violation(s) with severity error");
+    }
+    Ok(())
+}
+
+fn print_text(violations: &[BoundaryViolation], layer_count: usize, files_checked: usize) {
+    if violations.is_empty() {
+        println!(
+            "{} No boundary violations ({} layers, {} files checked)",
+            "✓".green(),
+            layer_count,
+            files_checked
+        );
+        return;
+    }
+    for violation in violations {
+        let severity = match violation.severity {
+            Severity::Error => violation.severity.as_str().red().bold(),
+            Severity::Warning => violation.severity.as_str().yellow(),
+            Severity::Info => violation.severity.as_str().blue(),
+        };
+        println!(
+            "{}:{} {} [{} -> {}] {}",
+            violation.path.cyan(),
+            violation.line.to_string().yellow(),
+            severity,
+            violation.layer,
+            violation.imported_layer,
+            violation.message
+        );
+        println!(
+            "    {} {}",
+            violation.code.dimmed(),
+            format!("({})", violation.imported_path).dimmed()
+        );
+    }
+    println!(
+        "\n{} {} boundary violations ({} files checked)",
+        "✗".red(),
+        violations.len(),
+        files_checked
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_match_directories_and_globs() {
+        let layers = parse_layers(
+            "[[layer]]\nname = \"domain\"\npaths = [\"./src/domain/\"]\n\n[[layer]]\nname = \"tests\"\npaths = [\"**/*_test.go\"]\nallow = [\"domain\"]\nseverity = \"warning\"\n",
+        )
+        .expect("layers");
+        assert_eq!(
+            layer_of(&layers, "src/domain/user.rs").map(|l| l.name.as_str()),
+            Some("domain")
+        );
+        assert!(layer_of(&layers, "src/domainx/user.rs").is_none());
+        let tests = layer_of(&layers, "pkg/store_test.go").expect("tests layer");
+        assert_eq!(tests.severity, Severity::Warning);
+        assert!(tests.allow.contains("domain"));
+    }
+
+    #[test]
+    fn rules_reject_unknown_layers_and_duplicates() {
+        assert!(
+            parse_layers("[[layer]]\nname = \"a\"\npaths = [\"a\"]\nallow = [\"b\"]\n").is_err()
+        );
+        assert!(parse_layers(
+            "[[layer]]\nname = \"a\"\npaths = [\"a\"]\n\n[[layer]]\nname = \"a\"\npaths = [\"b\"]\n"
+        )
+        .is_err());
+        assert!(parse_layers("[[layer]]\nname = \"a\"\npaths = []\n").is_err());
+    }
+}
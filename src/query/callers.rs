@@ -6,20 +6,25 @@ use anyhow::Result;
 use colored::Colorize;
 use regex::Regex;
 use serde::Serialize;
+use std::path::Path;
 
 use crate::cli::{OutputFormat, UsageSearchMode};
-use crate::indexer::scanner::FileScanner;
+use crate::indexer::scanner::{FileScanner, ScannedFile};
+use crate::parser::symbols::{enclosing_symbol, Symbol, SymbolExtractor};
 use crate::query::ast_usage::AstUsageExtractor;
 use crate::query::index_filter::{find_files_with_content, read_scanned_files};
-use cgrep::output::print_json;
+use cgrep::output::{print_json, print_quickfix, QuickfixEntry};
 use cgrep::utils::get_root_with_index;
 
 /// Caller result for JSON output
 #[derive(Debug, Serialize)]
-struct CallerResult {
-    path: String,
-    line: usize,
-    code: String,
+pub(crate) struct CallerResult {
+    pub(crate) path: String,
+    pub(crate) line: usize,
+    pub(crate) code: String,
+    /// Qualified name of the symbol holding the call, closures included.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) scope: Option<String>,
 }
 
 /// Run the callers command
@@ -39,18 +44,75 @@ pub fn run(
         }
     };
     let mut ast = AstUsageExtractor::new();
+    let results = find_callers(&files, &search_root, function, mode, &mut ast)?;
 
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => {
+            print_json(&results, compact)?;
+        }
+        OutputFormat::Quickfix => {
+            let entries: Vec<QuickfixEntry<'_>> = results
+                .iter()
+                .map(|result| QuickfixEntry {
+                    path: &result.path,
+                    line: result.line,
+                    column: 1,
+                    text: &result.code,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            if results.is_empty() {
+                println!("{} No callers found for: {}", "✗".red(), function.yellow());
+            } else {
+                println!(
+                    "\n{} Finding callers of: {}\n",
+                    "🔍".cyan(),
+                    function.yellow()
+                );
+                for result in &results {
+                    println!(
+                        "  {}:{} {}{}",
+                        result.path.cyan(),
+                        result.line.to_string().yellow(),
+                        result.code.dimmed(),
+                        scope_suffix(result.scope.as_deref())
+                    );
+                }
+                println!(
+                    "\n{} Found {} call sites",
+                    "✓".green(),
+                    results.len().to_string().cyan()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Call sites of `function` in `files`, with paths relative to `search_root`.
+pub(crate) fn find_callers(
+    files: &[ScannedFile],
+    search_root: &Path,
+    function: &str,
+    mode: UsageSearchMode,
+    ast: &mut AstUsageExtractor,
+) -> Result<Vec<CallerResult>> {
     // Pattern to match function calls
     // Matches: functionName( or object.functionName( or object?.functionName(
-    let pattern = format!(r"\b{}\s*\(", regex::escape(function));
+    // or macroName!(
+    let pattern = format!(r"\b{}!?\s*\(", regex::escape(function));
     let re = Regex::new(&pattern)?;
 
     let mut results: Vec<CallerResult> = Vec::new();
+    let extractor = SymbolExtractor::new();
 
-    for file in &files {
+    for file in files {
         let rel_path = file
             .path
-            .strip_prefix(&search_root)
+            .strip_prefix(search_root)
             .unwrap_or(&file.path)
             .display()
             .to_string();
@@ -64,6 +126,7 @@ pub fn run(
             })
         };
 
+        let mut scopes = FileScopes::new(&extractor, file);
         if let Some(matches) = ast_matches {
             for m in matches {
                 let code = file
@@ -80,6 +143,7 @@ pub fn run(
                     path: rel_path.clone(),
                     line: m.line,
                     code,
+                    scope: scopes.at(m.line),
                 });
             }
             continue;
@@ -107,39 +171,47 @@ pub fn run(
                 path: rel_path.clone(),
                 line: line_num + 1,
                 code: line.trim().to_string(),
+                scope: scopes.at(line_num + 1),
             });
         }
     }
 
-    match format {
-        OutputFormat::Json | OutputFormat::Json2 => {
-            print_json(&results, compact)?;
-        }
-        OutputFormat::Text => {
-            if results.is_empty() {
-                println!("{} No callers found for: {}", "✗".red(), function.yellow());
-            } else {
-                println!(
-                    "\n{} Finding callers of: {}\n",
-                    "🔍".cyan(),
-                    function.yellow()
-                );
-                for result in &results {
-                    println!(
-                        "  {}:{} {}",
-                        result.path.cyan(),
-                        result.line.to_string().yellow(),
-                        result.code.dimmed()
-                    );
-                }
-                println!(
-                    "\n{} Found {} call sites",
-                    "✓".green(),
-                    results.len().to_string().cyan()
-                );
-            }
+    Ok(results)
+}
+
+/// Symbols of one file, extracted on first use, for naming the scope of a
+/// match.
+pub(crate) struct FileScopes<'a> {
+    extractor: &'a SymbolExtractor,
+    file: &'a ScannedFile,
+    symbols: Option<Vec<Symbol>>,
+}
+
+impl<'a> FileScopes<'a> {
+    pub(crate) fn new(extractor: &'a SymbolExtractor, file: &'a ScannedFile) -> Self {
+        Self {
+            extractor,
+            file,
+            symbols: None,
         }
     }
 
-    Ok(())
+    /// Qualified name of the innermost symbol around `line`.
+    pub(crate) fn at(&mut self, line: usize) -> Option<String> {
+        let symbols = self.symbols.get_or_insert_with(|| {
+            self.file
+                .language
+                .as_deref()
+                .and_then(|lang| self.extractor.extract(&self.file.content, lang).ok())
+                .unwrap_or_default()
+        });
+        enclosing_symbol(symbols, line).map(Symbol::qualified_name)
+    }
+}
+
+/// Dimmed ` (in scope)` after a text-mode match.
+pub(crate) fn scope_suffix(scope: Option<&str>) -> String {
+    scope.map_or_else(String::new, |scope| {
+        format!(" {}", format!("(in {scope})").dimmed())
+    })
 }
@@ -3,9 +3,14 @@
 //! Git changed-files filter helpers.
 
 use anyhow::{bail, Context, Result};
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+
+use crate::indexer::git_history::{self, HistoryFilter};
+use crate::indexer::index::indexed_git_history;
+use crate::indexer::sparse::SparseExclusions;
 
 #[derive(Debug, Clone)]
 pub struct ChangedFiles {
@@ -13,25 +18,170 @@ pub struct ChangedFiles {
     repo_root: PathBuf,
     scope_prefix: Option<String>,
     paths: HashSet<String>,
+    /// Added lines per path, when the set comes from a patch
+    lines: Option<HashMap<String, BTreeSet<usize>>>,
     signature: String,
 }
 
+/// Revision label used for the staged (git index) file set.
+pub const STAGED_REV: &str = "staged";
+
+/// Revision label used for the uncommitted (git status) file set.
+pub const DIRTY_REV: &str = "dirty";
+
+/// Revision label used for the lines added by a patch.
+pub const PATCH_REV: &str = "patch";
+
 impl ChangedFiles {
     pub fn from_scope(scope_root: &Path, rev: &str) -> Result<Self> {
-        let scope_root = scope_root
-            .canonicalize()
-            .with_context(|| format!("Failed to resolve path: {}", scope_root.display()))?;
-        let repo_root = git_repo_root(&scope_root)?;
-        let scope_prefix = scope_root.strip_prefix(&repo_root).ok().and_then(|p| {
-            let v = normalize_rel_path_str(&p.to_string_lossy());
-            if v.is_empty() {
-                None
-            } else {
-                Some(v)
-            }
-        });
+        Self::collect(scope_root, Some(rev))
+    }
+
+    /// Files staged for the next commit (`git diff --cached`), without untracked files.
+    pub fn staged(scope_root: &Path) -> Result<Self> {
+        Self::collect(scope_root, None)
+    }
+
+    /// Files with uncommitted changes (`git status`): staged, unstaged, and
+    /// untracked. Works before the first commit, unlike `--changed HEAD`.
+    pub fn dirty(scope_root: &Path) -> Result<Self> {
+        let (repo_root, scope_prefix) = resolve_scope(scope_root, "--only-dirty")?;
+        let paths = collect_dirty_paths(&repo_root, scope_prefix.as_deref())?;
+        let signature = signature_for(DIRTY_REV, scope_prefix.as_deref(), &paths);
+
+        Ok(Self {
+            rev: DIRTY_REV.to_string(),
+            repo_root,
+            scope_prefix,
+            paths,
+            lines: None,
+            signature,
+        })
+    }
+
+    /// Lines added by a unified diff (`--from-patch`). Paths in the diff are
+    /// taken relative to the repository root, or to `scope_root` outside git,
+    /// and line numbers to the files as they are after the patch.
+    pub fn from_patch(scope_root: &Path, patch: &str) -> Result<Self> {
+        let (repo_root, scope_prefix) = match resolve_scope(scope_root, "--from-patch") {
+            Ok(scope) => scope,
+            Err(_) => (
+                scope_root
+                    .canonicalize()
+                    .with_context(|| format!("Failed to resolve path: {}", scope_root.display()))?,
+                None,
+            ),
+        };
+        let lines: HashMap<String, BTreeSet<usize>> = parse_added_lines(patch)
+            .into_iter()
+            .filter(|(path, _)| match &scope_prefix {
+                Some(prefix) => path.starts_with(&format!("{prefix}/")),
+                None => true,
+            })
+            .collect();
+        let paths: HashSet<String> = lines.keys().cloned().collect();
+        let mut entries: Vec<String> = lines
+            .iter()
+            .map(|(path, numbers)| format!("{path}:{numbers:?}"))
+            .collect();
+        entries.sort();
+        let signature = signature_for(
+            PATCH_REV,
+            scope_prefix.as_deref(),
+            &entries.into_iter().collect(),
+        );
+
+        Ok(Self {
+            rev: PATCH_REV.to_string(),
+            repo_root,
+            scope_prefix,
+            paths,
+            lines: Some(lines),
+            signature,
+        })
+    }
+
+    /// The file set selected by `--changed <rev>` or `--only-dirty`, if any.
+    pub fn from_flags(
+        scope_root: &Path,
+        rev: Option<&str>,
+        only_dirty: bool,
+    ) -> Result<Option<Self>> {
+        if only_dirty {
+            return Self::dirty(scope_root).map(Some);
+        }
+        rev.map(|rev| Self::from_scope(scope_root, rev)).transpose()
+    }
+
+    /// Files under `scope_root` whose git history passes `filter`, as
+    /// recorded by the index under `index_root` or, without one, read from git.
+    pub fn from_history(
+        scope_root: &Path,
+        index_root: &Path,
+        filter: &HistoryFilter,
+    ) -> Result<Self> {
+        let (repo_root, scope_prefix) = resolve_scope(scope_root, "--modified-since/--author")?;
+        let recorded = indexed_git_history(index_root);
+        let (histories, base_prefix) = if recorded.is_empty() {
+            (git_history::repo_history(&repo_root).files, None)
+        } else {
+            let index_root = index_root
+                .canonicalize()
+                .unwrap_or_else(|_| index_root.to_path_buf());
+            let prefix = index_root
+                .strip_prefix(&repo_root)
+                .ok()
+                .map(|p| normalize_rel_path_str(&p.to_string_lossy()))
+                .filter(|p| !p.is_empty());
+            (recorded, prefix)
+        };
+        let paths: HashSet<String> = histories
+            .into_iter()
+            .filter(|(_, history)| filter.matches(history))
+            .map(|(path, _)| match &base_prefix {
+                Some(prefix) => format!("{prefix}/{path}"),
+                None => normalize_rel_path_str(&path),
+            })
+            .filter(|path| match &scope_prefix {
+                Some(prefix) => path.starts_with(&format!("{prefix}/")),
+                None => true,
+            })
+            .collect();
+        let signature = signature_for(filter.label(), scope_prefix.as_deref(), &paths);
+
+        Ok(Self {
+            rev: filter.label().to_string(),
+            repo_root,
+            scope_prefix,
+            paths,
+            lines: None,
+            signature,
+        })
+    }
+
+    /// Files in both sets, e.g. `--changed` narrowed by `--author`.
+    pub fn intersect(mut self, other: &ChangedFiles) -> Self {
+        self.paths.retain(|path| other.paths.contains(path));
+        if let Some(lines) = self.lines.as_mut() {
+            lines.retain(|path, _| other.paths.contains(path));
+        }
+        self.rev = format!("{},{}", self.rev, other.rev);
+        self.signature = signature_for(&self.rev, self.scope_prefix.as_deref(), &self.paths);
+        self
+    }
 
-        let paths = collect_changed_paths(&repo_root, rev, scope_prefix.as_deref())?;
+    fn collect(scope_root: &Path, rev: Option<&str>) -> Result<Self> {
+        let (repo_root, scope_prefix) = resolve_scope(scope_root, "--changed")?;
+
+        let mut paths = match rev {
+            Some(rev) => collect_changed_paths(&repo_root, rev, scope_prefix.as_deref())?,
+            None => collect_staged_paths(&repo_root, scope_prefix.as_deref())?,
+        };
+        let rev = rev.unwrap_or(STAGED_REV);
+        // Diffs against other revisions can name paths outside the sparse cone.
+        if let Some(sparse) = SparseExclusions::load_for_checkout(&repo_root) {
+            paths.retain(|path| !sparse.contains_rel(path));
+        }
 
         let signature = signature_for(rev, scope_prefix.as_deref(), &paths);
 
@@ -40,6 +190,7 @@ impl ChangedFiles {
             repo_root,
             scope_prefix,
             paths,
+            lines: None,
             signature,
         })
     }
@@ -69,6 +220,90 @@ impl ChangedFiles {
         self.paths.contains(&repo_rel)
     }
 
+    /// Whether `line` (1-based) of a matching path is in the set: any line
+    /// for revision and status sets, only added lines for a patch.
+    pub fn matches_line(&self, rel_path: &str, line: usize) -> bool {
+        let Some(lines) = &self.lines else {
+            return self.matches_rel_path(rel_path);
+        };
+        let rel = normalize_rel_path_str(rel_path);
+        let repo_rel = match &self.scope_prefix {
+            Some(prefix) => format!("{prefix}/{rel}"),
+            None => rel,
+        };
+        lines
+            .get(&repo_rel)
+            .is_some_and(|numbers| numbers.contains(&line))
+    }
+
+    /// Changed files inside the scope that still exist on disk.
+    pub fn existing_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .map(|rel| self.repo_root.join(rel))
+            .filter(|path| path.is_file())
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Content of the changed files as staged in the git index, keyed by
+    /// working-tree path. Blobs that are not UTF-8 are left out.
+    pub fn staged_contents(&self) -> Result<HashMap<PathBuf, String>> {
+        let mut rel_paths: Vec<&String> = self.paths.iter().collect();
+        rel_paths.sort();
+        let request: String = rel_paths.iter().map(|rel| format!(":{rel}\n")).collect();
+
+        let mut child = Command::new("git")
+            .arg("-C")
+            .arg(&self.repo_root)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run git cat-file for staged contents")?;
+        let mut stdin = child.stdin.take().context("git cat-file stdin")?;
+        // Written from another thread so a large reply cannot block the request.
+        let writer = std::thread::spawn(move || stdin.write_all(request.as_bytes()));
+        let output = child
+            .wait_with_output()
+            .context("Failed to read staged contents from git cat-file")?;
+        let _ = writer.join();
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Failed to read staged contents from git: {}", stderr.trim());
+        }
+
+        let mut contents = HashMap::new();
+        let mut rest = output.stdout.as_slice();
+        for rel in rel_paths {
+            // Each reply is `<oid> <type> <size>\n<content>\n` or `<name> missing\n`.
+            let Some(header_end) = rest.iter().position(|byte| *byte == b'\n') else {
+                break;
+            };
+            let header = String::from_utf8_lossy(&rest[..header_end]).to_string();
+            rest = &rest[header_end + 1..];
+            if header.ends_with(" missing") {
+                continue;
+            }
+            let Some(size) = header
+                .rsplit(' ')
+                .next()
+                .and_then(|size| size.parse::<usize>().ok())
+                .filter(|size| *size <= rest.len())
+            else {
+                bail!("Unexpected git cat-file reply: {header}");
+            };
+            if let Ok(text) = std::str::from_utf8(&rest[..size]) {
+                contents.insert(self.repo_root.join(rel), text.to_string());
+            }
+            rest = rest.get(size + 1..).unwrap_or_default();
+        }
+        Ok(contents)
+    }
+
     #[allow(dead_code)]
     pub fn matches_path(&self, path: &Path) -> bool {
         let rel = path
@@ -81,7 +316,24 @@ impl ChangedFiles {
     }
 }
 
-fn git_repo_root(path: &Path) -> Result<PathBuf> {
+/// Repository root of `scope_root` and the scope's path inside it.
+fn resolve_scope(scope_root: &Path, flag: &str) -> Result<(PathBuf, Option<String>)> {
+    let scope_root = scope_root
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve path: {}", scope_root.display()))?;
+    let repo_root = git_repo_root(&scope_root, flag)?;
+    let scope_prefix = scope_root.strip_prefix(&repo_root).ok().and_then(|p| {
+        let v = normalize_rel_path_str(&p.to_string_lossy());
+        if v.is_empty() {
+            None
+        } else {
+            Some(v)
+        }
+    });
+    Ok((repo_root, scope_prefix))
+}
+
+pub(crate) fn git_repo_root(path: &Path, flag: &str) -> Result<PathBuf> {
     let output = Command::new("git")
         .arg("-C")
         .arg(path)
@@ -92,14 +344,15 @@ fn git_repo_root(path: &Path) -> Result<PathBuf> {
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         bail!(
-            "--changed requires a git repository (git rev-parse failed): {}",
+            "{} requires a git repository (git rev-parse failed): {}",
+            flag,
             stderr.trim()
         );
     }
 
     let top = String::from_utf8_lossy(&output.stdout).trim().to_string();
     if top.is_empty() {
-        bail!("--changed requires a git repository");
+        bail!("{flag} requires a git repository");
     }
 
     Ok(PathBuf::from(top))
@@ -138,6 +391,160 @@ fn collect_changed_paths(
     Ok(paths)
 }
 
+fn collect_staged_paths(repo_root: &Path, scope_prefix: Option<&str>) -> Result<HashSet<String>> {
+    let mut diff_args = vec!["diff", "--cached", "--name-only", "--diff-filter=d", "--"];
+    if let Some(prefix) = scope_prefix {
+        diff_args.push(prefix);
+    }
+    let diff_output = run_git_collect_paths(
+        repo_root,
+        &diff_args,
+        "Failed to run git diff for staged-files filter",
+        "Failed to resolve staged files from git diff",
+    )?;
+
+    let mut paths = HashSet::new();
+    extend_paths_from_stdout(&mut paths, &diff_output.stdout);
+    Ok(paths)
+}
+
+fn collect_dirty_paths(repo_root: &Path, scope_prefix: Option<&str>) -> Result<HashSet<String>> {
+    let mut status_args = vec![
+        "status",
+        "--porcelain=v1",
+        "-z",
+        "--no-renames",
+        "--untracked-files=all",
+        "--",
+    ];
+    if let Some(prefix) = scope_prefix {
+        status_args.push(prefix);
+    }
+    let status_output = run_git_collect_paths(
+        repo_root,
+        &status_args,
+        "Failed to run git status for dirty-files filter",
+        "Failed to resolve uncommitted files from git status",
+    )?;
+
+    // Entries are `XY path`, NUL-terminated, with paths relative to the repo root.
+    let mut paths = HashSet::new();
+    for entry in String::from_utf8_lossy(&status_output.stdout).split('\0') {
+        let normalized = normalize_rel_path_str(entry.get(3..).unwrap_or_default());
+        if !normalized.is_empty() {
+            paths.insert(normalized);
+        }
+    }
+    Ok(paths)
+}
+
+/// One hunk of a unified diff, on a file that exists after the patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PatchHunk {
+    /// New-side path
+    pub(crate) path: String,
+    pub(crate) old_start: usize,
+    pub(crate) old_lines: usize,
+    pub(crate) new_start: usize,
+    pub(crate) new_lines: usize,
+    /// New-side numbers of the lines the hunk adds
+    pub(crate) added: Vec<usize>,
+}
+
+/// Hunks of a unified diff. Hunks of deleted files are left out.
+pub(crate) fn parse_patch_hunks(patch: &str) -> Vec<PatchHunk> {
+    let mut hunks: Vec<PatchHunk> = Vec::new();
+    let mut path: Option<String> = None;
+    let mut new_line = 0;
+    // Lines left in the current hunk on each side; hunk bodies are read by
+    // count so removed lines that look like `--- ` headers stay in the hunk.
+    let (mut old_left, mut new_left) = (0usize, 0usize);
+    for line in patch.lines() {
+        if old_left > 0 || new_left > 0 {
+            match line.as_bytes().first() {
+                Some(b'+') => {
+                    if let Some(hunk) = hunks.last_mut().filter(|_| path.is_some()) {
+                        hunk.added.push(new_line);
+                    }
+                    new_line += 1;
+                    new_left = new_left.saturating_sub(1);
+                }
+                Some(b'-') => old_left = old_left.saturating_sub(1),
+                Some(b'\\') => {}
+                _ => {
+                    new_line += 1;
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
+            }
+            continue;
+        }
+        if let Some(target) = line.strip_prefix("+++ ") {
+            path = parse_patch_path(target);
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            let Some(((old_start, old_lines), (new_start, new_lines))) = parse_hunk_header(header)
+            else {
+                continue;
+            };
+            (old_left, new_left, new_line) = (old_lines, new_lines, new_start);
+            if let Some(path) = &path {
+                hunks.push(PatchHunk {
+                    path: path.clone(),
+                    old_start,
+                    old_lines,
+                    new_start,
+                    new_lines,
+                    added: Vec::new(),
+                });
+            }
+        }
+    }
+    hunks
+}
+
+/// Added line numbers per new-side path of a unified diff. Deleted files
+/// and hunks without additions are left out.
+fn parse_added_lines(patch: &str) -> HashMap<String, BTreeSet<usize>> {
+    let mut added: HashMap<String, BTreeSet<usize>> = HashMap::new();
+    for hunk in parse_patch_hunks(patch) {
+        if !hunk.added.is_empty() {
+            added.entry(hunk.path).or_default().extend(hunk.added);
+        }
+    }
+    added
+}
+
+/// New-side path of a `+++` line: `None` for a deleted file.
+fn parse_patch_path(target: &str) -> Option<String> {
+    // `git diff` separates a timestamp with a tab; quoted paths escape it.
+    let target = target.split('\t').next().unwrap_or(target).trim_end();
+    let target = target
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .unwrap_or(target);
+    if target == "/dev/null" {
+        return None;
+    }
+    let target = target.strip_prefix("b/").unwrap_or(target);
+    let normalized = normalize_rel_path_str(target);
+    (!normalized.is_empty()).then_some(normalized)
+}
+
+/// `-a,b +c,d @@` to ((old start, old count), (new start, new count));
+/// counts default to 1.
+fn parse_hunk_header(header: &str) -> Option<((usize, usize), (usize, usize))> {
+    let mut ranges = header.split_whitespace();
+    let old = ranges.next()?.strip_prefix('-')?;
+    let new = ranges.next()?.strip_prefix('+')?;
+    let count = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    Some((count(old)?, count(new)?))
+}
+
 fn run_git_collect_paths(
     repo_root: &Path,
     args: &[&str],
@@ -262,6 +669,163 @@ mod tests {
         assert!(!changed.matches_rel_path("tracked.rs"));
     }
 
+    #[test]
+    fn changed_files_skip_sparse_excluded_paths() {
+        let dir = TempDir::new().expect("tempdir");
+        run(dir.path(), &["init"]);
+        run(dir.path(), &["config", "user.email", "test@example.com"]);
+        run(dir.path(), &["config", "user.name", "test"]);
+
+        let src = dir.path().join("src");
+        let docs = dir.path().join("docs");
+        std::fs::create_dir_all(&src).expect("mkdir src");
+        std::fs::create_dir_all(&docs).expect("mkdir docs");
+        std::fs::write(src.join("lib.rs"), "pub fn kept() {}\n").expect("write lib");
+        std::fs::write(docs.join("guide.md"), "v1\n").expect("write docs");
+        run(dir.path(), &["add", "."]);
+        run(dir.path(), &["commit", "-m", "initial"]);
+
+        std::fs::write(src.join("lib.rs"), "pub fn kept() { let _ = 1; }\n").expect("rewrite");
+        std::fs::write(docs.join("guide.md"), "v2\n").expect("rewrite docs");
+        run(dir.path(), &["commit", "-am", "second"]);
+        run(dir.path(), &["sparse-checkout", "set", "src"]);
+
+        let changed = ChangedFiles::from_scope(dir.path(), "HEAD~1").expect("changed");
+        assert!(changed.matches_rel_path("src/lib.rs"));
+        assert!(!changed.matches_rel_path("docs/guide.md"));
+    }
+
+    #[test]
+    fn staged_files_exclude_unstaged_and_untracked_paths() {
+        let dir = TempDir::new().expect("tempdir");
+        run(dir.path(), &["init"]);
+        run(dir.path(), &["config", "user.email", "test@example.com"]);
+        run(dir.path(), &["config", "user.name", "test"]);
+
+        std::fs::write(dir.path().join("a.rs"), "pub fn a() {}\n").expect("write a");
+        std::fs::write(dir.path().join("b.rs"), "pub fn b() {}\n").expect("write b");
+        run(dir.path(), &["add", "."]);
+        run(dir.path(), &["commit", "-m", "initial"]);
+
+        std::fs::write(dir.path().join("a.rs"), "pub fn a() { let _ = 1; }\n").expect("edit a");
+        std::fs::write(dir.path().join("b.rs"), "pub fn b() { let _ = 2; }\n").expect("edit b");
+        std::fs::write(dir.path().join("c.rs"), "pub fn c() {}\n").expect("write c");
+        run(dir.path(), &["add", "a.rs"]);
+
+        let staged = ChangedFiles::staged(dir.path()).expect("staged");
+        assert_eq!(staged.rev(), STAGED_REV);
+        assert!(staged.matches_rel_path("a.rs"));
+        assert!(!staged.matches_rel_path("b.rs"));
+        assert!(!staged.matches_rel_path("c.rs"));
+        assert_eq!(staged.existing_files().len(), 1);
+
+        let contents = staged.staged_contents().expect("staged contents");
+        assert_eq!(contents.len(), 1);
+        assert_eq!(
+            contents
+                .get(&staged.repo_root.join("a.rs"))
+                .map(String::as_str),
+            Some("pub fn a() { let _ = 1; }\n")
+        );
+    }
+
+    #[test]
+    fn staged_contents_come_from_the_git_index() {
+        let dir = TempDir::new().expect("tempdir");
+        run(dir.path(), &["init"]);
+        run(dir.path(), &["config", "user.email", "test@example.com"]);
+        run(dir.path(), &["config", "user.name", "test"]);
+        std::fs::write(dir.path().join("a.rs"), "staged\n").expect("write a");
+        std::fs::write(dir.path().join("b.rs"), "gone\n").expect("write b");
+        run(dir.path(), &["add", "."]);
+        std::fs::write(dir.path().join("a.rs"), "unstaged\n").expect("edit a");
+        std::fs::remove_file(dir.path().join("b.rs")).expect("remove b");
+
+        let staged = ChangedFiles::staged(dir.path()).expect("staged");
+        let contents = staged.staged_contents().expect("staged contents");
+        let root = &staged.repo_root;
+        assert_eq!(
+            contents.get(&root.join("a.rs")).map(String::as_str),
+            Some("staged\n")
+        );
+        assert_eq!(
+            contents.get(&root.join("b.rs")).map(String::as_str),
+            Some("gone\n")
+        );
+    }
+
+    #[test]
+    fn dirty_files_cover_staged_unstaged_and_untracked_paths() {
+        let dir = TempDir::new().expect("tempdir");
+        run(dir.path(), &["init"]);
+        run(dir.path(), &["config", "user.email", "test@example.com"]);
+        run(dir.path(), &["config", "user.name", "test"]);
+
+        // Before the first commit everything is untracked.
+        std::fs::write(dir.path().join("a.rs"), "pub fn a() {}\n").expect("write a");
+        let dirty = ChangedFiles::dirty(dir.path()).expect("dirty before commit");
+        assert!(dirty.matches_rel_path("a.rs"));
+
+        std::fs::write(dir.path().join("b.rs"), "pub fn b() {}\n").expect("write b");
+        std::fs::write(dir.path().join("c.rs"), "pub fn c() {}\n").expect("write c");
+        run(dir.path(), &["add", "."]);
+        run(dir.path(), &["commit", "-m", "initial"]);
+
+        std::fs::write(dir.path().join("a.rs"), "pub fn a() { let _ = 1; }\n").expect("edit a");
+        std::fs::write(dir.path().join("b.rs"), "pub fn b() { let _ = 2; }\n").expect("edit b");
+        std::fs::write(dir.path().join("new file.rs"), "pub fn d() {}\n").expect("write d");
+        run(dir.path(), &["add", "b.rs"]);
+
+        let dirty = ChangedFiles::dirty(dir.path()).expect("dirty");
+        assert_eq!(dirty.rev(), DIRTY_REV);
+        assert!(dirty.matches_rel_path("a.rs"));
+        assert!(dirty.matches_rel_path("b.rs"));
+        assert!(dirty.matches_rel_path("new file.rs"));
+        assert!(!dirty.matches_rel_path("c.rs"));
+    }
+
+    #[test]
+    fn patch_added_lines_follow_hunk_positions() {
+        let patch = "diff --git a/src/lib.rs b/src/lib.rs\n\
+index 1111111..2222222 100644\n\
+--- a/src/lib.rs\n\
++++ b/src/lib.rs\n\
+@@ -1,4 +1,5 @@\n\
+ fn a() {}\n\
+-fn b() {}\n\
+--- not a header\n\
++fn b2() {}\n\
++fn b3() {}\n\
+ fn c() {}\n\
++fn d() {}\n\
+@@ -10 +11 @@ fn tail() {}\n\
+-old\n\
++new\n\
+diff --git a/gone.rs b/gone.rs\n\
+deleted file mode 100644\n\
+--- a/gone.rs\n\
++++ /dev/null\n\
+@@ -1 +0,0 @@\n\
+-fn gone() {}\n\
+diff --git a/new.rs b/new.rs\n\
+new file mode 100644\n\
+--- /dev/null\n\
++++ b/new.rs\n\
+@@ -0,0 +1,2 @@\n\
++fn fresh() {}\n\
++\\ No newline at end of file\n";
+        let added = parse_added_lines(patch);
+        assert_eq!(added.len(), 2);
+        assert_eq!(
+            added["src/lib.rs"].iter().copied().collect::<Vec<_>>(),
+            vec![2, 3, 5, 11]
+        );
+        assert_eq!(
+            added["new.rs"].iter().copied().collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
     #[test]
     fn normalize_rel_path_handles_windows_and_dots() {
         assert_eq!(normalize_rel_path_str(".\\src\\lib.rs"), "src/lib.rs");
@@ -0,0 +1,654 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep check`: evaluate policy rules from a YAML file over the codebase.
+//!
+//! Each rule pairs one matcher (a literal, a regex, or an AST `call` /
+//! `identifier` pattern) with a severity and message. Literal and AST rules
+//! narrow their candidate files through the index; regex rules read every
+//! file the scanner would index. Any `error` violation fails the command.
+//!
+//! With `--changed` the changed files are read directly unless a watch daemon
+//! is keeping the index current, in which case the index is trusted to narrow
+//! candidates as usual. `--staged` always checks the content staged in the
+//! git index, so unstaged edits do not affect a pre-commit check.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::indexer::daemon;
+use crate::indexer::scanner::{detect_language, is_indexable_extension, FileScanner};
+use crate::query::ast_usage::AstUsageExtractor;
+use crate::query::changed_files::ChangedFiles;
+use crate::query::index_filter::find_files_with_content;
+use cgrep::filters::{matches_file_type, matches_glob_compiled, CompiledGlob};
+use cgrep::output::{
+    print_github_annotations, print_gitlab_report, print_json, print_json2, print_quickfix,
+    Annotation, AnnotationLevel, QuickfixEntry,
+};
+use cgrep::paths;
+use cgrep::utils::get_root_with_index;
+
+/// Rule severity; only `error` violations make the command fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    fn sarif_level(self) -> &'static str {
+        match self {
+            Severity::Info => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    pub(crate) fn annotation_level(self) -> AnnotationLevel {
+        match self {
+            Severity::Info => AnnotationLevel::Notice,
+            Severity::Warning => AnnotationLevel::Warning,
+            Severity::Error => AnnotationLevel::Error,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    rules: Vec<RuleSpec>,
+}
+
+/// A rule as written in the rules file; exactly one matcher must be set.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    id: String,
+    message: String,
+    #[serde(default)]
+    severity: Severity,
+    literal: Option<String>,
+    regex: Option<String>,
+    /// Call sites of this function (AST).
+    call: Option<String>,
+    /// Identifier uses outside comments and strings (AST).
+    identifier: Option<String>,
+    /// File type filter, as accepted by `search --type`.
+    #[serde(rename = "type")]
+    file_type: Option<String>,
+    /// Glob the file path must match.
+    glob: Option<String>,
+}
+
+enum Matcher {
+    Literal(String),
+    Regex(Regex),
+    Call(String),
+    Identifier(String),
+}
+
+struct Rule {
+    id: String,
+    message: String,
+    severity: Severity,
+    matcher: Matcher,
+    file_type: Option<String>,
+    glob: Option<CompiledGlob>,
+}
+
+impl Rule {
+    fn compile(spec: RuleSpec) -> Result<Self> {
+        let mut matchers = Vec::new();
+        if let Some(literal) = spec.literal {
+            matchers.push(Matcher::Literal(literal));
+        }
+        if let Some(pattern) = spec.regex {
+            let re = Regex::new(&pattern)
+                .with_context(|| format!("Rule `{}` has an invalid regex", spec.id))?;
+            matchers.push(Matcher::Regex(re));
+        }
+        if let Some(function) = spec.call {
+            matchers.push(Matcher::Call(function));
+        }
+        if let Some(name) = spec.identifier {
+            matchers.push(Matcher::Identifier(name));
+        }
+        if matchers.len() != 1 {
+            bail!(
+                "Rule `{}` must set exactly one of `literal`, `regex`, `call`, `identifier`",
+                spec.id
+            );
+        }
+        let glob = match spec.glob.as_deref() {
+            Some(pattern) => Some(
+                CompiledGlob::new(pattern)
+                    .with_context(|| format!("Rule `{}` has an invalid glob", spec.id))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            id: spec.id,
+            message: spec.message,
+            severity: spec.severity,
+            matcher: matchers.remove(0),
+            file_type: spec.file_type,
+            glob,
+        })
+    }
+
+    /// Term used to narrow candidate files through the index.
+    fn index_term(&self) -> Option<&str> {
+        match &self.matcher {
+            Matcher::Literal(term) | Matcher::Call(term) | Matcher::Identifier(term) => {
+                Some(term.as_str())
+            }
+            Matcher::Regex(_) => None,
+        }
+    }
+
+    fn applies_to(&self, rel_path: &str) -> bool {
+        matches_file_type(rel_path, self.file_type.as_deref())
+            && matches_glob_compiled(rel_path, self.glob.as_ref())
+    }
+
+    /// 1-based (line, column) of each match, at most one per line.
+    fn find(
+        &self,
+        content: &str,
+        language: Option<&str>,
+        ast: &mut AstUsageExtractor,
+    ) -> Vec<(usize, usize)> {
+        let by_line = |find: &dyn Fn(&str) -> Option<usize>| -> Vec<(usize, usize)> {
+            content
+                .lines()
+                .enumerate()
+                .filter_map(|(idx, line)| find(line).map(|col| (idx + 1, col + 1)))
+                .collect()
+        };
+        let usages = match &self.matcher {
+            Matcher::Literal(term) => return by_line(&|line| line.find(term.as_str())),
+            Matcher::Regex(re) => return by_line(&|line| re.find(line).map(|m| m.start())),
+            Matcher::Call(function) => {
+                language.and_then(|lang| ast.callers(content, lang, function, usize::MAX))
+            }
+            Matcher::Identifier(name) => {
+                language.and_then(|lang| ast.references(content, lang, name, usize::MAX))
+            }
+        };
+        let mut found: Vec<(usize, usize)> = usages
+            .unwrap_or_default()
+            .into_iter()
+            .map(|usage| (usage.line, usage.column))
+            .collect();
+        found.sort_unstable();
+        found.dedup_by_key(|(line, _)| *line);
+        found
+    }
+}
+
+fn load_rules(path: &Path) -> Result<Vec<Rule>> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rules file {}", path.display()))?;
+    let file: RuleFile = serde_yaml::from_str(&raw)
+        .with_context(|| format!("Invalid rules file {}", path.display()))?;
+    file.rules.into_iter().map(Rule::compile).collect()
+}
+
+/// A single rule match.
+#[derive(Debug, Serialize)]
+pub(crate) struct Violation {
+    pub(crate) rule: String,
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+    pub(crate) path: String,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) code: String,
+}
+
+/// A rule as reported in SARIF and text summaries.
+pub(crate) struct RuleInfo<'a> {
+    pub(crate) id: &'a str,
+    pub(crate) message: &'a str,
+    pub(crate) severity: Severity,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct SeverityCounts {
+    pub(crate) error: usize,
+    pub(crate) warning: usize,
+    pub(crate) info: usize,
+}
+
+impl SeverityCounts {
+    pub(crate) fn of(violations: &[Violation]) -> Self {
+        let mut counts = Self::default();
+        for violation in violations {
+            match violation.severity {
+                Severity::Error => counts.error += 1,
+                Severity::Warning => counts.warning += 1,
+                Severity::Info => counts.info += 1,
+            }
+        }
+        counts
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CheckJson2Meta<'a> {
+    schema_version: &'static str,
+    command: &'static str,
+    rules: usize,
+    files_checked: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed_rev: Option<&'a str>,
+    counts: SeverityCounts,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckJson2Payload<'a> {
+    meta: CheckJson2Meta<'a>,
+    results: &'a [Violation],
+}
+
+/// Drop staged files the warm index says cannot match `term`. Files whose
+/// staged content differs from the working tree are always kept.
+fn narrow_staged(
+    files: &[PathBuf],
+    indexed_staged: &HashSet<PathBuf>,
+    index_root: &Path,
+    term: &str,
+    root: &Path,
+) -> Result<Option<Vec<PathBuf>>> {
+    let Some(hits) = find_files_with_content(index_root, term, Some(root))? else {
+        return Ok(Some(files.to_vec()));
+    };
+    let hits: HashSet<String> = hits
+        .iter()
+        .filter_map(|file| paths::relative_display(file, root))
+        .collect();
+    Ok(Some(
+        files
+            .iter()
+            .filter(|file| {
+                !indexed_staged.contains(*file)
+                    || paths::relative_display(file, root).is_some_and(|rel| hits.contains(&rel))
+            })
+            .cloned()
+            .collect(),
+    ))
+}
+
+/// Run the check command
+pub fn run(
+    rules_path: &str,
+    path: Option<&str>,
+    changed: Option<&str>,
+    staged: bool,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let cwd = paths::canonicalize(&std::env::current_dir()?)?;
+    let root = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => cwd.clone(),
+    };
+    let rules = load_rules(Path::new(rules_path))?;
+    let index_root = get_root_with_index(&root);
+    let changed_filter = if staged {
+        Some(ChangedFiles::staged(&root)?)
+    } else {
+        changed
+            .map(|rev| ChangedFiles::from_scope(&root, rev))
+            .transpose()?
+    };
+    // Staged checks read what will be committed, not the working tree.
+    let staged_contents: Option<HashMap<PathBuf, String>> = changed_filter
+        .as_ref()
+        .filter(|_| staged)
+        .map(ChangedFiles::staged_contents)
+        .transpose()?;
+    // Without a warm daemon the index may predate the changes being checked.
+    let warm_index = daemon::is_running(&index_root);
+    // The warm index holds the working tree, so it only speaks for staged
+    // files whose staged content is what is on disk.
+    let indexed_staged: HashSet<PathBuf> = match &staged_contents {
+        Some(staged) if warm_index => staged
+            .iter()
+            .filter(|(file, content)| fs::read_to_string(file).is_ok_and(|disk| disk == **content))
+            .map(|(file, _)| file.clone())
+            .collect(),
+        _ => HashSet::new(),
+    };
+    let changed_files: Option<Vec<PathBuf>> = changed_filter
+        .as_ref()
+        .filter(|_| staged || !warm_index)
+        .map(|filter| {
+            let files = if staged {
+                let mut files: Vec<PathBuf> = staged_contents
+                    .as_ref()
+                    .map(|staged| staged.keys().cloned().collect())
+                    .unwrap_or_default();
+                files.sort();
+                files
+            } else {
+                filter.existing_files()
+            };
+            files
+                .into_iter()
+                .filter(|file| {
+                    file.extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(is_indexable_extension)
+                })
+                .collect()
+        });
+
+    let mut all_files: Option<Vec<PathBuf>> = None;
+    let mut contents: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut ast = AstUsageExtractor::new();
+    let mut violations: Vec<Violation> = Vec::new();
+
+    for rule in &rules {
+        let indexed = match (&changed_files, rule.index_term()) {
+            (Some(files), Some(term)) if !indexed_staged.is_empty() => {
+                narrow_staged(files, &indexed_staged, &index_root, term, &root)?
+            }
+            (Some(files), _) => Some(files.clone()),
+            (None, Some(term)) => find_files_with_content(&index_root, term, Some(&root))?,
+            (None, None) => None,
+        };
+        let mut candidates = match indexed {
+            Some(paths) => paths,
+            None => match all_files.as_ref() {
+                Some(files) => files.clone(),
+                None => all_files
+                    .insert(FileScanner::new(&root).list_files()?)
+                    .clone(),
+            },
+        };
+        candidates.sort();
+
+        for file in candidates {
+            let scope_path = paths::relative_display(&file, &root).unwrap_or_default();
+            if !rule.applies_to(&scope_path) {
+                continue;
+            }
+            if let Some(filter) = changed_filter.as_ref() {
+                if !filter.matches_rel_path(&scope_path) {
+                    continue;
+                }
+            }
+            let Some(content) = contents
+                .entry(file.clone())
+                .or_insert_with(|| match &staged_contents {
+                    Some(staged) => staged.get(&file).cloned(),
+                    None => fs::read_to_string(&file).ok(),
+                })
+                .as_deref()
+            else {
+                continue;
+            };
+            let language = file
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(detect_language);
+            let display_path =
+                paths::relative_display(&file, &cwd).unwrap_or_else(|| paths::display(&file));
+            for (line, column) in rule.find(content, language.as_deref(), &mut ast) {
+                violations.push(Violation {
+                    rule: rule.id.clone(),
+                    severity: rule.severity,
+                    message: rule.message.clone(),
+                    path: display_path.clone(),
+                    line,
+                    column,
+                    code: content
+                        .lines()
+                        .nth(line - 1)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string(),
+                });
+            }
+        }
+    }
+
+    violations.sort_by(|a, b| {
+        (&a.path, a.line, a.column, &a.rule).cmp(&(&b.path, b.line, b.column, &b.rule))
+    });
+    let counts = SeverityCounts::of(&violations);
+    let errors = counts.error;
+
+    match format {
+        OutputFormat::Json => print_json(&violations, compact)?,
+        OutputFormat::Json2 => {
+            let payload = CheckJson2Payload {
+                meta: CheckJson2Meta {
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
+                    command: "check",
+                    rules: rules.len(),
+                    files_checked: contents.len(),
+                    changed_rev: changed_filter.as_ref().map(|f| f.rev()),
+                    counts,
+                },
+                results: &violations,
+            };
+            print_json2("check", &payload, compact)?;
+        }
+        _ => {
+            let infos: Vec<RuleInfo<'_>> = rules
+                .iter()
+                .map(|rule| RuleInfo {
+                    id: &rule.id,
+                    message: &rule.message,
+                    severity: rule.severity,
+                })
+                .collect();
+            print_violations(&violations, &infos, format, compact)?;
+        }
+    }
+
+    if errors > 0 {
+        bail!("{errors} rule violation(s) with severity error");
+    }
+    Ok(())
+}
+
+/// Print violations as annotations, SARIF, quickfix lines, or text; the JSON
+/// formats are left to the command.
+pub(crate) fn print_violations(
+    violations: &[Violation],
+    rules: &[RuleInfo<'_>],
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    match format {
+        OutputFormat::Github | OutputFormat::Gitlab => {
+            let annotations: Vec<Annotation<'_>> = violations
+                .iter()
+                .map(|violation| Annotation {
+                    path: &violation.path,
+                    line: Some(violation.line),
+                    level: violation.severity.annotation_level(),
+                    title: &violation.rule,
+                    message: &violation.message,
+                    id: None,
+                })
+                .collect();
+            if format == OutputFormat::Github {
+                print_github_annotations(&annotations);
+            } else {
+                print_gitlab_report(&annotations, compact)?;
+            }
+        }
+        OutputFormat::Sarif => print_json(&sarif_log(rules, violations), compact)?,
+        OutputFormat::Quickfix => {
+            let texts: Vec<String> = violations
+                .iter()
+                .map(|violation| {
+                    format!(
+                        "{}: {} [{}]",
+                        violation.severity.as_str(),
+                        violation.message,
+                        violation.rule
+                    )
+                })
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = violations
+                .iter()
+                .zip(&texts)
+                .map(|(violation, text)| QuickfixEntry {
+                    path: &violation.path,
+                    line: violation.line,
+                    column: violation.column,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Json | OutputFormat::Json2 => {
+            print_text(violations, rules.len(), &SeverityCounts::of(violations))
+        }
+    }
+    Ok(())
+}
+
+fn print_text(violations: &[Violation], rule_count: usize, counts: &SeverityCounts) {
+    if violations.is_empty() {
+        println!(
+            "{} No violations ({} rules checked)",
+            "✓".green(),
+            rule_count
+        );
+        return;
+    }
+    for violation in violations {
+        let severity = match violation.severity {
+            Severity::Error => violation.severity.as_str().red().bold(),
+            Severity::Warning => violation.severity.as_str().yellow(),
+            Severity::Info => violation.severity.as_str().blue(),
+        };
+        println!(
+            "{}:{}:{} {} [{}] {}",
+            violation.path.cyan(),
+            violation.line.to_string().yellow(),
+            violation.column.to_string().dimmed(),
+            severity,
+            violation.rule,
+            violation.message
+        );
+        if !violation.code.is_empty() {
+            println!("    {}", violation.code.dimmed());
+        }
+    }
+    println!(
+        "\n{} {} violations ({} errors, {} warnings, {} info)",
+        "✗".red(),
+        violations.len(),
+        counts.error,
+        counts.warning,
+        counts.info
+    );
+}
+
+fn sarif_log(rules: &[RuleInfo<'_>], violations: &[Violation]) -> serde_json::Value {
+    let rule_descriptors: Vec<serde_json::Value> = rules
+        .iter()
+        .map(|rule| {
+            serde_json::json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.message },
+                "defaultConfiguration": { "level": rule.severity.sarif_level() },
+            })
+        })
+        .collect();
+    let results: Vec<serde_json::Value> = violations
+        .iter()
+        .map(|violation| {
+            serde_json::json!({
+                "ruleId": violation.rule,
+                "level": violation.severity.sarif_level(),
+                "message": { "text": violation.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": violation.path.replace('\\', "/") },
+                        "region": {
+                            "startLine": violation.line,
+                            "startColumn": violation.column,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cgrep",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rule_descriptors,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(yaml: &str) -> Result<Rule> {
+        Rule::compile(serde_yaml::from_str(yaml)?)
+    }
+
+    #[test]
+    fn rules_require_exactly_one_matcher() {
+        assert!(rule("id: a\nmessage: m\nliteral: x\nregex: y\n").is_err());
+        assert!(rule("id: a\nmessage: m\n").is_err());
+        assert!(rule("id: a\nmessage: m\nregex: '('\n").is_err());
+        let parsed = rule("id: a\nmessage: m\nliteral: x\nseverity: error\n").expect("rule");
+        assert_eq!(parsed.severity, Severity::Error);
+    }
+
+    #[test]
+    fn ast_rules_skip_comments_and_strings() {
+        let source = "fn main() {\n    // eval(x) is banned\n    let s = \"eval(\";\n    eval(1);\n}\nfn eval(_: i32) {}\n";
+        let mut ast = AstUsageExtractor::new();
+        let call = rule("id: a\nmessage: m\ncall: eval\n").expect("rule");
+        assert_eq!(call.find(source, Some("rust"), &mut ast), vec![(4, 5)]);
+
+        let literal = rule("id: b\nmessage: m\nliteral: eval(\n").expect("rule");
+        let lines: Vec<usize> = literal
+            .find(source, Some("rust"), &mut ast)
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect();
+        assert_eq!(lines, vec![2, 3, 4, 6]);
+    }
+}
@@ -0,0 +1,449 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep search --cluster`: group results into labeled clusters.
+//!
+//! Results are grouped by the stored embedding of the symbol (or chunk)
+//! covering each hit. When any result has no embedding, such as an index
+//! built with `--embeddings off`, every result is compared by its
+//! IDF-weighted path and snippet terms instead. Grouping is a single
+//! rank-ordered leader pass, so the same results always produce the same
+//! clusters.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::query::search::SearchResult;
+use cgrep::embedding::{EmbeddingStorage, SymbolEmbedding};
+use cgrep::paths;
+
+/// Clusters reported before the remainder is folded into `other`.
+const MAX_CLUSTERS: usize = 8;
+/// Cosine similarity needed to join a cluster, per vector source.
+const EMBEDDING_JOIN_THRESHOLD: f32 = 0.75;
+const LEXICAL_JOIN_THRESHOLD: f32 = 0.3;
+/// Terms used in a cluster label.
+const LABEL_TERMS: usize = 2;
+/// Label weight of path terms over snippet terms: directories and file
+/// names describe what a group of results is (`tests`, `http`).
+const PATH_LABEL_WEIGHT: f32 = 2.0;
+const MIN_TERM_CHARS: usize = 3;
+/// Keywords and filler too common in code to describe a cluster.
+const STOPWORDS: &[&str] = &[
+    "and", "async", "await", "bool", "break", "case", "class", "const", "continue", "def", "else",
+    "enum", "export", "false", "fmt", "for", "from", "func", "function", "impl", "import", "int",
+    "let", "mod", "mut", "new", "none", "not", "null", "pub", "return", "self", "src", "static",
+    "str", "string", "struct", "the", "this", "true", "type", "use", "var", "void", "while",
+    "with",
+];
+
+/// Where cluster vectors came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ClusterMethod {
+    Embedding,
+    Lexical,
+}
+
+impl ClusterMethod {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ClusterMethod::Embedding => "embedding",
+            ClusterMethod::Lexical => "lexical",
+        }
+    }
+}
+
+/// Result closest to a cluster's center.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ClusterRepresentative {
+    pub id: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ResultCluster {
+    pub id: String,
+    pub label: String,
+    pub size: usize,
+    /// Member result ids in rank order.
+    pub result_ids: Vec<String>,
+    pub representative: ClusterRepresentative,
+}
+
+/// Cluster `results`, using `embeddings` when every result has one.
+pub(crate) fn cluster_results(
+    results: &[SearchResult],
+    embeddings: Option<Vec<Vec<f32>>>,
+) -> (ClusterMethod, Vec<ResultCluster>) {
+    let path_terms: Vec<Vec<String>> = results.iter().map(path_terms).collect();
+    let terms: Vec<Vec<String>> = results
+        .iter()
+        .zip(&path_terms)
+        .map(|(result, path)| {
+            let mut terms = path.clone();
+            split_terms(&result.snippet, &mut terms);
+            terms
+        })
+        .collect();
+    let idf = inverse_document_frequency(&terms);
+    let (method, vectors, threshold) = match embeddings {
+        Some(vectors) if vectors.len() == results.len() => {
+            (ClusterMethod::Embedding, vectors, EMBEDDING_JOIN_THRESHOLD)
+        }
+        _ => (
+            ClusterMethod::Lexical,
+            lexical_vectors(&terms, &idf),
+            LEXICAL_JOIN_THRESHOLD,
+        ),
+    };
+
+    let mut groups = leader_groups(&vectors, threshold);
+    let folded = groups.len() > MAX_CLUSTERS;
+    if folded {
+        let mut other: Vec<usize> = groups.drain(MAX_CLUSTERS - 1..).flatten().collect();
+        other.sort_unstable();
+        groups.push(other);
+    }
+
+    let clusters = groups
+        .iter()
+        .enumerate()
+        .map(|(idx, members)| {
+            let label = if folded && idx == MAX_CLUSTERS - 1 {
+                "other".to_string()
+            } else {
+                cluster_label(members, &terms, &path_terms, &idf)
+            };
+            let rep = representative(members, &vectors);
+            let result = &results[rep];
+            ResultCluster {
+                id: format!("c{}", idx + 1),
+                label,
+                size: members.len(),
+                result_ids: members.iter().map(|&i| result_id(&results[i])).collect(),
+                representative: ClusterRepresentative {
+                    id: result_id(result),
+                    path: result.path.clone(),
+                    line: result.line,
+                    snippet: result.snippet.clone(),
+                },
+            }
+        })
+        .collect();
+    (method, clusters)
+}
+
+/// Stored embedding of the innermost symbol or chunk covering each result.
+///
+/// `None` unless every result maps to an embedding of the same dimension.
+pub(crate) fn result_embeddings(
+    index_root: &Path,
+    workspace_root: &Path,
+    results: &[SearchResult],
+) -> Option<Vec<Vec<f32>>> {
+    let db_path = index_root.join(".cgrep").join("embeddings.sqlite");
+    if results.is_empty() || !db_path.exists() {
+        return None;
+    }
+    let storage = EmbeddingStorage::open(&db_path).ok()?;
+    let mut by_path: HashMap<String, Vec<SymbolEmbedding>> = HashMap::new();
+    let mut vectors = Vec::with_capacity(results.len());
+    for result in results {
+        let full_path = workspace_root.join(paths::decode_path(&result.path));
+        let rel = full_path.strip_prefix(index_root).ok()?;
+        let stored_path = rel.to_string_lossy().replace('\\', "/");
+        let symbols = by_path
+            .entry(stored_path)
+            .or_insert_with_key(|path| storage.get_symbols_for_path(path).unwrap_or_default());
+        let line = result.line.unwrap_or(1) as u32;
+        let symbol = symbols
+            .iter()
+            .filter(|symbol| symbol.start_line <= line && line <= symbol.end_line)
+            .min_by_key(|symbol| symbol.end_line - symbol.start_line)?;
+        vectors.push(symbol.embedding.clone());
+    }
+    let dimension = vectors[0].len();
+    vectors
+        .iter()
+        .all(|vector| vector.len() == dimension && dimension > 0)
+        .then_some(vectors)
+}
+
+fn result_id(result: &SearchResult) -> String {
+    result.result_id.clone().unwrap_or_default()
+}
+
+/// Directory and file-stem terms of a result path.
+fn path_terms(result: &SearchResult) -> Vec<String> {
+    let path = Path::new(&result.path);
+    let mut terms = Vec::new();
+    if let Some(parent) = path.parent() {
+        split_terms(&parent.to_string_lossy(), &mut terms);
+    }
+    if let Some(stem) = path.file_stem() {
+        split_terms(&stem.to_string_lossy(), &mut terms);
+    }
+    terms
+}
+
+/// Lowercased words of `text`, split on `snake_case` and `camelCase`
+/// boundaries.
+fn split_terms(text: &str, terms: &mut Vec<String>) {
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let mut current = String::new();
+        let mut prev: Option<char> = None;
+        for c in word.chars() {
+            if c.is_uppercase() && prev.is_some_and(char::is_lowercase) {
+                push_term(terms, &current);
+                current.clear();
+            }
+            current.extend(c.to_lowercase());
+            prev = Some(c);
+        }
+        push_term(terms, &current);
+    }
+}
+
+fn push_term(terms: &mut Vec<String>, term: &str) {
+    if term.chars().count() >= MIN_TERM_CHARS
+        && !term.chars().all(|c| c.is_ascii_digit())
+        && !STOPWORDS.contains(&term)
+    {
+        terms.push(term.to_string());
+    }
+}
+
+/// `ln(n / df)`: zero for terms every result shares, such as the query.
+fn inverse_document_frequency(terms: &[Vec<String>]) -> BTreeMap<String, f32> {
+    let mut df: BTreeMap<String, usize> = BTreeMap::new();
+    for doc in terms {
+        let mut seen: Vec<&String> = doc.iter().collect();
+        seen.sort_unstable();
+        seen.dedup();
+        for term in seen {
+            *df.entry(term.clone()).or_insert(0) += 1;
+        }
+    }
+    let n = terms.len() as f32;
+    df.into_iter()
+        .map(|(term, count)| (term, (n / count as f32).ln()))
+        .collect()
+}
+
+fn lexical_vectors(terms: &[Vec<String>], idf: &BTreeMap<String, f32>) -> Vec<Vec<f32>> {
+    let slots: HashMap<&str, usize> = idf
+        .keys()
+        .enumerate()
+        .map(|(slot, term)| (term.as_str(), slot))
+        .collect();
+    terms
+        .iter()
+        .map(|doc| {
+            let mut vector = vec![0.0; slots.len()];
+            for term in doc {
+                vector[slots[term.as_str()]] += idf[term];
+            }
+            vector
+        })
+        .collect()
+}
+
+/// Assign each vector, in rank order, to the most similar cluster centroid
+/// at or above `threshold`, or start a new cluster.
+fn leader_groups(vectors: &[Vec<f32>], threshold: f32) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut centroids: Vec<Vec<f32>> = Vec::new();
+    for (idx, vector) in vectors.iter().enumerate() {
+        let best = centroids
+            .iter()
+            .enumerate()
+            .map(|(group, centroid)| (group, cosine(vector, centroid)))
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)));
+        match best {
+            Some((group, _)) => {
+                groups[group].push(idx);
+                for (sum, value) in centroids[group].iter_mut().zip(vector) {
+                    *sum += value;
+                }
+            }
+            None => {
+                groups.push(vec![idx]);
+                centroids.push(vector.clone());
+            }
+        }
+    }
+    groups
+}
+
+/// Top terms shared within the cluster and rare elsewhere, skipping
+/// variants of a term already chosen (`test`, `tests`).
+fn cluster_label(
+    members: &[usize],
+    terms: &[Vec<String>],
+    path_terms: &[Vec<String>],
+    idf: &BTreeMap<String, f32>,
+) -> String {
+    let mut coverage: BTreeMap<&str, usize> = BTreeMap::new();
+    for &member in members {
+        let mut seen: Vec<&str> = terms[member].iter().map(String::as_str).collect();
+        seen.sort_unstable();
+        seen.dedup();
+        for term in seen {
+            *coverage.entry(term).or_insert(0) += 1;
+        }
+    }
+    let mut scored: Vec<(&str, f32, usize)> = coverage
+        .into_iter()
+        .map(|(term, count)| {
+            let share = count as f32 / members.len() as f32;
+            let in_path = members
+                .iter()
+                .any(|&member| path_terms[member].iter().any(|path| path == term));
+            let weight = if in_path { PATH_LABEL_WEIGHT } else { 1.0 };
+            // Squared so a term on every member beats rarer per-file names.
+            (term, share * share * idf[term] * weight, count)
+        })
+        .collect();
+    // Distinctive terms first; with none (a single cluster), the most common.
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(b.0)));
+    let mut label: Vec<&str> = Vec::new();
+    for (term, _, _) in scored {
+        if label.len() == LABEL_TERMS {
+            break;
+        }
+        if !label
+            .iter()
+            .any(|chosen| chosen.starts_with(term) || term.starts_with(chosen))
+        {
+            label.push(term);
+        }
+    }
+    if label.is_empty() {
+        "misc".to_string()
+    } else {
+        label.join(" ")
+    }
+}
+
+/// Member most similar to the cluster centroid; earlier rank wins ties.
+fn representative(members: &[usize], vectors: &[Vec<f32>]) -> usize {
+    let dimension = vectors[members[0]].len();
+    let mut centroid = vec![0.0; dimension];
+    for &member in members {
+        for (sum, value) in centroid.iter_mut().zip(&vectors[member]) {
+            *sum += value;
+        }
+    }
+    members
+        .iter()
+        .copied()
+        .max_by(|&a, &b| {
+            cosine(&vectors[a], &centroid)
+                .total_cmp(&cosine(&vectors[b], &centroid))
+                .then(b.cmp(&a))
+        })
+        .unwrap_or(members[0])
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(path: &str, snippet: &str, id: &str) -> SearchResult {
+        SearchResult {
+            path: path.to_string(),
+            score: 1.0,
+            snippet: snippet.to_string(),
+            line: Some(1),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            text_score: None,
+            vector_score: None,
+            hybrid_score: None,
+            result_id: Some(id.to_string()),
+            chunk_start: None,
+            chunk_end: None,
+            explain: None,
+            linguist: None,
+            stale: false,
+            column: None,
+            end_column: None,
+            match_bytes: None,
+            chunk_bytes: None,
+            symbol_header: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn lexical_clusters_group_by_shared_terms_and_label_them() {
+        let results = vec![
+            result(
+                "src/http/routes.rs",
+                "fn handle_request(req: Request) -> retry",
+                "r1",
+            ),
+            result(
+                "tests/parser_test.rs",
+                "fn parses_retry_config() { assert!(..) }",
+                "r2",
+            ),
+            result(
+                "src/http/server.rs",
+                "async fn serve_request(req) -> retry",
+                "r3",
+            ),
+            result(
+                "tests/loader_test.rs",
+                "fn loads_retry_config() { assert!(..) }",
+                "r4",
+            ),
+        ];
+
+        let (method, clusters) = cluster_results(&results, None);
+
+        assert_eq!(method, ClusterMethod::Lexical);
+        let members: Vec<&[String]> = clusters.iter().map(|c| c.result_ids.as_slice()).collect();
+        assert_eq!(members, [["r1", "r3"], ["r2", "r4"]]);
+        assert!(clusters[0].label.contains("http"), "{}", clusters[0].label);
+        assert!(clusters[1].label.contains("test"), "{}", clusters[1].label);
+        assert_eq!(clusters[1].id, "c2");
+    }
+
+    #[test]
+    fn embeddings_take_priority_and_overflow_folds_into_other() {
+        let results: Vec<SearchResult> = (0..10)
+            .map(|i| result(&format!("f{i}.rs"), "same words", &format!("r{i}")))
+            .collect();
+        let embeddings: Vec<Vec<f32>> = (0..10)
+            .map(|i| {
+                let mut axis = vec![0.0; 10];
+                axis[i] = 1.0;
+                axis
+            })
+            .collect();
+
+        let (method, clusters) = cluster_results(&results, Some(embeddings));
+
+        assert_eq!(method, ClusterMethod::Embedding);
+        assert_eq!(clusters.len(), MAX_CLUSTERS);
+        let other = clusters.last().expect("other");
+        assert_eq!(other.label, "other");
+        assert_eq!(other.size, 3);
+        assert_eq!(other.representative.id, "r7");
+    }
+}
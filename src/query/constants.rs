@@ -0,0 +1,294 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep const`: find a numeric value where it appears as a literal.
+//!
+//! Only numeric literal nodes in the syntax tree are compared, so digits in
+//! comments, strings, and identifiers (`retry_86400`, `"86400s"`) are never
+//! reported. Literals are compared by value: `86400`, `86_400`, `0x15180`,
+//! and `86400u64` all match `cgrep const 86400`.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::cli::OutputFormat;
+use crate::indexer::literals::constant_name;
+use crate::indexer::scanner::FileScanner;
+use crate::parser::languages::LANGUAGES;
+use crate::query::ast_usage::AstUsageExtractor;
+use cgrep::output::{
+    colorize_line_num, colorize_path, print_json, print_quickfix, use_colors, QuickfixEntry,
+};
+
+/// Value of a numeric literal.
+#[derive(Debug, Clone, Copy)]
+enum NumericValue {
+    Int(i128),
+    Float(f64),
+}
+
+impl NumericValue {
+    fn as_f64(self) -> f64 {
+        match self {
+            NumericValue::Int(value) => value as f64,
+            NumericValue::Float(value) => value,
+        }
+    }
+}
+
+impl PartialEq for NumericValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (NumericValue::Int(a), NumericValue::Int(b)) => a == b,
+            _ => self.as_f64() == other.as_f64(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ConstResult {
+    path: String,
+    line: usize,
+    column: usize,
+    /// Literal as written.
+    literal: String,
+    /// Constant the literal initializes, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    constant: Option<String>,
+    code: String,
+}
+
+/// Run the const command.
+pub fn run(
+    value: &str,
+    path: Option<&str>,
+    max_results: Option<usize>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let Some(needle) = parse_numeric(value) else {
+        bail!("Not a numeric literal: {}", value);
+    };
+
+    let search_root = match path {
+        Some(p) => PathBuf::from(p).canonicalize()?,
+        None => std::env::current_dir()?.canonicalize()?,
+    };
+
+    let mut ast = AstUsageExtractor::new();
+    let mut results = Vec::new();
+    for file in FileScanner::new(&search_root).scan()? {
+        let Some(language) = file.language.as_deref() else {
+            continue;
+        };
+        if LANGUAGES.get(language).is_none() {
+            continue;
+        }
+        let Some(literals) = ast.numeric_literals(&file.content, language) else {
+            continue;
+        };
+        let lines: Vec<&str> = file.content.lines().collect();
+        let rel_path = file
+            .path
+            .strip_prefix(&search_root)
+            .unwrap_or(&file.path)
+            .display()
+            .to_string();
+        for literal in literals {
+            if parse_numeric(&literal.text) != Some(needle) {
+                continue;
+            }
+            let line_text = lines.get(literal.line - 1).copied().unwrap_or_default();
+            let before = line_text.get(..literal.column - 1).unwrap_or_default();
+            results.push(ConstResult {
+                path: rel_path.clone(),
+                line: literal.line,
+                column: literal.column,
+                literal: literal.text,
+                constant: constant_name(before),
+                code: line_text.trim().to_string(),
+            });
+        }
+    }
+    results.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then(a.line.cmp(&b.line))
+            .then(a.column.cmp(&b.column))
+    });
+    if let Some(limit) = max_results {
+        results.truncate(limit);
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => {
+            print_json(&results, compact)?;
+        }
+        OutputFormat::Quickfix => {
+            let entries: Vec<QuickfixEntry<'_>> = results
+                .iter()
+                .map(|result| QuickfixEntry {
+                    path: &result.path,
+                    line: result.line,
+                    column: result.column,
+                    text: &result.code,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            let use_color = use_colors() && format == OutputFormat::Text;
+            if results.is_empty() {
+                if use_color {
+                    println!("{} No literal with value: {}", "✗".red(), value.yellow());
+                } else {
+                    println!("No literal with value: {}", value);
+                }
+                return Ok(());
+            }
+            for result in &results {
+                let constant = result
+                    .constant
+                    .as_deref()
+                    .map(|name| format!("  ({})", name))
+                    .unwrap_or_default();
+                println!(
+                    "{}:{}:{}: {}{}",
+                    colorize_path(&result.path, use_color),
+                    colorize_line_num(result.line, use_color),
+                    result.column,
+                    result.code,
+                    constant
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a numeric literal in any supported language's syntax: digit
+/// separators (`_`, `'`), radix prefixes (`0x`, `0o`, `0b`, C-style leading
+/// `0` octal), exponents, and type suffixes (`u64`, `UL`, `f`, `n`, ...).
+fn parse_numeric(text: &str) -> Option<NumericValue> {
+    let text = text.trim();
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let cleaned: String = digits
+        .chars()
+        .filter(|c| !matches!(c, '_' | '\''))
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    let value = if let Some((radix, rest)) = radix_prefix(&cleaned) {
+        let end = rest
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(rest.len());
+        if rest[end..].starts_with(['.', 'p']) {
+            return None;
+        }
+        NumericValue::Int(i128::from_str_radix(&rest[..end], radix).ok()?)
+    } else {
+        let (mantissa, suffix) = split_decimal(&cleaned);
+        if !mantissa.contains(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let float = mantissa.contains(['.', 'e']) || suffix.starts_with('f') || suffix == "d";
+        if float {
+            NumericValue::Float(mantissa.parse().ok()?)
+        } else if mantissa.len() > 1
+            && mantissa.starts_with('0')
+            && mantissa.chars().all(|c| c.is_digit(8))
+        {
+            NumericValue::Int(i128::from_str_radix(&mantissa[1..], 8).ok()?)
+        } else {
+            NumericValue::Int(mantissa.parse().ok()?)
+        }
+    };
+
+    Some(match (negative, value) {
+        (false, value) => value,
+        (true, NumericValue::Int(value)) => NumericValue::Int(-value),
+        (true, NumericValue::Float(value)) => NumericValue::Float(-value),
+    })
+}
+
+fn radix_prefix(text: &str) -> Option<(u32, &str)> {
+    [("0x", 16), ("0o", 8), ("0b", 2)]
+        .into_iter()
+        .find_map(|(prefix, radix)| text.strip_prefix(prefix).map(|rest| (radix, rest)))
+}
+
+/// Split `1.5e3f32` into the decimal part `1.5e3` and the suffix `f32`.
+fn split_decimal(text: &str) -> (&str, &str) {
+    let bytes = text.as_bytes();
+    let mut end = 0;
+    let mut seen_dot = false;
+    while end < bytes.len() {
+        match bytes[end] {
+            b'0'..=b'9' => end += 1,
+            b'.' if !seen_dot => {
+                seen_dot = true;
+                end += 1;
+            }
+            b'e' => {
+                let mut exp = end + 1;
+                if matches!(bytes.get(exp), Some(b'+' | b'-')) {
+                    exp += 1;
+                }
+                if !bytes.get(exp).is_some_and(u8::is_ascii_digit) {
+                    break;
+                }
+                end = exp;
+                while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+                    end += 1;
+                }
+                break;
+            }
+            _ => break,
+        }
+    }
+    text.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_spellings_compare_by_value() {
+        let day = parse_numeric("86400");
+        for spelling in [
+            "86_400", "0x15180", "86400u64", "86400UL", "86400n", "86400.0",
+        ] {
+            assert_eq!(parse_numeric(spelling), day, "{spelling}");
+        }
+        assert_eq!(parse_numeric("0755"), parse_numeric("0o755"));
+        assert_eq!(parse_numeric("1e3"), parse_numeric("1000"));
+        assert_eq!(parse_numeric("2.5f"), Some(NumericValue::Float(2.5)));
+        assert_eq!(parse_numeric("-1"), Some(NumericValue::Int(-1)));
+        assert_ne!(parse_numeric("-1"), parse_numeric("1"));
+        assert!(parse_numeric("abc").is_none());
+    }
+
+    #[test]
+    fn only_literal_nodes_are_reported() {
+        let source = "// wait 86400 seconds\nconst DAY: u64 = 86_400;\nfn retry_86400() -> i64 {\n    let s = \"86400\";\n    -86400\n}\n";
+        let mut ast = AstUsageExtractor::new();
+        let literals = ast.numeric_literals(source, "rust").expect("rust parses");
+        let found: Vec<(usize, usize, &str)> = literals
+            .iter()
+            .map(|l| (l.line, l.column, l.text.as_str()))
+            .collect();
+        assert_eq!(found, [(2, 18, "86_400"), (5, 5, "-86400")]);
+
+        let c = ast
+            .numeric_literals("#define TIMEOUT 30\nint x = 0x1e;\n", "c")
+            .expect("c parses");
+        let texts: Vec<&str> = c.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["30", "0x1e"]);
+    }
+}
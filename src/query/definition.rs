@@ -14,7 +14,7 @@ use crate::parser::symbols::{Symbol, SymbolExtractor, SymbolKind};
 use crate::query::index_filter::{
     find_files_with_symbol, find_files_with_symbol_definition, read_scanned_files, SymbolNameMatch,
 };
-use cgrep::output::print_json;
+use cgrep::output::{print_json, print_quickfix, QuickfixEntry};
 use cgrep::utils::get_root_with_index;
 
 /// Definition result for JSON output
@@ -76,8 +76,9 @@ pub fn run(
                 }
 
                 for symbol in symbols {
-                    // Skip variable/property references, focus on definitions
-                    if !is_definition_kind(&symbol.kind) {
+                    // Skip variable/property references and macro invocations
+                    // (`name!`), focus on definitions
+                    if !is_definition_kind(&symbol.kind) || symbol.name.ends_with('!') {
                         continue;
                     }
                     let line_text = lines
@@ -146,7 +147,24 @@ pub fn run(
         OutputFormat::Json | OutputFormat::Json2 => {
             print_json(&results, compact)?;
         }
-        OutputFormat::Text => {
+        OutputFormat::Quickfix => {
+            let texts: Vec<String> = results
+                .iter()
+                .map(|result| format!("[{}] {}", result.kind, result.name))
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = results
+                .iter()
+                .zip(&texts)
+                .map(|(result, text)| QuickfixEntry {
+                    path: &result.path,
+                    line: result.line,
+                    column: result.column,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
             if results.is_empty() {
                 println!("{} No definition found for: {}", "✗".red(), name.yellow());
                 return Ok(());
@@ -215,6 +233,8 @@ fn is_definition_kind(kind: &SymbolKind) -> bool {
             | SymbolKind::Struct
             | SymbolKind::Enum
             | SymbolKind::Trait
+            | SymbolKind::Key
+            | SymbolKind::Macro
     )
 }
 
@@ -429,6 +449,10 @@ mod tests {
             byte_start: None,
             byte_end: None,
             scope: None,
+            doc: None,
+            signature: None,
+            decorators: Vec::new(),
+            metrics: None,
         }
     }
 
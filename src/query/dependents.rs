@@ -1,110 +1,200 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 //! Find files that depend on a given file
+//!
+//! With `--transitive`, dependents of dependents are followed breadth-first
+//! up to `--depth` levels. Each result carries the chain of files leading
+//! back to the target, and an import that closes a loop is reported as a
+//! cycle instead of being followed.
 
 use anyhow::Result;
 use colored::Colorize;
-use regex::Regex;
 use serde::Serialize;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use crate::cli::OutputFormat;
-use crate::indexer::scanner::FileScanner;
+use crate::indexer::scanner::{FileScanner, ScannedFile};
+use crate::query::imports::extract_imports;
 use crate::query::index_filter::{find_files_with_content, read_scanned_files};
-use cgrep::output::print_json;
+use cgrep::output::{print_json, print_quickfix, QuickfixEntry};
 use cgrep::utils::get_root_with_index;
 
+/// Levels followed by `--transitive` without `--depth`.
+const DEFAULT_TRANSITIVE_DEPTH: usize = 5;
+
 /// Dependent result for JSON output
 #[derive(Debug, Serialize)]
 struct DependentResult {
     path: String,
     line: usize,
     import_line: String,
+    /// Import hops from the target (only with --transitive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth: Option<usize>,
+    /// This file, the files it reaches the target through, and the target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chain: Option<Vec<String>>,
 }
 
-/// Run the dependents command
-pub fn run(file: &str, format: OutputFormat, compact: bool) -> Result<()> {
-    let search_root = std::env::current_dir()?.canonicalize()?;
-    let index_root = get_root_with_index(&search_root);
-    let target_path = Path::new(file);
-    let target_stem = target_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or(file);
-
-    let files = match find_files_with_content(&index_root, target_stem, Some(&search_root))? {
-        Some(indexed_paths) => read_scanned_files(&indexed_paths),
-        None => {
-            let scanner = FileScanner::new(&search_root);
-            scanner.scan()?
-        }
-    };
+/// Transitive dependents report for JSON output
+#[derive(Debug, Serialize)]
+struct TransitiveReport<'a> {
+    target: &'a str,
+    depth: usize,
+    results: &'a [DependentResult],
+    /// Import loops, each listed from the file that closes it: every file
+    /// imports the next, and the last one is the first again
+    cycles: &'a [Vec<String>],
+}
 
-    // Patterns to match imports
-    let patterns = [
-        // JavaScript/TypeScript: import ... from 'path' or require('path')
-        format!(
-            r#"(?:import|from|require)\s*[\(\s]?['"](?:[./]*{})['"]"#,
-            regex::escape(target_stem)
-        ),
-        // Python: import path or from path import
-        format!(
-            r"(?:import|from)\s+{}(?:\s|$|,)",
-            regex::escape(target_stem)
-        ),
-        // Rust: use path or mod path
-        format!(
-            r"(?:use|mod)\s+(?:crate::)?{}(?:::|;|\s)",
-            regex::escape(target_stem)
-        ),
-        // Go: import "path"
-        format!(
-            r#"import\s+[\(\s]*['"](?:[./]*{})['"]"#,
-            regex::escape(target_stem)
-        ),
-    ];
-
-    let regexes: Vec<Regex> = patterns.iter().filter_map(|p| Regex::new(p).ok()).collect();
-
-    let mut results: Vec<DependentResult> = Vec::new();
-
-    for scanned_file in &files {
-        let rel_path = scanned_file
-            .path
-            .strip_prefix(&search_root)
-            .unwrap_or(&scanned_file.path);
-
-        // Skip the target file itself
-        if rel_path.to_string_lossy().contains(target_stem) {
-            continue;
-        }
+/// Module name imports use for `path`: its stem, or its directory for
+/// `mod.rs`, `index.*`, and `__init__.py`.
+fn module_stem(path: &str) -> &str {
+    let path = Path::new(path);
+    let stem = path.file_stem().and_then(|s| s.to_str());
+    match stem {
+        Some("mod" | "index" | "__init__") => path
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .and_then(|name| name.to_str())
+            .or(stem),
+        _ => stem,
+    }
+    .unwrap_or_default()
+}
+
+/// Files that may import a module, from the index when there is one.
+struct Candidates {
+    search_root: PathBuf,
+    index_root: PathBuf,
+    /// Every file, scanned once when there is no index
+    scanned: Option<Vec<ScannedFile>>,
+}
+
+impl Candidates {
+    fn direct_dependents(
+        &mut self,
+        stem: &str,
+        skip: impl Fn(&str) -> bool,
+    ) -> Result<Vec<DependentResult>> {
+        let indexed;
+        let files: &[ScannedFile] =
+            match find_files_with_content(&self.index_root, stem, Some(&self.search_root))? {
+                Some(indexed_paths) => {
+                    indexed = read_scanned_files(&indexed_paths);
+                    &indexed
+                }
+                None => {
+                    if self.scanned.is_none() {
+                        self.scanned = Some(FileScanner::new(&self.search_root).scan()?);
+                    }
+                    self.scanned.as_deref().unwrap_or_default()
+                }
+            };
+
+        let mut results = Vec::new();
+        for scanned_file in files {
+            let rel_path = scanned_file
+                .path
+                .strip_prefix(&self.search_root)
+                .unwrap_or(&scanned_file.path)
+                .display()
+                .to_string();
+            if skip(&rel_path) {
+                continue;
+            }
 
-        for (line_num, line) in scanned_file.content.lines().enumerate() {
-            for re in &regexes {
-                if re.is_match(line) {
+            let mut last_line = 0;
+            for import in extract_imports(&scanned_file.content, scanned_file.language.as_deref()) {
+                if import.line != last_line && import.names_module(stem) {
+                    last_line = import.line;
                     results.push(DependentResult {
-                        path: rel_path.display().to_string(),
-                        line: line_num + 1,
-                        import_line: line.trim().to_string(),
+                        path: rel_path.clone(),
+                        line: import.line,
+                        import_line: import.text,
+                        depth: None,
+                        chain: None,
                     });
-                    break;
                 }
             }
         }
+        Ok(results)
+    }
+}
+
+/// Run the dependents command
+pub fn run(
+    file: &str,
+    transitive: bool,
+    depth: Option<usize>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let search_root = std::env::current_dir()?.canonicalize()?;
+    let index_root = get_root_with_index(&search_root);
+    let target = file.trim_start_matches("./");
+    let target_stem = match module_stem(target) {
+        "" => file,
+        stem => stem,
+    };
+    let mut candidates = Candidates {
+        search_root,
+        index_root,
+        scanned: None,
+    };
+
+    // Skip the target file itself
+    let mut results =
+        candidates.direct_dependents(target_stem, |path| path.contains(target_stem))?;
+    let max_depth = depth.unwrap_or(DEFAULT_TRANSITIVE_DEPTH).max(1);
+    let mut cycles: Vec<Vec<String>> = Vec::new();
+    if transitive {
+        results = walk_dependents(&mut candidates, target, results, max_depth, &mut cycles)?;
     }
 
     match format {
         OutputFormat::Json | OutputFormat::Json2 => {
-            print_json(&results, compact)?;
+            if transitive {
+                print_json(
+                    &TransitiveReport {
+                        target,
+                        depth: max_depth,
+                        results: &results,
+                        cycles: &cycles,
+                    },
+                    compact,
+                )?;
+            } else {
+                print_json(&results, compact)?;
+            }
+        }
+        OutputFormat::Quickfix => {
+            let entries: Vec<QuickfixEntry<'_>> = results
+                .iter()
+                .map(|result| QuickfixEntry {
+                    path: &result.path,
+                    line: result.line,
+                    column: 1,
+                    text: &result.import_line,
+                })
+                .collect();
+            print_quickfix(&entries);
         }
-        OutputFormat::Text => {
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
             if results.is_empty() {
                 println!("{} No files depend on: {}", "✗".red(), file.yellow());
             } else {
+                let scope = if transitive {
+                    format!(" (transitive, depth ≤ {max_depth})")
+                } else {
+                    String::new()
+                };
                 println!(
-                    "\n{} Finding files that depend on: {}\n",
+                    "\n{} Finding files that depend on: {}{}\n",
                     "🔍".cyan(),
-                    file.yellow()
+                    file.yellow(),
+                    scope
                 );
                 for result in &results {
                     println!(
@@ -113,6 +203,13 @@ pub fn run(file: &str, format: OutputFormat, compact: bool) -> Result<()> {
                         result.line.to_string().yellow(),
                         result.import_line.dimmed()
                     );
+                    if let Some(chain) = result.chain.as_ref().filter(|chain| chain.len() > 2) {
+                        println!(
+                            "      {} {}",
+                            "via".dimmed(),
+                            chain[1..].join(" → ").dimmed()
+                        );
+                    }
                 }
                 println!(
                     "\n{} Found {} dependent files",
@@ -120,8 +217,86 @@ pub fn run(file: &str, format: OutputFormat, compact: bool) -> Result<()> {
                     results.len().to_string().cyan()
                 );
             }
+            for cycle in &cycles {
+                println!("{} Import cycle: {}", "⚠".yellow(), cycle.join(" → "));
+            }
         }
     }
 
     Ok(())
 }
+
+/// Breadth-first walk of the reverse import graph from `target`, starting
+/// from its direct dependents. Keeps one result per file, at its shortest
+/// depth, and records imports that lead back into a file's own chain as
+/// cycles.
+fn walk_dependents(
+    candidates: &mut Candidates,
+    target: &str,
+    direct: Vec<DependentResult>,
+    max_depth: usize,
+    cycles: &mut Vec<Vec<String>>,
+) -> Result<Vec<DependentResult>> {
+    let target_chain = vec![target.to_string()];
+    let mut chains: HashMap<String, Vec<String>> =
+        HashMap::from([(target.to_string(), target_chain.clone())]);
+    let mut seen_cycles: HashSet<Vec<String>> = HashSet::new();
+    let mut results = Vec::new();
+
+    let mut level = direct;
+    let mut parents = vec![target_chain; level.len()];
+    for depth in 1..=max_depth {
+        let mut frontier = Vec::new();
+        for (mut result, parent_chain) in level.into_iter().zip(parents) {
+            if let Some(position) = parent_chain.iter().position(|path| *path == result.path) {
+                let mut cycle = vec![result.path.clone()];
+                cycle.extend(parent_chain[..=position].iter().cloned());
+                if seen_cycles.insert(cycle.clone()) {
+                    cycles.push(cycle);
+                }
+                continue;
+            }
+            if chains.contains_key(&result.path) {
+                continue;
+            }
+            let mut chain = vec![result.path.clone()];
+            chain.extend(parent_chain);
+            chains.insert(result.path.clone(), chain.clone());
+            result.depth = Some(depth);
+            result.chain = Some(chain);
+            frontier.push(result.path.clone());
+            results.push(result);
+        }
+        if depth == max_depth {
+            break;
+        }
+
+        level = Vec::new();
+        parents = Vec::new();
+        for path in frontier {
+            let chain = chains[&path].clone();
+            for result in candidates.direct_dependents(module_stem(&path), |other| other == path)? {
+                level.push(result);
+                parents.push(chain.clone());
+            }
+        }
+        if level.is_empty() {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_stem_uses_directory_for_package_entry_files() {
+        assert_eq!(module_stem("src/query/search.rs"), "search");
+        assert_eq!(module_stem("src/query/mod.rs"), "query");
+        assert_eq!(module_stem("web/api/index.ts"), "api");
+        assert_eq!(module_stem("pkg/__init__.py"), "pkg");
+        assert_eq!(module_stem("index.ts"), "index");
+    }
+}
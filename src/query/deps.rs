@@ -0,0 +1,348 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep deps --external`: dependencies declared in package manifests.
+//!
+//! Every `Cargo.toml`, `package.json`, and `pyproject.toml` under the path
+//! is parsed (see `parser::manifests`), and each declaration is paired with
+//! the version pinned by the nearest lockfile of the same ecosystem, in its
+//! directory or a parent. Path and workspace-package dependencies are left
+//! out; Cargo `workspace = true` entries take their requirement and source
+//! from the nearest `[workspace.dependencies]`.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use ignore::WalkBuilder;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::parser::manifests::{
+    normalize_python_name, parse_lockfile, parse_manifest, DeclaredDependency, DependencySource,
+    ManifestKind,
+};
+use cgrep::output::{print_json, print_quickfix, QuickfixEntry};
+use cgrep::paths;
+
+const LOCKFILE_NAMES: [&str; 6] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "poetry.lock",
+    "uv.lock",
+];
+
+/// One declared external dependency for JSON output
+#[derive(Debug, Serialize)]
+struct DependencyRecord {
+    name: String,
+    ecosystem: &'static str,
+    /// Version requirement as declared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    /// Version pinned by the lockfile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locked: Option<String>,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    optional: bool,
+    /// `registry` or `git`
+    source: &'static str,
+    declared_in: String,
+    line: usize,
+}
+
+/// Versions pinned per package name, keyed by lockfile directory and ecosystem.
+type Lockfiles = HashMap<(PathBuf, &'static str), HashMap<String, Vec<String>>>;
+
+/// Run the external dependencies view of the deps command
+pub fn run(path: Option<&str>, format: OutputFormat, compact: bool) -> Result<()> {
+    let cwd = paths::canonicalize(&std::env::current_dir()?)?;
+    let root = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => cwd.clone(),
+    };
+
+    let mut manifests: Vec<(PathBuf, ManifestKind, Vec<DeclaredDependency>)> = Vec::new();
+    let mut lockfiles: Lockfiles = HashMap::new();
+    for (file, kind) in manifest_files(&root) {
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let dir = file.parent().unwrap_or(&root).to_path_buf();
+        if kind.is_lockfile() {
+            match parse_lockfile(kind, &content) {
+                Ok(packages) => {
+                    let pinned = lockfiles.entry((dir, kind.ecosystem())).or_default();
+                    for package in packages {
+                        pinned
+                            .entry(package_key(kind.ecosystem(), &package.name))
+                            .or_default()
+                            .push(package.version);
+                    }
+                }
+                Err(err) => eprintln!("Warning: failed to parse {}: {}", file.display(), err),
+            }
+            continue;
+        }
+        match parse_manifest(kind, &content) {
+            Ok(deps) => manifests.push((file, kind, deps)),
+            Err(err) => eprintln!("Warning: failed to parse {}: {}", file.display(), err),
+        }
+    }
+
+    // `[workspace.dependencies]` tables, by the directory that declares them.
+    let workspaces: HashMap<PathBuf, HashMap<&str, &DeclaredDependency>> = manifests
+        .iter()
+        .filter(|(_, kind, _)| *kind == ManifestKind::CargoToml)
+        .map(|(file, _, deps)| {
+            let declared = deps
+                .iter()
+                .filter(|dep| dep.kind == "workspace")
+                .map(|dep| (dep.name.as_str(), dep))
+                .collect();
+            (file.parent().unwrap_or(&root).to_path_buf(), declared)
+        })
+        .collect();
+
+    let mut records = Vec::new();
+    for (file, kind, deps) in &manifests {
+        let dir = file.parent().unwrap_or(&root);
+        let ecosystem = kind.ecosystem();
+        let declared_in =
+            paths::relative_display(file, &cwd).unwrap_or_else(|| paths::display(file));
+        for dep in deps {
+            let (requirement, source) = match dep.source {
+                DependencySource::Workspace => {
+                    let inherited = nearest(dir, &root, |dir| {
+                        workspaces.get(dir)?.get(dep.name.as_str()).copied()
+                    });
+                    match inherited {
+                        Some(inherited) => (inherited.requirement.clone(), inherited.source),
+                        None => (None, DependencySource::Registry),
+                    }
+                }
+                source => (dep.requirement.clone(), source),
+            };
+            if !matches!(source, DependencySource::Registry | DependencySource::Git) {
+                continue;
+            }
+            let key = package_key(ecosystem, &dep.name);
+            let locked = nearest(dir, &root, |dir| {
+                lockfiles.get(&(dir.to_path_buf(), ecosystem))?.get(&key)
+            })
+            .and_then(|versions| pick_locked(requirement.as_deref(), versions));
+            records.push(DependencyRecord {
+                name: dep.name.clone(),
+                ecosystem,
+                version: requirement,
+                locked,
+                kind: dep.kind,
+                group: dep.group.clone(),
+                optional: dep.optional,
+                source: source.as_str(),
+                declared_in: declared_in.clone(),
+                line: dep.line,
+            });
+        }
+    }
+    records.sort_by(|a, b| (&a.declared_in, a.line).cmp(&(&b.declared_in, b.line)));
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&records, compact)?,
+        OutputFormat::Quickfix => {
+            let texts: Vec<String> = records.iter().map(record_summary).collect();
+            let entries: Vec<QuickfixEntry<'_>> = records
+                .iter()
+                .zip(&texts)
+                .map(|(record, text)| QuickfixEntry {
+                    path: &record.declared_in,
+                    line: record.line,
+                    column: 1,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            print_text(&records)
+        }
+    }
+    Ok(())
+}
+
+/// Manifests and lockfiles under `root`, skipping vendored and build trees.
+fn manifest_files(root: &Path) -> Vec<(PathBuf, ManifestKind)> {
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(true)
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_str().unwrap_or("");
+            !matches!(
+                name,
+                ".git" | ".cgrep" | "node_modules" | "target" | ".venv"
+            )
+        })
+        .build();
+    let mut files: Vec<(PathBuf, ManifestKind)> = walker
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .filter_map(|entry| {
+            let kind = ManifestKind::of(entry.file_name().to_str()?)?;
+            Some((entry.into_path(), kind))
+        })
+        .collect();
+
+    // Lockfiles are often git-ignored (`Cargo.lock` in libraries), so look
+    // for them next to every manifest and in its parents.
+    let mut probed = HashSet::new();
+    let manifest_dirs: Vec<PathBuf> = files
+        .iter()
+        .filter(|(_, kind)| !kind.is_lockfile())
+        .filter_map(|(file, _)| file.parent().map(Path::to_path_buf))
+        .collect();
+    for dir in &manifest_dirs {
+        for ancestor in dir
+            .ancestors()
+            .take_while(|ancestor| ancestor.starts_with(root))
+        {
+            if !probed.insert(ancestor.to_path_buf()) {
+                break;
+            }
+            for name in LOCKFILE_NAMES {
+                let lockfile = ancestor.join(name);
+                if lockfile.is_file() && !files.iter().any(|(file, _)| *file == lockfile) {
+                    files.extend(ManifestKind::of(name).map(|kind| (lockfile, kind)));
+                }
+            }
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+fn package_key(ecosystem: &str, name: &str) -> String {
+    if ecosystem == "pypi" {
+        normalize_python_name(name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// First hit of `lookup` in `dir` or one of its parents up to `root`.
+fn nearest<'a, T>(
+    dir: &Path,
+    root: &Path,
+    lookup: impl Fn(&Path) -> Option<&'a T>,
+) -> Option<&'a T> {
+    dir.ancestors()
+        .take_while(|ancestor| ancestor.starts_with(root))
+        .find_map(lookup)
+}
+
+/// The pinned version a requirement resolved to. A lockfile can pin several
+/// versions of one package; the one sharing the requirement's leading
+/// version numbers wins, and all of them are listed when none does.
+fn pick_locked(requirement: Option<&str>, versions: &[String]) -> Option<String> {
+    let mut versions = versions.to_vec();
+    versions.sort();
+    versions.dedup();
+    if versions.len() <= 1 {
+        return versions.pop();
+    }
+    let prefix: String = requirement
+        .unwrap_or_default()
+        .trim_start_matches(|ch: char| !ch.is_ascii_digit())
+        .chars()
+        .take_while(|ch| ch.is_ascii_digit() || *ch == '.')
+        .collect();
+    let prefix = prefix.trim_end_matches('.');
+    let matching: Vec<&String> = versions
+        .iter()
+        .filter(|version| {
+            !prefix.is_empty()
+                && (version.as_str() == prefix || version.starts_with(&format!("{prefix}.")))
+        })
+        .collect();
+    match matching.as_slice() {
+        [version] => Some(version.to_string()),
+        _ => Some(versions.join(", ")),
+    }
+}
+
+fn record_summary(record: &DependencyRecord) -> String {
+    let mut summary = record.name.clone();
+    if let Some(version) = &record.version {
+        summary.push(' ');
+        summary.push_str(version);
+    }
+    if let Some(locked) = &record.locked {
+        summary.push_str(&format!(" (locked {locked})"));
+    }
+    if record.kind != "normal" {
+        summary.push_str(&format!(" [{}]", record.kind));
+    }
+    if record.source != "registry" {
+        summary.push_str(&format!(" [{}]", record.source));
+    }
+    summary
+}
+
+fn print_text(records: &[DependencyRecord]) {
+    if records.is_empty() {
+        println!("{} No external dependencies declared", "✗".red());
+        return;
+    }
+    println!(
+        "\n{} External dependencies ({})",
+        "📦".cyan(),
+        records.len().to_string().cyan()
+    );
+    let mut current: Option<&str> = None;
+    for record in records {
+        if current != Some(record.declared_in.as_str()) {
+            current = Some(&record.declared_in);
+            println!(
+                "\n  {} {}",
+                record.declared_in.bold(),
+                format!("({})", record.ecosystem).dimmed()
+            );
+        }
+        println!(
+            "    {:>4}  {}",
+            record.line.to_string().yellow(),
+            record_summary(record)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_locked_prefers_the_version_matching_the_requirement() {
+        let versions = |list: &[&str]| list.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            pick_locked(Some("^1.2"), &versions(&["1.2.7"])).as_deref(),
+            Some("1.2.7")
+        );
+        assert_eq!(
+            pick_locked(Some("0.9"), &versions(&["1.0.3", "0.9.1"])).as_deref(),
+            Some("0.9.1")
+        );
+        assert_eq!(
+            pick_locked(None, &versions(&["2.0.0", "1.0.0"])).as_deref(),
+            Some("1.0.0, 2.0.0")
+        );
+        assert_eq!(pick_locked(Some("1"), &[]), None);
+    }
+}
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep diff-index`: compare the symbols of two index states.
+//!
+//! Either side is a snapshot saved by `cgrep index snapshot`; the second
+//! side defaults to the live index. Symbols are matched by path, kind, and
+//! name (in line order when a file defines the same name twice), and a
+//! matched symbol counts as changed when its indexed preview differs. A
+//! symbol that only moved within its file is not reported.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::cli::OutputFormat;
+use crate::indexer::snapshot::{resolve_root, snapshot_index_dir};
+use crate::query::index_filter::{read_symbol_docs, IndexedSymbol};
+use cgrep::output::print_json;
+use cgrep::utils::INDEX_DIR;
+
+/// Label of the live index in reports.
+const CURRENT: &str = "current";
+
+#[derive(Debug, Serialize)]
+struct SymbolChange {
+    name: String,
+    kind: String,
+    path: String,
+    line: u64,
+    /// Line in the older index, for changed symbols.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before_line: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct DiffIndexReport {
+    from: String,
+    to: String,
+    added: Vec<SymbolChange>,
+    removed: Vec<SymbolChange>,
+    changed: Vec<SymbolChange>,
+}
+
+/// Run the diff-index command.
+pub fn run(
+    snapshots: &[String],
+    baseline: Option<&str>,
+    path: Option<&str>,
+    fail_on_removed: bool,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let (from, to) = match (baseline, snapshots) {
+        (Some(baseline), [] | [_]) => (baseline, snapshots.first().map(String::as_str)),
+        (None, [from]) => (from.as_str(), None),
+        (None, [from, to]) => (from.as_str(), Some(to.as_str())),
+        (None, []) => bail!(
+            "Name a snapshot to compare: `cgrep diff-index <from> [to]` or `--baseline <tag>`"
+        ),
+        _ => bail!("--baseline takes the place of the first snapshot; pass at most one more"),
+    };
+    let root = resolve_root(path)?;
+    let before = read_symbol_docs(&snapshot_index_dir(&root, from)?)?;
+    let after = match to {
+        Some(tag) => read_symbol_docs(&snapshot_index_dir(&root, tag)?)?,
+        None => {
+            let live = root.join(INDEX_DIR);
+            if !live.join("meta.json").is_file() {
+                bail!(
+                    "No index found at {}. Run `cgrep index` first.",
+                    root.display()
+                );
+            }
+            read_symbol_docs(&live)?
+        }
+    };
+    let mut report = diff(before, after);
+    report.from = from.trim().to_string();
+    report.to = to.map_or(CURRENT, str::trim).to_string();
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&report, compact)?,
+        _ => print_text(&report),
+    }
+
+    if fail_on_removed && !report.removed.is_empty() {
+        bail!(
+            "{} symbol(s) removed since `{}`",
+            report.removed.len(),
+            report.from
+        );
+    }
+    Ok(())
+}
+
+fn change(symbol: &IndexedSymbol, before_line: Option<u64>) -> SymbolChange {
+    SymbolChange {
+        name: symbol.name.clone(),
+        kind: symbol.kind.clone(),
+        path: symbol.path.clone(),
+        line: symbol.line,
+        before_line,
+    }
+}
+
+/// Symbols added, removed, and changed from `before` to `after`, sorted by
+/// path and line. The report labels are left empty.
+fn diff(before: Vec<IndexedSymbol>, after: Vec<IndexedSymbol>) -> DiffIndexReport {
+    type Key = (String, String, String);
+    let mut sides: BTreeMap<Key, (Vec<IndexedSymbol>, Vec<IndexedSymbol>)> = BTreeMap::new();
+    for symbol in before {
+        let key = (
+            symbol.path.clone(),
+            symbol.kind.clone(),
+            symbol.name.clone(),
+        );
+        sides.entry(key).or_default().0.push(symbol);
+    }
+    for symbol in after {
+        let key = (
+            symbol.path.clone(),
+            symbol.kind.clone(),
+            symbol.name.clone(),
+        );
+        sides.entry(key).or_default().1.push(symbol);
+    }
+
+    let mut report = DiffIndexReport {
+        from: String::new(),
+        to: String::new(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    for (_, (mut old, mut new)) in sides {
+        old.sort_by_key(|symbol| symbol.line);
+        new.sort_by_key(|symbol| symbol.line);
+        for (old_symbol, new_symbol) in old.iter().zip(&new) {
+            if old_symbol.content != new_symbol.content {
+                report
+                    .changed
+                    .push(change(new_symbol, Some(old_symbol.line)));
+            }
+        }
+        let paired = old.len().min(new.len());
+        report
+            .removed
+            .extend(old[paired..].iter().map(|symbol| change(symbol, None)));
+        report
+            .added
+            .extend(new[paired..].iter().map(|symbol| change(symbol, None)));
+    }
+    for changes in [&mut report.added, &mut report.removed, &mut report.changed] {
+        changes.sort_by(|a, b| (&a.path, a.line, &a.name).cmp(&(&b.path, b.line, &b.name)));
+    }
+    report
+}
+
+fn print_text(report: &DiffIndexReport) {
+    println!(
+        "Symbols from `{}` to `{}`: {} added, {} removed, {} changed",
+        report.from,
+        report.to,
+        report.added.len(),
+        report.removed.len(),
+        report.changed.len()
+    );
+    for (marker, changes) in [
+        ('+', &report.added),
+        ('-', &report.removed),
+        ('~', &report.changed),
+    ] {
+        for change in changes {
+            let moved = change
+                .before_line
+                .filter(|line| *line != change.line)
+                .map(|line| format!(" (was line {line})"))
+                .unwrap_or_default();
+            println!(
+                "{marker} {} {}  {}:{}{moved}",
+                change.kind, change.name, change.path, change.line
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, line: u64, body: &str) -> IndexedSymbol {
+        IndexedSymbol {
+            name: name.to_string(),
+            kind: "function".to_string(),
+            path: "src/lib.rs".to_string(),
+            language: "rust".to_string(),
+            line,
+            content: format!("{name} function\n{body}"),
+            metrics: None,
+        }
+    }
+
+    fn names(changes: &[SymbolChange]) -> Vec<(&str, u64)> {
+        changes
+            .iter()
+            .map(|change| (change.name.as_str(), change.line))
+            .collect()
+    }
+
+    #[test]
+    fn diff_pairs_symbols_by_path_kind_and_name() {
+        let before = vec![
+            symbol("keep", 1, "fn keep() {}"),
+            symbol("moved", 3, "fn moved() {}"),
+            symbol("edited", 5, "fn edited() {}"),
+            symbol("gone", 7, "fn gone() {}"),
+        ];
+        let after = vec![
+            symbol("keep", 1, "fn keep() {}"),
+            symbol("fresh", 3, "fn fresh() {}"),
+            symbol("moved", 5, "fn moved() {}"),
+            symbol("edited", 7, "fn edited(x: u8) {}"),
+        ];
+        let report = diff(before, after);
+        assert_eq!(names(&report.added), [("fresh", 3)]);
+        assert_eq!(names(&report.removed), [("gone", 7)]);
+        assert_eq!(names(&report.changed), [("edited", 7)]);
+        assert_eq!(report.changed[0].before_line, Some(5));
+    }
+}
@@ -0,0 +1,586 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep envvars`: inventory of environment variable reads.
+//!
+//! Reads are found in the syntax tree, so a variable named in a comment or a
+//! log message is not reported:
+//!
+//! - Rust: `env::var("X")`, `env::var_os`, `env!("X")`, `option_env!("X")`;
+//!   the default is the string in a following `.unwrap_or(...)` or
+//!   `.unwrap_or_else(...)`.
+//! - Python: `os.environ["X"]`, `os.environ.get("X", default)`,
+//!   `os.getenv("X", default)`.
+//! - JavaScript/TypeScript: `process.env.X`, `process.env["X"]`,
+//!   `import.meta.env.X`, and `const { X = default } = process.env`; the
+//!   default is the string after `||` or `??`.
+//! - Go `os.Getenv`/`os.LookupEnv`, Java `System.getenv`, C/C++ `getenv`,
+//!   and Ruby `ENV["X"]`/`ENV.fetch("X", default)`.
+//!
+//! Only literal names count; a name computed at runtime is skipped.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::FileScanner;
+use crate::parser::ast::is_string;
+use crate::parser::languages::LANGUAGES;
+use cgrep::output::{
+    colorize_line_num, colorize_path, print_json, print_quickfix, use_colors, QuickfixEntry,
+};
+use cgrep::paths;
+
+/// Markers that every supported read spells, checked before parsing.
+const MARKERS: &[&str] = &["env", "ENV", "getenv", "Getenv", "LookupEnv", "environ"];
+
+/// One read of an environment variable.
+#[derive(Debug, Clone, Serialize)]
+struct EnvRead {
+    path: String,
+    line: usize,
+    column: usize,
+    /// Fallback used when the variable is unset, when written as a literal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+    code: String,
+}
+
+/// Every read of one variable.
+#[derive(Debug, Serialize)]
+struct EnvVar {
+    name: String,
+    /// Distinct literal defaults, in first-seen order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    defaults: Vec<String>,
+    reads: Vec<EnvRead>,
+}
+
+/// Run the envvars command.
+pub fn run(
+    name: Option<&str>,
+    path: Option<&str>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let search_root = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => paths::canonicalize(&std::env::current_dir()?)?,
+    };
+    let filter = name
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_ascii_lowercase);
+
+    let mut by_name: BTreeMap<String, Vec<EnvRead>> = BTreeMap::new();
+    for file in FileScanner::new(&search_root).scan()? {
+        let Some(language) = file.language.as_deref() else {
+            continue;
+        };
+        if !MARKERS.iter().any(|marker| file.content.contains(marker)) {
+            continue;
+        }
+        let rel_path = paths::relative_display(&file.path, &search_root)
+            .unwrap_or_else(|| file.path.display().to_string());
+        let lines: Vec<&str> = file.content.lines().collect();
+        for found in env_reads(&file.content, language) {
+            if filter
+                .as_deref()
+                .is_some_and(|filter| !found.name.to_ascii_lowercase().contains(filter))
+            {
+                continue;
+            }
+            let code = lines
+                .get(found.line - 1)
+                .map(|line| line.trim().to_string())
+                .unwrap_or_default();
+            by_name.entry(found.name).or_default().push(EnvRead {
+                path: rel_path.clone(),
+                line: found.line,
+                column: found.column,
+                default: found.default,
+                code,
+            });
+        }
+    }
+
+    let vars: Vec<EnvVar> = by_name
+        .into_iter()
+        .map(|(name, mut reads)| {
+            reads.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+            let mut defaults: Vec<String> = Vec::new();
+            for default in reads.iter().filter_map(|read| read.default.as_ref()) {
+                if !defaults.contains(default) {
+                    defaults.push(default.clone());
+                }
+            }
+            EnvVar {
+                name,
+                defaults,
+                reads,
+            }
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&vars, compact)?,
+        OutputFormat::Quickfix => {
+            let texts: Vec<String> = vars
+                .iter()
+                .flat_map(|var| {
+                    var.reads.iter().map(|read| match &read.default {
+                        Some(default) => format!("{} (default {:?})", var.name, default),
+                        None => var.name.clone(),
+                    })
+                })
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = vars
+                .iter()
+                .flat_map(|var| &var.reads)
+                .zip(&texts)
+                .map(|(read, text)| QuickfixEntry {
+                    path: &read.path,
+                    line: read.line,
+                    column: read.column,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            let use_color = use_colors() && format == OutputFormat::Text;
+            if vars.is_empty() {
+                if use_color {
+                    println!("{} No environment variable reads found", "✗".red());
+                } else {
+                    println!("No environment variable reads found");
+                }
+                return Ok(());
+            }
+            for (idx, var) in vars.iter().enumerate() {
+                if idx > 0 {
+                    println!();
+                }
+                let defaults = if var.defaults.is_empty() {
+                    String::new()
+                } else {
+                    let quoted: Vec<String> = var
+                        .defaults
+                        .iter()
+                        .map(|default| format!("{default:?}"))
+                        .collect();
+                    format!(", default {}", quoted.join(" | "))
+                };
+                let summary = format!("({} reads{})", var.reads.len(), defaults);
+                if use_color {
+                    println!("{} {}", var.name.cyan().bold(), summary.dimmed());
+                } else {
+                    println!("{} {}", var.name, summary);
+                }
+                for read in &var.reads {
+                    println!(
+                        "  {}:{}: {}",
+                        colorize_path(&read.path, use_color),
+                        colorize_line_num(read.line, use_color),
+                        read.code
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A read found in one file.
+#[derive(Debug, PartialEq)]
+struct FoundRead {
+    name: String,
+    line: usize,
+    column: usize,
+    default: Option<String>,
+}
+
+/// Environment variable reads in one file, in source order.
+fn env_reads(content: &str, language: &str) -> Vec<FoundRead> {
+    let Some(mut parser) = LANGUAGES.parser(language) else {
+        return Vec::new();
+    };
+    let Some(tree) = parser.parse(content, None) else {
+        return Vec::new();
+    };
+    let mut reads = Vec::new();
+    visit(tree.root_node(), content.as_bytes(), language, &mut reads);
+    reads
+}
+
+fn visit(node: Node, source: &[u8], language: &str, reads: &mut Vec<FoundRead>) {
+    let found = match language {
+        "rust" => rust_read(node, source),
+        "python" => python_read(node, source),
+        "javascript" | "typescript" | "tsx" => {
+            if node.kind() == "variable_declarator" {
+                js_destructured(node, source, reads);
+                None
+            } else {
+                js_read(node, source)
+            }
+        }
+        "go" => call_read(
+            node,
+            source,
+            "call_expression",
+            &["os.Getenv", "os.LookupEnv"],
+        ),
+        "c" | "cpp" => call_read(node, source, "call_expression", &["getenv", "std::getenv"]),
+        "java" => java_read(node, source),
+        "ruby" => ruby_read(node, source),
+        _ => None,
+    };
+    if let Some((name, default)) = found {
+        reads.push(FoundRead {
+            name,
+            line: node.start_position().row + 1,
+            column: node.start_position().column + 1,
+            default,
+        });
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        visit(child, source, language, reads);
+    }
+}
+
+fn text<'s>(node: Node, source: &'s [u8]) -> &'s str {
+    node.utf8_text(source).unwrap_or_default()
+}
+
+/// `name(...)` calls whose callee is spelled as one of `callees`, reading
+/// the variable named by the first argument.
+fn call_read(
+    node: Node,
+    source: &[u8],
+    kind: &str,
+    callees: &[&str],
+) -> Option<(String, Option<String>)> {
+    if node.kind() != kind {
+        return None;
+    }
+    let function = node.child_by_field_name("function")?;
+    if !callees.contains(&text(function, source)) {
+        return None;
+    }
+    let name = nth_string_arg(node.child_by_field_name("arguments")?, 0, source)?;
+    Some((name, None))
+}
+
+fn rust_read(node: Node, source: &[u8]) -> Option<(String, Option<String>)> {
+    match node.kind() {
+        "call_expression" => {
+            let function = text(node.child_by_field_name("function")?, source);
+            let callee = function.rsplit("::").take(2).collect::<Vec<_>>();
+            if !matches!(callee.as_slice(), ["var" | "var_os", "env"]) {
+                return None;
+            }
+            let name = nth_string_arg(node.child_by_field_name("arguments")?, 0, source)?;
+            Some((name, rust_default(node, source)))
+        }
+        "macro_invocation" => {
+            let macro_name = text(node.child_by_field_name("macro")?, source);
+            if !matches!(macro_name, "env" | "option_env") {
+                return None;
+            }
+            let mut cursor = node.walk();
+            let token_tree = node
+                .named_children(&mut cursor)
+                .find(|child| child.kind() == "token_tree")?;
+            let name = nth_string_arg(token_tree, 0, source)?;
+            let default = (macro_name == "option_env")
+                .then(|| rust_default(node, source))
+                .flatten();
+            Some((name, default))
+        }
+        _ => None,
+    }
+}
+
+/// String passed to `.unwrap_or(...)` or `.unwrap_or_else(...)` on a read.
+fn rust_default(read: Node, source: &[u8]) -> Option<String> {
+    let field = read
+        .parent()
+        .filter(|parent| parent.kind() == "field_expression")?;
+    let method = text(field.child_by_field_name("field")?, source);
+    if !matches!(method, "unwrap_or" | "unwrap_or_else") {
+        return None;
+    }
+    let call = field
+        .parent()
+        .filter(|parent| parent.kind() == "call_expression")?;
+    first_string(call.child_by_field_name("arguments")?, source)
+}
+
+fn python_read(node: Node, source: &[u8]) -> Option<(String, Option<String>)> {
+    match node.kind() {
+        "subscript" => {
+            let value = text(node.child_by_field_name("value")?, source);
+            if !matches!(value, "os.environ" | "environ") {
+                return None;
+            }
+            let name = string_value(node.child_by_field_name("subscript")?, source)?;
+            Some((name, None))
+        }
+        "call" => {
+            let function = text(node.child_by_field_name("function")?, source);
+            if !matches!(
+                function,
+                "os.getenv" | "getenv" | "os.environ.get" | "environ.get"
+            ) {
+                return None;
+            }
+            let arguments = node.child_by_field_name("arguments")?;
+            let name = nth_string_arg(arguments, 0, source)?;
+            let mut cursor = arguments.walk();
+            let default = arguments
+                .named_children(&mut cursor)
+                .enumerate()
+                .find_map(|(idx, arg)| match arg.kind() {
+                    "keyword_argument" => arg
+                        .child_by_field_name("name")
+                        .filter(|key| text(*key, source) == "default")
+                        .and_then(|_| arg.child_by_field_name("value")),
+                    _ if idx == 1 => Some(arg),
+                    _ => None,
+                })
+                .and_then(|value| literal_value(value, source));
+            Some((name, default))
+        }
+        _ => None,
+    }
+}
+
+/// Objects whose properties are environment variables in JavaScript.
+fn is_js_env_object(node: Node, source: &[u8]) -> bool {
+    matches!(text(node, source), "process.env" | "import.meta.env")
+}
+
+fn js_read(node: Node, source: &[u8]) -> Option<(String, Option<String>)> {
+    let name = match node.kind() {
+        "member_expression" => {
+            if !is_js_env_object(node.child_by_field_name("object")?, source) {
+                return None;
+            }
+            text(node.child_by_field_name("property")?, source).to_string()
+        }
+        "subscript_expression" => {
+            if !is_js_env_object(node.child_by_field_name("object")?, source) {
+                return None;
+            }
+            string_value(node.child_by_field_name("index")?, source)?
+        }
+        _ => return None,
+    };
+    let default = node
+        .parent()
+        .filter(|parent| parent.kind() == "binary_expression")
+        .filter(|parent| parent.child_by_field_name("left") == Some(node))
+        .filter(|parent| {
+            parent
+                .child_by_field_name("operator")
+                .is_some_and(|op| matches!(text(op, source), "||" | "??"))
+        })
+        .and_then(|parent| parent.child_by_field_name("right"))
+        .and_then(|right| literal_value(right, source));
+    Some((name, default))
+}
+
+/// `const { PORT = "3000", HOST } = process.env`.
+fn js_destructured(node: Node, source: &[u8], reads: &mut Vec<FoundRead>) {
+    let (Some(pattern), Some(value)) = (
+        node.child_by_field_name("name")
+            .filter(|name| name.kind() == "object_pattern"),
+        node.child_by_field_name("value"),
+    ) else {
+        return;
+    };
+    if !is_js_env_object(value, source) {
+        return;
+    }
+    let mut cursor = pattern.walk();
+    for property in pattern.named_children(&mut cursor) {
+        let (key, default) = match property.kind() {
+            "shorthand_property_identifier_pattern" => (property, None),
+            "object_assignment_pattern" => {
+                let Some(left) = property.child_by_field_name("left") else {
+                    continue;
+                };
+                let default = property
+                    .child_by_field_name("right")
+                    .and_then(|right| literal_value(right, source));
+                (left, default)
+            }
+            "pair_pattern" => match property.child_by_field_name("key") {
+                Some(key) => (key, None),
+                None => continue,
+            },
+            _ => continue,
+        };
+        reads.push(FoundRead {
+            name: text(key, source).to_string(),
+            line: key.start_position().row + 1,
+            column: key.start_position().column + 1,
+            default,
+        });
+    }
+}
+
+fn java_read(node: Node, source: &[u8]) -> Option<(String, Option<String>)> {
+    if node.kind() != "method_invocation" {
+        return None;
+    }
+    let object = text(node.child_by_field_name("object")?, source);
+    let method = text(node.child_by_field_name("name")?, source);
+    if !matches!(object, "System" | "java.lang.System") || method != "getenv" {
+        return None;
+    }
+    let name = nth_string_arg(node.child_by_field_name("arguments")?, 0, source)?;
+    Some((name, None))
+}
+
+fn ruby_read(node: Node, source: &[u8]) -> Option<(String, Option<String>)> {
+    match node.kind() {
+        "element_reference" => {
+            if text(node.child_by_field_name("object")?, source) != "ENV" {
+                return None;
+            }
+            let mut cursor = node.walk();
+            let key = node.named_children(&mut cursor).nth(1)?;
+            Some((string_value(key, source)?, None))
+        }
+        "call" => {
+            let receiver = text(node.child_by_field_name("receiver")?, source);
+            let method = text(node.child_by_field_name("method")?, source);
+            if receiver != "ENV" || !matches!(method, "fetch" | "[]") {
+                return None;
+            }
+            let arguments = node.child_by_field_name("arguments")?;
+            let name = nth_string_arg(arguments, 0, source)?;
+            let mut cursor = arguments.walk();
+            let default = arguments
+                .named_children(&mut cursor)
+                .nth(1)
+                .and_then(|value| literal_value(value, source));
+            Some((name, default))
+        }
+        _ => None,
+    }
+}
+
+/// The value of a string literal without prefixes and quotes, or `None`
+/// when it interpolates.
+fn string_value(node: Node, source: &[u8]) -> Option<String> {
+    if !is_string(node) {
+        return None;
+    }
+    let mut cursor = node.walk();
+    if node
+        .named_children(&mut cursor)
+        .any(|child| matches!(child.kind(), "interpolation" | "template_substitution"))
+    {
+        return None;
+    }
+    let text = text(node, source)
+        .trim_start_matches(|ch: char| ch.is_ascii_alphabetic())
+        .trim_matches('#');
+    let quote = text.chars().next()?;
+    if !matches!(quote, '"' | '\'' | '`') {
+        return None;
+    }
+    let value = text.strip_prefix(quote)?.strip_suffix(quote)?;
+    Some(value.trim_matches(quote).to_string())
+}
+
+/// A default written as a literal: a string's value, or a number, boolean,
+/// or `None`/`null` as written.
+fn literal_value(node: Node, source: &[u8]) -> Option<String> {
+    if is_string(node) {
+        return string_value(node, source);
+    }
+    matches!(
+        node.kind(),
+        "integer" | "float" | "number" | "true" | "false" | "none" | "null" | "nil"
+    )
+    .then(|| text(node, source).to_string())
+}
+
+/// The `idx`th argument, when it is a plain string.
+fn nth_string_arg(arguments: Node, idx: usize, source: &[u8]) -> Option<String> {
+    let mut cursor = arguments.walk();
+    let arg = arguments.named_children(&mut cursor).nth(idx)?;
+    string_value(arg, source)
+}
+
+/// First string anywhere under `node`.
+fn first_string(node: Node, source: &[u8]) -> Option<String> {
+    if is_string(node) {
+        return string_value(node, source);
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|child| first_string(child, source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reads(content: &str, language: &str) -> Vec<String> {
+        env_reads(content, language)
+            .into_iter()
+            .map(|read| match read.default {
+                Some(default) => format!("{}={}", read.name, default),
+                None => read.name,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_and_literal_defaults_per_language() {
+        let rust = "// reads SKIPPED from env\nfn main() {\n    let port = std::env::var(\"PORT\").unwrap_or(\"8080\".to_string());\n    let home = env::var_os(\"HOME\");\n    let token = option_env!(\"TOKEN\").unwrap_or(\"dev\");\n    let name = std::env::var(key);\n}\n";
+        assert_eq!(reads(rust, "rust"), ["PORT=8080", "HOME", "TOKEN=dev"]);
+
+        let python = "import os\nDEBUG = os.environ.get(\"DEBUG\", \"0\")\nDB = os.environ[\"DATABASE_URL\"]\nLEVEL = os.getenv(\"LEVEL\", default=\"info\")\nNAME = os.getenv(f\"{prefix}_NAME\")\n";
+        assert_eq!(
+            reads(python, "python"),
+            ["DEBUG=0", "DATABASE_URL", "LEVEL=info"]
+        );
+
+        let js = "const port = process.env.PORT || 3000;\nconst url = process.env['API_URL'] ?? 'http://localhost';\nconst { REGION = 'us', STAGE } = process.env;\n";
+        assert_eq!(
+            reads(js, "javascript"),
+            [
+                "PORT=3000",
+                "API_URL=http://localhost",
+                "REGION=us",
+                "STAGE"
+            ]
+        );
+
+        let go = "package main\nimport \"os\"\nfunc main() {\n\t_ = os.Getenv(\"GOPATH\")\n\t_, _ = os.LookupEnv(`TERM`)\n}\n";
+        assert_eq!(reads(go, "go"), ["GOPATH", "TERM"]);
+
+        let java = "class A { String h = System.getenv(\"JAVA_HOME\"); }\n";
+        assert_eq!(reads(java, "java"), ["JAVA_HOME"]);
+
+        let ruby = "key = ENV[\"SECRET_KEY\"]\nenv = ENV.fetch(\"RACK_ENV\", \"development\")\n";
+        assert_eq!(reads(ruby, "ruby"), ["SECRET_KEY", "RACK_ENV=development"]);
+
+        let c = "#include <stdlib.h>\nint main() { char *p = getenv(\"PATH\"); return 0; }\n";
+        assert_eq!(reads(c, "c"), ["PATH"]);
+    }
+}
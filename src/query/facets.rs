@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Facet counts for `cgrep --format json2 search`.
+//!
+//! Results are counted by language, by directory (first two levels), and by
+//! document type, so a caller can offer "narrow to rust" or "narrow to
+//! src/api" refinements without running a second query. Counts come from the
+//! retrieved results before output budgets drop any of them.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+use crate::indexer::scanner::detect_language;
+use crate::query::search::SearchResult;
+use cgrep::paths;
+
+/// Directory levels kept in the `directories` facet.
+const DIRECTORY_DEPTH: usize = 2;
+
+/// Counts of results per facet value, most frequent first.
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct SearchFacets {
+    pub languages: Vec<FacetCount>,
+    pub directories: Vec<FacetCount>,
+    pub doc_types: Vec<FacetCount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct FacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+pub(crate) fn facet_counts(results: &[SearchResult]) -> SearchFacets {
+    let mut languages = HashMap::new();
+    let mut directories = HashMap::new();
+    let mut doc_types = HashMap::new();
+    for result in results {
+        let decoded = paths::decode_path(&result.path);
+        let path = decoded.as_path();
+        let language = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(detect_language)
+            .unwrap_or_else(|| "other".to_string());
+        *languages.entry(language).or_insert(0) += 1;
+        *directories.entry(directory_facet(path)).or_insert(0) += 1;
+        *doc_types.entry(doc_type(result).to_string()).or_insert(0) += 1;
+    }
+    SearchFacets {
+        languages: sorted(languages),
+        directories: sorted(directories),
+        doc_types: sorted(doc_types),
+    }
+}
+
+/// Leading directories of `path`'s parent, `.` for top-level files.
+fn directory_facet(path: &Path) -> String {
+    let parts: Vec<String> = path
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .take(DIRECTORY_DEPTH)
+        .collect();
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// Symbol documents carry their line span; file (and region) hits do not.
+fn doc_type(result: &SearchResult) -> &'static str {
+    if result.chunk_start.is_some() {
+        "symbol"
+    } else {
+        "file"
+    }
+}
+
+fn sorted(counts: HashMap<String, usize>) -> Vec<FacetCount> {
+    let mut facets: Vec<FacetCount> = counts
+        .into_iter()
+        .map(|(value, count)| FacetCount { value, count })
+        .collect();
+    facets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    facets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directories_keep_two_levels() {
+        assert_eq!(
+            directory_facet(Path::new("src/api/v1/routes.rs")),
+            "src/api"
+        );
+        assert_eq!(directory_facet(Path::new("src/lib.rs")), "src");
+        assert_eq!(directory_facet(Path::new("README.md")), ".");
+        assert_eq!(directory_facet(Path::new("../shared/util.go")), "shared");
+    }
+}
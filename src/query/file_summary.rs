@@ -0,0 +1,364 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep search --summarize-files`: one entry per file instead of one per
+//! match.
+//!
+//! Retrieval still picks the files, but index search keeps only a couple of
+//! results per file, so each file is read again and every line matching the
+//! query is counted. Matched lines are merged into ranges and mapped to the
+//! innermost symbol around them.
+
+use anyhow::Result;
+use colored::Colorize;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::detect_language;
+use crate::parser::symbols::SymbolExtractor;
+use crate::query::search::SearchResult;
+use crate::query::trace::innermost_symbol;
+use cgrep::encoding;
+use cgrep::output::{
+    colorize_line_num, colorize_path, print_github_annotations, print_gitlab_report, print_json,
+    print_quickfix, Annotation, AnnotationLevel, QuickfixEntry,
+};
+use cgrep::paths;
+
+/// Aggregated matches in one file.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct FileSummary {
+    pub path: String,
+    /// Lines matching the query, plus retrieved lines the query text misses
+    /// (semantic hits).
+    pub match_count: usize,
+    /// Best score among the file's retrieved results.
+    pub score: f32,
+    /// Innermost symbols around matched lines, in line order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<String>,
+    /// Inclusive `[start, end]` ranges of matched lines, adjacent lines merged.
+    pub line_ranges: Vec<(usize, usize)>,
+    /// Ids of the retrieved results in this file, for `agent expand`.
+    pub result_ids: Vec<String>,
+}
+
+/// How a line is tested against the query; mirrors the quickfix column
+/// search (regex, or any whitespace-separated term).
+pub(crate) struct LineMatcher<'a> {
+    regex: Option<&'a Regex>,
+    terms: Vec<String>,
+    case_sensitive: bool,
+}
+
+impl<'a> LineMatcher<'a> {
+    pub(crate) fn new(query: &str, regex: Option<&'a Regex>, case_sensitive: bool) -> Self {
+        let terms = query
+            .split_whitespace()
+            .map(|term| {
+                if case_sensitive {
+                    term.to_string()
+                } else {
+                    term.to_lowercase()
+                }
+            })
+            .collect();
+        Self {
+            regex,
+            terms,
+            case_sensitive,
+        }
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        if let Some(re) = self.regex {
+            return re.is_match(line);
+        }
+        if self.case_sensitive {
+            self.terms.iter().any(|term| line.contains(term.as_str()))
+        } else {
+            let lower = line.to_lowercase();
+            self.terms.iter().any(|term| lower.contains(term.as_str()))
+        }
+    }
+}
+
+/// One summary per file in `results`, ordered by the file's best-ranked result.
+pub(crate) fn summarize_files(
+    results: &[SearchResult],
+    roots: &[&Path],
+    matcher: &LineMatcher<'_>,
+) -> Vec<FileSummary> {
+    let mut order: Vec<&str> = Vec::new();
+    let mut by_path: HashMap<&str, Vec<&SearchResult>> = HashMap::new();
+    for result in results {
+        by_path
+            .entry(result.path.as_str())
+            .or_insert_with(|| {
+                order.push(result.path.as_str());
+                Vec::new()
+            })
+            .push(result);
+    }
+
+    let extractor = SymbolExtractor::new();
+    order
+        .into_iter()
+        .map(|path| summarize_file(path, &by_path[path], roots, matcher, &extractor))
+        .collect()
+}
+
+fn summarize_file(
+    path: &str,
+    hits: &[&SearchResult],
+    roots: &[&Path],
+    matcher: &LineMatcher<'_>,
+    extractor: &SymbolExtractor,
+) -> FileSummary {
+    let decoded = paths::decode_path(path);
+    let content = roots
+        .iter()
+        .map(|root| root.join(&decoded))
+        .find(|candidate| candidate.is_file())
+        .and_then(|full_path| encoding::read_text_file(&full_path).ok().flatten())
+        .map(|decoded| decoded.text.into_owned());
+
+    let mut lines: BTreeSet<usize> = hits.iter().filter_map(|hit| hit.line).collect();
+    let mut ranges: Vec<(usize, usize)> = hits
+        .iter()
+        .filter_map(|hit| {
+            let start = hit.chunk_start.map(|s| s as usize).or(hit.line)?;
+            let end = hit.chunk_end.map(|e| e as usize).or(hit.line)?;
+            Some((start, end.max(start)))
+        })
+        .collect();
+    let mut symbols = Vec::new();
+    if let Some(content) = &content {
+        for (idx, line) in content.lines().enumerate() {
+            if matcher.is_match(line) {
+                lines.insert(idx + 1);
+            }
+        }
+        let parsed = Path::new(&decoded)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(detect_language)
+            .and_then(|language| extractor.extract(content, &language).ok())
+            .unwrap_or_default();
+        for &line in &lines {
+            if let Some(symbol) = innermost_symbol(&parsed, line) {
+                if !symbols.contains(&symbol.name) {
+                    symbols.push(symbol.name.clone());
+                }
+            }
+        }
+    }
+    ranges.extend(lines.iter().map(|&line| (line, line)));
+
+    FileSummary {
+        path: path.to_string(),
+        match_count: lines.len(),
+        score: hits.iter().map(|hit| hit.score).fold(f32::MIN, f32::max),
+        symbols,
+        line_ranges: merge_ranges(ranges),
+        result_ids: hits
+            .iter()
+            .filter_map(|hit| hit.result_id.clone())
+            .collect(),
+    }
+}
+
+/// Sort and merge overlapping or adjacent inclusive ranges.
+fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn format_ranges(ranges: &[(usize, usize)]) -> String {
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Print summaries for every format except json2, which embeds them in the
+/// search payload.
+pub(crate) fn print_file_summaries(
+    files: &[FileSummary],
+    query: &str,
+    format: OutputFormat,
+    compact: bool,
+    use_color: bool,
+) -> Result<()> {
+    let first_line = |file: &FileSummary| file.line_ranges.first().map(|range| range.0);
+    let messages: Vec<String> = files
+        .iter()
+        .map(|file| {
+            let mut message = format!(
+                "{} matches, lines {}",
+                file.match_count,
+                format_ranges(&file.line_ranges)
+            );
+            if !file.symbols.is_empty() {
+                message.push_str(&format!("; symbols: {}", file.symbols.join(", ")));
+            }
+            message
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&files, compact)?,
+        OutputFormat::Quickfix => {
+            let entries: Vec<QuickfixEntry<'_>> = files
+                .iter()
+                .zip(&messages)
+                .map(|(file, message)| QuickfixEntry {
+                    path: &file.path,
+                    line: first_line(file).unwrap_or(1),
+                    column: 1,
+                    text: message,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Github | OutputFormat::Gitlab => {
+            let title = format!("cgrep: {query}");
+            let annotations: Vec<Annotation<'_>> = files
+                .iter()
+                .zip(&messages)
+                .map(|(file, message)| Annotation {
+                    path: &file.path,
+                    line: first_line(file),
+                    level: AnnotationLevel::Warning,
+                    title: &title,
+                    message,
+                    id: file.result_ids.first().map(String::as_str),
+                })
+                .collect();
+            if format == OutputFormat::Github {
+                print_github_annotations(&annotations);
+            } else {
+                print_gitlab_report(&annotations, compact)?;
+            }
+        }
+        OutputFormat::Text | OutputFormat::Sarif => {
+            if files.is_empty() {
+                if use_color {
+                    println!("{} No results found for: {}", "✗".red(), query.yellow());
+                } else {
+                    println!("No results found for: {}", query);
+                }
+                return Ok(());
+            }
+            for file in files {
+                let ranges = file
+                    .line_ranges
+                    .iter()
+                    .map(|&(start, end)| {
+                        if start == end {
+                            colorize_line_num(start, use_color)
+                        } else {
+                            format!(
+                                "{}-{}",
+                                colorize_line_num(start, use_color),
+                                colorize_line_num(end, use_color)
+                            )
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!(
+                    "{}  {} matches  lines {}",
+                    colorize_path(&file.path, use_color),
+                    file.match_count,
+                    ranges
+                );
+                if !file.symbols.is_empty() {
+                    println!("    symbols: {}", file.symbols.join(", "));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn hit(path: &str, line: usize, id: &str) -> SearchResult {
+        SearchResult {
+            path: path.to_string(),
+            score: line as f32,
+            snippet: String::new(),
+            line: Some(line),
+            context_before: Vec::new(),
+            context_after: Vec::new(),
+            text_score: None,
+            vector_score: None,
+            hybrid_score: None,
+            result_id: Some(id.to_string()),
+            chunk_start: None,
+            chunk_end: None,
+            explain: None,
+            linguist: None,
+            stale: false,
+            column: None,
+            end_column: None,
+            match_bytes: None,
+            chunk_bytes: None,
+            symbol_header: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ranges_merge_when_overlapping_or_adjacent() {
+        assert_eq!(
+            merge_ranges(vec![(10, 12), (3, 3), (4, 4), (11, 15), (20, 20)]),
+            [(3, 4), (10, 15), (20, 20)]
+        );
+        assert_eq!(format_ranges(&[(3, 4), (20, 20)]), "3-4, 20");
+    }
+
+    #[test]
+    fn summary_counts_every_matching_line_and_names_symbols() {
+        let dir = TempDir::new().expect("tempdir");
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "fn load() {\n    retry();\n    retry();\n}\n\nfn save() {\n    Retry::once();\n}\n",
+        )
+        .expect("write");
+        let results = vec![hit("lib.rs", 7, "b"), hit("lib.rs", 2, "a")];
+
+        let files = summarize_files(
+            &results,
+            &[dir.path()],
+            &LineMatcher::new("retry", None, false),
+        );
+
+        assert_eq!(files.len(), 1);
+        let file = &files[0];
+        assert_eq!(file.match_count, 3);
+        assert_eq!(file.line_ranges, [(2, 3), (7, 7)]);
+        assert_eq!(file.symbols, ["load", "save"]);
+        assert_eq!(file.result_ids, ["b", "a"]);
+        assert_eq!(file.score, 7.0);
+    }
+}
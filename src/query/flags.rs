@@ -0,0 +1,384 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep flags`: inventory of feature flag checks.
+//!
+//! Flags are found line by line with one regex per detector; the `flag`
+//! group (or the first group) captures the key. Built-in detectors:
+//!
+//! - `launchdarkly`: `variation("key", ...)`, `boolVariation`,
+//!   `BoolVariation`, `variationDetail`, and the other typed variants.
+//! - `cargo-feature`: `feature = "name"` in Rust `cfg!`, `#[cfg(...)]`, and
+//!   `cfg_attr` lines.
+//!
+//! Repo-specific helpers are named regexes in `[flags.patterns]`. Every
+//! detector runs by default; `--pattern` picks detectors by name or adds a
+//! regex of its own. Comment lines are skipped, and in languages cgrep parses
+//! a built-in detector's key must be a string literal in the syntax tree, so a
+//! check named in a trailing or block comment is not reported.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use tree_sitter::Tree;
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::FileScanner;
+use crate::parser::ast::enclosing_string;
+use crate::parser::languages::LANGUAGES;
+use cgrep::config::Config;
+use cgrep::output::{
+    colorize_line_num, colorize_path, print_json, print_quickfix, use_colors, QuickfixEntry,
+};
+use cgrep::paths;
+use cgrep::utils::get_root_with_index;
+
+/// A detector that ships with cgrep.
+struct Builtin {
+    name: &'static str,
+    pattern: &'static str,
+    /// Only files of this language are searched.
+    language: Option<&'static str>,
+    /// Only lines containing this text are searched.
+    line_marker: Option<&'static str>,
+}
+
+const BUILTIN_DETECTORS: &[Builtin] = &[
+    Builtin {
+        name: "launchdarkly",
+        pattern: r#"\b(?:[a-z]+|Bool|String|Int|Float|Double|Json|JSON)?[vV]ariation(?:Detail)?\s*\(\s*["'`](?P<flag>[^"'`]+)["'`]"#,
+        language: None,
+        line_marker: None,
+    },
+    Builtin {
+        name: "cargo-feature",
+        pattern: r#"\bfeature\s*=\s*"(?P<flag>[^"]+)""#,
+        language: Some("rust"),
+        line_marker: Some("cfg"),
+    },
+];
+
+/// A flag detector.
+struct Detector {
+    name: String,
+    regex: Regex,
+    language: Option<&'static str>,
+    line_marker: Option<&'static str>,
+    /// Keys must be string literals in the syntax tree (built-ins only;
+    /// configured patterns may capture identifiers).
+    string_keys: bool,
+}
+
+impl Detector {
+    fn new(name: &str, pattern: &str) -> Result<Self> {
+        let regex =
+            Regex::new(pattern).with_context(|| format!("Invalid flag pattern `{name}`"))?;
+        Ok(Self {
+            name: name.to_string(),
+            regex,
+            language: None,
+            line_marker: None,
+            string_keys: false,
+        })
+    }
+
+    fn builtin(builtin: &Builtin) -> Result<Self> {
+        Ok(Self {
+            language: builtin.language,
+            line_marker: builtin.line_marker,
+            string_keys: true,
+            ..Self::new(builtin.name, builtin.pattern)?
+        })
+    }
+
+    /// Flag keys on one line, with their 1-based byte columns.
+    fn keys(&self, line: &str) -> Vec<(String, usize)> {
+        if self
+            .line_marker
+            .is_some_and(|marker| !line.contains(marker))
+        {
+            return Vec::new();
+        }
+        self.regex
+            .captures_iter(line)
+            .filter_map(|caps| {
+                let key = caps
+                    .name("flag")
+                    .or_else(|| caps.get(1))
+                    .or_else(|| caps.get(0))?;
+                (!key.as_str().is_empty()).then(|| (key.as_str().to_string(), key.start() + 1))
+            })
+            .collect()
+    }
+}
+
+/// One check of a flag.
+#[derive(Debug, Serialize)]
+struct FlagUse {
+    path: String,
+    line: usize,
+    column: usize,
+    code: String,
+}
+
+/// Every check of one flag.
+#[derive(Debug, Serialize)]
+struct Flag {
+    flag: String,
+    /// Detectors that found the flag.
+    sources: Vec<String>,
+    /// Files checking the flag.
+    files: usize,
+    uses: Vec<FlagUse>,
+}
+
+/// Run the flags command.
+pub fn run(
+    flag: Option<&str>,
+    path: Option<&str>,
+    patterns: &[String],
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let search_root = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => paths::canonicalize(&std::env::current_dir()?)?,
+    };
+    let config = Config::load_for_dir(get_root_with_index(&search_root));
+    let detectors = detectors(&config.flags.patterns, patterns)?;
+
+    let mut by_flag: BTreeMap<String, (BTreeSet<String>, Vec<FlagUse>)> = BTreeMap::new();
+    for file in FileScanner::new(&search_root).scan()? {
+        let language = file.language.as_deref();
+        let active: Vec<&Detector> = detectors
+            .iter()
+            .filter(|detector| detector.language.is_none() || detector.language == language)
+            .collect();
+        if active.is_empty() {
+            continue;
+        }
+        let rel_path = paths::relative_display(&file.path, &search_root)
+            .unwrap_or_else(|| file.path.display().to_string());
+        let mut tree: Option<Option<Tree>> = None;
+        let mut line_start = 0;
+        for (idx, raw_line) in file.content.split_inclusive('\n').enumerate() {
+            let offset = line_start;
+            line_start += raw_line.len();
+            let line = raw_line.trim_end_matches(['\n', '\r']);
+            if is_comment_line(line) {
+                continue;
+            }
+            for detector in &active {
+                for (key, column) in detector.keys(line) {
+                    if flag.is_some_and(|flag| flag != key) {
+                        continue;
+                    }
+                    if detector.string_keys {
+                        let tree = tree.get_or_insert_with(|| parse(&file.content, language));
+                        let start = offset + column - 1;
+                        if tree
+                            .as_ref()
+                            .is_some_and(|tree| !in_string_literal(tree, start, start + key.len()))
+                        {
+                            continue;
+                        }
+                    }
+                    let (sources, uses) = by_flag.entry(key).or_default();
+                    sources.insert(detector.name.clone());
+                    uses.push(FlagUse {
+                        path: rel_path.clone(),
+                        line: idx + 1,
+                        column,
+                        code: line.trim().to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    let flags: Vec<Flag> = by_flag
+        .into_iter()
+        .map(|(flag, (sources, mut uses))| {
+            uses.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+            uses.dedup_by(|a, b| a.path == b.path && a.line == b.line && a.column == b.column);
+            let files = uses
+                .iter()
+                .map(|flag_use| flag_use.path.as_str())
+                .collect::<BTreeSet<_>>()
+                .len();
+            Flag {
+                flag,
+                sources: sources.into_iter().collect(),
+                files,
+                uses,
+            }
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&flags, compact)?,
+        OutputFormat::Quickfix => {
+            let entries: Vec<QuickfixEntry<'_>> = flags
+                .iter()
+                .flat_map(|flag| {
+                    flag.uses.iter().map(|flag_use| QuickfixEntry {
+                        path: &flag_use.path,
+                        line: flag_use.line,
+                        column: flag_use.column,
+                        text: &flag.flag,
+                    })
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            let use_color = use_colors() && format == OutputFormat::Text;
+            if flags.is_empty() {
+                if use_color {
+                    println!("{} No feature flag checks found", "✗".red());
+                } else {
+                    println!("No feature flag checks found");
+                }
+                return Ok(());
+            }
+            for (idx, flag) in flags.iter().enumerate() {
+                if idx > 0 {
+                    println!();
+                }
+                let summary = format!(
+                    "({} uses in {} files; {})",
+                    flag.uses.len(),
+                    flag.files,
+                    flag.sources.join(", ")
+                );
+                if use_color {
+                    println!("{} {}", flag.flag.cyan().bold(), summary.dimmed());
+                } else {
+                    println!("{} {}", flag.flag, summary);
+                }
+                for flag_use in &flag.uses {
+                    println!(
+                        "  {}:{}: {}",
+                        colorize_path(&flag_use.path, use_color),
+                        colorize_line_num(flag_use.line, use_color),
+                        flag_use.code
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Detectors to run: every built-in and configured one, or those `requested`
+/// names, with any other requested value compiled as a regex.
+fn detectors(configured: &BTreeMap<String, String>, requested: &[String]) -> Result<Vec<Detector>> {
+    if requested.is_empty() {
+        let mut detectors: Vec<Detector> = BUILTIN_DETECTORS
+            .iter()
+            .map(Detector::builtin)
+            .collect::<Result<_>>()?;
+        for (name, pattern) in configured {
+            detectors.push(Detector::new(name, pattern)?);
+        }
+        return Ok(detectors);
+    }
+    let mut detectors = Vec::new();
+    for value in requested {
+        let builtin = BUILTIN_DETECTORS
+            .iter()
+            .find(|builtin| builtin.name == value);
+        let detector = match (configured.get(value), builtin) {
+            (Some(pattern), _) => Detector::new(value, pattern)?,
+            (None, Some(builtin)) => Detector::builtin(builtin)?,
+            (None, None) => Detector::new("pattern", value)
+                .with_context(|| format!("`{value}` names no flag detector"))?,
+        };
+        detectors.push(detector);
+    }
+    Ok(detectors)
+}
+
+/// Syntax tree for `content`, when cgrep has a grammar for `language`.
+fn parse(content: &str, language: Option<&str>) -> Option<Tree> {
+    LANGUAGES.parser(language?)?.parse(content, None)
+}
+
+/// Whether `start..end` lies inside a string literal of `tree`.
+fn in_string_literal(tree: &Tree, start: usize, end: usize) -> bool {
+    tree.root_node()
+        .descendant_for_byte_range(start, end)
+        .and_then(enclosing_string)
+        .is_some()
+}
+
+/// Whether a line holds only a comment (`//`, `/*`, `*`, `#`, `--`).
+/// Rust attributes (`#[...]`) are code.
+fn is_comment_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    ["//", "/*", "* ", "--"]
+        .iter()
+        .any(|marker| trimmed.starts_with(marker))
+        || trimmed == "*"
+        || (trimmed.starts_with('#') && !trimmed.starts_with("#[") && !trimmed.starts_with("#!["))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(detectors: &[Detector], line: &str) -> Vec<String> {
+        detectors
+            .iter()
+            .flat_map(|detector| detector.keys(line))
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    #[test]
+    fn builtin_and_configured_detectors_capture_flag_keys() {
+        let configured = BTreeMap::from([(
+            "growthbook".to_string(),
+            r#"gb\.isOn\("(?P<flag>[^"]+)"\)"#.to_string(),
+        )]);
+        let all = detectors(&configured, &[]).unwrap();
+        assert_eq!(
+            keys(&all, "if (ld.variation('new-checkout', user, false)) {"),
+            ["new-checkout"]
+        );
+        assert_eq!(
+            keys(
+                &all,
+                "enabled, _ := client.BoolVariation(\"dark-mode\", ctx, false)"
+            ),
+            ["dark-mode"]
+        );
+        assert_eq!(
+            keys(
+                &all,
+                "#[cfg(all(feature = \"otel\", not(feature = \"lite\")))]"
+            ),
+            ["otel", "lite"]
+        );
+        assert!(keys(&all, "let feature = \"otel\";").is_empty());
+        assert_eq!(keys(&all, "if gb.isOn(\"beta\") {"), ["beta"]);
+
+        let only_custom = detectors(&configured, &["growthbook".to_string()]).unwrap();
+        assert!(keys(&only_custom, "ld.variation('new-checkout')").is_empty());
+        let ad_hoc = detectors(&configured, &[r"isEnabled\((\w+)\)".to_string()]).unwrap();
+        assert_eq!(keys(&ad_hoc, "if isEnabled(FAST_PATH) {"), ["FAST_PATH"]);
+        assert!(detectors(&configured, &["(".to_string()]).is_err());
+    }
+
+    #[test]
+    fn comment_lines_are_skipped_but_attributes_are_not() {
+        assert!(is_comment_line("  // ld.variation('old-flag')"));
+        assert!(is_comment_line("# client.variation(\"old-flag\")"));
+        assert!(!is_comment_line("#[cfg(feature = \"otel\")]"));
+        assert!(!is_comment_line("let x = 1; // note"));
+    }
+}
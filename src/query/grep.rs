@@ -0,0 +1,380 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep grep`: ripgrep-compatible line search
+//!
+//! Accepts the common ripgrep flags and prints ripgrep's plain (uncolored)
+//! output format, so scripts and editor plugins can swap binaries without
+//! changing invocations. Exit status follows ripgrep: 0 when something
+//! matched, 1 when nothing did.
+
+use anyhow::{bail, Context, Result};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use regex::{Regex, RegexBuilder};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use cgrep::filters::matches_file_type;
+
+/// Bytes inspected for NUL when deciding whether a file is binary.
+const BINARY_PROBE_BYTES: usize = 8 * 1024;
+
+/// Flags accepted by `cgrep grep`, mirroring ripgrep's names.
+#[derive(Debug, Default, Clone)]
+pub struct GrepOptions {
+    pub patterns: Vec<String>,
+    pub paths: Vec<String>,
+    pub ignore_case: bool,
+    pub smart_case: bool,
+    pub word_regexp: bool,
+    pub fixed_strings: bool,
+    pub globs: Vec<String>,
+    pub types: Vec<String>,
+    pub types_not: Vec<String>,
+    pub after_context: usize,
+    pub before_context: usize,
+    pub files_with_matches: bool,
+    pub count: bool,
+    /// `Some(true)` for `-n`, `Some(false)` for `-N`; defaults to on for terminals.
+    pub line_number: Option<bool>,
+    /// `Some(true)` for `-H`, `Some(false)` for `-I`; defaults to on for multiple files.
+    pub with_filename: Option<bool>,
+    pub column: bool,
+    pub vimgrep: bool,
+    pub max_count: Option<usize>,
+    pub quiet: bool,
+    pub hidden: bool,
+    pub no_ignore: bool,
+    pub no_ignore_vcs: bool,
+    pub no_ignore_global: bool,
+}
+
+/// Split `cgrep grep` positionals into patterns and paths the way ripgrep does:
+/// without `-e`, the first positional is the pattern.
+pub fn split_positionals(
+    regexp: Vec<String>,
+    mut args: Vec<String>,
+) -> Result<(Vec<String>, Vec<String>)> {
+    if !regexp.is_empty() {
+        return Ok((regexp, args));
+    }
+    if args.is_empty() {
+        bail!("grep requires a pattern (usage: cgrep grep [OPTIONS] PATTERN [PATH]...)");
+    }
+    let pattern = args.remove(0);
+    Ok((vec![pattern], args))
+}
+
+/// Run the search; returns whether anything matched.
+pub fn run(options: &GrepOptions) -> Result<bool> {
+    let regex = build_regex(options)?;
+    let explicit_paths = !options.paths.is_empty();
+    let roots: Vec<PathBuf> = if explicit_paths {
+        options.paths.iter().map(PathBuf::from).collect()
+    } else {
+        vec![PathBuf::from(".")]
+    };
+
+    let with_filename = options
+        .with_filename
+        .unwrap_or(options.vimgrep || roots.len() > 1 || roots.iter().any(|root| root.is_dir()));
+    let line_number = options.vimgrep
+        || options
+            .line_number
+            .unwrap_or_else(|| io::stdout().is_terminal());
+
+    let stdout = io::stdout();
+    let mut printer = Printer {
+        out: io::BufWriter::new(stdout.lock()),
+        with_filename,
+        line_number,
+        printed_any_group: false,
+    };
+    let mut matched = false;
+
+    for root in &roots {
+        for path in files_under(root, options)? {
+            let display = display_path(&path, explicit_paths);
+            let Some(text) = read_text(&path) else {
+                continue;
+            };
+            let file_matched = match printer.search_file(&display, &text, &regex, options) {
+                Ok(file_matched) => file_matched,
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return Ok(true),
+                Err(err) => return Err(err).context("Failed to write output"),
+            };
+            matched |= file_matched;
+            if matched && options.quiet {
+                return Ok(true);
+            }
+        }
+    }
+
+    match printer.out.flush() {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => {
+            Err(err).context("Failed to write output")?
+        }
+        _ => {}
+    }
+    Ok(matched)
+}
+
+fn build_regex(options: &GrepOptions) -> Result<Regex> {
+    let alternatives: Vec<String> = options
+        .patterns
+        .iter()
+        .map(|pattern| {
+            let pattern = if options.fixed_strings {
+                regex::escape(pattern)
+            } else {
+                pattern.clone()
+            };
+            if options.word_regexp {
+                format!(r"\b(?:{pattern})\b")
+            } else {
+                format!("(?:{pattern})")
+            }
+        })
+        .collect();
+    let case_insensitive = options.ignore_case
+        || (options.smart_case
+            && options
+                .patterns
+                .iter()
+                .all(|pattern| !pattern.chars().any(char::is_uppercase)));
+    RegexBuilder::new(&alternatives.join("|"))
+        .case_insensitive(case_insensitive)
+        .build()
+        .context("Invalid pattern")
+}
+
+fn files_under(root: &Path, options: &GrepOptions) -> Result<Vec<PathBuf>> {
+    // Like ripgrep, explicitly named files are searched even if ignored.
+    if root.is_file() {
+        return Ok(vec![root.to_path_buf()]);
+    }
+    if !root.exists() {
+        bail!("{}: No such file or directory", root.display());
+    }
+
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(!options.hidden)
+        .ignore(!options.no_ignore)
+        .git_ignore(!options.no_ignore && !options.no_ignore_vcs)
+        .git_exclude(!options.no_ignore && !options.no_ignore_vcs)
+        .git_global(!options.no_ignore && !options.no_ignore_vcs && !options.no_ignore_global)
+        .parents(!options.no_ignore)
+        .sort_by_file_path(|a, b| a.cmp(b));
+    if !options.globs.is_empty() {
+        let mut overrides = OverrideBuilder::new(root);
+        for glob in &options.globs {
+            overrides
+                .add(glob)
+                .with_context(|| format!("Invalid glob: {glob}"))?;
+        }
+        builder.overrides(overrides.build()?);
+    }
+
+    let mut files = Vec::new();
+    for entry in builder.build() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let path = entry.into_path();
+        let path_str = path.to_string_lossy();
+        if !options.types.is_empty()
+            && !options
+                .types
+                .iter()
+                .any(|kind| matches_file_type(&path_str, Some(kind)))
+        {
+            continue;
+        }
+        if options
+            .types_not
+            .iter()
+            .any(|kind| matches_file_type(&path_str, Some(kind)))
+        {
+            continue;
+        }
+        files.push(path);
+    }
+    Ok(files)
+}
+
+fn display_path(path: &Path, explicit_paths: bool) -> String {
+    let raw = path.to_string_lossy();
+    if explicit_paths {
+        return raw.into_owned();
+    }
+    raw.strip_prefix("./").unwrap_or(&raw).to_string()
+}
+
+/// File contents as text, or `None` for unreadable and binary files.
+fn read_text(path: &Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    let probe = &bytes[..bytes.len().min(BINARY_PROBE_BYTES)];
+    if probe.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+struct Printer<W: Write> {
+    out: W,
+    with_filename: bool,
+    line_number: bool,
+    /// Whether a context group was printed yet, for `--` separators.
+    printed_any_group: bool,
+}
+
+impl<W: Write> Printer<W> {
+    fn search_file(
+        &mut self,
+        path: &str,
+        text: &str,
+        regex: &Regex,
+        options: &GrepOptions,
+    ) -> io::Result<bool> {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut matches: Vec<usize> = Vec::new();
+        for (idx, line) in lines.iter().enumerate() {
+            if options.max_count.is_some_and(|max| matches.len() >= max) {
+                break;
+            }
+            if regex.is_match(line) {
+                matches.push(idx);
+            }
+        }
+        if matches.is_empty() {
+            return Ok(false);
+        }
+        if options.quiet {
+            return Ok(true);
+        }
+
+        if options.files_with_matches {
+            writeln!(self.out, "{path}")?;
+            return Ok(true);
+        }
+        if options.count {
+            if self.with_filename {
+                writeln!(self.out, "{path}:{}", matches.len())?;
+            } else {
+                writeln!(self.out, "{}", matches.len())?;
+            }
+            return Ok(true);
+        }
+        if options.vimgrep {
+            for &idx in &matches {
+                for found in regex.find_iter(lines[idx]) {
+                    writeln!(
+                        self.out,
+                        "{path}:{}:{}:{}",
+                        idx + 1,
+                        found.start() + 1,
+                        lines[idx]
+                    )?;
+                }
+            }
+            return Ok(true);
+        }
+
+        let has_context = options.before_context > 0 || options.after_context > 0;
+        let mut last_printed: Option<usize> = None;
+        for &idx in &matches {
+            let start = idx.saturating_sub(options.before_context);
+            let end = (idx + options.after_context).min(lines.len() - 1);
+            let from = last_printed.map_or(start, |last| start.max(last + 1));
+            let contiguous = last_printed.is_some_and(|last| from == last + 1);
+            if has_context && self.printed_any_group && !contiguous {
+                writeln!(self.out, "--")?;
+            }
+            for (line_idx, line) in lines.iter().enumerate().take(end + 1).skip(from) {
+                let is_match = line_idx == idx || matches.binary_search(&line_idx).is_ok();
+                let column = if is_match && options.column {
+                    regex.find(line).map(|found| found.start() + 1)
+                } else {
+                    None
+                };
+                self.write_line(path, line_idx + 1, column, line, is_match)?;
+            }
+            if end >= from {
+                last_printed = Some(end);
+            }
+            self.printed_any_group = true;
+        }
+        Ok(true)
+    }
+
+    fn write_line(
+        &mut self,
+        path: &str,
+        line_number: usize,
+        column: Option<usize>,
+        line: &str,
+        is_match: bool,
+    ) -> io::Result<()> {
+        let sep = if is_match { ':' } else { '-' };
+        if self.with_filename {
+            write!(self.out, "{path}{sep}")?;
+        }
+        if self.line_number {
+            write!(self.out, "{line_number}{sep}")?;
+        }
+        if let Some(column) = column {
+            write!(self.out, "{column}{sep}")?;
+        }
+        writeln!(self.out, "{line}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(text: &str, options: &GrepOptions) -> String {
+        let regex = build_regex(options).expect("regex");
+        let mut printer = Printer {
+            out: Vec::new(),
+            with_filename: true,
+            line_number: true,
+            printed_any_group: false,
+        };
+        printer
+            .search_file("a.txt", text, &regex, options)
+            .expect("search");
+        String::from_utf8(printer.out).expect("utf8")
+    }
+
+    #[test]
+    fn context_lines_use_dash_separators_and_group_breaks() {
+        let options = GrepOptions {
+            patterns: vec!["hit".to_string()],
+            after_context: 1,
+            ..GrepOptions::default()
+        };
+        let out = render("hit one\nctx\nfar\nfar\nhit two\nhit three\n", &options);
+        assert_eq!(
+            out,
+            "a.txt:1:hit one\na.txt-2-ctx\n--\na.txt:5:hit two\na.txt:6:hit three\n"
+        );
+    }
+
+    #[test]
+    fn word_fixed_and_smart_case_patterns() {
+        let options = GrepOptions {
+            patterns: vec!["a.b".to_string()],
+            fixed_strings: true,
+            word_regexp: true,
+            smart_case: true,
+            count: true,
+            ..GrepOptions::default()
+        };
+        assert_eq!(render("A.B\naxb\nxa.by\n", &options), "a.txt:1\n");
+    }
+}
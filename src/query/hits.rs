@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Hit streaming for `cgrep nvim-rpc`.
+//!
+//! With `CGREP_STREAM_HITS=1`, `search` (keyword mode) and `symbols` print each
+//! hit as a `{"hit": ...}` line on stdout as soon as it is found, before the
+//! command's normal output. Hits come in discovery order, ahead of ranking and
+//! truncation, and an index search that falls back to a scan may repeat one.
+
+use serde::Serialize;
+use std::io::Write;
+use std::sync::OnceLock;
+
+/// Environment variable that turns hit streaming on for a child cgrep.
+pub const STREAM_ENV: &str = "CGREP_STREAM_HITS";
+
+/// Start of every streamed hit line.
+pub const HIT_PREFIX: &str = "{\"hit\":";
+
+#[derive(Serialize)]
+struct HitLine<'a, T: Serialize> {
+    hit: &'a T,
+}
+
+/// Returns true when hits should be streamed.
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| std::env::var(STREAM_ENV).is_ok_and(|value| value == "1"))
+}
+
+/// Print one hit line and flush it, so the reader sees it right away.
+pub fn emit<T: Serialize>(hit: &T) {
+    if !enabled() {
+        return;
+    }
+    let Ok(line) = serde_json::to_string(&HitLine { hit }) else {
+        return;
+    };
+    let mut out = std::io::stdout().lock();
+    let _ = writeln!(out, "{line}").and_then(|()| out.flush());
+}
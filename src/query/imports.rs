@@ -0,0 +1,749 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Import statements of a file, and `cgrep imports <file>`.
+//!
+//! [`extract_imports`] finds the module specifiers a file names in its
+//! import statements (`import`/`require`, Python `import`/`from`, Rust
+//! `use`/`mod`, Go `import`, C/C++ `#include`); `dependents` matches them
+//! against a target file. `cgrep imports` resolves each specifier to a file
+//! in the workspace, or to the external package it comes from.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::detect_language;
+use cgrep::encoding;
+use cgrep::output::{print_json, print_quickfix, QuickfixEntry};
+
+/// One module named by an import statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ImportStatement {
+    /// 1-based line of the statement
+    pub line: usize,
+    /// The statement's line, trimmed
+    pub text: String,
+    /// Module as written: `./util`, `os.path`, `crate::query::search`, ...
+    pub specifier: String,
+}
+
+impl ImportStatement {
+    /// Whether the specifier names the module `stem` itself, ignoring
+    /// leading `./`, `../`, `crate::`, and `self::`.
+    pub(crate) fn names_module(&self, stem: &str) -> bool {
+        let specifier = self.specifier.trim_start_matches(['.', '/']);
+        let specifier = specifier
+            .strip_prefix("crate::")
+            .or_else(|| specifier.strip_prefix("self::"))
+            .unwrap_or(specifier);
+        specifier == stem
+            || specifier
+                .strip_prefix(stem)
+                .is_some_and(|rest| rest.starts_with("::"))
+    }
+}
+
+/// Import syntax families; a file is read with the one for its language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Script,
+    Python,
+    Rust,
+    Go,
+    C,
+}
+
+impl Syntax {
+    const ALL: [Syntax; 5] = [
+        Syntax::Script,
+        Syntax::Python,
+        Syntax::Rust,
+        Syntax::Go,
+        Syntax::C,
+    ];
+
+    fn for_language(language: &str) -> Option<Self> {
+        match language {
+            "javascript" | "typescript" | "vue" | "svelte" => Some(Self::Script),
+            "python" => Some(Self::Python),
+            "rust" => Some(Self::Rust),
+            "go" => Some(Self::Go),
+            "c" | "cpp" => Some(Self::C),
+            _ => None,
+        }
+    }
+}
+
+static SCRIPT_IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:\bimport|\bfrom|\brequire)\s*\(?\s*['"]([^'"]+)['"]"#).expect("valid regex")
+});
+static PYTHON_FROM: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*from\s+([\w.]+)\s+import\b").expect("valid regex"));
+static PYTHON_IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*import\s+([\w.]+(?:\s+as\s+\w+)?(?:\s*,\s*[\w.]+(?:\s+as\s+\w+)?)*)")
+        .expect("valid regex")
+});
+static RUST_USE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(use|mod)\s+([^;{]*(?:\{[^;]*\})?)\s*;")
+        .expect("valid regex")
+});
+static RUST_USE_START: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?use\s").expect("valid regex"));
+static GO_IMPORT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*import\s+(?:[\w.]+\s+)?"([^"]+)""#).expect("valid regex"));
+static GO_BLOCK_LINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*(?:[\w.]+\s+)?"([^"]+)""#).expect("valid regex"));
+static C_INCLUDE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*#\s*include\s*([<"])([^>"]+)[>"]"#).expect("valid regex"));
+
+/// Import statements of `content`, read with the syntax of `language`, or of
+/// every supported language when it is unknown.
+pub(crate) fn extract_imports(content: &str, language: Option<&str>) -> Vec<ImportStatement> {
+    let syntaxes: Vec<Syntax> = match language.and_then(Syntax::for_language) {
+        Some(syntax) => vec![syntax],
+        None => Syntax::ALL.to_vec(),
+    };
+    let mut imports = Vec::new();
+    let mut in_go_block = false;
+    // A Rust `use` tree spanning lines, joined up to its `;`.
+    let mut rust_pending: Option<String> = None;
+    let mut rust_start = 0;
+    let lines: Vec<&str> = content.lines().collect();
+    for (index, &line) in lines.iter().enumerate() {
+        let mut specifiers = Vec::new();
+        let mut statement_line = index;
+        for syntax in &syntaxes {
+            match syntax {
+                Syntax::Script => specifiers.extend(
+                    SCRIPT_IMPORT
+                        .captures_iter(line)
+                        .map(|caps| caps[1].to_string()),
+                ),
+                Syntax::Python => {
+                    if let Some(caps) = PYTHON_FROM.captures(line) {
+                        specifiers.push(caps[1].to_string());
+                    } else if let Some(caps) = PYTHON_IMPORT.captures(line) {
+                        specifiers.extend(
+                            caps[1].split(',').filter_map(|name| {
+                                name.split_whitespace().next().map(str::to_string)
+                            }),
+                        );
+                    }
+                }
+                Syntax::Rust => {
+                    let joined = match rust_pending.take() {
+                        Some(mut pending) => {
+                            pending.push(' ');
+                            pending.push_str(line);
+                            statement_line = rust_start;
+                            pending
+                        }
+                        None if RUST_USE_START.is_match(line) && !line.contains(';') => {
+                            rust_start = index;
+                            line.to_string()
+                        }
+                        None => line.to_string(),
+                    };
+                    if !joined.contains(';') && RUST_USE_START.is_match(&joined) {
+                        rust_pending = Some(joined);
+                    } else if let Some(caps) = RUST_USE.captures(&joined) {
+                        let path = caps[2].split_whitespace().collect::<Vec<_>>().join(" ");
+                        if &caps[1] == "mod" {
+                            specifiers.push(format!("self::{path}"));
+                        } else {
+                            specifiers.extend(expand_use_tree(&path));
+                        }
+                    }
+                }
+                Syntax::Go => {
+                    let trimmed = line.trim();
+                    if in_go_block {
+                        if trimmed.starts_with(')') {
+                            in_go_block = false;
+                        } else if let Some(caps) = GO_BLOCK_LINE.captures(line) {
+                            specifiers.push(caps[1].to_string());
+                        }
+                    } else if trimmed.starts_with("import") && trimmed.ends_with('(') {
+                        in_go_block = true;
+                    } else if let Some(caps) = GO_IMPORT.captures(line) {
+                        specifiers.push(caps[1].to_string());
+                    }
+                }
+                Syntax::C => {
+                    if let Some(caps) = C_INCLUDE.captures(line) {
+                        specifiers.push(if &caps[1] == "<" {
+                            format!("<{}>", &caps[2])
+                        } else {
+                            caps[2].to_string()
+                        });
+                    }
+                }
+            }
+        }
+        let text = lines[statement_line].trim();
+        let mut seen = HashSet::new();
+        for specifier in specifiers {
+            if seen.insert(specifier.clone()) {
+                imports.push(ImportStatement {
+                    line: statement_line + 1,
+                    text: text.to_string(),
+                    specifier,
+                });
+            }
+        }
+    }
+    imports
+}
+
+/// `a::{b, c::D}` → `a::b`, `a::c::D`; `x as y` keeps `x`; globs drop `*`.
+fn expand_use_tree(path: &str) -> Vec<String> {
+    let path = path.trim().trim_end_matches("::*");
+    let Some(open) = path.find('{') else {
+        return vec![strip_alias(path).to_string()];
+    };
+    let prefix = path[..open].trim().trim_end_matches("::");
+    let inner = path[open + 1..].trim().trim_end_matches('}');
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, ch) in inner.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(&inner[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&inner[start..]);
+    items
+        .into_iter()
+        .filter(|item| !item.trim().is_empty())
+        .flat_map(|item| {
+            expand_use_tree(item).into_iter().map(|rest| {
+                if rest == "self" || rest.is_empty() {
+                    prefix.to_string()
+                } else if prefix.is_empty() {
+                    rest
+                } else {
+                    format!("{prefix}::{rest}")
+                }
+            })
+        })
+        .collect()
+}
+
+fn strip_alias(path: &str) -> &str {
+    path.split_once(" as ")
+        .map_or(path, |(name, _)| name)
+        .trim()
+}
+
+/// Where one import leads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Target {
+    /// A file or package directory in the workspace, relative to the root
+    Internal(String),
+    /// The package or crate an external import comes from
+    External(String),
+    /// A relative import whose file could not be found
+    Unresolved,
+}
+
+#[derive(Debug, Serialize)]
+struct InternalImport {
+    path: String,
+    line: usize,
+    specifier: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ExternalImport {
+    package: String,
+    line: usize,
+    specifier: String,
+}
+
+#[derive(Debug, Serialize)]
+struct UnresolvedImport {
+    line: usize,
+    specifier: String,
+}
+
+/// Imports report for JSON output
+#[derive(Debug, Serialize)]
+struct ImportsReport {
+    file: String,
+    internal: Vec<InternalImport>,
+    external: Vec<ExternalImport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unresolved: Vec<UnresolvedImport>,
+}
+
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "vue", "svelte"];
+
+/// Normalize `.` and `..` without touching the filesystem.
+fn clean_path(path: &Path) -> PathBuf {
+    let mut cleaned = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                cleaned.pop();
+            }
+            other => cleaned.push(other),
+        }
+    }
+    cleaned
+}
+
+fn first_file(candidates: impl IntoIterator<Item = PathBuf>) -> Option<PathBuf> {
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+struct Resolver<'a> {
+    root: &'a Path,
+    /// Absolute path of the importing file
+    file: &'a Path,
+}
+
+impl Resolver<'_> {
+    fn dir(&self) -> &Path {
+        self.file.parent().unwrap_or(self.root)
+    }
+
+    fn internal(&self, path: &Path) -> Target {
+        let relative = path.strip_prefix(self.root).unwrap_or(path);
+        Target::Internal(relative.to_string_lossy().replace('\\', "/"))
+    }
+
+    fn resolve(&self, syntax: Syntax, specifier: &str) -> Target {
+        match syntax {
+            Syntax::Script => self.resolve_script(specifier),
+            Syntax::Python => self.resolve_python(specifier),
+            Syntax::Rust => self.resolve_rust(specifier),
+            Syntax::Go => self.resolve_go(specifier),
+            Syntax::C => self.resolve_c(specifier),
+        }
+    }
+
+    fn resolve_script(&self, specifier: &str) -> Target {
+        if !specifier.starts_with('.') && !specifier.starts_with('/') {
+            let mut parts = specifier.split('/');
+            let package = match parts.next() {
+                Some(scope) if scope.starts_with('@') => {
+                    format!("{scope}/{}", parts.next().unwrap_or_default())
+                }
+                Some(name) => name.to_string(),
+                None => specifier.to_string(),
+            };
+            return Target::External(package);
+        }
+        let base = clean_path(&self.dir().join(specifier));
+        let with_extensions = SCRIPT_EXTENSIONS.iter().map(|ext| {
+            let mut name = base.clone().into_os_string();
+            name.push(format!(".{ext}"));
+            PathBuf::from(name)
+        });
+        let index_files = SCRIPT_EXTENSIONS
+            .iter()
+            .map(|ext| base.join(format!("index.{ext}")));
+        first_file(
+            std::iter::once(base.clone())
+                .chain(with_extensions)
+                .chain(index_files),
+        )
+        .map_or(Target::Unresolved, |path| self.internal(&path))
+    }
+
+    fn resolve_python(&self, specifier: &str) -> Target {
+        let dots = specifier.chars().take_while(|ch| *ch == '.').count();
+        let module: PathBuf = specifier[dots..].split('.').collect();
+        let module_files = |base: &Path| {
+            let target = base.join(&module);
+            [target.with_extension("py"), target.join("__init__.py")]
+        };
+        if dots > 0 {
+            let mut base = self.dir().to_path_buf();
+            for _ in 1..dots {
+                base.pop();
+            }
+            return first_file(module_files(&base))
+                .map_or(Target::Unresolved, |path| self.internal(&path));
+        }
+        let bases = [
+            self.root.to_path_buf(),
+            self.root.join("src"),
+            self.dir().to_path_buf(),
+        ];
+        match first_file(bases.iter().flat_map(|base| module_files(base))) {
+            Some(path) => self.internal(&path),
+            None => Target::External(specifier.split('.').next().unwrap_or(specifier).to_string()),
+        }
+    }
+
+    /// The directory holding `self`'s child modules.
+    fn rust_module_dir(&self) -> PathBuf {
+        let dir = self.dir().to_path_buf();
+        match self.file.file_stem().and_then(|stem| stem.to_str()) {
+            Some("mod" | "lib" | "main") | None => dir,
+            Some(stem) => dir.join(stem),
+        }
+    }
+
+    /// Nearest ancestor `src` directory holding a crate root.
+    fn rust_crate_dir(&self) -> PathBuf {
+        self.dir()
+            .ancestors()
+            .take_while(|dir| dir.starts_with(self.root))
+            .find(|dir| dir.join("lib.rs").is_file() || dir.join("main.rs").is_file())
+            .unwrap_or_else(|| self.dir())
+            .to_path_buf()
+    }
+
+    fn resolve_rust(&self, specifier: &str) -> Target {
+        let segments: Vec<&str> = specifier.split("::").collect();
+        let (base, rest, local) = match segments.first().copied() {
+            Some("crate") => (self.rust_crate_dir(), &segments[1..], true),
+            Some("self") => (self.rust_module_dir(), &segments[1..], true),
+            Some("super") => {
+                let mut base = self.rust_module_dir();
+                let supers = segments.iter().take_while(|s| **s == "super").count();
+                for _ in 0..supers {
+                    base.pop();
+                }
+                (base, &segments[supers..], true)
+            }
+            // 2018 paths: a bare name may be a module of this crate.
+            _ => (self.rust_crate_dir(), &segments[..], false),
+        };
+        // The longest prefix of the path that is a module file.
+        for len in (1..=rest.len()).rev() {
+            let module: PathBuf = rest[..len].iter().collect();
+            let target = base.join(&module);
+            if let Some(path) = first_file([target.with_extension("rs"), target.join("mod.rs")]) {
+                return self.internal(&path);
+            }
+        }
+        if local {
+            // Items of the module itself: `use super::Config`, `use crate::run`.
+            let candidates = [
+                base.with_extension("rs"),
+                base.join("mod.rs"),
+                base.join("lib.rs"),
+                base.join("main.rs"),
+            ];
+            first_file(candidates).map_or(Target::Unresolved, |path| self.internal(&path))
+        } else {
+            Target::External(segments[0].to_string())
+        }
+    }
+
+    fn go_module(&self) -> Option<(PathBuf, String)> {
+        self.dir()
+            .ancestors()
+            .take_while(|dir| dir.starts_with(self.root))
+            .find_map(|dir| {
+                let raw = fs::read_to_string(dir.join("go.mod")).ok()?;
+                let module = raw
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix("module "))?;
+                Some((dir.to_path_buf(), module.trim().to_string()))
+            })
+    }
+
+    fn resolve_go(&self, specifier: &str) -> Target {
+        if let Some((dir, module)) = self.go_module() {
+            let package = if specifier == module {
+                Some("")
+            } else {
+                specifier
+                    .strip_prefix(&module)
+                    .and_then(|rest| rest.strip_prefix('/'))
+            };
+            if let Some(package) = package {
+                let target = dir.join(package);
+                return if target.is_dir() {
+                    self.internal(&target)
+                } else {
+                    Target::Unresolved
+                };
+            }
+        }
+        Target::External(specifier.to_string())
+    }
+
+    fn resolve_c(&self, specifier: &str) -> Target {
+        if let Some(system) = specifier
+            .strip_prefix('<')
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            let bases = [self.root.join("include"), self.root.to_path_buf()];
+            return first_file(bases.iter().map(|base| base.join(system))).map_or_else(
+                || Target::External(system.to_string()),
+                |path| self.internal(&path),
+            );
+        }
+        let bases = [
+            self.dir().to_path_buf(),
+            self.root.join("include"),
+            self.root.to_path_buf(),
+        ];
+        first_file(bases.iter().map(|base| clean_path(&base.join(specifier))))
+            .map_or(Target::Unresolved, |path| self.internal(&path))
+    }
+}
+
+/// Registry the external imports of `file` come from: `npm`, `pypi`,
+/// `crates.io`, `go`, or `system` for C/C++ headers.
+pub(crate) fn ecosystem(file: &Path) -> Option<&'static str> {
+    let language = file
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(detect_language)?;
+    Some(match Syntax::for_language(&language)? {
+        Syntax::Script => "npm",
+        Syntax::Python => "pypi",
+        Syntax::Rust => "crates.io",
+        Syntax::Go => "go",
+        Syntax::C => "system",
+    })
+}
+
+/// Imports of `file` (absolute, under `root`) and where each one leads, or
+/// `None` when the file's language is not supported.
+pub(crate) fn resolve_imports(
+    root: &Path,
+    file: &Path,
+    content: &str,
+) -> Option<Vec<(ImportStatement, Target)>> {
+    let language = file
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(detect_language)?;
+    let syntax = Syntax::for_language(&language)?;
+    let resolver = Resolver { root, file };
+    Some(
+        extract_imports(content, Some(&language))
+            .into_iter()
+            .map(|import| {
+                let target = resolver.resolve(syntax, &import.specifier);
+                (import, target)
+            })
+            .collect(),
+    )
+}
+
+/// Run the imports command
+pub fn run(file: &str, format: OutputFormat, compact: bool) -> Result<()> {
+    let root = std::env::current_dir()?.canonicalize()?;
+    let full_path = root.join(file);
+    if !full_path.is_file() {
+        bail!("File not found: {file}");
+    }
+    let full_path = full_path.canonicalize()?;
+    let Some(decoded) = encoding::read_text_file(&full_path)? else {
+        bail!("Not a text file: {file}");
+    };
+    let Some(imports) = resolve_imports(&root, &full_path, &decoded.text) else {
+        bail!("Imports are not supported for {file}; supported: JavaScript/TypeScript, Python, Rust, Go, C/C++");
+    };
+
+    let mut report = ImportsReport {
+        file: file.to_string(),
+        internal: Vec::new(),
+        external: Vec::new(),
+        unresolved: Vec::new(),
+    };
+    let own_path = full_path
+        .strip_prefix(&root)
+        .unwrap_or(&full_path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let mut seen = HashSet::from([own_path]);
+    for (import, target) in imports {
+        match target {
+            Target::Internal(path) => {
+                if seen.insert(path.clone()) {
+                    report.internal.push(InternalImport {
+                        path,
+                        line: import.line,
+                        specifier: import.specifier,
+                    });
+                }
+            }
+            Target::External(package) => {
+                if seen.insert(format!("\0{package}")) {
+                    report.external.push(ExternalImport {
+                        package,
+                        line: import.line,
+                        specifier: import.specifier,
+                    });
+                }
+            }
+            Target::Unresolved => report.unresolved.push(UnresolvedImport {
+                line: import.line,
+                specifier: import.specifier,
+            }),
+        }
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => {
+            print_json(&report, compact)?;
+        }
+        OutputFormat::Quickfix => {
+            let lines: Vec<(usize, String)> = report
+                .internal
+                .iter()
+                .map(|import| {
+                    (
+                        import.line,
+                        format!("{} -> {}", import.specifier, import.path),
+                    )
+                })
+                .chain(report.external.iter().map(|import| {
+                    (
+                        import.line,
+                        format!("{} (external: {})", import.specifier, import.package),
+                    )
+                }))
+                .chain(
+                    report
+                        .unresolved
+                        .iter()
+                        .map(|import| (import.line, format!("{} (unresolved)", import.specifier))),
+                )
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = lines
+                .iter()
+                .map(|(line, text)| QuickfixEntry {
+                    path: file,
+                    line: *line,
+                    column: 1,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            if report.internal.is_empty()
+                && report.external.is_empty()
+                && report.unresolved.is_empty()
+            {
+                println!("{} No imports in: {}", "✗".red(), file.yellow());
+                return Ok(());
+            }
+            println!("\n{} Imports of: {}\n", "🔍".cyan(), file.yellow());
+            if !report.internal.is_empty() {
+                println!("  {} ({})", "Internal".bold(), report.internal.len());
+                for import in &report.internal {
+                    println!(
+                        "    {}:{} {}",
+                        import.path.cyan(),
+                        import.line.to_string().yellow(),
+                        import.specifier.dimmed()
+                    );
+                }
+            }
+            if !report.external.is_empty() {
+                println!("  {} ({})", "External".bold(), report.external.len());
+                for import in &report.external {
+                    println!(
+                        "    {}:{} {}",
+                        import.package.cyan(),
+                        import.line.to_string().yellow(),
+                        import.specifier.dimmed()
+                    );
+                }
+            }
+            if !report.unresolved.is_empty() {
+                println!("  {} ({})", "Unresolved".bold(), report.unresolved.len());
+                for import in &report.unresolved {
+                    println!(
+                        "    {}:{}",
+                        import.specifier.cyan(),
+                        import.line.to_string().yellow()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specifiers(content: &str, language: &str) -> Vec<String> {
+        extract_imports(content, Some(language))
+            .into_iter()
+            .map(|import| import.specifier)
+            .collect()
+    }
+
+    #[test]
+    fn extracts_specifiers_per_language() {
+        assert_eq!(
+            specifiers(
+                "import x from './util';\nexport { y } from \"../lib/y\";\nconst z = require('lodash/fp');\n",
+                "typescript"
+            ),
+            ["./util", "../lib/y", "lodash/fp"]
+        );
+        assert_eq!(
+            specifiers(
+                "from .models import User\nimport os.path, json as j\n",
+                "python"
+            ),
+            [".models", "os.path", "json"]
+        );
+        assert_eq!(
+            specifiers(
+                "use crate::query::{search, cluster::{self, Cluster}};\npub(crate) mod util;\nuse serde::Serialize as Ser;\n",
+                "rust"
+            ),
+            [
+                "crate::query::search",
+                "crate::query::cluster",
+                "crate::query::cluster::Cluster",
+                "self::util",
+                "serde::Serialize",
+            ]
+        );
+        assert_eq!(
+            specifiers(
+                "import \"fmt\"\nimport (\n\t\"example.com/app/store\"\n\tlog \"github.com/sirupsen/logrus\"\n)\n",
+                "go"
+            ),
+            ["fmt", "example.com/app/store", "github.com/sirupsen/logrus"]
+        );
+        assert_eq!(
+            specifiers("#include <vector>\n#include \"util.h\"\n", "cpp"),
+            ["<vector>", "util.h"]
+        );
+    }
+
+    #[test]
+    fn names_module_ignores_relative_prefixes() {
+        let import = |specifier: &str| ImportStatement {
+            line: 1,
+            text: String::new(),
+            specifier: specifier.to_string(),
+        };
+        assert!(import("./utils").names_module("utils"));
+        assert!(import("crate::utils::parse").names_module("utils"));
+        assert!(!import("./lib/utils").names_module("utils"));
+        assert!(!import("utils_extra").names_module("utils"));
+    }
+}
@@ -2,18 +2,21 @@
 
 //! Index-backed helpers for narrowing file scans.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use tantivy::{
-    collector::DocSetCollector,
+    collector::{Count, DocSetCollector},
     query::{BooleanQuery, Occur, PhraseQuery, Query, RegexQuery, TermQuery},
     schema::{Field, FieldType, IndexRecordOption, Term, Value},
     Index, ReloadPolicy, TantivyDocument,
 };
 
+use crate::indexer::compat;
 use crate::indexer::scanner::{detect_language, ScannedFile};
+use crate::parser::metrics::SymbolMetrics;
 use crate::query::scope_query::{build_scope_path_query, normalize_scope, ScopeNormalization};
+use cgrep::encoding;
 use cgrep::utils::INDEX_DIR;
 
 #[derive(Clone, Copy)]
@@ -58,7 +61,7 @@ pub fn find_files_with_symbol_definition(
     symbol_match: SymbolNameMatch,
 ) -> Result<Option<Vec<PathBuf>>> {
     let index_path = root.join(INDEX_DIR);
-    if !index_path.exists() {
+    if !index_path.exists() || !compat::is_readable(root) {
         return Ok(None);
     }
 
@@ -163,11 +166,118 @@ pub fn find_files_with_symbol_definition(
     Ok(Some(paths))
 }
 
+/// Find files with `doc_type=literal` docs whose value or constant name
+/// contains every token of one of `variants`.
+///
+/// Returns `None` when there is no index or the index predates literal docs,
+/// so callers fall back to scanning.
+pub fn find_files_with_literal(
+    root: &Path,
+    variants: &[String],
+    scope: Option<&Path>,
+) -> Result<Option<Vec<PathBuf>>> {
+    let index_path = root.join(INDEX_DIR);
+    if !index_path.exists() || !compat::is_readable(root) {
+        return Ok(None);
+    }
+    let index = match Index::open_in_dir(&index_path) {
+        Ok(index) => index,
+        Err(_) => return Ok(None),
+    };
+
+    let schema = index.schema();
+    let (Ok(content_field), Ok(symbols_field), Ok(doc_type_field), Ok(path_field)) = (
+        schema.get_field("content"),
+        schema.get_field("symbols"),
+        schema.get_field("doc_type"),
+        schema.get_field("path"),
+    ) else {
+        return Ok(None);
+    };
+    let path_exact_field = schema.get_field("path_exact").ok();
+
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()
+        .context("Failed to create index reader")?;
+    let searcher = reader.searcher();
+
+    let literal_docs = TermQuery::new(
+        Term::from_field_text(doc_type_field, "literal"),
+        IndexRecordOption::Basic,
+    );
+    if searcher.search(&literal_docs, &Count)? == 0 {
+        return Ok(None);
+    }
+
+    let mut alternatives: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    for variant in variants {
+        for field in [content_field, symbols_field] {
+            let tokens = tokenize_for_field(&index, field, variant)?;
+            if !tokens.is_empty() {
+                alternatives.push((
+                    Occur::Should,
+                    build_token_query(field, &tokens, MatchMode::AllTokens),
+                ));
+            }
+        }
+    }
+    if alternatives.is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+
+    let effective_scope = match normalize_scope(root, scope) {
+        ScopeNormalization::None => None,
+        ScopeNormalization::Filter(path) => Some(path),
+        ScopeNormalization::OutsideRoot => return Ok(Some(Vec::new())),
+    };
+
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![
+        (Occur::Must, Box::new(literal_docs)),
+        (Occur::Must, Box::new(BooleanQuery::new(alternatives))),
+    ];
+    if let (Some(scope_path), Some(path_exact)) = (effective_scope.as_ref(), path_exact_field) {
+        if let Some(query) = build_scope_path_query(path_exact, root, scope_path) {
+            clauses.push((Occur::Must, query));
+        }
+    }
+
+    let query = BooleanQuery::new(clauses);
+    let docset = searcher.search(&query, &DocSetCollector)?;
+
+    let mut unique_paths: HashSet<PathBuf> = HashSet::with_capacity(docset.len());
+    for doc_address in docset {
+        let Ok(doc) = searcher.doc::<TantivyDocument>(doc_address) else {
+            continue;
+        };
+        let Some(path_value) = doc.get_first(path_field).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let full_path = if Path::new(path_value).is_absolute() {
+            PathBuf::from(path_value)
+        } else {
+            root.join(path_value)
+        };
+        if let Some(scope_path) = effective_scope.as_ref() {
+            if !full_path.starts_with(scope_path) {
+                continue;
+            }
+        }
+        unique_paths.insert(full_path);
+    }
+
+    let mut paths: Vec<PathBuf> = unique_paths.into_iter().collect();
+    paths.sort();
+    Ok(Some(paths))
+}
+
 /// Read a list of files into scanned-file structs.
 pub fn read_scanned_files(paths: &[PathBuf]) -> Vec<ScannedFile> {
     let mut scanned = Vec::with_capacity(paths.len());
     for path in paths {
-        if let Ok(content) = std::fs::read_to_string(path) {
+        if let Ok(Some(decoded)) = encoding::read_text_file(path) {
+            let content = decoded.text.into_owned();
             let language = path
                 .extension()
                 .and_then(|e| e.to_str())
@@ -182,6 +292,104 @@ pub fn read_scanned_files(paths: &[PathBuf]) -> Vec<ScannedFile> {
     scanned
 }
 
+/// A `doc_type=symbol` document read back from an index.
+#[derive(Debug, Clone)]
+pub struct IndexedSymbol {
+    pub name: String,
+    pub kind: String,
+    /// Path as indexed: relative to the index root, or absolute.
+    pub path: String,
+    pub language: String,
+    pub line: u64,
+    /// Symbol header and preview, as indexed.
+    pub content: String,
+    /// `None` for other symbol kinds and for indexes that predate metrics.
+    pub metrics: Option<SymbolMetrics>,
+}
+
+/// Every symbol document of the index in `index_dir`.
+///
+/// Fails when the index cannot be opened or predates symbol documents.
+pub fn read_symbol_docs(index_dir: &Path) -> Result<Vec<IndexedSymbol>> {
+    let index = Index::open_in_dir(index_dir)
+        .with_context(|| format!("Failed to open index at {}", index_dir.display()))?;
+    let schema = index.schema();
+    let (Ok(doc_type), Ok(name), Ok(path), Ok(language), Ok(line), Ok(end_line), Ok(content)) = (
+        schema.get_field("doc_type"),
+        schema.get_field("symbols"),
+        schema.get_field("path"),
+        schema.get_field("language"),
+        schema.get_field("line_number"),
+        schema.get_field("symbol_end_line"),
+        schema.get_field("content"),
+    ) else {
+        bail!(
+            "Index at {} predates symbol documents; rebuild it with `cgrep index --force`",
+            index_dir.display()
+        );
+    };
+    let metric_fields = (
+        schema.get_field("symbol_params").ok(),
+        schema.get_field("symbol_nesting").ok(),
+        schema.get_field("symbol_complexity").ok(),
+    );
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()
+        .context("Failed to create index reader")?;
+    let searcher = reader.searcher();
+    let query = TermQuery::new(
+        Term::from_field_text(doc_type, "symbol"),
+        IndexRecordOption::Basic,
+    );
+
+    let mut symbols = Vec::new();
+    for address in searcher.search(&query, &DocSetCollector)? {
+        let doc = searcher.doc::<TantivyDocument>(address)?;
+        let text = |field| {
+            doc.get_first(field)
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let number = |field| doc.get_first(field).and_then(|value| value.as_u64());
+        let content = text(content);
+        // Symbol content starts with a "<name> <kind>" header line.
+        let kind = content
+            .lines()
+            .next()
+            .and_then(|header| header.rsplit(' ').next())
+            .unwrap_or_default()
+            .to_string();
+        let line = number(line).unwrap_or_default();
+        let metrics = match metric_fields {
+            (Some(params), Some(nesting), Some(complexity)) => {
+                match (number(params), number(nesting), number(complexity)) {
+                    (Some(params), Some(nesting), Some(complexity)) => Some(SymbolMetrics {
+                        lines: (number(end_line).unwrap_or(line).saturating_sub(line) + 1) as usize,
+                        params: params as usize,
+                        nesting: nesting as usize,
+                        complexity: complexity as usize,
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        symbols.push(IndexedSymbol {
+            name: text(name),
+            kind,
+            path: text(path),
+            language: text(language),
+            line,
+            content,
+            metrics,
+        });
+    }
+    Ok(symbols)
+}
+
 fn find_files_with_field(
     root: &Path,
     field_name: &str,
@@ -190,7 +398,7 @@ fn find_files_with_field(
     match_mode: MatchMode,
 ) -> Result<Option<Vec<PathBuf>>> {
     let index_path = root.join(INDEX_DIR);
-    if !index_path.exists() {
+    if !index_path.exists() || !compat::is_readable(root) {
         return Ok(None);
     }
 
@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! GitHub Linguist classification of result paths.
+//!
+//! Paths marked `linguist-vendored` or `linguist-generated` in `.gitattributes`
+//! are what GitHub hides from diffs and language stats. Search demotes or
+//! drops them per `[search] linguist`. Attributes are resolved by
+//! `git check-attr`, so nested `.gitattributes` files, `info/attributes`, and
+//! macros behave exactly as in git.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use cgrep::config::LinguistPolicy;
+use cgrep::paths;
+use cgrep::utils::{find_checkout_root, resolve_git_dir};
+
+use crate::query::search::SearchResult;
+
+const VENDORED_ATTR: &str = "linguist-vendored";
+const GENERATED_ATTR: &str = "linguist-generated";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Linguist {
+    Vendored,
+    Generated,
+}
+
+/// Whether the checkout around `start` defines attributes at all.
+///
+/// Cheap probe so repositories without a root `.gitattributes` (or
+/// `info/attributes`) never pay for a `git` process.
+pub(crate) fn attributes_in_use(start: &Path) -> bool {
+    let Some(checkout_root) = find_checkout_root(start) else {
+        return false;
+    };
+    checkout_root.join(".gitattributes").is_file()
+        || resolve_git_dir(&checkout_root)
+            .is_some_and(|git_dir| git_dir.join("info").join("attributes").is_file())
+}
+
+/// Tag results with their classification and apply `policy`.
+///
+/// Result paths are resolved from `workspace_root`. Demoting is a stable
+/// partition, so score order holds within each group.
+pub(crate) fn apply_linguist_policy(
+    results: &mut Vec<SearchResult>,
+    workspace_root: &Path,
+    policy: LinguistPolicy,
+) {
+    if policy == LinguistPolicy::Off || results.is_empty() {
+        return;
+    }
+    let paths: Vec<String> = results
+        .iter()
+        .map(|result| {
+            paths::decode_path(&result.path)
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    let classes = classify_paths(workspace_root, &paths);
+    if classes.is_empty() {
+        return;
+    }
+    for (result, path) in results.iter_mut().zip(&paths) {
+        result.linguist = classes.get(path).copied();
+    }
+    match policy {
+        LinguistPolicy::Exclude => results.retain(|result| result.linguist.is_none()),
+        LinguistPolicy::Demote => results.sort_by_key(|result| result.linguist.is_some()),
+        LinguistPolicy::Off => {}
+    }
+}
+
+/// Classification of each path (relative to `cwd`) marked vendored or generated.
+fn classify_paths(cwd: &Path, rel_paths: &[String]) -> HashMap<String, Linguist> {
+    let child = Command::new("git")
+        .arg("-C")
+        .arg(cwd)
+        .args(["check-attr", "-z", "--stdin", VENDORED_ATTR, GENERATED_ATTR])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return HashMap::new();
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let mut input = Vec::new();
+        for path in rel_paths {
+            input.extend_from_slice(path.as_bytes());
+            input.push(0);
+        }
+        let _ = stdin.write_all(&input);
+    }
+    match child.wait_with_output() {
+        Ok(output) if output.status.success() => parse_check_attr(&output.stdout),
+        _ => HashMap::new(),
+    }
+}
+
+/// Parse `git check-attr -z` output (`path NUL attribute NUL value NUL` triples).
+///
+/// Vendored wins when a path carries both attributes.
+fn parse_check_attr(stdout: &[u8]) -> HashMap<String, Linguist> {
+    let fields: Vec<String> = stdout
+        .split(|byte| *byte == 0)
+        .map(|field| String::from_utf8_lossy(field).into_owned())
+        .collect();
+    let mut classes = HashMap::new();
+    for triple in fields.chunks_exact(3) {
+        let (path, attr, value) = (&triple[0], triple[1].as_str(), triple[2].as_str());
+        if !matches!(value, "set" | "true") {
+            continue;
+        }
+        let class = match attr {
+            VENDORED_ATTR => Linguist::Vendored,
+            GENERATED_ATTR => Linguist::Generated,
+            _ => continue,
+        };
+        classes
+            .entry(path.clone())
+            .and_modify(|existing| {
+                if class == Linguist::Vendored {
+                    *existing = class;
+                }
+            })
+            .or_insert(class);
+    }
+    classes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_attr_output_keeps_only_set_attributes() {
+        let stdout = b"third_party/x.js\0linguist-vendored\0set\0\
+third_party/x.js\0linguist-generated\0true\0\
+gen/api.rs\0linguist-vendored\0unspecified\0\
+gen/api.rs\0linguist-generated\0true\0\
+src/lib.rs\0linguist-vendored\0false\0\
+src/lib.rs\0linguist-generated\0unset\0";
+
+        let classes = parse_check_attr(stdout);
+
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes["third_party/x.js"], Linguist::Vendored);
+        assert_eq!(classes["gen/api.rs"], Linguist::Generated);
+    }
+}
@@ -0,0 +1,241 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep literals`: find every occurrence of a string literal.
+//!
+//! A literal matches when its value equals the query (`exact`), equals it
+//! after case and separator normalization (`normalized`, so `Content-Type`
+//! finds `content_type` and `contentType`), or initializes a constant whose
+//! name normalizes to the query (`constant`). `--contains` also reports
+//! literals that merely contain the query.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::cli::OutputFormat;
+use crate::indexer::literals::{extract_literals, normalize_literal};
+use crate::indexer::scanner::{FileScanner, ScannedFile};
+use crate::query::index_filter::{find_files_with_literal, read_scanned_files};
+use cgrep::output::{
+    colorize_line_num, colorize_path, print_json, print_quickfix, use_colors, QuickfixEntry,
+};
+use cgrep::utils::get_root_with_index;
+
+/// How a literal matched the query, best first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum LiteralMatch {
+    Exact,
+    Normalized,
+    Constant,
+    Contains,
+}
+
+impl LiteralMatch {
+    fn label(self) -> &'static str {
+        match self {
+            LiteralMatch::Exact => "exact",
+            LiteralMatch::Normalized => "normalized",
+            LiteralMatch::Constant => "constant",
+            LiteralMatch::Contains => "contains",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LiteralResult {
+    path: String,
+    line: u64,
+    column: u64,
+    value: String,
+    #[serde(rename = "match")]
+    kind: LiteralMatch,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    constant: Option<String>,
+}
+
+/// Run the literals command.
+pub fn run(
+    value: &str,
+    path: Option<&str>,
+    contains: bool,
+    max_results: Option<usize>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let normalized = normalize_literal(value);
+    if normalized.is_empty() {
+        bail!(
+            "Literal query needs at least one letter or digit: {:?}",
+            value
+        );
+    }
+
+    let search_root = match path {
+        Some(p) => PathBuf::from(p).canonicalize()?,
+        None => std::env::current_dir()?.canonicalize()?,
+    };
+    let index_root = get_root_with_index(&search_root);
+
+    let variants = vec![
+        value.to_string(),
+        normalized.replace('_', " "),
+        normalized.replace('_', ""),
+    ];
+    let files: Vec<ScannedFile> =
+        match find_files_with_literal(&index_root, &variants, Some(&search_root))? {
+            Some(paths) => read_scanned_files(&paths),
+            None => FileScanner::new(&search_root).scan()?,
+        };
+
+    let query = Query {
+        value,
+        normalized: &normalized,
+        lowered: value.to_lowercase(),
+        contains,
+    };
+    let mut results = Vec::new();
+    for file in &files {
+        let Some(language) = file.language.as_deref() else {
+            continue;
+        };
+        let rel_path = file
+            .path
+            .strip_prefix(&search_root)
+            .unwrap_or(&file.path)
+            .display()
+            .to_string();
+        for literal in extract_literals(&file.content, language) {
+            let Some(kind) = query.classify(&literal.value, literal.constant.as_deref()) else {
+                continue;
+            };
+            results.push(LiteralResult {
+                path: rel_path.clone(),
+                line: literal.line,
+                column: literal.column,
+                value: literal.value,
+                kind,
+                constant: literal.constant,
+            });
+        }
+    }
+    results.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then_with(|| a.path.cmp(&b.path))
+            .then(a.line.cmp(&b.line))
+            .then(a.column.cmp(&b.column))
+    });
+    if let Some(limit) = max_results {
+        results.truncate(limit);
+    }
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => {
+            print_json(&results, compact)?;
+        }
+        OutputFormat::Quickfix => {
+            let entries: Vec<QuickfixEntry<'_>> = results
+                .iter()
+                .map(|result| QuickfixEntry {
+                    path: &result.path,
+                    line: result.line as usize,
+                    column: result.column as usize,
+                    text: &result.value,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            let use_color = use_colors() && format == OutputFormat::Text;
+            if results.is_empty() {
+                if use_color {
+                    println!("{} No literal found for: {}", "✗".red(), value.yellow());
+                } else {
+                    println!("No literal found for: {}", value);
+                }
+                return Ok(());
+            }
+            for result in &results {
+                let constant = result
+                    .constant
+                    .as_deref()
+                    .map(|name| format!(" ({})", name))
+                    .unwrap_or_default();
+                println!(
+                    "{:<10} {}:{}:{}  \"{}\"{}",
+                    result.kind.label(),
+                    colorize_path(&result.path, use_color),
+                    colorize_line_num(result.line as usize, use_color),
+                    result.column,
+                    result.value,
+                    constant
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct Query<'a> {
+    value: &'a str,
+    normalized: &'a str,
+    lowered: String,
+    contains: bool,
+}
+
+impl Query<'_> {
+    fn classify(&self, literal: &str, constant: Option<&str>) -> Option<LiteralMatch> {
+        if literal == self.value {
+            return Some(LiteralMatch::Exact);
+        }
+        if normalize_literal(literal) == self.normalized {
+            return Some(LiteralMatch::Normalized);
+        }
+        if constant.is_some_and(|name| normalize_literal(name) == self.normalized) {
+            return Some(LiteralMatch::Constant);
+        }
+        let contained = literal.to_lowercase().contains(&self.lowered)
+            || normalize_literal(literal).contains(self.normalized);
+        (self.contains && contained).then_some(LiteralMatch::Contains)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_exact_normalized_constant_and_contains() {
+        let strict = Query {
+            value: "content-type",
+            normalized: "content_type",
+            lowered: "content-type".to_string(),
+            contains: false,
+        };
+        assert_eq!(
+            strict.classify("content-type", None),
+            Some(LiteralMatch::Exact)
+        );
+        assert_eq!(
+            strict.classify("Content-Type", None),
+            Some(LiteralMatch::Normalized)
+        );
+        assert_eq!(
+            strict.classify("application/json", Some("CONTENT_TYPE")),
+            Some(LiteralMatch::Constant)
+        );
+        assert_eq!(strict.classify("x-content-type-options", None), None);
+
+        let loose = Query {
+            contains: true,
+            ..strict
+        };
+        assert_eq!(
+            loose.classify("x-content-type-options", None),
+            Some(LiteralMatch::Contains)
+        );
+    }
+}
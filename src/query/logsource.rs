@@ -0,0 +1,374 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep logsource`: find the code that printed a log line.
+//!
+//! The log line is reduced to its message (timestamps, levels, and variable
+//! values dropped), then compared against string literals in source files.
+//! Format placeholders inside a literal (`%s`, `{}`, `{name}`, `${expr}`)
+//! stand for any value, so a literal whose template matches the message
+//! ranks above literals that only share words with it.
+
+use anyhow::Result;
+use colored::Colorize;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::{FileScanner, ScannedFile};
+use crate::query::index_filter::{find_files_with_content, read_scanned_files};
+use cgrep::output::{
+    colorize_line_num, colorize_path, print_json, print_quickfix, use_colors, QuickfixEntry,
+};
+use cgrep::utils::get_root_with_index;
+
+/// Index lookups use this many of the message's longest words.
+const MAX_INDEX_TERMS: usize = 4;
+const MIN_SCORE: f64 = 0.25;
+
+static LOG_PREFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:[.,]\d+)?(?:Z|[+-]\d{2}:?\d{2})?",
+        r"|\b[A-Z][a-z]{2} +\d{1,2} \d{2}:\d{2}:\d{2}\b",
+        r"|\b\d{2}:\d{2}:\d{2}(?:[.,]\d+)?\b",
+        r"|\[?\b(?:TRACE|DEBUG|INFO|NOTICE|WARN|WARNING|ERROR|FATAL|CRITICAL)\b\]?:?",
+    ))
+    .expect("log prefix regex")
+});
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"%(?:\([^)]*\))?[-+#0]*\d*(?:\.\d+)?[a-zA-Z]|\$?\{[^{}]*\}")
+        .expect("placeholder regex")
+});
+static ESCAPE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\[nrt]").expect("escape regex"));
+static LOG_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:log|logger|logging|console|tracing|slog|print|println|eprintln|printf|fprintf|puts|warn|info|debug|error|trace|fatal|panic)\b",
+    )
+    .expect("log call regex")
+});
+
+/// Candidate call site for a log line.
+#[derive(Debug, Serialize)]
+struct LogSourceResult {
+    path: String,
+    line: usize,
+    column: usize,
+    literal: String,
+    score: f64,
+    /// The literal's template (placeholders as wildcards) matches the message.
+    exact: bool,
+    /// The line looks like a logging or print call.
+    log_call: bool,
+}
+
+/// Run the logsource command.
+pub fn run(
+    log_line: &str,
+    path: Option<&str>,
+    max_results: usize,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let search_root = match path {
+        Some(p) => PathBuf::from(p).canonicalize()?,
+        None => std::env::current_dir()?.canonicalize()?,
+    };
+    let index_root = get_root_with_index(&search_root);
+
+    let message = strip_log_prefix(log_line);
+    let message_words = words(&message);
+    let mut results = if message_words.is_empty() {
+        Vec::new()
+    } else {
+        let files = load_candidate_files(&message_words, &search_root, &index_root)?;
+        rank_candidates(&files, &search_root, &message, &message_words)
+    };
+    results.truncate(max_results);
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => {
+            print_json(&results, compact)?;
+        }
+        OutputFormat::Quickfix => {
+            let entries: Vec<QuickfixEntry<'_>> = results
+                .iter()
+                .map(|result| QuickfixEntry {
+                    path: &result.path,
+                    line: result.line,
+                    column: result.column,
+                    text: &result.literal,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            let use_color = use_colors() && format == OutputFormat::Text;
+            if results.is_empty() {
+                if use_color {
+                    println!(
+                        "{} No log source found for: {}",
+                        "✗".red(),
+                        message.yellow()
+                    );
+                } else {
+                    println!("No log source found for: {}", message);
+                }
+                return Ok(());
+            }
+            for result in &results {
+                let marker = if result.exact { "=" } else { "~" };
+                println!(
+                    "{} {:.2} {}:{}  \"{}\"",
+                    marker,
+                    result.score,
+                    colorize_path(&result.path, use_color),
+                    colorize_line_num(result.line, use_color),
+                    result.literal
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn load_candidate_files(
+    message_words: &[String],
+    search_root: &Path,
+    index_root: &Path,
+) -> Result<Vec<ScannedFile>> {
+    let mut terms: Vec<&String> = message_words
+        .iter()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    terms.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+    terms.truncate(MAX_INDEX_TERMS);
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    for term in terms {
+        match find_files_with_content(index_root, term, Some(search_root))? {
+            Some(found) => paths.extend(found.into_iter().filter(|p| seen.insert(p.clone()))),
+            None => return FileScanner::new(search_root).scan(),
+        }
+    }
+    Ok(read_scanned_files(&paths))
+}
+
+fn rank_candidates(
+    files: &[ScannedFile],
+    search_root: &Path,
+    message: &str,
+    message_words: &[String],
+) -> Vec<LogSourceResult> {
+    let mut results = Vec::new();
+    for file in files {
+        let Some(language) = file.language.as_deref() else {
+            continue;
+        };
+        if matches!(language, "json" | "yaml" | "toml") {
+            continue;
+        }
+        let rel_path = file
+            .path
+            .strip_prefix(search_root)
+            .unwrap_or(&file.path)
+            .display()
+            .to_string();
+        for (idx, line) in file.content.lines().enumerate() {
+            let literals = string_literals(line, language);
+            if literals.is_empty() {
+                continue;
+            }
+            let log_call = LOG_CALL.is_match(line);
+            for (column, literal) in literals {
+                let Some((score, exact)) = template_score(&literal, message, message_words) else {
+                    continue;
+                };
+                let score = if log_call {
+                    (score + 0.05).min(1.0)
+                } else {
+                    score
+                };
+                if score < MIN_SCORE {
+                    continue;
+                }
+                results.push(LogSourceResult {
+                    path: rel_path.clone(),
+                    line: idx + 1,
+                    column,
+                    literal,
+                    score: (score * 1000.0).round() / 1000.0,
+                    exact,
+                    log_call,
+                });
+            }
+        }
+    }
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.path.cmp(&b.path))
+            .then(a.line.cmp(&b.line))
+    });
+    results
+}
+
+/// Drop timestamps and level markers, keeping the message text.
+fn strip_log_prefix(line: &str) -> String {
+    let stripped = LOG_PREFIX.replace_all(line, " ");
+    stripped
+        .trim_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '|' | ':'))
+        .to_string()
+}
+
+/// Lowercased words without digits; numbers, ids, and hashes are values.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| word.chars().count() >= 2 && !word.chars().any(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Score a literal as the template of `message`, or `None` if unrelated.
+fn template_score(literal: &str, message: &str, message_words: &[String]) -> Option<(f64, bool)> {
+    let unescaped = ESCAPE.replace_all(literal, " ");
+    let template_words = words(&PLACEHOLDER.replace_all(&unescaped, " "));
+    if template_words.is_empty() {
+        return None;
+    }
+
+    let mut remaining: Vec<&String> = message_words.iter().collect();
+    let mut overlap = 0usize;
+    for word in &template_words {
+        if let Some(pos) = remaining.iter().position(|candidate| *candidate == word) {
+            remaining.swap_remove(pos);
+            overlap += 1;
+        }
+    }
+    let precision = overlap as f64 / template_words.len() as f64;
+    if overlap == 0 || precision < 0.5 {
+        return None;
+    }
+    let recall = overlap as f64 / message_words.len() as f64;
+    let similarity = 2.0 * precision * recall / (precision + recall);
+
+    let exact = template_regex(&unescaped).is_some_and(|re| re.is_match(message));
+    let score = if exact {
+        0.5 + 0.5 * similarity
+    } else {
+        0.5 * similarity
+    };
+    Some((score, exact))
+}
+
+/// Regex for a literal with each placeholder matching any value.
+fn template_regex(literal: &str) -> Option<Regex> {
+    let mut pattern = String::from("(?i)");
+    let mut last = 0;
+    for placeholder in PLACEHOLDER.find_iter(literal) {
+        pattern.push_str(&regex::escape(literal[last..placeholder.start()].trim()));
+        pattern.push_str(r"\s*.*?\s*");
+        last = placeholder.end();
+    }
+    pattern.push_str(&regex::escape(literal[last..].trim()));
+    Regex::new(&pattern).ok()
+}
+
+/// String literals on a line as (1-based column, contents).
+fn string_literals(line: &str, language: &str) -> Vec<(usize, String)> {
+    let single_quote_strings = matches!(
+        language,
+        "python" | "javascript" | "typescript" | "ruby" | "php" | "lua" | "vue" | "svelte"
+    );
+    let backtick_strings = matches!(
+        language,
+        "javascript" | "typescript" | "vue" | "svelte" | "go"
+    );
+
+    let mut literals = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let is_quote =
+            c == '"' || (c == '\'' && single_quote_strings) || (c == '`' && backtick_strings);
+        if !is_quote {
+            continue;
+        }
+        let mut text = String::new();
+        let mut closed = false;
+        while let Some((_, next)) = chars.next() {
+            if next == '\\' {
+                text.push(next);
+                if let Some((_, escaped)) = chars.next() {
+                    text.push(escaped);
+                }
+                continue;
+            }
+            if next == c {
+                closed = true;
+                break;
+            }
+            text.push(next);
+        }
+        if closed && !text.trim().is_empty() {
+            literals.push((line[..start].chars().count() + 1, text));
+        }
+    }
+    literals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_timestamps_levels_and_values() {
+        let message = strip_log_prefix(
+            "2024-05-01T12:30:45.123Z [ERROR] Failed to connect to db-7 after 3 retries",
+        );
+        assert_eq!(message, "Failed to connect to db-7 after 3 retries");
+        assert_eq!(
+            words(&message),
+            ["failed", "to", "connect", "to", "db", "after", "retries"]
+        );
+    }
+
+    #[test]
+    fn template_with_placeholders_matches_message() {
+        let message = "Failed to connect to db-7 after 3 retries";
+        let message_words = words(message);
+        let (exact_score, exact) = template_score(
+            "Failed to connect to %s after %d retries",
+            message,
+            &message_words,
+        )
+        .expect("format string matches");
+        assert!(exact);
+        let (brace_score, brace_exact) = template_score(
+            "Failed to connect to {} after {} retries",
+            message,
+            &message_words,
+        )
+        .expect("brace template matches");
+        assert!(brace_exact);
+        assert_eq!(exact_score, brace_score);
+
+        let (partial_score, partial_exact) =
+            template_score("Failed to connect", message, &message_words).expect("partial");
+        assert!(!partial_exact || partial_score < exact_score);
+        assert!(template_score("unrelated text here", message, &message_words).is_none());
+    }
+
+    #[test]
+    fn finds_string_literals_per_language() {
+        let line = r#"log.error("retry {} of {}", n, 'x'); // "done""#;
+        let rust = string_literals(line, "rust");
+        assert_eq!(rust[0], (11, "retry {} of {}".to_string()));
+        assert_eq!(rust.len(), 2);
+        let python = string_literals("logger.warning('cache miss for %s', key)", "python");
+        assert_eq!(python, [(16, "cache miss for %s".to_string())]);
+    }
+}
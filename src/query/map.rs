@@ -6,13 +6,13 @@ use anyhow::{bail, Context, Result};
 use ignore::WalkBuilder;
 use serde::Serialize;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
-use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::cli::OutputFormat;
 use crate::indexer::scanner::detect_language;
 use crate::parser::symbols::SymbolExtractor;
-use cgrep::output::print_json;
+use cgrep::encoding;
+use cgrep::output::{print_json, print_json2};
 
 const MAX_SYMBOLS_PER_FILE: usize = 6;
 const MAX_SYMBOL_FILE_SIZE: u64 = 500_000;
@@ -68,7 +68,11 @@ pub fn run(path: Option<&str>, depth: usize, format: OutputFormat, compact: bool
     let root_display = display_root(&cwd, &root);
 
     match format {
-        OutputFormat::Text => {
+        OutputFormat::Text
+        | OutputFormat::Github
+        | OutputFormat::Gitlab
+        | OutputFormat::Sarif
+        | OutputFormat::Quickfix => {
             let rendered = render_text_map(&root_display, depth, &entries);
             println!("{rendered}");
         }
@@ -83,14 +87,14 @@ pub fn run(path: Option<&str>, depth: usize, format: OutputFormat, compact: bool
         OutputFormat::Json2 => {
             let payload = MapJson2Payload {
                 meta: MapJson2Meta {
-                    schema_version: "1",
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
                     command: "map",
                     root: &root_display,
                     depth,
                 },
                 entries: to_json_entries(&entries),
             };
-            print_json(&payload, compact)?;
+            print_json2("map", &payload, compact)?;
         }
     }
 
@@ -191,9 +195,9 @@ fn collect_symbols(path: &Path, size: u64, extractor: &SymbolExtractor) -> Vec<S
         return Vec::new();
     };
 
-    let content = match fs::read_to_string(path) {
-        Ok(content) => content,
-        Err(_) => return Vec::new(),
+    let content = match encoding::read_text_file(path) {
+        Ok(Some(decoded)) => decoded.text.into_owned(),
+        _ => return Vec::new(),
     };
     let symbols = match extractor.extract(&content, &language) {
         Ok(symbols) => symbols,
@@ -203,7 +207,7 @@ fn collect_symbols(path: &Path, size: u64, extractor: &SymbolExtractor) -> Vec<S
     let mut unique = HashSet::new();
     let mut names = Vec::new();
     for symbol in symbols {
-        if !unique.insert(symbol.name.clone()) {
+        if symbol.is_anonymous() || !unique.insert(symbol.name.clone()) {
             continue;
         }
         names.push(symbol.name);
@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep metrics`: size and complexity of the functions in a codebase.
+//!
+//! Metrics are recorded on symbol documents at index time (see
+//! `parser::metrics`), so ranking a whole repository reads the index instead
+//! of parsing every file. Without an index that carries them, files are
+//! scanned and parsed.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::cli::{CliMetric, OutputFormat};
+use crate::indexer::compat;
+use crate::indexer::scanner::FileScanner;
+use crate::parser::metrics::{Metric, MetricFilter, SymbolMetrics};
+use crate::parser::symbols::SymbolExtractor;
+use crate::query::index_filter::read_symbol_docs;
+use cgrep::output::{colorize_path, print_json, use_colors};
+use cgrep::paths;
+use cgrep::utils::{get_root_with_index, INDEX_DIR};
+
+#[derive(Debug, Serialize)]
+struct MetricRow {
+    name: String,
+    kind: String,
+    path: String,
+    line: u64,
+    #[serde(flatten)]
+    metrics: SymbolMetrics,
+}
+
+#[derive(Debug, Serialize)]
+struct Averages {
+    lines: f64,
+    params: f64,
+    nesting: f64,
+    complexity: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct MetricsReport<'a> {
+    by: &'static str,
+    /// Functions matching the filters, before `--top`.
+    total: usize,
+    averages: Averages,
+    symbols: &'a [MetricRow],
+}
+
+impl From<CliMetric> for Metric {
+    fn from(metric: CliMetric) -> Self {
+        match metric {
+            CliMetric::Complexity => Metric::Complexity,
+            CliMetric::Lines => Metric::Lines,
+            CliMetric::Params => Metric::Params,
+            CliMetric::Nesting => Metric::Nesting,
+        }
+    }
+}
+
+/// Run the metrics command.
+pub fn run(
+    path: Option<&str>,
+    lang: Option<&str>,
+    by: CliMetric,
+    top: usize,
+    filters: &[String],
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let cwd = paths::canonicalize(&std::env::current_dir()?)?;
+    let scope = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => cwd.clone(),
+    };
+    let filters: Vec<MetricFilter> = filters
+        .iter()
+        .map(|raw| MetricFilter::parse(raw))
+        .collect::<Result<_>>()?;
+    let by = Metric::from(by);
+
+    let mut rows = match indexed_rows(&scope, lang)? {
+        Some(rows) => rows,
+        None => scanned_rows(&scope, lang)?,
+    };
+    rows.retain(|row| filters.iter().all(|filter| filter.matches(&row.metrics)));
+    for row in &mut rows {
+        row.path =
+            paths::relative_display(Path::new(&row.path), &cwd).unwrap_or_else(|| row.path.clone());
+    }
+    rows.sort_by(|a, b| {
+        by.of(&b.metrics)
+            .cmp(&by.of(&a.metrics))
+            .then_with(|| (&a.path, a.line).cmp(&(&b.path, b.line)))
+    });
+
+    let total = rows.len();
+    let average = |metric: Metric| {
+        let sum: usize = rows.iter().map(|row| metric.of(&row.metrics)).sum();
+        if total == 0 {
+            0.0
+        } else {
+            (sum as f64 / total as f64 * 10.0).round() / 10.0
+        }
+    };
+    let averages = Averages {
+        lines: average(Metric::Lines),
+        params: average(Metric::Params),
+        nesting: average(Metric::Nesting),
+        complexity: average(Metric::Complexity),
+    };
+    rows.truncate(top);
+    let report = MetricsReport {
+        by: by.name(),
+        total,
+        averages,
+        symbols: &rows,
+    };
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&report, compact)?,
+        _ => print_text(&report, use_colors() && format == OutputFormat::Text),
+    }
+    Ok(())
+}
+
+/// Rows from the index covering `scope`, or `None` when there is no index
+/// with metrics to read.
+fn indexed_rows(scope: &Path, lang: Option<&str>) -> Result<Option<Vec<MetricRow>>> {
+    let root = get_root_with_index(scope);
+    let index_dir = root.join(INDEX_DIR);
+    if !index_dir.join("meta.json").is_file() || !compat::is_readable(&root) {
+        return Ok(None);
+    }
+    let symbols = read_symbol_docs(&index_dir)?;
+    // Function-like symbols of an index written before metrics carry none.
+    if !symbols.is_empty() && symbols.iter().all(|symbol| symbol.metrics.is_none()) {
+        return Ok(None);
+    }
+    let rows = symbols
+        .into_iter()
+        .filter(|symbol| lang.is_none_or(|lang| symbol.language == lang))
+        .filter_map(|symbol| {
+            let metrics = symbol.metrics?;
+            let full_path = if Path::new(&symbol.path).is_absolute() {
+                PathBuf::from(&symbol.path)
+            } else {
+                root.join(&symbol.path)
+            };
+            full_path.starts_with(scope).then(|| MetricRow {
+                name: symbol.name,
+                kind: symbol.kind,
+                path: paths::display(&full_path),
+                line: symbol.line,
+                metrics,
+            })
+        })
+        .collect();
+    Ok(Some(rows))
+}
+
+/// Rows from parsing every file under `scope`.
+fn scanned_rows(scope: &Path, lang: Option<&str>) -> Result<Vec<MetricRow>> {
+    let extractor = SymbolExtractor::new();
+    let mut parser_cache = std::collections::HashMap::new();
+    let mut rows = Vec::new();
+    for file in FileScanner::new(scope).scan()? {
+        let Some(language) = file.language.as_deref() else {
+            continue;
+        };
+        if lang.is_some_and(|lang| lang != language) {
+            continue;
+        }
+        let Ok(symbols) = extractor.extract_with_cache(&file.content, language, &mut parser_cache)
+        else {
+            continue;
+        };
+        for symbol in symbols {
+            let Some(metrics) = symbol.metrics else {
+                continue;
+            };
+            rows.push(MetricRow {
+                name: symbol.name,
+                kind: symbol.kind.to_string(),
+                path: paths::display(&file.path),
+                line: symbol.line as u64,
+                metrics,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+fn print_text(report: &MetricsReport<'_>, use_color: bool) {
+    let averages = &report.averages;
+    println!(
+        "{} functions | average complexity {}, lines {}, params {}, nesting {}",
+        report.total, averages.complexity, averages.lines, averages.params, averages.nesting
+    );
+    if report.symbols.is_empty() {
+        return;
+    }
+    println!(
+        "\nTop {} by {}:\n{:>10} {:>6} {:>6} {:>7}  symbol",
+        report.symbols.len(),
+        report.by,
+        "complexity",
+        "lines",
+        "params",
+        "nesting"
+    );
+    for row in report.symbols {
+        let metrics = &row.metrics;
+        println!(
+            "{:>10} {:>6} {:>6} {:>7}  {} {}  {}:{}",
+            metrics.complexity,
+            metrics.lines,
+            metrics.params,
+            metrics.nesting,
+            row.kind,
+            row.name,
+            colorize_path(&row.path, use_color),
+            row.line
+        );
+    }
+}
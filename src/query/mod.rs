@@ -3,15 +3,43 @@
 //! Query module - search and symbol lookup commands
 
 pub mod agent;
+pub mod api;
 pub mod ast_usage;
+pub mod audit;
+pub mod boundaries;
 pub mod callers;
 pub mod changed_files;
+pub mod check;
+pub mod cluster;
+pub mod constants;
 pub mod definition;
 pub mod dependents;
+pub mod deps;
+pub mod diff_index;
+pub mod envvars;
+pub mod facets;
+pub mod file_summary;
+pub mod flags;
+pub mod grep;
+pub mod hits;
+pub mod imports;
 pub mod index_filter;
+pub mod linguist;
+pub mod literals;
+pub mod logsource;
 pub mod map;
+pub mod metrics;
+pub mod packages;
 pub mod read;
 pub mod references;
+pub mod rename_check;
+pub mod review;
+pub mod routes;
+pub mod schema;
 pub mod scope_query;
 pub mod search;
+pub mod selftest;
+pub mod symbol_header;
 pub mod symbols;
+pub mod trace;
+pub mod usage;
@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep packages`: inventory of the external packages a codebase imports.
+//!
+//! Every file with a supported import syntax is read and its imports are
+//! resolved as in `cgrep imports`; those that lead outside the workspace are
+//! grouped by package, with how many imports and files use each one and a
+//! few example locations. Rust sysroot crates and `node:` builtins are not
+//! packages anyone installs and are left out.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::FileScanner;
+use crate::query::imports::{ecosystem, resolve_imports, Target};
+use cgrep::output::{print_json, print_quickfix, QuickfixEntry};
+use cgrep::paths;
+
+/// Crates that ship with the Rust toolchain.
+const RUST_SYSROOT: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+fn is_builtin(ecosystem: &str, package: &str) -> bool {
+    match ecosystem {
+        "crates.io" => RUST_SYSROOT.contains(&package),
+        "npm" => package.starts_with("node:"),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PackageLocation {
+    path: String,
+    line: usize,
+    code: String,
+}
+
+/// One external package for JSON output
+#[derive(Debug, Serialize)]
+struct PackageUsage {
+    package: String,
+    ecosystem: &'static str,
+    /// Import statements naming the package
+    imports: usize,
+    /// Files with at least one of them
+    files: usize,
+    examples: Vec<PackageLocation>,
+}
+
+/// Run the packages command
+pub fn run(
+    name: Option<&str>,
+    path: Option<&str>,
+    examples: usize,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let cwd = paths::canonicalize(&std::env::current_dir()?)?;
+    let root = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => cwd.clone(),
+    };
+    // A single package lists every location.
+    let examples = if name.is_some() { usize::MAX } else { examples };
+
+    let mut files = FileScanner::new(&root).list_files()?;
+    files.sort();
+    let mut usage: BTreeMap<(&'static str, String), (PackageUsage, BTreeSet<String>)> =
+        BTreeMap::new();
+    for file in files {
+        let Some(ecosystem) = ecosystem(&file) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let Some(imports) = resolve_imports(&root, &file, &content) else {
+            continue;
+        };
+        let display_path =
+            paths::relative_display(&file, &cwd).unwrap_or_else(|| paths::display(&file));
+        for (import, target) in imports {
+            let Target::External(package) = target else {
+                continue;
+            };
+            if is_builtin(ecosystem, &package) || name.is_some_and(|name| name != package) {
+                continue;
+            }
+            let (entry, files) = usage
+                .entry((ecosystem, package.clone()))
+                .or_insert_with(|| {
+                    (
+                        PackageUsage {
+                            package,
+                            ecosystem,
+                            imports: 0,
+                            files: 0,
+                            examples: Vec::new(),
+                        },
+                        BTreeSet::new(),
+                    )
+                });
+            entry.imports += 1;
+            files.insert(display_path.clone());
+            if entry.examples.len() < examples {
+                entry.examples.push(PackageLocation {
+                    path: display_path.clone(),
+                    line: import.line,
+                    code: import.text,
+                });
+            }
+        }
+    }
+
+    let mut packages: Vec<PackageUsage> = usage
+        .into_values()
+        .map(|(mut entry, files)| {
+            entry.files = files.len();
+            entry
+        })
+        .collect();
+    packages.sort_by(|a, b| {
+        (b.files, b.imports)
+            .cmp(&(a.files, a.imports))
+            .then_with(|| (&a.package, a.ecosystem).cmp(&(&b.package, b.ecosystem)))
+    });
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&packages, compact)?,
+        OutputFormat::Quickfix => {
+            let texts: Vec<(String, &PackageLocation)> = packages
+                .iter()
+                .flat_map(|package| {
+                    package.examples.iter().map(move |location| {
+                        (
+                            format!("{} ({})", package.package, package.ecosystem),
+                            location,
+                        )
+                    })
+                })
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = texts
+                .iter()
+                .map(|(text, location)| QuickfixEntry {
+                    path: &location.path,
+                    line: location.line,
+                    column: 1,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            if packages.is_empty() {
+                match name {
+                    Some(name) => {
+                        println!("{} No imports of package: {}", "✗".red(), name.yellow())
+                    }
+                    None => println!("{} No external imports found", "✗".red()),
+                }
+                return Ok(());
+            }
+            println!(
+                "\n{} External packages ({})\n",
+                "📦".cyan(),
+                packages.len().to_string().cyan()
+            );
+            for package in &packages {
+                println!(
+                    "  {} {} {} files, {} imports",
+                    package.package.cyan().bold(),
+                    format!("({})", package.ecosystem).dimmed(),
+                    package.files,
+                    package.imports
+                );
+                for location in &package.examples {
+                    println!(
+                        "      {}:{} {}",
+                        location.path,
+                        location.line.to_string().yellow(),
+                        location.code.dimmed()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
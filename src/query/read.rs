@@ -8,9 +8,12 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::cli::OutputFormat;
+use crate::indexer::index::indexed_encoding;
 use crate::indexer::scanner::detect_language;
 use crate::parser::symbols::SymbolExtractor;
-use cgrep::output::print_json;
+use cgrep::encoding;
+use cgrep::output::{print_json, print_json2};
+use cgrep::paths;
 
 const TOKEN_THRESHOLD: u64 = 1_500;
 const FILE_SIZE_CAP: u64 = 500_000;
@@ -52,16 +55,42 @@ struct ReadRender {
     line_count: usize,
     tokens_estimate: u64,
     content: String,
+    outline: Option<Vec<OutlineNode>>,
+    expanded: Option<String>,
+    encoding: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 struct ReadPayload<'a> {
     path: &'a str,
+    /// Readable form of a `path` with escaped non-UTF-8 bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_path: Option<String>,
     mode: ReadMode,
     size_bytes: u64,
     line_count: usize,
     tokens_estimate: u64,
     content: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outline: Option<&'a [OutlineNode]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expanded: Option<&'a str>,
+    /// Source encoding when the file is not UTF-8.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'a str>,
+}
+
+/// Symbol outline node with its line range, size, and nested symbols.
+#[derive(Debug, Clone, Serialize)]
+struct OutlineNode {
+    name: String,
+    kind: String,
+    start_line: usize,
+    end_line: usize,
+    lines: usize,
+    tokens_estimate: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<OutlineNode>,
 }
 
 #[derive(Debug, Serialize)]
@@ -81,6 +110,7 @@ pub fn run(
     path: &str,
     section: Option<&str>,
     full: bool,
+    expand: Option<&str>,
     format: OutputFormat,
     compact: bool,
 ) -> Result<()> {
@@ -97,11 +127,15 @@ pub fn run(
     let rendered = if absolute.is_dir() {
         render_directory(&cwd, &absolute)?
     } else {
-        render_file(&cwd, &absolute, section, full)?
+        render_file(&cwd, &absolute, section, full, expand)?
     };
 
     match format {
-        OutputFormat::Text => {
+        OutputFormat::Text
+        | OutputFormat::Github
+        | OutputFormat::Gitlab
+        | OutputFormat::Sarif
+        | OutputFormat::Quickfix => {
             println!(
                 "# {} ({} lines, {}) [{}]",
                 rendered.path,
@@ -117,30 +151,38 @@ pub fn run(
         OutputFormat::Json => {
             let payload = ReadPayload {
                 path: &rendered.path,
+                display_path: paths::display_name(&rendered.path),
                 mode: rendered.mode,
                 size_bytes: rendered.size_bytes,
                 line_count: rendered.line_count,
                 tokens_estimate: rendered.tokens_estimate,
                 content: &rendered.content,
+                outline: rendered.outline.as_deref(),
+                expanded: rendered.expanded.as_deref(),
+                encoding: rendered.encoding.as_deref(),
             };
             print_json(&payload, compact)?;
         }
         OutputFormat::Json2 => {
             let payload = ReadJson2Payload {
                 meta: ReadJson2Meta {
-                    schema_version: "1",
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
                     command: "read",
                 },
                 result: ReadPayload {
                     path: &rendered.path,
+                    display_path: paths::display_name(&rendered.path),
                     mode: rendered.mode,
                     size_bytes: rendered.size_bytes,
                     line_count: rendered.line_count,
                     tokens_estimate: rendered.tokens_estimate,
                     content: &rendered.content,
+                    outline: rendered.outline.as_deref(),
+                    expanded: rendered.expanded.as_deref(),
+                    encoding: rendered.encoding.as_deref(),
                 },
             };
-            print_json(&payload, compact)?;
+            print_json2("read", &payload, compact)?;
         }
     }
 
@@ -148,7 +190,8 @@ pub fn run(
 }
 
 fn resolve_path(cwd: &Path, raw_path: &str) -> PathBuf {
-    let path = PathBuf::from(raw_path);
+    // Accepts the escaped form of non-UTF-8 names printed by search/read.
+    let path = paths::decode_path(raw_path);
     if path.is_absolute() {
         path
     } else {
@@ -157,23 +200,23 @@ fn resolve_path(cwd: &Path, raw_path: &str) -> PathBuf {
 }
 
 fn display_path(cwd: &Path, path: &Path) -> String {
-    if cwd != Path::new("/") {
+    if !paths::is_filesystem_root(cwd) {
         if let Ok(rel) = path.strip_prefix(cwd) {
             if rel.as_os_str().is_empty() {
                 return ".".to_string();
             }
-            return rel.display().to_string();
+            return paths::display(rel);
         }
     }
 
-    path.display().to_string()
+    paths::display(path)
 }
 
 fn render_directory(cwd: &Path, path: &Path) -> Result<ReadRender> {
     let mut entries = Vec::new();
     for entry in fs::read_dir(path).with_context(|| format!("Cannot read {}", path.display()))? {
         let entry = entry?;
-        let mut name = entry.file_name().to_string_lossy().to_string();
+        let mut name = paths::encode_path(Path::new(&entry.file_name()));
         if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
             name.push('/');
         }
@@ -194,10 +237,19 @@ fn render_directory(cwd: &Path, path: &Path) -> Result<ReadRender> {
         line_count: entries.len(),
         tokens_estimate: estimate_tokens(body.len() as u64),
         content: body,
+        outline: None,
+        expanded: None,
+        encoding: None,
     })
 }
 
-fn render_file(cwd: &Path, path: &Path, section: Option<&str>, full: bool) -> Result<ReadRender> {
+fn render_file(
+    cwd: &Path,
+    path: &Path,
+    section: Option<&str>,
+    full: bool,
+    expand: Option<&str>,
+) -> Result<ReadRender> {
     let bytes = fs::read(path).with_context(|| format!("Cannot read {}", path.display()))?;
     let size_bytes = bytes.len() as u64;
     let display = display_path(cwd, path);
@@ -210,10 +262,14 @@ fn render_file(cwd: &Path, path: &Path, section: Option<&str>, full: bool) -> Re
             line_count: 0,
             tokens_estimate: 0,
             content: String::new(),
+            outline: None,
+            expanded: None,
+            encoding: None,
         });
     }
 
-    if is_binary(&bytes) {
+    let recorded = indexed_encoding(path, &bytes);
+    let Some(decoded) = encoding::decode_with_label(&bytes, recorded.as_deref()) else {
         return Ok(ReadRender {
             path: display,
             mode: ReadMode::Binary,
@@ -221,15 +277,13 @@ fn render_file(cwd: &Path, path: &Path, section: Option<&str>, full: bool) -> Re
             line_count: 0,
             tokens_estimate: estimate_tokens(size_bytes),
             content: format!("Binary file skipped ({})", mime_from_ext(path)),
+            outline: None,
+            expanded: None,
+            encoding: None,
         });
-    }
-
-    let content = String::from_utf8(bytes).with_context(|| {
-        format!(
-            "File is not valid UTF-8 text and cannot be rendered: {}",
-            path.display()
-        )
-    })?;
+    };
+    let encoding = decoded.encoding.map(str::to_string);
+    let content = decoded.text.into_owned();
     let total_lines = line_count(&content);
 
     if let Some(raw_section) = section {
@@ -241,6 +295,32 @@ fn render_file(cwd: &Path, path: &Path, section: Option<&str>, full: bool) -> Re
             line_count: line_count(&selected),
             tokens_estimate: estimate_tokens(selected.len() as u64),
             content: selected,
+            outline: None,
+            expanded: None,
+            encoding: encoding.clone(),
+        });
+    }
+
+    if let Some(symbol) = expand {
+        let FileType::Code(language) = detect_file_type(path) else {
+            bail!("--expand needs a source file with symbols: {display}");
+        };
+        let tree = outline_tree(&content, &language);
+        let Some(target) = find_outline_node(&tree, symbol) else {
+            bail!("Symbol not found in {display}: {symbol}");
+        };
+        let rendered = render_outline(&tree, &content, Some(target));
+        let expanded = target.name.clone();
+        return Ok(ReadRender {
+            path: display,
+            mode: ReadMode::Outline,
+            size_bytes,
+            line_count: total_lines,
+            tokens_estimate: estimate_tokens(size_bytes),
+            content: rendered,
+            outline: Some(tree),
+            expanded: Some(expanded),
+            encoding: encoding.clone(),
         });
     }
 
@@ -253,6 +333,9 @@ fn render_file(cwd: &Path, path: &Path, section: Option<&str>, full: bool) -> Re
             line_count: total_lines,
             tokens_estimate: estimate_tokens(size_bytes),
             content: "Generated file skipped".to_string(),
+            outline: None,
+            expanded: None,
+            encoding: encoding.clone(),
         });
     }
 
@@ -265,15 +348,28 @@ fn render_file(cwd: &Path, path: &Path, section: Option<&str>, full: bool) -> Re
             line_count: total_lines,
             tokens_estimate: tokens,
             content,
+            outline: None,
+            expanded: None,
+            encoding: encoding.clone(),
         });
     }
 
     let file_type = detect_file_type(path);
+    let mut tree = None;
     let outline = if size_bytes > FILE_SIZE_CAP {
         fallback_head_tail(&content)
     } else {
         match &file_type {
-            FileType::Code(language) => code_outline(&content, language),
+            FileType::Code(language) => {
+                let nodes = outline_tree(&content, language);
+                if nodes.is_empty() {
+                    fallback_head_tail(&content)
+                } else {
+                    let rendered = render_outline(&nodes, &content, None);
+                    tree = Some(nodes);
+                    rendered
+                }
+            }
             FileType::Markdown => markdown_outline(&content),
             FileType::Structured => structured_outline(path, &content),
             FileType::Tabular => tabular_outline(&content),
@@ -293,6 +389,9 @@ fn render_file(cwd: &Path, path: &Path, section: Option<&str>, full: bool) -> Re
         line_count: total_lines,
         tokens_estimate: tokens,
         content: outline,
+        outline: tree,
+        expanded: None,
+        encoding: encoding.clone(),
     })
 }
 
@@ -317,42 +416,158 @@ fn detect_file_type(path: &Path) -> FileType {
     }
 }
 
-fn code_outline(content: &str, language: &str) -> String {
+/// Symbols nested by line-range containment.
+fn outline_tree(content: &str, language: &str) -> Vec<OutlineNode> {
     let extractor = SymbolExtractor::new();
-    let symbols = match extractor.extract(content, language) {
-        Ok(mut symbols) => {
-            symbols.sort_by(|a, b| {
-                a.line
-                    .cmp(&b.line)
-                    .then(a.end_line.cmp(&b.end_line))
-                    .then(a.name.cmp(&b.name))
-            });
-            symbols
-        }
-        Err(_) => Vec::new(),
+    let mut symbols = extractor.extract(content, language).unwrap_or_default();
+    // Enclosing symbols sort before the symbols they contain.
+    symbols.sort_by(|a, b| {
+        a.line
+            .cmp(&b.line)
+            .then(b.end_line.max(b.line).cmp(&a.end_line.max(a.line)))
+            .then(a.name.cmp(&b.name))
+    });
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect();
+    let range_bytes = |start: usize, end: usize| {
+        let from = line_starts.get(start - 1).copied().unwrap_or(content.len());
+        let to = line_starts.get(end).copied().unwrap_or(content.len());
+        to.saturating_sub(from) as u64
     };
 
-    if symbols.is_empty() {
-        return fallback_head_tail(content);
+    let mut roots: Vec<OutlineNode> = Vec::new();
+    let mut stack: Vec<OutlineNode> = Vec::new();
+    for symbol in symbols {
+        let start_line = symbol.line.max(1);
+        let end_line = symbol.end_line.max(start_line);
+        while stack
+            .last()
+            .is_some_and(|open| end_line > open.end_line || start_line > open.end_line)
+        {
+            close_outline_node(&mut stack, &mut roots);
+        }
+        stack.push(OutlineNode {
+            name: symbol.local_name().to_string(),
+            kind: symbol.kind.to_string(),
+            start_line,
+            end_line,
+            lines: end_line - start_line + 1,
+            tokens_estimate: estimate_tokens(range_bytes(start_line, end_line)),
+            children: Vec::new(),
+        });
+    }
+    while !stack.is_empty() {
+        close_outline_node(&mut stack, &mut roots);
+    }
+    roots
+}
+
+fn close_outline_node(stack: &mut Vec<OutlineNode>, roots: &mut Vec<OutlineNode>) {
+    let Some(node) = stack.pop() else {
+        return;
+    };
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+/// Find `symbol` by name, or by a dotted path of nested names (`Outer.inner`).
+fn find_outline_node<'a>(nodes: &'a [OutlineNode], symbol: &str) -> Option<&'a OutlineNode> {
+    let mut segments = symbol.split('.');
+    if let Some(first) = segments.next() {
+        let mut current = nodes.iter().find(|node| node.name == first);
+        for segment in segments {
+            current = current.and_then(|node| node.children.iter().find(|c| c.name == segment));
+        }
+        if current.is_some() {
+            return current;
+        }
     }
+    nodes.iter().find_map(|node| {
+        if node.name == symbol {
+            Some(node)
+        } else {
+            find_outline_node(&node.children, symbol)
+        }
+    })
+}
 
+/// Indented outline text; `expanded` has its body inlined instead of its children.
+fn render_outline(nodes: &[OutlineNode], content: &str, expanded: Option<&OutlineNode>) -> String {
+    let lines: Vec<&str> = content.lines().collect();
     let mut out = Vec::new();
-    for symbol in symbols.iter().take(MAX_OUTLINE_LINES) {
-        let end_line = symbol.end_line.max(symbol.line);
-        out.push(format!(
-            "[{}-{}] {} {}",
-            symbol.line, end_line, symbol.kind, symbol.name
-        ));
+    let mut shown = 0usize;
+    let mut omitted = 0usize;
+    render_outline_nodes(
+        nodes,
+        0,
+        &lines,
+        expanded,
+        &mut out,
+        &mut shown,
+        &mut omitted,
+    );
+    if omitted > 0 {
+        out.push(format!("... {omitted} more symbols omitted"));
     }
+    out.join("\n")
+}
 
-    if symbols.len() > out.len() {
+fn render_outline_nodes(
+    nodes: &[OutlineNode],
+    depth: usize,
+    lines: &[&str],
+    expanded: Option<&OutlineNode>,
+    out: &mut Vec<String>,
+    shown: &mut usize,
+    omitted: &mut usize,
+) {
+    for node in nodes {
+        let is_expanded = expanded.is_some_and(|target| std::ptr::eq(target, node));
+        if *shown >= MAX_OUTLINE_LINES && !is_expanded {
+            *omitted += 1 + count_outline_nodes(&node.children);
+            continue;
+        }
+        *shown += 1;
+        let indent = "  ".repeat(depth);
         out.push(format!(
-            "... {} more symbols omitted",
-            symbols.len().saturating_sub(out.len())
+            "[{}-{}] {}{} {} ({} lines, {})",
+            node.start_line,
+            node.end_line,
+            indent,
+            node.kind,
+            node.name,
+            node.lines,
+            format_token_estimate(node.tokens_estimate)
         ));
+        if is_expanded {
+            let start = node.start_line.saturating_sub(1).min(lines.len());
+            let end = node.end_line.min(lines.len());
+            for (offset, line) in lines[start..end].iter().enumerate() {
+                out.push(format!("{}  {}: {}", indent, start + offset + 1, line));
+            }
+            continue;
+        }
+        render_outline_nodes(
+            &node.children,
+            depth + 1,
+            lines,
+            expanded,
+            out,
+            shown,
+            omitted,
+        );
     }
+}
 
-    out.join("\n")
+fn count_outline_nodes(nodes: &[OutlineNode]) -> usize {
+    nodes
+        .iter()
+        .map(|node| 1 + count_outline_nodes(&node.children))
+        .sum()
 }
 
 fn markdown_outline(content: &str) -> String {
@@ -780,10 +995,6 @@ fn truncate_text(text: &str, max: usize) -> String {
     out
 }
 
-fn is_binary(bytes: &[u8]) -> bool {
-    bytes.iter().take(512).any(|b| *b == 0)
-}
-
 fn is_generated_file(name: &str, content: &str) -> bool {
     if matches!(
         name,
@@ -897,4 +1108,36 @@ mod tests {
         let out = json_outline(input);
         assert!(out.contains("[3 items]"));
     }
+
+    #[test]
+    fn code_outline_nests_members_and_expands_one_symbol() {
+        let input = "struct Cache;\n\nmod store {\n    fn get() -> u32 {\n        1\n    }\n\n    fn put() {}\n}\n";
+        let tree = outline_tree(input, "rust");
+        let parent = tree
+            .iter()
+            .find(|node| !node.children.is_empty())
+            .expect("module node with members");
+        assert_eq!((parent.start_line, parent.end_line), (3, 9));
+        let names: Vec<&str> = parent.children.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["get", "put"]);
+        assert_eq!(parent.children[0].lines, 3);
+
+        let target = find_outline_node(&tree, "get").expect("get");
+        let out = render_outline(&tree, input, Some(target));
+        assert!(out.contains("4:     fn get() -> u32 {"));
+        assert!(out.contains("6:     }"));
+        assert!(!out.contains("8:     fn put"));
+        assert!(out.contains("function put"));
+    }
+
+    #[test]
+    fn code_outline_nests_closures_under_their_function() {
+        let input = "fn run(items: &[u32]) {\n    items.iter().for_each(|item| {\n        drop(item);\n    });\n}\n";
+        let tree = outline_tree(input, "rust");
+        assert_eq!(tree.len(), 1);
+        let closure = &tree[0].children[0];
+        assert_eq!(closure.name, "<closure@L2>");
+        assert_eq!(closure.kind, "closure");
+        assert_eq!((closure.start_line, closure.end_line), (2, 4));
+    }
 }
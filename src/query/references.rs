@@ -10,10 +10,13 @@ use std::path::Path;
 
 use crate::cli::{OutputFormat, UsageSearchMode};
 use crate::indexer::scanner::FileScanner;
+use crate::parser::symbols::SymbolExtractor;
 use crate::query::ast_usage::AstUsageExtractor;
+use crate::query::callers::{scope_suffix, FileScopes};
 use crate::query::changed_files::ChangedFiles;
 use crate::query::index_filter::{find_files_with_content, read_scanned_files};
-use cgrep::output::print_json;
+use cgrep::output::{print_json, print_quickfix, QuickfixEntry};
+use cgrep::paths;
 use cgrep::utils::get_root_with_index;
 
 /// Reference result for JSON output
@@ -23,6 +26,9 @@ struct ReferenceResult {
     line: usize,
     column: usize,
     code: String,
+    /// Qualified name of the symbol holding the reference, closures included.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
 }
 
 /// Run the references command
@@ -36,10 +42,10 @@ pub fn run(
     compact: bool,
 ) -> Result<()> {
     let search_root = match path {
-        Some(p) => std::path::PathBuf::from(p).canonicalize()?,
-        None => std::env::current_dir()?.canonicalize()?,
+        Some(p) => paths::canonicalize(Path::new(p))?,
+        None => paths::canonicalize(&std::env::current_dir()?)?,
     };
-    let workspace_root = std::env::current_dir()?.canonicalize()?;
+    let workspace_root = paths::canonicalize(&std::env::current_dir()?)?;
     let index_root = get_root_with_index(&search_root);
     let files = match find_files_with_content(&index_root, name, Some(&search_root))? {
         Some(indexed_paths) => read_scanned_files(&indexed_paths),
@@ -58,6 +64,7 @@ pub fn run(
 
     let mut results: Vec<ReferenceResult> = Vec::new();
     let mut ast = AstUsageExtractor::new();
+    let extractor = SymbolExtractor::new();
 
     for file in &files {
         let scope_path = scope_relative_path(&file.path, &search_root);
@@ -82,6 +89,7 @@ pub fn run(
             })
         };
 
+        let mut scopes = FileScopes::new(&extractor, file);
         if let Some(matches) = ast_matches {
             for m in matches {
                 let code = file
@@ -99,6 +107,7 @@ pub fn run(
                     line: m.line,
                     column: m.column,
                     code,
+                    scope: scopes.at(m.line),
                 });
                 if results.len() >= max_results {
                     break;
@@ -121,6 +130,7 @@ pub fn run(
                     line: line_num + 1,
                     column: mat.start() + 1,
                     code: line.trim().to_string(),
+                    scope: scopes.at(line_num + 1),
                 });
 
                 if results.len() >= max_results {
@@ -138,7 +148,19 @@ pub fn run(
         OutputFormat::Json | OutputFormat::Json2 => {
             print_json(&results, compact)?;
         }
-        OutputFormat::Text => {
+        OutputFormat::Quickfix => {
+            let entries: Vec<QuickfixEntry<'_>> = results
+                .iter()
+                .map(|result| QuickfixEntry {
+                    path: &result.path,
+                    line: result.line,
+                    column: result.column,
+                    text: &result.code,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
             if results.is_empty() {
                 println!("{} No references found for: {}", "✗".red(), name.yellow());
             } else {
@@ -149,11 +171,12 @@ pub fn run(
                 );
                 for result in &results {
                     println!(
-                        "  {}:{}:{} {}",
+                        "  {}:{}:{} {}{}",
                         result.path.cyan(),
                         result.line.to_string().yellow(),
                         result.column.to_string().dimmed(),
-                        result.code.dimmed()
+                        result.code.dimmed(),
+                        scope_suffix(result.scope.as_deref())
                     );
                 }
                 println!(
@@ -169,8 +192,7 @@ pub fn run(
 }
 
 fn scope_relative_path(full_path: &Path, search_root: &Path) -> String {
-    if let Ok(rel) = full_path.strip_prefix(search_root) {
-        let rendered = rel.display().to_string();
+    if let Some(rendered) = paths::relative_display(full_path, search_root) {
         if !rendered.is_empty() {
             return rendered;
         }
@@ -184,9 +206,8 @@ fn scope_relative_path(full_path: &Path, search_root: &Path) -> String {
 }
 
 fn workspace_display_path(full_path: &Path, workspace_root: &Path) -> String {
-    if workspace_root != Path::new("/") {
-        if let Ok(rel) = full_path.strip_prefix(workspace_root) {
-            let rendered = rel.display().to_string();
+    if !paths::is_filesystem_root(workspace_root) {
+        if let Some(rendered) = paths::relative_display(full_path, workspace_root) {
             if !rendered.is_empty() {
                 return rendered;
             }
@@ -194,5 +215,5 @@ fn workspace_display_path(full_path: &Path, workspace_root: &Path) -> String {
         }
     }
 
-    full_path.display().to_string()
+    paths::display(full_path)
 }
@@ -0,0 +1,392 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep rename-check`: validate a rename before making it.
+//!
+//! Every site of `old` is listed with the scope it sits in, i.e. the names of
+//! its enclosing symbols. Sites of `new` in the same files are then compared
+//! against those scopes:
+//!
+//! - `collision`: `new` is already defined in a scope that defines or uses
+//!   `old`, so the rename would declare the name twice.
+//! - `shadowing`: `new` appears in a scope nested in or around one that uses
+//!   `old`, so after the rename one name may hide the other.
+//! - `existing`: `new` is defined in a file without `old`; not a conflict by
+//!   itself, but imports of both may become ambiguous.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::{FileScanner, ScannedFile};
+use crate::parser::symbols::{Symbol, SymbolExtractor};
+use crate::query::ast_usage::{AstUsageExtractor, UsageMatch};
+use crate::query::index_filter::{find_files_with_content, read_scanned_files};
+use crate::query::symbol_header::enclosing_scopes;
+use cgrep::output::{print_json, print_quickfix, QuickfixEntry};
+use cgrep::paths;
+use cgrep::utils::get_root_with_index;
+
+/// A site of the old name that the rename would change.
+#[derive(Debug, Serialize)]
+struct RenameSite {
+    path: String,
+    line: usize,
+    column: usize,
+    code: String,
+    /// Enclosing symbols, outermost first (empty at file level)
+    scope: Vec<String>,
+    /// Whether the symbol is declared here
+    definition: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ConflictKind {
+    Collision,
+    Shadowing,
+    Existing,
+}
+
+impl ConflictKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ConflictKind::Collision => "collision",
+            ConflictKind::Shadowing => "shadowing",
+            ConflictKind::Existing => "existing",
+        }
+    }
+}
+
+/// A site of the new name that the rename could clash with.
+#[derive(Debug, Serialize)]
+struct RenameConflict {
+    kind: ConflictKind,
+    path: String,
+    line: usize,
+    column: usize,
+    code: String,
+    scope: Vec<String>,
+    /// Line of the `old` site it clashes with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_line: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct RenameReport<'a> {
+    old: &'a str,
+    new: &'a str,
+    /// No collisions or shadowing; `existing` definitions do not count
+    safe: bool,
+    references: Vec<RenameSite>,
+    conflicts: Vec<RenameConflict>,
+}
+
+/// A name's sites in one file, with the scope and whether it is declared there.
+struct Site {
+    usage: UsageMatch,
+    scope: Vec<String>,
+    definition: bool,
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Whether code in one scope can see a name declared in the other: the same
+/// scope, or one nested inside the other.
+fn scopes_overlap(a: &[String], b: &[String]) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+fn find_sites(
+    ast: &mut AstUsageExtractor,
+    file: &ScannedFile,
+    symbols: &[Symbol],
+    name: &str,
+    re: &Regex,
+) -> Vec<Site> {
+    let mut usages = file
+        .language
+        .as_deref()
+        .and_then(|lang| ast.references(&file.content, lang, name, usize::MAX))
+        .filter(|matches| !matches.is_empty())
+        .unwrap_or_else(|| {
+            file.content
+                .lines()
+                .enumerate()
+                .flat_map(|(index, line)| {
+                    re.find_iter(line).map(move |mat| UsageMatch {
+                        line: index + 1,
+                        column: mat.start() + 1,
+                    })
+                })
+                .collect()
+        });
+    usages.sort_by_key(|usage| (usage.line, usage.column));
+    usages
+        .into_iter()
+        .map(|usage| Site {
+            scope: enclosing_scopes(symbols, usage.line)
+                .into_iter()
+                .map(|symbol| symbol.name.clone())
+                .collect(),
+            definition: symbols
+                .iter()
+                .any(|symbol| symbol.name == name && symbol.line == usage.line),
+            usage,
+        })
+        .collect()
+}
+
+/// Run the rename-check command
+pub fn run(
+    old: &str,
+    new: &str,
+    path: Option<&str>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    if !is_identifier(new) {
+        bail!("`{new}` is not a valid identifier");
+    }
+    if old == new {
+        bail!("Old and new names are the same");
+    }
+    let cwd = paths::canonicalize(&std::env::current_dir()?)?;
+    let search_root = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => cwd.clone(),
+    };
+    let index_root = get_root_with_index(&search_root);
+    let scanned = match (
+        find_files_with_content(&index_root, old, Some(&search_root))?,
+        find_files_with_content(&index_root, new, Some(&search_root))?,
+    ) {
+        (Some(mut old_paths), Some(new_paths)) => {
+            old_paths.extend(new_paths);
+            read_scanned_files(&old_paths)
+        }
+        _ => FileScanner::new(&search_root).scan()?,
+    };
+    let files: BTreeMap<PathBuf, ScannedFile> = scanned
+        .into_iter()
+        .map(|file| (file.path.clone(), file))
+        .collect();
+
+    let old_re = Regex::new(&format!(r"\b{}\b", regex::escape(old)))?;
+    let new_re = Regex::new(&format!(r"\b{}\b", regex::escape(new)))?;
+    let extractor = SymbolExtractor::new();
+    let mut ast = AstUsageExtractor::new();
+    let mut references: Vec<RenameSite> = Vec::new();
+    let mut conflicts: Vec<RenameConflict> = Vec::new();
+    for file in files.values() {
+        if !old_re.is_match(&file.content) && !new_re.is_match(&file.content) {
+            continue;
+        }
+        let symbols = file
+            .language
+            .as_deref()
+            .and_then(|lang| extractor.extract(&file.content, lang).ok())
+            .unwrap_or_default();
+        let old_sites = find_sites(&mut ast, file, &symbols, old, &old_re);
+        let new_sites = find_sites(&mut ast, file, &symbols, new, &new_re);
+        if old_sites.is_empty() && new_sites.is_empty() {
+            continue;
+        }
+        let lines: Vec<&str> = file.content.lines().collect();
+        let code = |line: usize| lines.get(line - 1).unwrap_or(&"").trim().to_string();
+        let display_path =
+            paths::relative_display(&file.path, &cwd).unwrap_or_else(|| paths::display(&file.path));
+
+        for site in &new_sites {
+            let (kind, old_line) = if old_sites.is_empty() {
+                if !site.definition {
+                    continue;
+                }
+                (ConflictKind::Existing, None)
+            } else if let Some(clash) = old_sites
+                .iter()
+                .find(|old_site| site.definition && old_site.scope == site.scope)
+            {
+                (ConflictKind::Collision, Some(clash.usage.line))
+            } else if let Some(clash) = old_sites
+                .iter()
+                .filter(|old_site| scopes_overlap(&old_site.scope, &site.scope))
+                .min_by_key(|old_site| old_site.scope.len().abs_diff(site.scope.len()))
+            {
+                (ConflictKind::Shadowing, Some(clash.usage.line))
+            } else {
+                continue;
+            };
+            conflicts.push(RenameConflict {
+                kind,
+                path: display_path.clone(),
+                line: site.usage.line,
+                column: site.usage.column,
+                code: code(site.usage.line),
+                scope: site.scope.clone(),
+                old_line,
+            });
+        }
+        references.extend(old_sites.into_iter().map(|site| RenameSite {
+            path: display_path.clone(),
+            line: site.usage.line,
+            column: site.usage.column,
+            code: code(site.usage.line),
+            scope: site.scope,
+            definition: site.definition,
+        }));
+    }
+    let safe = conflicts
+        .iter()
+        .all(|conflict| conflict.kind == ConflictKind::Existing);
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(
+            &RenameReport {
+                old,
+                new,
+                safe,
+                references,
+                conflicts,
+            },
+            compact,
+        )?,
+        OutputFormat::Quickfix => {
+            let texts: Vec<String> = conflicts
+                .iter()
+                .map(|conflict| format!("{}: {}", conflict.kind.as_str(), conflict.code))
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = references
+                .iter()
+                .map(|site| QuickfixEntry {
+                    path: &site.path,
+                    line: site.line,
+                    column: site.column,
+                    text: &site.code,
+                })
+                .chain(
+                    conflicts
+                        .iter()
+                        .zip(&texts)
+                        .map(|(conflict, text)| QuickfixEntry {
+                            path: &conflict.path,
+                            line: conflict.line,
+                            column: conflict.column,
+                            text,
+                        }),
+                )
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            print_text(old, new, safe, &references, &conflicts)
+        }
+    }
+
+    Ok(())
+}
+
+fn scope_label(scope: &[String]) -> String {
+    if scope.is_empty() {
+        "file scope".to_string()
+    } else {
+        scope.join("::")
+    }
+}
+
+fn print_text(
+    old: &str,
+    new: &str,
+    safe: bool,
+    references: &[RenameSite],
+    conflicts: &[RenameConflict],
+) {
+    if references.is_empty() {
+        println!("{} No references found for: {}", "✗".red(), old.yellow());
+        return;
+    }
+    println!(
+        "\n{} Renaming {} → {}\n",
+        "🔍".cyan(),
+        old.yellow(),
+        new.yellow()
+    );
+    for site in references {
+        println!(
+            "  {}:{}:{} {} {}",
+            site.path.cyan(),
+            site.line.to_string().yellow(),
+            site.column.to_string().dimmed(),
+            site.code.dimmed(),
+            format!("[{}]", scope_label(&site.scope)).dimmed()
+        );
+    }
+    if !conflicts.is_empty() {
+        println!();
+    }
+    for conflict in conflicts {
+        let kind = match conflict.kind {
+            ConflictKind::Collision => conflict.kind.as_str().red().bold(),
+            ConflictKind::Shadowing => conflict.kind.as_str().yellow(),
+            ConflictKind::Existing => conflict.kind.as_str().blue(),
+        };
+        let clash = conflict
+            .old_line
+            .map(|line| format!(", {old} at line {line}"))
+            .unwrap_or_default();
+        println!(
+            "  {} {}:{} {} {}",
+            kind,
+            conflict.path.cyan(),
+            conflict.line.to_string().yellow(),
+            conflict.code.dimmed(),
+            format!("[{}{}]", scope_label(&conflict.scope), clash).dimmed()
+        );
+    }
+    if safe {
+        println!(
+            "\n{} {} references can be renamed without conflicts",
+            "✓".green(),
+            references.len().to_string().cyan()
+        );
+    } else {
+        println!(
+            "\n{} {} references, {} conflicts",
+            "✗".red(),
+            references.len(),
+            conflicts
+                .iter()
+                .filter(|conflict| conflict.kind != ConflictKind::Existing)
+                .count()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_overlap_when_nested() {
+        let outer = vec!["Config".to_string()];
+        let inner = vec!["Config".to_string(), "load".to_string()];
+        let other = vec!["Cache".to_string()];
+        assert!(scopes_overlap(&outer, &inner));
+        assert!(scopes_overlap(&[], &inner));
+        assert!(!scopes_overlap(&other, &inner));
+        assert!(is_identifier("_load2"));
+        assert!(!is_identifier("2load"));
+        assert!(!is_identifier("load-all"));
+    }
+}
@@ -0,0 +1,469 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep review`: annotate a diff for code review.
+//!
+//! Each hunk of `git diff <range>` is mapped to the innermost symbols it
+//! touches in the new version of the file. Every touched symbol comes with
+//! its definition as of that version and, for functions and methods, its call
+//! sites in the working tree, so a reviewer (or review agent) sees what a
+//! change affects without separate `definition` and `callers` lookups.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::{OutputFormat, UsageSearchMode};
+use crate::indexer::scanner::{detect_language, FileScanner, ScannedFile};
+use crate::parser::symbols::{Symbol, SymbolExtractor, SymbolKind};
+use crate::query::ast_usage::AstUsageExtractor;
+use crate::query::callers::find_callers;
+use crate::query::changed_files::{git_repo_root, parse_patch_hunks, PatchHunk};
+use crate::query::index_filter::{find_files_with_content, read_scanned_files};
+use cgrep::output::{print_json, print_json2, print_quickfix, QuickfixEntry};
+use cgrep::paths;
+use cgrep::utils::get_root_with_index;
+
+/// Definition lines included per symbol.
+const MAX_DEFINITION_LINES: usize = 40;
+
+#[derive(Debug, Serialize)]
+struct ReviewCaller {
+    path: String,
+    line: usize,
+    code: String,
+}
+
+/// A symbol a hunk touches, as defined after the change.
+#[derive(Debug, Serialize)]
+struct ReviewSymbol {
+    name: String,
+    kind: String,
+    line: usize,
+    end_line: usize,
+    definition: String,
+    /// The definition is longer than the lines included
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
+    /// First call sites in the working tree (functions and methods only)
+    callers: Vec<ReviewCaller>,
+    callers_total: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ReviewHunk {
+    old_start: usize,
+    old_lines: usize,
+    new_start: usize,
+    new_lines: usize,
+    /// Names of the innermost symbols the hunk touches
+    symbols: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReviewFile {
+    path: String,
+    hunks: Vec<ReviewHunk>,
+    symbols: Vec<ReviewSymbol>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReviewJson2Meta<'a> {
+    schema_version: &'static str,
+    command: &'static str,
+    range: &'a str,
+    files: usize,
+    hunks: usize,
+    symbols: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ReviewJson2Payload<'a> {
+    meta: ReviewJson2Meta<'a>,
+    files: &'a [ReviewFile],
+}
+
+/// The revision whose files the hunks' new side refers to, or `None` for
+/// the working tree.
+fn head_revision(range: &str) -> Option<&str> {
+    let (_, head) = range.split_once("...").or_else(|| range.split_once(".."))?;
+    Some(if head.is_empty() { "HEAD" } else { head })
+}
+
+fn git_output(repo_root: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_root)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Symbols that own code for review purposes; fields, locals, and closures
+/// roll up into their parent.
+fn is_reviewable(symbol: &Symbol) -> bool {
+    !matches!(symbol.kind, SymbolKind::Variable | SymbolKind::Property) && !symbol.is_anonymous()
+}
+
+/// Innermost reviewable symbols overlapping lines `start..=end`.
+fn touched_symbols(symbols: &[Symbol], start: usize, end: usize) -> Vec<&Symbol> {
+    let overlapping: Vec<&Symbol> = symbols
+        .iter()
+        .filter(|symbol| is_reviewable(symbol))
+        .filter(|symbol| symbol.line <= end && start <= symbol.end_line)
+        .collect();
+    overlapping
+        .iter()
+        .copied()
+        .filter(|outer| {
+            !overlapping.iter().any(|inner| {
+                (inner.line, inner.end_line) != (outer.line, outer.end_line)
+                    && outer.line <= inner.line
+                    && inner.end_line <= outer.end_line
+            })
+        })
+        .collect()
+}
+
+/// Files that may call a function, from the index when there is one.
+struct CallerFiles {
+    repo_root: PathBuf,
+    index_root: PathBuf,
+    /// Every file, scanned once when there is no index
+    scanned: Option<Vec<ScannedFile>>,
+    ast: AstUsageExtractor,
+    found: HashMap<String, Vec<ReviewCaller>>,
+}
+
+impl CallerFiles {
+    fn callers(&mut self, name: &str, cwd: &Path) -> Result<&[ReviewCaller]> {
+        if !self.found.contains_key(name) {
+            let indexed;
+            let files: &[ScannedFile] =
+                match find_files_with_content(&self.index_root, name, Some(&self.repo_root))? {
+                    Some(indexed_paths) => {
+                        indexed = read_scanned_files(&indexed_paths);
+                        &indexed
+                    }
+                    None => {
+                        if self.scanned.is_none() {
+                            self.scanned = Some(FileScanner::new(&self.repo_root).scan()?);
+                        }
+                        self.scanned.as_deref().unwrap_or_default()
+                    }
+                };
+            let mut callers: Vec<ReviewCaller> = find_callers(
+                files,
+                &self.repo_root,
+                name,
+                UsageSearchMode::Auto,
+                &mut self.ast,
+            )?
+            .into_iter()
+            .map(|caller| ReviewCaller {
+                path: paths::relative_display(&self.repo_root.join(&caller.path), cwd)
+                    .unwrap_or(caller.path),
+                line: caller.line,
+                code: caller.code,
+            })
+            .collect();
+            callers.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+            self.found.insert(name.to_string(), callers);
+        }
+        Ok(self.found.get(name).map(Vec::as_slice).unwrap_or_default())
+    }
+}
+
+/// Run the review command
+pub fn run(
+    range: &str,
+    path: Option<&str>,
+    max_callers: usize,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let cwd = paths::canonicalize(&std::env::current_dir()?)?;
+    let scope = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => cwd.clone(),
+    };
+    let repo_root = paths::canonicalize(&git_repo_root(&scope, "review")?)?;
+    let pathspec = paths::relative_display(&scope, &repo_root).unwrap_or_default();
+
+    let mut diff_args = vec![
+        "diff",
+        "--no-color",
+        "--no-ext-diff",
+        "--unified=0",
+        range,
+        "--",
+    ];
+    if !pathspec.is_empty() {
+        diff_args.push(&pathspec);
+    }
+    let diff = git_output(&repo_root, &diff_args)?;
+    let head = head_revision(range);
+
+    let mut by_path: BTreeMap<String, Vec<PatchHunk>> = BTreeMap::new();
+    for hunk in parse_patch_hunks(&diff) {
+        by_path.entry(hunk.path.clone()).or_default().push(hunk);
+    }
+
+    let extractor = SymbolExtractor::new();
+    let mut caller_files = CallerFiles {
+        index_root: get_root_with_index(&repo_root),
+        repo_root: repo_root.clone(),
+        scanned: None,
+        ast: AstUsageExtractor::new(),
+        found: HashMap::new(),
+    };
+    let mut files: Vec<ReviewFile> = Vec::new();
+    for (rel_path, hunks) in by_path {
+        let content = match head {
+            Some(rev) => git_output(&repo_root, &["show", &format!("{rev}:{rel_path}")]).ok(),
+            None => std::fs::read_to_string(repo_root.join(&rel_path)).ok(),
+        };
+        let symbols = content
+            .as_deref()
+            .zip(
+                Path::new(&rel_path)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .and_then(detect_language),
+            )
+            .and_then(|(content, language)| extractor.extract(content, &language).ok())
+            .unwrap_or_default();
+        let lines: Vec<&str> = content.as_deref().unwrap_or_default().lines().collect();
+
+        let mut review_hunks = Vec::with_capacity(hunks.len());
+        let mut touched: Vec<&Symbol> = Vec::new();
+        for hunk in &hunks {
+            // A pure deletion sits after line `new_start`.
+            let start = hunk.new_start.max(1);
+            let end = start + hunk.new_lines.saturating_sub(1);
+            let hunk_symbols = touched_symbols(&symbols, start, end);
+            for symbol in &hunk_symbols {
+                if !touched
+                    .iter()
+                    .any(|seen| seen.line == symbol.line && seen.name == symbol.name)
+                {
+                    touched.push(symbol);
+                }
+            }
+            review_hunks.push(ReviewHunk {
+                old_start: hunk.old_start,
+                old_lines: hunk.old_lines,
+                new_start: hunk.new_start,
+                new_lines: hunk.new_lines,
+                symbols: hunk_symbols
+                    .iter()
+                    .map(|symbol| symbol.name.clone())
+                    .collect(),
+            });
+        }
+
+        let mut review_symbols = Vec::with_capacity(touched.len());
+        for symbol in touched {
+            let last = symbol
+                .end_line
+                .min(symbol.line + MAX_DEFINITION_LINES - 1)
+                .min(lines.len());
+            let definition = lines
+                .get(symbol.line.saturating_sub(1)..last)
+                .unwrap_or_default()
+                .join("\n");
+            let callers = if matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method) {
+                caller_files.callers(&symbol.name, &cwd)?
+            } else {
+                &[]
+            };
+            review_symbols.push(ReviewSymbol {
+                name: symbol.name.clone(),
+                kind: symbol.kind.to_string(),
+                line: symbol.line,
+                end_line: symbol.end_line,
+                definition,
+                truncated: symbol.end_line > last,
+                callers: callers
+                    .iter()
+                    .take(max_callers)
+                    .map(|caller| ReviewCaller {
+                        path: caller.path.clone(),
+                        line: caller.line,
+                        code: caller.code.clone(),
+                    })
+                    .collect(),
+                callers_total: callers.len(),
+            });
+        }
+
+        files.push(ReviewFile {
+            path: paths::relative_display(&repo_root.join(&rel_path), &cwd).unwrap_or(rel_path),
+            hunks: review_hunks,
+            symbols: review_symbols,
+        });
+    }
+
+    match format {
+        OutputFormat::Json => print_json(&files, compact)?,
+        OutputFormat::Json2 => {
+            let payload = ReviewJson2Payload {
+                meta: ReviewJson2Meta {
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
+                    command: "review",
+                    range,
+                    files: files.len(),
+                    hunks: files.iter().map(|file| file.hunks.len()).sum(),
+                    symbols: files.iter().map(|file| file.symbols.len()).sum(),
+                },
+                files: &files,
+            };
+            print_json2("review", &payload, compact)?;
+        }
+        OutputFormat::Quickfix => {
+            let texts: Vec<Vec<String>> = files
+                .iter()
+                .map(|file| {
+                    file.symbols
+                        .iter()
+                        .map(|symbol| {
+                            format!(
+                                "{} {} ({} callers)",
+                                symbol.kind, symbol.name, symbol.callers_total
+                            )
+                        })
+                        .collect()
+                })
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = files
+                .iter()
+                .zip(&texts)
+                .flat_map(|(file, texts)| {
+                    file.symbols
+                        .iter()
+                        .zip(texts)
+                        .map(|(symbol, text)| QuickfixEntry {
+                            path: &file.path,
+                            line: symbol.line,
+                            column: 1,
+                            text,
+                        })
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            print_text(range, &files)
+        }
+    }
+
+    Ok(())
+}
+
+fn print_text(range: &str, files: &[ReviewFile]) {
+    if files.is_empty() {
+        println!("{} No changes in {}", "✗".red(), range.yellow());
+        return;
+    }
+    println!(
+        "\n{} Reviewing {} ({} files)\n",
+        "🔍".cyan(),
+        range.yellow(),
+        files.len().to_string().cyan()
+    );
+    for file in files {
+        println!("{}", file.path.cyan().bold());
+        for hunk in &file.hunks {
+            println!(
+                "  {} {}",
+                format!(
+                    "@@ -{},{} +{},{} @@",
+                    hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+                )
+                .dimmed(),
+                hunk.symbols.join(", ")
+            );
+        }
+        for symbol in &file.symbols {
+            println!(
+                "\n  {} {} {}",
+                symbol.kind.to_string().dimmed(),
+                symbol.name.yellow().bold(),
+                format!("({}-{})", symbol.line, symbol.end_line).dimmed()
+            );
+            for line in symbol.definition.lines().take(3) {
+                println!("      {}", line.dimmed());
+            }
+            if symbol.callers_total > 0 {
+                println!("    {} {} callers", "↳".cyan(), symbol.callers_total);
+            }
+            for caller in &symbol.callers {
+                println!(
+                    "      {}:{} {}",
+                    caller.path,
+                    caller.line.to_string().yellow(),
+                    caller.code.dimmed()
+                );
+            }
+        }
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, kind: SymbolKind, line: usize, end_line: usize) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            kind,
+            line,
+            column: 1,
+            end_line,
+            byte_start: None,
+            byte_end: None,
+            scope: None,
+            doc: None,
+            signature: None,
+            decorators: Vec::new(),
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn hunks_map_to_innermost_touched_symbols() {
+        let symbols = vec![
+            symbol("Store", SymbolKind::Class, 1, 20),
+            symbol("load", SymbolKind::Method, 2, 8),
+            symbol("save", SymbolKind::Method, 10, 18),
+            symbol("path", SymbolKind::Property, 19, 19),
+        ];
+        let names = |start, end| -> Vec<String> {
+            touched_symbols(&symbols, start, end)
+                .iter()
+                .map(|symbol| symbol.name.clone())
+                .collect()
+        };
+        assert_eq!(names(3, 4), vec!["load"]);
+        assert_eq!(names(7, 11), vec!["load", "save"]);
+        assert_eq!(names(19, 19), vec!["Store"]);
+        assert!(names(25, 30).is_empty());
+
+        assert_eq!(head_revision("main..feature"), Some("feature"));
+        assert_eq!(head_revision("main..."), Some("HEAD"));
+        assert_eq!(head_revision("HEAD~1"), None);
+    }
+}
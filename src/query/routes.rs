@@ -0,0 +1,889 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep routes`: list the HTTP endpoints a codebase declares.
+//!
+//! Routes are read from the syntax tree, per framework:
+//!
+//! - axum and actix-web: `.route("/p", get(h).post(h2))` and
+//!   `.route("/p", web::get().to(h))` registrations, plus the
+//!   `#[get("/p")]`-style attributes actix-web and Rocket put on handlers.
+//! - Flask and FastAPI: `@app.route("/p", methods=[...])`,
+//!   `@router.get("/p")`, and `api_route`, with the `prefix`/`url_prefix`
+//!   of an `APIRouter` or `Blueprint` created in the same file.
+//! - Express: `app.get("/p", h)` (and the other verbs, `all`) on any object,
+//!   and `router.route("/p").get(h).post(h2)` chains.
+//! - Spring: `@GetMapping`-style and `@RequestMapping` annotations on
+//!   methods, joined with the `@RequestMapping` path of the class.
+//!
+//! Only literal path strings count; a path built at runtime is not a route
+//! here. Handlers named by a plain function are located in the scanned
+//! files, preferring the file that registers the route.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::{FileScanner, ScannedFile};
+use crate::parser::ast::is_string;
+use crate::parser::languages::LANGUAGES;
+use crate::parser::symbols::{SymbolExtractor, SymbolKind};
+use cgrep::output::{print_json, print_json2, print_quickfix, QuickfixEntry};
+use cgrep::paths;
+
+const HTTP_METHODS: &[&str] = &[
+    "GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS", "TRACE", "CONNECT",
+];
+
+/// Method reported for routes that accept every method.
+const ANY_METHOD: &str = "ANY";
+
+/// `router = APIRouter(prefix="/users")` or `Blueprint(..., url_prefix=...)`.
+static PYTHON_ROUTER_PREFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?m)^\s*(?P<name>\w+)\s*=\s*(?:\w+\.)*(?:APIRouter|Blueprint)\((?s:[^)]*?)\b(?:url_)?prefix\s*=\s*["'](?P<prefix>[^"']*)["']"#,
+    )
+    .expect("valid router prefix")
+});
+
+/// One HTTP endpoint.
+#[derive(Debug, Clone, Serialize)]
+struct Route {
+    /// Upper-case HTTP method, or `ANY`.
+    method: String,
+    /// Path pattern as written (`/users/{id}`, `/users/:id`).
+    route: String,
+    framework: &'static str,
+    /// Handler function, when the route names one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    handler: Option<String>,
+    /// File and line registering the route.
+    path: String,
+    line: usize,
+    /// Where the handler is defined, when found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    handler_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    handler_line: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct RoutesJson2Meta<'a> {
+    schema_version: &'static str,
+    command: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    method: Option<&'a str>,
+    total: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct RoutesJson2Payload<'a> {
+    meta: RoutesJson2Meta<'a>,
+    routes: &'a [Route],
+}
+
+/// Run the routes command.
+pub fn run(
+    query: Option<&str>,
+    path: Option<&str>,
+    method: Option<&str>,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let search_root = match path {
+        Some(p) => {
+            paths::canonicalize(Path::new(p)).with_context(|| format!("Path not found: {p}"))?
+        }
+        None => paths::canonicalize(&std::env::current_dir()?)?,
+    };
+    let method = method.map(|method| method.trim().to_ascii_uppercase());
+    let query = query.map(str::trim).filter(|query| !query.is_empty());
+
+    let files = FileScanner::new(&search_root).scan()?;
+    let mut routes = Vec::new();
+    for file in &files {
+        let Some(language) = file.language.as_deref() else {
+            continue;
+        };
+        if !mentions_routes(&file.content) {
+            continue;
+        }
+        routes.extend(file_routes(
+            &file.content,
+            language,
+            &display_path(&file.path, &search_root),
+        ));
+    }
+    routes.retain(|route| {
+        method
+            .as_deref()
+            .is_none_or(|method| route.method == method || route.method == ANY_METHOD)
+            && query.is_none_or(|query| route_matches(&route.route, query))
+    });
+    resolve_handlers(&mut routes, &files, &search_root);
+    routes.sort_by(|a, b| {
+        (&a.route, &a.method, &a.path, a.line).cmp(&(&b.route, &b.method, &b.path, b.line))
+    });
+
+    match format {
+        OutputFormat::Json => print_json(&routes, compact)?,
+        OutputFormat::Json2 => {
+            let payload = RoutesJson2Payload {
+                meta: RoutesJson2Meta {
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
+                    command: "routes",
+                    query,
+                    method: method.as_deref(),
+                    total: routes.len(),
+                },
+                routes: &routes,
+            };
+            print_json2("routes", &payload, compact)?;
+        }
+        OutputFormat::Quickfix => {
+            let texts: Vec<String> = routes.iter().map(describe).collect();
+            let entries: Vec<QuickfixEntry<'_>> = routes
+                .iter()
+                .zip(&texts)
+                .map(|(route, text)| QuickfixEntry {
+                    path: &route.path,
+                    line: route.line,
+                    column: 1,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            print_text(&routes)
+        }
+    }
+    Ok(())
+}
+
+fn display_path(path: &Path, root: &Path) -> String {
+    paths::relative_display(path, root).unwrap_or_else(|| path.display().to_string())
+}
+
+fn describe(route: &Route) -> String {
+    match &route.handler {
+        Some(handler) => format!("{} {} -> {}", route.method, route.route, handler),
+        None => format!("{} {}", route.method, route.route),
+    }
+}
+
+fn print_text(routes: &[Route]) {
+    if routes.is_empty() {
+        println!("{} No routes found", "✗".red());
+        return;
+    }
+    let width = routes
+        .iter()
+        .map(|route| route.route.chars().count())
+        .max()
+        .unwrap_or_default();
+    println!();
+    for route in routes {
+        let location = match (&route.handler_path, route.handler_line) {
+            (Some(path), Some(line)) if *path != route.path || line != route.line => {
+                format!("{}:{} ({}:{})", route.path, route.line, path, line)
+            }
+            _ => format!("{}:{}", route.path, route.line),
+        };
+        println!(
+            "  {:<7} {:<width$}  {} {}",
+            route.method.yellow().bold(),
+            route.route,
+            route.handler.as_deref().unwrap_or("-").cyan(),
+            location.dimmed(),
+        );
+    }
+    println!(
+        "\n{} Found {} routes",
+        "✓".green(),
+        routes.len().to_string().cyan()
+    );
+}
+
+/// Cheap check before parsing: every supported registration spells one of
+/// these.
+fn mentions_routes(content: &str) -> bool {
+    [
+        "route", "Mapping", ".get(", ".post(", ".put(", ".delete(", ".patch(", "#[get", "#[post",
+    ]
+    .iter()
+    .any(|marker| content.contains(marker))
+}
+
+/// Whether `route` serves `query`: the same segments, where a path parameter
+/// (`{id}`, `:id`, `<id>`) matches any one segment, or `query` as a
+/// substring of the pattern.
+fn route_matches(route: &str, query: &str) -> bool {
+    if route.contains(query) {
+        return true;
+    }
+    let segments = |path: &str| -> Vec<String> {
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let route_segments = segments(route);
+    let query_segments = segments(query);
+    route_segments.len() == query_segments.len()
+        && route_segments
+            .iter()
+            .zip(&query_segments)
+            .all(|(pattern, actual)| pattern == actual || is_path_param(pattern))
+}
+
+fn is_path_param(segment: &str) -> bool {
+    segment.starts_with(':')
+        || segment.starts_with('*')
+        || (segment.starts_with('{') && segment.ends_with('}'))
+        || (segment.starts_with('<') && segment.ends_with('>'))
+}
+
+/// Routes declared in one file.
+fn file_routes(content: &str, language: &str, rel_path: &str) -> Vec<Route> {
+    let Some(mut parser) = LANGUAGES.parser(language) else {
+        return Vec::new();
+    };
+    let Some(tree) = parser.parse(content, None) else {
+        return Vec::new();
+    };
+    let mut finder = RouteFinder {
+        source: content.as_bytes(),
+        rel_path,
+        framework_hint: framework_hint(content, language),
+        python_prefixes: if language == "python" {
+            PYTHON_ROUTER_PREFIX
+                .captures_iter(content)
+                .map(|caps| (caps["name"].to_string(), caps["prefix"].to_string()))
+                .collect()
+        } else {
+            HashMap::new()
+        },
+        routes: Vec::new(),
+    };
+    finder.visit(tree.root_node(), language);
+    finder.routes
+}
+
+/// Framework named in the file's imports, to label routes whose syntax
+/// several frameworks share.
+fn framework_hint(content: &str, language: &str) -> &'static str {
+    match language {
+        "python" if content.contains("fastapi") => "fastapi",
+        "python" => "flask",
+        "rust" if content.contains("rocket") => "rocket",
+        "rust" if content.contains("actix") => "actix-web",
+        "rust" => "axum",
+        "java" => "spring",
+        _ => "express",
+    }
+}
+
+struct RouteFinder<'a> {
+    source: &'a [u8],
+    rel_path: &'a str,
+    framework_hint: &'static str,
+    /// Path prefix per router variable (Python).
+    python_prefixes: HashMap<String, String>,
+    routes: Vec<Route>,
+}
+
+impl RouteFinder<'_> {
+    fn visit(&mut self, node: Node, language: &str) {
+        match (language, node.kind()) {
+            ("python", "decorated_definition") => self.python_decorated(node),
+            ("typescript" | "javascript", "call_expression") => self.express_call(node),
+            ("rust", "call_expression") => self.rust_route_call(node),
+            ("rust", "function_item") => self.rust_attributes(node),
+            ("java", "method_declaration") => self.spring_method(node),
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child, language);
+        }
+    }
+
+    fn text(&self, node: Node) -> &str {
+        node.utf8_text(self.source).unwrap_or_default()
+    }
+
+    fn push(&mut self, method: &str, route: String, handler: Option<String>, line: usize) {
+        self.routes.push(Route {
+            method: method.to_string(),
+            route,
+            framework: self.framework_hint,
+            handler,
+            path: self.rel_path.to_string(),
+            line,
+            handler_path: None,
+            handler_line: None,
+        });
+    }
+
+    /// `@app.route("/p", methods=[...])`, `@router.get("/p")`.
+    fn python_decorated(&mut self, node: Node) {
+        let Some(definition) = node.child_by_field_name("definition") else {
+            return;
+        };
+        let handler = definition
+            .child_by_field_name("name")
+            .map(|name| self.text(name).to_string());
+        let mut cursor = node.walk();
+        let decorators: Vec<Node> = node
+            .children(&mut cursor)
+            .filter(|child| child.kind() == "decorator")
+            .collect();
+        for decorator in decorators {
+            let Some(call) = decorator
+                .named_child(0)
+                .filter(|call| call.kind() == "call")
+            else {
+                continue;
+            };
+            let (Some(function), Some(arguments)) = (
+                call.child_by_field_name("function")
+                    .filter(|function| function.kind() == "attribute"),
+                call.child_by_field_name("arguments"),
+            ) else {
+                continue;
+            };
+            let verb = function
+                .child_by_field_name("attribute")
+                .map(|attr| self.text(attr).to_ascii_uppercase())
+                .unwrap_or_default();
+            let Some(route) = first_string_arg(arguments, self.source) else {
+                continue;
+            };
+            let methods = match verb.as_str() {
+                "ROUTE" | "API_ROUTE" => {
+                    let methods = keyword_arg(arguments, &["methods"], self.source)
+                        .map(|value| http_methods(value, self.source))
+                        .unwrap_or_default();
+                    if methods.is_empty() {
+                        // Flask defaults to GET; FastAPI's api_route to GET too.
+                        vec!["GET".to_string()]
+                    } else {
+                        methods
+                    }
+                }
+                verb if HTTP_METHODS.contains(&verb) => vec![verb.to_string()],
+                _ => continue,
+            };
+            let prefix = function
+                .child_by_field_name("object")
+                .and_then(|object| self.python_prefixes.get(self.text(object)))
+                .cloned()
+                .unwrap_or_default();
+            let line = decorator.start_position().row + 1;
+            for method in methods {
+                self.push(&method, join_paths(&prefix, &route), handler.clone(), line);
+            }
+        }
+    }
+
+    /// `app.get("/p", handler)` and `router.route("/p").get(handler)`.
+    fn express_call(&mut self, node: Node) {
+        let (Some(function), Some(arguments)) = (
+            node.child_by_field_name("function")
+                .filter(|function| function.kind() == "member_expression"),
+            node.child_by_field_name("arguments"),
+        ) else {
+            return;
+        };
+        let Some(property) = function.child_by_field_name("property") else {
+            return;
+        };
+        let verb = self.text(property).to_ascii_uppercase();
+        let method = match verb.as_str() {
+            "ALL" => ANY_METHOD,
+            verb => match HTTP_METHODS.iter().find(|method| **method == verb) {
+                Some(method) => method,
+                None => return,
+            },
+        };
+        let direct = first_string_arg(arguments, self.source).filter(|path| path.starts_with('/'));
+        let route = direct.clone().or_else(|| {
+            // `router.route("/p").get(handler)`, possibly after other verbs.
+            let mut object = function.child_by_field_name("object");
+            while let Some(call) = object.filter(|node| node.kind() == "call_expression") {
+                let callee = call.child_by_field_name("function");
+                let name = callee
+                    .and_then(|callee| callee.child_by_field_name("property"))
+                    .map(|name| self.text(name));
+                if name == Some("route") {
+                    return call
+                        .child_by_field_name("arguments")
+                        .and_then(|args| first_string_arg(args, self.source));
+                }
+                object = callee.and_then(|callee| callee.child_by_field_name("object"));
+            }
+            None
+        });
+        let Some(route) = route.filter(|path| path.starts_with('/')) else {
+            return;
+        };
+        let mut cursor = arguments.walk();
+        let handler = arguments
+            .named_children(&mut cursor)
+            .skip(usize::from(direct.is_some()))
+            .last()
+            .filter(|last| matches!(last.kind(), "identifier" | "member_expression"))
+            .map(|last| self.text(last).to_string());
+        self.push(method, route, handler, node.start_position().row + 1);
+    }
+
+    /// `.route("/p", get(h).post(h2))` (axum) and
+    /// `.route("/p", web::get().to(h))` (actix-web).
+    fn rust_route_call(&mut self, node: Node) {
+        let (Some(function), Some(arguments)) = (
+            node.child_by_field_name("function")
+                .filter(|function| function.kind() == "field_expression"),
+            node.child_by_field_name("arguments"),
+        ) else {
+            return;
+        };
+        if function
+            .child_by_field_name("field")
+            .map(|field| self.text(field))
+            != Some("route")
+        {
+            return;
+        }
+        let Some(route) = first_string_arg(arguments, self.source) else {
+            return;
+        };
+        let mut cursor = arguments.walk();
+        let Some(router) = arguments.named_children(&mut cursor).nth(1) else {
+            return;
+        };
+        let line = node.start_position().row + 1;
+        for (method, handler) in self.rust_method_router(router) {
+            self.push(&method, route.clone(), handler, line);
+        }
+    }
+
+    /// Method and handler pairs of an axum `MethodRouter` or actix-web
+    /// `Route` expression.
+    fn rust_method_router(&self, node: Node) -> Vec<(String, Option<String>)> {
+        let mut pairs = Vec::new();
+        let mut current = Some(node);
+        while let Some(call) = current.filter(|node| node.kind() == "call_expression") {
+            let function = call.child_by_field_name("function");
+            let arguments = call.child_by_field_name("arguments");
+            let (name, receiver) = match function {
+                Some(function) if function.kind() == "field_expression" => (
+                    function
+                        .child_by_field_name("field")
+                        .map(|field| self.text(field)),
+                    function.child_by_field_name("value"),
+                ),
+                Some(function) => (self.text(function).rsplit("::").next(), None),
+                None => (None, None),
+            };
+            let first_arg = arguments.and_then(|args| {
+                let mut cursor = args.walk();
+                let first = args.named_children(&mut cursor).next();
+                first
+            });
+            let handler = first_arg
+                .filter(|arg| matches!(arg.kind(), "identifier" | "scoped_identifier"))
+                .map(|arg| self.text(arg).to_string());
+            match name {
+                // actix-web: `web::get().to(handler)`
+                Some("to") => {
+                    let methods = receiver
+                        .map(|receiver| self.rust_method_router(receiver))
+                        .unwrap_or_default();
+                    pairs.extend(
+                        methods
+                            .into_iter()
+                            .map(|(method, _)| (method, handler.clone())),
+                    );
+                    return pairs;
+                }
+                Some("any") => pairs.push((ANY_METHOD.to_string(), handler)),
+                Some(verb) => {
+                    let verb = verb.to_ascii_uppercase();
+                    if HTTP_METHODS.contains(&verb.as_str()) {
+                        pairs.push((verb, handler));
+                    }
+                }
+                None => {}
+            }
+            current = receiver;
+        }
+        pairs.reverse();
+        pairs
+    }
+
+    /// `#[get("/p")]` (actix-web, Rocket) and
+    /// `#[route("/p", method = "GET")]` on a handler.
+    fn rust_attributes(&mut self, node: Node) {
+        let handler = node
+            .child_by_field_name("name")
+            .map(|name| self.text(name).to_string());
+        let mut sibling = node.prev_named_sibling();
+        while let Some(attribute_item) = sibling.filter(|sibling| {
+            matches!(
+                sibling.kind(),
+                "attribute_item" | "line_comment" | "block_comment"
+            )
+        }) {
+            sibling = attribute_item.prev_named_sibling();
+            let Some(attribute) = attribute_item
+                .named_child(0)
+                .filter(|attr| attr.kind() == "attribute")
+            else {
+                continue;
+            };
+            let (Some(path), Some(arguments)) = (
+                attribute.named_child(0),
+                attribute.child_by_field_name("arguments"),
+            ) else {
+                continue;
+            };
+            let verb = self
+                .text(path)
+                .rsplit("::")
+                .next()
+                .unwrap_or_default()
+                .to_ascii_uppercase();
+            let Some(route) = first_string_arg(arguments, self.source) else {
+                continue;
+            };
+            let methods = if verb == "ROUTE" {
+                http_methods(arguments, self.source)
+            } else if HTTP_METHODS.contains(&verb.as_str()) {
+                vec![verb]
+            } else {
+                continue;
+            };
+            let line = attribute_item.start_position().row + 1;
+            for method in methods {
+                self.push(&method, route.clone(), handler.clone(), line);
+            }
+        }
+    }
+
+    /// `@GetMapping("/p")` or `@RequestMapping(value = "/p", method = ...)`
+    /// on a method, under the class's `@RequestMapping` path.
+    fn spring_method(&mut self, node: Node) {
+        let handler = node
+            .child_by_field_name("name")
+            .map(|name| self.text(name).to_string());
+        let class_prefix = node
+            .parent()
+            .and_then(|body| body.parent())
+            .filter(|class| class.kind() == "class_declaration")
+            .and_then(|class| {
+                self.spring_mappings(class)
+                    .into_iter()
+                    .find(|(annotation, _, _)| annotation == "RequestMapping")
+            })
+            .and_then(|(_, path, _)| path)
+            .unwrap_or_default();
+        for (annotation, path, annotation_node) in self.spring_mappings(node) {
+            let methods = match annotation.strip_suffix("Mapping") {
+                Some("Request") => {
+                    let methods = annotation_node
+                        .child_by_field_name("arguments")
+                        .and_then(|args| keyword_arg(args, &["method"], self.source))
+                        .map(|value| http_methods(value, self.source))
+                        .unwrap_or_default();
+                    if methods.is_empty() {
+                        vec![ANY_METHOD.to_string()]
+                    } else {
+                        methods
+                    }
+                }
+                Some(verb) if HTTP_METHODS.contains(&verb.to_ascii_uppercase().as_str()) => {
+                    vec![verb.to_ascii_uppercase()]
+                }
+                _ => continue,
+            };
+            let route = join_paths(&class_prefix, path.as_deref().unwrap_or_default());
+            let line = annotation_node.start_position().row + 1;
+            for method in methods {
+                self.push(&method, route.clone(), handler.clone(), line);
+            }
+        }
+    }
+
+    /// `*Mapping` annotations of a declaration: the annotation name, its
+    /// path (`value` or `path`, or the lone argument), and the node.
+    fn spring_mappings<'t>(&self, node: Node<'t>) -> Vec<(String, Option<String>, Node<'t>)> {
+        let mut mappings = Vec::new();
+        let mut cursor = node.walk();
+        for modifiers in node
+            .children(&mut cursor)
+            .filter(|child| child.kind() == "modifiers")
+        {
+            let mut cursor = modifiers.walk();
+            for annotation in modifiers
+                .children(&mut cursor)
+                .filter(|child| matches!(child.kind(), "annotation" | "marker_annotation"))
+            {
+                let Some(name) = annotation
+                    .child_by_field_name("name")
+                    .map(|name| self.text(name).rsplit('.').next().unwrap_or_default())
+                    .filter(|name| name.ends_with("Mapping"))
+                else {
+                    continue;
+                };
+                let path = annotation
+                    .child_by_field_name("arguments")
+                    .and_then(|args| {
+                        first_string_arg(args, self.source).or_else(|| {
+                            keyword_arg(args, &["value", "path"], self.source)
+                                .and_then(|value| first_string(value, self.source))
+                        })
+                    });
+                mappings.push((name.to_string(), path, annotation));
+            }
+        }
+        mappings
+    }
+}
+
+/// `prefix` and `path` joined with exactly one `/` between them.
+fn join_paths(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    match (prefix.is_empty(), path.is_empty()) {
+        (true, _) => path.to_string(),
+        (false, true) => prefix.to_string(),
+        (false, false) => format!("{}/{}", prefix, path.trim_start_matches('/')),
+    }
+}
+
+/// The value of a string literal, without prefixes and quotes.
+fn string_value(node: Node, source: &[u8]) -> Option<String> {
+    if !is_string(node) {
+        return None;
+    }
+    let text = node.utf8_text(source).ok()?;
+    let text = text.trim_start_matches(|ch: char| ch.is_ascii_alphabetic());
+    let quote = text.chars().next()?;
+    if !matches!(quote, '"' | '\'' | '`') {
+        return None;
+    }
+    let value = text
+        .trim_start_matches(quote)
+        .trim_end_matches(quote)
+        .to_string();
+    // Template strings with substitutions are built at runtime.
+    (!value.contains("${")).then_some(value)
+}
+
+/// First string among `node`'s direct children.
+fn first_string_arg(node: Node, source: &[u8]) -> Option<String> {
+    let mut cursor = node.walk();
+    let first = node
+        .named_children(&mut cursor)
+        .find(|child| is_string(*child));
+    first.and_then(|string| string_value(string, source))
+}
+
+/// First string anywhere under `node`.
+fn first_string(node: Node, source: &[u8]) -> Option<String> {
+    if is_string(node) {
+        return string_value(node, source);
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|child| first_string(child, source))
+}
+
+/// Value of a named argument (`methods=[...]` in Python, `method = ...` in
+/// Java annotations).
+fn keyword_arg<'t>(arguments: Node<'t>, names: &[&str], source: &[u8]) -> Option<Node<'t>> {
+    let mut cursor = arguments.walk();
+    let found = arguments.named_children(&mut cursor).find_map(|child| {
+        let (key, value) = match child.kind() {
+            "keyword_argument" => (
+                child.child_by_field_name("name")?,
+                child.child_by_field_name("value")?,
+            ),
+            "element_value_pair" => (
+                child.child_by_field_name("key")?,
+                child.child_by_field_name("value")?,
+            ),
+            _ => return None,
+        };
+        let key = key.utf8_text(source).ok()?;
+        names.contains(&key).then_some(value)
+    });
+    found
+}
+
+/// HTTP methods named under `node`, as strings (`"POST"`) or constants
+/// (`RequestMethod.POST`), in source order.
+fn http_methods(node: Node, source: &[u8]) -> Vec<String> {
+    let mut methods: Vec<String> = Vec::new();
+    let mut stack = vec![node];
+    let mut leaves = Vec::new();
+    while let Some(current) = stack.pop() {
+        if is_string(current) || current.named_child_count() == 0 {
+            leaves.push(current);
+            continue;
+        }
+        let mut cursor = current.walk();
+        let children: Vec<Node> = current.named_children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    for leaf in leaves {
+        let text = string_value(leaf, source)
+            .or_else(|| leaf.utf8_text(source).ok().map(str::to_string))
+            .unwrap_or_default();
+        let name = text
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        if HTTP_METHODS.contains(&name.as_str()) && !methods.contains(&name) {
+            methods.push(name);
+        }
+    }
+    methods
+}
+
+/// Fill `handler_path` and `handler_line` from the function or method the
+/// handler names, preferring the file that registers the route.
+fn resolve_handlers(routes: &mut [Route], files: &[ScannedFile], root: &Path) {
+    let extractor = SymbolExtractor::new();
+    let mut definitions: HashMap<String, Vec<(String, usize)>> = HashMap::new();
+    for route in routes.iter_mut() {
+        let Some(name) = route
+            .handler
+            .as_deref()
+            .and_then(|handler| handler.rsplit(['.', ':']).next())
+            .filter(|name| !name.is_empty())
+        else {
+            continue;
+        };
+        let found = definitions.entry(name.to_string()).or_insert_with(|| {
+            let mut found = Vec::new();
+            for file in files {
+                let Some(language) = file.language.as_deref() else {
+                    continue;
+                };
+                if !file.content.contains(name) {
+                    continue;
+                }
+                let Ok(symbols) = extractor.extract(&file.content, language) else {
+                    continue;
+                };
+                let rel_path = display_path(&file.path, root);
+                found.extend(
+                    symbols
+                        .iter()
+                        .filter(|symbol| {
+                            symbol.name == name
+                                && matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method)
+                        })
+                        .map(|symbol| (rel_path.clone(), symbol.line)),
+                );
+            }
+            found
+        });
+        let best = found
+            .iter()
+            .find(|(path, _)| *path == route.path)
+            .or_else(|| found.first());
+        if let Some((path, line)) = best {
+            route.handler_path = Some(path.clone());
+            route.handler_line = Some(*line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(content: &str, language: &str) -> Vec<String> {
+        file_routes(content, language, "app")
+            .into_iter()
+            .map(|route| {
+                format!(
+                    "{} {} {} {}",
+                    route.method,
+                    route.route,
+                    route.handler.unwrap_or_default(),
+                    route.framework
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_routes_per_framework() {
+        let flask = "from fastapi import APIRouter\nrouter = APIRouter(prefix=\"/users\")\n\n@router.get(\"/{id}\")\ndef get_user(id):\n    pass\n\n@app.route(\"/login\", methods=[\"GET\", \"POST\"])\ndef login():\n    pass\n";
+        assert_eq!(
+            summary(flask, "python"),
+            [
+                "GET /users/{id} get_user fastapi",
+                "GET /login login fastapi",
+                "POST /login login fastapi"
+            ]
+        );
+
+        let express = "app.get('/health', health);\nrouter.route('/items').get(list).post(create);\ncache.get('key');\n";
+        assert_eq!(
+            summary(express, "javascript"),
+            [
+                "GET /health health express",
+                "POST /items create express",
+                "GET /items list express"
+            ]
+        );
+
+        let axum = "fn app() -> Router {\n    Router::new()\n        .route(\"/users\", get(list_users).post(create_user))\n        .route(\"/any\", any(fallback))\n}\n";
+        assert_eq!(
+            summary(axum, "rust"),
+            [
+                "ANY /any fallback axum",
+                "GET /users list_users axum",
+                "POST /users create_user axum"
+            ]
+        );
+
+        let actix = "use actix_web::{get, web};\n#[get(\"/ping\")]\nasync fn ping() {}\nfn config(cfg: &mut web::ServiceConfig) {\n    cfg.route(\"/pong\", web::post().to(pong));\n}\n";
+        assert_eq!(
+            summary(actix, "rust"),
+            ["GET /ping ping actix-web", "POST /pong pong actix-web"]
+        );
+
+        let spring = "@RestController\n@RequestMapping(\"/api\")\nclass UserController {\n    @PostMapping(\"/users\")\n    User create() { return null; }\n    @RequestMapping(value = \"/users/{id}\", method = RequestMethod.DELETE)\n    void remove() {}\n}\n";
+        assert_eq!(
+            summary(spring, "java"),
+            [
+                "POST /api/users create spring",
+                "DELETE /api/users/{id} remove spring"
+            ]
+        );
+    }
+
+    #[test]
+    fn path_parameters_match_concrete_segments() {
+        assert!(route_matches("/users/{id}", "/users/42"));
+        assert!(route_matches("/users/:id", "/users/42"));
+        assert!(route_matches("/users/<int:id>", "/users/42"));
+        assert!(route_matches("/api/users", "/users"));
+        assert!(!route_matches("/users/{id}", "/users/42/posts"));
+    }
+}
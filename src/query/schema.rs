@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep schema`: print embedded JSON Schemas and validate payloads against them.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde_json::Value;
+use std::io::Read;
+
+use cgrep::schema;
+
+pub fn run(name: Option<&str>, validate: Option<&str>) -> Result<()> {
+    let Some(name) = name else {
+        for name in schema::schema_names() {
+            println!("{name}");
+        }
+        return Ok(());
+    };
+
+    let Some(source) = schema::schema_source(name) else {
+        bail!(
+            "Unknown schema `{}` (available: {})",
+            name,
+            schema::schema_names().join(", ")
+        );
+    };
+
+    let Some(input) = validate else {
+        print!("{source}");
+        return Ok(());
+    };
+
+    let raw = if input == "-" {
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buf)
+            .context("Failed to read payload from stdin")?;
+        buf
+    } else {
+        std::fs::read_to_string(input).with_context(|| format!("Failed to read {input}"))?
+    };
+    let payload: Value = serde_json::from_str(&raw).context("Payload is not valid JSON")?;
+    let schema: Value = serde_json::from_str(source)?;
+
+    let errors = schema::validate(&schema, &payload);
+    if errors.is_empty() {
+        println!("{} payload matches the {} schema", "✓".green(), name);
+        return Ok(());
+    }
+    for error in &errors {
+        eprintln!("{error}");
+    }
+    bail!(
+        "Payload does not match the {} schema ({} error(s))",
+        name,
+        errors.len()
+    );
+}
@@ -6,11 +6,13 @@ use anyhow::{Context, Result};
 use colored::Colorize;
 use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufReader, Read};
 use std::path::{Component, Path, PathBuf};
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
 use tantivy::{
     collector::TopDocs,
     query::{BooleanQuery, FuzzyTermQuery, Occur, QueryParser, TermQuery},
@@ -18,27 +20,48 @@ use tantivy::{
     Index, TantivyDocument,
 };
 
-use crate::cli::OutputFormat;
+use crate::cli::{ContextArg, OutputFormat};
+use crate::indexer::archive;
+use crate::indexer::churn;
+use crate::indexer::compat;
+use crate::indexer::freshness::{self, check_freshness};
+use crate::indexer::git_history::HistoryFilter;
+use crate::indexer::index::indexed_churn;
+use crate::indexer::regions;
 use crate::indexer::reuse;
-use crate::indexer::scanner::FileScanner;
+use crate::indexer::scanner::{FileScanner, IgnoreRules};
 use crate::query::changed_files::ChangedFiles;
+use crate::query::cluster::{self, ResultCluster};
+use crate::query::facets::{facet_counts, SearchFacets};
+use crate::query::file_summary::{print_file_summaries, summarize_files, FileSummary, LineMatcher};
+use crate::query::hits;
+use crate::query::linguist::{apply_linguist_policy, attributes_in_use, Linguist};
 use crate::query::scope_query::build_scope_path_query;
+use crate::query::symbol_header::{self, SymbolHeaderLine};
 use cgrep::cache::{CacheKey, SearchCache};
-use cgrep::config::{Config, EmbeddingProviderType, RankingConfig};
+use cgrep::cancel;
+use cgrep::config::{Config, EmbeddingProviderType, LinguistPolicy, RankingConfig, SearchConfig};
 use cgrep::embedding::{
     CommandProvider, DummyProvider, EmbeddingProvider, EmbeddingProviderConfig, EmbeddingStorage,
-    FastEmbedder, DEFAULT_EMBEDDING_DIM,
+    FastEmbedder, CHUNK_SYMBOL_KIND, DEFAULT_EMBEDDING_DIM,
 };
+use cgrep::encoding;
 use cgrep::errors::IndexNotFoundError;
 use cgrep::filters::{
     matches_file_type, matches_glob_compiled, should_exclude_compiled, CompiledGlob,
 };
 use cgrep::hybrid::{
-    BM25Result, HybridConfig, HybridResult, HybridSearcher, SearchMode as HybridSearchMode,
+    merge_overlapping_results, BM25Result, HybridConfig, HybridResult, HybridSearcher,
+    SearchMode as HybridSearchMode,
 };
 use cgrep::output::{
-    colorize_context, colorize_line_num, colorize_match, colorize_path, print_json, use_colors,
+    colorize_context, colorize_line_num, colorize_match, colorize_path, print_github_annotations,
+    print_gitlab_report, print_json, print_json2, print_quickfix, use_colors, Annotation,
+    AnnotationLevel, QuickfixEntry,
 };
+use cgrep::paths;
+use cgrep::telemetry;
+use cgrep::usage::{self, UsageEvent};
 use cgrep::utils::INDEX_DIR;
 const DEFAULT_CACHE_TTL_MS: u64 = 600_000; // 10 minutes
 
@@ -49,8 +72,25 @@ pub struct SearchResult {
     pub score: f32,
     pub snippet: String,
     pub line: Option<usize>,
+    /// 1-based byte column of the first query match on `line`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// 1-based byte column just past that match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
+    /// That match as byte offsets into the file on disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_bytes: Option<ByteSpan>,
+    /// The `chunk_start..=chunk_end` line window as byte offsets into the
+    /// file on disk: the symbol's lines for symbol hits, the embedded chunk
+    /// for semantic ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_bytes: Option<ByteSpan>,
     pub context_before: Vec<String>,
     pub context_after: Vec<String>,
+    /// Header lines of the enclosing symbols (only with `-C symbol-header`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbol_header: Vec<SymbolHeaderLine>,
     /// BM25/text score for hybrid search
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text_score: Option<f32>,
@@ -72,6 +112,19 @@ pub struct SearchResult {
     /// Keyword ranking component breakdown (only with --explain)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub explain: Option<ScoreExplain>,
+    /// `.gitattributes` linguist classification, when vendored or generated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linguist: Option<Linguist>,
+    /// The file changed since it was indexed (only with `--verify`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+}
+
+/// Half-open byte range `[start, end)` into a file as stored on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteSpan {
+    pub start: usize,
+    pub end: usize,
 }
 
 /// Deterministic keyword ranking breakdown.
@@ -81,6 +134,10 @@ pub struct ScoreExplain {
     pub path_boost: f32,
     pub symbol_boost: f32,
     pub changed_boost: f32,
+    #[serde(default)]
+    pub churn_boost: f32,
+    #[serde(default)]
+    pub recency_boost: f32,
     pub kind_boost: f32,
     pub penalties: f32,
     pub final_score: f32,
@@ -92,11 +149,21 @@ struct SearchResultJson<'a> {
     path: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
     snippet: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     context_before: Option<&'a [String]>,
     #[serde(skip_serializing_if = "Option::is_none")]
     context_after: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol_header: Option<&'a [SymbolHeaderLine]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    linguist: Option<Linguist>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stale: bool,
 }
 
 impl<'a> SearchResultJson<'a> {
@@ -104,6 +171,8 @@ impl<'a> SearchResultJson<'a> {
         Self {
             path: result.path.as_str(),
             line: result.line,
+            column: result.column,
+            end_column: result.end_column,
             snippet: result.snippet.as_str(),
             context_before: if result.context_before.is_empty() {
                 None
@@ -115,6 +184,13 @@ impl<'a> SearchResultJson<'a> {
             } else {
                 Some(result.context_after.as_slice())
             },
+            symbol_header: if result.symbol_header.is_empty() {
+                None
+            } else {
+                Some(result.symbol_header.as_slice())
+            },
+            linguist: result.linguist,
+            stale: result.stale,
         }
     }
 }
@@ -125,6 +201,8 @@ struct SearchResultCompactJson<'a> {
     path: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
     snippet: &'a str,
 }
 
@@ -133,6 +211,7 @@ impl<'a> SearchResultCompactJson<'a> {
         Self {
             path: result.path.as_str(),
             line: result.line,
+            column: result.column,
             snippet: result.snippet.as_str(),
         }
     }
@@ -158,6 +237,8 @@ struct RankingWeights {
     changed_weight: f32,
     kind_weight: f32,
     weak_signal_penalty: f32,
+    churn_weight: f32,
+    recency_weight: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -169,7 +250,17 @@ struct RankingStrategy {
     query_tokens: Vec<String>,
     identifier_query: Option<String>,
     language_filter: Option<String>,
+    /// Language inferred from the query text; boosted even when ranking is off.
+    language_route: Option<String>,
+    /// Boost for the doc comment field.
+    docs_boost: f32,
+    /// Match only doc comments (`--search-docs`).
+    docs_only: bool,
     changed_requested: bool,
+    /// Normalized commit frequency per scope-relative path.
+    churn: Arc<HashMap<String, f32>>,
+    /// Search root and reference time for the mtime recency bonus.
+    recency: Option<(PathBuf, SystemTime)>,
     weights: RankingWeights,
 }
 
@@ -189,7 +280,12 @@ impl RankingStrategy {
             query_tokens: query_tokens_for_ranking(query),
             identifier_query: single_identifier_query(query),
             language_filter: file_type.map(|value| value.to_ascii_lowercase()),
+            language_route: None,
+            docs_boost: SearchConfig::default().docs_boost(),
+            docs_only: false,
             changed_requested: changed_filter.is_some(),
+            churn: Arc::default(),
+            recency: None,
             weights: RankingWeights {
                 path_weight: config.path_weight(),
                 symbol_weight: config.symbol_weight(),
@@ -197,19 +293,55 @@ impl RankingStrategy {
                 changed_weight: config.changed_weight(),
                 kind_weight: config.kind_weight(),
                 weak_signal_penalty: config.weak_signal_penalty(),
+                churn_weight: config.churn_weight(),
+                recency_weight: config.recency_weight(),
             },
         }
     }
 
+    fn with_language_route(mut self, language: Option<&str>) -> Self {
+        self.language_route = language.map(str::to_string);
+        self
+    }
+
+    /// Load churn recorded by the last index build under `index_root`.
+    fn with_churn(mut self, index_root: &Path, search_root: &Path) -> Self {
+        if !self.enabled || self.weights.churn_weight <= 0.0 {
+            return self;
+        }
+        let counts = indexed_churn(index_root);
+        let scope = search_root
+            .strip_prefix(index_root)
+            .unwrap_or(Path::new(""));
+        self.churn = Arc::new(churn::normalized_churn(&counts, scope));
+        self
+    }
+
+    fn with_recency(mut self, search_root: &Path) -> Self {
+        if self.enabled && self.weights.recency_weight > 0.0 {
+            self.recency = Some((search_root.to_path_buf(), SystemTime::now()));
+        }
+        self
+    }
+
+    fn with_docs(mut self, boost: f32, docs_only: bool) -> Self {
+        self.docs_boost = boost;
+        self.docs_only = docs_only;
+        self
+    }
+
     fn cache_mode_suffix(&self) -> String {
         format!(
-            "rk{}:qc{}:ex{}",
+            "rk{}:qc{}:ex{}:lr{}:sd{}:db{}",
             usize::from(self.enabled),
             match self.query_class {
                 QueryClass::IdentifierLike => "id",
                 QueryClass::PhraseLike => "ph",
             },
-            usize::from(self.explain)
+            usize::from(self.explain),
+            self.language_route.as_deref().unwrap_or("-"),
+            usize::from(self.docs_only),
+            self.docs_boost
         )
     }
 }
@@ -234,6 +366,8 @@ struct SearchOutcome {
     total_matches: usize,
     mode: IndexMode,
     cache_hit: bool,
+    /// Index hits dropped because their file no longer exists.
+    stale_dropped: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -269,22 +403,58 @@ struct SearchJson2Meta<'a> {
     suppress_boilerplate: bool,
     confidence: f32,
     fallback_chain: Vec<String>,
+    /// Why an index search scanned files instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fallback_reason: Option<&'static str>,
     bootstrap_index: bool,
     payload_chars: usize,
     payload_tokens_estimate: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     changed_rev: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    language_route: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cluster_method: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stale_index: Option<bool>,
+    /// Index hits dropped because their file was deleted since indexing.
+    stale_dropped: usize,
+    /// Result files hashed against the index (`--verify`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verified_files: Option<usize>,
+    /// Verified result files that changed since indexing.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stale_files: Vec<String>,
+    /// `--timeout-ms` passed before retrieval finished; results are partial.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    timed_out: bool,
+    /// Ctrl-C stopped retrieval; results are partial.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    interrupted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     path_aliases: Option<BTreeMap<String, String>>,
+    /// Result counts by language, directory, and doc type.
+    facets: SearchFacets,
 }
 
 #[derive(Debug, Serialize)]
 struct SearchJson2Result {
     id: String,
     path: String,
+    /// Readable form of a `path` with escaped non-UTF-8 bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     line: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    match_bytes: Option<ByteSpan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chunk_bytes: Option<ByteSpan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     start_line: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     end_line: Option<usize>,
@@ -301,7 +471,13 @@ struct SearchJson2Result {
     #[serde(skip_serializing_if = "Option::is_none")]
     context_after: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    symbol_header: Option<Vec<SymbolHeaderLine>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     explain: Option<ScoreExplain>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    linguist: Option<Linguist>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stale: bool,
 }
 
 impl SearchJson2Result {
@@ -327,7 +503,12 @@ impl SearchJson2Result {
         Self {
             id,
             path: path_value.unwrap_or(result.path.as_str()).to_string(),
+            display_path: paths::display_name(&result.path),
             line: result.line,
+            column: result.column,
+            end_column: result.end_column,
+            match_bytes: result.match_bytes,
+            chunk_bytes: result.chunk_bytes,
             start_line,
             end_line,
             snippet: result.snippet.clone(),
@@ -345,11 +526,18 @@ impl SearchJson2Result {
             } else {
                 None
             },
+            symbol_header: if include_context && !result.symbol_header.is_empty() {
+                Some(result.symbol_header.clone())
+            } else {
+                None
+            },
             explain: if include_explain {
                 result.explain.clone()
             } else {
                 None
             },
+            linguist: result.linguist,
+            stale: result.stale,
         }
     }
 }
@@ -358,6 +546,96 @@ impl SearchJson2Result {
 struct SearchJson2Payload<'a> {
     meta: SearchJson2Meta<'a>,
     results: Vec<SearchJson2Result>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    clusters: Option<Vec<ResultCluster>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<FileSummary>>,
+}
+
+/// `search --plan-only`: how a query would be routed, without running it.
+#[derive(Debug, Serialize)]
+struct SearchPlan<'a> {
+    query: &'a str,
+    search_mode: String,
+    explicit_mode: bool,
+    index: SearchPlanIndex,
+    query_handling: SearchPlanQuery,
+    hybrid_fallback: SearchPlanHybridFallback,
+    filters: SearchPlanFilters<'a>,
+    fetch: SearchPlanFetch,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchPlanIndex {
+    /// `index` or `scan` before checking the index
+    requested: &'static str,
+    /// Flag that asked for scan mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scan_requested_by: Option<&'static str>,
+    /// `index` or `scan` as the search would run
+    effective: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fallback_reason: Option<&'static str>,
+    root: String,
+    available: bool,
+    /// The index lives in a parent of the search root
+    parent: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchPlanQuery {
+    /// Contains query-parser metacharacters and is matched as a phrase
+    literal: bool,
+    /// Text handed to the index query parser
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parser_query: Option<String>,
+    /// Query as normalized for the result cache
+    normalized: String,
+    regex: bool,
+    fuzzy: bool,
+    case_sensitive: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchPlanHybridFallback {
+    /// A keyword search may retry as hybrid when its results are weak
+    eligible: bool,
+    /// Results below this confidence (or none) trigger the retry
+    confidence_threshold: f32,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchPlanFilters<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    glob: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exclude: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    config_exclude: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed_rev: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed_files: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language_route: Option<&'a str>,
+    docs_only: bool,
+    recursive: bool,
+    linguist: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchPlanFetch {
+    max_results: usize,
+    /// Results retrieved before diversity and linguist passes trim to `max_results`
+    candidate_pool: usize,
+    /// Candidates ranked from the index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_candidates: Option<usize>,
+    /// Top documents read from the index before scope filtering
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_top_docs: Option<usize>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -381,11 +659,15 @@ pub fn run(
     query: &str,
     path: Option<&str>,
     max_results: usize,
-    context: usize,
+    context: ContextArg,
     file_type: Option<&str>,
     glob_pattern: Option<&str>,
     exclude_pattern: Option<&str>,
     changed: Option<&str>,
+    only_dirty: bool,
+    from_patch: Option<&str>,
+    modified_since: Option<&str>,
+    author: Option<&str>,
     quiet: bool,
     fuzzy: bool,
     no_index: bool,
@@ -393,6 +675,7 @@ pub fn run(
     case_sensitive: bool,
     recursive: bool,
     no_ignore: bool,
+    ignore_rules: IgnoreRules,
     format: OutputFormat,
     compact: bool,
     search_mode: Option<HybridSearchMode>,
@@ -409,13 +692,27 @@ pub fn run(
     explicit_mode: bool,
     bootstrap_index: bool,
     explain: bool,
+    language_routing: bool,
+    search_docs: bool,
+    diversify: bool,
+    cluster: bool,
+    summarize: bool,
+    require_fresh: bool,
+    no_fallback: bool,
+    plan_only: bool,
+    verify: bool,
 ) -> Result<()> {
     let start_time = Instant::now();
+    let span = telemetry::Span::start("cgrep.search");
     let use_color = use_colors() && format == OutputFormat::Text;
 
     if query.trim().is_empty() {
         anyhow::bail!("Search query cannot be empty");
     }
+    let (context, symbol_header) = match context {
+        ContextArg::Lines(lines) => (lines, false),
+        ContextArg::SymbolHeader => (0, true),
+    };
 
     // Precompile glob patterns for efficient repeated matching
     let compiled_glob = glob_pattern.and_then(CompiledGlob::new);
@@ -437,22 +734,61 @@ pub fn run(
 
     // Load config relative to the index root so running from subdirectories works.
     let config = Config::load_for_dir(&index_root);
-    let effective_max_results = max_results;
+    let diversify = diversify || config.search().diversify();
+    let linguist_policy = config.search().linguist();
+    let linguist_active =
+        linguist_policy != LinguistPolicy::Off && attributes_in_use(&workspace_root);
+    // Over-fetch so capped directories leave room for results from others.
+    let mut effective_max_results = if diversify {
+        max_results.saturating_mul(DIVERSIFY_CANDIDATE_FACTOR)
+    } else {
+        max_results
+    };
+    // Likewise so demoted or excluded vendored/generated paths can be replaced.
+    if linguist_active {
+        effective_max_results = effective_max_results.saturating_mul(LINGUIST_CANDIDATE_FACTOR);
+    }
     let config_exclude_patterns: Vec<CompiledGlob> = config
         .exclude_patterns
         .iter()
         .filter_map(|p| CompiledGlob::new(p.as_str()))
         .collect();
-    let changed_filter = changed
-        .map(|rev| ChangedFiles::from_scope(&search_root, rev))
-        .transpose()?;
+    let changed_filter = match from_patch {
+        Some(patch_path) => Some(ChangedFiles::from_patch(
+            &search_root,
+            &read_patch(patch_path)?,
+        )?),
+        None => ChangedFiles::from_flags(&search_root, changed, only_dirty)?,
+    };
+    let changed_filter = match HistoryFilter::new(modified_since, author)? {
+        Some(history) => {
+            let touched = ChangedFiles::from_history(&search_root, &index_root, &history)?;
+            Some(match changed_filter {
+                Some(changed) => changed.intersect(&touched),
+                None => touched,
+            })
+        }
+        None => changed_filter,
+    };
 
-    let requested_mode = if no_index || regex || no_ignore {
+    // Ignore-rule overrides apply to the walk, which the index cannot redo.
+    let walk_overrides = no_ignore || !ignore_rules.is_default();
+    // Added-line filtering happens per line, which only scan mode sees.
+    let requested_mode = if no_index || regex || walk_overrides || from_patch.is_some() {
         IndexMode::Scan
     } else {
         IndexMode::Index
     };
 
+    let stale_index = if requested_mode == IndexMode::Index
+        && !plan_only
+        && (require_fresh || config.search().freshness_check())
+    {
+        ensure_fresh_index(&index_root, require_fresh)?
+    } else {
+        None
+    };
+
     if requested_mode == IndexMode::Scan && fuzzy {
         eprintln!("Warning: --fuzzy is only supported with index search; ignoring.");
     }
@@ -470,14 +806,25 @@ pub fn run(
 
     // Check for hybrid search mode
     let mut effective_search_mode = search_mode.unwrap_or(HybridSearchMode::Keyword);
-    if no_ignore
+    if walk_overrides
         && matches!(
             effective_search_mode,
             HybridSearchMode::Semantic | HybridSearchMode::Hybrid
         )
     {
         eprintln!(
-            "Warning: --no-ignore is only supported for keyword search; falling back to --mode keyword."
+            "Warning: --no-ignore, --no-ignore-vcs, --no-ignore-global, and --hidden are only supported for keyword search; falling back to --mode keyword."
+        );
+        effective_search_mode = HybridSearchMode::Keyword;
+    }
+    if from_patch.is_some()
+        && matches!(
+            effective_search_mode,
+            HybridSearchMode::Semantic | HybridSearchMode::Hybrid
+        )
+    {
+        eprintln!(
+            "Warning: --from-patch is only supported for keyword search; falling back to --mode keyword."
         );
         effective_search_mode = HybridSearchMode::Keyword;
     }
@@ -487,13 +834,156 @@ pub fn run(
     if explain && !explain_keyword {
         eprintln!("Warning: --explain is currently supported for --mode keyword only; ignoring.");
     }
+    // An explicit `--type` always wins over a language named in the query.
+    let language_route = if language_routing && file_type.is_none() && !regex {
+        cgrep::filters::language_from_query(query)
+    } else {
+        None
+    };
     let ranking_strategy = RankingStrategy::from_config(
         config.ranking(),
         query,
         file_type,
         changed_filter.as_ref(),
         explain_keyword,
-    );
+    )
+    .with_language_route(language_route)
+    .with_docs(config.search().docs_boost(), search_docs)
+    .with_churn(&index_root, &search_root)
+    .with_recency(&search_root);
+
+    let scan_fallback = if effective_search_mode == HybridSearchMode::Keyword {
+        scan_fallback_reason(
+            query,
+            &index_root,
+            &index_path,
+            requested_mode,
+            fuzzy,
+            compiled_regex.is_some(),
+        )
+    } else {
+        None
+    };
+    if plan_only {
+        let index_available = has_full_index(&index_path);
+        let effective_index_mode = match effective_search_mode {
+            HybridSearchMode::Keyword
+                if requested_mode == IndexMode::Index && scan_fallback.is_none() =>
+            {
+                IndexMode::Index
+            }
+            HybridSearchMode::Semantic | HybridSearchMode::Hybrid if index_available => {
+                IndexMode::Index
+            }
+            _ => IndexMode::Scan,
+        };
+        let uses_index = effective_index_mode == IndexMode::Index;
+        let literal = !fuzzy && query_requires_literal_handling(query);
+        let index_candidates = uses_index.then(|| {
+            if language_route.is_some() {
+                effective_max_results.saturating_mul(LANGUAGE_ROUTE_POOL_FACTOR)
+            } else {
+                effective_max_results
+            }
+        });
+        let plan = SearchPlan {
+            query,
+            search_mode: effective_search_mode.to_string(),
+            explicit_mode,
+            index: SearchPlanIndex {
+                requested: index_mode_name(requested_mode),
+                scan_requested_by: if no_index {
+                    Some("no_index")
+                } else if regex {
+                    Some("regex")
+                } else if walk_overrides {
+                    Some("ignore_overrides")
+                } else if from_patch.is_some() {
+                    Some("from_patch")
+                } else {
+                    None
+                },
+                effective: index_mode_name(effective_index_mode),
+                fallback_reason: scan_fallback.map(ScanFallback::as_str),
+                root: paths::display(&index_root),
+                available: index_available,
+                parent: using_parent,
+            },
+            query_handling: SearchPlanQuery {
+                literal,
+                parser_query: (uses_index && !fuzzy).then(|| {
+                    if literal {
+                        cgrep::syntax::escape_as_query_phrase(query)
+                    } else {
+                        query.to_string()
+                    }
+                }),
+                normalized: if regex {
+                    query.to_string()
+                } else {
+                    normalize_query(query, !case_sensitive, uses_index)
+                },
+                regex,
+                fuzzy: fuzzy && uses_index,
+                case_sensitive,
+            },
+            hybrid_fallback: SearchPlanHybridFallback {
+                eligible: should_attempt_keyword_fallback(&KeywordFallbackPolicy {
+                    mode: effective_search_mode,
+                    explicit_mode,
+                    requested_mode,
+                    no_ignore: walk_overrides,
+                    fuzzy,
+                    has_regex: compiled_regex.is_some(),
+                    confidence: 0.0,
+                    results: &[],
+                }),
+                confidence_threshold: KEYWORD_FALLBACK_CONFIDENCE_THRESHOLD,
+            },
+            filters: SearchPlanFilters {
+                file_type,
+                glob: glob_pattern,
+                exclude: exclude_pattern,
+                config_exclude: config.exclude_patterns.iter().map(String::as_str).collect(),
+                changed_rev: changed_filter.as_ref().map(|f| f.rev()),
+                changed_files: changed_filter.as_ref().map(|f| f.existing_files().len()),
+                language_route,
+                docs_only: search_docs,
+                recursive,
+                linguist: if linguist_active {
+                    match linguist_policy {
+                        LinguistPolicy::Demote => "demote",
+                        LinguistPolicy::Exclude => "exclude",
+                        LinguistPolicy::Off => "off",
+                    }
+                } else {
+                    "off"
+                },
+            },
+            fetch: SearchPlanFetch {
+                max_results,
+                candidate_pool: effective_max_results,
+                index_candidates,
+                index_top_docs: index_candidates.map(|limit| limit.saturating_mul(5).max(1)),
+            },
+        };
+        return print_search_plan(&plan, format, compact);
+    }
+    if let Some(reason) = scan_fallback {
+        let detail = reason.describe(&index_root, &index_path);
+        if no_fallback {
+            anyhow::bail!(
+                "{}; refusing to fall back to scan mode (--no-fallback). Fix: {}.",
+                detail,
+                reason.suggestion()
+            );
+        }
+        eprintln!(
+            "{}. Falling back to scan mode; {}.",
+            detail,
+            reason.suggestion()
+        );
+    }
 
     let mut outcome = match effective_search_mode {
         HybridSearchMode::Semantic | HybridSearchMode::Hybrid => {
@@ -517,6 +1007,8 @@ pub fn run(
                 recursive,
                 use_cache,
                 effective_cache_ttl,
+                language_route,
+                search_docs,
             )?
         }
         HybridSearchMode::Keyword => keyword_search(
@@ -524,7 +1016,6 @@ pub fn run(
             &index_root,
             &search_root,
             &workspace_root,
-            &index_path,
             effective_max_results,
             context,
             file_type,
@@ -535,11 +1026,13 @@ pub fn run(
             &config_exclude_patterns,
             changed_filter.as_ref(),
             requested_mode,
+            scan_fallback,
             fuzzy,
             compiled_regex.as_ref(),
             case_sensitive,
             recursive,
             no_ignore,
+            ignore_rules,
             use_cache,
             effective_cache_ttl,
             &ranking_strategy,
@@ -559,13 +1052,13 @@ pub fn run(
         mode: effective_search_mode,
         explicit_mode,
         requested_mode,
-        no_ignore,
+        no_ignore: walk_overrides,
         fuzzy,
         has_regex: compiled_regex.is_some(),
         confidence,
         results: &outcome.results,
     };
-    if should_attempt_keyword_fallback(&fallback_policy) {
+    if should_attempt_keyword_fallback(&fallback_policy) && !cancel::should_stop() {
         match hybrid_search(
             query,
             &index_root,
@@ -585,6 +1078,8 @@ pub fn run(
             recursive,
             use_cache,
             effective_cache_ttl,
+            language_route,
+            search_docs,
         ) {
             Ok(hybrid_outcome) => {
                 let hybrid_confidence =
@@ -610,13 +1105,70 @@ pub fn run(
     if using_parent && outcome.mode == IndexMode::Index {
         eprintln!("Using index from: {}", index_root.display());
     }
+    if outcome.stale_dropped > 0 {
+        eprintln!(
+            "Warning: skipped {} index hit(s) in files deleted since indexing; run `cgrep index` to refresh.",
+            outcome.stale_dropped
+        );
+    }
+
+    if linguist_active {
+        apply_linguist_policy(&mut outcome.results, &workspace_root, linguist_policy);
+        if !diversify {
+            outcome.results.truncate(max_results);
+        }
+    }
+
+    if diversify {
+        diversify_results(
+            &mut outcome.results,
+            config.search().max_results_per_dir(),
+            max_results,
+        );
+    }
 
     let effective_context_pack = context_pack.filter(|v| *v > 0);
     if let Some(pack_gap) = effective_context_pack {
         apply_context_pack(&mut outcome.results, pack_gap);
     }
 
+    let verification = if (verify || config.search().verify()) && outcome.mode == IndexMode::Index {
+        let verification = verify_result_files(
+            &mut outcome.results,
+            &index_root,
+            &[&workspace_root, &search_root],
+            config.search().verify_sample(),
+        );
+        match &verification {
+            None => eprintln!(
+                "Warning: --verify needs an index manifest; run `cgrep index` to rebuild the index."
+            ),
+            Some(report) if !report.stale_files.is_empty() => eprintln!(
+                "Warning: {} of {} verified result files changed since indexing ({}); their snippets may be outdated. Run `cgrep index` to refresh.",
+                report.stale_files.len(),
+                report.checked,
+                report.stale_files.join(", ")
+            ),
+            Some(_) => {}
+        }
+        verification
+    } else {
+        None
+    };
+
     ensure_result_ids(&mut outcome.results);
+    if symbol_header {
+        symbol_header::attach(&mut outcome.results, &[&workspace_root, &search_root]);
+    }
+    // Summaries come from the full retrieved set, before output budgets drop results.
+    let file_summaries = summarize.then(|| {
+        summarize_files(
+            &outcome.results,
+            &[&workspace_root, &search_root],
+            &LineMatcher::new(query, compiled_regex.as_ref(), case_sensitive),
+        )
+    });
+    let facets = (format == OutputFormat::Json2).then(|| facet_counts(&outcome.results));
 
     let budget = SearchOutputBudget {
         max_chars_per_snippet,
@@ -626,14 +1178,82 @@ pub fn run(
         suppress_boilerplate: suppress_boilerplate || format == OutputFormat::Json2,
     };
     let budget_stats = apply_output_budget(&mut outcome.results, budget);
-    let (path_alias_lookup, path_aliases_meta) = if format == OutputFormat::Json2 && path_alias {
-        let (lookup, aliases) = build_path_aliases(&outcome.results);
-        (Some(lookup), Some(aliases))
-    } else {
-        (None, None)
-    };
+    if matches!(
+        format,
+        OutputFormat::Json | OutputFormat::Json2 | OutputFormat::Quickfix
+    ) {
+        annotate_match_locations(
+            &mut outcome.results,
+            &[&workspace_root, &search_root],
+            query,
+            compiled_regex.as_ref(),
+            case_sensitive,
+        );
+    }
+    let clusters = cluster.then(|| {
+        let embeddings = cluster::result_embeddings(&index_root, &workspace_root, &outcome.results);
+        cluster::cluster_results(&outcome.results, embeddings)
+    });
+    let (path_alias_lookup, path_aliases_meta) =
+        if format == OutputFormat::Json2 && path_alias && file_summaries.is_none() {
+            let (lookup, aliases) = build_path_aliases(&outcome.results);
+            (Some(lookup), Some(aliases))
+        } else {
+            (None, None)
+        };
 
     let elapsed = start_time.elapsed();
+    if cancel::timed_out() {
+        eprintln!(
+            "Warning: search hit --timeout-ms; {}.",
+            cancel::PARTIAL_RESULTS_NOTICE
+        );
+    } else if cancel::interrupted() {
+        eprintln!(
+            "Warning: search interrupted; {}.",
+            cancel::PARTIAL_RESULTS_NOTICE
+        );
+    }
+    let index_mode = match outcome.mode {
+        IndexMode::Index => "index",
+        IndexMode::Scan => "scan",
+    };
+    let attributes = vec![
+        ("search_mode", effective_search_mode.to_string()),
+        ("index_mode", index_mode.to_string()),
+    ];
+    telemetry::record_duration("cgrep.search.duration", elapsed, attributes.clone());
+    span.end(attributes, true);
+    usage::record(&UsageEvent {
+        ts: usage::now_ms(),
+        mode: effective_search_mode.to_string(),
+        index_mode: index_mode.to_string(),
+        elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        cache_hit: use_cache.then_some(outcome.cache_hit),
+    });
+    if use_cache {
+        telemetry::add_counter(
+            "cgrep.search.cache.requests",
+            1,
+            vec![("hit", outcome.cache_hit.to_string())],
+        );
+    }
+
+    if let Some(files) = file_summaries
+        .as_deref()
+        .filter(|_| format != OutputFormat::Json2)
+    {
+        print_file_summaries(files, query, format, compact, use_color)?;
+        if format == OutputFormat::Text && !quiet {
+            eprintln!(
+                "\n{} files | {} matches | {:.2}ms",
+                files.len(),
+                files.iter().map(|file| file.match_count).sum::<usize>(),
+                elapsed.as_secs_f64() * 1000.0
+            );
+        }
+        return Ok(());
+    }
 
     // Output based on format
     match format {
@@ -677,6 +1297,7 @@ pub fn run(
             let json2_results: Vec<SearchJson2Result> = outcome
                 .results
                 .iter()
+                .filter(|_| file_summaries.is_none())
                 .map(|result| {
                     let alias = path_alias_lookup
                         .as_ref()
@@ -685,12 +1306,15 @@ pub fn run(
                     SearchJson2Result::from_result(result, !compact, explain_keyword, alias)
                 })
                 .collect();
-            let payload_chars = estimate_json2_payload_chars(&json2_results);
+            let payload_chars = match &file_summaries {
+                Some(files) => serde_json::to_string(files).map_or(0, |json| json.len()),
+                None => estimate_json2_payload_chars(&json2_results),
+            };
             let payload_tokens_estimate = estimate_tokens_from_chars(payload_chars);
 
             let payload = SearchJson2Payload {
                 meta: SearchJson2Meta {
-                    schema_version: "1",
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
                     query,
                     search_mode: effective_search_mode.to_string(),
                     index_mode: match outcome.mode {
@@ -712,18 +1336,100 @@ pub fn run(
                     suppress_boilerplate: budget.suppress_boilerplate,
                     confidence,
                     fallback_chain: fallback_chain.clone(),
+                    fallback_reason: scan_fallback.map(ScanFallback::as_str),
                     bootstrap_index,
                     payload_chars,
                     payload_tokens_estimate,
                     changed_rev: changed_filter.as_ref().map(|f| f.rev()),
+                    language_route,
+                    cluster_method: clusters.as_ref().map(|(method, _)| method.as_str()),
+                    stale_index,
+                    stale_dropped: outcome.stale_dropped,
+                    verified_files: verification.as_ref().map(|report| report.checked),
+                    stale_files: verification
+                        .map(|report| report.stale_files)
+                        .unwrap_or_default(),
+                    timed_out: cancel::timed_out(),
+                    interrupted: cancel::interrupted(),
                     path_aliases: path_aliases_meta,
+                    facets: facets.unwrap_or_default(),
                 },
                 results: json2_results,
+                clusters: clusters.map(|(_, mut clusters)| {
+                    if let Some(lookup) = &path_alias_lookup {
+                        for cluster in &mut clusters {
+                            let rep = &mut cluster.representative;
+                            if let Some(alias) = lookup.get(&rep.path) {
+                                rep.path = alias.clone();
+                            }
+                        }
+                    }
+                    clusters
+                }),
+                files: file_summaries,
             };
 
-            print_json(&payload, compact)?;
+            print_json2("search", &payload, compact)?;
         }
-        OutputFormat::Text => {
+        OutputFormat::Github | OutputFormat::Gitlab => {
+            let title = format!("cgrep: {query}");
+            let annotations: Vec<Annotation<'_>> = outcome
+                .results
+                .iter()
+                .map(|result| Annotation {
+                    path: &result.path,
+                    line: result.line,
+                    level: AnnotationLevel::Warning,
+                    title: &title,
+                    message: result
+                        .snippet
+                        .lines()
+                        .map(str::trim)
+                        .find(|line| !line.is_empty())
+                        .unwrap_or_default(),
+                    id: result.result_id.as_deref(),
+                })
+                .collect();
+            if format == OutputFormat::Github {
+                print_github_annotations(&annotations);
+            } else {
+                print_gitlab_report(&annotations, compact)?;
+            }
+        }
+        OutputFormat::Quickfix => {
+            let locations: Vec<(String, usize, usize)> = outcome
+                .results
+                .iter()
+                .map(|result| {
+                    let result_path = paths::decode_path(&result.path);
+                    let full_path = [&workspace_root, &search_root]
+                        .iter()
+                        .map(|root| root.join(&result_path))
+                        .find(|candidate| candidate.exists())
+                        .unwrap_or_else(|| search_root.join(&result_path));
+                    let line = result
+                        .line
+                        .or(result.chunk_start.map(|start| start as usize))
+                        .unwrap_or(1);
+                    let path = paths::relative_display(&full_path, &workspace_root)
+                        .unwrap_or_else(|| paths::display(&full_path));
+                    (path, line, result.column.unwrap_or(1))
+                })
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = outcome
+                .results
+                .iter()
+                .zip(&locations)
+                .map(|(result, (path, line, column))| QuickfixEntry {
+                    path,
+                    line: *line,
+                    column: *column,
+                    text: &result.snippet,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Sarif => {
             if outcome.results.is_empty() {
                 if use_color {
                     println!("{} No results found for: {}", "✗".red(), query.yellow());
@@ -771,8 +1477,9 @@ pub fn run(
 
                 let mut prev_had_context = false;
                 for (idx, result) in outcome.results.iter().enumerate() {
-                    let has_context =
-                        !result.context_before.is_empty() || !result.context_after.is_empty();
+                    let has_context = !result.context_before.is_empty()
+                        || !result.context_after.is_empty()
+                        || !result.symbol_header.is_empty();
 
                     // Print separator between context groups
                     if idx > 0 && (prev_had_context || has_context) {
@@ -792,10 +1499,16 @@ pub fn run(
                         .map(|l| format!(":{}", colorize_line_num(l, use_color)))
                         .unwrap_or_default();
 
+                    let stale_marker = if result.stale { " [stale]" } else { "" };
                     if use_color {
-                        println!("{}{}", colorize_path(&result.path, use_color), line_info);
+                        println!(
+                            "{}{}{}",
+                            colorize_path(&result.path, use_color),
+                            line_info,
+                            stale_marker.red()
+                        );
                     } else {
-                        println!("{}{}", result.path, line_info);
+                        println!("{}{}{}", result.path, line_info, stale_marker);
                     }
 
                     if has_context {
@@ -804,6 +1517,19 @@ pub fn run(
                             let min_line = match_line.saturating_sub(result.context_before.len());
                             let width = std::cmp::max(max_line, min_line).to_string().len();
 
+                            // Print enclosing symbol headers, marking skipped lines
+                            for header in &result.symbol_header {
+                                let prefix = format_line_prefix(" ", header.line, width);
+                                println!("{}{}", prefix, colorize_context(&header.text, use_color));
+                            }
+                            if result
+                                .symbol_header
+                                .last()
+                                .is_some_and(|header| header.line + 1 < min_line)
+                            {
+                                println!("{}", colorize_context("  ⋮", use_color));
+                            }
+
                             // Print context before
                             for (i, line) in result.context_before.iter().enumerate() {
                                 let ctx_line_num =
@@ -837,11 +1563,13 @@ pub fn run(
                     if explain_keyword {
                         if let Some(explain) = &result.explain {
                             println!(
-                                "    [score] bm25={:.4} path={:.4} symbol={:.4} changed={:.4} kind={:.4} penalties={:.4} final={:.4}",
+                                "    [score] bm25={:.4} path={:.4} symbol={:.4} changed={:.4} churn={:.4} recency={:.4} kind={:.4} penalties={:.4} final={:.4}",
                                 explain.bm25,
                                 explain.path_boost,
                                 explain.symbol_boost,
                                 explain.changed_boost,
+                                explain.churn_boost,
+                                explain.recency_boost,
                                 explain.kind_boost,
                                 explain.penalties,
                                 explain.final_score
@@ -857,6 +1585,10 @@ pub fn run(
                 }
             }
 
+            if let Some((_, clusters)) = &clusters {
+                print_clusters(clusters, use_color);
+            }
+
             // Print stats unless quiet
             if !quiet {
                 eprintln!(
@@ -872,6 +1604,198 @@ pub fn run(
     Ok(())
 }
 
+/// Stream a hit in the json2 result shape; a no-op unless hit streaming is on.
+fn stream_hit(result: &SearchResult) {
+    if hits::enabled() {
+        hits::emit(&SearchJson2Result::from_result(result, true, false, None));
+    }
+}
+
+fn index_mode_name(mode: IndexMode) -> &'static str {
+    match mode {
+        IndexMode::Index => "index",
+        IndexMode::Scan => "scan",
+    }
+}
+
+fn print_search_plan(plan: &SearchPlan<'_>, format: OutputFormat, compact: bool) -> Result<()> {
+    if matches!(format, OutputFormat::Json | OutputFormat::Json2) {
+        return print_json(plan, compact);
+    }
+    let index = &plan.index;
+    println!("Query plan for: {}", plan.query);
+    println!(
+        "  mode:     {}{}",
+        plan.search_mode,
+        if plan.explicit_mode {
+            " (explicit)"
+        } else {
+            ""
+        }
+    );
+    let route = match (index.scan_requested_by, index.fallback_reason) {
+        (Some(flag), _) => format!("scan (requested by {flag})"),
+        (None, Some(reason)) => format!("scan (fallback: {reason})"),
+        (None, None) => index.effective.to_string(),
+    };
+    println!("  route:    {route}");
+    println!(
+        "  index:    {} ({}{})",
+        index.root,
+        if index.available {
+            "available"
+        } else {
+            "missing"
+        },
+        if index.parent {
+            ", parent directory"
+        } else {
+            ""
+        }
+    );
+    let handling = &plan.query_handling;
+    match &handling.parser_query {
+        Some(parser_query) if handling.literal => {
+            println!("  query:    literal phrase {parser_query}")
+        }
+        Some(parser_query) => println!("  query:    parsed as {parser_query}"),
+        None if handling.regex => println!("  query:    regex, matched line by line"),
+        None => println!("  query:    matched line by line"),
+    }
+    println!(
+        "  matching: case {}{}",
+        if handling.case_sensitive {
+            "sensitive"
+        } else {
+            "insensitive"
+        },
+        if handling.fuzzy { ", fuzzy" } else { "" }
+    );
+    println!(
+        "  hybrid:   {}",
+        if plan.hybrid_fallback.eligible {
+            format!(
+                "retried as hybrid when confidence < {:.2} or no results",
+                plan.hybrid_fallback.confidence_threshold
+            )
+        } else {
+            "no keyword-to-hybrid fallback".to_string()
+        }
+    );
+    let filters = &plan.filters;
+    let mut applied: Vec<String> = Vec::new();
+    if let Some(file_type) = filters.file_type {
+        applied.push(format!("type={file_type}"));
+    }
+    if let Some(glob) = filters.glob {
+        applied.push(format!("glob={glob}"));
+    }
+    if let Some(exclude) = filters.exclude {
+        applied.push(format!("exclude={exclude}"));
+    }
+    if !filters.config_exclude.is_empty() {
+        applied.push(format!(
+            "config exclude={}",
+            filters.config_exclude.join(",")
+        ));
+    }
+    if let Some(rev) = filters.changed_rev {
+        applied.push(format!(
+            "changed={rev} ({} files)",
+            filters.changed_files.unwrap_or_default()
+        ));
+    }
+    if let Some(route) = filters.language_route {
+        applied.push(format!("language route={route}"));
+    }
+    if filters.docs_only {
+        applied.push("docs only".to_string());
+    }
+    if !filters.recursive {
+        applied.push("no recursion".to_string());
+    }
+    if filters.linguist != "off" {
+        applied.push(format!("linguist={}", filters.linguist));
+    }
+    println!(
+        "  filters:  {}",
+        if applied.is_empty() {
+            "none".to_string()
+        } else {
+            applied.join("; ")
+        }
+    );
+    let fetch = &plan.fetch;
+    match (fetch.index_candidates, fetch.index_top_docs) {
+        (Some(candidates), Some(top_docs)) => println!(
+            "  fetch:    {} results from a pool of {}; {} index candidates from top {} docs",
+            fetch.max_results, fetch.candidate_pool, candidates, top_docs
+        ),
+        _ => println!(
+            "  fetch:    {} results from a pool of {}; every file in scope is scanned",
+            fetch.max_results, fetch.candidate_pool
+        ),
+    }
+    Ok(())
+}
+
+/// Check the index for staleness, reindexing under `--require-fresh`.
+///
+/// Returns whether the index is stale, or `None` without a manifest to check.
+fn ensure_fresh_index(index_root: &Path, require_fresh: bool) -> Result<Option<bool>> {
+    let Some(freshness) = check_freshness(index_root) else {
+        return Ok(None);
+    };
+    if !freshness.stale {
+        return Ok(Some(false));
+    }
+    let changed = freshness.changed.join(", ");
+    if !require_fresh {
+        eprintln!(
+            "Warning: index may be stale (changed since indexing: {}); run `cgrep index` or pass --require-fresh.",
+            changed
+        );
+        return Ok(Some(true));
+    }
+    crate::cli_auto_index::reindex_stale_index(index_root).with_context(|| {
+        format!(
+            "Index is stale (changed since indexing: {}) and could not be refreshed",
+            changed
+        )
+    })?;
+    Ok(Some(false))
+}
+
+fn print_clusters(clusters: &[ResultCluster], use_color: bool) {
+    if clusters.is_empty() {
+        return;
+    }
+    println!("Clusters:");
+    for cluster in clusters {
+        let rep = &cluster.representative;
+        let line_info = rep
+            .line
+            .map(|l| format!(":{}", colorize_line_num(l, use_color)))
+            .unwrap_or_default();
+        let label = if use_color {
+            cluster.label.cyan().to_string()
+        } else {
+            cluster.label.clone()
+        };
+        println!(
+            "  [{}] {} ({})  {}{}",
+            cluster.id,
+            label,
+            cluster.size,
+            colorize_path(&rep.path, use_color),
+            line_info
+        );
+        if let Some(line) = rep.snippet.lines().map(str::trim).find(|l| !l.is_empty()) {
+            println!("      {}", line);
+        }
+    }
+}
+
 fn stable_result_id(result: &SearchResult) -> String {
     let payload = format!(
         "{}:{}:{}",
@@ -883,10 +1807,45 @@ fn stable_result_id(result: &SearchResult) -> String {
     hash.to_hex()[..16].to_string()
 }
 
-fn ensure_result_ids(results: &mut [SearchResult]) {
-    for result in results.iter_mut() {
-        if result.result_id.is_none() {
-            result.result_id = Some(stable_result_id(result));
+fn ensure_result_ids(results: &mut [SearchResult]) {
+    for result in results.iter_mut() {
+        if result.result_id.is_none() {
+            result.result_id = Some(stable_result_id(result));
+        }
+    }
+}
+
+/// Keep at most `per_dir` results from each directory and interleave
+/// directories round-robin, in the order of their best-ranked result.
+fn diversify_results(results: &mut Vec<SearchResult>, per_dir: usize, max_results: usize) {
+    let mut groups: Vec<VecDeque<SearchResult>> = Vec::new();
+    let mut group_by_dir: HashMap<String, usize> = HashMap::new();
+    for result in results.drain(..) {
+        let dir = Path::new(&result.path)
+            .parent()
+            .map(|parent| parent.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let idx = *group_by_dir.entry(dir).or_insert_with(|| {
+            groups.push(VecDeque::new());
+            groups.len() - 1
+        });
+        if groups[idx].len() < per_dir {
+            groups[idx].push_back(result);
+        }
+    }
+
+    while results.len() < max_results {
+        let before = results.len();
+        for group in &mut groups {
+            if results.len() >= max_results {
+                break;
+            }
+            if let Some(result) = group.pop_front() {
+                results.push(result);
+            }
+        }
+        if results.len() == before {
+            break;
         }
     }
 }
@@ -1346,9 +2305,8 @@ fn context_for_line_cached(
 }
 
 fn read_file_lines(file_path: &Path) -> Option<Vec<String>> {
-    let file = fs::File::open(file_path).ok()?;
-    let reader = BufReader::new(file);
-    Some(reader.lines().map_while(|line| line.ok()).collect())
+    let decoded = encoding::read_text_file(file_path).ok()??;
+    Some(decoded.text.lines().map(str::to_string).collect())
 }
 
 fn get_context_from_string_lines(
@@ -1417,6 +2375,7 @@ fn collect_index_candidates(
         .into());
     }
 
+    compat::check_readable(index_root)?;
     let index = Index::open_in_dir(&index_path).context("Failed to open index")?;
     let reader = index.reader()?;
     let searcher = reader.searcher();
@@ -1445,14 +2404,26 @@ fn collect_index_candidates(
         .get_field("line_number")
         .context("Missing line_number field")?;
     let path_exact_field = schema.get_field("path_exact").ok();
+    let docs_field = schema.get_field("docs").ok();
+    if ranking_strategy.docs_only && docs_field.is_none() {
+        anyhow::bail!(
+            "This index has no doc comments for --search-docs. Run `cgrep index` to rebuild it."
+        );
+    }
 
     let literal_query = !fuzzy && query_requires_literal_handling(query);
     let query_for_parser = if literal_query {
-        escape_as_query_phrase(query)
+        cgrep::syntax::escape_as_query_phrase(query)
     } else {
         query.to_string()
     };
 
+    // `--search-docs` matches doc comment prose only.
+    let text_fields: Vec<tantivy::schema::Field> = match docs_field {
+        Some(docs_field) if ranking_strategy.docs_only => vec![docs_field],
+        Some(docs_field) => vec![content_field, symbols_field, path_field, docs_field],
+        None => vec![content_field, symbols_field, path_field],
+    };
     let text_query: Box<dyn tantivy::query::Query> = if fuzzy {
         let terms: Vec<&str> = query.split_whitespace().collect();
         if terms.is_empty() {
@@ -1463,31 +2434,42 @@ fn collect_index_candidates(
         for term in terms {
             let distance = if term.len() <= 4 { 1 } else { 2 };
 
-            let content_term = Term::from_field_text(content_field, term);
-            let content_fuzzy = FuzzyTermQuery::new(content_term, distance, true);
-            fuzzy_queries.push((Occur::Should, Box::new(content_fuzzy)));
-
-            let symbols_term = Term::from_field_text(symbols_field, term);
-            let symbols_fuzzy = FuzzyTermQuery::new(symbols_term, distance, true);
-            fuzzy_queries.push((Occur::Should, Box::new(symbols_fuzzy)));
+            for field in text_fields.iter().filter(|field| **field != path_field) {
+                let fuzzy_term =
+                    FuzzyTermQuery::new(Term::from_field_text(*field, term), distance, true);
+                fuzzy_queries.push((Occur::Should, Box::new(fuzzy_term)));
+            }
         }
 
         Box::new(BooleanQuery::new(fuzzy_queries))
     } else {
-        let mut query_parser =
-            QueryParser::for_index(&index, vec![content_field, symbols_field, path_field]);
+        let mut query_parser = QueryParser::for_index(&index, text_fields);
         query_parser.set_field_boost(symbols_field, 2.5);
         query_parser.set_field_boost(path_field, 0.3);
+        if let Some(docs_field) = docs_field {
+            query_parser.set_field_boost(docs_field, ranking_strategy.docs_boost);
+        }
         let (parsed_query, _errors) = query_parser.parse_query_lenient(&query_for_parser);
         parsed_query
     };
 
     let doc_type_term = Term::from_field_text(doc_type_field, doc_type);
-    let doc_type_query = TermQuery::new(doc_type_term, tantivy::schema::IndexRecordOption::Basic);
-    let mut clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> = vec![
-        (Occur::Must, text_query),
-        (Occur::Must, Box::new(doc_type_query)),
-    ];
+    let mut doc_type_query: Box<dyn tantivy::query::Query> = Box::new(TermQuery::new(
+        doc_type_term,
+        tantivy::schema::IndexRecordOption::Basic,
+    ));
+    // `--type sql` etc. also searches embedded regions inside host files.
+    let include_regions = doc_type == "file" && file_type.is_some_and(regions::is_embedded_type);
+    if include_regions {
+        let region_term = Term::from_field_text(doc_type_field, "region");
+        let region_query = TermQuery::new(region_term, tantivy::schema::IndexRecordOption::Basic);
+        doc_type_query = Box::new(BooleanQuery::new(vec![
+            (Occur::Should, doc_type_query),
+            (Occur::Should, Box::new(region_query)),
+        ]));
+    }
+    let mut clauses: Vec<(Occur, Box<dyn tantivy::query::Query>)> =
+        vec![(Occur::Must, text_query), (Occur::Must, doc_type_query)];
     if let Some(scope_query) =
         path_exact_field.and_then(|f| build_scope_path_query(f, search_root, index_root))
     {
@@ -1502,7 +2484,7 @@ fn collect_index_candidates(
     let mut per_path_counts: HashMap<String, usize> = HashMap::new();
 
     for (score, doc_address) in &top_docs {
-        if candidates.len() >= max_candidates {
+        if candidates.len() >= max_candidates || cancel::should_stop() {
             break;
         }
 
@@ -1530,7 +2512,20 @@ fn collect_index_candidates(
             }
         }
 
-        if !matches_file_type(&scope_path, file_type) {
+        let doc_type_value = doc
+            .get_first(doc_type_field)
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let language_value = doc
+            .get_first(language_field)
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let type_matches = if doc_type_value == "region" {
+            file_type.is_some_and(|filter| regions::region_matches_type(language_value, filter))
+        } else {
+            matches_file_type(&scope_path, file_type)
+        };
+        if !type_matches {
             continue;
         }
         if !matches_glob_compiled(&scope_path, compiled_glob) {
@@ -1573,14 +2568,6 @@ fn collect_index_candidates(
             .and_then(|v| v.as_u64())
             .unwrap_or(1) as usize;
 
-        let doc_type_value = doc
-            .get_first(doc_type_field)
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let language_value = doc
-            .get_first(language_field)
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
         let symbol_kind = if doc_type_value == "symbol" {
             infer_symbol_kind_from_content(content_value)
         } else {
@@ -1618,8 +2605,17 @@ fn collect_index_candidates(
             None
         };
 
-        if doc_type_value == "file" {
-            let matches = find_snippets_with_lines(content_value, query, 150);
+        if doc_type_value == "file" || doc_type_value == "region" {
+            let mut matches = find_snippets_with_lines(content_value, query, 150);
+            if ranking_strategy.docs_only {
+                let docs_value = docs_field
+                    .and_then(|field| doc.get_first(field))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                matches.retain(|(_, rel_line)| {
+                    is_doc_comment_line(content_value, *rel_line, docs_value)
+                });
+            }
             if !matches.is_empty() {
                 for (snippet, rel_line) in matches {
                     if candidates.len() >= max_candidates {
@@ -1682,7 +2678,6 @@ fn keyword_search(
     index_root: &Path,
     search_root: &Path,
     workspace_root: &Path,
-    index_path: &Path,
     max_results: usize,
     context: usize,
     file_type: Option<&str>,
@@ -1693,35 +2688,21 @@ fn keyword_search(
     config_exclude_patterns: &[CompiledGlob],
     changed_filter: Option<&ChangedFiles>,
     requested_mode: IndexMode,
+    scan_fallback: Option<ScanFallback>,
     fuzzy: bool,
     regex: Option<&Regex>,
     case_sensitive: bool,
     recursive: bool,
     no_ignore: bool,
+    ignore_rules: IgnoreRules,
     use_cache: bool,
     cache_ttl_ms: u64,
     ranking_strategy: &RankingStrategy,
 ) -> Result<SearchOutcome> {
-    let force_scan_for_literal_query = requested_mode == IndexMode::Index
-        && regex.is_none()
-        && !fuzzy
-        && should_force_scan_for_literal_query(query);
-    let full_index_available = has_full_index(index_path);
-    let mut use_index =
-        requested_mode == IndexMode::Index && full_index_available && !force_scan_for_literal_query;
+    let use_index = requested_mode == IndexMode::Index && scan_fallback.is_none();
     let reuse_active = reuse_stale_filter_active(index_root);
-    if use_index && reuse_active && !index_root.join(INDEX_DIR).join("metadata.json").is_file() {
-        eprintln!(
-            "Reuse stale-filter metadata missing at {}. Falling back to scan mode.",
-            index_root.join(INDEX_DIR).join("metadata.json").display()
-        );
-        use_index = false;
-    }
-    if requested_mode == IndexMode::Index && !full_index_available {
-        eprintln!(
-            "Index not found at {}. Falling back to scan mode.",
-            index_path.display()
-        );
+    if ranking_strategy.docs_only && !use_index {
+        eprintln!("Warning: --search-docs needs the index; scan mode searches all text.");
     }
     let effective_mode = if use_index {
         IndexMode::Index
@@ -1740,7 +2721,7 @@ fn keyword_search(
     let cache_key = CacheKey {
         query: normalized_query,
         mode: format!(
-            "keyword:{}:r{}:ni{}:{}:pv3",
+            "keyword:{}:r{}:ni{}{}:{}:pv3",
             if effective_mode == IndexMode::Index {
                 "index"
             } else {
@@ -1748,6 +2729,7 @@ fn keyword_search(
             },
             usize::from(recursive),
             usize::from(no_ignore),
+            ignore_rules.cache_tag(),
             ranking_strategy.cache_mode_suffix(),
         ),
         max_results,
@@ -1766,12 +2748,20 @@ fn keyword_search(
     if effective_use_cache {
         if let Ok(cache) = SearchCache::new(index_root, cache_ttl_ms) {
             if let Ok(Some(entry)) = cache.get::<KeywordCachePayload>(&cache_key) {
+                let mode = parse_index_mode(&entry.data.mode);
+                let mut results = entry.data.results;
+                let stale_dropped = if mode == IndexMode::Index {
+                    drop_missing_files(&mut results, &[workspace_root, search_root])
+                } else {
+                    0
+                };
                 return Ok(SearchOutcome {
-                    results: entry.data.results,
+                    results,
                     files_with_matches: entry.data.files_with_matches,
                     total_matches: entry.data.total_matches,
-                    mode: parse_index_mode(&entry.data.mode),
+                    mode,
                     cache_hit: true,
+                    stale_dropped,
                 });
             }
         }
@@ -1811,11 +2801,12 @@ fn keyword_search(
             case_sensitive,
             recursive,
             no_ignore,
+            ignore_rules,
             ranking_strategy,
         )?
     };
 
-    if effective_use_cache {
+    if effective_use_cache && !cancel::stopped() {
         if let Ok(cache) = SearchCache::new(index_root, cache_ttl_ms) {
             let payload = KeywordCachePayload {
                 results: outcome.results.clone(),
@@ -1843,6 +2834,10 @@ fn parse_index_mode(mode: &str) -> IndexMode {
 
 const KEYWORD_FALLBACK_CONFIDENCE_THRESHOLD: f32 = 0.45;
 const MAX_INITIAL_RESULTS_PER_PATH: usize = 2;
+/// Candidate pool multiplier for `--diversify`.
+const DIVERSIFY_CANDIDATE_FACTOR: usize = 4;
+/// Candidate pool multiplier when `.gitattributes` linguist classes apply.
+const LINGUIST_CANDIDATE_FACTOR: usize = 2;
 const NOISY_PATH_SEGMENTS: &[&str] = &["target/", "dist/", "build/", "node_modules/", ".venv/"];
 const CONTEXTUAL_NOISY_PATH_SEGMENTS: &[&str] = &[
     "cuda",
@@ -1857,6 +2852,72 @@ const CONTEXTUAL_NOISY_PATH_SEGMENTS: &[&str] = &[
     "hip",
 ];
 
+/// Why a keyword search that asked for the index scans files instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanFallback {
+    /// No full index at the index path
+    IndexMissing,
+    /// The query has no word the index can look up
+    LiteralQuery,
+    /// Reuse mode is active but its stale-filter metadata is gone
+    ReuseMetadataMissing,
+}
+
+impl ScanFallback {
+    /// Value of json2 `meta.fallback_reason`.
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::IndexMissing => "index_missing",
+            Self::LiteralQuery => "literal_query",
+            Self::ReuseMetadataMissing => "reuse_metadata_missing",
+        }
+    }
+
+    fn describe(self, index_root: &Path, index_path: &Path) -> String {
+        match self {
+            Self::IndexMissing => format!("Index not found at {}", index_path.display()),
+            Self::LiteralQuery => "Query has no word the index can look up".to_string(),
+            Self::ReuseMetadataMissing => format!(
+                "Reuse stale-filter metadata missing at {}",
+                index_root.join(INDEX_DIR).join("metadata.json").display()
+            ),
+        }
+    }
+
+    fn suggestion(self) -> &'static str {
+        match self {
+            Self::IndexMissing => "run `cgrep index` for ranked index search",
+            Self::LiteralQuery => "add a word of two or more letters to use the index",
+            Self::ReuseMetadataMissing => "rerun `cgrep index` to restore it",
+        }
+    }
+}
+
+/// The reason an index keyword search must scan, if any.
+fn scan_fallback_reason(
+    query: &str,
+    index_root: &Path,
+    index_path: &Path,
+    requested_mode: IndexMode,
+    fuzzy: bool,
+    has_regex: bool,
+) -> Option<ScanFallback> {
+    if requested_mode != IndexMode::Index {
+        return None;
+    }
+    if !has_full_index(index_path) {
+        Some(ScanFallback::IndexMissing)
+    } else if !has_regex && !fuzzy && should_force_scan_for_literal_query(query) {
+        Some(ScanFallback::LiteralQuery)
+    } else if reuse_stale_filter_active(index_root)
+        && !index_root.join(INDEX_DIR).join("metadata.json").is_file()
+    {
+        Some(ScanFallback::ReuseMetadataMissing)
+    } else {
+        None
+    }
+}
+
 struct KeywordFallbackPolicy<'a> {
     mode: HybridSearchMode,
     explicit_mode: bool,
@@ -1983,19 +3044,6 @@ fn is_query_parser_metachar(ch: char) -> bool {
     )
 }
 
-fn escape_as_query_phrase(query: &str) -> String {
-    let mut out = String::with_capacity(query.len() + 2);
-    out.push('"');
-    for ch in query.chars() {
-        if ch == '"' || ch == '\\' {
-            out.push('\\');
-        }
-        out.push(ch);
-    }
-    out.push('"');
-    out
-}
-
 fn matches_literal_query(
     content: &str,
     symbols: &str,
@@ -2044,6 +3092,8 @@ struct ScoreComponents {
     path_boost: f32,
     symbol_boost: f32,
     changed_boost: f32,
+    churn_boost: f32,
+    recency_boost: f32,
     kind_boost: f32,
     penalties: f32,
     final_score: f32,
@@ -2056,6 +3106,8 @@ impl ScoreComponents {
             path_boost: self.path_boost,
             symbol_boost: self.symbol_boost,
             changed_boost: self.changed_boost,
+            churn_boost: self.churn_boost,
+            recency_boost: self.recency_boost,
             kind_boost: self.kind_boost,
             penalties: self.penalties,
             final_score: self.final_score,
@@ -2149,6 +3201,7 @@ fn infer_symbol_kind_from_content(content: &str) -> Option<String> {
             | "enum"
             | "type"
             | "property"
+            | "key"
             | "constant"
             | "variable"
     ) {
@@ -2200,6 +3253,20 @@ fn language_ranking_bonus(
     }
 }
 
+/// Score multiplier bonus for results in a language named by the query.
+const LANGUAGE_ROUTE_BOOST: f32 = 0.25;
+
+/// Candidate pool multiplier for index search while a language route is active.
+const LANGUAGE_ROUTE_POOL_FACTOR: usize = 3;
+
+fn language_route_bonus(scope_path: &str, language_value: &str, route: Option<&str>) -> f32 {
+    if language_ranking_bonus(scope_path, language_value, route) > 0.0 {
+        LANGUAGE_ROUTE_BOOST
+    } else {
+        0.0
+    }
+}
+
 fn query_class_weights(class: QueryClass) -> (f32, f32, f32, f32, f32, f32) {
     match class {
         QueryClass::IdentifierLike => (0.75, 1.35, 1.05, 0.90, 1.20, 1.15),
@@ -2230,6 +3297,26 @@ fn weak_signal_penalty(
     }
 }
 
+/// Bonus for recently modified files, bucketed by mtime age so small clock
+/// differences between files do not reorder results.
+fn recency_ranking_bonus(search_root: &Path, scope_path: &str, now: SystemTime) -> f32 {
+    const DAY: u64 = 24 * 60 * 60;
+    let Some(age) = fs::metadata(search_root.join(scope_path))
+        .and_then(|meta| meta.modified())
+        .ok()
+        .map(|modified| now.duration_since(modified).unwrap_or_default())
+    else {
+        return 0.0;
+    };
+    match age.as_secs() {
+        secs if secs < DAY => 0.05,
+        secs if secs < 7 * DAY => 0.04,
+        secs if secs < 30 * DAY => 0.025,
+        secs if secs < 90 * DAY => 0.01,
+        _ => 0.0,
+    }
+}
+
 fn compute_keyword_score_components(
     bm25: f32,
     scope_path: &str,
@@ -2247,13 +3334,21 @@ fn compute_keyword_score_components(
         strategy.identifier_query.as_deref(),
     );
 
+    let route_boost = language_route_bonus(
+        scope_path,
+        language_value,
+        strategy.language_route.as_deref(),
+    );
+
     if !strategy.enabled {
-        let factor = (1.0 + path_legacy + symbol_legacy).max(0.05);
+        let factor = (1.0 + path_legacy + symbol_legacy + route_boost).max(0.05);
         return ScoreComponents {
             bm25,
-            path_boost: path_legacy,
+            path_boost: path_legacy + route_boost,
             symbol_boost: symbol_legacy,
             changed_boost: 0.0,
+            churn_boost: 0.0,
+            recency_boost: 0.0,
             kind_boost: 0.0,
             penalties: 0.0,
             final_score: bm25 * factor,
@@ -2283,20 +3378,36 @@ fn compute_keyword_score_components(
         query_class_weights(strategy.query_class);
 
     let language_boost = language_base * strategy.weights.language_weight * language_class_w;
-    let path_boost = (path_base * strategy.weights.path_weight * path_class_w) + language_boost;
+    let path_boost =
+        (path_base * strategy.weights.path_weight * path_class_w) + language_boost + route_boost;
     let symbol_boost = symbol_base * strategy.weights.symbol_weight * symbol_class_w;
     let changed_boost = changed_base * strategy.weights.changed_weight * changed_class_w;
+    let churn_base = strategy.churn.get(scope_path).copied().unwrap_or(0.0) * 0.06;
+    let churn_boost = churn_base * strategy.weights.churn_weight;
+    let recency_base = strategy.recency.as_ref().map_or(0.0, |(root, now)| {
+        recency_ranking_bonus(root, scope_path, *now)
+    });
+    let recency_boost = recency_base * strategy.weights.recency_weight;
     let kind_boost = kind_base * strategy.weights.kind_weight * kind_class_w;
     let penalties =
         (-noisy_penalty) + (weak_penalty_base * strategy.weights.weak_signal_penalty * penalty_w);
 
-    let factor =
-        (1.0 + path_boost + symbol_boost + changed_boost + kind_boost + penalties).clamp(0.05, 5.0);
+    let factor = (1.0
+        + path_boost
+        + symbol_boost
+        + changed_boost
+        + churn_boost
+        + recency_boost
+        + kind_boost
+        + penalties)
+        .clamp(0.05, 5.0);
     ScoreComponents {
         bm25,
         path_boost,
         symbol_boost,
         changed_boost,
+        churn_boost,
+        recency_boost,
         kind_boost,
         penalties,
         final_score: bm25 * factor,
@@ -2352,6 +3463,57 @@ fn fallback_hybrid_results(bm25_results: &[BM25Result]) -> Vec<HybridResult> {
         .collect()
 }
 
+/// Map file-level candidates onto embedded line chunks, one result per chunk.
+///
+/// Candidates in files that have symbol embeddings (and so no chunks) are dropped;
+/// the symbol candidates already cover them.
+fn chunk_candidates(
+    storage: &EmbeddingStorage,
+    candidates: Vec<IndexCandidate>,
+) -> Vec<BM25Result> {
+    let mut chunks_by_path: HashMap<String, Vec<(String, u32, u32)>> = HashMap::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut results = Vec::new();
+
+    for candidate in candidates {
+        let Some(line) = candidate.line else {
+            continue;
+        };
+        let chunks = chunks_by_path
+            .entry(candidate.stored_path.clone())
+            .or_insert_with(|| {
+                storage
+                    .get_symbols_for_path(&candidate.stored_path)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|symbol| symbol.symbol_kind == CHUNK_SYMBOL_KIND)
+                    .map(|symbol| (symbol.symbol_id, symbol.start_line, symbol.end_line))
+                    .collect()
+            });
+        let line = line as u32;
+        let Some((chunk_id, start, end)) = chunks
+            .iter()
+            .find(|(_, start, end)| *start <= line && line <= *end)
+        else {
+            continue;
+        };
+        if !seen.insert(chunk_id.clone()) {
+            continue;
+        }
+        results.push(BM25Result {
+            path: candidate.stored_path,
+            score: candidate.score,
+            snippet: candidate.snippet,
+            line: Some(line as usize),
+            chunk_start: Some(*start),
+            chunk_end: Some(*end),
+            symbol_id: Some(chunk_id.clone()),
+        });
+    }
+
+    results
+}
+
 fn semantic_backfill_results(
     storage: &EmbeddingStorage,
     query_embedding: &[f32],
@@ -2399,11 +3561,22 @@ fn hybrid_result_key(result: &HybridResult) -> String {
     })
 }
 
+/// Descending score order. Under `--stable`, scores are compared at output
+/// precision so float noise cannot reorder ties across platforms.
+fn compare_scores(a: f32, b: f32) -> std::cmp::Ordering {
+    if cgrep::output::stable() {
+        let (a, b) = (
+            cgrep::output::stable_float(f64::from(a)),
+            cgrep::output::stable_float(f64::from(b)),
+        );
+        return b.total_cmp(&a);
+    }
+    b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
+}
+
 fn sort_hybrid_results(results: &mut [HybridResult]) {
     results.sort_by(|a, b| {
-        b.score
-            .partial_cmp(&a.score)
-            .unwrap_or(std::cmp::Ordering::Equal)
+        compare_scores(a.score, b.score)
             .then_with(|| {
                 b.vector_norm
                     .partial_cmp(&a.vector_norm)
@@ -2437,12 +3610,18 @@ fn index_search(
     recursive: bool,
     ranking_strategy: &RankingStrategy,
 ) -> Result<SearchOutcome> {
+    // A routed language can lift results from below the BM25 cut, so rank a wider pool.
+    let candidate_limit = if ranking_strategy.language_route.is_some() {
+        max_results.saturating_mul(LANGUAGE_ROUTE_POOL_FACTOR)
+    } else {
+        max_results
+    };
     let candidates = collect_index_candidates(
         query,
         index_root,
         search_root,
         workspace_root,
-        max_results,
+        candidate_limit,
         "file",
         file_type,
         compiled_glob,
@@ -2460,8 +3639,14 @@ fn index_search(
     let mut context_cache: HashMap<PathBuf, Vec<String>> = HashMap::new();
     let stale_filter = load_reuse_stale_filter(index_root);
     let mut file_hash_cache: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut stale_dropped = 0;
 
     for candidate in candidates {
+        // Deleted since indexing; the next refresh removes its documents.
+        if !indexed_file_exists(&candidate.full_path) {
+            stale_dropped += 1;
+            continue;
+        }
         if let Some(filter) = stale_filter.as_ref() {
             if !candidate_is_fresh(&candidate, filter, &mut file_hash_cache) {
                 continue;
@@ -2477,7 +3662,7 @@ fn index_search(
         let display_path = candidate.display_path;
         files_with_matches.insert(display_path.clone());
 
-        results.push(SearchResult {
+        let result = SearchResult {
             path: display_path,
             score: candidate.score,
             snippet: candidate.snippet,
@@ -2491,12 +3676,29 @@ fn index_search(
             chunk_start: None,
             chunk_end: None,
             explain: candidate.explain,
-        });
+            linguist: None,
+            stale: false,
+            column: None,
+            end_column: None,
+            match_bytes: None,
+            chunk_bytes: None,
+            symbol_header: Vec::new(),
+        };
+        if results.len() < max_results {
+            stream_hit(&result);
+        }
+        results.push(result);
     }
 
-    if ranking_strategy.enabled || ranking_strategy.explain {
+    if ranking_strategy.enabled
+        || ranking_strategy.explain
+        || ranking_strategy.language_route.is_some()
+        || cgrep::output::stable()
+    {
         sort_results_deterministic(&mut results);
     }
+    results.truncate(max_results);
+    files_with_matches = results.iter().map(|r| r.path.clone()).collect();
     trim_explain_results(
         &mut results,
         ranking_strategy.explain,
@@ -2511,6 +3713,7 @@ fn index_search(
         total_matches,
         mode: IndexMode::Index,
         cache_hit: false,
+        stale_dropped,
     })
 }
 
@@ -2530,6 +3733,7 @@ fn scan_search(
     case_sensitive: bool,
     recursive: bool,
     no_ignore: bool,
+    ignore_rules: IgnoreRules,
     ranking_strategy: &RankingStrategy,
 ) -> Result<SearchOutcome> {
     if query.trim().is_empty() {
@@ -2540,7 +3744,9 @@ fn scan_search(
 
     let scanner = FileScanner::new(root)
         .with_recursive(recursive)
-        .with_gitignore(!no_ignore);
+        .with_gitignore(!no_ignore)
+        .with_ignore_rules(ignore_rules)
+        .with_cancellation(true);
     let mut files = scanner.scan()?;
     files.sort_by(|a, b| a.path.cmp(&b.path));
 
@@ -2548,7 +3754,7 @@ fn scan_search(
     let candidate_cap = max_results.max(1);
 
     'files: for file in files {
-        if results.len() >= candidate_cap {
+        if results.len() >= candidate_cap || cancel::should_stop() {
             break;
         }
         let scope_path = scope_relative_path(&file.path, root)
@@ -2590,6 +3796,9 @@ fn scan_search(
                 if !scan_line_matches(line, query, &query_lower, regex, case_sensitive) {
                     continue;
                 }
+                if changed_filter.is_some_and(|filter| !filter.matches_line(&scope_path, idx + 1)) {
+                    continue;
+                }
 
                 let snippet = truncate_with_ellipsis(line.trim(), 150);
                 let symbol_kind = infer_kind_from_snippet(&snippet);
@@ -2602,7 +3811,7 @@ fn scan_search(
                     symbol_kind.as_deref(),
                     ranking_strategy,
                 );
-                results.push(SearchResult {
+                let result = SearchResult {
                     path: display_path.clone(),
                     score: score_components.final_score,
                     snippet,
@@ -2620,7 +3829,16 @@ fn scan_search(
                     } else {
                         None
                     },
-                });
+                    linguist: None,
+                    stale: false,
+                    column: None,
+                    end_column: None,
+                    match_bytes: None,
+                    chunk_bytes: None,
+                    symbol_header: Vec::new(),
+                };
+                stream_hit(&result);
+                results.push(result);
             }
             continue;
         }
@@ -2633,6 +3851,9 @@ fn scan_search(
             if !scan_line_matches(line, query, &query_lower, regex, case_sensitive) {
                 continue;
             }
+            if changed_filter.is_some_and(|filter| !filter.matches_line(&scope_path, idx + 1)) {
+                continue;
+            }
 
             let snippet = truncate_with_ellipsis(line.trim(), 150);
             let symbol_kind = infer_kind_from_snippet(&snippet);
@@ -2646,7 +3867,7 @@ fn scan_search(
                 ranking_strategy,
             );
             let (context_before, context_after) = get_context_from_lines(&lines, idx + 1, context);
-            results.push(SearchResult {
+            let result = SearchResult {
                 path: display_path.clone(),
                 score: score_components.final_score,
                 snippet,
@@ -2664,7 +3885,16 @@ fn scan_search(
                 } else {
                     None
                 },
-            });
+                linguist: None,
+                stale: false,
+                column: None,
+                end_column: None,
+                match_bytes: None,
+                chunk_bytes: None,
+                symbol_header: Vec::new(),
+            };
+            stream_hit(&result);
+            results.push(result);
         }
     }
 
@@ -2691,9 +3921,22 @@ fn scan_search(
         total_matches,
         mode: IndexMode::Scan,
         cache_hit: false,
+        stale_dropped: 0,
     })
 }
 
+/// Unified diff for `--from-patch`, from a file or `-` for stdin.
+fn read_patch(path: &str) -> Result<String> {
+    if path == "-" {
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buf)
+            .context("Failed to read patch from stdin")?;
+        return Ok(buf);
+    }
+    std::fs::read_to_string(path).with_context(|| format!("Failed to read patch file: {path}"))
+}
+
 fn has_full_index(index_path: &Path) -> bool {
     index_path.join("meta.json").is_file()
 }
@@ -2738,9 +3981,7 @@ fn contains_ascii_case_insensitive(haystack: &str, needle_lower: &str) -> bool {
 
 fn sort_results_deterministic(results: &mut [SearchResult]) {
     results.sort_by(|a, b| {
-        b.score
-            .partial_cmp(&a.score)
-            .unwrap_or(std::cmp::Ordering::Equal)
+        compare_scores(a.score, b.score)
             .then_with(|| a.path.cmp(&b.path))
             .then_with(|| a.line.cmp(&b.line))
             .then_with(|| a.snippet.cmp(&b.snippet))
@@ -2769,6 +4010,20 @@ fn sort_and_dedupe_scan_results(results: &mut Vec<SearchResult>) {
         .retain(|result| seen.insert((result.path.clone(), result.line, result.snippet.clone())));
 }
 
+/// Whether line `rel_line` of a chunk holds part of one of its doc comments.
+fn is_doc_comment_line(content: &str, rel_line: usize, docs: &str) -> bool {
+    let Some(line) = rel_line
+        .checked_sub(1)
+        .and_then(|idx| content.lines().nth(idx))
+    else {
+        return false;
+    };
+    docs.lines()
+        .map(str::trim)
+        .filter(|doc_line| !doc_line.is_empty())
+        .any(|doc_line| line.contains(doc_line))
+}
+
 /// Hybrid search combining BM25 with vector embeddings
 #[allow(clippy::too_many_arguments)]
 fn hybrid_search(
@@ -2790,6 +4045,8 @@ fn hybrid_search(
     recursive: bool,
     use_cache: bool,
     cache_ttl_ms: u64,
+    language_route: Option<&str>,
+    search_docs: bool,
 ) -> Result<SearchOutcome> {
     let index_path = index_root.join(INDEX_DIR);
     let embedding_db_path = index_root.join(".cgrep").join("embeddings.sqlite");
@@ -2804,12 +4061,14 @@ fn hybrid_search(
     let weight_text_milli = (weight_text * 1000.0).round() as i32;
     let weight_vector_milli = (weight_vector * 1000.0).round() as i32;
     let cache_mode = format!(
-        "{}:k{}:wt{}:wv{}:r{}:pv2",
+        "{}:k{}:wt{}:wv{}:r{}:lr{}:sd{}:pv4",
         mode,
         candidate_k,
         weight_text_milli,
         weight_vector_milli,
-        usize::from(recursive)
+        usize::from(recursive),
+        language_route.unwrap_or("-"),
+        usize::from(search_docs)
     );
 
     // Build cache key
@@ -2833,13 +4092,18 @@ fn hybrid_search(
         if let Ok(cache) = SearchCache::new(index_root, cache_ttl_ms) {
             if let Ok(Some(entry)) = cache.get::<Vec<HybridResult>>(&cache_key) {
                 // Return cached results
+                let mut stale_dropped = 0;
                 let results: Vec<SearchResult> = entry
                     .data
                     .iter()
-                    .map(|hr| {
+                    .filter_map(|hr| {
                         let full_path = resolve_full_path(&hr.path, index_root);
+                        if !indexed_file_exists(&full_path) {
+                            stale_dropped += 1;
+                            return None;
+                        }
                         let display_path = workspace_display_path(&full_path, workspace_root);
-                        SearchResult {
+                        Some(SearchResult {
                             path: display_path,
                             score: hr.score,
                             snippet: hr.snippet.clone(),
@@ -2853,7 +4117,14 @@ fn hybrid_search(
                             chunk_start: hr.chunk_start,
                             chunk_end: hr.chunk_end,
                             explain: None,
-                        }
+                            linguist: None,
+                            stale: false,
+                            column: None,
+                            end_column: None,
+                            match_bytes: None,
+                            chunk_bytes: None,
+                            symbol_header: Vec::new(),
+                        })
                     })
                     .collect();
 
@@ -2870,6 +4141,7 @@ fn hybrid_search(
                     total_matches,
                     mode: IndexMode::Index,
                     cache_hit: true,
+                    stale_dropped,
                 });
             }
         }
@@ -2907,7 +4179,9 @@ fn hybrid_search(
         ));
     }
 
-    let ranking_strategy = legacy_ranking_strategy(query, file_type, changed_filter);
+    let ranking_strategy = legacy_ranking_strategy(query, file_type, changed_filter)
+        .with_language_route(language_route)
+        .with_docs(config.search().docs_boost(), search_docs);
     let bm25_candidates = collect_index_candidates(
         query,
         index_root,
@@ -2927,7 +4201,7 @@ fn hybrid_search(
     )?;
 
     // Convert to BM25Result format
-    let bm25_results: Vec<BM25Result> = bm25_candidates
+    let mut bm25_results: Vec<BM25Result> = bm25_candidates
         .into_iter()
         .map(|candidate| BM25Result {
             path: candidate.stored_path,
@@ -2944,6 +4218,35 @@ fn hybrid_search(
         })
         .collect();
 
+    // Files without symbols are embedded as line chunks; attach file-level
+    // matches to the chunk that covers them.
+    if let Some(ref storage) = embedding_storage {
+        let file_candidates = collect_index_candidates(
+            query,
+            index_root,
+            search_root,
+            workspace_root,
+            candidate_k,
+            "file",
+            file_type,
+            compiled_glob,
+            compiled_exclude,
+            config_exclude_patterns,
+            changed_filter,
+            recursive,
+            false,
+            false,
+            &ranking_strategy,
+        )?;
+        bm25_results.extend(chunk_candidates(storage, file_candidates));
+        bm25_results.sort_by(|a, b| {
+            compare_scores(a.score, b.score)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+        bm25_results.truncate(candidate_k);
+    }
+
     // Create hybrid searcher
     let hybrid_config = HybridConfig::new(weight_text, weight_vector)
         .with_candidate_k(candidate_k)
@@ -3029,12 +4332,14 @@ fn hybrid_search(
             fallback_hybrid_results(&bm25_results)
         }
     };
+    let hybrid_results = merge_overlapping_results(hybrid_results);
 
     // Convert to SearchResult with context
     let mut results: Vec<SearchResult> = Vec::with_capacity(max_results.min(hybrid_results.len()));
     let mut filtered_hybrid_results: Vec<HybridResult> = Vec::with_capacity(max_results);
     let mut files_with_matches: HashSet<String> = HashSet::new();
     let mut context_cache: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    let mut stale_dropped = 0;
 
     for hr in hybrid_results.iter() {
         if results.len() >= max_results {
@@ -3063,6 +4368,10 @@ fn hybrid_search(
         {
             continue;
         }
+        if !indexed_file_exists(&full_path) {
+            stale_dropped += 1;
+            continue;
+        }
 
         files_with_matches.insert(display_path.clone());
         filtered_hybrid_results.push(hr.clone());
@@ -3085,11 +4394,18 @@ fn hybrid_search(
             chunk_start: hr.chunk_start,
             chunk_end: hr.chunk_end,
             explain: None,
+            linguist: None,
+            stale: false,
+            column: None,
+            end_column: None,
+            match_bytes: None,
+            chunk_bytes: None,
+            symbol_header: Vec::new(),
         });
     }
 
-    // Store in cache
-    if use_cache {
+    // Store in cache; results cut short by a timeout or Ctrl-C are partial.
+    if use_cache && !cancel::stopped() {
         if let Ok(cache) = SearchCache::new(index_root, cache_ttl_ms) {
             let _ = cache.put(&cache_key, filtered_hybrid_results);
         }
@@ -3104,6 +4420,7 @@ fn hybrid_search(
         total_matches,
         mode: IndexMode::Index,
         cache_hit: false,
+        stale_dropped,
     })
 }
 
@@ -3297,14 +4614,199 @@ fn normalize_path(path: &Path) -> PathBuf {
 }
 
 fn resolve_full_path(path_value: &str, index_root: &Path) -> PathBuf {
-    let path = Path::new(path_value);
+    let path = paths::decode_path(path_value);
     if path.is_absolute() {
-        path.to_path_buf()
+        path
     } else {
         index_root.join(path)
     }
 }
 
+/// A file's normalized lines and, for UTF-8 files, where each starts on disk.
+struct LineIndex {
+    lines: Vec<String>,
+    starts: Option<Vec<usize>>,
+}
+
+impl LineIndex {
+    fn read(path: &Path) -> Option<Self> {
+        let bytes = fs::read(path).ok()?;
+        let decoded = encoding::decode_text(&bytes)?;
+        // Offsets into transcoded text would not address the file's bytes.
+        let starts = decoded
+            .encoding
+            .is_none()
+            .then(|| encoding::line_starts(&bytes, decoded.bom));
+        Some(Self {
+            lines: decoded.text.lines().map(str::to_string).collect(),
+            starts,
+        })
+    }
+
+    /// On-disk offset of byte `col` (0-based) of 1-based `line`.
+    fn offset(&self, line: usize, col: usize) -> Option<usize> {
+        let start = self.starts.as_ref()?.get(line.checked_sub(1)?)?;
+        Some(start + col)
+    }
+
+    fn span(&self, start: (usize, usize), end: (usize, usize)) -> Option<ByteSpan> {
+        Some(ByteSpan {
+            start: self.offset(start.0, start.1)?,
+            end: self.offset(end.0, end.1)?,
+        })
+    }
+}
+
+/// Whether the file behind an indexed path (archive entries included) still
+/// exists.
+fn indexed_file_exists(path: &Path) -> bool {
+    archive::containing_file(path).is_file()
+}
+
+/// Remove index hits whose file no longer exists under any of `roots`,
+/// returning how many were dropped.
+fn drop_missing_files(results: &mut Vec<SearchResult>, roots: &[&Path]) -> usize {
+    let before = results.len();
+    results.retain(|result| {
+        let decoded = paths::decode_path(&result.path);
+        roots
+            .iter()
+            .any(|root| indexed_file_exists(&root.join(&decoded)))
+    });
+    before - results.len()
+}
+
+/// Outcome of `--verify`.
+#[derive(Debug)]
+struct ResultVerification {
+    /// Distinct result files hashed.
+    checked: usize,
+    /// Hashed files whose content no longer matches the index, as displayed.
+    stale_files: Vec<String>,
+}
+
+/// Hash the files behind the top results, at most `sample` distinct files in
+/// rank order, against the index manifest and mark results from changed or
+/// deleted files `stale`.
+///
+/// `None` when the index has no manifest.
+fn verify_result_files(
+    results: &mut [SearchResult],
+    index_root: &Path,
+    roots: &[&Path],
+    sample: usize,
+) -> Option<ResultVerification> {
+    let mut sampled: Vec<(&str, PathBuf)> = Vec::new();
+    for result in results.iter() {
+        if sampled.len() >= sample {
+            break;
+        }
+        if sampled.iter().any(|(path, _)| *path == result.path) {
+            continue;
+        }
+        let decoded = paths::decode_path(&result.path);
+        let full_path = roots
+            .iter()
+            .map(|root| root.join(&decoded))
+            .find(|candidate| candidate.is_file())
+            .unwrap_or_else(|| roots[0].join(&decoded));
+        sampled.push((result.path.as_str(), full_path));
+    }
+    let files: Vec<PathBuf> = sampled.iter().map(|(_, path)| path.clone()).collect();
+    let changed = freshness::changed_files(index_root, &files)?;
+    let stale_files: Vec<String> = sampled
+        .iter()
+        .filter(|(_, full_path)| changed.contains(full_path))
+        .map(|(path, _)| path.to_string())
+        .collect();
+    let checked = sampled.len();
+    for result in results.iter_mut() {
+        result.stale = stale_files.contains(&result.path);
+    }
+    Some(ResultVerification {
+        checked,
+        stale_files,
+    })
+}
+
+/// Locate each result's first query match on its line (columns and byte
+/// offsets) and the byte range of its chunk lines, reading the file under
+/// the first of `roots` that has it.
+///
+/// Results whose line holds no literal match (semantic hits) keep `None`.
+fn annotate_match_locations(
+    results: &mut [SearchResult],
+    roots: &[&Path],
+    query: &str,
+    regex: Option<&regex::Regex>,
+    case_sensitive: bool,
+) {
+    let mut cache: HashMap<PathBuf, Option<LineIndex>> = HashMap::new();
+    for result in results {
+        let decoded = paths::decode_path(&result.path);
+        let Some(full_path) = roots
+            .iter()
+            .map(|root| root.join(&decoded))
+            .find(|candidate| candidate.is_file())
+        else {
+            continue;
+        };
+        let Some(index) = cache
+            .entry(full_path)
+            .or_insert_with_key(|path| LineIndex::read(path))
+        else {
+            continue;
+        };
+        if let Some(line) = result.line {
+            let span = index
+                .lines
+                .get(line.saturating_sub(1))
+                .and_then(|text| match_span(text, query, regex, case_sensitive));
+            if let Some((start, end)) = span {
+                result.column = Some(start + 1);
+                result.end_column = Some(end + 1);
+                result.match_bytes = index.span((line, start), (line, end));
+            }
+        }
+        if let (Some(start), Some(end)) = (result.chunk_start, result.chunk_end) {
+            let (start, end) = (start as usize, end as usize);
+            result.chunk_bytes = end
+                .checked_sub(1)
+                .and_then(|idx| index.lines.get(idx))
+                .and_then(|last| index.span((start, 0), (end, last.len())));
+        }
+    }
+}
+
+/// Byte range of the first match in `text`: the regex, or the earliest
+/// whitespace-separated query term (ASCII case folded unless case-sensitive).
+fn match_span(
+    text: &str,
+    query: &str,
+    regex: Option<&regex::Regex>,
+    case_sensitive: bool,
+) -> Option<(usize, usize)> {
+    if let Some(re) = regex {
+        return re.find(text).map(|found| (found.start(), found.end()));
+    }
+    let haystack = if case_sensitive {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.to_ascii_lowercase())
+    };
+    query
+        .split_whitespace()
+        .filter_map(|term| {
+            let start = if case_sensitive {
+                haystack.find(term)
+            } else {
+                haystack.find(&term.to_ascii_lowercase())
+            }?;
+            Some((start, start + term.len()))
+        })
+        .min()
+}
+
 fn normalize_hint_path(result_path: &str, search_root: &Path, workspace_root: &Path) -> String {
     let candidate = Path::new(result_path);
     if !candidate.is_absolute() {
@@ -3334,8 +4836,7 @@ fn normalize_hint_path(result_path: &str, search_root: &Path, workspace_root: &P
 }
 
 fn scope_relative_path(full_path: &Path, search_root: &Path) -> Option<String> {
-    let rel = full_path.strip_prefix(search_root).ok()?;
-    let rendered = rel.display().to_string();
+    let rendered = paths::relative_display(full_path, search_root)?;
     if !rendered.is_empty() {
         return Some(rendered);
     }
@@ -3347,9 +4848,8 @@ fn scope_relative_path(full_path: &Path, search_root: &Path) -> Option<String> {
 }
 
 fn workspace_display_path(full_path: &Path, workspace_root: &Path) -> String {
-    if workspace_root != Path::new("/") {
-        if let Ok(rel) = full_path.strip_prefix(workspace_root) {
-            let rendered = rel.display().to_string();
+    if !paths::is_filesystem_root(workspace_root) {
+        if let Some(rendered) = paths::relative_display(full_path, workspace_root) {
             if !rendered.is_empty() {
                 return rendered;
             }
@@ -3357,12 +4857,10 @@ fn workspace_display_path(full_path: &Path, workspace_root: &Path) -> String {
     }
 
     if full_path.is_absolute() {
-        return normalize_path(full_path).display().to_string();
+        return paths::display(&normalize_path(full_path));
     }
 
-    normalize_path(&workspace_root.join(full_path))
-        .display()
-        .to_string()
+    paths::display(&normalize_path(&workspace_root.join(full_path)))
 }
 
 #[cfg(test)]
@@ -3394,6 +4892,7 @@ mod tests {
             false,
             true,
             false,
+            IgnoreRules::default(),
             &legacy_ranking_strategy("world", None, None),
         )
         .expect("scan");
@@ -3425,6 +4924,7 @@ mod tests {
             true,
             true,
             false,
+            IgnoreRules::default(),
             &legacy_ranking_strategy(r"\d{3}", None, None),
         )
         .expect("scan");
@@ -3642,6 +5142,13 @@ mod tests {
                 chunk_start: None,
                 chunk_end: None,
                 explain: None,
+                linguist: None,
+                stale: false,
+                column: None,
+                end_column: None,
+                match_bytes: None,
+                chunk_bytes: None,
+                symbol_header: Vec::new(),
             },
             SearchResult {
                 path: "src/lib.rs".to_string(),
@@ -3657,6 +5164,13 @@ mod tests {
                 chunk_start: None,
                 chunk_end: None,
                 explain: None,
+                linguist: None,
+                stale: false,
+                column: None,
+                end_column: None,
+                match_bytes: None,
+                chunk_bytes: None,
+                symbol_header: Vec::new(),
             },
         ];
 
@@ -3682,6 +5196,13 @@ mod tests {
             chunk_start: None,
             chunk_end: None,
             explain: None,
+            linguist: None,
+            stale: false,
+            column: None,
+            end_column: None,
+            match_bytes: None,
+            chunk_bytes: None,
+            symbol_header: Vec::new(),
         };
 
         let a = stable_result_id(&result);
@@ -3768,6 +5289,37 @@ mod tests {
         assert!((components.final_score - expected).abs() < 0.0001);
     }
 
+    #[test]
+    fn recency_bonus_is_bucketed_by_mtime_age() {
+        let dir = TempDir::new().expect("tempdir");
+        let path = dir.path().join("a.rs");
+        std::fs::write(&path, "fn a() {}\n").expect("write");
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .expect("open");
+        let modified = file
+            .metadata()
+            .expect("metadata")
+            .modified()
+            .expect("mtime");
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+
+        assert_eq!(recency_ranking_bonus(dir.path(), "a.rs", modified), 0.05);
+        assert_eq!(
+            recency_ranking_bonus(dir.path(), "a.rs", modified + day * 10),
+            0.025
+        );
+        assert_eq!(
+            recency_ranking_bonus(dir.path(), "a.rs", modified + day * 400),
+            0.0
+        );
+        assert_eq!(
+            recency_ranking_bonus(dir.path(), "missing.rs", modified),
+            0.0
+        );
+    }
+
     #[test]
     fn explain_trimming_keeps_only_top_k() {
         let mut results = vec![
@@ -3781,6 +5333,8 @@ mod tests {
                 path_boost: 0.0,
                 symbol_boost: 0.0,
                 changed_boost: 0.0,
+                churn_boost: 0.0,
+                recency_boost: 0.0,
                 kind_boost: 0.0,
                 penalties: 0.0,
                 final_score: 1.0 + idx as f32,
@@ -3820,6 +5374,39 @@ mod tests {
         assert!(should_attempt_keyword_fallback(&implicit));
     }
 
+    #[test]
+    fn match_span_finds_the_earliest_term_or_regex_match() {
+        let text = "    let retry = Retry::new(); // retry";
+        assert_eq!(match_span(text, "new retry", None, false), Some((8, 13)));
+        assert_eq!(match_span(text, "Retry", None, true), Some((16, 21)));
+        assert_eq!(match_span(text, "missing", None, false), None);
+        let re = regex::Regex::new(r"::\w+").expect("regex");
+        assert_eq!(match_span(text, "", Some(&re), false), Some((21, 26)));
+    }
+
+    #[test]
+    fn match_locations_map_to_bytes_on_disk() {
+        let dir = TempDir::new().expect("tempdir");
+        let content = b"\xEF\xBB\xBF// header\r\nfn load() {\r\n    retry();\r\n}\r\n";
+        std::fs::write(dir.path().join("lib.rs"), content).expect("write");
+        let mut result = sample_result("lib.rs", 3, "retry();");
+        result.chunk_start = Some(2);
+        result.chunk_end = Some(4);
+        let mut results = vec![result];
+
+        annotate_match_locations(&mut results, &[dir.path()], "retry", None, false);
+
+        let result = &results[0];
+        assert_eq!((result.column, result.end_column), (Some(5), Some(10)));
+        let found = result.match_bytes.expect("match bytes");
+        assert_eq!(&content[found.start..found.end], b"retry");
+        let chunk = result.chunk_bytes.expect("chunk bytes");
+        assert_eq!(
+            &content[chunk.start..chunk.end],
+            b"fn load() {\r\n    retry();\r\n}"
+        );
+    }
+
     fn sample_result(path: &str, line: usize, snippet: &str) -> SearchResult {
         SearchResult {
             path: path.to_string(),
@@ -3835,6 +5422,13 @@ mod tests {
             chunk_start: None,
             chunk_end: None,
             explain: None,
+            linguist: None,
+            stale: false,
+            column: None,
+            end_column: None,
+            match_bytes: None,
+            chunk_bytes: None,
+            symbol_header: Vec::new(),
         }
     }
 
@@ -3856,6 +5450,36 @@ mod tests {
         assert_eq!(results[0].snippet, "01234...");
     }
 
+    #[test]
+    fn diversify_caps_and_interleaves_directories() {
+        let mut results: Vec<SearchResult> = [
+            "src/db/a.rs",
+            "src/db/b.rs",
+            "src/db/c.rs",
+            "src/db/d.rs",
+            "src/api/x.rs",
+            "README.md",
+            "src/api/y.rs",
+        ]
+        .into_iter()
+        .map(|path| sample_result(path, 1, "hit"))
+        .collect();
+
+        diversify_results(&mut results, 2, 5);
+
+        let paths: Vec<&str> = results.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "src/db/a.rs",
+                "src/api/x.rs",
+                "README.md",
+                "src/db/b.rs",
+                "src/api/y.rs"
+            ]
+        );
+    }
+
     #[test]
     fn budget_truncates_context_chars() {
         let mut results = vec![sample_result("a.rs", 1, "short")];
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep selftest`: run the query-parsing property checks against this build.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+use crate::cli::OutputFormat;
+use cgrep::output::print_json;
+use cgrep::selftest;
+
+pub fn run(cases: usize, seed: Option<u64>, format: OutputFormat, compact: bool) -> Result<()> {
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(1, |elapsed| elapsed.as_nanos() as u64)
+    });
+    let report = selftest::run(cases, seed);
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&report, compact)?,
+        _ => {
+            for check in &report.checks {
+                if check.failures.is_empty() {
+                    println!("{} {} ({} cases)", "✓".green(), check.name, check.cases);
+                    continue;
+                }
+                println!("{} {} ({} cases)", "✗".red(), check.name, check.cases);
+                for failure in &check.failures {
+                    println!("    input {:?}: {}", failure.input, failure.detail);
+                }
+            }
+            println!("seed: {}", report.seed);
+        }
+    }
+
+    let failed = report.failed();
+    if failed > 0 {
+        bail!(
+            "{} selftest check(s) failed; rerun with --seed {} to reproduce",
+            failed,
+            report.seed
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep search -C symbol-header`: orient each match by the signatures of
+//! the symbols around it instead of by neighbouring lines.
+//!
+//! Every function, class, module, or config key whose range contains the
+//! match contributes its header, outermost first. A header is the symbol's
+//! first line, continued up to the line that opens its body when the
+//! signature wraps.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::indexer::scanner::detect_language;
+use crate::parser::symbols::{Symbol, SymbolExtractor, SymbolKind};
+use crate::query::search::SearchResult;
+use cgrep::encoding;
+use cgrep::paths;
+
+/// Lines kept from one wrapped signature.
+const MAX_SIGNATURE_LINES: usize = 3;
+
+/// One line of an enclosing symbol's header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolHeaderLine {
+    pub line: usize,
+    pub text: String,
+}
+
+struct ParsedFile {
+    lines: Vec<String>,
+    symbols: Vec<Symbol>,
+}
+
+/// Fill `symbol_header` for every result with a line number. Files are read
+/// and parsed once each; paths resolve against the first root holding them.
+pub(crate) fn attach(results: &mut [SearchResult], roots: &[&Path]) {
+    let extractor = SymbolExtractor::new();
+    let mut parsed: HashMap<String, Option<ParsedFile>> = HashMap::new();
+    for result in results {
+        let Some(line) = result.line else {
+            continue;
+        };
+        let file = parsed
+            .entry(result.path.clone())
+            .or_insert_with(|| parse(&result.path, roots, &extractor));
+        if let Some(file) = file {
+            result.symbol_header = header_lines(file, line);
+        }
+    }
+}
+
+fn parse(path: &str, roots: &[&Path], extractor: &SymbolExtractor) -> Option<ParsedFile> {
+    let decoded = paths::decode_path(path);
+    let language = Path::new(&decoded)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(detect_language)?;
+    let content = roots
+        .iter()
+        .map(|root| root.join(&decoded))
+        .find(|candidate| candidate.is_file())
+        .and_then(|full_path| encoding::read_text_file(&full_path).ok().flatten())?
+        .text
+        .into_owned();
+    let symbols = extractor.extract(&content, &language).ok()?;
+    Some(ParsedFile {
+        lines: content.lines().map(str::to_string).collect(),
+        symbols,
+    })
+}
+
+/// Symbols that scope code beneath them; variables and fields do not.
+fn is_scope(symbol: &Symbol) -> bool {
+    !matches!(
+        symbol.kind,
+        SymbolKind::Variable | SymbolKind::Constant | SymbolKind::Property
+    )
+}
+
+/// Scope-forming symbols whose body contains `line`, outermost first. A
+/// symbol starting on `line` itself does not enclose it.
+pub(crate) fn enclosing_scopes(symbols: &[Symbol], line: usize) -> Vec<&Symbol> {
+    let mut enclosing: Vec<&Symbol> = symbols
+        .iter()
+        .filter(|symbol| is_scope(symbol))
+        .filter(|symbol| symbol.line < line && line <= symbol.end_line)
+        .collect();
+    enclosing.sort_by_key(|symbol| (symbol.line, std::cmp::Reverse(symbol.end_line)));
+    enclosing
+}
+
+fn header_lines(file: &ParsedFile, line: usize) -> Vec<SymbolHeaderLine> {
+    let enclosing = enclosing_scopes(&file.symbols, line);
+    let mut header: Vec<SymbolHeaderLine> = Vec::new();
+    for symbol in enclosing {
+        for number in signature_lines(&file.lines, symbol.line, line) {
+            if header.last().is_some_and(|last| last.line >= number) {
+                continue;
+            }
+            header.push(SymbolHeaderLine {
+                line: number,
+                text: file.lines[number - 1].clone(),
+            });
+        }
+    }
+    header
+}
+
+/// 1-based lines of the signature starting at `start`, stopping at the line
+/// that opens the body and before `before`.
+fn signature_lines(lines: &[String], start: usize, before: usize) -> Vec<usize> {
+    let mut numbers = Vec::new();
+    for number in start..before.min(start + MAX_SIGNATURE_LINES) {
+        let Some(text) = lines.get(number - 1) else {
+            break;
+        };
+        numbers.push(number);
+        let trimmed = text.trim_end();
+        if trimmed.contains('{') || trimmed.ends_with(':') || trimmed.ends_with("=>") {
+            break;
+        }
+    }
+    numbers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(content: &str, language: &str) -> ParsedFile {
+        ParsedFile {
+            lines: content.lines().map(str::to_string).collect(),
+            symbols: SymbolExtractor::new()
+                .extract(content, language)
+                .expect("extract"),
+        }
+    }
+
+    #[test]
+    fn headers_list_enclosing_signatures_outermost_first() {
+        let file = parsed(
+            "class Cache:\n    def get(\n        self,\n        key,\n    ):\n        value = self.lookup(key)\n        return value\n",
+            "python",
+        );
+        let lines = header_lines(&file, 6);
+        let header: Vec<(usize, &str)> = lines
+            .iter()
+            .map(|line| (line.line, line.text.as_str()))
+            .collect();
+        assert_eq!(
+            header,
+            [
+                (1, "class Cache:"),
+                (2, "    def get("),
+                (3, "        self,"),
+                (4, "        key,"),
+            ]
+        );
+        // The signature line itself needs no header of its own symbol.
+        assert_eq!(header_lines(&file, 1), []);
+    }
+}
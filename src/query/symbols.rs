@@ -2,23 +2,36 @@
 
 //! Symbol search command
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
+use regex::Regex;
 use serde::Serialize;
+use std::cmp::Reverse;
 use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
-use crate::cli::OutputFormat;
+use crate::cli::{CliSymbolSort, OutputFormat};
 use crate::indexer::scanner::{FileScanner, ScannedFile};
-use crate::parser::symbols::SymbolExtractor;
+use crate::parser::metrics::{MetricFilter, SymbolMetrics};
+use crate::parser::signatures::Signature;
+use crate::parser::symbols::{decorator_name, Symbol, SymbolExtractor};
 use crate::query::changed_files::ChangedFiles;
-use crate::query::index_filter::{find_files_with_symbol, read_scanned_files};
+use crate::query::hits;
+use crate::query::index_filter::{
+    find_files_with_content, find_files_with_symbol, read_scanned_files,
+};
+use crate::query::search::ByteSpan;
+use cgrep::cancel;
 use cgrep::config::Config;
+use cgrep::encoding::DiskOffsets;
 use cgrep::filters::{
     matches_file_type, matches_glob_compiled, should_exclude_compiled, CompiledGlob,
 };
 use cgrep::output::{
-    colorize_kind, colorize_line_num, colorize_name, colorize_path, print_json, use_colors,
+    colorize_kind, colorize_line_num, colorize_name, colorize_path, print_json, print_json2,
+    print_quickfix, use_colors, QuickfixEntry,
 };
 use cgrep::utils::get_root_with_index;
 
@@ -29,18 +42,209 @@ struct SymbolResult {
     kind: String,
     path: String,
     line: usize,
+    /// Parameter and return types, reported when filtering by signature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    /// Decorators, reported when filtering by decorator.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    decorators: Vec<String>,
+    /// Size and complexity, reported when filtering by metric.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metrics: Option<SymbolMetrics>,
+    /// The definition as byte offsets into the file on disk (json2 only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol_bytes: Option<ByteSpan>,
+}
+
+/// The symbol's definition as byte offsets into the file at `path`, which is
+/// read on first use and kept in `offsets`.
+fn symbol_bytes(
+    symbol: &Symbol,
+    path: &Path,
+    offsets: &mut Option<Option<DiskOffsets>>,
+) -> Option<ByteSpan> {
+    let (start, end) = (symbol.byte_start?, symbol.byte_end?);
+    let offsets = offsets
+        .get_or_insert_with(|| {
+            std::fs::read(path)
+                .ok()
+                .and_then(|bytes| DiskOffsets::new(&bytes))
+        })
+        .as_ref()?;
+    Some(ByteSpan {
+        start: offsets.map(start)?,
+        end: offsets.map(end)?,
+    })
+}
+
+/// `--returns` and `--params` patterns for signature-shaped lookups.
+struct SignatureFilter {
+    returns: Option<Regex>,
+    params: Vec<Regex>,
+    label: String,
+}
+
+impl SignatureFilter {
+    fn new(returns: Option<&str>, params: &[String]) -> Result<Option<Self>> {
+        if returns.is_none() && params.is_empty() {
+            return Ok(None);
+        }
+        let mut label: Vec<String> = params.iter().map(|p| format!("--params {p}")).collect();
+        if let Some(returns) = returns {
+            label.push(format!("--returns {returns}"));
+        }
+        Ok(Some(Self {
+            returns: returns.map(type_pattern).transpose()?,
+            params: params
+                .iter()
+                .map(|p| type_pattern(p))
+                .collect::<Result<_>>()?,
+            label: label.join(" "),
+        }))
+    }
+
+    /// Every `--params` pattern matches some parameter and `--returns`
+    /// matches the declared return type.
+    fn matches(&self, signature: &Signature) -> bool {
+        let returns_ok = match &self.returns {
+            Some(pattern) => signature
+                .returns
+                .as_deref()
+                .is_some_and(|ty| pattern.is_match(ty)),
+            None => true,
+        };
+        returns_ok
+            && self
+                .params
+                .iter()
+                .all(|pattern| signature.params.iter().any(|ty| pattern.is_match(ty)))
+    }
+}
+
+/// Compile a type pattern: a standalone `_` matches any type text and
+/// whitespace is optional, so `Result<_>` matches `io::Result<Vec<u8>>`.
+fn type_pattern(pattern: &str) -> Result<Regex> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        bail!("Empty type pattern");
+    }
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut regex = String::new();
+    if chars[0] != '_' && is_word(chars[0]) {
+        regex.push_str(r"\b");
+    }
+    let mut idx = 0;
+    while idx < chars.len() {
+        let c = chars[idx];
+        let standalone = |i: usize| i >= chars.len() || !is_word(chars[i]);
+        if c.is_whitespace() {
+            while chars.get(idx + 1).is_some_and(|c| c.is_whitespace()) {
+                idx += 1;
+            }
+            regex.push_str(r"\s*");
+        } else if c == '_' && (idx == 0 || standalone(idx - 1)) && standalone(idx + 1) {
+            regex.push_str(".+?");
+        } else {
+            regex.push_str(&regex::escape(&c.to_string()));
+        }
+        idx += 1;
+    }
+    let last = chars[chars.len() - 1];
+    if is_word(last) && !regex.ends_with(".+?") {
+        regex.push_str(r"\b");
+    }
+    Regex::new(&regex).with_context(|| format!("Invalid type pattern: {pattern}"))
 }
 
-/// Run the symbols command
+#[derive(Debug, Serialize)]
+struct SymbolsJson2Meta<'a> {
+    schema_version: &'static str,
+    command: &'static str,
+    /// Requested name, absent when batching.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query: Option<&'a str>,
+    sort: &'static str,
+    offset: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
+    /// Matches before `offset` and `limit`, summed over names when batching.
+    total: usize,
+    returned: usize,
+    /// `--offset` of the next page, when any name has more results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_offset: Option<usize>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    timed_out: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    interrupted: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct SymbolsJson2Payload<'a, T: Serialize> {
+    meta: SymbolsJson2Meta<'a>,
+    results: T,
+}
+
+/// Results per requested name, serialized as a map in request order.
+struct BatchResults<'a>(&'a [(String, Vec<SymbolResult>)]);
+
+impl Serialize for BatchResults<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(name, results)| (name, results)))
+    }
+}
+
+/// Last path segment of a decorator (`fixture` for `@pytest.fixture`).
+fn decorator_search_term(decorator: &str) -> &str {
+    let name = decorator_name(decorator);
+    name.rsplit(['.', ':']).next().unwrap_or(name)
+}
+
+/// Read symbol names from a file (`-` for stdin), one per line.
+///
+/// Blank lines and lines starting with `#` are skipped.
+pub fn read_names_file(path: &str) -> Result<Vec<String>> {
+    let content = if path == "-" {
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buf)
+            .context("Failed to read symbol names from stdin")?;
+        buf
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read names file: {path}"))?
+    };
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Run the symbols command.
+///
+/// With `batch`, every name is resolved in the same pass over candidate files
+/// and results are grouped by requested name.
 #[allow(clippy::too_many_arguments)]
 pub fn run(
-    name: &str,
+    names: &[String],
+    batch: bool,
     symbol_type: Option<&str>,
+    returns: Option<&str>,
+    params: &[String],
+    decorated_with: Option<&str>,
+    metric_filters: &[String],
     lang: Option<&str>,
     file_type: Option<&str>,
     glob_pattern: Option<&str>,
     exclude_pattern: Option<&str>,
     changed: Option<&str>,
+    only_dirty: bool,
+    sort: CliSymbolSort,
+    offset: usize,
+    limit: Option<usize>,
     quiet: bool,
     format: OutputFormat,
     compact: bool,
@@ -48,6 +252,28 @@ pub fn run(
     let start_time = Instant::now();
     let use_color = use_colors() && format == OutputFormat::Text;
 
+    let signature_filter = SignatureFilter::new(returns, params)?;
+    let mut seen = HashSet::new();
+    let mut names: Vec<&str> = names
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty() && seen.insert(*name))
+        .collect();
+    let decorated_with = decorated_with
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    let metric_filters: Vec<MetricFilter> = metric_filters
+        .iter()
+        .map(|raw| MetricFilter::parse(raw))
+        .collect::<Result<_>>()?;
+    if names.is_empty() {
+        if signature_filter.is_none() && decorated_with.is_none() && metric_filters.is_empty() {
+            bail!("No symbol names given");
+        }
+        // A signature, decorator, or metric alone matches symbols of any name.
+        names.push("");
+    }
+
     let search_root = std::env::current_dir()?.canonicalize()?;
     let index_root = get_root_with_index(&search_root);
     // Load config for exclude patterns
@@ -65,26 +291,53 @@ pub fn run(
         .collect();
 
     let extractor = SymbolExtractor::new();
-    let name_lower = name.to_lowercase();
-    let changed_filter = changed
-        .map(|rev| ChangedFiles::from_scope(&search_root, rev))
-        .transpose()?;
-
-    // Try to use index for fast file filtering first
-    let files: Vec<ScannedFile> =
-        match find_files_with_symbol(&index_root, name, Some(&search_root))? {
-            Some(indexed_paths) => read_scanned_files(&indexed_paths),
+    let names_lower: Vec<String> = names.iter().map(|name| name.to_lowercase()).collect();
+    let changed_filter = ChangedFiles::from_flags(&search_root, changed, only_dirty)?;
+
+    // Try to use index for fast file filtering first; one name without index
+    // coverage falls back to a full scan for the whole batch.
+    let mut indexed_paths: Option<Vec<PathBuf>> = Some(Vec::new());
+    let mut seen_paths: HashSet<PathBuf> = HashSet::new();
+    for name in &names {
+        let found = match (name.is_empty(), decorated_with) {
+            (false, _) => find_files_with_symbol(&index_root, name, Some(&search_root))?,
+            // Decorators are spelled out in the file, so their last path
+            // segment narrows the files to parse.
+            (true, Some(decorator)) => find_files_with_content(
+                &index_root,
+                decorator_search_term(decorator),
+                Some(&search_root),
+            )?,
+            (true, None) => None,
+        };
+        match found {
+            Some(paths) => {
+                if let Some(all) = indexed_paths.as_mut() {
+                    all.extend(paths.into_iter().filter(|p| seen_paths.insert(p.clone())));
+                }
+            }
             None => {
-                let scanner = FileScanner::new(&search_root);
-                scanner.scan()?
+                indexed_paths = None;
+                break;
             }
-        };
+        }
+    }
+    let files: Vec<ScannedFile> = match indexed_paths {
+        Some(paths) => read_scanned_files(&paths),
+        None => {
+            let scanner = FileScanner::new(&search_root).with_cancellation(true);
+            scanner.scan()?
+        }
+    };
 
-    let mut results: Vec<SymbolResult> = Vec::new();
+    let mut grouped: Vec<Vec<SymbolResult>> = names.iter().map(|_| Vec::new()).collect();
     let mut files_searched: HashSet<String> = HashSet::new();
     let mut parser_cache = std::collections::HashMap::new();
 
     for file in files {
+        if cancel::should_stop() {
+            break;
+        }
         let rel_path = file
             .path
             .strip_prefix(&search_root)
@@ -124,16 +377,12 @@ pub fn run(
 
         files_searched.insert(rel_path.clone());
 
+        let mut disk_offsets = None;
         if let Some(ref file_lang) = file.language {
             if let Ok(symbols) =
                 extractor.extract_with_cache(&file.content, file_lang, &mut parser_cache)
             {
                 for symbol in symbols {
-                    // Filter by name
-                    if !symbol.name.to_lowercase().contains(&name_lower) {
-                        continue;
-                    }
-
                     // Filter by type if specified
                     if let Some(filter_type) = symbol_type {
                         if symbol.kind.to_string() != filter_type.to_lowercase() {
@@ -141,24 +390,173 @@ pub fn run(
                         }
                     }
 
-                    results.push(SymbolResult {
-                        name: symbol.name.clone(),
-                        kind: symbol.kind.to_string(),
-                        path: rel_path.clone(),
-                        line: symbol.line,
-                    });
+                    if decorated_with.is_some_and(|name| !symbol.has_decorator(name)) {
+                        continue;
+                    }
+
+                    let metrics = match symbol.metrics {
+                        _ if metric_filters.is_empty() => None,
+                        Some(metrics)
+                            if metric_filters.iter().all(|filter| filter.matches(&metrics)) =>
+                        {
+                            Some(metrics)
+                        }
+                        _ => continue,
+                    };
+
+                    let signature = match (&signature_filter, &symbol.signature) {
+                        (None, _) => None,
+                        (Some(filter), Some(signature)) if filter.matches(signature) => {
+                            Some(signature.to_string())
+                        }
+                        (Some(_), _) => continue,
+                    };
+
+                    // Filter by name
+                    let symbol_lower = symbol.name.to_lowercase();
+                    for (results, name_lower) in grouped.iter_mut().zip(&names_lower) {
+                        if symbol_lower.contains(name_lower.as_str()) {
+                            let result = SymbolResult {
+                                name: symbol.name.clone(),
+                                kind: symbol.kind.to_string(),
+                                path: rel_path.clone(),
+                                line: symbol.line,
+                                signature: signature.clone(),
+                                decorators: if decorated_with.is_some() {
+                                    symbol.decorators.clone()
+                                } else {
+                                    Vec::new()
+                                },
+                                metrics,
+                                symbol_bytes: if format == OutputFormat::Json2 {
+                                    symbol_bytes(&symbol, &file.path, &mut disk_offsets)
+                                } else {
+                                    None
+                                },
+                            };
+                            if !batch
+                                && offset == 0
+                                && limit.is_none_or(|limit| results.len() < limit)
+                            {
+                                hits::emit(&result);
+                            }
+                            results.push(result);
+                        }
+                    }
                 }
             }
         }
     }
 
+    let totals: Vec<usize> = grouped.iter().map(Vec::len).collect();
+    for (results, name) in grouped.iter_mut().zip(&names) {
+        sort_symbols(results, sort, name);
+        results.drain(..offset.min(results.len()));
+        if let Some(limit) = limit {
+            results.truncate(limit);
+        }
+    }
+    let next_offset = limit
+        .map(|limit| offset.saturating_add(limit))
+        .filter(|next| totals.iter().any(|total| total > next));
+    let mut meta = SymbolsJson2Meta {
+        schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
+        command: "symbols",
+        query: None,
+        sort: sort.name(),
+        offset,
+        limit,
+        total: totals.iter().sum(),
+        returned: grouped.iter().map(Vec::len).sum(),
+        next_offset,
+        timed_out: cancel::timed_out(),
+        interrupted: cancel::interrupted(),
+    };
+
     let elapsed = start_time.elapsed();
+    if cancel::timed_out() {
+        eprintln!(
+            "Warning: symbol search hit --timeout-ms; {}.",
+            cancel::PARTIAL_RESULTS_NOTICE
+        );
+    } else if cancel::interrupted() {
+        eprintln!(
+            "Warning: symbol search interrupted; {}.",
+            cancel::PARTIAL_RESULTS_NOTICE
+        );
+    }
+
+    if batch {
+        let grouped: Vec<(String, Vec<SymbolResult>)> = names
+            .iter()
+            .map(|name| name.to_string())
+            .zip(grouped)
+            .collect();
+        print_batch(&grouped, meta, format, compact, use_color)?;
+        if !quiet
+            && !matches!(
+                format,
+                OutputFormat::Json | OutputFormat::Json2 | OutputFormat::Quickfix
+            )
+        {
+            eprintln!(
+                "\n{} files | {} names | {} symbols | {:.2}ms",
+                files_searched.len(),
+                grouped.len(),
+                grouped
+                    .iter()
+                    .map(|(_, results)| results.len())
+                    .sum::<usize>(),
+                elapsed.as_secs_f64() * 1000.0
+            );
+        }
+        return Ok(());
+    }
 
+    let filter_label = match decorated_with {
+        Some(name) => format!("--decorated-with {name}"),
+        None => metric_filters
+            .iter()
+            .map(|filter| format!("--metric {filter}"))
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+    let name = match (names[0], &signature_filter) {
+        ("", Some(filter)) => filter.label.as_str(),
+        ("", None) => filter_label.as_str(),
+        (name, _) => name,
+    };
+    let results = grouped.swap_remove(0);
     match format {
-        OutputFormat::Json | OutputFormat::Json2 => {
+        OutputFormat::Json => {
             print_json(&results, compact)?;
         }
-        OutputFormat::Text => {
+        OutputFormat::Json2 => {
+            meta.query = Some(name);
+            let payload = SymbolsJson2Payload {
+                meta,
+                results: &results,
+            };
+            print_json2("symbols", &payload, compact)?;
+        }
+        OutputFormat::Quickfix => {
+            let texts: Vec<String> = results
+                .iter()
+                .map(|result| format!("[{}] {}", result.kind, result.name))
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = results
+                .iter()
+                .zip(&texts)
+                .map(|(result, text)| QuickfixEntry {
+                    path: &result.path,
+                    line: result.line,
+                    column: 1,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
             if results.is_empty() {
                 if use_color {
                     println!("{} No symbols found matching: {}", "✗".red(), name.yellow());
@@ -176,25 +574,25 @@ pub fn run(
                     println!("\nSearching for symbol: {}\n", name);
                 }
 
-                for result in &results {
-                    let kind_str = format!("[{}]", result.kind);
-                    println!(
-                        "  {} {} {}:{}",
-                        colorize_kind(&kind_str, use_color),
-                        colorize_name(&result.name, use_color),
-                        colorize_path(&result.path, use_color),
-                        colorize_line_num(result.line, use_color)
-                    );
-                }
+                print_kind_groups(&results, use_color);
 
+                let found = if results.len() < meta.total {
+                    format!(
+                        "{}-{} of {}",
+                        offset + 1,
+                        offset + results.len(),
+                        meta.total
+                    )
+                } else {
+                    results.len().to_string()
+                };
                 if use_color {
-                    println!(
-                        "\n{} Found {} symbols",
-                        "✓".green(),
-                        results.len().to_string().cyan()
-                    );
+                    println!("\n{} Found {} symbols", "✓".green(), found.cyan());
                 } else {
-                    println!("\nFound {} symbols", results.len());
+                    println!("\nFound {} symbols", found);
+                }
+                if let Some(next) = meta.next_offset {
+                    println!("Next page: --offset {next}");
                 }
             }
 
@@ -212,3 +610,146 @@ pub fn run(
 
     Ok(())
 }
+
+fn print_batch(
+    grouped: &[(String, Vec<SymbolResult>)],
+    meta: SymbolsJson2Meta<'_>,
+    format: OutputFormat,
+    compact: bool,
+    use_color: bool,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => print_json(&BatchResults(grouped), compact),
+        OutputFormat::Json2 => {
+            let payload = SymbolsJson2Payload {
+                meta,
+                results: BatchResults(grouped),
+            };
+            print_json2("symbols", &payload, compact)
+        }
+        OutputFormat::Quickfix => {
+            let results: Vec<&SymbolResult> =
+                grouped.iter().flat_map(|(_, results)| results).collect();
+            let texts: Vec<String> = results
+                .iter()
+                .map(|result| format!("[{}] {}", result.kind, result.name))
+                .collect();
+            let entries: Vec<QuickfixEntry<'_>> = results
+                .iter()
+                .zip(&texts)
+                .map(|(result, text)| QuickfixEntry {
+                    path: &result.path,
+                    line: result.line,
+                    column: 1,
+                    text,
+                })
+                .collect();
+            print_quickfix(&entries);
+            Ok(())
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            for (name, results) in grouped {
+                println!("\n{} ({})", colorize_name(name, use_color), results.len());
+                if results.is_empty() {
+                    println!("  No symbols found");
+                }
+                print_kind_groups(results, use_color);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Order `results` for paging; ties fall back to path and line so pages are
+/// stable across runs.
+fn sort_symbols(results: &mut [SymbolResult], sort: CliSymbolSort, query: &str) {
+    let by_location =
+        |a: &SymbolResult, b: &SymbolResult| (&a.path, a.line).cmp(&(&b.path, b.line));
+    match sort {
+        CliSymbolSort::Path => results.sort_by(by_location),
+        CliSymbolSort::Name => results.sort_by(|a, b| {
+            (a.name.to_lowercase(), &a.name)
+                .cmp(&(b.name.to_lowercase(), &b.name))
+                .then_with(|| by_location(a, b))
+        }),
+        CliSymbolSort::Kind => {
+            results.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| by_location(a, b)))
+        }
+        CliSymbolSort::Score => results.sort_by_cached_key(|result| {
+            (
+                Reverse(name_match_score(&result.name, query)),
+                result.name.len(),
+                result.path.clone(),
+                result.line,
+            )
+        }),
+    }
+}
+
+/// How closely `name` matches the requested `query` (higher is closer).
+fn name_match_score(name: &str, query: &str) -> u8 {
+    let name_lower = name.to_lowercase();
+    let query_lower = query.to_lowercase();
+    if name == query {
+        4
+    } else if name_lower == query_lower {
+        3
+    } else if name_lower.starts_with(&query_lower) {
+        2
+    } else if name_lower.ends_with(&query_lower) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Print results under one heading per kind, in order of first appearance.
+fn print_kind_groups(results: &[SymbolResult], use_color: bool) {
+    let mut kinds: Vec<&str> = Vec::new();
+    for result in results {
+        if !kinds.contains(&result.kind.as_str()) {
+            kinds.push(&result.kind);
+        }
+    }
+    for kind in kinds {
+        let group: Vec<&SymbolResult> = results.iter().filter(|r| r.kind == kind).collect();
+        let heading = format!("[{kind}]");
+        println!("  {} ({})", colorize_kind(&heading, use_color), group.len());
+        for result in group {
+            print_result_line(result, use_color);
+        }
+    }
+}
+
+fn print_result_line(result: &SymbolResult, use_color: bool) {
+    let signature = result.signature.as_deref().unwrap_or_default();
+    println!(
+        "    {}{} {}:{}",
+        colorize_name(&result.name, use_color),
+        signature,
+        colorize_path(&result.path, use_color),
+        colorize_line_num(result.line, use_color)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_patterns_use_underscore_wildcards_and_word_boundaries() {
+        let result = type_pattern("Result<_>").unwrap();
+        assert!(result.is_match("Result<Config>"));
+        assert!(result.is_match("io::Result<Vec<u8>>"));
+        assert!(!result.is_match("Option<Config>"));
+
+        let path = type_pattern("Path").unwrap();
+        assert!(path.is_match("&Path"));
+        assert!(path.is_match("impl AsRef<Path>"));
+        assert!(!path.is_match("PathBuf"));
+
+        let map = type_pattern("HashMap<_, usize>").unwrap();
+        assert!(map.is_match("HashMap<String,usize>"));
+        assert!(type_pattern("max_len").unwrap().is_match("max_len"));
+    }
+}
@@ -0,0 +1,607 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep trace`: resolve a pasted stack trace against the current workspace.
+//!
+//! Frames from Python, Java, Node, and Rust (panic messages and backtraces)
+//! are mapped to workspace files by path suffix, so traces captured on CI or
+//! in a container still land on local files. The frame's function is then
+//! checked against the symbol table: when the reported line no longer falls
+//! inside it, the frame moves to the function's current location. Frames
+//! without a usable path are looked up by function name in the index.
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::cli::OutputFormat;
+use crate::indexer::scanner::{detect_language, FileScanner};
+use crate::parser::symbols::{Symbol, SymbolExtractor};
+use crate::query::index_filter::{find_files_with_symbol_definition, SymbolNameMatch};
+use cgrep::encoding;
+use cgrep::output::{
+    colorize_line_num, colorize_name, colorize_path, print_json, print_json2, print_quickfix,
+    use_colors, QuickfixEntry,
+};
+use cgrep::utils::get_root_with_index;
+
+static PYTHON_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\s*File "(?P<file>[^"]+)", line (?P<line>\d+)(?:, in (?P<func>.+))?$"#)
+        .expect("python frame regex")
+});
+static JAVA_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*at (?P<func>[\w$.<>/]+)\((?P<file>[^():]+\.\w+):(?P<line>\d+)\)")
+        .expect("java frame regex")
+});
+static NODE_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*at (?:(?P<func>.+?) \()?(?P<file>[^\s()]+?):(?P<line>\d+):(?P<col>\d+)\)?$")
+        .expect("node frame regex")
+});
+static RUST_PANIC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"panicked at (?:'.*', )?(?P<file>[^\s:']+):(?P<line>\d+):(?P<col>\d+)")
+        .expect("rust panic regex")
+});
+static RUST_BACKTRACE_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*\d+:\s+(?:0x[0-9a-f]+ - )?(?P<func>\S.*)$").expect("rust frame regex")
+});
+static RUST_BACKTRACE_AT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s+at (?P<file>.+?):(?P<line>\d+):(?P<col>\d+)$").expect("rust at regex")
+});
+static RUST_HASH_SUFFIX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"::h[0-9a-f]{16}$").expect("rust hash regex"));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TraceFormat {
+    Python,
+    Java,
+    Node,
+    Rust,
+}
+
+/// One frame as written in the trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Frame {
+    format: TraceFormat,
+    raw: String,
+    function: Option<String>,
+    file: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResolvedFrame {
+    path: String,
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol: Option<String>,
+    drift: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct SnippetLine {
+    line: usize,
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TraceFrame {
+    index: usize,
+    format: TraceFormat,
+    raw: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+    resolved: Option<ResolvedFrame>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    snippet: Vec<SnippetLine>,
+}
+
+#[derive(Debug, Serialize)]
+struct TracePayload<'a> {
+    total: usize,
+    resolved: usize,
+    frames: &'a [TraceFrame],
+}
+
+#[derive(Debug, Serialize)]
+struct TraceJson2Meta {
+    schema_version: &'static str,
+    command: &'static str,
+    total: usize,
+    resolved: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct TraceJson2Payload<'a> {
+    meta: TraceJson2Meta,
+    frames: &'a [TraceFrame],
+}
+
+/// Run the trace command on `input` (a file path, or stdin when `None`/`-`).
+pub fn run(input: Option<&str>, context: usize, format: OutputFormat, compact: bool) -> Result<()> {
+    let text = match input {
+        None | Some("-") => {
+            let mut buf = String::new();
+            std::io::stdin()
+                .read_to_string(&mut buf)
+                .context("Failed to read stack trace from stdin")?;
+            buf
+        }
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read stack trace: {path}"))?,
+    };
+
+    let parsed = parse_frames(&text);
+    if parsed.is_empty() {
+        bail!("No stack frames recognized (supported: Python, Java, Node, Rust)");
+    }
+
+    let root = std::env::current_dir()?.canonicalize()?;
+    let mut resolver = Resolver::new(&root);
+    let frames: Vec<TraceFrame> = parsed
+        .into_iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            let resolved = resolver.resolve(&frame);
+            let snippet = resolved
+                .as_ref()
+                .map(|resolved| resolver.snippet(&resolved.path, resolved.line, context))
+                .unwrap_or_default();
+            TraceFrame {
+                index,
+                format: frame.format,
+                raw: frame.raw,
+                function: frame.function,
+                file: frame.file,
+                line: frame.line,
+                column: frame.column,
+                resolved,
+                snippet,
+            }
+        })
+        .collect();
+    let resolved = frames.iter().filter(|f| f.resolved.is_some()).count();
+
+    match format {
+        OutputFormat::Json => {
+            let payload = TracePayload {
+                total: frames.len(),
+                resolved,
+                frames: &frames,
+            };
+            print_json(&payload, compact)?;
+        }
+        OutputFormat::Json2 => {
+            let payload = TraceJson2Payload {
+                meta: TraceJson2Meta {
+                    schema_version: cgrep::schema::JSON2_SCHEMA_VERSION,
+                    command: "trace",
+                    total: frames.len(),
+                    resolved,
+                },
+                frames: &frames,
+            };
+            print_json2("trace", &payload, compact)?;
+        }
+        OutputFormat::Quickfix => {
+            let entries: Vec<QuickfixEntry<'_>> = frames
+                .iter()
+                .filter_map(|frame| {
+                    let resolved = frame.resolved.as_ref()?;
+                    Some(QuickfixEntry {
+                        path: &resolved.path,
+                        line: resolved.line,
+                        column: 1,
+                        text: frame.function.as_deref().unwrap_or(&frame.raw),
+                    })
+                })
+                .collect();
+            print_quickfix(&entries);
+        }
+        OutputFormat::Text | OutputFormat::Github | OutputFormat::Gitlab | OutputFormat::Sarif => {
+            print_text(&frames, use_colors() && format == OutputFormat::Text);
+            eprintln!("\n{} of {} frames resolved", resolved, frames.len());
+        }
+    }
+
+    Ok(())
+}
+
+fn print_text(frames: &[TraceFrame], use_color: bool) {
+    for frame in frames {
+        let function = frame.function.as_deref().unwrap_or("<unknown>");
+        let Some(resolved) = frame.resolved.as_ref() else {
+            println!("#{} {}  [unresolved]", frame.index, frame.raw);
+            continue;
+        };
+        let mut header = format!(
+            "#{} {} {}:{}",
+            frame.index,
+            colorize_name(function, use_color),
+            colorize_path(&resolved.path, use_color),
+            colorize_line_num(resolved.line, use_color)
+        );
+        if resolved.drift {
+            match (frame.file.as_deref(), frame.line) {
+                (Some(file), Some(line)) => header.push_str(&format!("  (trace: {file}:{line})")),
+                _ => header.push_str("  (located by symbol)"),
+            }
+        }
+        println!("{header}");
+        let width = frame
+            .snippet
+            .last()
+            .map_or(1, |line| line.line.to_string().len());
+        for line in &frame.snippet {
+            let marker = if line.line == resolved.line { ">" } else { " " };
+            println!("  {marker} {:>width$} | {}", line.line, line.text);
+        }
+    }
+}
+
+/// Extract frames in trace order; lines that are not frames are ignored.
+fn parse_frames(text: &str) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    // Rust backtraces put the location on the line after the function.
+    let mut pending_rust: Option<Frame> = None;
+    for line in text.lines().map(str::trim_end) {
+        if let Some(caps) = RUST_BACKTRACE_FRAME.captures(line) {
+            frames.extend(pending_rust.take());
+            pending_rust = Some(Frame {
+                format: TraceFormat::Rust,
+                raw: line.trim().to_string(),
+                function: Some(clean_rust_function(&caps["func"])),
+                file: None,
+                line: None,
+                column: None,
+            });
+            continue;
+        }
+        if let Some(mut frame) = pending_rust.take() {
+            if let Some(caps) = RUST_BACKTRACE_AT.captures(line) {
+                frame.file = Some(caps["file"].to_string());
+                frame.line = caps["line"].parse().ok();
+                frame.column = caps["col"].parse().ok();
+                frames.push(frame);
+                continue;
+            }
+            frames.push(frame);
+        }
+        frames.extend(parse_frame_line(line));
+    }
+    frames.extend(pending_rust);
+    frames
+}
+
+fn parse_frame_line(line: &str) -> Option<Frame> {
+    let frame = |format, caps: &regex::Captures<'_>, function: Option<String>| Frame {
+        format,
+        raw: line.trim().to_string(),
+        function,
+        file: caps.name("file").map(|m| m.as_str().to_string()),
+        line: caps.name("line").and_then(|m| m.as_str().parse().ok()),
+        column: caps.name("col").and_then(|m| m.as_str().parse().ok()),
+    };
+    if let Some(caps) = PYTHON_FRAME.captures(line) {
+        let function = caps.name("func").map(|m| m.as_str().trim().to_string());
+        return Some(frame(TraceFormat::Python, &caps, function));
+    }
+    if let Some(caps) = JAVA_FRAME.captures(line) {
+        let function = caps["func"].to_string();
+        let mut parsed = frame(TraceFormat::Java, &caps, Some(function.clone()));
+        // `com.acme.Parser.parse(Parser.java:12)` lives in com/acme/Parser.java.
+        let segments: Vec<&str> = function.split('.').collect();
+        if segments.len() > 2 {
+            let package = segments[..segments.len() - 2].join("/");
+            parsed.file = Some(format!("{package}/{}", &caps["file"]));
+        }
+        return Some(parsed);
+    }
+    if let Some(caps) = NODE_FRAME.captures(line) {
+        let function = caps.name("func").map(|m| m.as_str().to_string());
+        return Some(frame(TraceFormat::Node, &caps, function));
+    }
+    if let Some(caps) = RUST_PANIC.captures(line) {
+        return Some(frame(TraceFormat::Rust, &caps, None));
+    }
+    None
+}
+
+fn clean_rust_function(raw: &str) -> String {
+    RUST_HASH_SUFFIX.replace(raw.trim(), "").into_owned()
+}
+
+/// The bare function name to look up in the symbol table.
+fn short_function_name(function: &str) -> Option<&str> {
+    let function = function
+        .trim()
+        .trim_start_matches("async ")
+        .trim_start_matches("new ");
+    let last = function
+        .rsplit("::")
+        .find(|segment| !segment.starts_with("{{"))?
+        .rsplit(['.', '/'])
+        .next()?;
+    let name = last.split('<').next().unwrap_or(last).trim();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    valid.then_some(name)
+}
+
+struct LoadedFile {
+    lines: Vec<String>,
+    symbols: Vec<Symbol>,
+}
+
+struct Resolver<'a> {
+    root: &'a Path,
+    index_root: PathBuf,
+    workspace_files: Option<Vec<String>>,
+    loaded: HashMap<String, Option<Rc<LoadedFile>>>,
+    extractor: SymbolExtractor,
+    parser_cache: HashMap<String, tree_sitter::Parser>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(root: &'a Path) -> Self {
+        Self {
+            root,
+            index_root: get_root_with_index(root),
+            workspace_files: None,
+            loaded: HashMap::new(),
+            extractor: SymbolExtractor::new(),
+            parser_cache: HashMap::new(),
+        }
+    }
+
+    fn resolve(&mut self, frame: &Frame) -> Option<ResolvedFrame> {
+        let name = frame.function.as_deref().and_then(short_function_name);
+        if let Some(path) = frame.file.as_deref().and_then(|file| self.locate(file)) {
+            let file = self.load(&path)?;
+            let reported = frame.line.unwrap_or(1);
+            let mut line = reported.clamp(1, file.lines.len().max(1));
+            let mut drift = frame.line.is_some() && line != reported;
+            let named: Vec<&Symbol> = name
+                .map(|name| file.symbols.iter().filter(|s| s.name == name).collect())
+                .unwrap_or_default();
+            let symbol = if named.is_empty() {
+                innermost_symbol(&file.symbols, line).map(|s| s.name.clone())
+            } else {
+                if !named.iter().any(|s| contains_line(s, reported)) {
+                    let nearest = named.iter().min_by_key(|s| s.line.abs_diff(reported))?;
+                    line = nearest.line;
+                    drift = true;
+                }
+                name.map(str::to_string)
+            };
+            return Some(ResolvedFrame {
+                path,
+                line,
+                symbol,
+                drift,
+            });
+        }
+
+        // No usable path: find where the function is defined now.
+        let name = name?;
+        let paths = find_files_with_symbol_definition(
+            &self.index_root,
+            name,
+            Some(self.root),
+            SymbolNameMatch::Exact,
+        )
+        .ok()
+        .flatten()?;
+        paths.iter().find_map(|path| {
+            let rel = self.relative(path)?;
+            let file = self.load(&rel)?;
+            let symbol = file.symbols.iter().find(|s| s.name == name)?;
+            Some(ResolvedFrame {
+                path: rel,
+                line: symbol.line,
+                symbol: Some(name.to_string()),
+                drift: frame.line.is_some_and(|line| line != symbol.line),
+            })
+        })
+    }
+
+    fn snippet(&mut self, path: &str, line: usize, context: usize) -> Vec<SnippetLine> {
+        let Some(file) = self.load(path) else {
+            return Vec::new();
+        };
+        let start = line.saturating_sub(context).max(1);
+        let end = (line + context).min(file.lines.len());
+        (start..=end)
+            .map(|number| SnippetLine {
+                line: number,
+                text: file.lines[number - 1].clone(),
+            })
+            .collect()
+    }
+
+    /// Map a path from the trace to a workspace-relative path.
+    fn locate(&mut self, file: &str) -> Option<String> {
+        let normalized = file.trim_start_matches("file://").replace('\\', "/");
+        let normalized = normalized.trim_start_matches("./");
+        let path = Path::new(normalized);
+        if path.is_absolute() {
+            if path.is_file() {
+                if let Some(rel) = self.relative(path) {
+                    return Some(rel);
+                }
+            }
+        } else if self.root.join(path).is_file() {
+            return Some(normalized.to_string());
+        }
+
+        // Match the longest path suffix among workspace files.
+        let wanted: Vec<&str> = normalized
+            .split('/')
+            .filter(|part| !part.is_empty() && *part != ".")
+            .collect();
+        let file_name = *wanted.last()?;
+        self.workspace_files()
+            .iter()
+            .filter_map(|candidate| {
+                let parts: Vec<&str> = candidate.split('/').collect();
+                if parts.last() != Some(&file_name) {
+                    return None;
+                }
+                let score = parts
+                    .iter()
+                    .rev()
+                    .zip(wanted.iter().rev())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                Some((score, candidate))
+            })
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.len().cmp(&a.1.len())))
+            .map(|(_, candidate)| candidate.clone())
+    }
+
+    fn workspace_files(&mut self) -> &[String] {
+        let root = self.root;
+        self.workspace_files.get_or_insert_with(|| {
+            let mut files: Vec<String> = FileScanner::new(root)
+                .list_files()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|path| {
+                    let rel = path.strip_prefix(root).unwrap_or(path);
+                    Some(rel.to_str()?.replace('\\', "/"))
+                })
+                .collect();
+            files.sort();
+            files
+        })
+    }
+
+    fn relative(&self, path: &Path) -> Option<String> {
+        let canonical = path.canonicalize().ok()?;
+        let rel = canonical.strip_prefix(self.root).ok()?;
+        Some(rel.to_str()?.replace('\\', "/"))
+    }
+
+    fn load(&mut self, rel: &str) -> Option<Rc<LoadedFile>> {
+        if let Some(cached) = self.loaded.get(rel) {
+            return cached.clone();
+        }
+        let path = self.root.join(rel);
+        let loaded = encoding::read_text_file(&path)
+            .ok()
+            .flatten()
+            .map(|decoded| {
+                let content = decoded.text.into_owned();
+                let symbols = path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .and_then(detect_language)
+                    .and_then(|language| {
+                        self.extractor
+                            .extract_with_cache(&content, &language, &mut self.parser_cache)
+                            .ok()
+                    })
+                    .unwrap_or_default();
+                Rc::new(LoadedFile {
+                    lines: content.lines().map(str::to_string).collect(),
+                    symbols,
+                })
+            });
+        self.loaded.insert(rel.to_string(), loaded.clone());
+        loaded
+    }
+}
+
+fn contains_line(symbol: &Symbol, line: usize) -> bool {
+    symbol.line <= line && line <= symbol.end_line.max(symbol.line)
+}
+
+/// Smallest named symbol around `line`; closures roll up into their parent.
+pub(crate) fn innermost_symbol(symbols: &[Symbol], line: usize) -> Option<&Symbol> {
+    symbols
+        .iter()
+        .filter(|s| !s.is_anonymous() && contains_line(s, line))
+        .min_by_key(|s| s.end_line.max(s.line) - s.line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_python_java_node_and_rust_frames() {
+        let trace = "\
+Traceback (most recent call last):
+  File \"/srv/app/config.py\", line 12, in load_config
+Exception in thread \"main\" java.lang.IllegalStateException
+\tat com.acme.Parser.parse(Parser.java:40)
+    at loadUser (/home/ci/app/src/users.js:7:13)
+thread 'main' panicked at src/main.rs:4:5:
+   3: cgrep::query::run::h0123456789abcdef
+             at ./src/query/mod.rs:20:9
+   4: std::rt::lang_start
+";
+        let frames = parse_frames(trace);
+        let summary: Vec<_> = frames
+            .iter()
+            .map(|f| (f.format, f.function.as_deref(), f.file.as_deref(), f.line))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    TraceFormat::Python,
+                    Some("load_config"),
+                    Some("/srv/app/config.py"),
+                    Some(12)
+                ),
+                (
+                    TraceFormat::Java,
+                    Some("com.acme.Parser.parse"),
+                    Some("com/acme/Parser.java"),
+                    Some(40)
+                ),
+                (
+                    TraceFormat::Node,
+                    Some("loadUser"),
+                    Some("/home/ci/app/src/users.js"),
+                    Some(7)
+                ),
+                (TraceFormat::Rust, None, Some("src/main.rs"), Some(4)),
+                (
+                    TraceFormat::Rust,
+                    Some("cgrep::query::run"),
+                    Some("./src/query/mod.rs"),
+                    Some(20)
+                ),
+                (TraceFormat::Rust, Some("std::rt::lang_start"), None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn short_function_names() {
+        assert_eq!(short_function_name("cgrep::query::run"), Some("run"));
+        assert_eq!(
+            short_function_name("<cgrep::Cache as Drop>::drop::{{closure}}"),
+            Some("drop")
+        );
+        assert_eq!(short_function_name("com.acme.Parser.parse"), Some("parse"));
+        assert_eq!(short_function_name("async Server.handle"), Some("handle"));
+        assert_eq!(short_function_name("<module>"), None);
+        assert_eq!(short_function_name("Object.<anonymous>"), None);
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `cgrep usage`: summary of the local usage file.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::cli::OutputFormat;
+use cgrep::output::print_json;
+use cgrep::usage::{self, UsageSummary};
+
+#[derive(Debug, Serialize)]
+struct UsageResult {
+    /// Usage file the summary was read from.
+    file: Option<String>,
+    #[serde(flatten)]
+    summary: UsageSummary,
+}
+
+pub fn run(days: u64, format: OutputFormat, compact: bool) -> Result<()> {
+    let file = usage::usage_file_path();
+    let events = file.as_deref().map(usage::load).unwrap_or_default();
+    let result = UsageResult {
+        file: file.map(|path| path.display().to_string()),
+        summary: usage::summarize(&events, usage::now_ms(), days),
+    };
+
+    match format {
+        OutputFormat::Json | OutputFormat::Json2 => print_json(&result, compact)?,
+        OutputFormat::Text
+        | OutputFormat::Github
+        | OutputFormat::Gitlab
+        | OutputFormat::Sarif
+        | OutputFormat::Quickfix => print_text(&result),
+    }
+    Ok(())
+}
+
+fn print_text(result: &UsageResult) {
+    let summary = &result.summary;
+    println!(
+        "Usage file: {}",
+        result.file.as_deref().unwrap_or("(unavailable)")
+    );
+    if summary.queries == 0 {
+        println!("No searches recorded in the last {} day(s)", summary.days);
+        return;
+    }
+    println!(
+        "{} searches in the last {} day(s) ({} index, {} scan)",
+        summary.queries.to_string().bold(),
+        summary.days,
+        summary.index_queries,
+        summary.scan_queries
+    );
+    println!("Per day:");
+    for day in &summary.per_day {
+        println!("  {}  {}", day.date, day.queries);
+    }
+    let modes: Vec<String> = summary
+        .modes
+        .iter()
+        .map(|mode| format!("{} {}", mode.mode, mode.queries))
+        .collect();
+    println!("Modes: {}", modes.join(", "));
+    if let Some(latency) = &summary.latency {
+        println!(
+            "Latency: avg {:.1}ms, p50 {:.1}ms, p95 {:.1}ms",
+            latency.avg_ms, latency.p50_ms, latency.p95_ms
+        );
+    }
+    match &summary.cache {
+        Some(cache) => println!(
+            "Cache: {}/{} hits ({:.0}%)",
+            cache.hits,
+            cache.lookups,
+            cache.hit_rate * 100.0
+        ),
+        None => println!("Cache: not used"),
+    }
+}
@@ -0,0 +1,493 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! JSON Schemas for machine-readable payloads.
+//!
+//! The schema files under `schemas/` are embedded in the binary and exposed via
+//! `cgrep schema <command>`; `schemas/v1/` keeps the version 1 schemas that
+//! `--schema-version 1` still emits. [`validate`] implements the subset of JSON
+//! Schema (draft 2020-12) those files use, so payloads can be checked without an
+//! extra dependency.
+
+use once_cell::sync::OnceCell;
+use serde_json::Value;
+
+/// json2 schema version emitted when `--schema-version` is not given.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+/// [`CURRENT_SCHEMA_VERSION`] as written to `meta.schema_version`.
+pub const JSON2_SCHEMA_VERSION: &str = "2";
+/// Oldest json2 schema version `--schema-version` can still emit.
+///
+/// When a breaking payload change bumps [`CURRENT_SCHEMA_VERSION`], keep this at
+/// the previous major and register a step in [`DOWNGRADES`].
+pub const OLDEST_SCHEMA_VERSION: u32 = 1;
+
+/// Rewrites a `(command, from_version)` payload into `from_version - 1` shape.
+type DowngradeStep = fn(Value) -> Value;
+
+/// Registered downgrade steps.
+///
+/// Version 2 only added fields, so going back to version 1 drops whatever the
+/// version 1 schema does not declare.
+const DOWNGRADES: &[(&str, u32, DowngradeStep)] = &[
+    ("search", 2, |payload| prune_to_v1("search", payload)),
+    ("read", 2, |payload| prune_to_v1("read", payload)),
+    ("status", 2, |payload| prune_to_v1("status", payload)),
+];
+
+static REQUESTED_VERSION: OnceCell<u32> = OnceCell::new();
+
+/// Embedded schemas for [`CURRENT_SCHEMA_VERSION`], keyed by the command name
+/// accepted by `cgrep schema`.
+pub const SCHEMAS: &[(&str, &str)] = &[
+    ("search", include_str!("../schemas/search.schema.json")),
+    ("status", include_str!("../schemas/status.schema.json")),
+    ("doctor", include_str!("../schemas/doctor.schema.json")),
+    (
+        "agent-plan",
+        include_str!("../schemas/agent-plan.schema.json"),
+    ),
+    (
+        "agent-expand",
+        include_str!("../schemas/agent-expand.schema.json"),
+    ),
+    ("read", include_str!("../schemas/read.schema.json")),
+    ("map", include_str!("../schemas/map.schema.json")),
+    ("check", include_str!("../schemas/check.schema.json")),
+    ("trace", include_str!("../schemas/trace.schema.json")),
+    ("review", include_str!("../schemas/review.schema.json")),
+    ("symbols", include_str!("../schemas/symbols.schema.json")),
+    ("routes", include_str!("../schemas/routes.schema.json")),
+    ("audit", include_str!("../schemas/audit.schema.json")),
+];
+
+/// Embedded schemas for version 1, covering the commands that had a json2
+/// payload at the time.
+pub const V1_SCHEMAS: &[(&str, &str)] = &[
+    ("search", include_str!("../schemas/v1/search.schema.json")),
+    ("status", include_str!("../schemas/v1/status.schema.json")),
+    ("doctor", include_str!("../schemas/v1/doctor.schema.json")),
+    (
+        "agent-plan",
+        include_str!("../schemas/v1/agent-plan.schema.json"),
+    ),
+    (
+        "agent-expand",
+        include_str!("../schemas/v1/agent-expand.schema.json"),
+    ),
+    ("read", include_str!("../schemas/v1/read.schema.json")),
+    ("map", include_str!("../schemas/v1/map.schema.json")),
+];
+
+fn schemas_for(version: u32) -> &'static [(&'static str, &'static str)] {
+    if version == 1 {
+        V1_SCHEMAS
+    } else {
+        SCHEMAS
+    }
+}
+
+/// Names accepted by [`schema_source`] in the requested schema version.
+pub fn schema_names() -> Vec<&'static str> {
+    schemas_for(requested_version())
+        .iter()
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Raw schema text for a command in the requested schema version.
+pub fn schema_source(name: &str) -> Option<&'static str> {
+    schema_source_for(name, requested_version())
+}
+
+fn schema_source_for(name: &str, version: u32) -> Option<&'static str> {
+    schemas_for(version)
+        .iter()
+        .find(|(candidate, _)| *candidate == name)
+        .map(|(_, source)| *source)
+}
+
+/// Parsed schema for a command in the requested schema version.
+pub fn schema(name: &str) -> Option<Value> {
+    schema_source(name).and_then(|source| serde_json::from_str(source).ok())
+}
+
+/// Select the json2 schema version for this process (`--schema-version`).
+pub fn set_requested_version(version: u32) -> Result<(), String> {
+    if !(OLDEST_SCHEMA_VERSION..=CURRENT_SCHEMA_VERSION).contains(&version) {
+        return Err(format!(
+            "Unsupported --schema-version {} (supported: {})",
+            version,
+            supported_versions_label()
+        ));
+    }
+    let _ = REQUESTED_VERSION.set(version);
+    Ok(())
+}
+
+/// json2 schema version to emit.
+pub fn requested_version() -> u32 {
+    REQUESTED_VERSION
+        .get()
+        .copied()
+        .unwrap_or(CURRENT_SCHEMA_VERSION)
+}
+
+fn supported_versions_label() -> String {
+    if OLDEST_SCHEMA_VERSION == CURRENT_SCHEMA_VERSION {
+        CURRENT_SCHEMA_VERSION.to_string()
+    } else {
+        format!("{OLDEST_SCHEMA_VERSION}-{CURRENT_SCHEMA_VERSION}")
+    }
+}
+
+/// Convert a current-version json2 payload for `command` into `target` shape.
+///
+/// Steps are applied one version at a time; `meta.schema_version` is set to
+/// `target` at the end. Fails for commands that had no json2 payload in
+/// `target`.
+pub fn downgrade(command: &str, mut payload: Value, target: u32) -> Result<Value, String> {
+    if schema_source_for(command, target).is_none() {
+        return Err(format!(
+            "`{command}` has no schema version {target} payload (json2 output for it starts at version {})",
+            (target + 1..=CURRENT_SCHEMA_VERSION)
+                .find(|version| schema_source_for(command, *version).is_some())
+                .unwrap_or(CURRENT_SCHEMA_VERSION)
+        ));
+    }
+    let mut version = CURRENT_SCHEMA_VERSION;
+    while version > target {
+        if let Some((_, _, step)) = DOWNGRADES
+            .iter()
+            .find(|(name, from, _)| *name == command && *from == version)
+        {
+            payload = step(payload);
+        }
+        version -= 1;
+    }
+    if let Some(meta) = payload.get_mut("meta").and_then(Value::as_object_mut) {
+        meta.insert(
+            "schema_version".to_string(),
+            Value::String(target.to_string()),
+        );
+    }
+    Ok(payload)
+}
+
+fn prune_to_v1(command: &str, mut payload: Value) -> Value {
+    if let Some(schema) =
+        schema_source_for(command, 1).and_then(|source| serde_json::from_str(source).ok())
+    {
+        prune(&schema, &schema, &mut payload);
+    }
+    payload
+}
+
+/// Drop properties `schema` does not declare wherever it disallows additional
+/// properties, recursing the same way [`validate`] does.
+fn prune(root: &Value, schema: &Value, instance: &mut Value) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        if let Some(target) = resolve_ref(root, reference) {
+            prune(root, target, instance);
+        }
+        return;
+    }
+    match instance {
+        Value::Object(fields) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let additional = schema.get("additionalProperties");
+            if additional == Some(&Value::Bool(false)) {
+                fields.retain(|name, _| properties.is_some_and(|props| props.contains_key(name)));
+            }
+            for (name, value) in fields.iter_mut() {
+                match properties.and_then(|props| props.get(name)) {
+                    Some(property) => prune(root, property, value),
+                    None => {
+                        if let Some(extra @ Value::Object(_)) = additional {
+                            prune(root, extra, value);
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for item in items {
+                    prune(root, item_schema, item);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A single validation failure, located by JSON pointer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            self.pointer.as_str()
+        };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// Validate `instance` against `schema`, returning every failure found.
+///
+/// Supported keywords: `type`, `const`, `enum`, `properties`, `required`,
+/// `additionalProperties`, `items`, `minimum`, and local `$ref`s into `$defs`.
+pub fn validate(schema: &Value, instance: &Value) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    validate_node(schema, schema, instance, "", &mut errors);
+    errors
+}
+
+fn validate_node(
+    root: &Value,
+    schema: &Value,
+    instance: &Value,
+    pointer: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut fail = |message: String| {
+        errors.push(ValidationError {
+            pointer: pointer.to_string(),
+            message,
+        })
+    };
+
+    let Some(schema) = schema.as_object() else {
+        // `true` accepts anything; `false` rejects everything.
+        if schema == &Value::Bool(false) {
+            fail("no value is allowed here".to_string());
+        }
+        return;
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve_ref(root, reference) {
+            Some(target) => validate_node(root, target, instance, pointer, errors),
+            None => fail(format!("unresolvable $ref {reference}")),
+        }
+        return;
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.iter().any(|name| type_matches(name, instance)) {
+            fail(format!(
+                "expected {}, found {}",
+                allowed.join(" or "),
+                type_name(instance)
+            ));
+            return;
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if expected != instance {
+            fail(format!("expected constant {expected}, found {instance}"));
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(instance) {
+            fail(format!(
+                "{instance} is not one of {}",
+                Value::Array(options.clone())
+            ));
+        }
+    }
+
+    if let (Some(minimum), Some(value)) = (
+        schema.get("minimum").and_then(Value::as_f64),
+        instance.as_f64(),
+    ) {
+        if value < minimum {
+            fail(format!("{value} is less than minimum {minimum}"));
+        }
+    }
+
+    if let Value::Object(fields) = instance {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    fail(format!("missing required property `{name}`"));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let additional = schema.get("additionalProperties");
+        for (name, value) in fields {
+            let child = format!("{pointer}/{}", escape_pointer(name));
+            match properties.and_then(|props| props.get(name)) {
+                Some(property) => validate_node(root, property, value, &child, errors),
+                None => match additional {
+                    Some(Value::Bool(false)) => errors.push(ValidationError {
+                        pointer: child,
+                        message: format!("unexpected property `{name}`"),
+                    }),
+                    Some(extra @ Value::Object(_)) => {
+                        validate_node(root, extra, value, &child, errors)
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (instance, schema.get("items")) {
+        for (idx, item) in items.iter().enumerate() {
+            validate_node(root, item_schema, item, &format!("{pointer}/{idx}"), errors);
+        }
+    }
+}
+
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let path = reference.strip_prefix('#')?;
+    if path.is_empty() {
+        return Some(root);
+    }
+    root.pointer(path)
+}
+
+fn type_matches(name: &str, instance: &Value) -> bool {
+    match name {
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "boolean" => instance.is_boolean(),
+        "null" => instance.is_null(),
+        "number" => instance.is_number(),
+        "integer" => instance.is_i64() || instance.is_u64(),
+        _ => false,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn out_of_range_schema_versions_are_rejected() {
+        let err = set_requested_version(CURRENT_SCHEMA_VERSION + 1).expect_err("too new");
+        assert!(err.contains("Unsupported --schema-version"));
+        assert!(set_requested_version(0).is_err());
+    }
+
+    #[test]
+    fn downgrade_to_current_version_keeps_payload() {
+        let payload = json!({"meta": {"schema_version": "2", "query": "q"}, "results": []});
+        assert_eq!(
+            downgrade("search", payload.clone(), CURRENT_SCHEMA_VERSION),
+            Ok(payload)
+        );
+        assert_eq!(JSON2_SCHEMA_VERSION, CURRENT_SCHEMA_VERSION.to_string());
+    }
+
+    #[test]
+    fn downgrade_to_v1_drops_fields_added_in_v2() {
+        let payload = json!({
+            "meta": {"schema_version": "2", "command": "read"},
+            "result": {
+                "path": "a.rs",
+                "display_path": "a.rs",
+                "mode": "full",
+                "size_bytes": 10,
+                "line_count": 1,
+                "tokens_estimate": 3,
+                "content": "fn a() {}\n",
+                "outline": [],
+                "encoding": "utf-8"
+            }
+        });
+        let v1_schema = serde_json::from_str(schema_source_for("read", 1).unwrap()).unwrap();
+        assert!(!validate(&v1_schema, &payload).is_empty());
+        let v1 = downgrade("read", payload, 1).expect("read had a v1 payload");
+        assert_eq!(v1["meta"]["schema_version"], "1");
+        assert!(v1["result"].get("display_path").is_none());
+        assert!(v1["result"].get("outline").is_none());
+        assert!(v1["result"].get("encoding").is_none());
+        assert_eq!(v1["result"]["content"], "fn a() {}\n");
+        let errors = validate(&v1_schema, &v1);
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn downgrade_rejects_commands_added_after_the_target() {
+        let err = downgrade("audit", json!({"meta": {}}), 1).expect_err("audit is new in v2");
+        assert!(err.contains("starts at version 2"), "{err}");
+    }
+
+    #[test]
+    fn embedded_schemas_parse() {
+        for (version, schemas) in [(1, V1_SCHEMAS), (CURRENT_SCHEMA_VERSION, SCHEMAS)] {
+            for (name, source) in schemas {
+                let parsed: Value = serde_json::from_str(source)
+                    .unwrap_or_else(|_| panic!("schema {name} should parse"));
+                assert_eq!(parsed["type"], "object", "{name}");
+                assert_eq!(
+                    parsed["properties"]["meta"]["properties"]["schema_version"]["const"],
+                    version.to_string(),
+                    "{name}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn validate_reports_pointer_for_each_failure() {
+        let schema = json!({
+            "type": "object",
+            "required": ["meta", "results"],
+            "additionalProperties": false,
+            "properties": {
+                "meta": { "type": "object", "properties": { "n": { "type": "integer", "minimum": 0 } } },
+                "results": { "type": "array", "items": { "$ref": "#/$defs/item" } }
+            },
+            "$defs": { "item": { "enum": ["a", "b"] } }
+        });
+        let errors = validate(
+            &schema,
+            &json!({"meta": {"n": -1}, "results": ["a", "c"], "x": 1}),
+        );
+        let pointers: Vec<&str> = errors.iter().map(|e| e.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/meta/n", "/results/1", "/x"]);
+
+        assert!(validate(&schema, &json!({"meta": {}, "results": []})).is_empty());
+        assert_eq!(
+            validate(&schema, &json!({"meta": {}}))[0].to_string(),
+            "/: missing required property `results`"
+        );
+    }
+}
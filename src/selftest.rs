@@ -0,0 +1,286 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Property checks behind `cgrep selftest` and the fuzz targets.
+//!
+//! Each `check_*` function takes one arbitrary input and returns `Err` with
+//! a description when a property of the code under test does not hold. The
+//! fuzz targets under `fuzz/` feed them libFuzzer input; [`run`] feeds them
+//! strings from a seeded generator that favors punctuation, whitespace, and
+//! non-ASCII text, so a failing case can be replayed from its seed.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Schema, TEXT};
+use tantivy::Index;
+
+use crate::filters::{matches_glob, CompiledGlob};
+use crate::syntax::{escape_as_query_phrase, normalize_section_range};
+
+/// Failing inputs kept per check.
+const MAX_FAILURES: usize = 5;
+const MAX_INPUT_CHARS: usize = 24;
+
+/// Characters the generator draws from: query parser and glob metacharacters,
+/// whitespace, and a few multi-byte characters.
+const ALPHABET: &[char] = &[
+    'a', 'b', 'z', 'A', 'Z', '0', '1', '9', '_', '-', '+', '!', '(', ')', '{', '}', '[', ']', '^',
+    '"', '~', '*', '?', ':', '\\', '/', '.', ',', '\'', '`', '|', '&', '$', '#', '@', '%', '=',
+    '<', '>', ';', ' ', '\t', '\n', 'é', 'ß', '中', '🦀', '\u{200b}',
+];
+
+/// Parser over a single text field, as search builds for each query.
+static QUERY_PARSER: Lazy<QueryParser> = Lazy::new(|| {
+    let mut builder = Schema::builder();
+    let content = builder.add_text_field("content", TEXT);
+    let index = Index::create_in_ram(builder.build());
+    QueryParser::for_index(&index, vec![content])
+});
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelftestReport {
+    pub seed: u64,
+    pub cases: usize,
+    pub checks: Vec<CheckReport>,
+}
+
+impl SelftestReport {
+    pub fn failed(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| !check.failures.is_empty())
+            .count()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckReport {
+    pub name: &'static str,
+    pub cases: usize,
+    pub failures: Vec<CheckFailure>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckFailure {
+    pub input: String,
+    pub detail: String,
+}
+
+/// An escaped query is one Tantivy phrase that the parser accepts and that
+/// unescapes back to the original text.
+pub fn check_query_phrase(query: &str) -> Result<(), String> {
+    let escaped = escape_as_query_phrase(query);
+    let inner = escaped
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .ok_or_else(|| format!("not wrapped in quotes: {escaped:?}"))?;
+    let mut unescaped = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => unescaped.extend(chars.next()),
+            '"' => return Err(format!("unescaped quote in {escaped:?}")),
+            ch => unescaped.push(ch),
+        }
+    }
+    if unescaped != query {
+        return Err(format!("round-trips to {unescaped:?}"));
+    }
+
+    QUERY_PARSER
+        .parse_query(&escaped)
+        .map(|_| ())
+        .map_err(|err| format!("parser rejected {escaped:?}: {err}"))
+}
+
+/// Section ranges are trimmed, idempotent, and `a:b` digit pairs become `a-b`.
+pub fn check_section_range(value: &str) -> Result<(), String> {
+    let normalized = normalize_section_range(value);
+    if normalized != normalized.trim() {
+        return Err(format!("not trimmed: {normalized:?}"));
+    }
+    let again = normalize_section_range(&normalized);
+    if again != normalized {
+        return Err(format!("not idempotent: {normalized:?} -> {again:?}"));
+    }
+    if let Some((start, end)) = normalized.split_once(':') {
+        let digits = |part: &str| {
+            let part = part.trim();
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())
+        };
+        if digits(start) && digits(end) {
+            return Err(format!("digit range left as {normalized:?}"));
+        }
+    }
+    Ok(())
+}
+
+/// Every pattern compiles, the one-shot and compiled matchers agree, a
+/// pattern without wildcards matches itself, and `**/` prefixes match any
+/// directory depth.
+pub fn check_glob(pattern: &str, path: &str) -> Result<(), String> {
+    let Some(glob) = CompiledGlob::new(pattern) else {
+        return Err("pattern does not compile".to_string());
+    };
+    if glob.is_match(path) != matches_glob(path, Some(pattern)) {
+        return Err(format!("matchers disagree on {path:?}"));
+    }
+    if !pattern.contains(['*', '?']) && !glob.is_match(pattern) {
+        return Err("literal pattern does not match itself".to_string());
+    }
+    let nested = format!("**/{pattern}");
+    if !pattern.contains(['*', '?'])
+        && !CompiledGlob::new(&nested).is_some_and(|glob| glob.is_match(&format!("x/y/{pattern}")))
+    {
+        return Err(format!("{nested:?} does not match a nested copy"));
+    }
+    Ok(())
+}
+
+/// xorshift64*: small, seedable, and the same on every platform.
+struct Generator(u64);
+
+impl Generator {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn string(&mut self) -> String {
+        let len = self.below(MAX_INPUT_CHARS + 1);
+        (0..len)
+            .map(|_| ALPHABET[self.below(ALPHABET.len())])
+            .collect()
+    }
+}
+
+/// Run every check on `cases` generated inputs.
+pub fn run(cases: usize, seed: u64) -> SelftestReport {
+    let mut generator = Generator::new(seed);
+    let mut query_phrase = CheckReport::new("query_phrase", cases);
+    let mut section_range = CheckReport::new("section_range", cases);
+    let mut glob = CheckReport::new("glob", cases);
+    for _ in 0..cases {
+        let input = generator.string();
+        query_phrase.record(&input, check_query_phrase(&input));
+        section_range.record(&input, check_section_range(&input));
+        let path = generator.string();
+        glob.record(&format!("{input} | {path}"), check_glob(&input, &path));
+    }
+    SelftestReport {
+        seed,
+        cases,
+        checks: vec![query_phrase, section_range, glob, embedded_schemas()],
+    }
+}
+
+/// The json2 schemas compiled into the binary parse.
+fn embedded_schemas() -> CheckReport {
+    let names = crate::schema::schema_names();
+    let mut report = CheckReport::new("embedded_schemas", names.len());
+    for name in names {
+        let parsed = crate::schema::schema(name)
+            .map(|_| ())
+            .ok_or_else(|| "schema is not valid JSON".to_string());
+        report.record(name, parsed);
+    }
+    report
+}
+
+impl CheckReport {
+    fn new(name: &'static str, cases: usize) -> Self {
+        Self {
+            name,
+            cases,
+            failures: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, input: &str, outcome: Result<(), String>) {
+        if let Err(detail) = outcome {
+            if self.failures.len() < MAX_FAILURES {
+                self.failures.push(CheckFailure {
+                    input: input.to_string(),
+                    detail,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Strings over [`ALPHABET`], like those `cgrep selftest` generates.
+    fn input() -> impl Strategy<Value = String> {
+        proptest::collection::vec(proptest::sample::select(ALPHABET), 0..=MAX_INPUT_CHARS)
+            .prop_map(|chars| chars.into_iter().collect())
+    }
+
+    /// Metacharacter-heavy strings, or any string at all.
+    fn any_input() -> impl Strategy<Value = String> {
+        prop_oneof![3 => input(), 1 => any::<String>()]
+    }
+
+    proptest! {
+        #[test]
+        fn escaped_queries_round_trip_and_parse(query in any_input()) {
+            check_query_phrase(&query).map_err(TestCaseError::fail)?;
+        }
+
+        #[test]
+        fn section_ranges_normalize(value in any_input()) {
+            check_section_range(&value).map_err(TestCaseError::fail)?;
+        }
+
+        #[test]
+        fn globs_compile_and_agree(pattern in any_input(), path in any_input()) {
+            check_glob(&pattern, &path).map_err(TestCaseError::fail)?;
+        }
+    }
+
+    #[test]
+    fn seeded_runs_pass_every_check() {
+        let report = run(50, 0x5eed);
+        for check in &report.checks {
+            assert!(
+                check.failures.is_empty(),
+                "{}: {:?}",
+                check.name,
+                check.failures
+            );
+        }
+    }
+
+    #[test]
+    fn exotic_inputs_satisfy_all_properties() {
+        for input in [
+            "",
+            "\"",
+            "\\",
+            "\\\"",
+            "a\"b\\",
+            "   ",
+            "1 : 2",
+            "(*?)",
+            "🦀::new",
+        ] {
+            check_query_phrase(input).expect(input);
+            check_section_range(input).expect(input);
+            check_glob(input, input).expect(input);
+        }
+        assert_eq!(normalize_section_range(" 3 : 7 "), "3-7");
+    }
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Escaping and normalization of user-supplied query text.
+//!
+//! These run on arbitrary input from the CLI and MCP clients, so they are
+//! covered by `cgrep selftest` and the fuzz targets under `fuzz/`.
+
+/// Wrap `query` in a Tantivy phrase so parser metacharacters match literally.
+pub fn escape_as_query_phrase(query: &str) -> String {
+    let mut out = String::with_capacity(query.len() + 2);
+    out.push('"');
+    for ch in query.chars() {
+        if ch == '"' || ch == '\\' {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out.push('"');
+    out
+}
+
+/// Accept `start:end` as well as `start-end` for a read section range.
+pub fn normalize_section_range(value: &str) -> String {
+    let trimmed = value.trim();
+    if let Some((start, end)) = trimmed.split_once(':') {
+        let start = start.trim();
+        let end = end.trim();
+        if !start.is_empty()
+            && !end.is_empty()
+            && start.chars().all(|c| c.is_ascii_digit())
+            && end.chars().all(|c| c.is_ascii_digit())
+        {
+            return format!("{start}-{end}");
+        }
+    }
+    trimmed.to_string()
+}
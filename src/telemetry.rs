@@ -0,0 +1,732 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Optional OpenTelemetry metrics and trace export.
+//!
+//! Metrics are aggregated in-process and spans are buffered until the next
+//! flush; both are exported as OTLP/HTTP JSON to `<endpoint>/v1/metrics` and
+//! `<endpoint>/v1/traces`. Recording is a no-op unless `[telemetry]` is
+//! enabled in config, and exporting requires the `otel` cargo feature.
+//!
+//! Exported instruments:
+//! - `cgrep.search.duration` (histogram, ms): search latency by mode
+//! - `cgrep.search.cache.requests` (counter): cache lookups by `hit`
+//! - `cgrep.index.build.duration` (histogram, ms): index builds by kind
+//! - `cgrep.mcp.tool_calls` (counter): MCP tool calls by tool/outcome
+//! - `cgrep.mcp.tool_call.duration` (histogram, ms): MCP tool call latency
+//!
+//! Exported spans: `cgrep.search`, `cgrep.index.build`, `cgrep.mcp.tool_call`.
+//! A `TRACEPARENT` environment variable (W3C trace context) parents the
+//! process's root spans, which is how MCP tool calls link to the cgrep
+//! subprocess that serves them.
+
+use crate::config::TelemetryConfig;
+use once_cell::sync::OnceCell;
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Histogram bucket upper bounds in milliseconds.
+pub const DURATION_BUCKETS_MS: &[f64] = &[
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1_000.0, 2_500.0, 5_000.0, 10_000.0,
+];
+
+/// Attribute list attached to a data point (`key=value` pairs).
+pub type Attributes = Vec<(&'static str, String)>;
+
+type SeriesKey = (&'static str, Attributes);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramPoint {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    /// One count per bucket in [`DURATION_BUCKETS_MS`] plus the overflow bucket.
+    pub bucket_counts: Vec<u64>,
+}
+
+impl HistogramPoint {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            bucket_counts: vec![0; DURATION_BUCKETS_MS.len() + 1],
+        }
+    }
+
+    fn record(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        let bucket = DURATION_BUCKETS_MS
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(DURATION_BUCKETS_MS.len());
+        self.bucket_counts[bucket] += 1;
+    }
+}
+
+/// Aggregated metrics since the last export (delta temporality).
+#[derive(Debug, Default)]
+pub struct MetricsSnapshot {
+    pub start_unix_nanos: u64,
+    pub counters: BTreeMap<SeriesKey, u64>,
+    pub histograms: BTreeMap<SeriesKey, HistogramPoint>,
+}
+
+impl MetricsSnapshot {
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty() && self.histograms.is_empty()
+    }
+}
+
+/// Spans buffered past this count are dropped until the next export.
+const MAX_PENDING_SPANS: usize = 4096;
+
+/// Trace and span ids shared by a span and its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpanContext {
+    pub trace_id: u128,
+    pub span_id: u64,
+}
+
+impl SpanContext {
+    /// Parse a W3C `traceparent` header value (`00-<trace>-<span>-<flags>`).
+    pub fn from_traceparent(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('-');
+        let (version, trace, span) = (parts.next()?, parts.next()?, parts.next()?);
+        parts.next()?;
+        if version != "00" || trace.len() != 32 || span.len() != 16 {
+            return None;
+        }
+        let trace_id = u128::from_str_radix(trace, 16).ok()?;
+        let span_id = u64::from_str_radix(span, 16).ok()?;
+        (trace_id != 0 && span_id != 0).then_some(Self { trace_id, span_id })
+    }
+
+    pub fn traceparent(&self) -> String {
+        format!("00-{:032x}-{:016x}-01", self.trace_id, self.span_id)
+    }
+}
+
+/// A finished span waiting for export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanRecord {
+    pub name: &'static str,
+    pub context: SpanContext,
+    pub parent_span_id: Option<u64>,
+    pub start_unix_nanos: u64,
+    pub end_unix_nanos: u64,
+    pub attributes: Attributes,
+    pub status: SpanStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanStatus {
+    /// The span was dropped without [`Span::end`], e.g. on an early return.
+    Unset,
+    Ok,
+    Error,
+}
+
+struct Telemetry {
+    settings: ExportSettings,
+    /// Parent for root spans, from the `TRACEPARENT` environment variable.
+    remote_parent: Option<SpanContext>,
+    state: Mutex<TelemetryState>,
+}
+
+struct TelemetryState {
+    snapshot: MetricsSnapshot,
+    spans: Vec<SpanRecord>,
+    last_export: Instant,
+}
+
+/// Resolved exporter settings (config + `OTEL_*` environment overrides).
+#[derive(Debug, Clone)]
+pub struct ExportSettings {
+    pub endpoint: String,
+    pub service_name: String,
+    pub headers: Vec<(String, String)>,
+    pub export_interval: Duration,
+    pub timeout: Duration,
+}
+
+impl ExportSettings {
+    pub fn from_config(config: &TelemetryConfig) -> Self {
+        let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| config.endpoint().to_string());
+        let service_name = std::env::var("OTEL_SERVICE_NAME")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| config.service_name().to_string());
+        let mut headers: Vec<(String, String)> = config
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        headers.sort();
+        Self {
+            endpoint,
+            service_name,
+            headers,
+            export_interval: Duration::from_millis(config.export_interval_ms()),
+            timeout: Duration::from_millis(config.timeout_ms()),
+        }
+    }
+
+    /// Full OTLP/HTTP metrics URL.
+    pub fn metrics_url(&self) -> String {
+        self.signal_url("metrics")
+    }
+
+    /// Full OTLP/HTTP traces URL.
+    pub fn traces_url(&self) -> String {
+        self.signal_url("traces")
+    }
+
+    fn signal_url(&self, signal: &str) -> String {
+        let base = self.endpoint.trim_end_matches('/');
+        let base = ["/v1/metrics", "/v1/traces"]
+            .iter()
+            .find_map(|suffix| base.strip_suffix(suffix))
+            .unwrap_or(base);
+        format!("{}/v1/{}", base, signal)
+    }
+}
+
+static TELEMETRY: OnceCell<Option<Telemetry>> = OnceCell::new();
+
+/// Flushes pending metrics when dropped (e.g. at the end of `main`).
+#[must_use = "metrics are flushed when the guard is dropped"]
+pub struct TelemetryGuard {
+    _private: (),
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        flush();
+    }
+}
+
+/// Initialize telemetry from config. Only the first call takes effect.
+pub fn init(config: &TelemetryConfig) -> TelemetryGuard {
+    TELEMETRY.get_or_init(|| {
+        if !config.enabled() {
+            return None;
+        }
+        if !cfg!(feature = "otel") {
+            eprintln!(
+                "Warning: [telemetry] is enabled but this cgrep build lacks the `otel` feature; metrics and traces will not be exported."
+            );
+            return None;
+        }
+        Some(Telemetry {
+            settings: ExportSettings::from_config(config),
+            remote_parent: std::env::var("TRACEPARENT")
+                .ok()
+                .and_then(|value| SpanContext::from_traceparent(&value)),
+            state: Mutex::new(TelemetryState {
+                snapshot: MetricsSnapshot {
+                    start_unix_nanos: unix_nanos(),
+                    ..MetricsSnapshot::default()
+                },
+                spans: Vec::new(),
+                last_export: Instant::now(),
+            }),
+        })
+    });
+    TelemetryGuard { _private: () }
+}
+
+fn active() -> Option<&'static Telemetry> {
+    TELEMETRY.get().and_then(Option::as_ref)
+}
+
+/// Returns true when metrics are being recorded.
+pub fn enabled() -> bool {
+    active().is_some()
+}
+
+/// Add `value` to a monotonic counter.
+pub fn add_counter(name: &'static str, value: u64, attributes: Attributes) {
+    let Some(telemetry) = active() else {
+        return;
+    };
+    if let Ok(mut state) = telemetry.state.lock() {
+        *state
+            .snapshot
+            .counters
+            .entry((name, attributes))
+            .or_insert(0) += value;
+    }
+}
+
+/// Record a duration (in milliseconds) into a histogram.
+pub fn record_duration(name: &'static str, elapsed: Duration, attributes: Attributes) {
+    let Some(telemetry) = active() else {
+        return;
+    };
+    if let Ok(mut state) = telemetry.state.lock() {
+        state
+            .snapshot
+            .histograms
+            .entry((name, attributes))
+            .or_insert_with(HistogramPoint::new)
+            .record(elapsed.as_secs_f64() * 1000.0);
+    }
+}
+
+thread_local! {
+    static CURRENT_SPAN: Cell<Option<SpanContext>> = const { Cell::new(None) };
+}
+
+/// An in-progress span. Spans started while another is open on the same
+/// thread become its children.
+#[must_use = "the span is recorded when ended or dropped"]
+pub struct Span {
+    inner: Option<OpenSpan>,
+}
+
+struct OpenSpan {
+    name: &'static str,
+    context: SpanContext,
+    parent_span_id: Option<u64>,
+    previous: Option<SpanContext>,
+    start_unix_nanos: u64,
+    started: Instant,
+}
+
+impl Span {
+    /// Start a span; inert when telemetry is disabled.
+    pub fn start(name: &'static str) -> Self {
+        let Some(telemetry) = active() else {
+            return Self { inner: None };
+        };
+        let previous = CURRENT_SPAN.with(Cell::get);
+        let parent = previous.or(telemetry.remote_parent);
+        let context = SpanContext {
+            trace_id: parent.map_or_else(|| random_id(2), |parent| parent.trace_id),
+            span_id: random_id(1) as u64,
+        };
+        CURRENT_SPAN.with(|current| current.set(Some(context)));
+        Self {
+            inner: Some(OpenSpan {
+                name,
+                context,
+                parent_span_id: parent.map(|parent| parent.span_id),
+                previous,
+                start_unix_nanos: unix_nanos(),
+                started: Instant::now(),
+            }),
+        }
+    }
+
+    /// End the span with its attributes and outcome.
+    pub fn end(mut self, attributes: Attributes, ok: bool) {
+        let status = if ok {
+            SpanStatus::Ok
+        } else {
+            SpanStatus::Error
+        };
+        self.finish(attributes, status);
+    }
+
+    fn finish(&mut self, attributes: Attributes, status: SpanStatus) {
+        let Some(open) = self.inner.take() else {
+            return;
+        };
+        CURRENT_SPAN.with(|current| current.set(open.previous));
+        let Some(telemetry) = active() else {
+            return;
+        };
+        let record = SpanRecord {
+            name: open.name,
+            context: open.context,
+            parent_span_id: open.parent_span_id,
+            start_unix_nanos: open.start_unix_nanos,
+            end_unix_nanos: open.start_unix_nanos + open.started.elapsed().as_nanos() as u64,
+            attributes,
+            status,
+        };
+        if let Ok(mut state) = telemetry.state.lock() {
+            if state.spans.len() < MAX_PENDING_SPANS {
+                state.spans.push(record);
+            }
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        self.finish(Vec::new(), SpanStatus::Unset);
+    }
+}
+
+/// W3C `traceparent` value for the span open on this thread, if any.
+pub fn traceparent() -> Option<String> {
+    CURRENT_SPAN
+        .with(Cell::get)
+        .map(|context| context.traceparent())
+}
+
+/// Non-zero random id of `words` 64-bit words.
+fn random_id(words: u32) -> u128 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut id = 0u128;
+    for _ in 0..words {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        hasher.write_u64(unix_nanos());
+        id = (id << 64) | u128::from(hasher.finish());
+    }
+    id.max(1)
+}
+
+/// Export if the configured interval has elapsed (for long-running servers).
+pub fn maybe_flush() {
+    let Some(telemetry) = active() else {
+        return;
+    };
+    let due = telemetry
+        .state
+        .lock()
+        .map(|state| state.last_export.elapsed() >= telemetry.settings.export_interval)
+        .unwrap_or(false);
+    if due {
+        flush();
+    }
+}
+
+/// Export all pending metrics and spans now. Export failures are logged, never fatal.
+pub fn flush() {
+    let Some(telemetry) = active() else {
+        return;
+    };
+    let (snapshot, spans) = {
+        let Ok(mut state) = telemetry.state.lock() else {
+            return;
+        };
+        state.last_export = Instant::now();
+        let snapshot = std::mem::replace(
+            &mut state.snapshot,
+            MetricsSnapshot {
+                start_unix_nanos: unix_nanos(),
+                ..MetricsSnapshot::default()
+            },
+        );
+        (snapshot, std::mem::take(&mut state.spans))
+    };
+    let settings = &telemetry.settings;
+    if !snapshot.is_empty() {
+        let payload = encode_otlp_json(&snapshot, &settings.service_name, unix_nanos());
+        if let Err(err) = export(settings, &settings.metrics_url(), &payload) {
+            tracing::warn!("telemetry metrics export failed: {}", err);
+        }
+    }
+    if !spans.is_empty() {
+        let payload = encode_otlp_traces_json(&spans, &settings.service_name);
+        if let Err(err) = export(settings, &settings.traces_url(), &payload) {
+            tracing::warn!("telemetry trace export failed: {}", err);
+        }
+    }
+}
+
+#[cfg(feature = "otel")]
+fn export(settings: &ExportSettings, url: &str, payload: &serde_json::Value) -> Result<(), String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(settings.timeout))
+        .build()
+        .into();
+    let mut request = agent.post(url).header("Content-Type", "application/json");
+    for (key, value) in &settings.headers {
+        request = request.header(key.as_str(), value.as_str());
+    }
+    request
+        .send(payload.to_string())
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "otel"))]
+fn export(
+    _settings: &ExportSettings,
+    _url: &str,
+    _payload: &serde_json::Value,
+) -> Result<(), String> {
+    Err("built without the `otel` feature".to_string())
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn encode_attributes(attributes: &Attributes) -> serde_json::Value {
+    serde_json::Value::Array(
+        attributes
+            .iter()
+            .map(|(key, value)| {
+                serde_json::json!({
+                    "key": key,
+                    "value": { "stringValue": value }
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Encode a snapshot as an OTLP `ExportMetricsServiceRequest` (JSON mapping).
+pub fn encode_otlp_json(
+    snapshot: &MetricsSnapshot,
+    service_name: &str,
+    time_unix_nanos: u64,
+) -> serde_json::Value {
+    // OTLP JSON uses string-encoded 64-bit integers.
+    let start = snapshot.start_unix_nanos.to_string();
+    let now = time_unix_nanos.to_string();
+    let mut metrics: Vec<serde_json::Value> = Vec::new();
+
+    let mut counter_names: Vec<&'static str> = snapshot.counters.keys().map(|k| k.0).collect();
+    counter_names.dedup();
+    for name in counter_names {
+        let points: Vec<serde_json::Value> = snapshot
+            .counters
+            .iter()
+            .filter(|((n, _), _)| *n == name)
+            .map(|((_, attrs), value)| {
+                serde_json::json!({
+                    "attributes": encode_attributes(attrs),
+                    "startTimeUnixNano": start,
+                    "timeUnixNano": now,
+                    "asInt": value.to_string(),
+                })
+            })
+            .collect();
+        metrics.push(serde_json::json!({
+            "name": name,
+            "unit": "1",
+            "sum": {
+                "dataPoints": points,
+                "aggregationTemporality": 1,
+                "isMonotonic": true,
+            }
+        }));
+    }
+
+    let mut histogram_names: Vec<&'static str> = snapshot.histograms.keys().map(|k| k.0).collect();
+    histogram_names.dedup();
+    for name in histogram_names {
+        let points: Vec<serde_json::Value> = snapshot
+            .histograms
+            .iter()
+            .filter(|((n, _), _)| *n == name)
+            .map(|((_, attrs), point)| {
+                serde_json::json!({
+                    "attributes": encode_attributes(attrs),
+                    "startTimeUnixNano": start,
+                    "timeUnixNano": now,
+                    "count": point.count.to_string(),
+                    "sum": point.sum,
+                    "min": point.min,
+                    "max": point.max,
+                    "bucketCounts": point
+                        .bucket_counts
+                        .iter()
+                        .map(u64::to_string)
+                        .collect::<Vec<_>>(),
+                    "explicitBounds": DURATION_BUCKETS_MS,
+                })
+            })
+            .collect();
+        metrics.push(serde_json::json!({
+            "name": name,
+            "unit": "ms",
+            "histogram": {
+                "dataPoints": points,
+                "aggregationTemporality": 1,
+            }
+        }));
+    }
+
+    serde_json::json!({
+        "resourceMetrics": [{
+            "resource": encode_resource(service_name),
+            "scopeMetrics": [{
+                "scope": { "name": "cgrep", "version": env!("CARGO_PKG_VERSION") },
+                "metrics": metrics,
+            }]
+        }]
+    })
+}
+
+fn encode_resource(service_name: &str) -> serde_json::Value {
+    serde_json::json!({
+        "attributes": [{
+            "key": "service.name",
+            "value": { "stringValue": service_name }
+        }, {
+            "key": "service.version",
+            "value": { "stringValue": env!("CARGO_PKG_VERSION") }
+        }]
+    })
+}
+
+/// Encode spans as an OTLP `ExportTraceServiceRequest` (JSON mapping).
+pub fn encode_otlp_traces_json(spans: &[SpanRecord], service_name: &str) -> serde_json::Value {
+    let spans: Vec<serde_json::Value> = spans
+        .iter()
+        .map(|span| {
+            let mut encoded = serde_json::json!({
+                "traceId": format!("{:032x}", span.context.trace_id),
+                "spanId": format!("{:016x}", span.context.span_id),
+                "name": span.name,
+                // SPAN_KIND_INTERNAL
+                "kind": 1,
+                "startTimeUnixNano": span.start_unix_nanos.to_string(),
+                "endTimeUnixNano": span.end_unix_nanos.to_string(),
+                "attributes": encode_attributes(&span.attributes),
+                "status": {
+                    "code": match span.status {
+                        SpanStatus::Unset => 0,
+                        SpanStatus::Ok => 1,
+                        SpanStatus::Error => 2,
+                    }
+                },
+            });
+            if let Some(parent) = span.parent_span_id {
+                encoded["parentSpanId"] = format!("{:016x}", parent).into();
+            }
+            encoded
+        })
+        .collect();
+    serde_json::json!({
+        "resourceSpans": [{
+            "resource": encode_resource(service_name),
+            "scopeSpans": [{
+                "scope": { "name": "cgrep", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }]
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_assigns_values_to_buckets() {
+        let mut point = HistogramPoint::new();
+        point.record(0.5);
+        point.record(7.0);
+        point.record(60_000.0);
+        assert_eq!(point.count, 3);
+        assert_eq!(point.bucket_counts[0], 1);
+        assert_eq!(point.bucket_counts[2], 1);
+        assert_eq!(point.bucket_counts[DURATION_BUCKETS_MS.len()], 1);
+        assert_eq!(point.min, 0.5);
+        assert_eq!(point.max, 60_000.0);
+    }
+
+    #[test]
+    fn otlp_json_groups_series_by_metric_name() {
+        let mut snapshot = MetricsSnapshot {
+            start_unix_nanos: 10,
+            ..MetricsSnapshot::default()
+        };
+        snapshot.counters.insert(
+            ("cgrep.search.cache.requests", vec![("hit", "true".into())]),
+            2,
+        );
+        snapshot.counters.insert(
+            ("cgrep.search.cache.requests", vec![("hit", "false".into())]),
+            1,
+        );
+        let mut point = HistogramPoint::new();
+        point.record(12.0);
+        snapshot.histograms.insert(
+            ("cgrep.search.duration", vec![("mode", "keyword".into())]),
+            point,
+        );
+
+        let json = encode_otlp_json(&snapshot, "cgrep-test", 20);
+        let metrics = json["resourceMetrics"][0]["scopeMetrics"][0]["metrics"]
+            .as_array()
+            .expect("metrics array");
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0]["name"], "cgrep.search.cache.requests");
+        assert_eq!(
+            metrics[0]["sum"]["dataPoints"].as_array().map(Vec::len),
+            Some(2)
+        );
+        assert_eq!(metrics[1]["histogram"]["dataPoints"][0]["count"], "1");
+        assert_eq!(
+            json["resourceMetrics"][0]["resource"]["attributes"][0]["value"]["stringValue"],
+            "cgrep-test"
+        );
+    }
+
+    #[test]
+    fn metrics_url_appends_signal_path_once() {
+        let mut settings = ExportSettings::from_config(&TelemetryConfig::default());
+        settings.endpoint = "http://collector:4318/".to_string();
+        assert_eq!(settings.metrics_url(), "http://collector:4318/v1/metrics");
+        settings.endpoint = "http://collector:4318/v1/metrics".to_string();
+        assert_eq!(settings.metrics_url(), "http://collector:4318/v1/metrics");
+        assert_eq!(settings.traces_url(), "http://collector:4318/v1/traces");
+    }
+
+    #[test]
+    fn traceparent_round_trips_and_rejects_malformed_values() {
+        let value = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = SpanContext::from_traceparent(value).expect("valid traceparent");
+        assert_eq!(context.span_id, 0x00f0_67aa_0ba9_02b7);
+        assert_eq!(context.traceparent(), value);
+        for bad in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-zzf067aa0ba902b7-01",
+        ] {
+            assert_eq!(SpanContext::from_traceparent(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn otlp_traces_json_links_children_to_their_parent() {
+        let context = SpanContext {
+            trace_id: 0xab,
+            span_id: 0x2,
+        };
+        let spans = vec![SpanRecord {
+            name: "cgrep.search",
+            context,
+            parent_span_id: Some(0x1),
+            start_unix_nanos: 10,
+            end_unix_nanos: 25,
+            attributes: vec![("search_mode", "keyword".into())],
+            status: SpanStatus::Error,
+        }];
+        let json = encode_otlp_traces_json(&spans, "cgrep-test");
+        let span = &json["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["traceId"], format!("{:032x}", 0xab));
+        assert_eq!(span["spanId"], "0000000000000002");
+        assert_eq!(span["parentSpanId"], "0000000000000001");
+        assert_eq!(span["endTimeUnixNano"], "25");
+        assert_eq!(span["status"]["code"], 2);
+        assert_eq!(span["attributes"][0]["key"], "search_mode");
+    }
+}
@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Local usage stats behind `cgrep usage`.
+//!
+//! Each search appends one JSON line (timestamp, mode, latency, cache
+//! outcome) to a file in the user's data directory. Query text and paths are
+//! never recorded, and nothing leaves the machine. Recording is on unless
+//! `[usage] enabled = false`; `CGREP_USAGE_FILE` moves the file.
+
+use crate::config::UsageConfig;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment override for the usage file location.
+pub const USAGE_FILE_ENV: &str = "CGREP_USAGE_FILE";
+
+/// Size at which the file is trimmed to its newest half.
+const MAX_USAGE_FILE_BYTES: u64 = 4 * 1024 * 1024;
+
+const MS_PER_DAY: u64 = 86_400_000;
+
+static ENABLED: OnceCell<bool> = OnceCell::new();
+
+/// One recorded search.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageEvent {
+    /// Unix time in milliseconds.
+    pub ts: u64,
+    /// Search mode (`keyword`, `semantic`, `hybrid`).
+    pub mode: String,
+    /// `index` or `scan`.
+    pub index_mode: String,
+    pub elapsed_ms: f64,
+    /// Cache outcome; absent when the cache was not consulted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_hit: Option<bool>,
+}
+
+/// Enable recording from config. Only the first call takes effect.
+pub fn init(config: &UsageConfig) {
+    let _ = ENABLED.set(config.enabled());
+}
+
+/// Location of the usage file shared by all repositories.
+pub fn usage_file_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(USAGE_FILE_ENV) {
+        if !path.trim().is_empty() {
+            return Some(PathBuf::from(path));
+        }
+    }
+    dirs::data_local_dir().map(|base| base.join("cgrep").join("usage.jsonl"))
+}
+
+/// Append `event` when recording is enabled. Failures are ignored.
+pub fn record(event: &UsageEvent) {
+    if ENABLED.get() != Some(&true) {
+        return;
+    }
+    if let Some(path) = usage_file_path() {
+        let _ = append(&path, event);
+    }
+}
+
+fn append(path: &Path, event: &UsageEvent) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::metadata(path).is_ok_and(|metadata| metadata.len() > MAX_USAGE_FILE_BYTES) {
+        let content = fs::read_to_string(path).unwrap_or_default();
+        let lines: Vec<&str> = content.lines().collect();
+        let kept = lines[lines.len() / 2..].join("\n");
+        fs::write(path, format!("{kept}\n"))?;
+    }
+    let mut line = serde_json::to_string(event).map_err(std::io::Error::other)?;
+    line.push('\n');
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Recorded events, oldest first; unreadable lines are skipped.
+pub fn load(path: &Path) -> Vec<UsageEvent> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayCount {
+    /// UTC date, `YYYY-MM-DD`.
+    pub date: String,
+    pub queries: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModeCount {
+    pub mode: String,
+    pub queries: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencySummary {
+    pub avg_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CacheSummary {
+    pub lookups: usize,
+    pub hits: usize,
+    pub hit_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageSummary {
+    pub days: u64,
+    pub queries: usize,
+    /// Days with at least one query, oldest first.
+    pub per_day: Vec<DayCount>,
+    /// Search modes, most used first.
+    pub modes: Vec<ModeCount>,
+    pub index_queries: usize,
+    pub scan_queries: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencySummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheSummary>,
+}
+
+/// Summarize events from the last `days` UTC days, ending with the day of
+/// `now_ms`.
+pub fn summarize(events: &[UsageEvent], now_ms: u64, days: u64) -> UsageSummary {
+    let today = now_ms / MS_PER_DAY;
+    let first_day = today.saturating_sub(days.max(1) - 1);
+    let window: Vec<&UsageEvent> = events
+        .iter()
+        .filter(|event| event.ts / MS_PER_DAY >= first_day)
+        .collect();
+
+    let mut per_day: BTreeMap<u64, usize> = BTreeMap::new();
+    let mut modes: BTreeMap<&str, usize> = BTreeMap::new();
+    for event in &window {
+        *per_day.entry(event.ts / MS_PER_DAY).or_default() += 1;
+        *modes.entry(event.mode.as_str()).or_default() += 1;
+    }
+    let mut modes: Vec<ModeCount> = modes
+        .into_iter()
+        .map(|(mode, queries)| ModeCount {
+            mode: mode.to_string(),
+            queries,
+        })
+        .collect();
+    modes.sort_by(|a, b| b.queries.cmp(&a.queries).then_with(|| a.mode.cmp(&b.mode)));
+
+    let mut latencies: Vec<f64> = window.iter().map(|event| event.elapsed_ms).collect();
+    latencies.sort_by(f64::total_cmp);
+    let latency = (!latencies.is_empty()).then(|| LatencySummary {
+        avg_ms: latencies.iter().sum::<f64>() / latencies.len() as f64,
+        p50_ms: percentile(&latencies, 50),
+        p95_ms: percentile(&latencies, 95),
+    });
+
+    let lookups: Vec<bool> = window.iter().filter_map(|event| event.cache_hit).collect();
+    let hits = lookups.iter().filter(|hit| **hit).count();
+    let cache = (!lookups.is_empty()).then(|| CacheSummary {
+        lookups: lookups.len(),
+        hits,
+        hit_rate: hits as f64 / lookups.len() as f64,
+    });
+
+    let index_queries = window
+        .iter()
+        .filter(|event| event.index_mode == "index")
+        .count();
+    UsageSummary {
+        days: days.max(1),
+        queries: window.len(),
+        per_day: per_day
+            .into_iter()
+            .map(|(day, queries)| DayCount {
+                date: civil_date(day),
+                queries,
+            })
+            .collect(),
+        modes,
+        index_queries,
+        scan_queries: window.len() - index_queries,
+        latency,
+        cache,
+    }
+}
+
+/// Nearest-rank percentile of sorted `values`.
+fn percentile(sorted: &[f64], pct: usize) -> f64 {
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// `YYYY-MM-DD` for a count of days since the Unix epoch (proleptic
+/// Gregorian, UTC).
+fn civil_date(days: u64) -> String {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Current Unix time in milliseconds.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(day: u64, mode: &str, elapsed_ms: f64, cache_hit: Option<bool>) -> UsageEvent {
+        UsageEvent {
+            ts: day * MS_PER_DAY + 1_000,
+            mode: mode.to_string(),
+            index_mode: "index".to_string(),
+            elapsed_ms,
+            cache_hit,
+        }
+    }
+
+    #[test]
+    fn civil_dates_match_the_gregorian_calendar() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(11_016), "2000-02-29");
+        assert_eq!(civil_date(20_742), "2026-10-16");
+    }
+
+    #[test]
+    fn summary_counts_days_modes_latency_and_cache_in_window() {
+        let today = 20_742;
+        let events = vec![
+            event(today - 30, "keyword", 500.0, None),
+            event(today - 1, "keyword", 10.0, Some(false)),
+            event(today - 1, "hybrid", 30.0, Some(true)),
+            event(today, "keyword", 20.0, Some(true)),
+        ];
+
+        let summary = summarize(&events, today * MS_PER_DAY + 5_000, 7);
+
+        assert_eq!(summary.queries, 3);
+        assert_eq!(
+            summary.per_day,
+            [
+                DayCount {
+                    date: "2026-10-15".to_string(),
+                    queries: 2
+                },
+                DayCount {
+                    date: "2026-10-16".to_string(),
+                    queries: 1
+                },
+            ]
+        );
+        assert_eq!(summary.modes[0].mode, "keyword");
+        assert_eq!(summary.modes[0].queries, 2);
+        let latency = summary.latency.expect("latency");
+        assert_eq!(latency.avg_ms, 20.0);
+        assert_eq!(latency.p50_ms, 20.0);
+        assert_eq!(latency.p95_ms, 30.0);
+        let cache = summary.cache.expect("cache");
+        assert_eq!((cache.lookups, cache.hits), (3, 2));
+    }
+}
@@ -20,11 +20,14 @@ pub struct IndexRoot {
 
 /// Find the nearest .cgrep index directory by walking up from the given path.
 /// Returns None if no .cgrep directory is found.
+///
+/// The walk stops at the root of a linked git worktree so a worktree never
+/// resolves to the index of the checkout it was created from.
 pub fn find_index_root(start: impl AsRef<Path>) -> Option<IndexRoot> {
     let mut current = start.as_ref().to_path_buf();
 
     // Canonicalize to handle relative paths
-    if let Ok(canonical) = current.canonicalize() {
+    if let Ok(canonical) = crate::paths::canonicalize(&current) {
         current = canonical;
     }
 
@@ -40,6 +43,9 @@ pub fn find_index_root(start: impl AsRef<Path>) -> Option<IndexRoot> {
             });
         }
 
+        if is_linked_worktree_root(&current) {
+            break;
+        }
         if !current.pop() {
             break;
         }
@@ -52,6 +58,61 @@ fn is_valid_index_dir(index_path: &Path) -> bool {
     index_path.is_dir() && index_path.join("meta.json").is_file()
 }
 
+/// Resolve the git directory of a checkout root: `.git` itself, or the target
+/// of the `gitdir:` line when `.git` is a file (worktrees, submodules).
+pub fn resolve_git_dir(checkout_root: &Path) -> Option<PathBuf> {
+    let dot_git = checkout_root.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = std::fs::read_to_string(&dot_git).ok()?;
+    let target = content
+        .lines()
+        .find_map(|line| line.strip_prefix("gitdir:"))?
+        .trim();
+    if target.is_empty() {
+        return None;
+    }
+    let target = Path::new(target);
+    Some(if target.is_absolute() {
+        target.to_path_buf()
+    } else {
+        checkout_root.join(target)
+    })
+}
+
+/// Whether `dir` is the root of a linked worktree (`git worktree add`).
+///
+/// Submodules also use a `.git` file, but their git dir lives under
+/// `modules/` rather than `worktrees/`, so they are not treated as boundaries.
+pub fn is_linked_worktree_root(dir: &Path) -> bool {
+    if !dir.join(".git").is_file() {
+        return false;
+    }
+    resolve_git_dir(dir)
+        .and_then(|git_dir| {
+            git_dir
+                .parent()
+                .and_then(|parent| parent.file_name())
+                .map(|name| name == "worktrees")
+        })
+        .unwrap_or(false)
+}
+
+/// Find the nearest enclosing git checkout root (a directory containing `.git`).
+pub fn find_checkout_root(start: impl AsRef<Path>) -> Option<PathBuf> {
+    let start = start.as_ref();
+    let mut current = crate::paths::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+    loop {
+        if current.join(".git").exists() {
+            return Some(current);
+        }
+        if !current.pop() {
+            return None;
+        }
+    }
+}
+
 /// Get the index path for the current directory, walking up to find parent indexes.
 /// Falls back to the given path if no index is found anywhere.
 pub fn get_index_path(path: impl AsRef<Path>) -> PathBuf {
@@ -132,6 +193,35 @@ mod tests {
         assert!(result.is_parent);
     }
 
+    #[test]
+    fn find_index_root_stops_at_linked_worktree_root() {
+        let dir = TempDir::new().unwrap();
+        let main_index = dir.path().join(INDEX_DIR);
+        fs::create_dir(&main_index).unwrap();
+        fs::write(main_index.join("meta.json"), "{}").unwrap();
+        fs::create_dir_all(dir.path().join(".git/worktrees/feature")).unwrap();
+
+        let worktree = dir.path().join("wt");
+        fs::create_dir_all(worktree.join("src")).unwrap();
+        let git_dir = dir.path().join(".git/worktrees/feature");
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", git_dir.display()),
+        )
+        .unwrap();
+
+        assert!(is_linked_worktree_root(&worktree));
+        assert!(!is_linked_worktree_root(dir.path()));
+        assert!(find_index_root(worktree.join("src")).is_none());
+
+        let submodule = dir.path().join("sub");
+        fs::create_dir_all(dir.path().join(".git/modules/sub")).unwrap();
+        fs::create_dir(&submodule).unwrap();
+        fs::write(submodule.join(".git"), "gitdir: ../.git/modules/sub\n").unwrap();
+        assert!(!is_linked_worktree_root(&submodule));
+        assert!(find_index_root(&submodule).unwrap().is_parent);
+    }
+
     #[test]
     fn find_index_root_returns_none_for_invalid_cgrep_dir() {
         let dir = TempDir::new().unwrap();
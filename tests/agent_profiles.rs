@@ -89,3 +89,44 @@ fn profile_agent_switches_default_output_to_json2() {
         .and_then(|m| m.get("schema_version"))
         .is_some());
 }
+
+#[test]
+fn env_profile_sets_format_and_default_flags_for_any_command() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join(".cgreprc.toml"),
+        "[profile.ci]\nformat = \"json\"\ncompact = true\n\n[profile.ci.args]\nsearch = [\"-m\", \"3\", \"--no-index\"]\n",
+    );
+    let content = (0..10)
+        .map(|i| format!("needle line {}", i + 1))
+        .collect::<Vec<_>>()
+        .join("\n");
+    write_file(&dir.path().join("sample.txt"), &content);
+    write_file(&dir.path().join("src/lib.rs"), "pub fn needle_fn() {}\n");
+
+    let run = |args: &[&str]| -> String {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .env("CGREP_PROFILE", "ci")
+            .args(args)
+            .assert()
+            .success();
+        String::from_utf8(assert.get_output().stdout.clone()).expect("utf8")
+    };
+
+    let stdout = run(&["search", "needle", "-g", "*.txt"]);
+    assert_eq!(stdout.trim().lines().count(), 1, "compact output");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    assert_eq!(json.as_array().expect("array").len(), 3);
+
+    // An explicit flag wins over the profile default.
+    let stdout = run(&["search", "needle", "-g", "*.txt", "-m", "5"]);
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    assert_eq!(json.as_array().expect("array").len(), 5);
+
+    let stdout = run(&["symbols", "needle_fn"]);
+    assert_eq!(stdout.trim().lines().count(), 1, "compact output");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    assert_eq!(json[0]["name"], "needle_fn");
+}
@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn api_json(dir: &Path) -> String {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir)
+        .args(["--format", "json", "api"])
+        .assert()
+        .success();
+    String::from_utf8(assert.get_output().stdout.clone()).expect("utf8")
+}
+
+#[test]
+fn api_lists_public_symbols_per_module() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub struct Client;\n\nimpl Client {\n    pub fn connect(addr: &str) -> Result<Self> { todo!() }\n    fn retry(&self) {}\n}\n\nfn helper() {}\n",
+    );
+    write_file(
+        &dir.path().join("web/index.ts"),
+        "export function render(node: Node): string { return ''; }\nfunction local() {}\n",
+    );
+
+    let stdout = api_json(dir.path());
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    let items: Vec<(&str, String)> = json
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|item| {
+            let name = match item["container"].as_str() {
+                Some(container) => format!("{}.{}", container, item["name"].as_str().unwrap()),
+                None => item["name"].as_str().unwrap().to_string(),
+            };
+            (item["module"].as_str().unwrap(), name)
+        })
+        .collect();
+    assert_eq!(
+        items,
+        [
+            ("src/lib.rs", "Client".to_string()),
+            ("src/lib.rs", "Client.connect".to_string()),
+            ("web/index.ts", "render".to_string()),
+        ]
+    );
+    assert_eq!(json[1]["signature"], "(&str) -> Result<Self>");
+}
+
+#[test]
+fn api_baseline_fails_on_changes() {
+    let dir = TempDir::new().expect("tempdir");
+    let lib = dir.path().join("src/lib.rs");
+    write_file(&lib, "pub fn load(path: &str) {}\npub fn save() {}\n");
+    write_file(&dir.path().join("api.json"), &api_json(dir.path()));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    cmd.current_dir(dir.path())
+        .args(["api", "--baseline", "api.json"])
+        .assert()
+        .success();
+
+    write_file(
+        &lib,
+        "pub fn load(path: &str, strict: bool) {}\npub fn open() {}\n",
+    );
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["--format", "json", "api", "--baseline", "api.json"])
+        .assert()
+        .failure();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let diff: Value = serde_json::from_str(&stdout).expect("json");
+    assert_eq!(diff["added"][0]["name"], "open");
+    assert_eq!(diff["removed"][0]["name"], "save");
+    assert_eq!(diff["changed"][0]["after"]["signature"], "(&str, bool)");
+}
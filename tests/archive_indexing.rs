@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_jar(path: &Path) {
+    let file = fs::File::create(path).expect("create jar");
+    let mut jar = zip::ZipWriter::new(file);
+    jar.start_file(
+        "com/acme/Vendor.java",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .expect("start entry");
+    jar.write_all(b"class Vendor { void vendoredMarker() {} }\n")
+        .expect("write entry");
+    jar.finish().expect("finish jar");
+}
+
+fn index(dir: &Path) {
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir)
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+}
+
+fn search_paths(dir: &Path, query: &str) -> Vec<String> {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir)
+        .args(["--format", "json2", "search", query])
+        .output()
+        .expect("run search");
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).expect("search json");
+    payload["results"]
+        .as_array()
+        .map(|results| {
+            results
+                .iter()
+                .filter_map(|result| result["path"].as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn archive_entries_are_indexed_only_when_enabled() {
+    let dir = TempDir::new().expect("tempdir");
+    let root = dir.path();
+    fs::create_dir_all(root.join("libs")).expect("mkdir libs");
+    fs::write(root.join("main.rs"), "fn main() {}\n").expect("write main");
+    write_jar(&root.join("libs/vendor.jar"));
+
+    index(root);
+    assert!(search_paths(root, "vendoredMarker").is_empty());
+
+    fs::write(
+        root.join(".cgreprc.toml"),
+        "[index.archives]\nenabled = true\n",
+    )
+    .expect("write config");
+    index(root);
+    let paths = search_paths(root, "vendoredMarker");
+    assert!(
+        paths.contains(&"libs/vendor.jar!/com/acme/Vendor.java".to_string()),
+        "unexpected paths: {paths:?}"
+    );
+
+    fs::remove_file(root.join("libs/vendor.jar")).expect("remove jar");
+    index(root);
+    assert!(search_paths(root, "vendoredMarker").is_empty());
+}
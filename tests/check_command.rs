@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const RULES: &str = r#"
+rules:
+  - id: no-eval
+    call: eval
+    severity: error
+    message: Do not call eval
+  - id: no-unwrap
+    literal: .unwrap()
+    type: rust
+    message: Prefer ? over unwrap
+"#;
+
+fn fixture() -> TempDir {
+    let dir = TempDir::new().expect("tempdir");
+    fs::create_dir_all(dir.path().join("src")).expect("mkdir");
+    fs::write(dir.path().join("rules.yaml"), RULES).expect("write rules");
+    fs::write(
+        dir.path().join("src/main.rs"),
+        "fn main() {\n    // eval(x) is banned\n    let v = opt.unwrap();\n}\n",
+    )
+    .expect("write main");
+    dir
+}
+
+fn check(root: &Path, format: &str) -> (bool, Value) {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(root)
+        .args(["--format", format, "check", "--rules", "rules.yaml"])
+        .output()
+        .expect("run check");
+    let payload = serde_json::from_slice(&output.stdout).expect("json");
+    (output.status.success(), payload)
+}
+
+#[test]
+fn warnings_pass_and_errors_fail() {
+    let dir = fixture();
+    let (ok, payload) = check(dir.path(), "json2");
+    assert!(ok, "warnings alone should not fail: {payload}");
+    assert_eq!(payload["meta"]["counts"]["warning"], 1);
+    assert_eq!(payload["meta"]["counts"]["error"], 0);
+    let results = payload["results"].as_array().expect("results");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["rule"], "no-unwrap");
+    assert_eq!(results[0]["path"], "src/main.rs");
+    assert_eq!(results[0]["line"], 3);
+
+    fs::write(
+        dir.path().join("src/lib.rs"),
+        "pub fn run() {\n    eval(1);\n}\n",
+    )
+    .expect("write lib");
+    let (ok, payload) = check(dir.path(), "json2");
+    assert!(!ok);
+    assert_eq!(payload["meta"]["counts"]["error"], 1);
+}
+
+#[test]
+fn sarif_output_lists_rules_and_results() {
+    let dir = fixture();
+    let (_, sarif) = check(dir.path(), "sarif");
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(
+        run["tool"]["driver"]["rules"].as_array().map(Vec::len),
+        Some(2)
+    );
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "no-unwrap");
+    assert_eq!(result["level"], "warning");
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "src/main.rs"
+    );
+}
+
+#[test]
+fn invalid_rules_are_rejected() {
+    let dir = fixture();
+    fs::write(
+        dir.path().join("rules.yaml"),
+        "rules:\n  - id: both\n    literal: a\n    regex: b\n    message: m\n",
+    )
+    .expect("write rules");
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["check", "--rules", "rules.yaml"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("exactly one of"));
+}
+
+fn git(root: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .current_dir(root)
+        .args(args)
+        .status()
+        .expect("run git");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn staged_check_and_precommit_hook() {
+    let dir = fixture();
+    git(dir.path(), &["init", "-q"]);
+    git(dir.path(), &["config", "user.email", "test@example.com"]);
+    git(dir.path(), &["config", "user.name", "test"]);
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-qm", "initial"]);
+
+    fs::write(dir.path().join("src/staged.rs"), "fn a() { eval(1); }\n").expect("write");
+    fs::write(
+        dir.path().join("src/unstaged.rs"),
+        "fn b() { x.unwrap(); }\n",
+    )
+    .expect("write");
+    git(dir.path(), &["add", "src/staged.rs"]);
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["--format", "json2", "check", "-r", "rules.yaml", "--staged"])
+        .output()
+        .expect("run check");
+    assert!(!output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(payload["meta"]["changed_rev"], "staged");
+    let paths: Vec<&str> = payload["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .filter_map(|r| r["path"].as_str())
+        .collect();
+    assert_eq!(paths, vec!["src/staged.rs"]);
+
+    let install = || {
+        Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+            .current_dir(dir.path())
+            .arg("install-precommit")
+            .assert()
+            .success();
+    };
+    install();
+    install();
+    let hook = fs::read_to_string(dir.path().join(".git/hooks/pre-commit")).expect("hook");
+    assert!(hook.starts_with("#!/bin/sh\n"));
+    assert!(hook.contains("cgrep check --rules 'rules.yaml' --changed --staged"));
+
+    fs::write(
+        dir.path().join(".git/hooks/pre-commit"),
+        "#!/bin/sh\nexit 0\n",
+    )
+    .expect("write");
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .arg("install-precommit")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn staged_check_reads_the_staged_version() {
+    let dir = fixture();
+    git(dir.path(), &["init", "-q"]);
+    git(dir.path(), &["config", "user.email", "test@example.com"]);
+    git(dir.path(), &["config", "user.name", "test"]);
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-qm", "initial"]);
+
+    let staged_check = || {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+            .current_dir(dir.path())
+            .args(["--format", "json2", "check", "-r", "rules.yaml", "--staged"])
+            .output()
+            .expect("run check");
+        let payload: Value = serde_json::from_slice(&output.stdout).expect("json");
+        (output.status.success(), payload)
+    };
+
+    // A violation staged for commit fails even when the working tree fixed it.
+    fs::write(dir.path().join("src/lib.rs"), "fn a() { eval(1); }\n").expect("write");
+    git(dir.path(), &["add", "src/lib.rs"]);
+    fs::write(dir.path().join("src/lib.rs"), "fn a() {}\n").expect("write");
+    let (ok, payload) = staged_check();
+    assert!(!ok, "staged violation should fail: {payload}");
+    assert_eq!(payload["results"][0]["rule"], "no-eval");
+
+    // An unstaged violation does not fail a clean staged version.
+    git(dir.path(), &["add", "src/lib.rs"]);
+    fs::write(dir.path().join("src/lib.rs"), "fn a() { eval(1); }\n").expect("write");
+    let (ok, payload) = staged_check();
+    assert!(ok, "unstaged violation should not fail: {payload}");
+    assert_eq!(payload["meta"]["counts"]["error"], 0);
+}
+
+#[test]
+fn boundaries_report_imports_that_cross_disallowed_layers() {
+    let dir = TempDir::new().expect("tempdir");
+    let root = dir.path();
+    fs::write(
+        root.join("boundaries.toml"),
+        "[[layer]]\nname = \"domain\"\npaths = [\"app/domain\"]\n\n[[layer]]\nname = \"web\"\npaths = [\"app/web\"]\nallow = [\"domain\"]\n\n[[layer]]\nname = \"scripts\"\npaths = [\"scripts\"]\nseverity = \"warning\"\n",
+    )
+    .expect("write rules");
+    for (path, content) in [
+        (
+            "app/domain/models.py",
+            "from ..web import views\nimport os\n",
+        ),
+        ("app/domain/__init__.py", ""),
+        ("app/web/views.py", "from app.domain import models\n"),
+        ("app/web/__init__.py", ""),
+        ("app/__init__.py", ""),
+    ] {
+        let full = root.join(path);
+        fs::create_dir_all(full.parent().expect("parent")).expect("mkdir");
+        fs::write(full, content).expect("write");
+    }
+
+    let run = || {
+        Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+            .current_dir(root)
+            .args(["--format", "json", "boundaries"])
+            .output()
+            .expect("run boundaries")
+    };
+    let output = run();
+    assert!(!output.status.success(), "errors must fail the command");
+    let violations: Value = serde_json::from_slice(&output.stdout).expect("json");
+    let violations = violations.as_array().expect("array");
+    assert_eq!(violations.len(), 1, "{violations:?}");
+    assert_eq!(violations[0]["path"], "app/domain/models.py");
+    assert_eq!(violations[0]["line"], 1);
+    assert_eq!(violations[0]["layer"], "domain");
+    assert_eq!(violations[0]["imported_layer"], "web");
+    assert_eq!(violations[0]["imported_path"], "app/web/__init__.py");
+    assert_eq!(violations[0]["code"], "from ..web import views");
+
+    // Warning-level layers report without failing.
+    fs::write(root.join("app/domain/models.py"), "import os\n").expect("write");
+    fs::create_dir_all(root.join("scripts")).expect("mkdir");
+    fs::write(root.join("scripts/seed.py"), "from app.web import views\n").expect("write");
+    let output = run();
+    assert!(output.status.success());
+    let violations: Value = serde_json::from_slice(&output.stdout).expect("json");
+    assert_eq!(violations[0]["severity"], "warning");
+    assert_eq!(violations[0]["path"], "scripts/seed.py");
+}
+
+fn audit(root: &Path, args: &[&str]) -> (bool, Value) {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(root)
+        .args(args)
+        .output()
+        .expect("run audit");
+    let payload = serde_json::from_slice(&output.stdout).expect("json");
+    (output.status.success(), payload)
+}
+
+#[test]
+fn audit_presets_report_risky_constructs_with_sarif() {
+    let dir = TempDir::new().expect("tempdir");
+    fs::create_dir_all(dir.path().join("src")).expect("mkdir");
+    fs::write(
+        dir.path().join("src/lib.rs"),
+        "// unsafe { }\npub fn read(p: *const u8) -> u8 {\n    unsafe { *p }\n}\n",
+    )
+    .expect("write lib");
+    fs::write(
+        dir.path().join("src/db.py"),
+        "def find(cur, name):\n    cur.execute(\"SELECT * FROM users WHERE name = '\" + name + \"'\")\n",
+    )
+    .expect("write db");
+
+    let (ok, payload) = audit(dir.path(), &["--format", "json2", "audit"]);
+    assert!(!ok, "sql concatenation is an error");
+    let schema = cgrep::schema::schema("audit").expect("embedded schema");
+    let errors = cgrep::schema::validate(&schema, &payload);
+    assert!(errors.is_empty(), "schema errors: {errors:?}\n{payload}");
+    let found: Vec<(&str, &str, u64)> = payload["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .map(|result| {
+            (
+                result["rule"].as_str().unwrap(),
+                result["path"].as_str().unwrap(),
+                result["line"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("sql-string-concat", "src/db.py", 2),
+            ("rust-unsafe", "src/lib.rs", 3),
+        ]
+    );
+
+    let (ok, sarif) = audit(
+        dir.path(),
+        &["--format", "sarif", "audit", "--preset", "rust"],
+    );
+    assert!(ok, "warnings alone pass");
+    let run = &sarif["runs"][0];
+    let rule_ids: Vec<&str> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .expect("rules")
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(rule_ids, ["rust-unsafe", "rust-transmute"]);
+    assert_eq!(run["results"][0]["ruleId"], "rust-unsafe");
+    assert_eq!(run["results"][0]["level"], "warning");
+}
+
+#[test]
+fn staged_check_narrows_with_a_warm_index() {
+    let dir = fixture();
+    git(dir.path(), &["init", "-q"]);
+    git(dir.path(), &["config", "user.email", "test@example.com"]);
+    git(dir.path(), &["config", "user.name", "test"]);
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-qm", "initial"]);
+
+    // Staged as on disk: the index speaks for these.
+    fs::write(dir.path().join("src/clean.rs"), "fn c() {}\n").expect("write");
+    fs::write(dir.path().join("src/bad.rs"), "fn b() { eval(1); }\n").expect("write");
+    // Staged with a violation the working tree no longer has.
+    fs::write(dir.path().join("src/edited.rs"), "fn e() { eval(2); }\n").expect("write");
+    git(dir.path(), &["add", "src"]);
+    fs::write(dir.path().join("src/edited.rs"), "fn e() {}\n").expect("write");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .arg("index")
+        .assert()
+        .success();
+    // A live pid stands in for the watch daemon.
+    fs::write(
+        dir.path().join(".cgrep/watch.pid"),
+        std::process::id().to_string(),
+    )
+    .expect("write pid");
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["--format", "json2", "check", "-r", "rules.yaml", "--staged"])
+        .output()
+        .expect("run check");
+    assert!(!output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).expect("json");
+    let paths: Vec<&str> = payload["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .filter_map(|r| r["path"].as_str())
+        .collect();
+    assert_eq!(paths, vec!["src/bad.rs", "src/edited.rs"]);
+    // src/clean.rs matches no rule term in the index, so it is never read.
+    assert_eq!(payload["meta"]["files_checked"], 2);
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+fn search(dir: &std::path::Path, format: &str) -> String {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir)
+        .args(["--format", format, "search", "TODO", "--no-index"])
+        .output()
+        .expect("run search");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).expect("utf8")
+}
+
+#[test]
+fn github_format_emits_workflow_commands() {
+    let dir = TempDir::new().expect("tempdir");
+    fs::write(
+        dir.path().join("lib.rs"),
+        "fn a() {}\n// TODO: remove, soon\n",
+    )
+    .expect("write");
+
+    let stdout = search(dir.path(), "github");
+    assert_eq!(
+        stdout.trim_end(),
+        "::warning file=lib.rs,line=2,title=cgrep%3A TODO::// TODO: remove, soon"
+    );
+}
+
+#[test]
+fn gitlab_format_emits_code_quality_report() {
+    let dir = TempDir::new().expect("tempdir");
+    fs::write(dir.path().join("lib.rs"), "// TODO: remove\n").expect("write");
+
+    let report: Value = serde_json::from_str(&search(dir.path(), "gitlab")).expect("json");
+    let issues = report.as_array().expect("array");
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0]["location"]["path"], "lib.rs");
+    assert_eq!(issues[0]["location"]["lines"]["begin"], 1);
+    assert_eq!(issues[0]["description"], "// TODO: remove");
+    assert!(issues[0]["fingerprint"]
+        .as_str()
+        .is_some_and(|f| !f.is_empty()));
+}
+
+#[test]
+fn quickfix_format_emits_file_line_column_lines() {
+    let dir = TempDir::new().expect("tempdir");
+    fs::write(
+        dir.path().join("lib.rs"),
+        "fn a() {}\n    let x = 1; // TODO: remove\n",
+    )
+    .expect("write");
+
+    let stdout = search(dir.path(), "quickfix");
+    assert_eq!(stdout.trim_end(), "lib.rs:2:19: let x = 1; // TODO: remove");
+}
+
+#[test]
+fn json_formats_report_match_columns_in_scan_and_index_modes() {
+    let dir = TempDir::new().expect("tempdir");
+    fs::write(
+        dir.path().join("lib.rs"),
+        "fn a() {}\n    let x = 1; // TODO: remove\n",
+    )
+    .expect("write");
+    let run = |args: &[&str]| -> Value {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+            .current_dir(dir.path())
+            .args(args)
+            .output()
+            .expect("run search");
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).expect("json")
+    };
+
+    let scan = run(&["--format", "json2", "search", "todo", "--no-index"]);
+    assert_eq!(scan["results"][0]["line"], 2);
+    assert_eq!(scan["results"][0]["column"], 19);
+    assert_eq!(scan["results"][0]["end_column"], 23);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+    let indexed = run(&["--format", "json", "search", "TODO"]);
+    assert_eq!(indexed[0]["column"], 19);
+    assert_eq!(indexed[0]["end_column"], 23);
+
+    let regex = run(&["--format", "json2", "search", "x = \\d", "--regex"]);
+    assert_eq!(regex["results"][0]["column"], 9);
+    assert_eq!(regex["results"][0]["end_column"], 14);
+}
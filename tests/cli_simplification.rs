@@ -513,6 +513,7 @@ fn indexed_search_falls_back_to_scan_for_dash_only_literal_query() {
     let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
     let json: Value = serde_json::from_str(&stdout).expect("json");
     assert_eq!(json["meta"]["index_mode"], "scan");
+    assert_eq!(json["meta"]["fallback_reason"], "literal_query");
     let results = json["results"].as_array().expect("results");
     assert!(!results.is_empty());
     assert!(results.iter().any(|r| {
@@ -527,6 +528,43 @@ fn indexed_search_falls_back_to_scan_for_dash_only_literal_query() {
     }));
 }
 
+#[test]
+fn scan_fallback_reports_reason_and_no_fallback_fails() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(&dir.path().join("sample.txt"), "needle\n");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+        .args(["--format", "json2", "search", "needle"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Falling back to scan mode"));
+    let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(json["meta"]["index_mode"], "scan");
+    assert_eq!(json["meta"]["fallback_reason"], "index_missing");
+
+    // Scanning on request is not a fallback.
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["--format", "json2", "search", "needle", "--no-index"])
+        .assert()
+        .success();
+    let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert!(json["meta"].get("fallback_reason").is_none());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    cmd.current_dir(dir.path())
+        .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+        .args(["search", "needle", "--no-fallback"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Index not found"))
+        .stderr(predicate::str::contains("cgrep index"));
+}
+
 #[test]
 fn indexed_search_treats_colon_query_as_literal() {
     let dir = TempDir::new().expect("tempdir");
@@ -1017,3 +1055,74 @@ fn search_json2_does_not_persist_agent_hints() {
         .join("agent_expand_hints.json");
     assert!(!hint_path.exists());
 }
+
+#[test]
+fn selftest_reports_every_check_with_its_seed() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .args([
+            "--format", "json", "selftest", "--cases", "25", "--seed", "42",
+        ])
+        .assert()
+        .success();
+    let report: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(report["seed"], 42);
+    let checks = report["checks"].as_array().expect("checks");
+    let names: Vec<&str> = checks
+        .iter()
+        .map(|check| check["name"].as_str().expect("name"))
+        .collect();
+    assert_eq!(
+        names,
+        ["query_phrase", "section_range", "glob", "embedded_schemas"]
+    );
+    assert!(checks
+        .iter()
+        .all(|check| check["failures"].as_array().is_some_and(Vec::is_empty)));
+}
+
+#[test]
+fn plan_only_reports_routing_without_searching() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(&dir.path().join("src/lib.rs"), "pub fn needle() {}\n");
+
+    let plan = |args: &[&str]| -> Value {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+            .args(["--format", "json", "search", "--plan-only"])
+            .args(args)
+            .assert()
+            .success();
+        serde_json::from_slice(&assert.get_output().stdout).expect("json")
+    };
+
+    let json = plan(&["needle", "--no-index", "-t", "rust"]);
+    assert_eq!(json["index"]["requested"], "scan");
+    assert_eq!(json["index"]["scan_requested_by"], "no_index");
+    assert_eq!(json["filters"]["file_type"], "rust");
+    assert_eq!(json["hybrid_fallback"]["eligible"], false);
+    assert!(json["fetch"].get("index_candidates").is_none());
+
+    let json = plan(&["needle"]);
+    assert_eq!(json["index"]["effective"], "scan");
+    assert_eq!(json["index"]["fallback_reason"], "index_missing");
+    assert!(!dir.path().join(".cgrep").exists());
+
+    let mut index_cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    index_cmd
+        .current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+
+    let json = plan(&["needle(x", "--diversify"]);
+    assert_eq!(json["index"]["effective"], "index");
+    assert_eq!(json["query_handling"]["literal"], true);
+    assert_eq!(json["query_handling"]["parser_query"], "\"needle(x\"");
+    assert_eq!(json["hybrid_fallback"]["eligible"], true);
+    assert_eq!(json["fetch"]["max_results"], 20);
+    assert_eq!(json["fetch"]["candidate_pool"], 80);
+    assert_eq!(json["fetch"]["index_top_docs"], 400);
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+#[test]
+fn const_matches_numeric_literals_by_value_only() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/cache.rs"),
+        "// entries expire after 86400 seconds\npub const TTL_SECS: u64 = 86_400;\n\nfn label_86400() -> &'static str {\n    \"86400\"\n}\n",
+    );
+    write_file(
+        &dir.path().join("web/session.js"),
+        "const maxAge = 0x15180;\nconst retries = 3;\n",
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["--format", "json", "const", "86400"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    let results = json.as_array().expect("array");
+    assert_eq!(results.len(), 2, "{stdout}");
+    assert_eq!(results[0]["path"], "src/cache.rs");
+    assert_eq!(results[0]["line"], 2);
+    assert_eq!(results[0]["literal"], "86_400");
+    assert_eq!(results[0]["constant"], "TTL_SECS");
+    assert_eq!(results[1]["path"], "web/session.js");
+    assert_eq!(results[1]["literal"], "0x15180");
+    assert_eq!(results[1]["constant"], "maxAge");
+
+    let mut bad = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    bad.current_dir(dir.path())
+        .args(["const", "day"])
+        .assert()
+        .failure();
+}
@@ -294,3 +294,53 @@ struct TORCH_API TensorIterator final : public TensorIteratorBase {\n\
         "expected struct definition in macro-annotated C++ header, got: {results:?}"
     );
 }
+
+#[test]
+fn definition_finds_dotted_config_keys() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("config/app.yaml"),
+        "server:\n  http:\n    port: 8080\n",
+    );
+    write_file(
+        &dir.path().join("Cargo.toml"),
+        "[package]\nname = \"demo\"\n",
+    );
+    write_file(
+        &dir.path().join("src/main.py"),
+        "def port(cfg):\n    return cfg.get(\"server.http.port\")\n",
+    );
+
+    let mut index_cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    index_cmd
+        .current_dir(dir.path())
+        .args(["index"])
+        .assert()
+        .success();
+
+    let run = |args: &[&str]| -> Vec<Value> {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .args(["--format", "json", "--compact"])
+            .args(args)
+            .assert()
+            .success();
+        serde_json::from_slice(&assert.get_output().stdout).expect("json")
+    };
+
+    let results = run(&["definition", "server.http.port"]);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["kind"], "key");
+    assert_eq!(results[0]["path"], "config/app.yaml");
+    assert_eq!(results[0]["line"], 3);
+
+    let results = run(&["definition", "package.name"]);
+    assert_eq!(results[0]["path"], "Cargo.toml");
+    assert_eq!(results[0]["line"], 2);
+
+    let references = run(&["references", "server.http.port"]);
+    assert!(references
+        .iter()
+        .any(|r| r["path"] == "src/main.py" && r["line"] == 2));
+}
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn search(dir: &Path, args: &[&str]) -> Vec<Value> {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir)
+        .args(["--format", "json2", "search"])
+        .args(args)
+        .output()
+        .expect("run search");
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).expect("search json");
+    payload["results"].as_array().cloned().unwrap_or_default()
+}
+
+#[test]
+fn type_filter_finds_sql_embedded_in_python() {
+    let dir = TempDir::new().expect("tempdir");
+    let root = dir.path();
+    fs::write(
+        root.join("repo.py"),
+        "def active(db):\n    return db.execute(\n        \"SELECT id FROM ledger_entries WHERE active = 1\"\n    )\n",
+    )
+    .expect("write repo.py");
+    fs::write(
+        root.join("notes.md"),
+        "ledger_entries are archived monthly\n",
+    )
+    .expect("write notes");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(root)
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+
+    let sql = search(root, &["ledger_entries", "--type", "sql"]);
+    assert_eq!(sql.len(), 1, "unexpected results: {sql:?}");
+    assert_eq!(sql[0]["path"], "repo.py");
+    assert_eq!(sql[0]["line"], 3);
+
+    let python = search(root, &["ledger_entries", "--type", "python"]);
+    assert_eq!(python.len(), 1, "regions should not duplicate host hits");
+}
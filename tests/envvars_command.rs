@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn envvars_json(dir: &Path, args: &[&str]) -> Value {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir)
+        .args(["--format", "json", "envvars"])
+        .args(args)
+        .assert()
+        .success();
+    serde_json::from_slice(&assert.get_output().stdout).expect("json")
+}
+
+#[test]
+fn envvars_group_reads_by_name_with_defaults() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/config.rs"),
+        "// DATABASE_URL is documented in the README\npub fn database_url() -> String {\n    std::env::var(\"DATABASE_URL\").unwrap_or(\"postgres://localhost\".to_string())\n}\n",
+    );
+    write_file(
+        &dir.path().join("worker/main.py"),
+        "import os\n\nURL = os.environ[\"DATABASE_URL\"]\nLOG = os.getenv(\"LOG_LEVEL\", \"info\")\n",
+    );
+
+    let json = envvars_json(dir.path(), &[]);
+    let vars = json.as_array().expect("array");
+    let names: Vec<&str> = vars
+        .iter()
+        .map(|var| var["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["DATABASE_URL", "LOG_LEVEL"]);
+
+    let database = &vars[0];
+    let reads: Vec<String> = database["reads"]
+        .as_array()
+        .expect("reads")
+        .iter()
+        .map(|read| format!("{}:{}", read["path"].as_str().unwrap(), read["line"]))
+        .collect();
+    assert_eq!(reads, ["src/config.rs:3", "worker/main.py:3"]);
+    assert_eq!(
+        database["defaults"],
+        serde_json::json!(["postgres://localhost"])
+    );
+    assert_eq!(vars[1]["reads"][0]["default"], "info");
+
+    let filtered = envvars_json(dir.path(), &["log"]);
+    assert_eq!(filtered.as_array().expect("array").len(), 1);
+    assert_eq!(filtered[0]["name"], "LOG_LEVEL");
+}
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn flags_json(dir: &Path, args: &[&str]) -> Value {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir)
+        .args(["--format", "json", "flags"])
+        .args(args)
+        .assert()
+        .success();
+    serde_json::from_slice(&assert.get_output().stdout).expect("json")
+}
+
+fn summary(json: &Value) -> Vec<String> {
+    json.as_array()
+        .expect("array")
+        .iter()
+        .map(|flag| {
+            let uses: Vec<String> = flag["uses"]
+                .as_array()
+                .expect("uses")
+                .iter()
+                .map(|flag_use| {
+                    format!(
+                        "{}:{}",
+                        flag_use["path"].as_str().unwrap(),
+                        flag_use["line"]
+                    )
+                })
+                .collect();
+            format!("{} {}", flag["flag"].as_str().unwrap(), uses.join(","))
+        })
+        .collect()
+}
+
+#[test]
+fn flags_inventory_builtin_and_configured_detectors() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join(".cgreprc.toml"),
+        "[flags.patterns]\nhelper = 'isFeatureOn\\(\"(?P<flag>[^\"]+)\"\\)'\n",
+    );
+    write_file(
+        &dir.path().join("web/checkout.ts"),
+        "// ld.variation('retired-flag', user, false)\nif (ld.variation('new-checkout', user, false)) {}\nif (isFeatureOn(\"fast-cart\")) {}\nready(); // ld.variation('trailing-flag', user, false)\n/*\n  ld.variation('block-flag', user, false)\n*/\n",
+    );
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "#[cfg(feature = \"otel\")]\nmod telemetry;\n\nfn on() -> bool {\n    cfg!(feature = \"otel\")\n}\n",
+    );
+
+    assert_eq!(
+        summary(&flags_json(dir.path(), &[])),
+        [
+            "fast-cart web/checkout.ts:3",
+            "new-checkout web/checkout.ts:2",
+            "otel src/lib.rs:1,src/lib.rs:5",
+        ]
+    );
+    assert_eq!(
+        summary(&flags_json(dir.path(), &["--pattern", "helper"])),
+        ["fast-cart web/checkout.ts:3"]
+    );
+    assert_eq!(
+        summary(&flags_json(dir.path(), &["otel"])),
+        ["otel src/lib.rs:1,src/lib.rs:5"]
+    );
+}
+
+#[test]
+fn flags_reject_an_invalid_pattern() {
+    let dir = TempDir::new().expect("tempdir");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    cmd.current_dir(dir.path())
+        .args(["flags", "--pattern", "isOn("])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("names no flag detector"));
+}
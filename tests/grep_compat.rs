@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+fn write_file(path: &std::path::Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn fixture() -> TempDir {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "fn parse_config() {}\n// Config loader\nfn reparse() {}\n",
+    );
+    write_file(&dir.path().join("src/app.py"), "def parse():\n    pass\n");
+    write_file(&dir.path().join("notes.md"), "parse later\n");
+    write_file(&dir.path().join(".ignore"), "ignored.rs\n");
+    write_file(&dir.path().join("ignored.rs"), "fn parse() {}\n");
+    dir
+}
+
+fn grep(dir: &TempDir, args: &[&str]) -> (Option<i32>, String) {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let output = cmd
+        .current_dir(dir.path())
+        .arg("grep")
+        .args(args)
+        .output()
+        .expect("run cgrep grep");
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).expect("utf8"),
+    )
+}
+
+#[test]
+fn grep_prints_ripgrep_style_lines_and_respects_ignore_files() {
+    let dir = fixture();
+    let (code, stdout) = grep(&dir, &["-n", "-w", "parse"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        stdout,
+        "notes.md:1:parse later\nsrc/app.py:1:def parse():\n"
+    );
+}
+
+#[test]
+fn grep_type_glob_count_and_list_flags() {
+    let dir = fixture();
+    let (_, stdout) = grep(&dir, &["-c", "-t", "rust", "-i", "config"]);
+    assert_eq!(stdout, "src/lib.rs:2\n");
+
+    let (_, stdout) = grep(&dir, &["-l", "-g", "!*.md", "parse"]);
+    assert_eq!(stdout, "src/app.py\nsrc/lib.rs\n");
+
+    let (_, stdout) = grep(&dir, &["-N", "-A", "1", "parse_config", "src/lib.rs"]);
+    assert_eq!(stdout, "fn parse_config() {}\n// Config loader\n");
+}
+
+#[test]
+fn grep_exit_status_matches_ripgrep() {
+    let dir = fixture();
+    let (code, stdout) = grep(&dir, &["no_such_token"]);
+    assert_eq!(code, Some(1));
+    assert!(stdout.is_empty());
+
+    let (code, _) = grep(&dir, &["(unclosed"]);
+    assert_eq!(code, Some(2));
+}
@@ -0,0 +1,311 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn imports(root: &Path, file: &str) -> Value {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(root)
+        .args(["--format", "json", "imports", file])
+        .assert()
+        .success();
+    serde_json::from_slice(&assert.get_output().stdout).expect("json")
+}
+
+fn field(items: &Value, key: &str) -> Vec<String> {
+    items
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|item| item[key].as_str().expect("string").to_string())
+        .collect()
+}
+
+#[test]
+fn imports_separates_workspace_files_from_packages() {
+    let dir = TempDir::new().expect("tempdir");
+    let root = dir.path();
+    write_file(&root.join("Cargo.toml"), "[package]\nname = \"app\"\n");
+    write_file(
+        &root.join("src/main.rs"),
+        "mod query;\nmod util;\n\nuse crate::query::{\n    search::run,\n    Query,\n};\nuse serde::Serialize;\nuse std::fs;\n\nfn main() {}\n",
+    );
+    write_file(
+        &root.join("src/query/mod.rs"),
+        "pub mod search;\npub struct Query;\n",
+    );
+    write_file(
+        &root.join("src/query/search.rs"),
+        "use super::Query;\npub fn run() {}\n",
+    );
+    write_file(&root.join("src/util.rs"), "");
+
+    let report = imports(root, "src/main.rs");
+    assert_eq!(report["file"], "src/main.rs");
+    assert_eq!(
+        field(&report["internal"], "path"),
+        ["src/query/mod.rs", "src/util.rs", "src/query/search.rs"]
+    );
+    assert_eq!(field(&report["external"], "package"), ["serde", "std"]);
+    assert!(report.get("unresolved").is_none());
+
+    let report = imports(root, "src/query/search.rs");
+    assert_eq!(field(&report["internal"], "path"), ["src/query/mod.rs"]);
+
+    write_file(
+        &root.join("web/app.ts"),
+        "import { api } from './api';\nimport React from 'react';\nimport { z } from '@scope/zod/v4';\nimport { gone } from './missing';\n",
+    );
+    write_file(&root.join("web/api/index.ts"), "export const api = 1;\n");
+    let report = imports(root, "web/app.ts");
+    assert_eq!(field(&report["internal"], "path"), ["web/api/index.ts"]);
+    assert_eq!(
+        field(&report["external"], "package"),
+        ["react", "@scope/zod"]
+    );
+    assert_eq!(field(&report["unresolved"], "specifier"), ["./missing"]);
+
+    write_file(
+        &root.join("pkg/service.py"),
+        "import os\nfrom .models import User\nfrom pkg.store import save\n",
+    );
+    write_file(&root.join("pkg/models.py"), "class User: ...\n");
+    write_file(&root.join("pkg/store/__init__.py"), "def save(): ...\n");
+    let report = imports(root, "pkg/service.py");
+    assert_eq!(
+        field(&report["internal"], "path"),
+        ["pkg/models.py", "pkg/store/__init__.py"]
+    );
+    assert_eq!(field(&report["external"], "package"), ["os"]);
+    assert_eq!(report["internal"][0]["line"], 2);
+}
+
+#[test]
+fn dependents_and_imports_share_import_extraction() {
+    let dir = TempDir::new().expect("tempdir");
+    let root = dir.path();
+    write_file(
+        &root.join("src/lib.rs"),
+        "pub mod helpers;\nuse crate::{\n    helpers::trim,\n};\n",
+    );
+    write_file(&root.join("src/helpers.rs"), "pub fn trim() {}\n");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(root)
+        .args(["--format", "json", "dependents", "src/helpers.rs"])
+        .assert()
+        .success();
+    let dependents: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    let lines: Vec<u64> = dependents
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|item| item["line"].as_u64().expect("line"))
+        .collect();
+    assert_eq!(lines, [1, 2]);
+
+    let report = imports(root, "src/lib.rs");
+    assert_eq!(field(&report["internal"], "path"), ["src/helpers.rs"]);
+}
+
+#[test]
+fn transitive_dependents_report_chains_and_cycles() {
+    let dir = TempDir::new().expect("tempdir");
+    let root = dir.path();
+    // alpha <- beta <- gamma <- delta, and alpha imports gamma back.
+    write_file(
+        &root.join("web/alpha.ts"),
+        "import { g } from './gamma';\nexport const a = 1;\n",
+    );
+    write_file(
+        &root.join("web/beta.ts"),
+        "import { a } from './alpha';\nexport const b = a;\n",
+    );
+    write_file(
+        &root.join("web/gamma.ts"),
+        "import { b } from './beta';\nexport const g = b;\n",
+    );
+    write_file(&root.join("web/delta.ts"), "import { g } from './gamma';\n");
+
+    let run = |args: &[&str]| -> Value {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(root)
+            .args(["--format", "json", "dependents", "web/alpha.ts"])
+            .args(args)
+            .assert()
+            .success();
+        serde_json::from_slice(&assert.get_output().stdout).expect("json")
+    };
+
+    let report = run(&["--transitive"]);
+    assert_eq!(report["target"], "web/alpha.ts");
+    let chains: Vec<Vec<String>> = report["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .map(|result| {
+            result["chain"]
+                .as_array()
+                .expect("chain")
+                .iter()
+                .map(|path| path.as_str().expect("path").to_string())
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        chains,
+        [
+            vec!["web/beta.ts", "web/alpha.ts"],
+            vec!["web/gamma.ts", "web/beta.ts", "web/alpha.ts"],
+            vec![
+                "web/delta.ts",
+                "web/gamma.ts",
+                "web/beta.ts",
+                "web/alpha.ts"
+            ],
+        ]
+    );
+    assert_eq!(report["results"][2]["depth"], 3);
+    assert_eq!(
+        report["cycles"],
+        serde_json::json!([[
+            "web/alpha.ts",
+            "web/gamma.ts",
+            "web/beta.ts",
+            "web/alpha.ts"
+        ]])
+    );
+
+    let shallow = run(&["--transitive", "--depth", "2"]);
+    assert_eq!(shallow["results"].as_array().expect("results").len(), 2);
+    assert_eq!(shallow["cycles"], serde_json::json!([]));
+
+    // Without --transitive the output keeps its flat shape.
+    let direct = run(&[]);
+    assert_eq!(direct.as_array().expect("array").len(), 1);
+    assert!(direct[0].get("chain").is_none());
+}
+
+#[test]
+fn packages_counts_external_imports_across_files() {
+    let dir = TempDir::new().expect("tempdir");
+    let root = dir.path();
+    write_file(
+        &root.join("web/app.ts"),
+        "import _ from 'lodash';\nimport { get } from 'lodash';\nimport fs from 'node:fs';\nimport { api } from './api';\n",
+    );
+    write_file(
+        &root.join("web/api.ts"),
+        "import debounce from 'lodash';\nimport { z } from '@acme/schema/v2';\nexport const api = 1;\n",
+    );
+    write_file(&root.join("tools/run.py"), "import requests\n");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(root)
+        .args(["--format", "json", "packages", "--examples", "1"])
+        .assert()
+        .success();
+    let packages: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(
+        field(&packages, "package"),
+        vec!["lodash", "@acme/schema", "requests"]
+    );
+    assert_eq!(packages[0]["ecosystem"], "npm");
+    assert_eq!(packages[0]["files"], 2);
+    assert_eq!(packages[0]["imports"], 3);
+    assert_eq!(packages[0]["examples"].as_array().map(Vec::len), Some(1));
+    assert_eq!(packages[2]["ecosystem"], "pypi");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(root)
+        .args(["--format", "json", "packages", "lodash"])
+        .assert()
+        .success();
+    let packages: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(
+        field(&packages[0]["examples"], "path"),
+        vec!["web/api.ts", "web/app.ts", "web/app.ts"]
+    );
+}
+
+#[test]
+fn deps_external_lists_manifest_dependencies_with_locked_versions() {
+    let dir = TempDir::new().expect("tempdir");
+    let root = dir.path();
+    write_file(&root.join(".gitignore"), "Cargo.lock\n");
+    write_file(
+        &root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crates/core\"]\n\n[workspace.dependencies]\nserde = \"1\"\n",
+    );
+    write_file(
+        &root.join("crates/core/Cargo.toml"),
+        "[package]\nname = \"core\"\n\n[dependencies]\nserde.workspace = true\nutil = { path = \"../util\" }\n",
+    );
+    write_file(
+        &root.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"serde\"\nversion = \"1.0.210\"\n",
+    );
+    write_file(
+        &root.join("web/package.json"),
+        "{\n  \"devDependencies\": {\n    \"vitest\": \"^1.6.0\"\n  }\n}\n",
+    );
+    write_file(
+        &root.join("web/package-lock.json"),
+        "{\"lockfileVersion\": 3, \"packages\": {\"node_modules/vitest\": {\"version\": \"1.6.1\"}}}",
+    );
+    write_file(
+        &root.join("tools/pyproject.toml"),
+        "[project]\nname = \"tools\"\ndependencies = [\"Requests>=2.31\"]\n",
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(root)
+        .args(["--format", "json", "deps", "--external"])
+        .assert()
+        .success();
+    let deps: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    let rows: Vec<(String, String, String)> = deps
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|dep| {
+            (
+                dep["name"].as_str().expect("name").to_string(),
+                dep["locked"].as_str().unwrap_or("-").to_string(),
+                format!(
+                    "{}:{}",
+                    dep["declared_in"].as_str().expect("path"),
+                    dep["line"]
+                ),
+            )
+        })
+        .collect();
+    let row = |name: &str, locked: &str, at: &str| (name.into(), locked.into(), at.into());
+    assert_eq!(
+        rows,
+        vec![
+            row("serde", "1.0.210", "Cargo.toml:5"),
+            row("serde", "1.0.210", "crates/core/Cargo.toml:5"),
+            row("Requests", "-", "tools/pyproject.toml:3"),
+            row("vitest", "1.6.1", "web/package.json:3"),
+        ]
+    );
+    assert_eq!(deps[1]["version"], "1");
+    assert_eq!(deps[3]["kind"], "dev");
+}
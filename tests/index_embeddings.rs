@@ -246,3 +246,36 @@ fn index_auto_skips_on_schema_mismatch() {
     let storage = EmbeddingStorage::open_default(dir.path()).unwrap();
     assert_eq!(storage.count_symbols().unwrap(), 0);
 }
+
+#[test]
+fn files_without_symbols_are_embedded_as_chunks_and_searchable() {
+    let dir = TempDir::new().unwrap();
+    write_dummy_embeddings_config(dir.path());
+
+    let doc_path = dir.path().join("docs").join("deploy.md");
+    let mut doc = String::from("# Deployment\n\n");
+    for i in 0..120 {
+        doc.push_str(&format!(
+            "Step {i}: roll out the canary release carefully.\n"
+        ));
+    }
+    write_file(&doc_path, &doc);
+    write_file(&dir.path().join("src").join("lib.rs"), "fn alpha() {}\n");
+
+    run_index(dir.path(), &["--force", "--embeddings", "precompute"]);
+
+    let storage = EmbeddingStorage::open_default(dir.path()).unwrap();
+    let chunks = storage
+        .get_symbols_for_path(doc_path.to_string_lossy().as_ref())
+        .unwrap();
+    assert!(chunks.len() >= 2, "expected several line windows");
+    assert!(chunks.iter().all(|c| c.symbol_kind == "chunk"));
+    assert_eq!(chunks[0].start_line, 1);
+    assert_eq!(chunks[0].symbol_name, "# Deployment");
+    drop(storage);
+
+    let mut cmd = cargo_bin_cmd!("cgrep");
+    cmd.current_dir(dir.path())
+        .args(["search", "canary", "--mode", "hybrid"]);
+    cmd.assert().success().stdout(contains("deploy.md"));
+}
@@ -138,3 +138,131 @@ fn index_include_path_includes_specific_ignored_path_only() {
     let target_results = target_json.as_array().expect("results");
     assert!(target_results.is_empty());
 }
+
+#[test]
+fn stored_scope_is_reused_until_reset() {
+    let dir = TempDir::new().expect("tempdir");
+    init_git_repo(dir.path());
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn scoped_src_marker() {}\n",
+    );
+    write_file(
+        &dir.path().join("vendor/dep.rs"),
+        "pub fn scoped_vendor_marker() {}\n",
+    );
+
+    let run_index = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd.current_dir(dir.path()).args(args).assert().success();
+        String::from_utf8(assert.get_output().stdout.clone()).expect("utf8")
+    };
+
+    run_index(&["index", "--embeddings", "off", "--exclude", "vendor/"]);
+    write_file(
+        &dir.path().join("vendor/dep.rs"),
+        "pub fn scoped_vendor_marker_changed() {}\n",
+    );
+    // Incremental run without scope flags keeps excluding vendor/.
+    run_index(&["index", "--embeddings", "off"]);
+
+    let metadata: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join(".cgrep/metadata.json")).expect("read metadata"),
+    )
+    .expect("parse metadata");
+    assert_eq!(metadata["scope"]["exclude_paths"][0], "vendor/");
+    assert!(metadata["files"]
+        .as_object()
+        .expect("files")
+        .keys()
+        .all(|path| !path.contains("vendor")));
+
+    let shown = run_index(&["index", "--show-scope"]);
+    assert!(shown.contains("exclude (stored): vendor/"), "{shown}");
+
+    run_index(&["index", "--embeddings", "off", "--reset-scope"]);
+    let shown = run_index(&["index", "--show-scope"]);
+    assert!(shown.contains("exclude (stored): (none)"), "{shown}");
+    let results = run_search(dir.path(), "scoped_vendor_marker_changed");
+    assert!(!results.as_array().expect("results").is_empty());
+}
+
+#[test]
+fn hidden_and_per_source_ignore_flags_match_between_scan_and_index() {
+    let dir = TempDir::new().expect("tempdir");
+    let home = TempDir::new().expect("home");
+    init_git_repo(dir.path());
+    write_file(&dir.path().join(".gitignore"), "gen/\n");
+    write_file(&home.path().join("git/ignore"), "global.rs\n");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn walk_marker_src() {}\n",
+    );
+    write_file(
+        &dir.path().join(".tools/h.rs"),
+        "pub fn walk_marker_hidden() {}\n",
+    );
+    write_file(
+        &dir.path().join("gen/out.rs"),
+        "pub fn walk_marker_gen() {}\n",
+    );
+    write_file(
+        &dir.path().join("global.rs"),
+        "pub fn walk_marker_global() {}\n",
+    );
+
+    let cgrep = || {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        cmd.current_dir(dir.path())
+            .env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1");
+        cmd
+    };
+    let found = |extra: &[&str]| -> Vec<String> {
+        let assert = cgrep()
+            .args(["--format", "json", "search", "walk_marker"])
+            .args(extra)
+            .assert()
+            .success();
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+        let json: Value = serde_json::from_str(&stdout).expect("json");
+        let mut paths: Vec<String> = json
+            .as_array()
+            .expect("results")
+            .iter()
+            .filter_map(|r| r["path"].as_str().map(str::to_string))
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    };
+
+    assert_eq!(found(&["--no-index"]), ["src/lib.rs"]);
+    assert_eq!(found(&["--hidden"]), [".tools/h.rs", "src/lib.rs"]);
+    assert_eq!(found(&["--no-ignore-global"]), ["global.rs", "src/lib.rs"]);
+    assert_eq!(
+        found(&["--no-ignore-vcs"]),
+        ["gen/out.rs", "global.rs", "src/lib.rs"]
+    );
+
+    cgrep()
+        .args([
+            "index",
+            "--hidden",
+            "--no-ignore-vcs",
+            "--embeddings",
+            "off",
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        found(&[]),
+        [".tools/h.rs", "gen/out.rs", "global.rs", "src/lib.rs"]
+    );
+
+    let scope = cgrep().args(["index", "--show-scope"]).assert().success();
+    let scope = String::from_utf8(scope.get_output().stdout.clone()).expect("utf8");
+    assert!(scope.contains("hidden files:     true"), "{scope}");
+    assert!(scope.contains("vcs ignore:       false"), "{scope}");
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use predicates::str::contains;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_lock(dir: &Path, pid: u32) {
+    let lock_dir = dir.join(".cgrep");
+    fs::create_dir_all(&lock_dir).expect("create .cgrep");
+    let payload = serde_json::json!({
+        "pid": pid,
+        "acquired_at_ms": 1,
+        "command": "index",
+    });
+    fs::write(lock_dir.join("index.lock"), payload.to_string()).expect("write lock");
+}
+
+#[test]
+fn no_wait_fails_with_structured_error_when_lock_is_held() {
+    let dir = TempDir::new().expect("tempdir");
+    fs::write(dir.path().join("lib.rs"), "pub fn locked_marker() {}\n").expect("write");
+    // The test process itself is alive, so its lock is never stale.
+    write_lock(dir.path(), std::process::id());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    cmd.current_dir(dir.path())
+        .args(["index", "--no-wait", "--embeddings", "off"])
+        .assert()
+        .failure()
+        .stderr(contains("Index is locked by pid"))
+        .stderr(contains("index.lock"));
+
+    assert!(dir.path().join(".cgrep/index.lock").exists());
+    assert!(!dir.path().join(".cgrep/meta.json").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn stale_lock_from_dead_process_is_replaced_and_released() {
+    let dir = TempDir::new().expect("tempdir");
+    fs::write(dir.path().join("lib.rs"), "pub fn stale_marker() {}\n").expect("write");
+    write_lock(dir.path(), u32::MAX - 1);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    cmd.current_dir(dir.path())
+        .args(["index", "--no-wait", "--embeddings", "off"])
+        .assert()
+        .success()
+        .stderr(contains("Removing stale index lock"));
+
+    assert!(dir.path().join(".cgrep/meta.json").exists());
+    assert!(!dir.path().join(".cgrep/index.lock").exists());
+}
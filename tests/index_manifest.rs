@@ -108,3 +108,586 @@ fn json2_compact_output_is_stable_after_incremental_index() {
     let second_json: Value = serde_json::from_str(&second).expect("second json2");
     assert_eq!(first_json["results"], second_json["results"]);
 }
+
+#[test]
+fn dry_run_previews_diff_without_writing() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(&dir.path().join("src/a.rs"), "pub fn a() {}\n");
+    write_file(&dir.path().join("src/b.rs"), "pub fn b() {}\n");
+
+    let fresh = run_index(dir.path(), &["index", "--dry-run", "--print-diff"]);
+    assert!(fresh.contains("    src/a.rs"), "{fresh}");
+    assert!(fresh.contains("2 files to index"), "{fresh}");
+    assert!(fresh.contains("[full rebuild]"), "{fresh}");
+    assert!(!dir.path().join(".cgrep").exists());
+
+    let _ = run_index(dir.path(), &["index", "--embeddings", "off"]);
+    let manifest_before =
+        fs::read(dir.path().join(".cgrep/manifest/v1.json")).expect("read manifest");
+    write_file(&dir.path().join("src/b.rs"), "pub fn b_changed() {}\n");
+    write_file(&dir.path().join("src/c.rs"), "pub fn c() {}\n");
+
+    let preview = run_index(dir.path(), &["index", "--dry-run", "--print-diff"]);
+    assert!(
+        preview.contains("Manifest diff: 1 added, 1 modified, 0 deleted"),
+        "{preview}"
+    );
+    assert!(preview.contains("    src/c.rs"), "{preview}");
+    assert!(preview.contains("2 files to index"), "{preview}");
+    assert!(!preview.contains("[full rebuild]"), "{preview}");
+    assert_eq!(
+        fs::read(dir.path().join(".cgrep/manifest/v1.json")).expect("read manifest"),
+        manifest_before
+    );
+}
+
+#[test]
+fn stale_index_is_reported_and_require_fresh_reindexes() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(&dir.path().join("src/lib.rs"), "pub fn fresh_marker() {}\n");
+    write_file(
+        &dir.path().join(".cgreprc.toml"),
+        "[search]\nfreshness_check = true\n",
+    );
+    let _ = run_index(dir.path(), &["index", "--embeddings", "off"]);
+
+    let search = |extra: &[&str], auto_index: bool| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        cmd.current_dir(dir.path())
+            .args(["--format", "json2", "--compact", "search", "fresh_marker"])
+            .args(extra);
+        if !auto_index {
+            cmd.env("CGREP_DISABLE_CLI_AUTO_INDEX", "1");
+        }
+        cmd.assert()
+    };
+    let meta = |assert: assert_cmd::assert::Assert| -> Value {
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+        serde_json::from_str::<Value>(&stdout).expect("json2")["meta"].clone()
+    };
+
+    assert_eq!(meta(search(&[], false).success())["stale_index"], false);
+
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn fresh_marker() {}\npub fn fresh_marker_two() {}\n",
+    );
+    let stale = search(&[], false).success();
+    let stderr = String::from_utf8(stale.get_output().stderr.clone()).expect("utf8");
+    assert!(stderr.contains("index may be stale"), "stderr: {stderr}");
+    assert!(stderr.contains("src/lib.rs"), "stderr: {stderr}");
+    assert_eq!(meta(stale)["stale_index"], true);
+
+    let refused = search(&["--require-fresh"], false).failure();
+    let stderr = String::from_utf8(refused.get_output().stderr.clone()).expect("utf8");
+    assert!(stderr.contains("Index is stale"), "stderr: {stderr}");
+
+    let refreshed = meta(search(&["--require-fresh"], true).success());
+    assert_eq!(refreshed["stale_index"], false);
+    assert_eq!(meta(search(&[], false).success())["stale_index"], false);
+}
+
+#[test]
+fn verify_flags_results_from_files_changed_since_indexing() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn verify_marker() {}\n",
+    );
+    write_file(
+        &dir.path().join("src/other.rs"),
+        "pub fn verify_marker_other() {}\n",
+    );
+    let _ = run_index(dir.path(), &["index", "--embeddings", "off"]);
+
+    let search = || -> Value {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+            .args(["--format", "json2", "search", "verify_marker", "--verify"])
+            .assert()
+            .success();
+        serde_json::from_slice(&assert.get_output().stdout).expect("json2")
+    };
+
+    let fresh = search();
+    assert_eq!(fresh["meta"]["verified_files"], 2);
+    assert!(fresh["meta"].get("stale_files").is_none());
+
+    write_file(&dir.path().join("src/lib.rs"), "pub fn renamed() {}\n");
+    let stale = search();
+    let schema = cgrep::schema::schema("search").expect("embedded schema");
+    assert!(cgrep::schema::validate(&schema, &stale).is_empty());
+    assert_eq!(
+        stale["meta"]["stale_files"],
+        serde_json::json!(["src/lib.rs"])
+    );
+    let results = stale["results"].as_array().expect("results");
+    for result in results {
+        assert_eq!(result["stale"] == true, result["path"] == "src/lib.rs");
+    }
+}
+
+#[test]
+fn hits_in_deleted_files_are_dropped_and_counted() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/kept.rs"),
+        "pub fn tombstone_marker() {}\n",
+    );
+    write_file(
+        &dir.path().join("src/gone.rs"),
+        "pub fn tombstone_marker_gone() {}\n",
+    );
+    let _ = run_index(dir.path(), &["index", "--embeddings", "off"]);
+    fs::remove_file(dir.path().join("src/gone.rs")).expect("remove");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+        .args(["--format", "json2", "search", "tombstone_marker"])
+        .assert()
+        .success();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr).into_owned();
+    assert!(
+        stderr.contains("files deleted since indexing"),
+        "stderr: {stderr}"
+    );
+    let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json2");
+    assert_eq!(json["meta"]["stale_dropped"], 1);
+    let paths: Vec<&str> = json["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .filter_map(|result| result["path"].as_str())
+        .collect();
+    assert_eq!(paths, ["src/kept.rs"]);
+}
+
+#[test]
+fn auto_index_config_controls_cli_bootstrap() {
+    let search = |root: &std::path::Path| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        cmd.current_dir(root)
+            .args(["--format", "json2", "--compact", "search", "policy_marker"])
+            .assert()
+            .success();
+    };
+    let repo = |config: &str| {
+        let dir = TempDir::new().expect("tempdir");
+        write_file(
+            &dir.path().join("src/lib.rs"),
+            "pub fn policy_marker() {}\n",
+        );
+        write_file(&dir.path().join(".cgreprc.toml"), config);
+        dir
+    };
+
+    let disabled = repo("[auto_index]\nenabled = false\n");
+    search(disabled.path());
+    assert!(!disabled.path().join(".cgrep").exists());
+
+    let too_large = repo("[auto_index]\nmax_repo_size_mb = 0\n");
+    search(too_large.path());
+    assert!(!too_large.path().join(".cgrep").exists());
+
+    let allowed = repo("[auto_index]\nmax_repo_size_mb = 10\n");
+    search(allowed.path());
+    assert!(allowed.path().join(".cgrep/manifest").exists());
+}
+
+#[test]
+fn refresh_applies_pending_changes_once_and_exits() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn before_marker() {}\n",
+    );
+    write_file(&dir.path().join("src/gone.rs"), "pub fn gone_marker() {}\n");
+    write_file(
+        &dir.path().join(".cgreprc.toml"),
+        "[search]\nfreshness_check = true\n",
+    );
+    let _ = run_index(dir.path(), &["index", "--embeddings", "off"]);
+
+    write_file(&dir.path().join("src/lib.rs"), "pub fn after_marker() {}\n");
+    write_file(&dir.path().join("src/new.rs"), "pub fn added_marker() {}\n");
+    fs::remove_file(dir.path().join("src/gone.rs")).expect("remove file");
+
+    let stdout = run_index(dir.path(), &["refresh"]);
+    assert!(stdout.contains("Refresh complete"), "stdout: {stdout}");
+
+    let search = |query: &str| -> Value {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+            .args(["--format", "json2", "--compact", "search", query])
+            .assert()
+            .success();
+        serde_json::from_slice(&assert.get_output().stdout).expect("json2")
+    };
+    let paths = |payload: &Value| -> Vec<String> {
+        payload["results"]
+            .as_array()
+            .expect("results")
+            .iter()
+            .map(|result| result["path"].as_str().expect("path").to_string())
+            .collect()
+    };
+
+    let after = search("after_marker");
+    assert_eq!(after["meta"]["stale_index"], false);
+    assert_eq!(paths(&after), ["src/lib.rs"]);
+    assert_eq!(paths(&search("added_marker")), ["src/new.rs"]);
+    assert!(paths(&search("before_marker")).is_empty());
+    assert!(paths(&search("gone_marker")).is_empty());
+}
+
+#[test]
+fn install_git_hooks_writes_background_refresh_hooks() {
+    let dir = TempDir::new().expect("tempdir");
+    let status = std::process::Command::new("git")
+        .current_dir(dir.path())
+        .args(["init", "-q"])
+        .status()
+        .expect("git init");
+    assert!(status.success());
+
+    let install = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        cmd.current_dir(dir.path())
+            .arg("install-git-hooks")
+            .args(args)
+            .assert()
+    };
+    install(&[]).success();
+    install(&[]).success();
+    let hooks = dir.path().join(".git/hooks");
+    for name in ["post-checkout", "post-merge", "post-rewrite"] {
+        let hook = fs::read_to_string(hooks.join(name)).expect("hook");
+        assert!(hook.starts_with("#!/bin/sh\n"), "{name}: {hook}");
+        assert!(hook.contains("cgrep refresh"), "{name}: {hook}");
+    }
+
+    fs::write(hooks.join("post-merge"), "#!/bin/sh\nexit 0\n").expect("write");
+    let refused = install(&[]).failure();
+    let stderr = String::from_utf8(refused.get_output().stderr.clone()).expect("utf8");
+    assert!(stderr.contains("post-merge"), "stderr: {stderr}");
+    install(&["--force"]).success();
+    let hook = fs::read_to_string(hooks.join("post-merge")).expect("hook");
+    assert!(hook.contains("cgrep refresh"));
+}
+
+#[test]
+fn index_from_newer_cgrep_is_read_only_or_refused() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn compat_marker() {}\n",
+    );
+    let _ = run_index(dir.path(), &["index", "--embeddings", "off"]);
+
+    let metadata_path = dir.path().join(".cgrep/metadata.json");
+    let stamp = |min_reader: u64| {
+        let mut metadata: Value =
+            serde_json::from_str(&fs::read_to_string(&metadata_path).expect("read metadata"))
+                .expect("metadata json");
+        assert!(metadata["compat"]["format"].as_u64().is_some());
+        metadata["compat"] = serde_json::json!({
+            "format": 99,
+            "min_reader": min_reader,
+            "min_writer": 99,
+            "written_by": "99.0.0",
+        });
+        fs::write(&metadata_path, metadata.to_string()).expect("write metadata");
+    };
+    let cgrep = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        cmd.current_dir(dir.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+            .args(args)
+            .assert()
+    };
+    let stderr = |assert: assert_cmd::assert::Assert| {
+        String::from_utf8(assert.get_output().stderr.clone()).expect("utf8")
+    };
+
+    stamp(1);
+    let found = cgrep(&["--format", "json2", "--compact", "search", "compat_marker"]).success();
+    let payload: Value = serde_json::from_slice(&found.get_output().stdout).expect("json2");
+    assert_eq!(payload["results"][0]["path"], "src/lib.rs");
+    let refused = stderr(cgrep(&["index", "--embeddings", "off"]).failure());
+    assert!(
+        refused.contains("written by cgrep 99.0.0"),
+        "stderr: {refused}"
+    );
+    assert!(refused.contains("cgrep index --force"), "stderr: {refused}");
+
+    stamp(99);
+    let refused = stderr(cgrep(&["search", "compat_marker"]).failure());
+    assert!(refused.contains("To read it"), "stderr: {refused}");
+
+    cgrep(&["index", "--force", "--embeddings", "off"]).success();
+    cgrep(&["search", "compat_marker"]).success();
+    let metadata: Value =
+        serde_json::from_str(&fs::read_to_string(&metadata_path).expect("read metadata"))
+            .expect("metadata json");
+    assert_eq!(metadata["compat"]["written_by"], env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn branch_aware_index_swaps_per_branch_overlays() {
+    let dir = TempDir::new().expect("tempdir");
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir.path())
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .status()
+            .expect("run git");
+        assert!(status.success(), "git {args:?}");
+    };
+    let index_stderr = || {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .args(["index", "--embeddings", "off"])
+            .assert()
+            .success();
+        String::from_utf8(assert.get_output().stderr.clone()).expect("utf8")
+    };
+    let paths_for = |query: &str| -> Vec<String> {
+        let payload: Value =
+            serde_json::from_str(&run_search_json2_compact(dir.path(), query)).expect("json2");
+        payload["results"]
+            .as_array()
+            .expect("results")
+            .iter()
+            .map(|result| result["path"].as_str().expect("path").to_string())
+            .collect()
+    };
+
+    git(&["init", "-q"]);
+    git(&["symbolic-ref", "HEAD", "refs/heads/main"]);
+    write_file(&dir.path().join(".gitignore"), ".cgrep/\n");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn shared_marker() {}\n",
+    );
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "base"]);
+    run_index(
+        dir.path(),
+        &["index", "--branch-aware", "--embeddings", "off"],
+    );
+    let state: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join(".cgrep/branch.json")).expect("branch state"),
+    )
+    .expect("state json");
+    assert_eq!(state["branch"], "main");
+    assert_eq!(state["head"], state["merge_base"]);
+
+    git(&["checkout", "-q", "-b", "feature/login"]);
+    write_file(
+        &dir.path().join("src/login.rs"),
+        "pub fn feature_marker() {}\n",
+    );
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "feature"]);
+    let stderr = index_stderr();
+    assert!(
+        stderr.contains("started feature/login from the base index"),
+        "stderr: {stderr}"
+    );
+    assert_eq!(paths_for("feature_marker"), ["src/login.rs"]);
+
+    // The next search swaps main's parked index back in on its own.
+    git(&["checkout", "-q", "main"]);
+    assert!(paths_for("feature_marker").is_empty());
+    assert_eq!(paths_for("shared_marker"), ["src/lib.rs"]);
+    let branches: Vec<String> = fs::read_dir(dir.path().join(".cgrep/branches"))
+        .expect("branches dir")
+        .map(|entry| {
+            entry
+                .expect("entry")
+                .file_name()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    assert!(
+        branches
+            .iter()
+            .any(|name| name.starts_with("feature-login-")),
+        "{branches:?}"
+    );
+
+    git(&["checkout", "-q", "feature/login"]);
+    let stderr = index_stderr();
+    assert!(
+        stderr.contains("restored the index parked for feature/login"),
+        "stderr: {stderr}"
+    );
+    assert_eq!(paths_for("feature_marker"), ["src/login.rs"]);
+}
+
+#[test]
+fn index_snapshot_and_rollback_restore_a_tagged_index() {
+    let dir = TempDir::new().expect("tempdir");
+    let cgrep = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        cmd.current_dir(dir.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+            .args(args);
+        cmd.assert()
+    };
+    let hits = |query: &str| -> usize {
+        let assert = cgrep(&["--format", "json2", "--compact", "search", query]).success();
+        let payload: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json2");
+        payload["results"].as_array().expect("results").len()
+    };
+
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn pinned_marker() {}\n",
+    );
+    run_index(dir.path(), &["index", "--embeddings", "off"]);
+    let assert = cgrep(&[
+        "--format",
+        "json",
+        "--compact",
+        "index",
+        "snapshot",
+        "--tag",
+        "eval-v1",
+    ])
+    .success();
+    let report: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(report["tag"], "eval-v1");
+    assert!(report["hardlinked"].as_u64().expect("hardlinked") > 0);
+    assert!(dir
+        .path()
+        .join(".cgrep/snapshots/eval-v1/snapshot.json")
+        .is_file());
+    cgrep(&["index", "snapshot", "--tag", "eval-v1"]).failure();
+
+    write_file(
+        &dir.path().join("src/later.rs"),
+        "pub fn later_marker() {}\n",
+    );
+    run_index(dir.path(), &["index", "--embeddings", "off"]);
+    assert_eq!(hits("later_marker"), 1);
+
+    let missing = cgrep(&["index", "rollback", "nope"]).failure();
+    let stderr = String::from_utf8_lossy(&missing.get_output().stderr).into_owned();
+    assert!(stderr.contains("available: eval-v1"), "stderr: {stderr}");
+
+    cgrep(&["index", "rollback", "eval-v1"]).success();
+    assert_eq!(hits("later_marker"), 0);
+    assert_eq!(hits("pinned_marker"), 1);
+    // The snapshot survives the rollback and can be restored again.
+    assert!(dir
+        .path()
+        .join(".cgrep/snapshots/eval-v1/meta.json")
+        .is_file());
+}
+
+#[test]
+fn diff_index_reports_symbols_changed_since_a_snapshot() {
+    let dir = TempDir::new().expect("tempdir");
+    let cgrep = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        cmd.current_dir(dir.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+            .args(args);
+        cmd.assert()
+    };
+    let names = |report: &Value, key: &str| -> Vec<String> {
+        report[key]
+            .as_array()
+            .expect("array")
+            .iter()
+            .map(|symbol| symbol["name"].as_str().expect("name").to_string())
+            .collect()
+    };
+
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn stable() {}\n\npub fn retired() {}\n\npub fn reshaped(a: u8) {}\n",
+    );
+    run_index(dir.path(), &["index", "--embeddings", "off"]);
+    cgrep(&["index", "snapshot", "--tag", "v1"]).success();
+
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn introduced() {}\n\npub fn stable() {}\n\npub fn reshaped(a: u8, b: u8) {}\n",
+    );
+    run_index(dir.path(), &["index", "--embeddings", "off"]);
+
+    let assert = cgrep(&["--format", "json", "diff-index", "--baseline", "v1"]).success();
+    let report: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(report["from"], "v1");
+    assert_eq!(report["to"], "current");
+    assert_eq!(names(&report, "added"), ["introduced"]);
+    assert_eq!(names(&report, "removed"), ["retired"]);
+    assert_eq!(names(&report, "changed"), ["reshaped"]);
+
+    let failed = cgrep(&["diff-index", "v1", "--fail-on-removed"]).failure();
+    let stdout = String::from_utf8_lossy(&failed.get_output().stdout).into_owned();
+    assert!(stdout.contains("- function retired"), "stdout: {stdout}");
+    let stderr = String::from_utf8_lossy(&failed.get_output().stderr).into_owned();
+    assert!(
+        stderr.contains("1 symbol(s) removed since `v1`"),
+        "stderr: {stderr}"
+    );
+
+    cgrep(&["diff-index", "v0"])
+        .failure()
+        .stderr(predicates::str::contains("available: v1"));
+}
+
+#[test]
+fn size_policies_keep_oversized_files_discoverable_by_path_only() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join(".cgreprc.toml"),
+        "[[index.size_policies]]\npath = \"package-lock.json\"\nmode = \"path\"\n\n[[index.size_policies]]\npath = \"dist/**\"\nmax_bytes = 200\nmode = \"symbols\"\n",
+    );
+    write_file(
+        &dir.path().join("package-lock.json"),
+        "{\"packages\": {\"lockedMarker\": {\"version\": \"1.0.0\"}}}\n",
+    );
+    let filler = "// generated\n".repeat(20);
+    write_file(
+        &dir.path().join("dist/bundle.js"),
+        &format!("{filler}function bundledEntry() {{ return \"bundledMarker\"; }}\n"),
+    );
+    write_file(
+        &dir.path().join("dist/small.js"),
+        "function smallEntry() { return \"smallMarker\"; }\n",
+    );
+    run_index(dir.path(), &["index", "--embeddings", "off"]);
+
+    let paths = |query: &str| -> Vec<String> {
+        let payload: Value =
+            serde_json::from_str(&run_search_json2_compact(dir.path(), query)).expect("json");
+        payload["results"]
+            .as_array()
+            .expect("results")
+            .iter()
+            .map(|result| result["path"].as_str().expect("path").to_string())
+            .collect()
+    };
+    assert!(paths("lockedMarker").is_empty());
+    assert!(paths("bundledMarker").is_empty());
+    assert_eq!(paths("smallMarker"), ["dist/small.js"]);
+    assert!(paths("lock").contains(&"package-lock.json".to_string()));
+    assert!(paths("bundledEntry").contains(&"dist/bundle.js".to_string()));
+}
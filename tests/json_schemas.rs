@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use predicates::str::contains;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn run_json2(root: &Path, args: &[&str]) -> Value {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(root)
+        .args(["--format", "json2", "--compact"])
+        .args(args)
+        .assert()
+        .success();
+    serde_json::from_slice(&assert.get_output().stdout).expect("json parse")
+}
+
+fn assert_matches_schema(name: &str, payload: &Value) {
+    let schema = cgrep::schema::schema(name).expect("embedded schema");
+    let errors = cgrep::schema::validate(&schema, payload);
+    assert!(
+        errors.is_empty(),
+        "{name} payload violates its schema:\n{}\npayload: {payload}",
+        errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+fn indexed_fixture() -> TempDir {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/auth.rs"),
+        "pub fn validate_token(input: &str) -> bool {\n    input.starts_with(\"tok_\")\n}\n",
+    );
+    write_file(
+        &dir.path().join("src/service.rs"),
+        "pub fn auth_service_flow() {\n    let _ok = validate_token(\"tok_sample\");\n}\n",
+    );
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+    dir
+}
+
+#[test]
+fn json2_payloads_match_published_schemas() {
+    let dir = indexed_fixture();
+    let root = dir.path();
+
+    let search = run_json2(root, &["search", "validate_token", "--explain"]);
+    assert_matches_schema("search", &search);
+    let clustered = run_json2(root, &["search", "validate_token", "--cluster"]);
+    assert_matches_schema("search", &clustered);
+    let summarized = run_json2(root, &["search", "validate_token", "--summarize-files"]);
+    assert_matches_schema("search", &summarized);
+
+    let locate = run_json2(root, &["agent", "locate", "validate_token"]);
+    assert_matches_schema("search", &locate);
+    let id = locate["results"][0]["id"]
+        .as_str()
+        .expect("locate result id")
+        .to_string();
+    let expand = run_json2(root, &["agent", "expand", "--id", &id]);
+    assert_matches_schema("agent-expand", &expand);
+
+    let plan = run_json2(root, &["agent", "plan", "validate_token"]);
+    assert_matches_schema("agent-plan", &plan);
+
+    assert_matches_schema("status", &run_json2(root, &["status"]));
+    let watched = run_json2(root, &["status", "--watchers"]);
+    assert_matches_schema("status", &watched);
+    #[cfg(target_os = "linux")]
+    assert!(watched["result"]["watch"]["capacity"]["max_user_watches"]
+        .as_u64()
+        .is_some());
+    assert_matches_schema("doctor", &run_json2(root, &["doctor"]));
+    assert_matches_schema("read", &run_json2(root, &["read", "src/auth.rs"]));
+    assert_matches_schema("map", &run_json2(root, &["map"]));
+}
+
+#[test]
+fn json2_byte_offsets_address_the_file_on_disk() {
+    let dir = TempDir::new().expect("tempdir");
+    let content = b"\xEF\xBB\xBF// header\r\nfn load() {\r\n    let retry_marker = 1;\r\n}\r\n";
+    fs::write(dir.path().join("lib.rs"), content).expect("write");
+    let slice = |span: &Value| {
+        let start = span["start"].as_u64().expect("start") as usize;
+        let end = span["end"].as_u64().expect("end") as usize;
+        &content[start..end]
+    };
+
+    let search = run_json2(dir.path(), &["search", "retry_marker", "--no-index"]);
+    assert_matches_schema("search", &search);
+    assert_eq!(slice(&search["results"][0]["match_bytes"]), b"retry_marker");
+
+    let symbols = run_json2(dir.path(), &["symbols", "load"]);
+    assert_matches_schema("symbols", &symbols);
+    assert_eq!(
+        slice(&symbols["results"][0]["symbol_bytes"]),
+        b"fn load() {\r\n    let retry_marker = 1;\r\n}"
+    );
+}
+
+#[test]
+fn schema_command_prints_and_validates() {
+    let dir = indexed_fixture();
+    let root = dir.path();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd.args(["schema", "status"]).assert().success();
+    let printed: Value =
+        serde_json::from_slice(&assert.get_output().stdout).expect("schema is json");
+    assert_eq!(printed["title"], "cgrep status json2 payload");
+
+    let status = run_json2(root, &["status"]);
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .args(["schema", "status", "--validate", "-"])
+        .write_stdin(status.to_string())
+        .assert()
+        .success()
+        .stdout(contains("payload matches the status schema"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .args(["schema", "search", "--validate", "-"])
+        .write_stdin(status.to_string())
+        .assert()
+        .failure()
+        .stderr(contains("missing required property `results`"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .args(["schema", "stats"])
+        .assert()
+        .failure()
+        .stderr(contains("Unknown schema `stats`"));
+}
+
+#[test]
+fn schema_version_flag_pins_or_rejects_payload_version() {
+    let dir = indexed_fixture();
+    let root = dir.path();
+
+    let default = run_json2(root, &["search", "validate_token"]);
+    assert_eq!(default["meta"]["schema_version"], "2");
+    assert!(default["results"][0].get("column").is_some());
+
+    for command in [
+        &["search", "validate_token"][..],
+        &["read", "src/auth.rs"][..],
+        &["status"][..],
+    ] {
+        let mut args = vec!["--schema-version", "1"];
+        args.extend_from_slice(command);
+        let pinned = run_json2(root, &args);
+        assert_eq!(pinned["meta"]["schema_version"], "1", "{command:?}");
+
+        let schema = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+            .args(["--schema-version", "1", "schema", command[0]])
+            .output()
+            .expect("schema output");
+        let schema: Value = serde_json::from_slice(&schema.stdout).expect("v1 schema json");
+        let errors = cgrep::schema::validate(&schema, &pinned);
+        assert!(errors.is_empty(), "{command:?} v1 payload: {errors:?}");
+    }
+
+    let pinned = run_json2(root, &["--schema-version", "1", "search", "validate_token"]);
+    assert!(pinned["results"][0].get("column").is_none());
+    assert_eq!(
+        pinned["results"][0]["score"],
+        default["results"][0]["score"]
+    );
+    assert_eq!(pinned["results"][0]["path"], default["results"][0]["path"]);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(root)
+        .args(["--format", "json2", "--schema-version", "1", "audit"])
+        .assert()
+        .failure()
+        .stderr(contains("starts at version 2"));
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(root)
+        .args(["--format", "json2", "--schema-version", "99", "status"])
+        .assert()
+        .failure()
+        .stderr(contains("Unsupported --schema-version 99"));
+}
@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use encoding_rs::{GBK, SHIFT_JIS, WINDOWS_1252};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_encoded(path: &Path, text: &str, encoding: &'static encoding_rs::Encoding) {
+    let (bytes, _, had_errors) = encoding.encode(text);
+    assert!(!had_errors);
+    fs::write(path, bytes).expect("write file");
+}
+
+fn run_json2(dir: &Path, args: &[&str]) -> Value {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir)
+        .args(["--format", "json2"])
+        .args(args)
+        .assert()
+        .success();
+    serde_json::from_slice(&assert.get_output().stdout).expect("json2")
+}
+
+fn first_snippet(payload: &Value) -> (&str, &str) {
+    let result = &payload["results"][0];
+    (
+        result["path"].as_str().expect("path"),
+        result["snippet"].as_str().expect("snippet"),
+    )
+}
+
+#[test]
+fn legacy_encoded_files_are_transcoded_for_search_and_read() {
+    let dir = TempDir::new().expect("tempdir");
+    write_encoded(
+        &dir.path().join("config.rs"),
+        "// 設定ファイルを読み込む\nfn load_settings() {}\n",
+        SHIFT_JIS,
+    );
+    write_encoded(
+        &dir.path().join("server.rs"),
+        "// 读取配置文件并返回结果\nfn read_config() {}\n",
+        GBK,
+    );
+    write_encoded(
+        &dir.path().join("notes.txt"),
+        "café naïve façade\n",
+        WINDOWS_1252,
+    );
+
+    let scan = run_json2(dir.path(), &["search", "ファイル", "--no-index"]);
+    assert_eq!(
+        first_snippet(&scan),
+        ("config.rs", "// 設定ファイルを読み込む")
+    );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+    let metadata: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join(".cgrep/metadata.json")).expect("metadata"),
+    )
+    .expect("metadata json");
+    let encodings: Vec<&str> = metadata["files"]
+        .as_object()
+        .expect("files")
+        .values()
+        .filter_map(|meta| meta["encoding"].as_str())
+        .collect();
+    assert_eq!(encodings.len(), 3, "{encodings:?}");
+
+    let indexed = run_json2(dir.path(), &["search", "读取配置文件并返回结果"]);
+    assert_eq!(
+        first_snippet(&indexed),
+        ("server.rs", "// 读取配置文件并返回结果")
+    );
+    let latin = run_json2(dir.path(), &["search", "naïve"]);
+    assert_eq!(first_snippet(&latin).0, "notes.txt");
+
+    let read = run_json2(dir.path(), &["read", "config.rs"]);
+    assert_eq!(read["result"]["encoding"], "Shift_JIS");
+    assert!(read["result"]["content"]
+        .as_str()
+        .expect("content")
+        .contains("設定ファイルを読み込む"));
+}
+
+fn quickfix_lines(dir: &Path, args: &[&str]) -> Vec<String> {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir)
+        .args(["--format", "quickfix"])
+        .args(args)
+        .assert()
+        .success();
+    let mut lines: Vec<String> = String::from_utf8(assert.get_output().stdout.clone())
+        .expect("utf8")
+        .lines()
+        .map(|line| line.split(':').take(3).collect::<Vec<_>>().join(":"))
+        .collect();
+    lines.sort();
+    lines
+}
+
+#[test]
+fn windows_authored_files_report_editor_line_and_column_numbers() {
+    let dir = TempDir::new().expect("tempdir");
+    // UTF-8 BOM + CRLF, as saved by many Windows editors.
+    fs::write(
+        dir.path().join("crlf.rs"),
+        b"\xEF\xBB\xBFeol_marker_a();\r\n\r\nfn f() {\r\n    eol_marker_b();\r\n}\r\n",
+    )
+    .expect("write crlf");
+    // Classic Mac line breaks: editors still count each CR as a line.
+    fs::write(
+        dir.path().join("cr.rs"),
+        b"// header\r\rfn g() {\r    eol_marker_c();\r}\r",
+    )
+    .expect("write cr");
+    let expected = ["cr.rs:4:5", "crlf.rs:1:1", "crlf.rs:4:5"];
+
+    let scan = quickfix_lines(dir.path(), &["search", "eol_marker", "--no-index"]);
+    assert_eq!(scan, expected);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+    let indexed = quickfix_lines(dir.path(), &["search", "eol_marker"]);
+    assert_eq!(indexed, expected);
+
+    let metadata: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join(".cgrep/metadata.json")).expect("metadata"),
+    )
+    .expect("metadata json");
+    let meta_of = |name: &str| {
+        let key = dir.path().canonicalize().expect("canonical").join(name);
+        metadata["files"][key.to_string_lossy().as_ref()].clone()
+    };
+    assert_eq!(meta_of("crlf.rs")["line_ending"], "crlf");
+    assert_eq!(meta_of("crlf.rs")["bom"], true);
+    assert_eq!(meta_of("cr.rs")["line_ending"], "cr");
+    assert!(meta_of("cr.rs").get("bom").is_none());
+
+    let read = run_json2(dir.path(), &["read", "crlf.rs"]);
+    assert_eq!(read["result"]["line_count"], 5);
+    assert!(!read["result"]["content"]
+        .as_str()
+        .expect("content")
+        .contains('\r'));
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn literals_json(dir: &Path, args: &[&str]) -> Vec<Value> {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir)
+        .args(["--format", "json", "literals"])
+        .args(args)
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    json.as_array().expect("array").clone()
+}
+
+#[test]
+fn literals_reports_exact_normalized_and_constant_matches_with_and_without_index() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/http.rs"),
+        "pub const CONTENT_TYPE: &str = \"application/json\";\n\nfn send() {\n    // \"content-type\" in a comment\n    headers.insert(\"content-type\", CONTENT_TYPE);\n}\n",
+    );
+    write_file(
+        &dir.path().join("web/client.ts"),
+        "const headers = { 'Content-Type': 'text/plain', 'x-content-type-options': 'nosniff' };\n",
+    );
+
+    let summarize = |results: &[Value]| -> Vec<(String, String, u64)> {
+        results
+            .iter()
+            .map(|r| {
+                (
+                    r["match"].as_str().unwrap_or_default().to_string(),
+                    r["path"].as_str().unwrap_or_default().to_string(),
+                    r["line"].as_u64().unwrap_or_default(),
+                )
+            })
+            .collect()
+    };
+    let expected = vec![
+        ("exact".to_string(), "src/http.rs".to_string(), 5),
+        ("normalized".to_string(), "web/client.ts".to_string(), 1),
+        ("constant".to_string(), "src/http.rs".to_string(), 1),
+    ];
+
+    let scanned = literals_json(dir.path(), &["content-type"]);
+    assert_eq!(summarize(&scanned), expected);
+    assert_eq!(scanned[0]["column"], 20);
+    assert_eq!(scanned[2]["constant"], "CONTENT_TYPE");
+
+    let mut index = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    index
+        .current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+    let indexed = literals_json(dir.path(), &["content-type"]);
+    assert_eq!(summarize(&indexed), expected);
+
+    let loose = literals_json(dir.path(), &["content-type", "--contains"]);
+    assert_eq!(loose.len(), 4);
+    assert_eq!(loose[3]["value"], "x-content-type-options");
+}
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+#[test]
+fn logsource_ranks_matching_format_string_first() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/db.py"),
+        "def connect(host, retries):\n    log.info(\"Connecting to %s\", host)\n    log.error(\"Failed to connect to %s after %d retries\", host, retries)\n",
+    );
+    write_file(
+        &dir.path().join("src/net.rs"),
+        "fn connect(host: &str) {\n    tracing::warn!(\"connect to {} timed out\", host);\n}\n",
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args([
+            "--format",
+            "json",
+            "logsource",
+            "2024-05-01T12:30:45Z [ERROR] Failed to connect to db-7 after 3 retries",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    let results = json.as_array().expect("array");
+    assert_eq!(results[0]["path"], "src/db.py");
+    assert_eq!(results[0]["line"], 3);
+    assert_eq!(results[0]["exact"], true);
+    assert_eq!(results[0]["log_call"], true);
+    assert!(results
+        .iter()
+        .skip(1)
+        .all(|r| r["score"].as_f64() < results[0]["score"].as_f64()));
+}
@@ -132,6 +132,8 @@ fn explain_json2_compact_is_deterministic_and_parseable() {
         "path_boost",
         "symbol_boost",
         "changed_boost",
+        "churn_boost",
+        "recency_boost",
         "kind_boost",
         "penalties",
         "final_score",
@@ -222,11 +224,20 @@ fn explain_components_match_final_score_and_tiebreak_is_stable() {
         let path_boost = explain["path_boost"].as_f64().expect("path");
         let symbol_boost = explain["symbol_boost"].as_f64().expect("symbol");
         let changed_boost = explain["changed_boost"].as_f64().expect("changed");
+        let churn_boost = explain["churn_boost"].as_f64().expect("churn");
+        let recency_boost = explain["recency_boost"].as_f64().expect("recency");
         let kind_boost = explain["kind_boost"].as_f64().expect("kind");
         let penalties = explain["penalties"].as_f64().expect("penalties");
         let final_score = explain["final_score"].as_f64().expect("final");
-        let recomposed =
-            bm25 * (1.0 + path_boost + symbol_boost + changed_boost + kind_boost + penalties);
+        let recomposed = bm25
+            * (1.0
+                + path_boost
+                + symbol_boost
+                + changed_boost
+                + churn_boost
+                + recency_boost
+                + kind_boost
+                + penalties);
         assert!((recomposed - final_score).abs() < 0.0001);
 
         let result_score = result["score"].as_f64().expect("result score");
@@ -301,3 +312,302 @@ fn changed_language_and_scope_filters_remain_correct_with_ranking() {
         assert!(path.ends_with(".rs"));
     }
 }
+
+#[test]
+fn language_named_in_query_routes_ranking_and_reports_meta() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("a_retry.rs"),
+        "// retry decorator helper\nfn wrap() {}\n",
+    );
+    write_file(
+        &dir.path().join("b_retry.py"),
+        "# retry decorator helper\ndef wrap(): pass\n",
+    );
+    run_index(dir.path());
+
+    let routed = run_json2(dir.path(), &["search", "python retry decorator"]);
+    assert_eq!(routed["meta"]["language_route"], "python");
+    assert_eq!(routed["results"][0]["path"], "b_retry.py");
+
+    let plain = run_json2(
+        dir.path(),
+        &["search", "python retry decorator", "--no-lang-route"],
+    );
+    assert!(plain["meta"].get("language_route").is_none());
+    assert_eq!(plain["results"][0]["path"], "a_retry.rs");
+
+    let explicit = run_json2(
+        dir.path(),
+        &["search", "python retry decorator", "--type", "rust"],
+    );
+    assert!(explicit["meta"].get("language_route").is_none());
+}
+
+#[test]
+fn search_docs_matches_doc_comment_prose_only() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("net.rs"),
+        "/// Exponential backoff for flaky network calls.\npub fn retry_with_jitter() {}\n\npub fn unrelated() { let backoff = 2; }\n",
+    );
+    write_file(
+        &dir.path().join("smooth.py"),
+        "def smooth(xs):\n    \"\"\"Exponential decay over a window.\"\"\"\n    return xs\n",
+    );
+    write_file(
+        &dir.path().join("notes.rs"),
+        "// exponential backoff is configured elsewhere\nfn noop() {}\n",
+    );
+    run_index(dir.path());
+
+    let docs = run_json2(
+        dir.path(),
+        &["search", "exponential backoff", "--search-docs"],
+    );
+    let hits: Vec<(String, u64)> = docs["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .map(|r| {
+            (
+                r["path"].as_str().unwrap_or_default().to_string(),
+                r["line"].as_u64().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert_eq!(hits[0], ("net.rs".to_string(), 1));
+    assert!(hits.contains(&("smooth.py".to_string(), 2)));
+    assert!(hits
+        .iter()
+        .all(|(path, line)| path != "notes.rs" && *line != 4));
+
+    let all = run_json2(dir.path(), &["search", "exponential backoff"]);
+    assert!(all["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .any(|r| r["path"] == "notes.rs"));
+}
+
+#[test]
+fn frequently_committed_files_rank_above_dormant_ones() {
+    let dir = TempDir::new().expect("tempdir");
+    write_ranking_enabled_config(dir.path());
+    init_git_repo(dir.path());
+    write_file(&dir.path().join("a_vendored.txt"), "churn_token\n");
+    write_file(&dir.path().join("z_hot.txt"), "churn_token\n");
+    run_git(dir.path(), &["add", "."]);
+    run_git(dir.path(), &["commit", "--quiet", "-m", "initial"]);
+    for round in 1..=3 {
+        write_file(
+            &dir.path().join("z_hot.txt"),
+            &format!("churn_token\nrevision {round}\n"),
+        );
+        run_git(dir.path(), &["commit", "--quiet", "-am", "edit"]);
+    }
+    // Same text as the vendored file, so only churn separates them.
+    write_file(&dir.path().join("z_hot.txt"), "churn_token\n");
+    run_index(dir.path());
+
+    let payload = run_json2(
+        dir.path(),
+        &["search", "churn_token", "--limit", "10", "--explain"],
+    );
+    let results = payload["results"].as_array().expect("results");
+    assert_eq!(results[0]["path"], "z_hot.txt");
+    let hot = results[0]["explain"]["churn_boost"]
+        .as_f64()
+        .expect("churn");
+    let cold = results[1]["explain"]["churn_boost"]
+        .as_f64()
+        .expect("churn");
+    assert!(hot > cold && cold > 0.0, "hot={hot} cold={cold}");
+}
+
+#[test]
+fn recently_modified_files_rank_above_stale_copies() {
+    let dir = TempDir::new().expect("tempdir");
+    write_ranking_enabled_config(dir.path());
+    write_file(&dir.path().join("a_copied.txt"), "recency_token\n");
+    write_file(&dir.path().join("z_active.txt"), "recency_token\n");
+    let stale = std::time::SystemTime::now() - std::time::Duration::from_secs(400 * 24 * 60 * 60);
+    fs::File::options()
+        .write(true)
+        .open(dir.path().join("a_copied.txt"))
+        .expect("open")
+        .set_modified(stale)
+        .expect("set mtime");
+    run_index(dir.path());
+
+    let payload = run_json2(
+        dir.path(),
+        &["search", "recency_token", "--limit", "10", "--explain"],
+    );
+    let results = payload["results"].as_array().expect("results");
+    assert_eq!(results[0]["path"], "z_active.txt");
+    assert!(
+        results[0]["explain"]["recency_boost"]
+            .as_f64()
+            .expect("recency")
+            > 0.0
+    );
+    assert_eq!(results[1]["explain"]["recency_boost"], 0.0);
+}
+
+#[test]
+fn diversify_spreads_top_results_across_directories() {
+    let dir = TempDir::new().expect("tempdir");
+    for name in ["a", "b", "c", "d", "e"] {
+        write_file(
+            &dir.path().join(format!("db/{name}.txt")),
+            "spread_token spread_token spread_token\n",
+        );
+    }
+    write_file(&dir.path().join("api/handler.txt"), "spread_token\n");
+    run_index(dir.path());
+
+    let paths = |payload: &Value| -> Vec<String> {
+        payload["results"]
+            .as_array()
+            .expect("results")
+            .iter()
+            .map(|r| r["path"].as_str().expect("path").to_string())
+            .collect()
+    };
+
+    let plain = run_json2(dir.path(), &["search", "spread_token", "--limit", "4"]);
+    assert!(paths(&plain).iter().all(|p| p.starts_with("db/")));
+
+    let diversified = run_json2(
+        dir.path(),
+        &["search", "spread_token", "--limit", "4", "--diversify"],
+    );
+    let diversified = paths(&diversified);
+    assert_eq!(diversified.len(), 4);
+    assert_eq!(diversified[1], "api/handler.txt");
+
+    write_config(
+        dir.path(),
+        "[search]\ndiversify = true\nmax_results_per_dir = 1\n",
+    );
+    let configured = paths(&run_json2(
+        dir.path(),
+        &["search", "spread_token", "--limit", "4"],
+    ));
+    assert_eq!(configured.len(), 2);
+    assert!(configured.contains(&"api/handler.txt".to_string()));
+}
+
+#[test]
+fn cluster_groups_results_with_labels_and_representatives() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/http/routes.rs"),
+        "fn handle_request(req: Request) { backoff_token(req) }\n",
+    );
+    write_file(
+        &dir.path().join("src/http/server.rs"),
+        "fn serve_request(req: Request) { backoff_token(req) }\n",
+    );
+    write_file(
+        &dir.path().join("tests/parser_test.rs"),
+        "fn parses_retry_config() { assert!(backoff_token(config)) }\n",
+    );
+    write_file(
+        &dir.path().join("tests/loader_test.rs"),
+        "fn loads_retry_config() { assert!(backoff_token(config)) }\n",
+    );
+    run_index(dir.path());
+
+    let payload = run_json2(
+        dir.path(),
+        &["search", "backoff_token", "--limit", "10", "--cluster"],
+    );
+    assert_eq!(payload["meta"]["cluster_method"], "lexical");
+    let results = payload["results"].as_array().expect("results");
+    let path_of = |id: &Value| {
+        results
+            .iter()
+            .find(|r| r["id"] == *id)
+            .and_then(|r| r["path"].as_str())
+            .expect("member is a result")
+            .to_string()
+    };
+
+    let clusters = payload["clusters"].as_array().expect("clusters");
+    assert_eq!(clusters.len(), 2);
+    let mut covered = 0;
+    for cluster in clusters {
+        let members: Vec<String> = cluster["result_ids"]
+            .as_array()
+            .expect("ids")
+            .iter()
+            .map(path_of)
+            .collect();
+        covered += members.len();
+        assert_eq!(cluster["size"], members.len());
+        let dir_of = |p: &str| p.rsplit_once('/').map(|(d, _)| d.to_string());
+        assert!(members.iter().all(|m| dir_of(m) == dir_of(&members[0])));
+        let label = cluster["label"].as_str().expect("label");
+        let expected = if members[0].starts_with("tests/") {
+            "test"
+        } else {
+            "http"
+        };
+        assert!(label.contains(expected), "label {label:?} for {members:?}");
+        assert!(members.contains(&path_of(&cluster["representative"]["id"])));
+    }
+    assert_eq!(covered, results.len());
+
+    let plain = run_json2(dir.path(), &["search", "backoff_token"]);
+    assert!(plain.get("clusters").is_none());
+}
+
+#[test]
+fn linguist_vendored_and_generated_paths_are_demoted_or_excluded() {
+    let dir = TempDir::new().expect("tempdir");
+    init_git_repo(dir.path());
+    write_file(
+        &dir.path().join(".gitattributes"),
+        "vendor/** linguist-vendored\ngen/*.rs linguist-generated\n",
+    );
+    // Denser matches so the classified files would otherwise rank first.
+    write_file(
+        &dir.path().join("vendor/lib.rs"),
+        "fn attr_token() { attr_token(); attr_token(); }\n",
+    );
+    write_file(
+        &dir.path().join("gen/api.rs"),
+        "fn attr_token() { attr_token(); attr_token(); }\n",
+    );
+    write_file(&dir.path().join("src/main.rs"), "fn attr_token() {}\n");
+    run_index(dir.path());
+
+    let payload = run_json2(dir.path(), &["search", "attr_token", "--limit", "10"]);
+    let results = payload["results"].as_array().expect("results");
+    let paths: Vec<&str> = results
+        .iter()
+        .map(|r| r["path"].as_str().expect("path"))
+        .collect();
+    assert_eq!(paths[0], "src/main.rs", "{paths:?}");
+    assert!(results[0].get("linguist").is_none());
+    let class_of = |path: &str| {
+        results
+            .iter()
+            .find(|r| r["path"] == path)
+            .map(|r| r["linguist"].clone())
+    };
+    assert_eq!(class_of("vendor/lib.rs"), Some(Value::from("vendored")));
+    assert_eq!(class_of("gen/api.rs"), Some(Value::from("generated")));
+
+    write_config(dir.path(), "[search]\nlinguist = \"exclude\"\n");
+    let excluded = run_json2(dir.path(), &["search", "attr_token", "--limit", "10"]);
+    let paths: Vec<&str> = excluded["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .map(|r| r["path"].as_str().expect("path"))
+        .collect();
+    assert_eq!(paths, ["src/main.rs"]);
+}
@@ -101,7 +101,7 @@ fn json2_compact_contract_is_stable_for_status_and_plan() {
     assert_eq!(status_1, status_2);
 
     let status: Value = serde_json::from_str(&status_1).expect("status json");
-    assert_eq!(status["meta"]["schema_version"], "1");
+    assert_eq!(status["meta"]["schema_version"], "2");
     assert!(status["result"].get("phase").is_some());
     assert!(status["result"].get("progress").is_some());
 
@@ -284,3 +284,55 @@ fn legacy_mode_aliases_remain_compatible_with_keyword_default() {
 
     assert_eq!(default_payload["results"], legacy_payload["results"]);
 }
+
+#[test]
+fn expired_timeout_returns_valid_partial_output() {
+    let dir = TempDir::new().expect("tempdir");
+    write_fixture(dir.path());
+    run_index(dir.path());
+
+    let complete = run_json2(dir.path(), &["search", "validate_token"]);
+    assert!(complete["meta"].get("timed_out").is_none());
+    assert!(!complete["results"].as_array().expect("results").is_empty());
+
+    for mode in [&["--no-index"][..], &[]] {
+        let mut args = vec![
+            "--format",
+            "json2",
+            "--compact",
+            "search",
+            "validate_token",
+            "--timeout-ms",
+            "0",
+        ];
+        args.extend(mode);
+        let assert = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+            .current_dir(dir.path())
+            .args(&args)
+            .assert()
+            .code(124);
+        let partial: Value =
+            serde_json::from_slice(&assert.get_output().stdout).expect("json parse");
+        assert_eq!(partial["meta"]["timed_out"], true, "args: {args:?}");
+        assert!(partial["results"].as_array().expect("results").is_empty());
+    }
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args([
+            "--format",
+            "json",
+            "symbols",
+            "validate_token",
+            "--timeout-ms",
+            "0",
+        ])
+        .assert()
+        .code(124);
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let symbols: Value = serde_json::from_str(&stdout).expect("json");
+    assert!(symbols.as_array().is_some());
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).expect("utf8");
+    assert!(stderr.contains("results are partial"), "stderr: {stderr}");
+}
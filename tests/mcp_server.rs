@@ -90,6 +90,10 @@ fn mcp_initialize_and_list_tools() {
         "params": {}
     }));
     assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+    assert!(
+        init["result"]["_meta"].is_null(),
+        "warmup is off by default"
+    );
     assert!(init["result"]["instructions"]
         .as_str()
         .unwrap_or_default()
@@ -124,9 +128,12 @@ fn mcp_initialize_and_list_tools() {
         "cgrep_symbols",
         "cgrep_definition",
         "cgrep_references",
+        "cgrep_rename_check",
+        "cgrep_review",
         "cgrep_callers",
         "cgrep_dependents",
         "cgrep_index",
+        "cgrep_status",
     ] {
         let tool = tools_array
             .iter()
@@ -136,6 +143,10 @@ fn mcp_initialize_and_list_tools() {
             tool["inputSchema"]["properties"]["cwd"].is_object(),
             "{tool_name} should expose optional cwd in MCP schema"
         );
+        assert!(
+            tool["inputSchema"]["properties"]["profile"].is_object(),
+            "{tool_name} should expose optional profile in MCP schema"
+        );
     }
 
     let cgrep_search = tools_array
@@ -155,6 +166,12 @@ fn mcp_initialize_and_list_tools() {
     assert!(cgrep_read["inputSchema"]["properties"]["section_start"].is_object());
     assert!(cgrep_read["inputSchema"]["properties"]["section_end"].is_object());
 
+    let cgrep_symbols = tools_array
+        .iter()
+        .find(|t| t["name"].as_str() == Some("cgrep_symbols"))
+        .expect("cgrep_symbols schema");
+    assert!(cgrep_symbols["inputSchema"]["properties"]["names"].is_object());
+
     let cgrep_definition = tools_array
         .iter()
         .find(|t| t["name"].as_str() == Some("cgrep_definition"))
@@ -516,6 +533,271 @@ fn mcp_search_auto_indexes_once_when_missing() {
     mcp.stop();
 }
 
+#[test]
+fn mcp_status_reports_readiness_counts_and_last_build() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn status_marker() {}\n",
+    );
+
+    let mut mcp = McpProc::spawn(dir.path());
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {}
+    }));
+    let status = |mcp: &mut McpProc, id: u64| -> Value {
+        let resp = mcp.call(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": "cgrep_status", "arguments": {} }
+        }));
+        let text = resp["result"]["content"][0]["text"]
+            .as_str()
+            .expect("status text");
+        serde_json::from_str(text).expect("status json")
+    };
+
+    let before = status(&mut mcp, 2);
+    assert_eq!(before["next_step"], "run_cgrep_index");
+    assert_eq!(before["status"]["full_ready"], false);
+    assert!(before["status"]["index"].is_null());
+    assert!(before["doctor"]["findings"].is_array());
+
+    let search = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_search",
+            "arguments": { "query": "status_marker" }
+        }
+    }));
+    assert!(search["result"]["isError"].is_null(), "{search}");
+
+    let after = status(&mut mcp, 4);
+    assert_eq!(after["next_step"], "ready", "{after}");
+    assert_eq!(after["status"]["index"]["files"], 1);
+    assert!(after["status"]["index"]["documents"].as_u64().unwrap_or(0) >= 1);
+    assert_eq!(after["status"]["last_build"]["kind"], "full");
+    assert_eq!(after["status"]["last_build"]["files_updated"], 1);
+    assert!(after["auto_index"].is_object());
+
+    mcp.stop();
+}
+
+#[test]
+fn mcp_logging_reports_auto_index_bootstrap_after_set_level() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn logged_marker() {}\n",
+    );
+
+    let mut mcp = McpProc::spawn(dir.path());
+    let init = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {}
+    }));
+    assert!(init["result"]["capabilities"]["logging"].is_object());
+
+    let invalid = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "logging/setLevel",
+        "params": { "level": "loud" }
+    }));
+    assert_eq!(invalid["error"]["code"], -32602);
+    let set = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "logging/setLevel",
+        "params": { "level": "info" }
+    }));
+    assert_eq!(set["result"], json!({}));
+
+    let mut message = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 4,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_search",
+            "arguments": { "query": "logged_marker" }
+        }
+    }));
+    let mut events = Vec::new();
+    while message["id"] != 4 {
+        assert_eq!(message["method"], "notifications/message", "{message}");
+        assert_eq!(message["params"]["logger"], "cgrep");
+        events.push(message["params"]["data"]["event"].clone());
+        let mut line = String::new();
+        mcp.stdout.read_line(&mut line).expect("read message");
+        message = serde_json::from_str(&line).expect("parse message");
+    }
+    assert!(message["result"]["isError"].is_null(), "{message}");
+    assert_eq!(
+        events,
+        [
+            json!("auto_index_bootstrap_started"),
+            json!("auto_index_bootstrap")
+        ]
+    );
+
+    mcp.stop();
+}
+
+#[test]
+fn mcp_repeated_tool_calls_are_served_from_the_result_cache() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn cached_marker() {}\n",
+    );
+
+    let mut mcp = McpProc::spawn(dir.path());
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {}
+    }));
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "logging/setLevel",
+        "params": { "level": "debug" }
+    }));
+    // Returns the response text and the logged events before it.
+    let search = |mcp: &mut McpProc, id: u64, no_cache: bool| -> (String, Vec<Value>) {
+        let mut message = mcp.call(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {
+                "name": "cgrep_search",
+                "arguments": { "query": "cached_marker", "no_cache": no_cache }
+            }
+        }));
+        let mut events = Vec::new();
+        while message["id"] != id {
+            events.push(message["params"]["data"]["event"].clone());
+            let mut line = String::new();
+            mcp.stdout.read_line(&mut line).expect("read message");
+            message = serde_json::from_str(&line).expect("parse message");
+        }
+        let text = message["result"]["content"][0]["text"]
+            .as_str()
+            .expect("search text")
+            .to_string();
+        (text, events)
+    };
+
+    let (_, bootstrap) = search(&mut mcp, 3, false);
+    assert!(bootstrap.contains(&json!("auto_index_bootstrap")));
+    let (stored, events) = search(&mut mcp, 4, false);
+    assert!(!events.contains(&json!("result_cache_hit")));
+    let (cached, events) = search(&mut mcp, 5, false);
+    assert_eq!(events, [json!("result_cache_hit")]);
+    assert_eq!(cached, stored);
+    let (_, events) = search(&mut mcp, 6, true);
+    assert!(!events.contains(&json!("result_cache_hit")));
+
+    mcp.stop();
+}
+
+#[test]
+fn mcp_file_reads_are_not_served_from_the_result_cache() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(&dir.path().join("src/lib.rs"), "pub fn first_marker() {}\n");
+    // An index without auto-index state: nothing marks the scope dirty.
+    assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .arg("index")
+        .assert()
+        .success();
+
+    let mut mcp = McpProc::spawn(dir.path());
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {}
+    }));
+    let read = |mcp: &mut McpProc, id: u64| -> String {
+        let message = mcp.call(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": "cgrep_read", "arguments": { "path": "src/lib.rs" } }
+        }));
+        message["result"]["content"][0]["text"]
+            .as_str()
+            .expect("read text")
+            .to_string()
+    };
+    assert!(read(&mut mcp, 2).contains("first_marker"));
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn edited_marker() {}\n",
+    );
+    assert!(read(&mut mcp, 3).contains("edited_marker"));
+
+    mcp.stop();
+}
+
+#[test]
+fn mcp_initialize_warms_the_index_when_configured() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(&dir.path().join("src/lib.rs"), "pub fn warm_marker() {}\n");
+    write_file(
+        &dir.path().join(".cgreprc.toml"),
+        "[auto_index]\nmcp_warmup = true\n",
+    );
+
+    let mut mcp = McpProc::spawn(dir.path());
+    let init = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {}
+    }));
+    let warmup = &init["result"]["_meta"]["cgrep/warmup"];
+    assert_eq!(warmup["state"], "started", "{init}");
+    assert_eq!(warmup["index"], "missing");
+
+    let meta = dir.path().join(".cgrep/meta.json");
+    for _ in 0..200 {
+        if meta.exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(meta.exists(), "warmup builds the index in the background");
+
+    let search = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_search",
+            "arguments": { "query": "warm_marker" }
+        }
+    }));
+    let text = search["result"]["content"][0]["text"]
+        .as_str()
+        .expect("search text");
+    let payload: Value = serde_json::from_str(text).expect("search json");
+    assert_eq!(payload["meta"]["index_mode"], "index");
+    assert_eq!(payload["meta"]["bootstrap_index"], false);
+
+    mcp.stop();
+}
+
 #[test]
 fn mcp_search_auto_refreshes_after_source_change() {
     let dir = TempDir::new().expect("tempdir");
@@ -682,6 +964,105 @@ fn mcp_agent_locate_respects_auto_index_false() {
     mcp.stop();
 }
 
+#[test]
+fn mcp_search_respects_auto_index_config() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn config_no_auto_index_marker() {}\n",
+    );
+    write_file(
+        &dir.path().join(".cgreprc.toml"),
+        "[auto_index]\nenabled = false\n",
+    );
+
+    let mut mcp = McpProc::spawn(dir.path());
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {}
+    }));
+
+    let search = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_search",
+            "arguments": {
+                "query": "config_no_auto_index_marker"
+            }
+        }
+    }));
+    let search_text = search["result"]["content"][0]["text"]
+        .as_str()
+        .expect("search text");
+    let search_json: Value = serde_json::from_str(search_text).expect("search json");
+    assert_eq!(search_json["meta"]["index_mode"], "scan");
+    assert!(!dir.path().join(".cgrep/manifest").exists());
+
+    mcp.stop();
+}
+
+#[test]
+fn mcp_search_refuses_to_bootstrap_oversized_scope() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/a.rs"),
+        "pub fn oversized_marker() {}\n",
+    );
+    write_file(
+        &dir.path().join("src/b.rs"),
+        "pub fn oversized_other() {}\n",
+    );
+    write_file(
+        &dir.path().join(".cgreprc.toml"),
+        "[auto_index]\nmax_repo_files = 1\n",
+    );
+
+    let mut mcp = McpProc::spawn(dir.path());
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {}
+    }));
+
+    let refused = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_search",
+            "arguments": { "query": "oversized_marker" }
+        }
+    }));
+    assert_eq!(refused["result"]["isError"], true);
+    let error_text = refused["result"]["content"][0]["text"]
+        .as_str()
+        .expect("error text");
+    let error: Value = serde_json::from_str(error_text).expect("structured error");
+    assert_eq!(error["error"], "auto_index_scope_too_large");
+    assert_eq!(error["limits"]["max_repo_files"], 1);
+    assert_eq!(error["estimate"]["files"], 2);
+    assert!(error["hint"].as_str().expect("hint").contains("`cwd`"));
+    assert!(!dir.path().join(".cgrep").exists());
+
+    let scan = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_search",
+            "arguments": { "query": "oversized_marker", "auto_index": false }
+        }
+    }));
+    assert!(scan["result"]["isError"].is_null());
+
+    mcp.stop();
+}
+
 #[test]
 fn mcp_unknown_tool_returns_is_error() {
     let dir = TempDir::new().expect("tempdir");
@@ -1186,7 +1567,7 @@ fn mcp_map_applies_default_depth_when_omitted() {
 }
 
 #[test]
-fn mcp_map_returns_error_when_output_exceeds_limit() {
+fn mcp_map_pages_output_over_limit_with_continuation() {
     let dir = TempDir::new().expect("tempdir");
     for idx in 0..500 {
         let path = dir.path().join(format!("src/capped_{idx:04}.txt"));
@@ -1197,7 +1578,7 @@ fn mcp_map_returns_error_when_output_exceeds_limit() {
         dir.path(),
         &[
             ("CGREP_MCP_TOOL_TIMEOUT_MS", "5000"),
-            ("CGREP_MCP_TOOL_MAX_OUTPUT_BYTES", "1024"),
+            ("CGREP_MCP_TOOL_MAX_OUTPUT_BYTES", "4096"),
         ],
     );
     let _ = mcp.call(json!({
@@ -1207,24 +1588,60 @@ fn mcp_map_returns_error_when_output_exceeds_limit() {
         "params": {}
     }));
 
-    let map = mcp.call(json!({
+    let mut arguments = json!({
+        "path": ".",
+        "depth": 2,
+        "cwd": dir.path().to_string_lossy().to_string()
+    });
+    let mut paths = Vec::new();
+    for id in 2..100 {
+        let map = mcp.call(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": "cgrep_map", "arguments": arguments.clone() }
+        }));
+        assert!(map["result"]["isError"].is_null(), "{map}");
+        let text = map["result"]["content"][0]["text"]
+            .as_str()
+            .expect("map text");
+        assert!(text.len() <= 4096, "page of {} bytes", text.len());
+        let page: Value = serde_json::from_str(text).expect("map json");
+        paths.extend(
+            page["entries"]
+                .as_array()
+                .expect("entries")
+                .iter()
+                .map(|entry| entry["path"].as_str().unwrap_or_default().to_string()),
+        );
+        let Some(note) = map["result"]["content"][1]["text"].as_str() else {
+            break;
+        };
+        let token = note.split('"').nth(1).expect("continuation token in note");
+        arguments["continuation"] = json!(token);
+    }
+    assert_eq!(paths.len(), 500);
+    assert_eq!(
+        paths.first().map(String::as_str),
+        Some("src/capped_0000.txt")
+    );
+    assert_eq!(
+        paths.last().map(String::as_str),
+        Some("src/capped_0499.txt")
+    );
+
+    arguments["depth"] = json!(1);
+    let mismatched = mcp.call(json!({
         "jsonrpc": "2.0",
-        "id": 2,
+        "id": 100,
         "method": "tools/call",
-        "params": {
-            "name": "cgrep_map",
-            "arguments": {
-                "path": ".",
-                "depth": 2,
-                "cwd": dir.path().to_string_lossy().to_string()
-            }
-        }
+        "params": { "name": "cgrep_map", "arguments": arguments }
     }));
-    assert_eq!(map["result"]["isError"], true);
-    assert!(map["result"]["content"][0]["text"]
+    assert_eq!(mismatched["result"]["isError"], true);
+    assert!(mismatched["result"]["content"][0]["text"]
         .as_str()
         .unwrap_or_default()
-        .contains("output exceeded"));
+        .contains("continuation token does not match"));
 
     mcp.stop();
 }
@@ -1457,3 +1874,381 @@ fn mcp_map_root_contract_is_stable() {
 
     mcp.stop();
 }
+
+#[test]
+fn mcp_search_and_symbols_return_partial_results_at_deadline() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn partial_marker() {}\n",
+    );
+    let cwd = dir.path().to_string_lossy().to_string();
+    let mut mcp = McpProc::spawn(dir.path());
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {}
+    }));
+
+    let complete = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_search",
+            "arguments": { "query": "partial_marker", "cwd": cwd }
+        }
+    }));
+    assert_ne!(complete["result"]["isError"], true);
+    assert_eq!(
+        complete["result"]["content"].as_array().map(Vec::len),
+        Some(1)
+    );
+
+    for (id, name, query_key) in [(3, "cgrep_search", "query"), (4, "cgrep_symbols", "name")] {
+        let partial = mcp.call(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {
+                "name": name,
+                "arguments": { query_key: "partial_marker", "cwd": cwd, "timeout_ms": 0 }
+            }
+        }));
+        assert_ne!(partial["result"]["isError"], true, "{name}: {partial}");
+        let content = partial["result"]["content"].as_array().expect("content");
+        let text = content[0]["text"].as_str().expect("text");
+        let payload: Value = serde_json::from_str(text).expect("json payload");
+        if name == "cgrep_search" {
+            assert_eq!(payload["meta"]["timed_out"], true);
+        }
+        assert!(content[1]["text"]
+            .as_str()
+            .unwrap_or_default()
+            .starts_with("Partial results"));
+    }
+
+    mcp.stop();
+}
+
+#[test]
+fn mcp_ping_is_answered_while_tool_call_runs() {
+    let dir = TempDir::new().expect("tempdir");
+    for idx in 0..500 {
+        write_file(
+            &dir.path().join(format!("src/mod_{idx:03}.rs")),
+            "pub fn concurrent_marker() {}\n",
+        );
+    }
+    let cwd = dir.path().to_string_lossy().to_string();
+    let mut mcp = McpProc::spawn(dir.path());
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {}
+    }));
+
+    for req in [
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": {
+                "name": "cgrep_search",
+                "arguments": {
+                    "query": "concurrent_marker",
+                    "cwd": cwd,
+                    "auto_index": false,
+                    "no_index": true
+                }
+            }
+        }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "ping" }),
+    ] {
+        writeln!(mcp.stdin, "{}", req).expect("write req");
+    }
+    mcp.stdin.flush().expect("flush");
+
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        let mut line = String::new();
+        mcp.stdout.read_line(&mut line).expect("read resp");
+        let resp: Value = serde_json::from_str(&line).expect("parse resp");
+        if resp["id"] == 2 {
+            assert_ne!(resp["result"]["isError"], true, "{resp}");
+        }
+        ids.push(resp["id"].clone());
+    }
+    assert_eq!(ids, [json!(3), json!(2)]);
+
+    mcp.stop();
+}
+
+#[test]
+fn mcp_roots_scope_tool_calls_and_prewarm_the_index() {
+    let server_dir = TempDir::new().expect("server tempdir");
+    let workspace = TempDir::new().expect("workspace tempdir");
+    write_file(
+        &workspace.path().join("src/lib.rs"),
+        "pub fn roots_marker() {}\n",
+    );
+    let root = workspace.path().canonicalize().expect("canonical root");
+    let mut mcp = McpProc::spawn(server_dir.path());
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": { "capabilities": { "roots": { "listChanged": true } } }
+    }));
+
+    // The server asks for roots once initialized.
+    let roots_request = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/initialized"
+    }));
+    assert_eq!(roots_request["method"], "roots/list");
+    writeln!(
+        mcp.stdin,
+        "{}",
+        json!({
+            "jsonrpc": "2.0",
+            "id": roots_request["id"],
+            "result": {
+                "roots": [{ "uri": format!("file://{}", root.display()), "name": "workspace" }]
+            }
+        })
+    )
+    .expect("write roots");
+    mcp.stdin.flush().expect("flush");
+
+    let meta = root.join(".cgrep/meta.json");
+    for _ in 0..200 {
+        if meta.exists() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(meta.exists(), "roots are indexed in the background");
+
+    let search = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_search",
+            "arguments": { "query": "roots_marker" }
+        }
+    }));
+    assert_ne!(search["result"]["isError"], true, "{search}");
+    let text = search["result"]["content"][0]["text"]
+        .as_str()
+        .expect("text");
+    let payload: Value = serde_json::from_str(text).expect("json2");
+    assert!(resolved_paths(&payload)
+        .iter()
+        .any(|path| path.ends_with("src/lib.rs")));
+
+    let outside = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_search",
+            "arguments": {
+                "query": "roots_marker",
+                "cwd": server_dir.path().to_string_lossy()
+            }
+        }
+    }));
+    assert_eq!(outside["result"]["isError"], true);
+    assert!(outside["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap_or_default()
+        .contains("outside the client's workspace roots"));
+
+    mcp.stop();
+}
+
+#[test]
+fn mcp_default_root_comes_from_handshake_config_and_set_root() {
+    let server_dir = TempDir::new().expect("server tempdir");
+    let first = TempDir::new().expect("first tempdir");
+    let second = TempDir::new().expect("second tempdir");
+    write_file(&first.path().join("src/a.rs"), "pub fn first_marker() {}\n");
+    write_file(
+        &second.path().join("src/b.rs"),
+        "pub fn second_marker() {}\n",
+    );
+    let first_root = first.path().canonicalize().expect("canonical first");
+    let second_root = second.path().canonicalize().expect("canonical second");
+    write_file(
+        &server_dir.path().join(".cgreprc.toml"),
+        &format!(
+            "[mcp]\ncwd_fallback = false\n\n[mcp.client_roots]\n\"test-host\" = {:?}\n",
+            second_root.display().to_string()
+        ),
+    );
+    let search = |mcp: &mut McpProc, id: u64, query: &str| {
+        mcp.call(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {
+                "name": "cgrep_search",
+                "arguments": { "query": query, "auto_index": false }
+            }
+        }))
+    };
+    let found = |resp: &Value, file: &str| {
+        assert_ne!(resp["result"]["isError"], true, "{resp}");
+        let text = resp["result"]["content"][0]["text"].as_str().expect("text");
+        let payload: Value = serde_json::from_str(text).expect("json2");
+        resolved_paths(&payload)
+            .iter()
+            .any(|path| path.ends_with(file))
+    };
+
+    // The handshake root wins over the client's configured root.
+    let mut mcp = McpProc::spawn(server_dir.path());
+    let init = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "clientInfo": { "name": "test-host", "version": "1.0" },
+            "_meta": { "cgrep/root": format!("file://{}", first_root.display()) }
+        }
+    }));
+    let root = &init["result"]["_meta"]["cgrep/root"];
+    assert_eq!(root["path"], first_root.display().to_string());
+    assert_eq!(root["source"], "initialize");
+    assert!(found(&search(&mut mcp, 2, "first_marker"), "src/a.rs"));
+
+    let set = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "tools/call",
+        "params": {
+            "name": "cgrep_set_root",
+            "arguments": { "path": second_root.display().to_string() }
+        }
+    }));
+    assert_ne!(set["result"]["isError"], true, "{set}");
+    let summary: Value =
+        serde_json::from_str(set["result"]["content"][0]["text"].as_str().expect("text"))
+            .expect("summary json");
+    assert_eq!(summary["root"], second_root.display().to_string());
+    assert_eq!(summary["previous"], first_root.display().to_string());
+    assert!(found(&search(&mut mcp, 4, "second_marker"), "src/b.rs"));
+    mcp.stop();
+
+    // Without a handshake, the client's configured root applies.
+    let mut mcp = McpProc::spawn(server_dir.path());
+    let init = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": { "clientInfo": { "name": "test-host" } }
+    }));
+    let root = &init["result"]["_meta"]["cgrep/root"];
+    assert_eq!(root["path"], second_root.display().to_string());
+    assert_eq!(root["source"], "client_roots");
+    mcp.stop();
+
+    // An unknown client has no root, and `cwd_fallback = false` refuses.
+    let mut mcp = McpProc::spawn(server_dir.path());
+    let _ = mcp.call(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": { "clientInfo": { "name": "other-host" } }
+    }));
+    let refused = search(&mut mcp, 2, "first_marker");
+    assert_eq!(refused["result"]["isError"], true);
+    assert!(refused["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap_or_default()
+        .contains("cgrep_set_root"));
+    mcp.stop();
+}
+
+#[test]
+fn mcp_auto_index_state_is_per_session_and_watchers_are_capped() {
+    let server_dir = TempDir::new().expect("server tempdir");
+    let first = TempDir::new().expect("first tempdir");
+    let second = TempDir::new().expect("second tempdir");
+    for (dir, marker) in [(&first, "first_marker"), (&second, "second_marker")] {
+        write_file(
+            &dir.path().join("src/lib.rs"),
+            &format!("pub fn {marker}() {{}}\n"),
+        );
+        let mut index_cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        index_cmd
+            .current_dir(dir.path())
+            .args(["index", "--embeddings", "off"])
+            .assert()
+            .success();
+    }
+    let cwd = |dir: &TempDir| dir.path().to_string_lossy().to_string();
+    let mut mcp = McpProc::spawn_with_env(server_dir.path(), &[("CGREP_MCP_MAX_WATCHERS", "1")]);
+    let initialize = |mcp: &mut McpProc, id: u64| {
+        let _ = mcp.call(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "initialize",
+            "params": {}
+        }));
+    };
+    let call = |mcp: &mut McpProc, id: u64, name: &str, args: Value, session: Option<&str>| {
+        let mut params = json!({ "name": name, "arguments": args });
+        if let Some(session) = session {
+            params["_meta"] = json!({ "cgrep/session": session });
+        }
+        let resp = mcp.call(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": params
+        }));
+        assert_ne!(resp["result"]["isError"], true, "{resp}");
+        let text = resp["result"]["content"][0]["text"]
+            .as_str()
+            .expect("text")
+            .to_string();
+        serde_json::from_str::<Value>(&text).expect("json")
+    };
+    let watch_state = |mcp: &mut McpProc, id: u64, dir: &TempDir, session: Option<&str>| {
+        call(mcp, id, "cgrep_status", json!({ "cwd": cwd(dir) }), session)["auto_index"].clone()
+    };
+    initialize(&mut mcp, 1);
+
+    call(
+        &mut mcp,
+        2,
+        "cgrep_search",
+        json!({ "query": "first_marker", "cwd": cwd(&first) }),
+        None,
+    );
+    assert!(watch_state(&mut mcp, 3, &first, None).is_object());
+    // Another client session of the same server starts from scratch.
+    assert!(watch_state(&mut mcp, 4, &first, Some("other-client")).is_null());
+
+    // With room for one watcher, the second scope evicts the first.
+    call(
+        &mut mcp,
+        5,
+        "cgrep_search",
+        json!({ "query": "second_marker", "cwd": cwd(&second) }),
+        None,
+    );
+    assert!(watch_state(&mut mcp, 6, &second, None).is_object());
+    assert!(watch_state(&mut mcp, 7, &first, None).is_null());
+
+    // A new `initialize` ends the session and releases its state.
+    initialize(&mut mcp, 8);
+    assert!(watch_state(&mut mcp, 9, &second, None).is_null());
+
+    mcp.stop();
+}
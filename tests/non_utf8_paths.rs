@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use tempfile::TempDir;
+
+#[test]
+fn non_utf8_file_names_are_searchable_and_readable() {
+    let dir = TempDir::new().expect("tempdir");
+    let name = OsStr::from_bytes(b"caf\xE9.rs");
+    if fs::write(dir.path().join(name), "pub fn latin1_marker() {}\n").is_err() {
+        // Some filesystems reject non-UTF-8 names outright.
+        return;
+    }
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["--format", "json2", "search", "latin1_marker"])
+        .output()
+        .expect("run search");
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).expect("search json");
+    let path = payload["results"][0]["path"]
+        .as_str()
+        .expect("result path")
+        .to_string();
+    assert_eq!(path, "caf\\xE9.rs");
+    assert_eq!(payload["results"][0]["display_path"], "caf\u{FFFD}.rs");
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["--format", "json2", "read", &path])
+        .output()
+        .expect("run read");
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).expect("read json");
+    assert_eq!(payload["result"]["path"], "caf\\xE9.rs");
+    assert_eq!(payload["result"]["display_path"], "caf\u{FFFD}.rs");
+    assert!(payload["result"]["content"]
+        .as_str()
+        .unwrap_or_default()
+        .contains("latin1_marker"));
+}
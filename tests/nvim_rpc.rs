@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+fn write_file(path: &std::path::Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn fixstr(text: &str) -> Vec<u8> {
+    assert!(text.len() < 32);
+    let mut out = vec![0xa0 | text.len() as u8];
+    out.extend(text.as_bytes());
+    out
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+fn rpc(dir: &TempDir, input: Vec<u8>) -> Vec<u8> {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .arg("nvim-rpc")
+        .write_stdin(input)
+        .output()
+        .expect("run nvim-rpc");
+    assert!(output.status.success());
+    output.stdout
+}
+
+#[test]
+fn nvim_rpc_answers_ping_requests() {
+    let dir = TempDir::new().expect("tempdir");
+    // [0, 7, "ping", []]
+    let mut input = vec![0x94, 0x00, 0x07];
+    input.extend(fixstr("ping"));
+    input.push(0x90);
+
+    // [1, 7, nil, "pong"]
+    let mut expected = vec![0x94, 0x01, 0x07, 0xc0];
+    expected.extend(fixstr("pong"));
+    assert_eq!(rpc(&dir, input), expected);
+}
+
+#[test]
+fn nvim_rpc_search_request_and_streamed_notification() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn refresh_token() {}\n",
+    );
+
+    // [0, 1, "search", [{"query": "refresh_token", "auto_index": false}]]
+    let mut input = vec![0x94, 0x00, 0x01];
+    input.extend(fixstr("search"));
+    input.extend([0x91, 0x82]);
+    input.extend(fixstr("query"));
+    input.extend(fixstr("refresh_token"));
+    input.extend(fixstr("auto_index"));
+    input.push(0xc2);
+    // [2, "definition", [{"name": "refresh_token", "token": 9, "auto_index": false}]]
+    input.extend([0x93, 0x02]);
+    input.extend(fixstr("definition"));
+    input.extend([0x91, 0x83]);
+    input.extend(fixstr("name"));
+    input.extend(fixstr("refresh_token"));
+    input.extend(fixstr("token"));
+    input.push(0x09);
+    input.extend(fixstr("auto_index"));
+    input.push(0xc2);
+
+    let stdout = rpc(&dir, input);
+    // Response header [1, 1, nil, {...}] with the match path in the results.
+    assert!(contains(&stdout, &[0x94, 0x01, 0x01, 0xc0]));
+    assert!(contains(&stdout, &fixstr("src/lib.rs")));
+    // Streamed events call into the Lua module through nvim_exec_lua.
+    assert!(contains(&stdout, &fixstr("nvim_exec_lua")));
+    assert!(contains(&stdout, b"require('cgrep')._on_event(...)"));
+    assert!(contains(&stdout, &fixstr("results")));
+    assert!(contains(&stdout, &fixstr("done")));
+}
+
+#[test]
+fn nvim_rpc_answers_every_request_beyond_the_worker_pool() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn refresh_token() {}\n",
+    );
+
+    // More queries than workers: the rest queue and are answered in turn.
+    let mut input = Vec::new();
+    for msgid in 1..=10u8 {
+        // [0, msgid, "symbols", [{"name": "refresh", "auto_index": false}]]
+        input.extend([0x94, 0x00, msgid]);
+        input.extend(fixstr("symbols"));
+        input.extend([0x91, 0x82]);
+        input.extend(fixstr("name"));
+        input.extend(fixstr("refresh"));
+        input.extend(fixstr("auto_index"));
+        input.push(0xc2);
+    }
+
+    let stdout = rpc(&dir, input);
+    for msgid in 1..=10u8 {
+        assert!(
+            contains(&stdout, &[0x94, 0x01, msgid, 0xc0]),
+            "no response to request {msgid}"
+        );
+    }
+}
+
+fn position(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[test]
+fn streamed_hits_precede_the_final_output() {
+    let dir = TempDir::new().expect("tempdir");
+    for i in 1..=3 {
+        write_file(
+            &dir.path().join(format!("src/f{i}.rs")),
+            &format!("pub fn refresh_token_{i}() {{}}\n"),
+        );
+    }
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .env("CGREP_STREAM_HITS", "1")
+        .args(["--format", "json2", "--compact", "search", "refresh_token"])
+        .output()
+        .expect("run search");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8 stdout");
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4, "{stdout}");
+    for line in &lines[..3] {
+        let hit: serde_json::Value = serde_json::from_str(line).expect("hit json");
+        assert!(hit["hit"]["path"].as_str().is_some(), "{line}");
+    }
+    let payload: serde_json::Value = serde_json::from_str(lines[3]).expect("payload json");
+    assert_eq!(payload["results"].as_array().map(Vec::len), Some(3));
+}
+
+#[test]
+fn nvim_rpc_sends_result_batches_before_done() {
+    let dir = TempDir::new().expect("tempdir");
+    for i in 1..=3 {
+        write_file(
+            &dir.path().join(format!("src/f{i}.rs")),
+            &format!("pub fn refresh_token_{i}() {{}}\n"),
+        );
+    }
+
+    // [2, "search", [{"query": "refresh_token", "token": 5, "batch_size": 1, "auto_index": false}]]
+    let mut input = vec![0x93, 0x02];
+    input.extend(fixstr("search"));
+    input.extend([0x91, 0x84]);
+    input.extend(fixstr("query"));
+    input.extend(fixstr("refresh_token"));
+    input.extend(fixstr("token"));
+    input.push(0x05);
+    input.extend(fixstr("batch_size"));
+    input.push(0x01);
+    input.extend(fixstr("auto_index"));
+    input.push(0xc2);
+
+    let stdout = rpc(&dir, input);
+    let first_batch = position(&stdout, &fixstr("results")).expect("a results batch");
+    let done = position(&stdout, &fixstr("done")).expect("a done event");
+    assert!(first_batch < done);
+    // One hit per batch, each file once.
+    let batches = stdout
+        .windows(fixstr("results").len())
+        .filter(|window| *window == fixstr("results").as_slice())
+        .count();
+    assert_eq!(batches, 3);
+}
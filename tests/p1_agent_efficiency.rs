@@ -118,6 +118,180 @@ fn search_changed_filters_to_modified_files() {
     assert!(results.iter().all(|r| r["path"] == "src/a.rs"));
 }
 
+#[test]
+fn only_dirty_limits_search_and_symbols_to_uncommitted_files() {
+    let dir = TempDir::new().expect("tempdir");
+    init_git_repo(dir.path());
+    write_file(&dir.path().join("src/a.rs"), "pub fn needle() {}\n");
+    write_file(&dir.path().join("src/b.rs"), "pub fn needle() {}\n");
+    write_file(&dir.path().join("src/c.rs"), "pub fn needle() {}\n");
+    commit_all(dir.path(), "initial");
+
+    write_file(
+        &dir.path().join("src/a.rs"),
+        "pub fn needle() { let _ = 1; }\n",
+    );
+    write_file(&dir.path().join("src/new.rs"), "pub fn needle() {}\n");
+    run_git(dir.path(), &["add", "src/a.rs"]);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args([
+            "--format",
+            "json",
+            "search",
+            "needle",
+            "--no-index",
+            "--only-dirty",
+        ])
+        .assert()
+        .success();
+    let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    let mut paths: Vec<&str> = json
+        .as_array()
+        .expect("results")
+        .iter()
+        .filter_map(|r| r["path"].as_str())
+        .collect();
+    paths.sort();
+    paths.dedup();
+    assert_eq!(paths, vec!["src/a.rs", "src/new.rs"]);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["--format", "json", "symbols", "needle", "--only-dirty"])
+        .assert()
+        .success();
+    let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    assert_eq!(json.as_array().map(Vec::len), Some(2));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    cmd.current_dir(dir.path())
+        .args(["search", "needle", "--only-dirty", "--changed"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn from_patch_matches_only_added_lines() {
+    let dir = TempDir::new().expect("tempdir");
+    init_git_repo(dir.path());
+    write_file(
+        &dir.path().join("src/a.rs"),
+        "fn old() { needle(); }\nfn keep() {}\n",
+    );
+    write_file(&dir.path().join("src/b.rs"), "fn other() { needle(); }\n");
+    commit_all(dir.path(), "initial");
+
+    write_file(
+        &dir.path().join("src/a.rs"),
+        "fn old() { needle(); }\nfn keep() {}\nfn added() { needle(); }\n",
+    );
+    let diff = ProcessCommand::new("git")
+        .arg("-C")
+        .arg(dir.path())
+        .args(["diff"])
+        .output()
+        .expect("git diff");
+    let patch = dir.path().join("change.diff");
+    fs::write(&patch, &diff.stdout).expect("write patch");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path().join("src"))
+        .args(["--format", "json", "search", "needle", "--from-patch"])
+        .arg(&patch)
+        .assert()
+        .success();
+    let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+    let hits: Vec<(String, u64)> = json
+        .as_array()
+        .expect("results")
+        .iter()
+        .map(|r| {
+            (
+                r["path"].as_str().unwrap_or_default().to_string(),
+                r["line"].as_u64().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert_eq!(hits, vec![("a.rs".to_string(), 3)]);
+}
+
+fn commit_as(dir: &Path, author: &str, date: &str, message: &str) {
+    run_git(dir, &["add", "."]);
+    let output = ProcessCommand::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["commit", "--quiet", "-m", message])
+        .env("GIT_AUTHOR_NAME", author)
+        .env(
+            "GIT_AUTHOR_EMAIL",
+            format!("{}@example.com", author.to_lowercase()),
+        )
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .output()
+        .expect("run git commit");
+    assert!(output.status.success(), "git commit failed");
+}
+
+#[test]
+fn search_filters_by_commit_date_and_author() {
+    let dir = TempDir::new().expect("tempdir");
+    init_git_repo(dir.path());
+    write_file(
+        &dir.path().join("src/old.rs"),
+        "pub fn history_needle() {}
+",
+    );
+    commit_as(dir.path(), "Ada", "2020-03-01T12:00:00Z", "old");
+    write_file(
+        &dir.path().join("src/new.rs"),
+        "pub fn history_needle() {}
+",
+    );
+    commit_as(dir.path(), "Grace", "2024-06-01T12:00:00Z", "new");
+
+    let paths = |extra: &[&str]| -> Vec<String> {
+        let mut args = vec!["--format", "json2", "search", "history_needle"];
+        args.extend_from_slice(extra);
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd.current_dir(dir.path()).args(&args).assert().success();
+        let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+        let mut paths: Vec<String> = json["results"]
+            .as_array()
+            .expect("results")
+            .iter()
+            .map(|result| result["path"].as_str().expect("path").to_string())
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    };
+
+    assert_eq!(
+        paths(&["--no-index", "--modified-since", "2024-01-01"]),
+        ["src/new.rs"]
+    );
+    assert_eq!(paths(&["--no-index", "--author", "ADA"]), ["src/old.rs"]);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+    let metadata = fs::read_to_string(dir.path().join(".cgrep/metadata.json")).expect("metadata");
+    assert!(metadata.contains("grace@example.com"), "{metadata}");
+    assert_eq!(
+        paths(&["--modified-since", "2020-01-01", "--author", "grace"]),
+        ["src/new.rs"]
+    );
+    assert!(paths(&["--modified-since", "2030-01-01"]).is_empty());
+}
+
 #[test]
 fn symbols_and_references_honor_changed_filter() {
     let dir = TempDir::new().expect("tempdir");
@@ -163,6 +337,173 @@ fn symbols_and_references_honor_changed_filter() {
     assert!(refs.iter().all(|r| r["path"] == "src/a.rs"));
 }
 
+#[test]
+fn symbols_batch_returns_results_keyed_by_requested_name() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/a.rs"),
+        "pub fn load_config() {}\npub struct Parser;\n",
+    );
+    write_file(
+        &dir.path().join("src/b.py"),
+        "def flush_cache():\n    pass\n",
+    );
+    write_file(
+        &dir.path().join("names.txt"),
+        "# from stack trace\nflush_cache\n\n",
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args([
+            "--format",
+            "json",
+            "symbols",
+            "--name",
+            "load_config",
+            "--name",
+            "missing_fn",
+            "--names-file",
+            "names.txt",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    let keys: Vec<&str> = json
+        .as_object()
+        .expect("object")
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(keys.len(), 3);
+    assert_eq!(json["load_config"][0]["path"], "src/a.rs");
+    assert_eq!(json["flush_cache"][0]["path"], "src/b.py");
+    assert_eq!(json["missing_fn"], Value::Array(Vec::new()));
+}
+
+#[test]
+fn symbols_filter_by_parameter_and_return_types() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/load.rs"),
+        "use std::path::{Path, PathBuf};
+pub fn load(path: &Path, retries: usize) -> Result<Config> { todo!() }
+pub fn owned(path: PathBuf) -> Result<Config> { todo!() }
+pub fn name(path: &Path) -> String { todo!() }
+",
+    );
+    write_file(
+        &dir.path().join("src/io.ts"),
+        "export function read(path: Path): Result<Buffer> { return r; }
+",
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args([
+            "--format",
+            "json",
+            "symbols",
+            "--returns",
+            "Result<_>",
+            "--params",
+            "Path",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    let mut found: Vec<(&str, &str)> = json
+        .as_array()
+        .expect("array")
+        .iter()
+        .map(|r| {
+            (
+                r["name"].as_str().expect("name"),
+                r["signature"].as_str().expect("signature"),
+            )
+        })
+        .collect();
+    found.sort();
+    assert_eq!(
+        found,
+        [
+            ("load", "(&Path, usize) -> Result<Config>"),
+            ("read", "(Path) -> Result<Buffer>"),
+        ]
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["--format", "json", "symbols", "name", "--params", "usize"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    assert_eq!(json, Value::Array(Vec::new()));
+}
+
+#[test]
+fn symbols_sort_and_page_with_json2_meta() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/b.rs"),
+        "pub fn parse_header() {}\npub struct Parse;\npub fn parse() {}\n",
+    );
+    write_file(
+        &dir.path().join("src/a.rs"),
+        "pub fn reparse() {}\npub fn parse_body() {}\n",
+    );
+
+    let run = |extra: &[&str]| -> Value {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .args(["--format", "json2", "symbols", "parse"])
+            .args(extra)
+            .assert()
+            .success();
+        serde_json::from_slice(&assert.get_output().stdout).expect("json2")
+    };
+    let names = |json: &Value| -> Vec<String> {
+        json["results"]
+            .as_array()
+            .expect("results")
+            .iter()
+            .map(|r| r["name"].as_str().expect("name").to_string())
+            .collect()
+    };
+
+    let by_path = run(&[]);
+    let schema = cgrep::schema::schema("symbols").expect("embedded schema");
+    assert!(cgrep::schema::validate(&schema, &by_path).is_empty());
+    assert_eq!(by_path["meta"]["sort"], "path");
+    assert_eq!(by_path["meta"]["total"], 5);
+    assert_eq!(
+        names(&by_path),
+        ["reparse", "parse_body", "parse_header", "Parse", "parse"]
+    );
+
+    assert_eq!(
+        names(&run(&["--sort", "score"])),
+        ["parse", "Parse", "parse_body", "parse_header", "reparse"]
+    );
+
+    let page = run(&["--sort", "name", "--offset", "1", "--limit", "2"]);
+    assert_eq!(names(&page), ["parse", "parse_body"]);
+    assert_eq!(page["meta"]["offset"], 1);
+    assert_eq!(page["meta"]["limit"], 2);
+    assert_eq!(page["meta"]["returned"], 2);
+    assert_eq!(page["meta"]["next_offset"], 3);
+    let last = run(&["--sort", "name", "--offset", "3", "--limit", "2"]);
+    assert_eq!(names(&last), ["parse_header", "reparse"]);
+    assert!(last["meta"].get("next_offset").is_none());
+}
+
 #[test]
 fn json2_path_alias_and_boilerplate_suppression_work() {
     let dir = TempDir::new().expect("tempdir");
@@ -202,3 +543,251 @@ fn json2_path_alias_and_boilerplate_suppression_work() {
         assert_eq!(result["snippet"], "[boilerplate suppressed]");
     }
 }
+
+#[test]
+fn summarize_files_aggregates_matches_per_file() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/retry.rs"),
+        "fn backoff() {\n    wait_token();\n    wait_token();\n}\n\nfn give_up() {\n    wait_token();\n}\n\nfn extra() {\n    wait_token();\n}\n",
+    );
+    write_file(
+        &dir.path().join("src/other.rs"),
+        "fn helper() { wait_token(); }\n",
+    );
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args([
+            "--format",
+            "json2",
+            "search",
+            "wait_token",
+            "--summarize-files",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+
+    assert_eq!(json["results"].as_array().map(Vec::len), Some(0));
+    let files = json["files"].as_array().expect("files");
+    assert_eq!(files.len(), 2);
+    let retry = files
+        .iter()
+        .find(|file| file["path"] == "src/retry.rs")
+        .expect("retry.rs summary");
+    assert_eq!(retry["match_count"], 4);
+    assert_eq!(
+        retry["symbols"],
+        serde_json::json!(["backoff", "give_up", "extra"])
+    );
+    assert_eq!(
+        retry["line_ranges"],
+        serde_json::json!([[2, 3], [7, 7], [11, 11]])
+    );
+    assert!(!retry["result_ids"].as_array().expect("ids").is_empty());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["search", "wait_token", "--summarize-files"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    assert!(stdout.contains("src/retry.rs  4 matches  lines 2-3, 7, 11"));
+    assert!(stdout.contains("symbols: backoff, give_up, extra"));
+}
+
+#[test]
+fn json2_meta_counts_facets_across_retrieved_results() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/api/v1/routes.rs"),
+        "fn route() { facet_token(); }\n",
+    );
+    write_file(
+        &dir.path().join("src/api/handlers.rs"),
+        "fn handle() { facet_token(); }\n",
+    );
+    write_file(
+        &dir.path().join("scripts/run.py"),
+        "def run():\n    facet_token()\n",
+    );
+    write_file(&dir.path().join("NOTES.txt"), "facet_token\n");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args([
+            "--format",
+            "json2",
+            "search",
+            "facet_token",
+            "--no-index",
+            "--max-total-chars",
+            "1",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+
+    let facets = &json["meta"]["facets"];
+    assert_eq!(
+        facets["languages"],
+        serde_json::json!([
+            { "value": "rust", "count": 2 },
+            { "value": "other", "count": 1 },
+            { "value": "python", "count": 1 }
+        ])
+    );
+    assert_eq!(
+        facets["directories"],
+        serde_json::json!([
+            { "value": "src/api", "count": 2 },
+            { "value": ".", "count": 1 },
+            { "value": "scripts", "count": 1 }
+        ])
+    );
+    assert_eq!(
+        facets["doc_types"],
+        serde_json::json!([{ "value": "file", "count": 4 }])
+    );
+    // Facets cover results the output budget dropped.
+    assert!(json["results"].as_array().expect("results").len() < 4);
+}
+
+#[test]
+fn symbols_filter_by_decorator() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("tests/conftest.py"),
+        "import pytest\n\n\n@pytest.fixture(scope=\"module\")\ndef client():\n    return 1\n\n\ndef helper():\n    return 2\n",
+    );
+    write_file(
+        &dir.path().join("src/main.rs"),
+        "#[tokio::main]\nasync fn main() {}\n\nfn client_id() {}\n",
+    );
+
+    let run = |args: &[&str]| -> Value {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .args(["--format", "json2", "symbols"])
+            .args(args)
+            .assert()
+            .success();
+        serde_json::from_slice(&assert.get_output().stdout).expect("json2")
+    };
+
+    let fixtures = run(&["--decorated-with", "pytest.fixture"]);
+    let schema = cgrep::schema::schema("symbols").expect("embedded schema");
+    assert!(cgrep::schema::validate(&schema, &fixtures).is_empty());
+    assert_eq!(fixtures["meta"]["query"], "--decorated-with pytest.fixture");
+    assert_eq!(fixtures["results"].as_array().expect("results").len(), 1);
+    assert_eq!(fixtures["results"][0]["name"], "client");
+    assert_eq!(
+        fixtures["results"][0]["decorators"],
+        serde_json::json!(["pytest.fixture"])
+    );
+
+    // A trailing path segment matches, and a name narrows further.
+    let entry = run(&["main", "--decorated-with", "main"]);
+    assert_eq!(entry["results"][0]["path"], "src/main.rs");
+    assert_eq!(
+        entry["results"][0]["decorators"],
+        serde_json::json!(["tokio::main"])
+    );
+    let none = run(&["client", "--decorated-with", "tokio::main"]);
+    assert_eq!(none["meta"]["total"], 0);
+}
+
+#[test]
+fn metrics_rank_functions_and_filter_symbols() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "pub fn simple() {}\n\npub fn busy(a: u8, b: u8) -> u8 {\n    if a > b && b > 0 {\n        for _ in 0..a {\n            if b == 2 {\n                return 1;\n            }\n        }\n    }\n    0\n}\n",
+    );
+    write_file(
+        &dir.path().join("app.py"),
+        "def route(request, retries):\n    if request.ok or retries:\n        return 1\n    return 0\n",
+    );
+    let run = |args: &[&str]| -> Value {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+            .args(args)
+            .assert()
+            .success();
+        serde_json::from_slice(&assert.get_output().stdout).expect("json")
+    };
+    let ranked = |report: &Value| -> Vec<(String, u64)> {
+        report["symbols"]
+            .as_array()
+            .expect("symbols")
+            .iter()
+            .map(|row| {
+                (
+                    row["name"].as_str().expect("name").to_string(),
+                    row["complexity"].as_u64().expect("complexity"),
+                )
+            })
+            .collect()
+    };
+
+    // Parsed from source without an index, then read back from the index.
+    let scanned = run(&["--format", "json", "metrics"]);
+    assert_eq!(
+        ranked(&scanned),
+        [
+            ("busy".to_string(), 5),
+            ("route".to_string(), 3),
+            ("simple".to_string(), 1)
+        ]
+    );
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    cmd.current_dir(dir.path())
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+    let indexed = run(&[
+        "--format", "json", "metrics", "--top", "1", "--by", "nesting",
+    ]);
+    assert_eq!(indexed["total"], 3);
+    assert_eq!(indexed["symbols"][0]["name"], "busy");
+    assert_eq!(indexed["symbols"][0]["nesting"], 3);
+    assert_eq!(indexed["symbols"][0]["lines"], 10);
+
+    let filtered = run(&[
+        "--format",
+        "json2",
+        "symbols",
+        "--metric",
+        "complexity>=3",
+        "--metric",
+        "params=2",
+    ]);
+    let schema = cgrep::schema::schema("symbols").expect("embedded schema");
+    assert!(cgrep::schema::validate(&schema, &filtered).is_empty());
+    assert_eq!(
+        filtered["meta"]["query"],
+        "--metric complexity>=3 --metric params=2"
+    );
+    let names: Vec<&str> = filtered["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .map(|result| result["name"].as_str().expect("name"))
+        .collect();
+    assert_eq!(names, ["route", "busy"]);
+    assert_eq!(filtered["results"][0]["metrics"]["complexity"], 3);
+}
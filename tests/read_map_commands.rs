@@ -61,6 +61,40 @@ fn read_large_file_returns_outline_mode() {
     assert!(body.contains("function function_0"));
 }
 
+#[test]
+fn read_outline_nests_symbols_and_expands_one() {
+    let dir = TempDir::new().expect("tempdir");
+    let mut content = String::from("mod store {\n    pub fn get() -> i32 {\n        1\n    }\n}\n");
+    for i in 0..450 {
+        content.push_str(&format!("pub fn filler_{i}() -> i32 {{ {i} }}\n"));
+    }
+    write_file(&dir.path().join("src/large.rs"), &content);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["--format", "json", "read", "src/large.rs"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    assert_eq!(json["mode"], "outline");
+    assert_eq!(json["outline"][0]["name"], "store");
+    assert_eq!(json["outline"][0]["lines"], 5);
+    assert_eq!(json["outline"][0]["children"][0]["name"], "get");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["read", "src/large.rs", "--expand", "store.get"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    assert!(stdout.contains("[2-4]   function get (3 lines"));
+    assert!(stdout.contains("    3:         1"));
+    assert!(!stdout.contains("filler_0() -> i32 { 0 }"));
+}
+
 #[test]
 fn read_section_line_range_returns_subset() {
     let dir = TempDir::new().expect("tempdir");
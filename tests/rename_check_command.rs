@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+fn write_file(path: &std::path::Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn rename_check(root: &std::path::Path, old: &str, new: &str) -> Value {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(root)
+        .args(["--format", "json", "rename-check", old, new])
+        .assert()
+        .success();
+    serde_json::from_slice(&assert.get_output().stdout).expect("json")
+}
+
+#[test]
+fn rename_check_flags_collisions_and_shadowing_by_scope() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("app.py"),
+        "def load(path):\n    settings = read(path)\n    return settings\n\n\ndef read(path):\n    return path.upper()\n\n\ndef fetch(url):\n    return url\n",
+    );
+    write_file(
+        &dir.path().join("net.py"),
+        "def settings():\n    return {}\n",
+    );
+
+    let report = rename_check(dir.path(), "read", "settings");
+    assert_eq!(report["safe"], false);
+    let references = report["references"].as_array().expect("references");
+    let sites: Vec<(u64, bool)> = references
+        .iter()
+        .map(|site| (site["line"].as_u64().unwrap(), site["definition"] == true))
+        .collect();
+    assert_eq!(sites, vec![(2, false), (6, true)]);
+    assert_eq!(references[0]["scope"], serde_json::json!(["load"]));
+
+    let conflicts = report["conflicts"].as_array().expect("conflicts");
+    let kinds: Vec<(&str, &str, u64)> = conflicts
+        .iter()
+        .map(|conflict| {
+            (
+                conflict["kind"].as_str().unwrap(),
+                conflict["path"].as_str().unwrap(),
+                conflict["line"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("shadowing", "app.py", 2),
+            ("shadowing", "app.py", 3),
+            ("existing", "net.py", 1),
+        ]
+    );
+
+    let report = rename_check(dir.path(), "fetch", "read");
+    assert_eq!(report["safe"], false);
+    let collision = report["conflicts"]
+        .as_array()
+        .expect("conflicts")
+        .iter()
+        .find(|conflict| conflict["kind"] == "collision")
+        .expect("collision");
+    assert_eq!(collision["line"], 6);
+    assert_eq!(collision["old_line"], 10);
+
+    let report = rename_check(dir.path(), "fetch", "download");
+    assert_eq!(report["safe"], true);
+    assert_eq!(report["references"].as_array().map(Vec::len), Some(1));
+}
+
+#[test]
+fn rename_check_rejects_invalid_names() {
+    let dir = TempDir::new().expect("tempdir");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    cmd.current_dir(dir.path())
+        .args(["rename-check", "load", "load-config"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command as ProcessCommand;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn run_git(dir: &Path, args: &[&str]) {
+    let output = ProcessCommand::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .expect("run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn commit_all(dir: &Path, message: &str) {
+    run_git(dir, &["add", "."]);
+    run_git(dir, &["commit", "--quiet", "-m", message]);
+}
+
+fn review(dir: &Path, args: &[&str]) -> Value {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir)
+        .args(["--format", "json2", "review"])
+        .args(args)
+        .assert()
+        .success();
+    serde_json::from_slice(&assert.get_output().stdout).expect("json")
+}
+
+#[test]
+fn review_lists_touched_symbols_with_definitions_and_callers() {
+    let dir = TempDir::new().expect("tempdir");
+    run_git(dir.path(), &["init", "-q"]);
+    run_git(dir.path(), &["config", "user.email", "test@example.com"]);
+    run_git(dir.path(), &["config", "user.name", "test"]);
+    write_file(
+        &dir.path().join("app.py"),
+        "def load(path):\n    return path\n\n\ndef save(path, data):\n    return data\n",
+    );
+    write_file(
+        &dir.path().join("cli.py"),
+        "from app import load\n\n\ndef main():\n    return load(\"config\")\n",
+    );
+    commit_all(dir.path(), "base");
+    write_file(
+        &dir.path().join("app.py"),
+        "def load(path):\n    return path.strip()\n\n\ndef save(path, data):\n    return data\n",
+    );
+    commit_all(dir.path(), "change");
+
+    let json = review(dir.path(), &["-u", "HEAD~1..HEAD"]);
+    let schema = cgrep::schema::schema("review").expect("embedded schema");
+    assert!(cgrep::schema::validate(&schema, &json).is_empty());
+    assert_eq!(json["meta"]["range"], "HEAD~1..HEAD");
+    assert_eq!(json["meta"]["files"], 1);
+
+    let file = &json["files"][0];
+    assert_eq!(file["path"], "app.py");
+    assert_eq!(file["hunks"][0]["new_start"], 2);
+    assert_eq!(file["hunks"][0]["symbols"], serde_json::json!(["load"]));
+
+    let symbols = file["symbols"].as_array().expect("symbols");
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0]["name"], "load");
+    assert_eq!(
+        symbols[0]["definition"],
+        "def load(path):\n    return path.strip()"
+    );
+    assert_eq!(symbols[0]["callers_total"], 1);
+    assert_eq!(symbols[0]["callers"][0]["path"], "cli.py");
+    assert_eq!(symbols[0]["callers"][0]["line"], 5);
+
+    // A single revision compares with the working tree.
+    write_file(
+        &dir.path().join("app.py"),
+        "def load(path):\n    return path.strip()\n\n\ndef save(path, data):\n    return dict(data)\n",
+    );
+    let json = review(dir.path(), &[]);
+    assert_eq!(json["meta"]["range"], "HEAD");
+    assert_eq!(
+        json["files"][0]["hunks"][0]["symbols"],
+        serde_json::json!(["save"])
+    );
+    assert_eq!(json["files"][0]["symbols"][0]["callers_total"], 0);
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn routes_json2(dir: &Path, args: &[&str]) -> Value {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir)
+        .args(["--format", "json2", "routes"])
+        .args(args)
+        .assert()
+        .success();
+    serde_json::from_slice(&assert.get_output().stdout).expect("json2")
+}
+
+fn fixture() -> TempDir {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("server/app.rs"),
+        "use axum::{routing::get, Router};\n\nfn app() -> Router {\n    Router::new()\n        .route(\"/users/{id}\", get(handlers::show_user).delete(handlers::remove_user))\n}\n",
+    );
+    write_file(
+        &dir.path().join("server/handlers.rs"),
+        "pub async fn show_user() {}\n\npub async fn remove_user() {}\n",
+    );
+    write_file(
+        &dir.path().join("web/server.js"),
+        "const express = require('express');\nconst app = express();\napp.post('/login', login);\nfunction login(req, res) {}\n",
+    );
+    dir
+}
+
+#[test]
+fn routes_list_endpoints_with_handler_locations() {
+    let dir = fixture();
+    let json = routes_json2(dir.path(), &[]);
+    let schema = cgrep::schema::schema("routes").expect("embedded schema");
+    let errors = cgrep::schema::validate(&schema, &json);
+    assert!(errors.is_empty(), "schema errors: {errors:?}\n{json}");
+
+    let routes: Vec<String> = json["routes"]
+        .as_array()
+        .expect("routes")
+        .iter()
+        .map(|route| {
+            format!(
+                "{} {} {} {}:{}",
+                route["method"].as_str().unwrap(),
+                route["route"].as_str().unwrap(),
+                route["framework"].as_str().unwrap(),
+                route["handler_path"].as_str().unwrap_or("-"),
+                route["handler_line"].as_u64().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        routes,
+        [
+            "POST /login express web/server.js:4",
+            "DELETE /users/{id} axum server/handlers.rs:3",
+            "GET /users/{id} axum server/handlers.rs:1",
+        ]
+    );
+    assert_eq!(json["meta"]["total"], 3);
+}
+
+#[test]
+fn routes_filter_by_concrete_path_and_method() {
+    let dir = fixture();
+    let json = routes_json2(dir.path(), &["/users/42", "-X", "get"]);
+    let routes = json["routes"].as_array().expect("routes");
+    assert_eq!(routes.len(), 1, "{json}");
+    assert_eq!(routes[0]["handler"], "handlers::show_user");
+    assert_eq!(json["meta"]["query"], "/users/42");
+    assert_eq!(json["meta"]["method"], "GET");
+}
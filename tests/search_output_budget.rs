@@ -81,3 +81,104 @@ fn max_total_chars_sets_json2_budget_meta() {
     assert_eq!(json["meta"]["max_total_chars"], 80);
     assert!(json["meta"]["truncated"].is_boolean());
 }
+
+#[test]
+fn symbol_header_context_shows_enclosing_signatures() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/cache.rs"),
+        "pub struct Cache;\n\npub mod cache {\n    pub fn refresh(key: &str) -> bool {\n        let fresh = key.len() > 2;\n        header_needle(fresh)\n    }\n}\n\nfn header_needle(value: bool) -> bool {\n    value\n}\n",
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args([
+            "--format",
+            "json2",
+            "search",
+            "header_needle(fresh)",
+            "--no-index",
+            "-C",
+            "symbol-header",
+        ])
+        .assert()
+        .success();
+    let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json2");
+    let result = json["results"]
+        .as_array()
+        .and_then(|results| results.iter().find(|r| r["line"] == 6))
+        .expect("result on line 6");
+    assert_eq!(
+        result["symbol_header"],
+        serde_json::json!([
+            { "line": 3, "text": "pub mod cache {" },
+            { "line": 4, "text": "    pub fn refresh(key: &str) -> bool {" }
+        ])
+    );
+    assert!(result.get("context_before").is_none());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args([
+            "search",
+            "header_needle(fresh)",
+            "--no-index",
+            "-C",
+            "symbol-header",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    assert!(stdout.contains("4 | "), "{stdout}");
+    assert!(
+        stdout.contains("pub fn refresh(key: &str) -> bool {"),
+        "{stdout}"
+    );
+    assert!(stdout.contains('⋮'), "{stdout}");
+}
+
+#[test]
+fn custom_budget_preset_from_config_sets_caps() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join(".cgreprc.toml"),
+        "[budgets.review]\nmax_total_chars = 90\nmax_chars_per_snippet = 12\n\n[budgets.tight]\nmax_total_chars = 1000\n",
+    );
+    let content = (0..20)
+        .map(|i| format!("needle line {} with some repeated words", i + 1))
+        .collect::<Vec<_>>()
+        .join("\n");
+    write_file(&dir.path().join("sample.txt"), &content);
+
+    let search = |budget: &str| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        cmd.current_dir(dir.path()).args([
+            "--format",
+            "json2",
+            "search",
+            "needle",
+            "--no-index",
+            "-B",
+            budget,
+        ]);
+        cmd
+    };
+
+    let assert = search("review").assert().success();
+    let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json2");
+    assert_eq!(json["meta"]["max_total_chars"], 90);
+    let snippet = json["results"][0]["snippet"].as_str().expect("snippet");
+    assert!(snippet.chars().count() <= 12);
+
+    // A table named after a built-in overrides only the fields it sets.
+    let assert = search("tight").assert().success();
+    let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json2");
+    assert_eq!(json["meta"]["max_total_chars"], 1000);
+
+    let assert = search("missing").assert().failure();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(stderr.contains("Unknown budget preset `missing`"));
+    assert!(stderr.contains("review, tight"));
+}
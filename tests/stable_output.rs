@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+fn run_stable(root: &Path, format: &str, args: &[&str]) -> Vec<u8> {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(root)
+        .args(["--stable", "--format", format])
+        .args(args)
+        .assert()
+        .success();
+    assert.get_output().stdout.clone()
+}
+
+/// Run `args` several times and require byte-identical stdout.
+fn assert_byte_identical(root: &Path, format: &str, args: &[&str]) -> Vec<u8> {
+    let first = run_stable(root, format, args);
+    for _ in 0..2 {
+        let again = run_stable(root, format, args);
+        assert_eq!(
+            String::from_utf8_lossy(&first),
+            String::from_utf8_lossy(&again),
+            "--stable output differs between runs of {format} {args:?}"
+        );
+    }
+    first
+}
+
+fn assert_keys_sorted(value: &Value) {
+    match value {
+        Value::Object(map) => {
+            let keys: Vec<&String> = map.keys().collect();
+            let mut sorted = keys.clone();
+            sorted.sort();
+            assert_eq!(keys, sorted);
+            map.values().for_each(assert_keys_sorted);
+        }
+        Value::Array(items) => items.iter().for_each(assert_keys_sorted),
+        _ => {}
+    }
+}
+
+#[test]
+fn stable_json_outputs_are_byte_identical_across_runs() {
+    let dir = TempDir::new().expect("tempdir");
+    let root = dir.path();
+    // Identical files score identically, so their order rests on tie-breaks.
+    for name in ["b_copy", "a_copy", "c_copy"] {
+        write_file(
+            &root.join(format!("src/{name}.rs")),
+            "pub fn stable_marker(input: &str) -> bool {\n    input.is_empty()\n}\n",
+        );
+    }
+    write_file(
+        &root.join("src/nested/user.rs"),
+        "pub struct StableUser {\n    pub name: String,\n}\n\npub fn stable_marker_user() -> StableUser {\n    StableUser { name: String::new() }\n}\n",
+    );
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(root)
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+
+    for format in ["json", "json2"] {
+        for args in [
+            &["search", "stable_marker"][..],
+            &["search", "stable_marker", "--explain"],
+            &["read", "src/nested/user.rs"],
+            &["map"],
+            &["symbols", "Stable"],
+            &["definition", "stable_marker"],
+        ] {
+            assert_byte_identical(root, format, args);
+        }
+    }
+
+    let search = assert_byte_identical(root, "json2", &["search", "stable_marker"]);
+    // A rebuild may store documents in another order; ties must not follow it.
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(root)
+        .args(["index", "--force", "--embeddings", "off"])
+        .assert()
+        .success();
+    assert_eq!(
+        String::from_utf8_lossy(&run_stable(root, "json2", &["search", "stable_marker"])),
+        String::from_utf8_lossy(&search)
+    );
+    let payload: Value = serde_json::from_slice(&search).expect("json2");
+    assert_keys_sorted(&payload);
+    assert_eq!(payload["meta"]["elapsed_ms"], 0);
+    let paths: Vec<&str> = payload["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .map(|result| result["path"].as_str().expect("path"))
+        .collect();
+    let copies: Vec<&str> = paths
+        .iter()
+        .copied()
+        .filter(|path| path.ends_with("_copy.rs"))
+        .collect();
+    assert_eq!(
+        copies,
+        ["src/a_copy.rs", "src/b_copy.rs", "src/c_copy.rs"],
+        "paths: {paths:?}"
+    );
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_file(path: &Path, content: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent");
+    }
+    fs::write(path, content).expect("write file");
+}
+
+#[test]
+fn trace_resolves_frames_by_path_suffix_and_symbol() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/users.js"),
+        "// users\n\nfunction loadUser(id) {\n  return db.get(id);\n}\n",
+    );
+    write_file(
+        &dir.path().join("src/main.rs"),
+        "fn main() {\n    let value: Option<u8> = None;\n    value.unwrap();\n}\n",
+    );
+    let trace = "\
+thread 'main' panicked at src/main.rs:3:11:
+TypeError: db is undefined
+    at loadUser (/home/ci/app/src/users.js:1:10)
+    at node:internal/main/run_main_module:28:49
+";
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir.path())
+        .args(["--format", "json2", "trace", "-C", "0"])
+        .write_stdin(trace)
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).expect("utf8");
+    let json: Value = serde_json::from_str(&stdout).expect("json");
+    let schema = cgrep::schema::schema("trace").expect("embedded schema");
+    assert!(cgrep::schema::validate(&schema, &json).is_empty());
+
+    assert_eq!(json["meta"]["total"], 3);
+    assert_eq!(json["meta"]["resolved"], 2);
+    let frames = json["frames"].as_array().expect("frames");
+
+    assert_eq!(frames[0]["resolved"]["path"], "src/main.rs");
+    assert_eq!(frames[0]["resolved"]["line"], 3);
+    assert_eq!(frames[0]["resolved"]["drift"], false);
+    assert_eq!(frames[0]["snippet"][0]["text"], "    value.unwrap();");
+
+    // The function moved down two lines since the trace was captured.
+    assert_eq!(frames[1]["resolved"]["path"], "src/users.js");
+    assert_eq!(frames[1]["resolved"]["line"], 3);
+    assert_eq!(frames[1]["resolved"]["symbol"], "loadUser");
+    assert_eq!(frames[1]["resolved"]["drift"], true);
+
+    assert!(frames[2]["resolved"].is_null());
+}
+
+#[test]
+fn trace_rejects_input_without_frames() {
+    let dir = TempDir::new().expect("tempdir");
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    cmd.current_dir(dir.path())
+        .arg("trace")
+        .write_stdin("nothing to see here\n")
+        .assert()
+        .failure();
+}
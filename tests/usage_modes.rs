@@ -169,3 +169,62 @@ fn mcp_section() {
         .unwrap_or_default()
         .contains("resolve_cgrep_command"));
 }
+
+#[test]
+fn callers_and_references_name_the_enclosing_closure() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "fn outer(items: &[i32]) -> Vec<i32> {\n    items.iter().map(|x| {\n        helper(*x)\n    }).collect()\n}\n\nfn helper(x: i32) -> i32 {\n    x\n}\n",
+    );
+
+    for command in ["callers", "references"] {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+            .args(["--format", "json", "--compact", command, "helper"])
+            .assert()
+            .success();
+        let json: Value = serde_json::from_slice(&assert.get_output().stdout).expect("json");
+        let call = json
+            .as_array()
+            .expect("array")
+            .iter()
+            .find(|result| result["line"] == 3)
+            .unwrap_or_else(|| panic!("{command} finds the call in the closure"));
+        assert_eq!(call["scope"], "outer::<closure@L2>", "{command}");
+    }
+}
+
+#[test]
+fn rust_macro_definitions_and_call_sites_resolve() {
+    let dir = TempDir::new().expect("tempdir");
+    write_file(
+        &dir.path().join("src/lib.rs"),
+        "macro_rules! make_handler {\n    () => {\n        fn generated_handler() {}\n    };\n}\n\n#[derive(Clone)]\npub struct Config;\n\nfn run() {\n    make_handler!();\n}\n",
+    );
+    let run = |args: &[&str]| -> Value {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+        let assert = cmd
+            .current_dir(dir.path())
+            .env("CGREP_DISABLE_CLI_AUTO_INDEX", "1")
+            .args(["--format", "json", "--compact"])
+            .args(args)
+            .assert()
+            .success();
+        serde_json::from_slice(&assert.get_output().stdout).expect("json")
+    };
+
+    let definition = run(&["definition", "make_handler"]);
+    assert_eq!(definition[0]["kind"], "macro");
+    assert_eq!(definition[0]["line"], 1);
+
+    let callers = run(&["callers", "make_handler"]);
+    assert_eq!(callers[0]["line"], 11);
+
+    let symbols = run(&["symbols", "generated_handler"]);
+    assert_eq!(symbols[0]["line"], 3);
+    let symbols = run(&["symbols", "clone", "--type", "method"]);
+    assert_eq!(symbols[0]["line"], 7);
+}
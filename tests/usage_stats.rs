@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn cgrep(dir: &Path, usage_file: &Path, args: &[&str]) -> String {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"));
+    let assert = cmd
+        .current_dir(dir)
+        .env("CGREP_USAGE_FILE", usage_file)
+        .args(args)
+        .assert()
+        .success();
+    String::from_utf8(assert.get_output().stdout.clone()).expect("utf8 stdout")
+}
+
+#[test]
+fn usage_summarizes_local_searches_without_query_text() {
+    let dir = TempDir::new().expect("tempdir");
+    let usage_file = dir.path().join("state/usage.jsonl");
+    fs::write(dir.path().join("lib.rs"), "fn secret_marker() {}\n").expect("write");
+
+    cgrep(dir.path(), &usage_file, &["search", "secret_marker"]);
+    cgrep(dir.path(), &usage_file, &["search", "secret_marker"]);
+
+    let recorded = fs::read_to_string(&usage_file).expect("usage file");
+    assert_eq!(recorded.lines().count(), 2);
+    assert!(!recorded.contains("secret_marker"));
+
+    let stdout = cgrep(
+        dir.path(),
+        &usage_file,
+        &["--format", "json", "usage", "--days", "1"],
+    );
+    let summary: Value = serde_json::from_str(&stdout).expect("json");
+    assert_eq!(summary["queries"], 2);
+    assert_eq!(summary["per_day"].as_array().expect("per_day").len(), 1);
+    assert_eq!(summary["modes"][0]["queries"], 2);
+    assert!(summary["latency"]["avg_ms"].as_f64().is_some());
+
+    let text = cgrep(dir.path(), &usage_file, &["usage"]);
+    assert!(text.contains("2 searches"), "stdout: {text}");
+}
+
+#[test]
+fn usage_recording_can_be_disabled() {
+    let dir = TempDir::new().expect("tempdir");
+    let usage_file = dir.path().join("usage.jsonl");
+    fs::write(dir.path().join("lib.rs"), "fn marker() {}\n").expect("write");
+    fs::write(
+        dir.path().join(".cgreprc.toml"),
+        "[usage]\nenabled = false\n",
+    )
+    .expect("write config");
+
+    cgrep(dir.path(), &usage_file, &["search", "marker"]);
+
+    assert!(!usage_file.exists());
+}
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command as ProcessCommand;
+use tempfile::TempDir;
+
+fn run_git(dir: &Path, args: &[&str]) {
+    let output = ProcessCommand::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("run git");
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        panic!("git {:?} failed: {}", args, stderr);
+    }
+}
+
+fn init_repo(path: &Path) {
+    run_git(path, &["init", "-q"]);
+    run_git(path, &["config", "user.email", "test@example.com"]);
+    run_git(path, &["config", "user.name", "test"]);
+    fs::write(path.join(".gitignore"), ".cgrep/\n").expect("write .gitignore");
+    fs::create_dir_all(path.join("src")).expect("mkdir src");
+    fs::create_dir_all(path.join("docs")).expect("mkdir docs");
+    fs::write(path.join("src/lib.rs"), "pub fn cone_marker() {}\n").expect("write lib");
+    fs::write(path.join("docs/notes.rs"), "pub fn outside_marker() {}\n").expect("write notes");
+    run_git(path, &["add", "."]);
+    run_git(path, &["commit", "-q", "-m", "initial"]);
+}
+
+fn index(dir: &Path) {
+    Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir)
+        .args(["index", "--embeddings", "off"])
+        .assert()
+        .success();
+}
+
+fn doctor_codes(dir: &Path) -> Vec<String> {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(dir)
+        .args(["--format", "json2", "doctor"])
+        .output()
+        .expect("run doctor");
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).expect("doctor json");
+    payload["result"]["findings"]
+        .as_array()
+        .expect("findings")
+        .iter()
+        .map(|finding| finding["code"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[test]
+fn linked_worktree_does_not_use_main_checkout_index() {
+    let dir = TempDir::new().expect("tempdir");
+    let main = dir.path().join("main");
+    fs::create_dir_all(&main).expect("mkdir main");
+    init_repo(&main);
+    index(&main);
+
+    // Nest the worktree inside the main checkout so a naive upward walk finds its index.
+    run_git(&main, &["worktree", "add", "-q", "wt"]);
+    let worktree = main.join("wt");
+    assert!(doctor_codes(&worktree).contains(&"worktree_index_mismatch".to_string()));
+
+    index(&worktree);
+    assert!(worktree.join(".cgrep/meta.json").exists());
+    assert!(doctor_codes(&worktree).is_empty());
+}
+
+#[test]
+fn sparse_checkout_drops_excluded_paths_from_index() {
+    let dir = TempDir::new().expect("tempdir");
+    let repo = dir.path();
+    init_repo(repo);
+    index(repo);
+
+    // Narrowing the cone leaves the index referencing docs/ until the next run.
+    run_git(repo, &["sparse-checkout", "set", "src"]);
+    let codes = doctor_codes(repo);
+    assert!(codes.contains(&"sparse_checkout".to_string()));
+    assert!(codes.contains(&"sparse_excluded_indexed".to_string()));
+
+    index(repo);
+    let codes = doctor_codes(repo);
+    assert!(!codes.contains(&"sparse_excluded_indexed".to_string()));
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("cgrep"))
+        .current_dir(repo)
+        .args(["search", "outside_marker"])
+        .output()
+        .expect("run search");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("docs/notes.rs"));
+}